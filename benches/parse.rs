@@ -0,0 +1,93 @@
+//! Benchmarks of parsing DOT, eagerly and lazily, building the graph model and writing DOT back,
+//! on generated graphs of a few sizes.
+//!
+//! ```text
+//! cargo bench [--features parallel] [-- FILTER]
+//! ```
+//!
+//! Only benchmarks whose names contain `FILTER` run. Each runs for about a second after a warm-up
+//! run, and reports its median time and throughput in input bytes.
+
+use std::{
+    env,
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use simpledot::{
+    corpus::{generate, CorpusOptions},
+    export::dot,
+    ir::{parse_graph, parse_graph_lazy},
+    model::Graph,
+};
+
+/// How long each benchmark runs for at least.
+const BUDGET: Duration = Duration::from_secs(1);
+
+/// Runs `f` repeatedly, printing its median time and throughput over `bytes` of input, if `name`
+/// contains `filter`.
+fn bench(filter: &str, name: &str, bytes: usize, mut f: impl FnMut()) {
+    if !name.contains(filter) {
+        return;
+    }
+    f();
+    let mut times = vec![];
+    let start = Instant::now();
+    while start.elapsed() < BUDGET || times.len() < 3 {
+        let run = Instant::now();
+        f();
+        times.push(run.elapsed());
+    }
+    times.sort();
+    let median = times[times.len() / 2];
+    println!(
+        "{:<32} {:>10.3} ms {:>8.2} MB/s  ({} runs)",
+        name,
+        median.as_secs_f64() * 1e3,
+        bytes as f64 / 1e6 / median.as_secs_f64(),
+        times.len()
+    );
+}
+
+fn main() {
+    // `cargo bench` passes `--bench` along with the filter
+    let filter = env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with('-'))
+        .unwrap_or_default();
+    let sizes = [
+        ("small", 1_000, 0.5),
+        ("large", 50_000, 0.5),
+        ("large-sparse", 50_000, 0.0),
+        ("large-dense", 50_000, 1.0),
+    ];
+    for (size, nodes, density) in sizes {
+        let input = generate(&CorpusOptions {
+            nodes,
+            edges: 2 * nodes,
+            density,
+            ..CorpusOptions::default()
+        });
+        let ir = parse_graph(&input).unwrap();
+        let graph = Graph::from_ir(&ir);
+        bench(&filter, &format!("parse/{}", size), input.len(), || {
+            black_box(parse_graph(black_box(&input)).unwrap());
+        });
+        bench(
+            &filter,
+            &format!("parse-lazy/{}", size),
+            input.len(),
+            || {
+                black_box(parse_graph_lazy(black_box(&input)).unwrap());
+            },
+        );
+        bench(&filter, &format!("model/{}", size), input.len(), || {
+            black_box(Graph::from_ir(black_box(&ir)));
+        });
+        bench(&filter, &format!("write/{}", size), input.len(), || {
+            let mut output = Vec::with_capacity(input.len());
+            dot::write(black_box(&graph), &mut output).unwrap();
+            black_box(output);
+        });
+    }
+}
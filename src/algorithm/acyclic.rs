@@ -0,0 +1,186 @@
+//! Cycle finding and breaking, like Graphviz's `acyclic`.
+//!
+//! [`find_cycles`] finds the back edges of a depth-first search, visiting nodes and their
+//! out-edges in order, each with the path of the search it closes into an elementary cycle.
+//! Every cycle of the graph goes through at least one of these edges, and cycles are broken by
+//! reversing them. Like `acyclic`, this is a heuristic: the reversed edges break every
+//! cycle, but aren't necessarily the fewest edges that would. Loops can't be broken by reversal
+//! and are left alone, as are undirected graphs.
+
+use crate::{
+    algorithm::reverse::swap_end,
+    attribute::{Attribute, DirType},
+    model::{Edge, Graph, NodeIndex},
+};
+
+/// A cycle closed by a back edge of a depth-first search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The index of the edge closing the cycle, from the last of `nodes` back to the first.
+    pub back_edge: usize,
+    /// The nodes of the cycle in order, from the head of the back edge to its tail.
+    pub nodes: Vec<NodeIndex>,
+}
+
+/// Whether `graph` is a digraph with cycles, not counting loops.
+pub fn has_cycles(graph: &Graph) -> bool {
+    !find_cycles(graph).is_empty()
+}
+
+/// The cycles closed by the back edges of a depth-first search of `graph`, in the order of their
+/// back edges; see the [module documentation](self). Undirected graphs and loops have none.
+pub fn find_cycles(graph: &Graph) -> Vec<Cycle> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Unvisited,
+        OnStack,
+        Done,
+    }
+
+    if !graph.is_directed() {
+        return vec![];
+    }
+    let mut out_edges = vec![vec![]; graph.nodes().len()];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        out_edges[edge.tail].push(index);
+    }
+    let mut state = vec![State::Unvisited; graph.nodes().len()];
+    let mut cycles = vec![];
+    for root in 0..graph.nodes().len() {
+        if state[root] != State::Unvisited {
+            continue;
+        }
+        state[root] = State::OnStack;
+        // nodes on the search path, each with the position of its next out-edge to follow
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match out_edges[node].get(*next) {
+                Some(&index) => {
+                    *next += 1;
+                    let Edge { tail, head, .. } = &graph.edges()[index];
+                    match state[*head] {
+                        State::Unvisited => {
+                            state[*head] = State::OnStack;
+                            stack.push((*head, 0));
+                        }
+                        State::OnStack if tail != head => {
+                            let start = stack.iter().rposition(|&(node, _)| node == *head);
+                            let nodes = stack[start.unwrap()..].iter().map(|&(node, _)| node);
+                            cycles.push(Cycle {
+                                back_edge: index,
+                                nodes: nodes.collect(),
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = State::Done;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    cycles.sort_unstable_by_key(|cycle| cycle.back_edge);
+    cycles
+}
+
+/// Reverses `edge`, swapping its ends along with its head and tail attributes as
+/// [`reverse`](super::reverse) does. The `dir` attribute is flipped, so that the edge is still
+/// drawn pointing the same way and is marked as reversed by `dir=back`.
+fn reverse(edge: &mut Edge) {
+    std::mem::swap(&mut edge.tail, &mut edge.head);
+    let mut has_dir = false;
+    for attribute in &mut edge.attributes {
+        *attribute = match swap_end(attribute.clone()) {
+            Attribute::Dir(DirType::Forward) => Attribute::Dir(DirType::Back),
+            Attribute::Dir(DirType::Back) => Attribute::Dir(DirType::Forward),
+            attribute => attribute,
+        };
+        has_dir |= matches!(attribute, Attribute::Dir(_));
+    }
+    if !has_dir {
+        edge.attributes.push(Attribute::Dir(DirType::Back));
+    }
+}
+
+/// Makes `graph` acyclic by reversing the back edges found by a depth-first search, returning
+/// the indices of the reversed edges. See the [module documentation](self) for the details.
+pub fn make_acyclic(graph: &mut Graph) -> Vec<usize> {
+    let cycles = find_cycles(graph);
+    let back = cycles
+        .iter()
+        .map(|cycle| cycle.back_edge)
+        .collect::<Vec<_>>();
+    for &index in &back {
+        reverse(graph.edge_mut(index));
+    }
+    back
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::ArrowType, ir::parse_graph};
+
+    #[test]
+    fn acyclic() {
+        let mut graph = Graph::from_ir(
+            &parse_graph(
+                "digraph { a -> b -> c -> a [arrowhead=dot, headlabel=x] c -> c b -> a [dir=back] }",
+            )
+            .unwrap(),
+        );
+        assert!(has_cycles(&graph));
+        assert_eq!(
+            find_cycles(&graph),
+            vec![
+                Cycle {
+                    back_edge: 2,
+                    nodes: vec![0, 1, 2]
+                },
+                Cycle {
+                    back_edge: 4,
+                    nodes: vec![0, 1]
+                }
+            ]
+        );
+        assert_eq!(make_acyclic(&mut graph), vec![2, 4]);
+        assert!(!has_cycles(&graph));
+
+        let edge = &graph.edges()[2];
+        assert_eq!((edge.tail, edge.head), (0, 2));
+        assert!(edge
+            .attributes
+            .contains(&Attribute::ArrowTail(ArrowType::Dot)));
+        assert!(edge.attributes.contains(&Attribute::Dir(DirType::Back)));
+        assert!(edge
+            .attributes
+            .contains(&Attribute::from_pair("taillabel", "x").unwrap()));
+        assert_eq!(
+            graph.edges()[4].attributes,
+            vec![Attribute::Dir(DirType::Forward)]
+        );
+        assert_eq!((graph.edges()[3].tail, graph.edges()[3].head), (2, 2));
+
+        // ports go with their ends
+        let mut graph =
+            Graph::from_ir(&parse_graph("digraph { a -> b  b:s -> a:n [headlabel=h] }").unwrap());
+        assert_eq!(make_acyclic(&mut graph), vec![1]);
+        let expected = [
+            ("taillabel", "h"),
+            ("headport", "s"),
+            ("tailport", "n"),
+            ("dir", "back"),
+        ];
+        for (name, value) in expected {
+            assert!(graph.edges()[1]
+                .attributes
+                .contains(&Attribute::from_pair(name, value).unwrap()));
+        }
+
+        let mut graph = Graph::from_ir(&parse_graph("graph { a -- b -- a }").unwrap());
+        assert!(make_acyclic(&mut graph).is_empty());
+    }
+}
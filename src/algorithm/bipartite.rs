@@ -0,0 +1,176 @@
+//! Bipartite graphs, whose nodes split into two sides with every edge between them, as the
+//! layers of a dependency diagram.
+//!
+//! Edges are taken either way. Nodes are split by breadth-first search from the first declared
+//! node of each connected component, which goes on the first side; a graph has no bipartition
+//! exactly when it has a cycle of odd length, which a loop is too.
+
+use thiserror::Error;
+
+use crate::{
+    attribute::Attribute,
+    ir::Ident,
+    model::{set_attribute, Graph, NodeIndex},
+};
+#[cfg(feature = "layout")]
+use crate::{
+    attribute::RankType,
+    layout::rank::{RankGroup, RankOptions},
+};
+
+/// A cycle of odd length keeping a graph from being split in two.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("graph has an odd cycle `{}`", .path.join(" -- "))]
+pub struct OddCycleError {
+    /// The nodes of the cycle in order.
+    pub nodes: Vec<NodeIndex>,
+    /// The names of the nodes of the cycle in order, ending with the first again.
+    pub path: Vec<Ident>,
+}
+
+/// The two sides of a bipartite graph, each in order of the nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bipartition {
+    pub first: Vec<NodeIndex>,
+    pub second: Vec<NodeIndex>,
+}
+
+impl Bipartition {
+    /// Gives the nodes of each side of `graph` the attributes for it, replacing any they have.
+    pub fn mark(&self, graph: &mut Graph, first: &[Attribute], second: &[Attribute]) {
+        for (side, attributes) in [(&self.first, first), (&self.second, second)] {
+            for &node in side {
+                for attribute in attributes {
+                    set_attribute(&mut graph.node_mut(node).attributes, attribute.clone());
+                }
+            }
+        }
+    }
+
+    /// Rank options for layered layouts putting the first side on the minimum rank and the
+    /// second on the maximum.
+    #[cfg(feature = "layout")]
+    pub fn rank_options(&self) -> RankOptions {
+        let group = |kind, nodes: &Vec<NodeIndex>| RankGroup {
+            kind,
+            nodes: nodes.clone(),
+        };
+        RankOptions {
+            groups: vec![
+                group(RankType::Min, &self.first),
+                group(RankType::Max, &self.second),
+            ],
+            ..RankOptions::default()
+        }
+    }
+}
+
+/// The two sides of `graph`, or an odd cycle if it has no bipartition; see the
+/// [module documentation](self).
+pub fn bipartition(graph: &Graph) -> Result<Bipartition, OddCycleError> {
+    let nodes = graph.nodes().len();
+    let mut neighbors = vec![vec![]; nodes];
+    for edge in graph.edges() {
+        neighbors[edge.tail].push(edge.head);
+        neighbors[edge.head].push(edge.tail);
+    }
+
+    // the side of each node reached, and the node it was reached from
+    let mut side = vec![None; nodes];
+    let mut parent = vec![usize::MAX; nodes];
+    for root in 0..nodes {
+        if side[root].is_some() {
+            continue;
+        }
+        side[root] = Some(false);
+        let mut queue = vec![root];
+        let mut next = 0;
+        while let Some(&node) = queue.get(next) {
+            next += 1;
+            for &neighbor in &neighbors[node] {
+                match side[neighbor] {
+                    None => {
+                        side[neighbor] = side[node].map(|side| !side);
+                        parent[neighbor] = node;
+                        queue.push(neighbor);
+                    }
+                    Some(other) if Some(other) == side[node] => {
+                        return Err(odd_cycle(graph, &parent, node, neighbor))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+    let (second, first) = (0..nodes).partition(|&node| side[node] == Some(true));
+    Ok(Bipartition { first, second })
+}
+
+/// The odd cycle closed by the edge between `a` and `b`, on the same side of the breadth-first
+/// search tree given by `parent`: up from `a` to where the paths to the two meet, and back down
+/// to `b`.
+fn odd_cycle(graph: &Graph, parent: &[NodeIndex], a: NodeIndex, b: NodeIndex) -> OddCycleError {
+    let up = |mut node: NodeIndex| {
+        let mut path = vec![node];
+        while parent[node] != usize::MAX {
+            node = parent[node];
+            path.push(node);
+        }
+        path
+    };
+    let (mut from_a, mut from_b) = (up(a), up(b));
+    // drop the common part of the paths from the root but where they meet
+    while from_a.len() > 1
+        && from_b.len() > 1
+        && from_a[from_a.len() - 2] == from_b[from_b.len() - 2]
+    {
+        from_a.pop();
+        from_b.pop();
+    }
+    from_b.pop();
+    from_b.reverse();
+    let mut nodes = from_a;
+    nodes.extend(from_b);
+    let path = nodes
+        .iter()
+        .chain(nodes.first())
+        .map(|&node| graph.nodes()[node].id.clone())
+        .collect();
+    OddCycleError { nodes, path }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn graph(source: &str) -> Graph {
+        Graph::from_ir(&parse_graph(source).unwrap())
+    }
+
+    #[test]
+    fn bipartite() {
+        let graph = graph("digraph { a -> x b -> x b -> y c -> y z }");
+        let bipartition = bipartition(&graph).unwrap();
+        assert_eq!(bipartition.first, vec![0, 2, 4, 5]);
+        assert_eq!(bipartition.second, vec![1, 3]);
+        #[cfg(feature = "layout")]
+        {
+            let ranks = crate::layout::rank::rank(&graph, &bipartition.rank_options());
+            assert_eq!(ranks, vec![0, 1, 0, 1, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn odd_cycles() {
+        let error = bipartition(&graph("graph { a -- b -- c -- d -- e -- a x -- a }")).unwrap_err();
+        assert_eq!(error.nodes.len(), 5);
+        assert_eq!(
+            error.to_string(),
+            "graph has an odd cycle `c -- b -- a -- e -- d -- c`"
+        );
+        let error = bipartition(&graph("graph { a -- b b -- b }")).unwrap_err();
+        assert_eq!(error.to_string(), "graph has an odd cycle `b -- b`");
+        assert!(bipartition(&graph("graph { a -- b -- c -- d -- a }")).is_ok());
+    }
+}
@@ -0,0 +1,272 @@
+//! Centrality: how important each node is to the structure of a graph, for heatmap renderings
+//! that size or color nodes by it.
+//!
+//! Edges of digraphs are followed from tail to head, and edges of undirected graphs either way.
+//! A [`Heatmap`] writes scores back as node attributes, scaled from the lowest score to the
+//! highest.
+
+use crate::{
+    attribute::{Attribute, ColorAttribute, Style},
+    color::{Color, Rgb},
+    model::{set_attribute, Graph, NodeIndex},
+};
+
+/// A measure of centrality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Centrality {
+    /// The number of edges at a node, loops counting twice.
+    Degree,
+    /// The number of shortest paths between other nodes through a node, by Brandes' algorithm.
+    Betweenness,
+    /// The PageRank of a node, with a damping factor of 0.85.
+    PageRank,
+}
+
+impl Centrality {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "degree" => Centrality::Degree,
+            "betweenness" => Centrality::Betweenness,
+            "pagerank" => Centrality::PageRank,
+            _ => return None,
+        })
+    }
+
+    /// The score of each node of `graph`, by index.
+    pub fn scores(self, graph: &Graph) -> Vec<f64> {
+        match self {
+            Centrality::Degree => degree(graph),
+            Centrality::Betweenness => betweenness(graph),
+            Centrality::PageRank => pagerank(graph, 0.85),
+        }
+    }
+}
+
+/// The nodes each node leads to, either way in undirected graphs, with an entry per edge.
+fn successors(graph: &Graph) -> Vec<Vec<NodeIndex>> {
+    let mut successors = vec![vec![]; graph.nodes().len()];
+    for edge in graph.edges() {
+        successors[edge.tail].push(edge.head);
+        if !graph.is_directed() && edge.tail != edge.head {
+            successors[edge.head].push(edge.tail);
+        }
+    }
+    successors
+}
+
+/// The number of edges at each node of `graph`, loops counting twice.
+pub fn degree(graph: &Graph) -> Vec<f64> {
+    let mut degree = vec![0.0; graph.nodes().len()];
+    for edge in graph.edges() {
+        degree[edge.tail] += 1.0;
+        degree[edge.head] += 1.0;
+    }
+    degree
+}
+
+/// The number of shortest paths between pairs of other nodes of `graph` through each node, paths
+/// of the same length sharing a pair. Pairs of an undirected graph count once.
+pub fn betweenness(graph: &Graph) -> Vec<f64> {
+    let nodes = graph.nodes().len();
+    let mut successors = successors(graph);
+    for (node, list) in successors.iter_mut().enumerate() {
+        list.sort_unstable();
+        list.dedup();
+        list.retain(|&next| next != node);
+    }
+
+    let mut betweenness = vec![0.0; nodes];
+    for source in 0..nodes {
+        // the number of shortest paths to each node, their length, and the nodes before it on
+        // them, by breadth-first search
+        let mut paths = vec![0.0; nodes];
+        let mut distance = vec![usize::MAX; nodes];
+        let mut previous = vec![vec![]; nodes];
+        let mut order = vec![source];
+        paths[source] = 1.0;
+        distance[source] = 0;
+        let mut next = 0;
+        while let Some(&node) = order.get(next) {
+            next += 1;
+            for &neighbor in &successors[node] {
+                if distance[neighbor] == usize::MAX {
+                    distance[neighbor] = distance[node] + 1;
+                    order.push(neighbor);
+                }
+                if distance[neighbor] == distance[node] + 1 {
+                    paths[neighbor] += paths[node];
+                    previous[neighbor].push(node);
+                }
+            }
+        }
+        // the share of paths from the source through each node, from the farthest back
+        let mut dependency = vec![0.0; nodes];
+        for &node in order.iter().rev() {
+            for &before in &previous[node] {
+                dependency[before] += paths[before] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                betweenness[node] += dependency[node];
+            }
+        }
+    }
+    if !graph.is_directed() {
+        for score in &mut betweenness {
+            *score /= 2.0;
+        }
+    }
+    betweenness
+}
+
+/// The PageRank of each node of `graph`, adding up to 1, with the probability `damping` of
+/// following an edge rather than jumping anywhere. Nodes without out-edges jump anywhere.
+pub fn pagerank(graph: &Graph, damping: f64) -> Vec<f64> {
+    let nodes = graph.nodes().len();
+    if nodes == 0 {
+        return vec![];
+    }
+    let successors = successors(graph);
+    let mut rank = vec![1.0 / nodes as f64; nodes];
+    for _ in 0..100 {
+        let dangling = (0..nodes)
+            .filter(|&node| successors[node].is_empty())
+            .map(|node| rank[node])
+            .sum::<f64>();
+        let base = (1.0 - damping + damping * dangling) / nodes as f64;
+        let mut next = vec![base; nodes];
+        for (node, list) in successors.iter().enumerate() {
+            for &successor in list {
+                next[successor] += damping * rank[node] / list.len() as f64;
+            }
+        }
+        let change = rank
+            .iter()
+            .zip(&next)
+            .map(|(a, b)| (a - b).abs())
+            .sum::<f64>();
+        rank = next;
+        if change < 1e-10 {
+            break;
+        }
+    }
+    rank
+}
+
+/// How to show scores on nodes, each scaled between the lowest score and the highest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    /// The `width` of the nodes with the lowest and highest scores, in inches.
+    pub width: Option<(f64, f64)>,
+    /// The `fillcolor` of the nodes with the lowest and highest scores, with which nodes are
+    /// filled.
+    pub fill: Option<(Rgb, Rgb)>,
+}
+
+impl Default for Heatmap {
+    /// Fills nodes from pale yellow to dark red.
+    fn default() -> Self {
+        Heatmap {
+            width: None,
+            fill: Some((
+                Rgb {
+                    r: 0xff,
+                    g: 0xff,
+                    b: 0xb2,
+                },
+                Rgb {
+                    r: 0xbd,
+                    g: 0x00,
+                    b: 0x26,
+                },
+            )),
+        }
+    }
+}
+
+impl Heatmap {
+    /// Sets the attributes of the nodes of `graph` by their `scores`, replacing any they have.
+    pub fn apply(&self, graph: &mut Graph, scores: &[f64]) {
+        let low = scores.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mix = |low: u8, high: u8, t: f64| (low as f64 + (high as f64 - low as f64) * t).round();
+        for (node, &score) in scores.iter().enumerate() {
+            let t = if high > low {
+                (score - low) / (high - low)
+            } else {
+                0.0
+            };
+            let attributes = &mut graph.node_mut(node).attributes;
+            if let Some((low, high)) = self.width {
+                set_attribute(attributes, Attribute::Width(low + (high - low) * t));
+            }
+            if let Some((low, high)) = &self.fill {
+                let color = Rgb {
+                    r: mix(low.r, high.r, t) as u8,
+                    g: mix(low.g, high.g, t) as u8,
+                    b: mix(low.b, high.b, t) as u8,
+                };
+                let fill = ColorAttribute::Color(Color::Rgb(color));
+                set_attribute(attributes, Attribute::FillColor(fill));
+                let mut styles = attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::Style(styles) => Some(styles.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                if !styles.contains(&Style::Filled) {
+                    styles.push(Style::Filled);
+                    set_attribute(attributes, Attribute::Style(styles));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn graph(source: &str) -> Graph {
+        Graph::from_ir(&parse_graph(source).unwrap())
+    }
+
+    #[test]
+    fn centrality() {
+        // a star with a tail: b is the hub
+        let star = graph("graph { b -- a b -- c b -- d d -- e }");
+        assert_eq!(degree(&star), vec![3.0, 1.0, 1.0, 2.0, 1.0]);
+        assert_eq!(betweenness(&star), vec![5.0, 0.0, 0.0, 3.0, 0.0]);
+
+        let chain = graph("digraph { a -> b -> c a -> c a -> d -> c }");
+        assert_eq!(betweenness(&chain), vec![0.0, 0.0, 0.0, 0.0]);
+        let chain = graph("digraph { a -> b -> c -> d }");
+        assert_eq!(betweenness(&chain), vec![0.0, 2.0, 2.0, 0.0]);
+
+        let cycle = pagerank(&graph("digraph { a -> b -> c -> a }"), 0.85);
+        assert!(cycle.iter().all(|rank| (rank - 1.0 / 3.0).abs() < 1e-9));
+        let sink = pagerank(&graph("digraph { a -> c b -> c }"), 0.85);
+        assert!((sink.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(sink[1] > sink[0] && sink[0] == sink[2]);
+    }
+
+    #[test]
+    fn heatmap() {
+        let mut star = graph("graph { b [style=dashed] b -- a b -- c }");
+        let scores = Centrality::from_name("degree").unwrap().scores(&star);
+        let heatmap = Heatmap {
+            width: Some((1.0, 2.0)),
+            ..Heatmap::default()
+        };
+        heatmap.apply(&mut star, &scores);
+        let hub = star.node("b").unwrap();
+        assert!(hub.attributes.contains(&Attribute::Width(2.0)));
+        assert!(hub
+            .attributes
+            .contains(&Attribute::Style(vec![Style::Dashed, Style::Filled])));
+        let leaf = &star.node("a").unwrap().attributes;
+        assert!(leaf.contains(&Attribute::Width(1.0)));
+        assert!(leaf.contains(&Attribute::from_pair("fillcolor", "#ffffb2").unwrap()));
+    }
+}
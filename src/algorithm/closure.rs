@@ -0,0 +1,83 @@
+//! Transitive closure, the inverse of [`reduction`](super::reduction): an edge from each node to
+//! every node it can reach, to show the dependencies implied by longer paths.
+//!
+//! Edges are added from each node to each node reachable from it which it has no edge to yet,
+//! in the order of their tails and then of their heads, after the edges of the graph. Nodes
+//! reaching themselves around a cycle don't get loops, and undirected graphs are left alone.
+
+use std::collections::HashSet;
+
+use crate::{
+    attribute::Attribute,
+    model::{Graph, NodeIndex},
+};
+
+/// The nodes reachable from `from` along the out-edges given, `from` itself only around a cycle.
+fn reached(out_edges: &[Vec<NodeIndex>], from: NodeIndex) -> Vec<bool> {
+    let mut reached = vec![false; out_edges.len()];
+    let mut stack = vec![from];
+    while let Some(node) = stack.pop() {
+        for &head in &out_edges[node] {
+            if !reached[head] {
+                reached[head] = true;
+                stack.push(head);
+            }
+        }
+    }
+    reached
+}
+
+/// Adds the edges of the transitive closure of `graph` missing from it, each with `attributes`,
+/// such as `style=dashed` to tell them apart, returning the number of edges added. See the
+/// [module documentation](self) for the details.
+pub fn transitive_closure(graph: &mut Graph, attributes: &[Attribute]) -> usize {
+    if !graph.is_directed() {
+        return 0;
+    }
+    let mut out_edges = vec![vec![]; graph.nodes().len()];
+    let mut edges = HashSet::new();
+    for edge in graph.edges() {
+        out_edges[edge.tail].push(edge.head);
+        edges.insert((edge.tail, edge.head));
+    }
+    let mut added = 0;
+    for tail in 0..graph.nodes().len() {
+        for (head, reached) in reached(&out_edges, tail).into_iter().enumerate() {
+            if reached && head != tail && !edges.contains(&(tail, head)) {
+                graph.add_edge(tail, head, attributes.to_vec());
+                added += 1;
+            }
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{algorithm::reduction::transitive_reduction, ir::parse_graph};
+
+    #[test]
+    fn closure() {
+        let source = "digraph { a -> b -> c -> d a -> c e -> f -> e }";
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        let dashed = [Attribute::from_pair("style", "dashed").unwrap()];
+        assert_eq!(transitive_closure(&mut graph, &dashed), 2);
+        let name = |node: NodeIndex| graph.nodes()[node].id.as_str();
+        let added = graph.edges()[6..]
+            .iter()
+            .map(|edge| (name(edge.tail), name(edge.head), &edge.attributes[..]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            added,
+            vec![("a", "d", &dashed[..]), ("b", "d", &dashed[..])]
+        );
+
+        // the reduction takes the closure back, along with the edge it already implied
+        assert_eq!(transitive_reduction(&mut graph), 3);
+        assert_eq!(graph.edges().len(), 5);
+
+        let mut graph = Graph::from_ir(&parse_graph("graph { a -- b -- c }").unwrap());
+        assert_eq!(transitive_closure(&mut graph, &[]), 0);
+    }
+}
@@ -0,0 +1,66 @@
+//! Graph complements: the same nodes, with an edge between exactly the nodes that had none.
+//!
+//! Nodes keep their attributes, as does the graph, and the edges added have none. Undirected
+//! graphs get an edge for each unordered pair of nodes without one, digraphs for each ordered
+//! pair; the complement never has loops.
+
+use std::collections::HashSet;
+
+use crate::model::Graph;
+
+/// The complement of `graph`; see the [module documentation](self).
+pub fn complement(graph: &Graph) -> Graph {
+    let nodes = graph.nodes().len();
+    let mut adjacent = HashSet::new();
+    for edge in graph.edges() {
+        adjacent.insert((edge.tail, edge.head));
+        if !graph.is_directed() {
+            adjacent.insert((edge.head, edge.tail));
+        }
+    }
+    let mut complement = graph.subgraph(0..nodes, None);
+    for tail in 0..nodes {
+        let heads = if graph.is_directed() { 0 } else { tail + 1 };
+        for head in heads..nodes {
+            if head != tail && !adjacent.contains(&(tail, head)) {
+                complement.add_edge(tail, head, vec![]);
+            }
+        }
+    }
+    complement
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn edges(graph: &Graph) -> Vec<(&str, &str)> {
+        let id = |node: usize| graph.nodes()[node].id.as_str();
+        graph
+            .edges()
+            .iter()
+            .map(|edge| (id(edge.tail), id(edge.head)))
+            .collect()
+    }
+
+    #[test]
+    fn complement() {
+        let graph =
+            Graph::from_ir(&parse_graph("graph { a [shape=box] a -- b c -- b c -- c d }").unwrap());
+        let complement = super::complement(&graph);
+        assert_eq!(complement.nodes(), graph.nodes());
+        assert_eq!(
+            edges(&complement),
+            vec![("a", "c"), ("a", "d"), ("b", "d"), ("c", "d")]
+        );
+        // the complement of the complement has the edges back but for loops
+        assert_eq!(
+            edges(&super::complement(&complement)),
+            vec![("a", "b"), ("b", "c")]
+        );
+
+        let graph = Graph::from_ir(&parse_graph("digraph { a -> b }").unwrap());
+        assert_eq!(edges(&super::complement(&graph)), vec![("b", "a")]);
+    }
+}
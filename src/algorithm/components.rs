@@ -0,0 +1,201 @@
+//! Connected components, like Graphviz's `ccomps`, and biconnected components.
+//!
+//! Components are weakly connected: edge direction is ignored.
+
+use crate::model::{Graph, NodeIndex};
+
+/// Index of the root of `node` in a union-find forest, compressing the path to it.
+fn root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+/// The number of the connected component of each node of `graph`, numbered from 0 in the order
+/// of their first node.
+pub fn component_labels(graph: &Graph) -> Vec<usize> {
+    let nodes = graph.nodes().len();
+    let mut parents = (0..nodes).collect::<Vec<_>>();
+    for edge in graph.edges() {
+        let (tail, head) = (root(&mut parents, edge.tail), root(&mut parents, edge.head));
+        parents[tail] = head;
+    }
+    let mut component_of_root = vec![None; nodes];
+    let mut components = 0;
+    (0..nodes)
+        .map(|node| {
+            let root = root(&mut parents, node);
+            *component_of_root[root].get_or_insert_with(|| {
+                components += 1;
+                components - 1
+            })
+        })
+        .collect()
+}
+
+/// Groups nodes by their `labels`, numbered from 0, into lists of node indices in order.
+pub(crate) fn group(labels: &[usize]) -> Vec<Vec<NodeIndex>> {
+    let mut groups = vec![vec![]; labels.iter().max().map_or(0, |&label| label + 1)];
+    for (node, &label) in labels.iter().enumerate() {
+        groups[label].push(node);
+    }
+    groups
+}
+
+/// The connected components of `graph`, as lists of node indices in order, ordered by their first
+/// node.
+pub fn connected_components(graph: &Graph) -> Vec<Vec<NodeIndex>> {
+    group(&component_labels(graph))
+}
+
+/// Whether every node of `graph` can be reached from every other, ignoring edge direction. A
+/// graph without nodes is connected.
+pub fn is_connected(graph: &Graph) -> bool {
+    component_labels(graph).iter().all(|&label| label == 0)
+}
+
+/// The biconnected components, or blocks, of `graph`: the largest sets of nodes which stay
+/// connected after removing any one node, as lists of node indices in order, ordered by their
+/// first node. Nodes in several blocks are the cut vertices of the graph. Loops are ignored, so
+/// nodes without other edges belong to no block.
+pub fn biconnected_components(graph: &Graph) -> Vec<Vec<NodeIndex>> {
+    let nodes = graph.nodes().len();
+    let mut neighbors = vec![vec![]; nodes];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        if edge.tail != edge.head {
+            neighbors[edge.tail].push((edge.head, index));
+            neighbors[edge.head].push((edge.tail, index));
+        }
+    }
+    let mut discovered = vec![None; nodes];
+    let mut low = vec![0; nodes];
+    let mut time = 0;
+    let mut edges = vec![];
+    let mut blocks = vec![];
+    for root in 0..nodes {
+        if discovered[root].is_some() {
+            continue;
+        }
+        discovered[root] = Some(time);
+        low[root] = time;
+        time += 1;
+        // the nodes of the current path, with the edge they were reached by and how many of
+        // their neighbors were visited
+        let mut path: Vec<(NodeIndex, Option<usize>, usize)> = vec![(root, None, 0)];
+        while let Some(&mut (node, parent_edge, ref mut next)) = path.last_mut() {
+            if let Some(&(neighbor, edge)) = neighbors[node].get(*next) {
+                *next += 1;
+                if Some(edge) == parent_edge {
+                    continue;
+                }
+                match discovered[neighbor] {
+                    None => {
+                        edges.push(edge);
+                        discovered[neighbor] = Some(time);
+                        low[neighbor] = time;
+                        time += 1;
+                        path.push((neighbor, Some(edge), 0));
+                    }
+                    Some(order) if order < discovered[node].unwrap() => {
+                        edges.push(edge);
+                        low[node] = low[node].min(order);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+            path.pop();
+            if let Some(&(parent, _, _)) = path.last() {
+                low[parent] = low[parent].min(low[node]);
+                if low[node] >= discovered[parent].unwrap() {
+                    let mut block = vec![];
+                    while let Some(edge) = edges.pop() {
+                        let edge_ends = &graph.edges()[edge];
+                        block.extend([edge_ends.tail, edge_ends.head]);
+                        if Some(edge) == parent_edge {
+                            break;
+                        }
+                    }
+                    block.sort_unstable();
+                    block.dedup();
+                    blocks.push(block);
+                }
+            }
+        }
+    }
+    blocks.sort();
+    blocks
+}
+
+/// Splits `graph` into one graph per connected component, ordered as by
+/// [`connected_components`]. Each keeps the graph attributes, and if the graph has an ID, is named
+/// after it with the component's number, starting at 1: `G_1`, `G_2`, ...
+pub fn split(graph: &Graph) -> Vec<Graph> {
+    let component_of = component_labels(graph);
+    let components = group(&component_of);
+    let mut edges = vec![vec![]; components.len()];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        edges[component_of[edge.tail]].push(index);
+    }
+    components
+        .into_iter()
+        .zip(edges)
+        .enumerate()
+        .map(|(component, (nodes, edges))| {
+            let mut subgraph = graph.subgraph(nodes, edges);
+            subgraph.id = graph
+                .id
+                .as_ref()
+                .map(|id| format!("{}_{}", id, component + 1));
+            subgraph
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn components() {
+        let graph = Graph::from_ir(
+            &parse_graph("digraph G { label=L a -> b c d -> c e -> a f -> f }").unwrap(),
+        );
+        assert_eq!(
+            connected_components(&graph),
+            vec![vec![0, 1, 4], vec![2, 3], vec![5]]
+        );
+        assert_eq!(component_labels(&graph), vec![0, 0, 1, 1, 0, 2]);
+        assert!(!is_connected(&graph));
+        let split = split(&graph);
+        assert_eq!(split.len(), 3);
+        assert!(is_connected(&split[0]));
+        assert_eq!(split[0].id.as_deref(), Some("G_1"));
+        assert_eq!(split[0].attributes, graph.attributes);
+        assert_eq!(
+            split[0]
+                .nodes()
+                .iter()
+                .map(|node| node.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "e"]
+        );
+        assert_eq!(split[0].edges().len(), 2);
+        assert_eq!(split[2].edges().len(), 1);
+    }
+
+    #[test]
+    fn biconnected() {
+        let graph = Graph::from_ir(
+            &parse_graph("graph { a -- b -- c -- a c -- d d -- e -- f -- d -- g g -- g h }")
+                .unwrap(),
+        );
+        assert_eq!(
+            biconnected_components(&graph),
+            vec![vec![0, 1, 2], vec![2, 3], vec![3, 4, 5], vec![3, 6]]
+        );
+    }
+}
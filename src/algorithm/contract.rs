@@ -0,0 +1,162 @@
+//! Contraction: collapsing groups of nodes into single summary nodes, for readable overviews of
+//! large graphs.
+//!
+//! [`contract`] takes the name of each node's group from a function of the node, and
+//! [`contract_by_attribute`] groups the nodes sharing the value of an attribute. Each group
+//! becomes a node named like it, labeled with its name and the number of its members, with their
+//! IDs in its `comment` as for [`condensation`](super::scc::condensation); a node named like a
+//! group is part of it. Nodes in no group stay as they are.
+//!
+//! Edges inside a group are dropped. Of the edges between the same two nodes of the
+//! contraction, either way in undirected graphs, a single one keeps its attributes and several
+//! are merged into one labeled with their number.
+
+use std::collections::HashMap;
+
+use crate::{
+    attribute::Attribute,
+    ir::quote_id,
+    model::{Graph, Node, NodeIndex},
+};
+
+/// A graph with groups of nodes collapsed into single nodes.
+#[derive(Debug, Clone)]
+pub struct Contraction {
+    pub graph: Graph,
+    /// The nodes of the original graph in each node of `graph`, in order.
+    pub members: Vec<Vec<NodeIndex>>,
+}
+
+/// The contraction of `graph` with the nodes for which `group` gives the same name collapsed
+/// into one node of that name; see the [module documentation](self).
+pub fn contract(graph: &Graph, group: impl Fn(&Node) -> Option<String>) -> Contraction {
+    let mut contracted = Graph::new(graph.kind, false);
+    contracted.id = graph.id.clone();
+    contracted.attributes = graph.attributes.clone();
+    let mut members = vec![];
+    let mut node_of = vec![0; graph.nodes().len()];
+    let mut grouped = vec![];
+    for (index, node) in graph.nodes().iter().enumerate() {
+        let name = group(node);
+        let summary = contracted.add_node(name.as_deref().unwrap_or(&node.id));
+        if summary == members.len() {
+            members.push(vec![]);
+            grouped.push(false);
+            contracted.node_mut(summary).attributes = node.attributes.clone();
+        }
+        if name.is_some() && !grouped[summary] {
+            grouped[summary] = true;
+            contracted.node_mut(summary).attributes.clear();
+        }
+        members[summary].push(index);
+        node_of[index] = summary;
+    }
+    for (summary, nodes) in members
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| grouped[*index])
+    {
+        let id = contracted.nodes()[summary].id.clone();
+        let ids = nodes
+            .iter()
+            .map(|&node| quote_id(&graph.nodes()[node].id))
+            .collect::<Vec<_>>();
+        let attributes = &mut contracted.node_mut(summary).attributes;
+        attributes.push(Attribute::Label(format!("{} ({})", id, nodes.len())));
+        attributes.push(Attribute::Comment(ids.join(" ")));
+    }
+
+    // the edges between each pair of nodes of the contraction, in order of their first
+    let mut pairs = HashMap::new();
+    let mut edges: Vec<Vec<usize>> = vec![];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        let (mut tail, mut head) = (node_of[edge.tail], node_of[edge.head]);
+        if tail == head && grouped[tail] {
+            continue;
+        }
+        if !graph.is_directed() && head < tail {
+            std::mem::swap(&mut tail, &mut head);
+        }
+        let pair = *pairs.entry((tail, head)).or_insert_with(|| {
+            edges.push(vec![]);
+            edges.len() - 1
+        });
+        edges[pair].push(index);
+    }
+    for merged in edges {
+        let edge = &graph.edges()[merged[0]];
+        let attributes = match merged.len() {
+            1 => edge.attributes.clone(),
+            count => vec![Attribute::Label(count.to_string())],
+        };
+        contracted.add_edge(node_of[edge.tail], node_of[edge.head], attributes);
+    }
+    contracted.strict = graph.strict;
+    Contraction {
+        graph: contracted,
+        members,
+    }
+}
+
+/// The contraction of `graph` with the nodes sharing the value of the attribute `name` collapsed
+/// into one node named by the value.
+pub fn contract_by_attribute(graph: &Graph, name: &str) -> Contraction {
+    contract(graph, |node| {
+        node.attributes
+            .iter()
+            .rev()
+            .find(|attribute| attribute.name() == name)
+            .map(Attribute::value_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn contract() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                "digraph { p [shape=box] node [color=red] a -> b a -> b b -> c \
+                 node [color=blue] x -> y a -> x c -> y b -> y p -> a p -> b p -> y [style=bold] }",
+            )
+            .unwrap(),
+        );
+        let Contraction {
+            graph: contracted,
+            members,
+        } = contract_by_attribute(&graph, "color");
+        let ids = contracted
+            .nodes()
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["p", "red", "blue"]);
+        assert_eq!(members, vec![vec![0], vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(
+            contracted.nodes()[1].attributes,
+            vec![
+                Attribute::Label("red (3)".to_owned()),
+                Attribute::Comment("a b c".to_owned())
+            ]
+        );
+        assert_eq!(
+            contracted.nodes()[0].attributes,
+            graph.nodes()[0].attributes
+        );
+
+        let edges = contracted
+            .edges()
+            .iter()
+            .map(|edge| (edge.tail, edge.head, edge.attributes.clone()))
+            .collect::<Vec<_>>();
+        let label = |label: &str| vec![Attribute::Label(label.to_owned())];
+        let bold = vec![Attribute::from_pair("style", "bold").unwrap()];
+        assert_eq!(
+            edges,
+            vec![(1, 2, label("3")), (0, 1, label("2")), (0, 2, bold)]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Graph equivalence, for comparing generated graphs regardless of the order of their statements.
+//!
+//! [`structurally_equal`] compares graphs by node ID: the same nodes with the same attributes and
+//! the same edges between them, in any order. [`is_isomorphic`] looks for any one-to-one mapping
+//! of the nodes of one graph onto the other preserving edges, whatever the nodes are called, by a
+//! backtracking search in the manner of VF2: nodes are matched in an order keeping each next to
+//! those matched before it where possible, so that mismatched edges cut the search short.
+//!
+//! Attributes are compared by name and value, in any order and ignoring ones set twice. Edges
+//! between the same nodes are compared as a multiset, and edges of undirected graphs either way.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    attribute::Attribute,
+    model::{Graph, NodeIndex},
+};
+
+/// The attributes of an element by name and value, in order of name.
+type Attributes = BTreeMap<&'static str, String>;
+
+/// The attributes of an element by name, the last of each name winning.
+fn attributes(attributes: &[Attribute]) -> Attributes {
+    attributes
+        .iter()
+        .map(|attribute| (attribute.name(), attribute.value_string()))
+        .collect()
+}
+
+/// Whether `a` and `b` have the same nodes by ID and the same edges between them, with the same
+/// attributes; see the [module documentation](self).
+pub fn structurally_equal(a: &Graph, b: &Graph) -> bool {
+    a.kind == b.kind
+        && a.strict == b.strict
+        && a.id == b.id
+        && attributes(&a.attributes) == attributes(&b.attributes)
+        && canonical(a) == canonical(b)
+}
+
+/// The nodes of `graph` by ID and its edges by the IDs of their ends, in order.
+#[allow(clippy::type_complexity)]
+fn canonical(graph: &Graph) -> (Vec<(&str, Attributes)>, Vec<(&str, &str, Attributes)>) {
+    let id = |node: NodeIndex| graph.nodes()[node].id.as_str();
+    let mut nodes = graph
+        .nodes()
+        .iter()
+        .map(|node| (node.id.as_str(), attributes(&node.attributes)))
+        .collect::<Vec<_>>();
+    nodes.sort();
+    let mut edges = graph
+        .edges()
+        .iter()
+        .map(|edge| {
+            let (mut tail, mut head) = (id(edge.tail), id(edge.head));
+            if !graph.is_directed() && head < tail {
+                std::mem::swap(&mut tail, &mut head);
+            }
+            (tail, head, attributes(&edge.attributes))
+        })
+        .collect::<Vec<_>>();
+    edges.sort();
+    (nodes, edges)
+}
+
+/// The edges of a graph from each node to each other, with what is compared of each.
+struct Adjacency {
+    /// The sorted labels of the edges from one node to another, both ways in undirected graphs.
+    edges: HashMap<(NodeIndex, NodeIndex), Vec<Attributes>>,
+    /// The neighbors of each node, either way.
+    neighbors: Vec<Vec<NodeIndex>>,
+    /// What a node has to match in the other graph: its label and numbers of in- and out-edges.
+    invariants: Vec<(Attributes, usize, usize)>,
+}
+
+impl Adjacency {
+    fn new(graph: &Graph, compare_attributes: bool) -> Self {
+        let label = |list: &[Attribute]| {
+            if compare_attributes {
+                attributes(list)
+            } else {
+                Attributes::new()
+            }
+        };
+        let nodes = graph.nodes().len();
+        let mut edges = HashMap::<_, Vec<_>>::new();
+        let mut neighbors = vec![vec![]; nodes];
+        let mut invariants = graph
+            .nodes()
+            .iter()
+            .map(|node| (label(&node.attributes), 0, 0))
+            .collect::<Vec<_>>();
+        for edge in graph.edges() {
+            let attributes = label(&edge.attributes);
+            if !graph.is_directed() && edge.tail != edge.head {
+                let reverse = edges.entry((edge.head, edge.tail)).or_default();
+                reverse.push(attributes.clone());
+                invariants[edge.head].2 += 1;
+                invariants[edge.tail].1 += 1;
+            }
+            edges
+                .entry((edge.tail, edge.head))
+                .or_default()
+                .push(attributes);
+            invariants[edge.tail].2 += 1;
+            invariants[edge.head].1 += 1;
+            neighbors[edge.tail].push(edge.head);
+            neighbors[edge.head].push(edge.tail);
+        }
+        for labels in edges.values_mut() {
+            labels.sort();
+        }
+        Adjacency {
+            edges,
+            neighbors,
+            invariants,
+        }
+    }
+
+    fn edges(&self, tail: NodeIndex, head: NodeIndex) -> &[Attributes] {
+        self.edges.get(&(tail, head)).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Whether the nodes of `a` can be mapped one-to-one onto those of `b` so that every edge of
+/// either is matched by one of the other, comparing the attributes of the graphs, nodes and edges
+/// too if `compare_attributes`; see the [module documentation](self).
+pub fn is_isomorphic(a: &Graph, b: &Graph, compare_attributes: bool) -> bool {
+    if a.kind != b.kind
+        || a.nodes().len() != b.nodes().len()
+        || a.edges().len() != b.edges().len()
+        || (compare_attributes && attributes(&a.attributes) != attributes(&b.attributes))
+    {
+        return false;
+    }
+    let (a, b) = (
+        Adjacency::new(a, compare_attributes),
+        Adjacency::new(b, compare_attributes),
+    );
+    let mut invariants = (a.invariants.clone(), b.invariants.clone());
+    invariants.0.sort();
+    invariants.1.sort();
+    if invariants.0 != invariants.1 {
+        return false;
+    }
+
+    // match the nodes of `a` with the most neighbors matched before them first, to check their
+    // edges as early as possible
+    let nodes = a.neighbors.len();
+    let (mut order, mut ordered, mut links) = (vec![], vec![false; nodes], vec![0; nodes]);
+    while order.len() < nodes {
+        let next = (0..nodes)
+            .filter(|&node| !ordered[node])
+            .max_by_key(|&node| (links[node], a.neighbors[node].len(), nodes - node))
+            .unwrap();
+        ordered[next] = true;
+        order.push(next);
+        for &neighbor in &a.neighbors[next] {
+            links[neighbor] += 1;
+        }
+    }
+
+    let mut mapping = vec![None; nodes];
+    let mut used = vec![false; nodes];
+    extend(&a, &b, &order, &mut mapping, &mut used)
+}
+
+/// Extends a mapping of the first nodes of `order` to the rest of them, if it can be.
+fn extend(
+    a: &Adjacency,
+    b: &Adjacency,
+    order: &[NodeIndex],
+    mapping: &mut [Option<NodeIndex>],
+    used: &mut [bool],
+) -> bool {
+    let matched = order.len() - mapping.iter().filter(|node| node.is_none()).count();
+    let node = match order.get(matched) {
+        Some(&node) => node,
+        None => return true,
+    };
+    for candidate in 0..used.len() {
+        if used[candidate] || a.invariants[node] != b.invariants[candidate] {
+            continue;
+        }
+        let consistent = a.edges(node, node) == b.edges(candidate, candidate)
+            && order[..matched].iter().all(|&other| {
+                let image = mapping[other].unwrap();
+                a.edges(node, other) == b.edges(candidate, image)
+                    && a.edges(other, node) == b.edges(image, candidate)
+            });
+        if !consistent {
+            continue;
+        }
+        mapping[node] = Some(candidate);
+        used[candidate] = true;
+        if extend(a, b, order, mapping, used) {
+            return true;
+        }
+        mapping[node] = None;
+        used[candidate] = false;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn graph(source: &str) -> Graph {
+        Graph::from_ir(&parse_graph(source).unwrap())
+    }
+
+    #[test]
+    fn structural_equality() {
+        let a = graph("digraph { a [shape=box color=red] a -> b [style=dashed] b -> c }");
+        let b = graph("digraph { c b -> c a [color=red shape=box] a -> b [style=dashed] }");
+        assert!(structurally_equal(&a, &b));
+        assert!(!structurally_equal(
+            &a,
+            &graph("digraph { a [shape=box color=red] a -> b b -> c }")
+        ));
+        assert!(!structurally_equal(
+            &a,
+            &graph("digraph { a [shape=box color=red] b -> a [style=dashed] b -> c }")
+        ));
+        assert!(structurally_equal(
+            &graph("graph { a -- b }"),
+            &graph("graph { b -- a }")
+        ));
+    }
+
+    #[test]
+    fn isomorphism() {
+        let cycle = graph("digraph { a -> b -> c -> a c -> d }");
+        let renamed = graph("digraph { z -> x x -> w w -> z w -> y }");
+        assert!(is_isomorphic(&cycle, &renamed, false));
+        assert!(!is_isomorphic(
+            &cycle,
+            &graph("digraph { a -> b -> c -> a d -> c }"),
+            false
+        ));
+        assert!(!structurally_equal(&cycle, &renamed));
+
+        // a hexagon and two triangles have the same degrees
+        let hexagon = graph("graph { a -- b -- c -- d -- e -- f -- a }");
+        let triangles = graph("graph { a -- b -- c -- a d -- e -- f -- d }");
+        assert!(!is_isomorphic(&hexagon, &triangles, false));
+        assert!(is_isomorphic(
+            &hexagon,
+            &graph("graph { f -- d -- b -- a -- c -- e -- f }"),
+            false
+        ));
+
+        // parallel edges, loops and attributes
+        let a = graph("digraph { a -> b a -> b [color=red] b -> b [style=bold] }");
+        let b = graph("digraph { y -> y [style=bold] x -> y [color=red] x -> y }");
+        assert!(is_isomorphic(&a, &b, true));
+        let c = graph("digraph { x -> y x -> y y -> y [style=bold] }");
+        assert!(is_isomorphic(&a, &c, false) && !is_isomorphic(&a, &c, true));
+    }
+}
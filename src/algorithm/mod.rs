@@ -0,0 +1,20 @@
+//! Graph algorithms and transforms over the semantic [`model`](crate::model).
+
+pub mod acyclic;
+pub mod bipartite;
+pub mod centrality;
+pub mod closure;
+pub mod complement;
+pub mod components;
+pub mod contract;
+pub mod isomorphism;
+pub mod neighborhood;
+pub mod pack;
+pub mod path;
+pub mod reachability;
+pub mod reduction;
+pub mod reverse;
+pub mod scc;
+pub mod spanning;
+pub mod topological;
+pub mod unflatten;
@@ -0,0 +1,106 @@
+//! Neighborhoods: the nodes within a number of hops of some seed nodes.
+
+use crate::model::{Graph, NodeIndex};
+
+/// Which edges to follow away from the seeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From tail to head, reaching successors.
+    Out,
+    /// From head to tail, reaching predecessors.
+    In,
+    Both,
+}
+
+/// The nodes at most `hops` edges away from any of `seeds`, seeds included, in order, or all the
+/// nodes reachable from them with `usize::MAX` hops. Edges of undirected graphs are followed both
+/// ways whatever the direction.
+pub fn neighborhood(
+    graph: &Graph,
+    seeds: &[NodeIndex],
+    hops: usize,
+    direction: Direction,
+) -> Vec<NodeIndex> {
+    let direction = if graph.is_directed() {
+        direction
+    } else {
+        Direction::Both
+    };
+    let mut neighbors = vec![vec![]; graph.nodes().len()];
+    for edge in graph.edges() {
+        if direction != Direction::In {
+            neighbors[edge.tail].push(edge.head);
+        }
+        if direction != Direction::Out {
+            neighbors[edge.head].push(edge.tail);
+        }
+    }
+    let mut reached = vec![false; graph.nodes().len()];
+    let mut frontier = vec![];
+    for &seed in seeds {
+        if !reached[seed] {
+            reached[seed] = true;
+            frontier.push(seed);
+        }
+    }
+    for _ in 0..hops {
+        if frontier.is_empty() {
+            break;
+        }
+        let mut next = vec![];
+        for node in frontier {
+            for &neighbor in &neighbors[node] {
+                if !reached[neighbor] {
+                    reached[neighbor] = true;
+                    next.push(neighbor);
+                }
+            }
+        }
+        frontier = next;
+    }
+    (0..graph.nodes().len())
+        .filter(|&node| reached[node])
+        .collect()
+}
+
+/// The subgraph of the [`neighborhood`] of `seeds`, with all edges between its nodes.
+pub fn neighborhood_subgraph(
+    graph: &Graph,
+    seeds: &[NodeIndex],
+    hops: usize,
+    direction: Direction,
+) -> Graph {
+    graph.induced_subgraph(neighborhood(graph, seeds, hops, direction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn neighborhood() {
+        let graph = Graph::from_ir(&parse_graph("digraph { a -> b -> c -> d x -> b e }").unwrap());
+        let b = graph.node_index("b").unwrap();
+        assert_eq!(
+            super::neighborhood(&graph, &[b], 0, Direction::Both),
+            vec![1]
+        );
+        assert_eq!(
+            super::neighborhood(&graph, &[b], 1, Direction::Both),
+            vec![0, 1, 2, 4]
+        );
+        assert_eq!(
+            super::neighborhood(&graph, &[b], usize::MAX, Direction::In),
+            vec![0, 1, 4]
+        );
+        assert_eq!(
+            super::neighborhood(&graph, &[b], 2, Direction::Out),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            super::neighborhood(&graph, &[b], 5, Direction::In),
+            vec![0, 1, 4]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Packing several graphs into one, like Graphviz's `gvpack`.
+//!
+//! The nodes and edges of all graphs are combined into a single graph, renaming nodes whose IDs
+//! are already taken by appending the number of their graph, `a_2` for node `a` of the second
+//! graph. Graph attributes are merged, the first graph setting an attribute winning.
+//!
+//! If every graph is laid out, with positions on all of its nodes, the drawings are also
+//! arranged side by side without overlapping, following [`PackOptions`]: node positions, edge
+//! splines and label positions are moved with their graph, and `bb` is set to the bounding box
+//! of the whole. Graphs are placed in rows, aligned in a grid in `array` mode. Graphviz's other
+//! modes fit graphs into each other's gaps instead; here they only place graphs in rows, with
+//! as many graphs to a row as there are rows.
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, Int, Pack, PackFlag, PackMode, Point, Position, Rectangle},
+    ir::GraphKind,
+    model::Graph,
+};
+
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("can't pack directed and undirected graphs together")]
+    MixedKinds,
+}
+
+/// Margin between packed graphs in points when the `pack` attribute doesn't give one.
+const DEFAULT_MARGIN: f64 = 8.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackOptions {
+    /// Margin left around each graph, in points.
+    pub margin: f64,
+    pub mode: PackMode,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions {
+            margin: DEFAULT_MARGIN,
+            mode: PackMode::Graph,
+        }
+    }
+}
+
+impl PackOptions {
+    /// Options from the `pack` and `packmode` attributes of `graph`, where `pack` gives the
+    /// margin unless it's just `true` or `false`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut options = PackOptions::default();
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::Pack(Pack::Value(margin)) => options.margin = *margin as f64,
+                Attribute::PackMode(mode) => options.mode = (**mode).clone(),
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+/// Moves all positions in `attributes` by `(dx, dy)`.
+fn translate(attributes: &mut [Attribute], dx: f64, dy: f64) {
+    for_each_point(attributes, |point| {
+        point.x += dx;
+        point.y += dy;
+    });
+}
+
+/// Calls `shift` on all positions in `attributes`, of nodes, edges, labels and boxes.
+pub(crate) fn for_each_point(attributes: &mut [Attribute], mut shift: impl FnMut(&mut Point)) {
+    for attribute in attributes {
+        match attribute {
+            Attribute::Pos(Position::Point(point) | Position::Pinned(point))
+            | Attribute::TailLp(point) => shift(point),
+            Attribute::Pos(Position::Spline(points)) => points.iter_mut().for_each(&mut shift),
+            Attribute::Bb(rectangle) | Attribute::Rects(rectangle) => {
+                shift(&mut rectangle.lower_left);
+                shift(&mut rectangle.upper_right);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lower left corners at which to place boxes of the given sizes, in points, so that they don't
+/// overlap, along with the size of the whole.
+pub(crate) fn arrange(
+    sizes: &[(f64, f64)],
+    sortv: &[Int],
+    options: &PackOptions,
+) -> (Vec<(f64, f64)>, (f64, f64)) {
+    let count = sizes.len();
+    let (size, flags): (usize, &[PackFlag]) = match &options.mode {
+        PackMode::Array { size, flags } => (*size as usize, flags),
+        _ => (0, &[]),
+    };
+    let array = matches!(options.mode, PackMode::Array { .. });
+    let lines = if size > 0 {
+        size
+    } else {
+        (count as f64).sqrt().ceil() as usize
+    }
+    .max(1);
+    let column_major = flags.contains(&PackFlag::ColumnMajor);
+    let mut order = (0..count).collect::<Vec<_>>();
+    if flags.contains(&PackFlag::User) {
+        order.sort_by_key(|&index| sortv[index]);
+    }
+    // cell of each box, by its place in the order
+    let (columns, rows) = if column_major {
+        (count.div_ceil(lines), lines)
+    } else {
+        (lines, count.div_ceil(lines))
+    };
+    let mut cells = vec![(0, 0); count];
+    for (place, &index) in order.iter().enumerate() {
+        cells[index] = if column_major {
+            (place / lines, place % lines)
+        } else {
+            (place % lines, place / lines)
+        };
+    }
+
+    let margin = options.margin;
+    let mut widths = vec![0.0f64; columns];
+    let mut heights = vec![0.0f64; rows];
+    for (&(column, row), &(width, height)) in cells.iter().zip(sizes) {
+        widths[column] = widths[column].max(width + 2.0 * margin);
+        heights[row] = heights[row].max(height + 2.0 * margin);
+    }
+    let total_height = heights.iter().sum::<f64>();
+    // bottom of each row, the first row at the top
+    let mut bottoms = vec![0.0; rows];
+    let mut top = total_height;
+    for (bottom, height) in bottoms.iter_mut().zip(&heights) {
+        top -= height;
+        *bottom = top;
+    }
+
+    let mut corners = vec![(0.0, 0.0); count];
+    let mut total_width = 0.0f64;
+    if array {
+        let mut lefts = vec![0.0; columns];
+        for column in 1..columns {
+            lefts[column] = lefts[column - 1] + widths[column - 1];
+        }
+        for (index, &(column, row)) in cells.iter().enumerate() {
+            let (width, height) = sizes[index];
+            let free = (
+                widths[column] - width - 2.0 * margin,
+                heights[row] - height - 2.0 * margin,
+            );
+            let x = if flags.contains(&PackFlag::Left) {
+                0.0
+            } else if flags.contains(&PackFlag::Right) {
+                free.0
+            } else {
+                free.0 / 2.0
+            };
+            let y = if flags.contains(&PackFlag::Bottom) {
+                0.0
+            } else if flags.contains(&PackFlag::Top) {
+                free.1
+            } else {
+                free.1 / 2.0
+            };
+            corners[index] = (lefts[column] + margin + x, bottoms[row] + margin + y);
+        }
+        total_width = widths.iter().sum();
+    } else {
+        let mut lefts = vec![0.0; rows];
+        for &index in &order {
+            let (_, row) = cells[index];
+            let (width, height) = sizes[index];
+            let y = (heights[row] - height - 2.0 * margin) / 2.0;
+            corners[index] = (lefts[row] + margin, bottoms[row] + margin + y);
+            lefts[row] += width + 2.0 * margin;
+            total_width = total_width.max(lefts[row]);
+        }
+    }
+    (corners, (total_width, total_height))
+}
+
+/// Packs `graphs` into one; see the [module documentation](self) for the details.
+pub fn pack(graphs: &[Graph], options: &PackOptions) -> Result<Graph, PackError> {
+    if graphs
+        .windows(2)
+        .any(|pair| pair[0].is_directed() != pair[1].is_directed())
+    {
+        return Err(PackError::MixedKinds);
+    }
+    let kind = graphs
+        .first()
+        .map_or(GraphKind::Directed, |graph| graph.kind);
+    let mut packed = Graph::new(kind, graphs.iter().all(|graph| graph.strict));
+
+    let boxes = graphs
+        .iter()
+        .map(|graph| {
+            graph.bounding_box().map(|bb| {
+                graph
+                    .attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::Bb(bb) => Some((**bb).clone()),
+                        _ => None,
+                    })
+                    .unwrap_or(bb)
+            })
+        })
+        .collect::<Option<Vec<_>>>();
+    let offsets = boxes.as_ref().map(|boxes| {
+        let sizes = boxes
+            .iter()
+            .map(|bb| {
+                (
+                    bb.upper_right.x - bb.lower_left.x,
+                    bb.upper_right.y - bb.lower_left.y,
+                )
+            })
+            .collect::<Vec<_>>();
+        let sortv = graphs
+            .iter()
+            .map(|graph| {
+                graph
+                    .attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::SortV(sortv) => Some(*sortv),
+                        _ => None,
+                    })
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<_>>();
+        let (corners, (width, height)) = arrange(&sizes, &sortv, options);
+        packed.attributes.push(Attribute::Bb(Box::new(Rectangle {
+            lower_left: Point { x: 0.0, y: 0.0 },
+            upper_right: Point {
+                x: width,
+                y: height,
+            },
+        })));
+        corners
+            .into_iter()
+            .zip(boxes)
+            .map(|((x, y), bb)| (x - bb.lower_left.x, y - bb.lower_left.y))
+            .collect::<Vec<_>>()
+    });
+
+    let mut taken = HashSet::new();
+    for (number, graph) in graphs.iter().enumerate() {
+        for attribute in &graph.attributes {
+            if !packed
+                .attributes
+                .iter()
+                .any(|existing| existing.name() == attribute.name())
+            {
+                packed.attributes.push(attribute.clone());
+            }
+        }
+        let (dx, dy) = offsets
+            .as_ref()
+            .map_or((0.0, 0.0), |offsets| offsets[number]);
+        let mut indices = vec![];
+        for node in graph.nodes() {
+            let mut id = node.id.clone();
+            if taken.contains(&id) {
+                id = format!("{}_{}", node.id, number + 1);
+                let mut suffix = 1;
+                while taken.contains(&id) {
+                    suffix += 1;
+                    id = format!("{}_{}_{}", node.id, number + 1, suffix);
+                }
+            }
+            taken.insert(id.clone());
+            let index = packed.add_node(&id);
+            packed.node_mut(index).attributes = node.attributes.clone();
+            translate(&mut packed.node_mut(index).attributes, dx, dy);
+            indices.push(index);
+        }
+        for edge in graph.edges() {
+            let mut attributes = edge.attributes.clone();
+            translate(&mut attributes, dx, dy);
+            packed.add_edge(indices[edge.tail], indices[edge.head], attributes);
+        }
+    }
+    Ok(packed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn graph(input: &str) -> Graph {
+        Graph::from_ir(&parse_graph(input).unwrap())
+    }
+
+    fn positions(graph: &Graph) -> Vec<(&str, f64, f64)> {
+        graph
+            .nodes()
+            .iter()
+            .map(|node| {
+                let pos = node.position().unwrap();
+                (node.id.as_str(), pos.x, pos.y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pack() {
+        let graphs = vec![
+            graph(r#"graph { label=A a [pos="0,0", width=1, height=1] }"#),
+            graph(
+                r#"graph { label=B a [pos="100,100", width=1, height=1] a -- b b [pos="100,0", width=1, height=1] }"#,
+            ),
+        ];
+        let packed = super::pack(&graphs, &PackOptions::default()).unwrap();
+        assert_eq!(
+            positions(&packed),
+            vec![("a", 44.0, 94.0), ("a_2", 132.0, 144.0), ("b", 132.0, 44.0)]
+        );
+        assert_eq!(
+            packed.attributes,
+            vec![
+                Attribute::Bb(Box::new(Rectangle {
+                    lower_left: Point { x: 0.0, y: 0.0 },
+                    upper_right: Point { x: 176.0, y: 188.0 }
+                })),
+                Attribute::from_pair("label", "A").unwrap(),
+            ]
+        );
+        assert_eq!(packed.edges().len(), 1);
+
+        let mut graph = graph(r#"graph { pack=0 packmode="array_b1" }"#);
+        let options = PackOptions::from_graph(&graph);
+        let packed = super::pack(&graphs, &options).unwrap();
+        assert_eq!(
+            positions(&packed),
+            vec![("a", 36.0, 208.0), ("a_2", 36.0, 136.0), ("b", 36.0, 36.0)]
+        );
+
+        graph.kind = GraphKind::Directed;
+        assert!(matches!(
+            super::pack(&[graph, graphs[0].clone()], &options),
+            Err(PackError::MixedKinds)
+        ));
+    }
+}
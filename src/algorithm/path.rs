@@ -0,0 +1,210 @@
+//! Shortest paths between two nodes, for highlighting the route from one to the other.
+//!
+//! Paths follow edges from tail to head in digraphs and either way in undirected graphs. Their
+//! length is their number of edges, found by breadth-first search, or the sum of a numeric
+//! attribute of their edges such as `weight`, found by Dijkstra's algorithm, with edges without
+//! the attribute counting 1. Of paths of the same length, the one through the first declared
+//! nodes and edges is chosen.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use thiserror::Error;
+
+use crate::model::{Graph, NodeIndex};
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PathError {
+    #[error("no node `{0}`")]
+    UnknownNode(String),
+    #[error("edge {0} has a negative or invalid length")]
+    InvalidLength(usize),
+}
+
+/// What the length of a path adds up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cost<'a> {
+    /// The number of edges.
+    Hops,
+    /// The value of the numeric attribute of this name of each edge, 1 if it isn't set.
+    Attribute(&'a str),
+}
+
+/// A path from one node to another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    /// The nodes of the path in order, from the first to the last.
+    pub nodes: Vec<NodeIndex>,
+    /// The indices of the edges between successive nodes.
+    pub edges: Vec<usize>,
+    pub length: f64,
+}
+
+/// A node reached at a distance, ordered for a min-heap, nearest and then first declared first.
+#[derive(Debug, PartialEq)]
+struct Reached(f64, NodeIndex);
+
+impl Eq for Reached {}
+
+impl Ord for Reached {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
+
+impl PartialOrd for Reached {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The length of `edge` of `graph` for `cost`.
+pub(crate) fn length(graph: &Graph, edge: usize, cost: Cost) -> Result<f64, PathError> {
+    let name = match cost {
+        Cost::Hops => return Ok(1.0),
+        Cost::Attribute(name) => name,
+    };
+    let attribute = graph.edges()[edge]
+        .attributes
+        .iter()
+        .rev()
+        .find(|attribute| attribute.name() == name);
+    match attribute.map(|attribute| attribute.value_string().parse::<f64>()) {
+        None => Ok(1.0),
+        Some(Ok(length)) if length >= 0.0 => Ok(length),
+        _ => Err(PathError::InvalidLength(edge)),
+    }
+}
+
+/// The shortest path in `graph` from the node `from` to the node `to`, if `to` can be reached;
+/// see the [module documentation](self).
+pub fn shortest_path(
+    graph: &Graph,
+    from: &str,
+    to: &str,
+    cost: Cost,
+) -> Result<Option<Path>, PathError> {
+    let node = |id: &str| {
+        graph
+            .node_index(id)
+            .ok_or_else(|| PathError::UnknownNode(id.to_owned()))
+    };
+    let (from, to) = (node(from)?, node(to)?);
+    let nodes = graph.nodes().len();
+    let mut neighbors = vec![vec![]; nodes];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        let length = length(graph, index, cost)?;
+        neighbors[edge.tail].push((index, edge.head, length));
+        if !graph.is_directed() && edge.tail != edge.head {
+            neighbors[edge.head].push((index, edge.tail, length));
+        }
+    }
+
+    // the distance to each node reached, and the edge it was reached by
+    let mut distance = vec![f64::INFINITY; nodes];
+    let mut previous = vec![None; nodes];
+    distance[from] = 0.0;
+    if cost == Cost::Hops {
+        let mut queue = VecDeque::from(vec![from]);
+        while let Some(node) = queue.pop_front() {
+            for &(edge, head, _) in &neighbors[node] {
+                if distance[head].is_infinite() {
+                    distance[head] = distance[node] + 1.0;
+                    previous[head] = Some(edge);
+                    queue.push_back(head);
+                }
+            }
+        }
+    } else {
+        let mut done = vec![false; nodes];
+        let mut heap = BinaryHeap::from(vec![Reached(0.0, from)]);
+        while let Some(Reached(_, node)) = heap.pop() {
+            if std::mem::replace(&mut done[node], true) {
+                continue;
+            }
+            for &(edge, head, length) in &neighbors[node] {
+                if distance[node] + length < distance[head] {
+                    distance[head] = distance[node] + length;
+                    previous[head] = Some(edge);
+                    heap.push(Reached(distance[head], head));
+                }
+            }
+        }
+    }
+    if distance[to].is_infinite() {
+        return Ok(None);
+    }
+
+    let (mut path, mut edges) = (vec![to], vec![]);
+    let mut node = to;
+    while node != from {
+        let edge = previous[node].unwrap();
+        let ends = &graph.edges()[edge];
+        node = if ends.head == node {
+            ends.tail
+        } else {
+            ends.head
+        };
+        path.push(node);
+        edges.push(edge);
+    }
+    path.reverse();
+    edges.reverse();
+    Ok(Some(Path {
+        nodes: path,
+        edges,
+        length: distance[to],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn shortest_paths() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                "digraph { a -> b -> d [weight=5] a -> c [weight=2] c -> d [weight=0.5] \
+                 d -> e [len=-1] f }",
+            )
+            .unwrap(),
+        );
+        let path = |from, to, cost| {
+            let path = shortest_path(&graph, from, to, cost).unwrap().unwrap();
+            let names = path
+                .nodes
+                .iter()
+                .map(|&node| graph.nodes()[node].id.as_str());
+            (names.collect::<Vec<_>>().join(" "), path.edges, path.length)
+        };
+        assert_eq!(
+            path("a", "d", Cost::Hops),
+            ("a b d".to_owned(), vec![0, 1], 2.0)
+        );
+        assert_eq!(
+            path("a", "d", Cost::Attribute("weight")),
+            ("a c d".to_owned(), vec![2, 3], 2.5)
+        );
+        assert_eq!(path("a", "a", Cost::Hops), ("a".to_owned(), vec![], 0.0));
+        assert_eq!(shortest_path(&graph, "d", "a", Cost::Hops), Ok(None));
+        assert_eq!(
+            shortest_path(&graph, "a", "e", Cost::Attribute("len")),
+            Err(PathError::InvalidLength(4))
+        );
+        assert_eq!(
+            shortest_path(&graph, "a", "z", Cost::Hops),
+            Err(PathError::UnknownNode("z".to_owned()))
+        );
+
+        // undirected graphs are followed both ways
+        let graph = Graph::from_ir(&parse_graph("graph { a -- b c -- b }").unwrap());
+        let found = shortest_path(&graph, "a", "c", Cost::Hops)
+            .unwrap()
+            .unwrap();
+        assert_eq!((found.nodes, found.edges), (vec![0, 1, 2], vec![0, 1]));
+    }
+}
@@ -0,0 +1,102 @@
+//! Reachability: which nodes can be reached from which along the edges of a graph.
+//!
+//! A [`Reachability`] keeps the edges of a graph in both directions, so that repeated queries of
+//! the nodes downstream or upstream of others each take only a search from the node asked
+//! about. Edges of undirected graphs lead both ways.
+
+use crate::model::{Graph, NodeIndex};
+
+/// The edges of a graph indexed both ways, for reachability queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reachability {
+    successors: Vec<Vec<NodeIndex>>,
+    predecessors: Vec<Vec<NodeIndex>>,
+}
+
+impl Reachability {
+    pub fn new(graph: &Graph) -> Self {
+        let nodes = graph.nodes().len();
+        let (mut successors, mut predecessors) = (vec![vec![]; nodes], vec![vec![]; nodes]);
+        for edge in graph.edges() {
+            successors[edge.tail].push(edge.head);
+            predecessors[edge.head].push(edge.tail);
+            if !graph.is_directed() {
+                successors[edge.head].push(edge.tail);
+                predecessors[edge.tail].push(edge.head);
+            }
+        }
+        Reachability {
+            successors,
+            predecessors,
+        }
+    }
+
+    /// The nodes reached from `from` along `next`, stopping early at `to`, and whether `to` was.
+    fn search(
+        next: &[Vec<NodeIndex>],
+        from: NodeIndex,
+        to: Option<NodeIndex>,
+    ) -> (Vec<bool>, bool) {
+        let mut reached = vec![false; next.len()];
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            for &neighbor in &next[node] {
+                if Some(neighbor) == to {
+                    return (reached, true);
+                }
+                if !reached[neighbor] {
+                    reached[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        (reached, false)
+    }
+
+    fn reached(next: &[Vec<NodeIndex>], from: NodeIndex) -> Vec<NodeIndex> {
+        let (reached, _) = Reachability::search(next, from, None);
+        (0..next.len())
+            .filter(|&node| node != from && reached[node])
+            .collect()
+    }
+
+    /// The nodes `node` leads to along edges, in order, without `node` itself.
+    pub fn descendants(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        Reachability::reached(&self.successors, node)
+    }
+
+    /// The nodes leading to `node` along edges, in order, without `node` itself.
+    pub fn ancestors(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        Reachability::reached(&self.predecessors, node)
+    }
+
+    /// Whether a path leads from `from` to `to`; every node reaches itself.
+    pub fn reachable(&self, from: NodeIndex, to: NodeIndex) -> bool {
+        from == to || Reachability::search(&self.successors, from, Some(to)).1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn reachability() {
+        let graph =
+            Graph::from_ir(&parse_graph("digraph { a -> b -> c b -> d e -> a f c -> b }").unwrap());
+        let reachability = Reachability::new(&graph);
+        assert_eq!(reachability.descendants(0), vec![1, 2, 3]);
+        assert_eq!(reachability.descendants(2), vec![1, 3]);
+        assert_eq!(reachability.ancestors(3), vec![0, 1, 2, 4]);
+        assert!(reachability.reachable(4, 3));
+        assert!(!reachability.reachable(3, 0));
+        assert!(reachability.reachable(5, 5));
+        assert!(reachability.descendants(5).is_empty());
+
+        let graph = Graph::from_ir(&parse_graph("graph { a -- b c }").unwrap());
+        let reachability = Reachability::new(&graph);
+        assert_eq!(reachability.ancestors(0), vec![1]);
+        assert!(reachability.reachable(1, 0) && !reachability.reachable(0, 2));
+    }
+}
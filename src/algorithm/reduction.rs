@@ -0,0 +1,120 @@
+//! Transitive reduction, like Graphviz's `tred`.
+//!
+//! An edge is redundant if its head can be reached from its tail along other edges, so that
+//! removing it doesn't change which nodes can reach which. In a DAG, the transitive reduction
+//! is unique: it drops every edge implied by a longer path, and every duplicate of another
+//! edge. With cycles, it isn't unique. Edges are then considered from last to first, each
+//! dropped if redundant given the edges still there, so that earlier edges are kept over later
+//! ones. Loops are kept, and undirected graphs are left alone.
+
+use crate::model::{Graph, NodeIndex};
+
+/// Whether `to` can be reached from `from` along the out-edges given, skipping `removed` ones.
+fn reachable(
+    out_edges: &[Vec<(usize, NodeIndex)>],
+    removed: &[bool],
+    from: NodeIndex,
+    to: NodeIndex,
+) -> bool {
+    let mut visited = vec![false; out_edges.len()];
+    visited[from] = true;
+    let mut stack = vec![from];
+    while let Some(node) = stack.pop() {
+        for &(edge, head) in &out_edges[node] {
+            if removed[edge] || visited[head] {
+                continue;
+            }
+            if head == to {
+                return true;
+            }
+            visited[head] = true;
+            stack.push(head);
+        }
+    }
+    false
+}
+
+/// Indices of the edges removed by the transitive reduction of `graph`, in order.
+pub fn redundant_edges(graph: &Graph) -> Vec<usize> {
+    if !graph.is_directed() {
+        return vec![];
+    }
+    let mut out_edges = vec![vec![]; graph.nodes().len()];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        out_edges[edge.tail].push((index, edge.head));
+    }
+    let mut removed = vec![false; graph.edges().len()];
+    let mut redundant = vec![];
+    for (index, edge) in graph.edges().iter().enumerate().rev() {
+        if edge.tail == edge.head {
+            continue;
+        }
+        removed[index] = true;
+        if reachable(&out_edges, &removed, edge.tail, edge.head) {
+            redundant.push(index);
+        } else {
+            removed[index] = false;
+        }
+    }
+    redundant.reverse();
+    redundant
+}
+
+/// Reduces `graph` to its transitive reduction, keeping all nodes and the kept edges with their
+/// attributes, in order, and returning the number of edges removed. See the
+/// [module documentation](self) for the details.
+pub fn transitive_reduction(graph: &mut Graph) -> usize {
+    let mut keep = vec![true; graph.edges().len()];
+    let redundant = redundant_edges(graph);
+    for &index in &redundant {
+        keep[index] = false;
+    }
+    graph.retain_edges(|index| keep[index]);
+    redundant.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::Attribute, ir::parse_graph};
+
+    fn edges(graph: &Graph) -> Vec<(&str, &str)> {
+        graph
+            .edges()
+            .iter()
+            .map(|edge| {
+                (
+                    graph.nodes()[edge.tail].id.as_str(),
+                    graph.nodes()[edge.head].id.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reduction() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                "digraph { a -> b [color=red] a -> c -> d a -> d b -> d a -> b d -> d e }",
+            )
+            .unwrap(),
+        );
+        assert_eq!(redundant_edges(&graph), vec![3, 5]);
+        let mut reduced = graph.clone();
+        assert_eq!(transitive_reduction(&mut reduced), 2);
+        assert_eq!(reduced.nodes().len(), 5);
+        assert_eq!(
+            edges(&reduced),
+            vec![("a", "b"), ("a", "c"), ("c", "d"), ("b", "d"), ("d", "d")]
+        );
+        assert_eq!(
+            reduced.edges()[0].attributes,
+            vec![Attribute::from_pair("color", "red").unwrap()]
+        );
+
+        let mut graph =
+            Graph::from_ir(&parse_graph("digraph { a -> b -> c -> a a -> c }").unwrap());
+        assert_eq!(transitive_reduction(&mut graph), 1);
+        assert_eq!(edges(&graph), vec![("a", "b"), ("b", "c"), ("c", "a")]);
+    }
+}
@@ -0,0 +1,73 @@
+//! Edge reversal: the same graph with every edge of a digraph turned around, as for drawing
+//! dependencies from the other end.
+//!
+//! Attributes of the ends of edges go with their ends: `arrowhead` becomes `arrowtail`,
+//! `headlabel` becomes `taillabel`, and likewise for ports and clipping. The position of a tail
+//! label from a previous layout is dropped, as there's none for head labels. Undirected graphs
+//! are left alone.
+
+use crate::{attribute::Attribute, model::Graph};
+
+/// The attribute for the other end of an edge, or the attribute itself if it isn't one of an end.
+pub(crate) fn swap_end(attribute: Attribute) -> Attribute {
+    match attribute {
+        Attribute::ArrowHead(value) => Attribute::ArrowTail(value),
+        Attribute::ArrowTail(value) => Attribute::ArrowHead(value),
+        Attribute::HeadClip(value) => Attribute::TailClip(value),
+        Attribute::TailClip(value) => Attribute::HeadClip(value),
+        Attribute::HeadLabel(value) => Attribute::TailLabel(value),
+        Attribute::TailLabel(value) => Attribute::HeadLabel(value),
+        Attribute::HeadPort(value) => Attribute::TailPort(value),
+        Attribute::TailPort(value) => Attribute::HeadPort(value),
+        attribute => attribute,
+    }
+}
+
+/// Turns every edge of `graph` around, swapping the attributes of its ends; see the
+/// [module documentation](self).
+pub fn reverse(graph: &mut Graph) {
+    if !graph.is_directed() {
+        return;
+    }
+    for index in 0..graph.edges().len() {
+        let edge = graph.edge_mut(index);
+        std::mem::swap(&mut edge.tail, &mut edge.head);
+        edge.attributes = std::mem::take(&mut edge.attributes)
+            .into_iter()
+            .filter(|attribute| !matches!(attribute, Attribute::TailLp(_)))
+            .map(swap_end)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn reverse() {
+        let mut graph = Graph::from_ir(
+            &parse_graph("digraph { a:n -> b [arrowhead=dot headlabel=x color=red] b -> c }")
+                .unwrap(),
+        );
+        super::reverse(&mut graph);
+        let edge = &graph.edges()[0];
+        assert_eq!((edge.tail, edge.head), (1, 0));
+        let expected = [
+            ("arrowtail", "dot"),
+            ("taillabel", "x"),
+            ("color", "red"),
+            ("headport", "n"),
+        ]
+        .iter()
+        .map(|(name, value)| Attribute::from_pair(name, value).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(edge.attributes, expected);
+        assert_eq!((graph.edges()[1].tail, graph.edges()[1].head), (2, 1));
+
+        let mut graph = Graph::from_ir(&parse_graph("graph { a -- b }").unwrap());
+        super::reverse(&mut graph);
+        assert_eq!((graph.edges()[0].tail, graph.edges()[0].head), (0, 1));
+    }
+}
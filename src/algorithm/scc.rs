@@ -0,0 +1,209 @@
+//! Strongly connected components, like Graphviz's `sccmap`.
+//!
+//! Components are found by Tarjan's algorithm and numbered in the order of their first node, so
+//! that the numbering depends only on the order nodes are declared in. In undirected graphs,
+//! strongly connected components are just connected components.
+
+use crate::{
+    algorithm::components::connected_components,
+    attribute::Attribute,
+    ir::quote_id,
+    model::{Graph, NodeIndex},
+};
+
+/// The strongly connected components of `graph`, as lists of node indices in order, ordered by
+/// their first node.
+pub fn strongly_connected_components(graph: &Graph) -> Vec<Vec<NodeIndex>> {
+    if !graph.is_directed() {
+        return connected_components(graph);
+    }
+    // Tarjan's algorithm, with an explicit stack of nodes being searched along with the
+    // position of their next out-edge to follow
+    let nodes = graph.nodes().len();
+    let mut out_edges = vec![vec![]; nodes];
+    for edge in graph.edges() {
+        out_edges[edge.tail].push(edge.head);
+    }
+    let mut order = vec![None; nodes];
+    let mut lowlink = vec![0; nodes];
+    let mut on_stack = vec![false; nodes];
+    let mut stack = vec![];
+    let mut visited = 0;
+    let mut components = vec![];
+    for root in 0..nodes {
+        if order[root].is_some() {
+            continue;
+        }
+        let mut search = vec![(root, 0)];
+        order[root] = Some(visited);
+        lowlink[root] = visited;
+        visited += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some((node, next)) = search.last_mut() {
+            let node = *node;
+            if let Some(&head) = out_edges[node].get(*next) {
+                *next += 1;
+                match order[head] {
+                    None => {
+                        order[head] = Some(visited);
+                        lowlink[head] = visited;
+                        visited += 1;
+                        stack.push(head);
+                        on_stack[head] = true;
+                        search.push((head, 0));
+                    }
+                    Some(head_order) if on_stack[head] => {
+                        lowlink[node] = lowlink[node].min(head_order)
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+            search.pop();
+            if let Some(&(parent, _)) = search.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if Some(lowlink[node]) == order[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+        }
+    }
+    components.sort_unstable_by_key(|component| component[0]);
+    components
+}
+
+/// The number of the strongly connected component of each node of `graph`, numbered from 0 as
+/// ordered by [`strongly_connected_components`].
+pub fn strong_component_labels(graph: &Graph) -> Vec<usize> {
+    let mut labels = vec![0; graph.nodes().len()];
+    for (component, nodes) in strongly_connected_components(graph).iter().enumerate() {
+        for &node in nodes {
+            labels[node] = component;
+        }
+    }
+    labels
+}
+
+/// Whether every node of `graph` can be reached from every other along its edges. A graph
+/// without nodes is strongly connected.
+pub fn is_strongly_connected(graph: &Graph) -> bool {
+    strong_component_labels(graph)
+        .iter()
+        .all(|&label| label == 0)
+}
+
+/// A graph with each strongly connected component collapsed into a single node.
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    pub graph: Graph,
+    /// The nodes of the original graph in each node of `graph`, in order.
+    pub members: Vec<Vec<NodeIndex>>,
+}
+
+/// The condensation of `graph`: a graph with a node for each strongly connected component,
+/// ordered as by [`strongly_connected_components`], and an edge between two components for each
+/// pair of components with edges between them. Components are named `scc_1`, `scc_2`, ..., and
+/// their `comment` lists the IDs of their nodes, separated by spaces and quoted as in DOT. The
+/// graph keeps the graph attributes and ID, but not the attributes of nodes and edges.
+pub fn condensation(graph: &Graph) -> Condensation {
+    let components = strongly_connected_components(graph);
+    let mut component_of = vec![0; graph.nodes().len()];
+    let mut condensed = Graph::new(graph.kind, true);
+    condensed.id = graph.id.clone();
+    condensed.attributes = graph.attributes.clone();
+    for (component, nodes) in components.iter().enumerate() {
+        let members = nodes
+            .iter()
+            .map(|&node| {
+                component_of[node] = component;
+                quote_id(&graph.nodes()[node].id)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let index = condensed.add_node(&format!("scc_{}", component + 1));
+        condensed
+            .node_mut(index)
+            .attributes
+            .push(Attribute::Comment(members));
+    }
+    for edge in graph.edges() {
+        let (tail, head) = (component_of[edge.tail], component_of[edge.head]);
+        if tail != head {
+            // the graph is strict, so edges between the same components are merged
+            condensed.add_edge(tail, head, vec![]);
+        }
+    }
+    condensed.strict = graph.strict;
+    Condensation {
+        graph: condensed,
+        members: components,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn components() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph G { a -> b -> c -> a c -> d d -> e -> d b -> e "f g" -> f f -> f }"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            strongly_connected_components(&graph),
+            vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6]]
+        );
+        assert_eq!(strong_component_labels(&graph), vec![0, 0, 0, 1, 1, 2, 3]);
+        assert!(!is_strongly_connected(&graph));
+
+        let Condensation {
+            graph: condensed,
+            members,
+        } = condensation(&graph);
+        assert_eq!(members, strongly_connected_components(&graph));
+        assert_eq!(condensed.id.as_deref(), Some("G"));
+        assert_eq!(
+            condensed
+                .nodes()
+                .iter()
+                .map(|node| (node.id.as_str(), &node.attributes[..]))
+                .collect::<Vec<_>>(),
+            vec![
+                ("scc_1", &[Attribute::Comment("a b c".to_owned())][..]),
+                ("scc_2", &[Attribute::Comment("d e".to_owned())][..]),
+                ("scc_3", &[Attribute::Comment(r#""f g""#.to_owned())][..]),
+                ("scc_4", &[Attribute::Comment("f".to_owned())][..]),
+            ]
+        );
+        assert_eq!(
+            condensed
+                .edges()
+                .iter()
+                .map(|edge| (edge.tail, edge.head))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (2, 3)]
+        );
+
+        let graph = Graph::from_ir(&parse_graph("graph { a -- b c }").unwrap());
+        assert_eq!(
+            strongly_connected_components(&graph),
+            vec![vec![0, 1], vec![2]]
+        );
+        let graph = Graph::from_ir(&parse_graph("digraph { a -> b -> c -> a }").unwrap());
+        assert!(is_strongly_connected(&graph));
+    }
+}
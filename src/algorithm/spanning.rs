@@ -0,0 +1,111 @@
+//! Minimum spanning trees, for showing the cheapest way to connect the nodes of a network.
+//!
+//! Trees are found by Kruskal's algorithm over the edges by their length, as for
+//! [`shortest_path`](super::path::shortest_path): their number or a numeric attribute such as
+//! `weight`. Edges of the same length are taken in the order they are declared. Graphs that
+//! aren't connected get a tree of each component, and edges of digraphs are taken either way.
+//! Loops are never part of a tree.
+
+use crate::{
+    algorithm::path::{length, Cost, PathError},
+    attribute::Attribute,
+    model::{set_attribute, Graph, NodeIndex},
+};
+
+/// The root of the set of `node`, halving the paths to it along the way.
+fn find(parent: &mut [NodeIndex], mut node: NodeIndex) -> NodeIndex {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+/// The indices of the edges of a minimum spanning tree of `graph` by `cost`, in order; see the
+/// [module documentation](self).
+pub fn minimum_spanning_tree(graph: &Graph, cost: Cost) -> Result<Vec<usize>, PathError> {
+    let mut edges = (0..graph.edges().len())
+        .map(|edge| Ok((length(graph, edge, cost)?, edge)))
+        .collect::<Result<Vec<_>, _>>()?;
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut parent = (0..graph.nodes().len()).collect::<Vec<_>>();
+    let mut tree = vec![];
+    for (_, edge) in edges {
+        let (tail, head) = (graph.edges()[edge].tail, graph.edges()[edge].head);
+        let (tail, head) = (find(&mut parent, tail), find(&mut parent, head));
+        if tail != head {
+            parent[tail] = head;
+            tree.push(edge);
+        }
+    }
+    tree.sort_unstable();
+    Ok(tree)
+}
+
+/// The subgraph of all the nodes of `graph` and the edges of its minimum spanning tree.
+pub fn spanning_subgraph(graph: &Graph, cost: Cost) -> Result<Graph, PathError> {
+    let tree = minimum_spanning_tree(graph, cost)?;
+    Ok(graph.subgraph(0..graph.nodes().len(), tree))
+}
+
+/// Gives the edges of the minimum spanning tree of `graph` `attributes`, such as `style=bold` to
+/// pick them out, replacing any values they had, and returns their number.
+pub fn mark_spanning_tree(
+    graph: &mut Graph,
+    cost: Cost,
+    attributes: &[Attribute],
+) -> Result<usize, PathError> {
+    let tree = minimum_spanning_tree(graph, cost)?;
+    for &edge in &tree {
+        for attribute in attributes {
+            set_attribute(&mut graph.edge_mut(edge).attributes, attribute.clone());
+        }
+    }
+    Ok(tree.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn spanning_tree() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                "graph { a -- b [weight=4] b -- c [weight=1] a -- c [weight=2] c -- d [weight=3] \
+                 b -- d [weight=1] d -- d e -- f }",
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            minimum_spanning_tree(&graph, Cost::Attribute("weight")),
+            Ok(vec![1, 2, 4, 6])
+        );
+        assert_eq!(
+            minimum_spanning_tree(&graph, Cost::Hops),
+            Ok(vec![0, 1, 3, 6])
+        );
+        let tree = spanning_subgraph(&graph, Cost::Attribute("weight")).unwrap();
+        assert_eq!((tree.nodes().len(), tree.edges().len()), (6, 4));
+
+        let mut graph = Graph::from_ir(
+            &parse_graph("digraph { a -> b b -> a c -> b [style=dashed, color=red] }").unwrap(),
+        );
+        let bold = [
+            Attribute::from_pair("style", "bold").unwrap(),
+            Attribute::from_pair("color", "blue").unwrap(),
+        ];
+        assert_eq!(mark_spanning_tree(&mut graph, Cost::Hops, &bold), Ok(2));
+        assert!(graph.edges()[1].attributes.is_empty());
+        // marking replaces the values the edge had
+        assert_eq!(graph.edges()[2].attributes, bold);
+
+        let graph = Graph::from_ir(&parse_graph("graph { a -- b [len=-1] }").unwrap());
+        assert_eq!(
+            minimum_spanning_tree(&graph, Cost::Attribute("len")),
+            Err(PathError::InvalidLength(0))
+        );
+    }
+}
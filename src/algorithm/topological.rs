@@ -0,0 +1,139 @@
+//! Topological ordering, for the order of dependencies given by the edges of a digraph.
+//!
+//! Nodes are ordered so that the tail of every edge comes before its head, by Kahn's algorithm:
+//! of the nodes whose in-edges all come from nodes already ordered, the first declared is taken
+//! next, so that the order stays as close to that of the source as the edges allow. Loops are
+//! ignored, and undirected graphs are in the order of their nodes.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use thiserror::Error;
+
+use crate::{
+    algorithm::acyclic::Cycle,
+    attribute::Attribute,
+    ir::Ident,
+    model::{Graph, NodeIndex},
+};
+
+/// A cycle keeping a graph from being ordered.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("graph has a cycle `{}`", .path.join(" -> "))]
+pub struct CycleError {
+    pub cycle: Cycle,
+    /// The names of the nodes of the cycle in order, ending with the first again.
+    pub path: Vec<Ident>,
+}
+
+/// Whether `edge` of `graph` orders its ends: it isn't a loop and, with `constraint`, isn't
+/// marked `constraint=false`.
+fn orders(graph: &Graph, edge: usize, constraint: bool) -> bool {
+    let edge = &graph.edges()[edge];
+    edge.tail != edge.head
+        && !(constraint && edge.attributes.contains(&Attribute::Constraint(false)))
+}
+
+/// The nodes of `graph` in topological order, or a cycle if there's none. With `constraint`,
+/// edges with `constraint=false` are left out, as layered layouts leave them out of ranking.
+pub fn topological_sort(graph: &Graph, constraint: bool) -> Result<Vec<NodeIndex>, CycleError> {
+    let nodes = graph.nodes().len();
+    if !graph.is_directed() {
+        return Ok((0..nodes).collect());
+    }
+    let mut out_edges = vec![vec![]; nodes];
+    let mut in_degree = vec![0; nodes];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        if orders(graph, index, constraint) {
+            out_edges[edge.tail].push(edge.head);
+            in_degree[edge.head] += 1;
+        }
+    }
+    let mut ready = (0..nodes)
+        .filter(|&node| in_degree[node] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(nodes);
+    while let Some(Reverse(node)) = ready.pop() {
+        order.push(node);
+        for &head in &out_edges[node] {
+            in_degree[head] -= 1;
+            if in_degree[head] == 0 {
+                ready.push(Reverse(head));
+            }
+        }
+    }
+    if order.len() == nodes {
+        return Ok(order);
+    }
+
+    // every node left has an in-edge from another node left, so following in-edges back from
+    // any of them comes around to a node already seen
+    let mut in_edges = vec![None; nodes];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        if in_degree[edge.head] > 0 && in_degree[edge.tail] > 0 && orders(graph, index, constraint)
+        {
+            in_edges[edge.head].get_or_insert(index);
+        }
+    }
+    let mut seen = vec![None; nodes];
+    let mut node = (0..nodes).find(|&node| in_degree[node] > 0).unwrap();
+    let mut path = vec![];
+    while seen[node].is_none() {
+        seen[node] = Some(path.len());
+        let edge = in_edges[node].unwrap();
+        path.push(edge);
+        node = graph.edges()[edge].tail;
+    }
+    // the edges into `node` and the nodes after it, followed back; turned forward, the first is
+    // the one closing the cycle
+    let mut edges = path.split_off(seen[node].unwrap());
+    edges.reverse();
+    let back_edge = edges.pop().unwrap();
+    let mut cycle = vec![node];
+    cycle.extend(edges.iter().map(|&edge| graph.edges()[edge].head));
+    let names = cycle.iter().chain(&cycle[..1]);
+    Err(CycleError {
+        path: names.map(|&node| graph.nodes()[node].id.clone()).collect(),
+        cycle: Cycle {
+            back_edge,
+            nodes: cycle,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn topological_sort() {
+        let graph = |source| Graph::from_ir(&parse_graph(source).unwrap());
+        let sorted = |graph: &Graph, constraint| {
+            let order = super::topological_sort(graph, constraint)?;
+            let names = order
+                .into_iter()
+                .map(|node| graph.nodes()[node].id.as_str());
+            Ok::<_, CycleError>(names.collect::<Vec<_>>().join(" "))
+        };
+        // ties go to the first declared
+        let dependencies = graph("digraph { d c -> b -> a c -> a e -> e }");
+        assert_eq!(sorted(&dependencies, false).unwrap(), "d c b a e");
+
+        let cyclic = graph("digraph { x a -> b -> c -> d c -> a [constraint=false] d -> b }");
+        let error = sorted(&cyclic, true).unwrap_err();
+        assert_eq!(error.to_string(), "graph has a cycle `b -> c -> d -> b`");
+        assert_eq!(
+            error.cycle,
+            Cycle {
+                back_edge: 4,
+                nodes: vec![2, 3, 4]
+            }
+        );
+        let cyclic = graph("digraph { a -> b -> c c -> a [constraint=false] }");
+        assert_eq!(sorted(&cyclic, true).unwrap(), "a b c");
+        assert!(sorted(&cyclic, false).is_err());
+
+        assert_eq!(sorted(&graph("graph { b -- a a }"), false).unwrap(), "b a");
+    }
+}
@@ -0,0 +1,165 @@
+//! The `unflatten` transform, improving the aspect ratio of wide, shallow graphs for layered
+//! layouts, like Graphviz's `unflatten`.
+//!
+//! Edges between a node and its leaves, the nodes with only one edge, are staggered by giving
+//! them increasing `minlen`s, so that the leaves are spread over several ranks rather than all
+//! placed side by side. Isolated nodes can likewise be stacked into chains with invisible edges.
+//! Loops don't count towards degrees, and edges which already have a `minlen` are left alone.
+
+use crate::{
+    attribute::{Attribute, Int, Style},
+    model::Graph,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnflattenOptions {
+    /// Staggers the edges to the leaves of each node with `minlen`s cycling from 1 to this.
+    pub stagger: Option<usize>,
+    /// Staggers edges to chain nodes too, nodes with one in-edge and one out-edge, when
+    /// staggering edges to leaves.
+    pub fans: bool,
+    /// Stacks isolated nodes into chains of up to this many nodes.
+    pub chain: Option<usize>,
+}
+
+/// Applies the transform to `graph`; see the [module documentation](self) for the details.
+pub fn unflatten(graph: &mut Graph, options: &UnflattenOptions) {
+    let mut indegree = vec![0; graph.nodes().len()];
+    let mut outdegree = vec![0; graph.nodes().len()];
+    for edge in graph.edges().iter().filter(|edge| edge.tail != edge.head) {
+        outdegree[edge.tail] += 1;
+        indegree[edge.head] += 1;
+    }
+    let is_leaf = |node: usize| indegree[node] + outdegree[node] == 1;
+    let is_chain_node = |node: usize| indegree[node] == 1 && outdegree[node] == 1;
+
+    let mut staggered = vec![];
+    if let Some(stagger) = options.stagger.filter(|&stagger| stagger > 0) {
+        for node in 0..graph.nodes().len() {
+            if indegree[node] + outdegree[node] < 2 {
+                continue;
+            }
+            let (mut incoming, mut outgoing) = (0, 0);
+            for (index, edge) in graph.edges().iter().enumerate() {
+                let count = if edge.head == node && edge.tail != node && is_leaf(edge.tail) {
+                    &mut incoming
+                } else if edge.tail == node
+                    && edge.head != node
+                    && (is_leaf(edge.head) || options.fans && is_chain_node(edge.head))
+                {
+                    &mut outgoing
+                } else {
+                    continue;
+                };
+                staggered.push((index, (*count % stagger + 1) as Int));
+                *count += 1;
+            }
+        }
+    }
+    for (index, minlen) in staggered {
+        let attributes = &mut graph.edge_mut(index).attributes;
+        if !attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::MinLen(_)))
+        {
+            attributes.push(Attribute::MinLen(minlen));
+        }
+    }
+
+    if let Some(chain) = options.chain.filter(|&chain| chain > 0) {
+        let mut last = None;
+        let mut length = 0;
+        for node in 0..graph.nodes().len() {
+            if indegree[node] + outdegree[node] > 0 {
+                continue;
+            }
+            if let Some(last) = last {
+                graph.add_edge(last, node, vec![Attribute::Style(vec![Style::Invis])]);
+                length += 1;
+            }
+            if length + 1 < chain {
+                last = Some(node);
+            } else {
+                last = None;
+                length = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn minlens(graph: &Graph) -> Vec<Option<Int>> {
+        graph
+            .edges()
+            .iter()
+            .map(|edge| {
+                edge.attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::MinLen(minlen) => Some(*minlen),
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unflatten() {
+        let input = "digraph {
+            r -> a r -> b r -> c r -> d r -> e -> f x -> r r -> b [minlen=5]
+            l -> l p q s t
+        }";
+        let mut graph = Graph::from_ir(&parse_graph(input).unwrap());
+        let options = UnflattenOptions {
+            stagger: Some(3),
+            chain: Some(2),
+            ..UnflattenOptions::default()
+        };
+        super::unflatten(&mut graph, &options);
+        // b has two in-edges, and e one in-edge and one out-edge, so neither is a leaf
+        assert_eq!(
+            minlens(&graph),
+            vec![
+                Some(1),
+                None,
+                Some(2),
+                Some(3),
+                None,
+                Some(1),
+                Some(1),
+                Some(5),
+                None,
+                None,
+                None
+            ]
+        );
+        let chained = graph.edges()[9..]
+            .iter()
+            .map(|edge| {
+                (
+                    graph.nodes()[edge.tail].id.as_str(),
+                    graph.nodes()[edge.head].id.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(chained, vec![("l", "p"), ("q", "s")]);
+        assert_eq!(
+            graph.edges()[9].attributes,
+            vec![Attribute::Style(vec![Style::Invis])]
+        );
+
+        let mut graph = Graph::from_ir(&parse_graph(input).unwrap());
+        let options = UnflattenOptions {
+            stagger: Some(3),
+            fans: true,
+            ..UnflattenOptions::default()
+        };
+        super::unflatten(&mut graph, &options);
+        assert_eq!(minlens(&graph)[4], Some(1));
+        assert_eq!(graph.edges().len(), 9);
+    }
+}
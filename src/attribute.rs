@@ -1,28 +1,47 @@
 //! Attribute definitions
 
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
 use nom::{
     branch::alt,
-    bytes::complete::tag,
     character::complete::char,
-    combinator::{map, opt},
-    multi::separated_list1,
+    combinator::{cut, opt},
+    error::ErrorKind,
     sequence::{pair, separated_pair},
-    Parser,
 };
 
-use crate::{color::Color, ir::ParseResult, ws::ws};
+use crate::{
+    color::{parse_color, Color, ColorName, ColorScheme},
+    ir::{ident_str_parser, AttributeKind, DotError, ParseResult},
+    ws::dot_ws,
+};
 
 pub type Double = f64;
 pub type Int = i64;
 
-#[derive(Debug)]
+/// Graphviz's default `fontname`, also that of `labelfontname`.
+pub const DEFAULT_FONT_NAME: &str = "Times-Roman";
+
+/// Graphviz's default `fontsize` in points, also that of `labelfontsize`.
+pub const DEFAULT_FONT_SIZE: Double = 14.0;
 
+/// A Graphviz attribute with its value.
+///
+/// Values larger than three words, and rarely set, are boxed to keep attributes to four words.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Attribute {
     Background(String),
     ArrowHead(ArrowType),
     ArrowSize(Double),
     ArrowTail(ArrowType),
-    Bb(Rectangle),
+    Bb(Box<Rectangle>),
     BgColor(ColorAttribute),
     Center(bool),
     Charset(String),
@@ -30,9 +49,11 @@ pub enum Attribute {
     ColorsSheme(String),
     Comment(String),
     Concentrate(bool),
+    Constraint(bool),
     Decorate(bool),
     Dir(DirType),
     Distortion(Double),
+    Dpi(Double),
     FillColor(ColorAttribute),
     FixedSize(FixedSize),
     FontColor(Color),
@@ -43,7 +64,9 @@ pub enum Attribute {
     GradientAngle(Int),
     HeadClip(bool),
     HeadLabel(LabelString),
+    HeadPort(Box<PortPos>),
     Height(Double),
+    Href(String),
     Image(String),
     ImagePath(String),
     ImagePos(String),
@@ -64,24 +87,34 @@ pub enum Attribute {
     LayerSelect(Vec<String>),
     LayerSep(String),
     Layout(String),
+    Len(Double),
     Margin(Point),
+    MaxIter(Int),
+    MinDist(Double),
+    MinLen(Int),
     NodeSep(Double),
     NoJustify(bool),
     Orientation(Orientation),
     OutputOrder(OutputMode),
+    Overlap(Overlap),
     Pack(Pack),
-    PackMode(PackMode),
+    PackMode(Box<PackMode>),
     Pad(Point),
     Page(Point),
     PageDir(PageDir),
     PenColor(Color),
     PenWidth(Double),
     Peripheries(Int),
+    Pin(bool),
     Pos(Position),
     Quantum(Double),
+    Rank(RankType),
+    RankDir(RankDir),
+    RankSep(Box<RankSeparation>),
     Ratio(Ratio),
-    Rects(Rectangle),
+    Rects(Box<Rectangle>),
     Regular(bool),
+    Root(String),
     Rotate(Int),
     SamplePoints(Int),
     Shape(Shape),
@@ -95,15 +128,611 @@ pub enum Attribute {
     TailLp(Point),
     TailClip(bool),
     TailLabel(LabelString),
+    TailPort(Box<PortPos>),
+    Target(String),
+    Tooltip(String),
+    Url(String),
     Vertices(Vec<Point>),
-    ViewPort(ViewPort),
+    ViewPort(Box<ViewPort>),
     Weight(Double),
     Width(Double),
     XLabel(LabelString),
     Z(Double),
 }
 
-#[derive(Debug)]
+impl core::str::FromStr for Attribute {
+    type Err = AttributeParseError;
+
+    /// Parses an attribute as written in DOT, such as `label="a b"` or `shape=box`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match attribute_pair_parser::<()>(s) {
+            Ok(("", (name, value))) => Attribute::from_pair(&name, &value),
+            _ => Err(AttributeParseError::InvalidAttribueValue),
+        }
+    }
+}
+
+impl Attribute {
+    /// The DOT name of this attribute, such as `label`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Background(_) => "_background",
+            Attribute::ArrowHead(_) => "arrowhead",
+            Attribute::ArrowSize(_) => "arrowsize",
+            Attribute::ArrowTail(_) => "arrowtail",
+            Attribute::Bb(_) => "bb",
+            Attribute::BgColor(_) => "bgcolor",
+            Attribute::Center(_) => "center",
+            Attribute::Charset(_) => "charset",
+            Attribute::Color(_) => "color",
+            Attribute::ColorsSheme(_) => "colorscheme",
+            Attribute::Comment(_) => "comment",
+            Attribute::Concentrate(_) => "concentrate",
+            Attribute::Constraint(_) => "constraint",
+            Attribute::Decorate(_) => "decorate",
+            Attribute::Dir(_) => "dir",
+            Attribute::Distortion(_) => "distortion",
+            Attribute::Dpi(_) => "dpi",
+            Attribute::FillColor(_) => "fillcolor",
+            Attribute::FixedSize(_) => "fixedsize",
+            Attribute::FontColor(_) => "fontcolor",
+            Attribute::FontName(_) => "fontname",
+            Attribute::FontPath(_) => "fontpath",
+            Attribute::FontSize(_) => "fontsize",
+            Attribute::ForceLabels(_) => "forcelabels",
+            Attribute::GradientAngle(_) => "gradientangle",
+            Attribute::HeadClip(_) => "headclip",
+            Attribute::HeadLabel(_) => "headlabel",
+            Attribute::HeadPort(_) => "headport",
+            Attribute::Height(_) => "height",
+            Attribute::Href(_) => "href",
+            Attribute::Image(_) => "image",
+            Attribute::ImagePath(_) => "imagepath",
+            Attribute::ImagePos(_) => "imagepos",
+            Attribute::ImageScale(_) => "imagescale",
+            Attribute::Label(_) => "label",
+            Attribute::LabelAngle(_) => "labelangle",
+            Attribute::LabelDistance(_) => "labeldistance",
+            Attribute::LabelFloat(_) => "labelfloat",
+            Attribute::LabelFontColor(_) => "labelfontcolor",
+            Attribute::LabelFontName(_) => "labelfontname",
+            Attribute::LabelFontSize(_) => "labelfontsize",
+            Attribute::LabelJust(_) => "labeljust",
+            Attribute::LabelLoc(_) => "labelloc",
+            Attribute::Landscape(_) => "landscape",
+            Attribute::Layer(_) => "layer",
+            Attribute::LayerListSep(_) => "layerlistsep",
+            Attribute::Layers(_) => "layers",
+            Attribute::LayerSelect(_) => "layerselect",
+            Attribute::LayerSep(_) => "layersep",
+            Attribute::Layout(_) => "layout",
+            Attribute::Len(_) => "len",
+            Attribute::Margin(_) => "margin",
+            Attribute::MaxIter(_) => "maxiter",
+            Attribute::MinDist(_) => "mindist",
+            Attribute::MinLen(_) => "minlen",
+            Attribute::NodeSep(_) => "nodesep",
+            Attribute::NoJustify(_) => "nojustify",
+            Attribute::Orientation(_) => "orientation",
+            Attribute::OutputOrder(_) => "outputorder",
+            Attribute::Overlap(_) => "overlap",
+            Attribute::Pack(_) => "pack",
+            Attribute::PackMode(_) => "packmode",
+            Attribute::Pad(_) => "pad",
+            Attribute::Page(_) => "page",
+            Attribute::PageDir(_) => "pagedir",
+            Attribute::PenColor(_) => "pencolor",
+            Attribute::PenWidth(_) => "penwidth",
+            Attribute::Peripheries(_) => "peripheries",
+            Attribute::Pin(_) => "pin",
+            Attribute::Pos(_) => "pos",
+            Attribute::Quantum(_) => "quantum",
+            Attribute::Rank(_) => "rank",
+            Attribute::RankDir(_) => "rankdir",
+            Attribute::RankSep(_) => "ranksep",
+            Attribute::Ratio(_) => "ratio",
+            Attribute::Rects(_) => "rects",
+            Attribute::Regular(_) => "regular",
+            Attribute::Root(_) => "root",
+            Attribute::Rotate(_) => "rotate",
+            Attribute::SamplePoints(_) => "samplepoints",
+            Attribute::Shape(_) => "shape",
+            Attribute::ShapeFile(_) => "shapefile",
+            Attribute::Sides(_) => "sides",
+            Attribute::Size(_) => "size",
+            Attribute::Skew(_) => "skew",
+            Attribute::SortV(_) => "sortv",
+            Attribute::Splines(_) => "splines",
+            Attribute::Style(_) => "style",
+            Attribute::TailLp(_) => "tail_lp",
+            Attribute::TailClip(_) => "tailclip",
+            Attribute::TailLabel(_) => "taillabel",
+            Attribute::TailPort(_) => "tailport",
+            Attribute::Target(_) => "target",
+            Attribute::Tooltip(_) => "tooltip",
+            Attribute::Url(_) => "URL",
+            Attribute::Vertices(_) => "vertices",
+            Attribute::ViewPort(_) => "viewport",
+            Attribute::Weight(_) => "weight",
+            Attribute::Width(_) => "width",
+            Attribute::XLabel(_) => "xlabel",
+            Attribute::Z(_) => "z",
+        }
+    }
+
+    /// Whether Graphviz reads this attribute on components of the given kind.
+    pub(crate) fn applies_to(&self, kind: &AttributeKind) -> bool {
+        // components this attribute is used by, as listed in the Graphviz attribute reference;
+        // cluster attributes count as graph attributes, as clusters inherit them from the graph
+        let used_by = match self {
+            Attribute::ArrowHead(_)
+            | Attribute::ArrowSize(_)
+            | Attribute::ArrowTail(_)
+            | Attribute::Constraint(_)
+            | Attribute::Decorate(_)
+            | Attribute::Dir(_)
+            | Attribute::HeadClip(_)
+            | Attribute::HeadLabel(_)
+            | Attribute::HeadPort(_)
+            | Attribute::LabelAngle(_)
+            | Attribute::LabelDistance(_)
+            | Attribute::LabelFloat(_)
+            | Attribute::LabelFontColor(_)
+            | Attribute::LabelFontName(_)
+            | Attribute::LabelFontSize(_)
+            | Attribute::Len(_)
+            | Attribute::MinLen(_)
+            | Attribute::TailLp(_)
+            | Attribute::TailClip(_)
+            | Attribute::TailLabel(_)
+            | Attribute::TailPort(_)
+            | Attribute::Weight(_) => "E",
+            Attribute::Distortion(_)
+            | Attribute::FixedSize(_)
+            | Attribute::Height(_)
+            | Attribute::Image(_)
+            | Attribute::ImagePos(_)
+            | Attribute::ImageScale(_)
+            | Attribute::Pin(_)
+            | Attribute::Rects(_)
+            | Attribute::Regular(_)
+            | Attribute::SamplePoints(_)
+            | Attribute::Shape(_)
+            | Attribute::ShapeFile(_)
+            | Attribute::Sides(_)
+            | Attribute::Skew(_)
+            | Attribute::Vertices(_)
+            | Attribute::Width(_)
+            | Attribute::Z(_) => "N",
+            Attribute::Pos(_) | Attribute::XLabel(_) => "NE",
+            Attribute::GradientAngle(_)
+            | Attribute::LabelLoc(_)
+            | Attribute::Margin(_)
+            | Attribute::NoJustify(_)
+            | Attribute::Orientation(_)
+            | Attribute::Peripheries(_)
+            | Attribute::Root(_)
+            | Attribute::SortV(_) => "GN",
+            Attribute::Color(_)
+            | Attribute::ColorsSheme(_)
+            | Attribute::Comment(_)
+            | Attribute::FillColor(_)
+            | Attribute::FontColor(_)
+            | Attribute::FontName(_)
+            | Attribute::FontSize(_)
+            | Attribute::Href(_)
+            | Attribute::Label(_)
+            | Attribute::Layer(_)
+            | Attribute::PenWidth(_)
+            | Attribute::Style(_)
+            | Attribute::Target(_)
+            | Attribute::Tooltip(_)
+            | Attribute::Url(_) => "GNE",
+            _ => "G",
+        };
+        used_by.contains(match kind {
+            AttributeKind::Graph => 'G',
+            AttributeKind::Node => 'N',
+            AttributeKind::Edge => 'E',
+        })
+    }
+
+    /// Parses an attribute from its DOT name and the (unquoted) text of its value. Together with
+    /// [`name`](Attribute::name) and [`value_string`](Attribute::value_string), this converts
+    /// between typed attributes and the names and values of other tools and formats.
+    pub fn from_pair(name: &str, value: &str) -> Result<Attribute, AttributeParseError> {
+        Ok(match name {
+            "_background" => Attribute::Background(value.to_owned()),
+            "arrowhead" => Attribute::ArrowHead(parse_value(value, ArrowType::from_keyword)?),
+            "arrowsize" => Attribute::ArrowSize(parse_number(value)?),
+            "arrowtail" => Attribute::ArrowTail(parse_value(value, ArrowType::from_keyword)?),
+            "bb" => Attribute::Bb(Box::new(parse_value(value, parse_rectangle)?)),
+            "bgcolor" => Attribute::BgColor(parse_value(value, parse_color_attribute)?),
+            "center" => Attribute::Center(parse_value(value, parse_bool)?),
+            "charset" => Attribute::Charset(value.to_owned()),
+            "color" => Attribute::Color(parse_value(value, parse_color_attribute)?),
+            "colorscheme" => Attribute::ColorsSheme(value.to_owned()),
+            "comment" => Attribute::Comment(value.to_owned()),
+            "concentrate" => Attribute::Concentrate(parse_value(value, parse_bool)?),
+            "constraint" => Attribute::Constraint(parse_value(value, parse_bool)?),
+            "decorate" => Attribute::Decorate(parse_value(value, parse_bool)?),
+            "dir" => Attribute::Dir(parse_value(value, DirType::from_keyword)?),
+            "distortion" => Attribute::Distortion(parse_number(value)?),
+            "dpi" => Attribute::Dpi(parse_number(value)?),
+            "fillcolor" => Attribute::FillColor(parse_value(value, parse_color_attribute)?),
+            "fixedsize" => Attribute::FixedSize(parse_value(value, FixedSize::from_keyword)?),
+            "fontcolor" => Attribute::FontColor(parse_value(value, parse_color)?),
+            "fontname" => Attribute::FontName(value.to_owned()),
+            "fontpath" => Attribute::FontPath(value.to_owned()),
+            "fontsize" => Attribute::FontSize(parse_number(value)?),
+            "forcelabels" => Attribute::ForceLabels(parse_value(value, parse_bool)?),
+            "gradientangle" => Attribute::GradientAngle(parse_number(value)?),
+            "headclip" => Attribute::HeadClip(parse_value(value, parse_bool)?),
+            "headlabel" => Attribute::HeadLabel(value.to_owned()),
+            "headport" => Attribute::HeadPort(Box::new(parse_value(value, parse_port_pos)?)),
+            "height" => Attribute::Height(parse_number(value)?),
+            "href" => Attribute::Href(value.to_owned()),
+            "image" => Attribute::Image(value.to_owned()),
+            "imagepath" => Attribute::ImagePath(value.to_owned()),
+            "imagepos" => Attribute::ImagePos(value.to_owned()),
+            "imagescale" => Attribute::ImageScale(parse_value(value, ImageScale::from_keyword)?),
+            "label" => Attribute::Label(value.to_owned()),
+            "labelangle" => Attribute::LabelAngle(parse_number(value)?),
+            "labeldistance" => Attribute::LabelDistance(parse_number(value)?),
+            "labelfloat" => Attribute::LabelFloat(parse_value(value, parse_bool)?),
+            "labelfontcolor" => Attribute::LabelFontColor(parse_value(value, parse_color)?),
+            "labelfontname" => Attribute::LabelFontName(value.to_owned()),
+            "labelfontsize" => Attribute::LabelFontSize(parse_number(value)?),
+            "labeljust" => {
+                Attribute::LabelJust(parse_value(value, TextJustification::from_keyword)?)
+            }
+            "labelloc" => Attribute::LabelLoc(parse_value(value, TextLocation::from_keyword)?),
+            "landscape" => Attribute::Landscape(parse_value(value, parse_bool)?),
+            "layer" => Attribute::Layer(parse_layers(value)),
+            "layerlistsep" => Attribute::LayerListSep(value.to_owned()),
+            "layers" => Attribute::Layers(parse_layers(value)),
+            "layerselect" => Attribute::LayerSelect(parse_layers(value)),
+            "layersep" => Attribute::LayerSep(value.to_owned()),
+            "layout" => Attribute::Layout(value.to_owned()),
+            "len" => Attribute::Len(parse_number(value)?),
+            "margin" => Attribute::Margin(parse_value(value, parse_point)?),
+            "maxiter" => Attribute::MaxIter(parse_number(value)?),
+            "mindist" => Attribute::MinDist(parse_number(value)?),
+            "minlen" => Attribute::MinLen(parse_number(value)?),
+            "nodesep" => Attribute::NodeSep(parse_number(value)?),
+            "nojustify" => Attribute::NoJustify(parse_value(value, parse_bool)?),
+            "orientation" => Attribute::Orientation(parse_value(value, parse_orientation)?),
+            "outputorder" => Attribute::OutputOrder(parse_value(value, OutputMode::from_keyword)?),
+            "overlap" => Attribute::Overlap(parse_value(value, Overlap::from_keyword)?),
+            "pack" => Attribute::Pack(parse_value(value, parse_pack)?),
+            "packmode" => Attribute::PackMode(Box::new(parse_value(value, parse_pack_mode)?)),
+            "pad" => Attribute::Pad(parse_value(value, parse_point)?),
+            "page" => Attribute::Page(parse_value(value, parse_point)?),
+            "pagedir" => Attribute::PageDir(parse_value(value, parse_page_dir)?),
+            "pencolor" => Attribute::PenColor(parse_value(value, parse_color)?),
+            "penwidth" => Attribute::PenWidth(parse_number(value)?),
+            "peripheries" => Attribute::Peripheries(parse_number(value)?),
+            "pin" => Attribute::Pin(parse_value(value, parse_bool)?),
+            "pos" => Attribute::Pos(parse_value(value, parse_position)?),
+            "quantum" => Attribute::Quantum(parse_number(value)?),
+            "rank" => Attribute::Rank(parse_value(value, RankType::from_keyword)?),
+            "rankdir" => Attribute::RankDir(parse_value(value, RankDir::from_keyword)?),
+            "ranksep" => Attribute::RankSep(Box::new(parse_value(value, parse_rank_separation)?)),
+            "ratio" => Attribute::Ratio(parse_value(value, parse_ratio)?),
+            "rects" => Attribute::Rects(Box::new(parse_value(value, parse_rectangle)?)),
+            "regular" => Attribute::Regular(parse_value(value, parse_bool)?),
+            "root" => Attribute::Root(value.to_owned()),
+            "rotate" => Attribute::Rotate(parse_number(value)?),
+            "samplepoints" => Attribute::SamplePoints(parse_number(value)?),
+            "shape" => Attribute::Shape(parse_value(value, Shape::from_keyword)?),
+            "shapefile" => Attribute::ShapeFile(value.to_owned()),
+            "sides" => Attribute::Sides(parse_number(value)?),
+            "size" => Attribute::Size(parse_value(value, parse_point)?),
+            "skew" => Attribute::Skew(parse_number(value)?),
+            "sortv" => Attribute::SortV(parse_number(value)?),
+            "splines" => Attribute::Splines(parse_value(value, EdgeRespresentation::from_keyword)?),
+            "style" => Attribute::Style(parse_value(value, parse_styles)?),
+            "tail_lp" => Attribute::TailLp(parse_value(value, parse_point)?),
+            "tailclip" => Attribute::TailClip(parse_value(value, parse_bool)?),
+            "taillabel" => Attribute::TailLabel(value.to_owned()),
+            "tailport" => Attribute::TailPort(Box::new(parse_value(value, parse_port_pos)?)),
+            "target" => Attribute::Target(value.to_owned()),
+            "tooltip" => Attribute::Tooltip(value.to_owned()),
+            "URL" => Attribute::Url(value.to_owned()),
+            "vertices" => Attribute::Vertices(parse_value(value, parse_points)?),
+            "viewport" => Attribute::ViewPort(Box::new(parse_value(value, parse_view_port)?)),
+            "weight" => Attribute::Weight(parse_number(value)?),
+            "width" => Attribute::Width(parse_number(value)?),
+            "xlabel" => Attribute::XLabel(value.to_owned()),
+            "z" => Attribute::Z(parse_number(value)?),
+            _ => return Err(AttributeParseError::AttributeNameNotFound),
+        })
+    }
+
+    /// The value Graphviz gives the attribute with the given DOT name on components of the given
+    /// kind when it isn't set, as documented in its attribute reference. `None` if the attribute
+    /// isn't read on them, or its default isn't a value: unset, like `pos`, or left to the
+    /// layout engine or output format, like `splines` and `fontpath`. Edges default to
+    /// `dir=forward`, as in directed graphs; undirected graphs default to `dir=none`.
+    pub fn default_value(name: &str, kind: &AttributeKind) -> Option<Attribute> {
+        let string = |value: &str| value.to_owned();
+        let color = |name: &str| {
+            Color::Name(ColorName {
+                scheme: ColorScheme::default(),
+                name: name.into(),
+            })
+        };
+        let center = || {
+            Box::new(PortPos {
+                port: None,
+                compass: Some(CompassPoint::Center),
+            })
+        };
+        let attribute = match name {
+            "arrowhead" => Attribute::ArrowHead(ArrowType::default()),
+            "arrowsize" => Attribute::ArrowSize(1.0),
+            "arrowtail" => Attribute::ArrowTail(ArrowType::default()),
+            "center" => Attribute::Center(false),
+            "charset" => Attribute::Charset(string("UTF-8")),
+            "color" => Attribute::Color(ColorAttribute::default()),
+            "colorscheme" => Attribute::ColorsSheme(String::new()),
+            "comment" => Attribute::Comment(String::new()),
+            "concentrate" => Attribute::Concentrate(false),
+            "constraint" => Attribute::Constraint(true),
+            "decorate" => Attribute::Decorate(false),
+            "dir" => Attribute::Dir(DirType::Forward),
+            "distortion" => Attribute::Distortion(0.0),
+            "dpi" => Attribute::Dpi(96.0),
+            // light grey inside nodes, black inside clusters and arrowheads
+            "fillcolor" => Attribute::FillColor(ColorAttribute::Color(match kind {
+                AttributeKind::Node => color("lightgrey"),
+                _ => Color::default(),
+            })),
+            "fixedsize" => Attribute::FixedSize(FixedSize::default()),
+            "fontcolor" => Attribute::FontColor(Color::default()),
+            "fontname" => Attribute::FontName(string(DEFAULT_FONT_NAME)),
+            "fontsize" => Attribute::FontSize(DEFAULT_FONT_SIZE),
+            "forcelabels" => Attribute::ForceLabels(true),
+            "headclip" => Attribute::HeadClip(true),
+            "headlabel" => Attribute::HeadLabel(String::new()),
+            "headport" => Attribute::HeadPort(center()),
+            "height" => Attribute::Height(0.5),
+            "href" => Attribute::Href(String::new()),
+            "image" => Attribute::Image(String::new()),
+            "imagepath" => Attribute::ImagePath(String::new()),
+            "imagepos" => Attribute::ImagePos(string("mc")),
+            "imagescale" => Attribute::ImageScale(ImageScale::default()),
+            // nodes are labelled with their IDs
+            "label" => Attribute::Label(match kind {
+                AttributeKind::Node => string("\\N"),
+                _ => String::new(),
+            }),
+            "labelangle" => Attribute::LabelAngle(-25.0),
+            "labeldistance" => Attribute::LabelDistance(1.0),
+            "labelfloat" => Attribute::LabelFloat(false),
+            "labelfontcolor" => Attribute::LabelFontColor(Color::default()),
+            "labelfontname" => Attribute::LabelFontName(string(DEFAULT_FONT_NAME)),
+            "labelfontsize" => Attribute::LabelFontSize(DEFAULT_FONT_SIZE),
+            "labeljust" => Attribute::LabelJust(TextJustification::default()),
+            // centered in nodes, at the bottom of graphs
+            "labelloc" => Attribute::LabelLoc(match kind {
+                AttributeKind::Node => TextLocation::Center,
+                _ => TextLocation::Bottom,
+            }),
+            "landscape" => Attribute::Landscape(false),
+            "layer" => Attribute::Layer(vec![]),
+            "layerlistsep" => Attribute::LayerListSep(string(",")),
+            "layers" => Attribute::Layers(vec![]),
+            "layerselect" => Attribute::LayerSelect(vec![]),
+            "layersep" => Attribute::LayerSep(string(":\t ")),
+            "len" => Attribute::Len(1.0),
+            "mindist" => Attribute::MinDist(1.0),
+            "minlen" => Attribute::MinLen(1),
+            "nodesep" => Attribute::NodeSep(0.25),
+            "nojustify" => Attribute::NoJustify(false),
+            "orientation" => Attribute::Orientation(Orientation::Rotation(0.0)),
+            "outputorder" => Attribute::OutputOrder(OutputMode::default()),
+            "overlap" => Attribute::Overlap(Overlap::default()),
+            "pack" => Attribute::Pack(Pack::False),
+            "packmode" => Attribute::PackMode(Box::default()),
+            "pad" => Attribute::Pad(Point {
+                x: 0.0555,
+                y: 0.0555,
+            }),
+            "pagedir" => Attribute::PageDir(PageDir {
+                primary: TraversalDir::Vertical(VerticalDir::BottomToTop),
+                secondary: TraversalDir::Horizontal(HorizontalDir::LeftToRight),
+            }),
+            "pencolor" => Attribute::PenColor(Color::default()),
+            "penwidth" => Attribute::PenWidth(1.0),
+            "peripheries" => Attribute::Peripheries(1),
+            "pin" => Attribute::Pin(false),
+            "quantum" => Attribute::Quantum(0.0),
+            "rankdir" => Attribute::RankDir(RankDir::default()),
+            "ranksep" => Attribute::RankSep(Box::new(RankSeparation {
+                values: vec![0.5],
+                equally: false,
+            })),
+            "regular" => Attribute::Regular(false),
+            "rotate" => Attribute::Rotate(0),
+            "samplepoints" => Attribute::SamplePoints(8),
+            "shape" => Attribute::Shape(Shape::default()),
+            "shapefile" => Attribute::ShapeFile(String::new()),
+            "sides" => Attribute::Sides(4),
+            "skew" => Attribute::Skew(0.0),
+            "sortv" => Attribute::SortV(0),
+            "style" => Attribute::Style(vec![]),
+            "tailclip" => Attribute::TailClip(true),
+            "taillabel" => Attribute::TailLabel(String::new()),
+            "tailport" => Attribute::TailPort(center()),
+            "target" => Attribute::Target(String::new()),
+            "tooltip" => Attribute::Tooltip(String::new()),
+            "URL" => Attribute::Url(String::new()),
+            "weight" => Attribute::Weight(1.0),
+            "width" => Attribute::Width(0.75),
+            "xlabel" => Attribute::XLabel(String::new()),
+            "z" => Attribute::Z(0.0),
+            _ => return None,
+        };
+        Some(attribute).filter(|attribute| attribute.applies_to(kind))
+    }
+
+    /// The DOT spelling of this attribute's value, unquoted, which [`from_pair`] parses back to
+    /// the same attribute.
+    ///
+    /// [`from_pair`]: Attribute::from_pair
+    pub fn value_string(&self) -> String {
+        match self {
+            Attribute::Background(value)
+            | Attribute::Charset(value)
+            | Attribute::ColorsSheme(value)
+            | Attribute::Comment(value)
+            | Attribute::FontName(value)
+            | Attribute::FontPath(value)
+            | Attribute::HeadLabel(value)
+            | Attribute::Href(value)
+            | Attribute::Image(value)
+            | Attribute::ImagePath(value)
+            | Attribute::ImagePos(value)
+            | Attribute::Label(value)
+            | Attribute::LabelFontName(value)
+            | Attribute::LayerListSep(value)
+            | Attribute::LayerSep(value)
+            | Attribute::Layout(value)
+            | Attribute::Root(value)
+            | Attribute::ShapeFile(value)
+            | Attribute::TailLabel(value)
+            | Attribute::Target(value)
+            | Attribute::Tooltip(value)
+            | Attribute::Url(value)
+            | Attribute::XLabel(value) => value.clone(),
+            Attribute::ArrowHead(value) | Attribute::ArrowTail(value) => value.to_string(),
+            Attribute::ArrowSize(value)
+            | Attribute::Distortion(value)
+            | Attribute::Dpi(value)
+            | Attribute::FontSize(value)
+            | Attribute::Height(value)
+            | Attribute::LabelAngle(value)
+            | Attribute::LabelDistance(value)
+            | Attribute::LabelFontSize(value)
+            | Attribute::Len(value)
+            | Attribute::MinDist(value)
+            | Attribute::NodeSep(value)
+            | Attribute::PenWidth(value)
+            | Attribute::Quantum(value)
+            | Attribute::Skew(value)
+            | Attribute::Weight(value)
+            | Attribute::Width(value)
+            | Attribute::Z(value) => value.to_string(),
+            Attribute::Bb(value) | Attribute::Rects(value) => value.to_string(),
+            Attribute::BgColor(value) | Attribute::Color(value) | Attribute::FillColor(value) => {
+                value.to_string()
+            }
+            Attribute::Center(value)
+            | Attribute::Concentrate(value)
+            | Attribute::Constraint(value)
+            | Attribute::Decorate(value)
+            | Attribute::ForceLabels(value)
+            | Attribute::HeadClip(value)
+            | Attribute::LabelFloat(value)
+            | Attribute::Landscape(value)
+            | Attribute::NoJustify(value)
+            | Attribute::Pin(value)
+            | Attribute::Regular(value)
+            | Attribute::TailClip(value) => value.to_string(),
+            Attribute::Dir(value) => value.as_str().to_owned(),
+            Attribute::FixedSize(value) => value.as_str().to_owned(),
+            Attribute::FontColor(value)
+            | Attribute::LabelFontColor(value)
+            | Attribute::PenColor(value) => value.to_string(),
+            Attribute::GradientAngle(value)
+            | Attribute::MaxIter(value)
+            | Attribute::MinLen(value)
+            | Attribute::Peripheries(value)
+            | Attribute::Rotate(value)
+            | Attribute::SamplePoints(value)
+            | Attribute::Sides(value)
+            | Attribute::SortV(value) => value.to_string(),
+            Attribute::ImageScale(value) => value.as_str().to_owned(),
+            Attribute::LabelJust(value) => value.as_str().to_owned(),
+            Attribute::LabelLoc(value) => value.as_str().to_owned(),
+            Attribute::Layer(value) | Attribute::Layers(value) | Attribute::LayerSelect(value) => {
+                value.join(":")
+            }
+            Attribute::Margin(value)
+            | Attribute::Pad(value)
+            | Attribute::Page(value)
+            | Attribute::Size(value)
+            | Attribute::TailLp(value) => value.to_string(),
+            Attribute::Orientation(value) => value.to_string(),
+            Attribute::OutputOrder(value) => value.as_str().to_owned(),
+            Attribute::Overlap(value) => value.as_str().to_owned(),
+            Attribute::Rank(value) => value.as_str().to_owned(),
+            Attribute::RankDir(value) => value.as_str().to_owned(),
+            Attribute::Pack(value) => value.to_string(),
+            Attribute::PackMode(value) => value.to_string(),
+            Attribute::PageDir(value) => value.to_string(),
+            Attribute::Pos(value) => value.to_string(),
+            Attribute::RankSep(value) => value.to_string(),
+            Attribute::Ratio(value) => value.to_string(),
+            Attribute::Shape(value) => value.as_str().to_owned(),
+            Attribute::Splines(value) => value.as_str().to_owned(),
+            Attribute::Style(value) => join(value, ",", Style::as_str),
+            Attribute::Vertices(value) => join(value, " ", Point::to_string),
+            Attribute::ViewPort(value) => value.to_string(),
+            Attribute::HeadPort(value) | Attribute::TailPort(value) => value.to_string(),
+        }
+    }
+}
+
+/// Displays the keyword-valued enums as their DOT spelling, which is also what
+/// [`Attribute::value_string`] writes for them.
+macro_rules! display_as_str {
+    ($($value:ty),* $(,)?) => {
+        $(
+            impl fmt::Display for $value {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        )*
+    };
+}
+
+display_as_str!(
+    ArrowShape,
+    ArrowSide,
+    ClusterMode,
+    DirType,
+    CompassPoint,
+    ImageScale,
+    TextJustification,
+    TextLocation,
+    FixedSize,
+    OutputMode,
+    RankType,
+    RankDir,
+    Overlap,
+    PackFlag,
+    VerticalDir,
+    HorizontalDir,
+    Shape,
+    EdgeRespresentation,
+    Style
+);
+
+fn join<T, S: AsRef<str>>(values: &[T], separator: &str, f: impl Fn(&T) -> S) -> String {
+    values
+        .iter()
+        .map(|value| f(value).as_ref().to_owned())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// The shape of an arrowhead: one of Graphviz's named arrows, or up to four shapes stacked along
+/// the edge from the node out, each of them open or closed and whole or only its left or right
+/// half, such as `lteeoldiamond`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArrowType {
     Normal,
     Inv,
@@ -124,34 +753,306 @@ pub enum ArrowType {
     Open,
     HalfOpen,
     Vee,
+    Compound(Vec<Arrow>),
+}
+
+impl Default for ArrowType {
+    /// `normal`, the default arrowhead and arrowtail.
+    fn default() -> Self {
+        ArrowType::Normal
+    }
+}
+
+impl fmt::Display for ArrowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrowType::Normal => write!(f, "normal"),
+            ArrowType::Inv => write!(f, "inv"),
+            ArrowType::Dot => write!(f, "dot"),
+            ArrowType::InvDot => write!(f, "invdot"),
+            ArrowType::ODot => write!(f, "odot"),
+            ArrowType::InvODot => write!(f, "invodot"),
+            ArrowType::None => write!(f, "none"),
+            ArrowType::Tee => write!(f, "tee"),
+            ArrowType::Empty => write!(f, "empty"),
+            ArrowType::InvEmpty => write!(f, "invempty"),
+            ArrowType::Diamond => write!(f, "diamond"),
+            ArrowType::ODiamond => write!(f, "odiamond"),
+            ArrowType::EDiamond => write!(f, "ediamond"),
+            ArrowType::Crow => write!(f, "crow"),
+            ArrowType::Box => write!(f, "box"),
+            ArrowType::OBox => write!(f, "obox"),
+            ArrowType::Open => write!(f, "open"),
+            ArrowType::HalfOpen => write!(f, "halfopen"),
+            ArrowType::Vee => write!(f, "vee"),
+            ArrowType::Compound(arrows) => write!(f, "{}", join(arrows, "", Arrow::to_string)),
+        }
+    }
+}
+
+impl ArrowType {
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "normal" => Some(ArrowType::Normal),
+            "inv" => Some(ArrowType::Inv),
+            "dot" => Some(ArrowType::Dot),
+            "invdot" => Some(ArrowType::InvDot),
+            "odot" => Some(ArrowType::ODot),
+            "invodot" => Some(ArrowType::InvODot),
+            "none" => Some(ArrowType::None),
+            "tee" => Some(ArrowType::Tee),
+            "empty" => Some(ArrowType::Empty),
+            "invempty" => Some(ArrowType::InvEmpty),
+            "diamond" => Some(ArrowType::Diamond),
+            "odiamond" => Some(ArrowType::ODiamond),
+            "ediamond" => Some(ArrowType::EDiamond),
+            "crow" => Some(ArrowType::Crow),
+            "box" => Some(ArrowType::Box),
+            "obox" => Some(ArrowType::OBox),
+            "open" => Some(ArrowType::Open),
+            "halfopen" => Some(ArrowType::HalfOpen),
+            "vee" => Some(ArrowType::Vee),
+            s => parse_arrows(s).map(ArrowType::Compound),
+        }
+    }
+
+    /// The shapes of the arrowhead, from the node out.
+    pub fn arrows(&self) -> Vec<Arrow> {
+        let arrow = |shape, open, side| Arrow { shape, open, side };
+        let closed = |shape| vec![arrow(shape, false, None)];
+        let open = |shape| vec![arrow(shape, true, None)];
+        match self {
+            ArrowType::Normal => closed(ArrowShape::Normal),
+            ArrowType::Inv => closed(ArrowShape::Inv),
+            ArrowType::Dot => closed(ArrowShape::Dot),
+            ArrowType::InvDot => vec![
+                arrow(ArrowShape::Inv, false, None),
+                arrow(ArrowShape::Dot, false, None),
+            ],
+            ArrowType::ODot => open(ArrowShape::Dot),
+            ArrowType::InvODot => vec![
+                arrow(ArrowShape::Inv, false, None),
+                arrow(ArrowShape::Dot, true, None),
+            ],
+            ArrowType::None => closed(ArrowShape::None),
+            ArrowType::Tee => closed(ArrowShape::Tee),
+            ArrowType::Empty => open(ArrowShape::Normal),
+            ArrowType::InvEmpty => open(ArrowShape::Inv),
+            ArrowType::Diamond => closed(ArrowShape::Diamond),
+            ArrowType::ODiamond | ArrowType::EDiamond => open(ArrowShape::Diamond),
+            ArrowType::Crow => closed(ArrowShape::Crow),
+            ArrowType::Box => closed(ArrowShape::Box),
+            ArrowType::OBox => open(ArrowShape::Box),
+            ArrowType::Open | ArrowType::Vee => closed(ArrowShape::Vee),
+            ArrowType::HalfOpen => vec![arrow(ArrowShape::Vee, false, Some(ArrowSide::Left))],
+            ArrowType::Compound(arrows) => arrows.clone(),
+        }
+    }
+}
+
+impl core::str::FromStr for ArrowType {
+    type Err = AttributeParseError;
+
+    /// Parses an `arrowhead` or `arrowtail` value, such as `normal` or `lteeoldiamond`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_value(value, ArrowType::from_keyword)
+    }
+}
+
+/// One shape of an arrowhead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrow {
+    pub shape: ArrowShape,
+    /// Whether the shape is drawn as an outline only, written with an `o` before it.
+    pub open: bool,
+    /// The half of the shape drawn, written with an `l` or `r` before it; both if `None`.
+    pub side: Option<ArrowSide>,
+}
+
+impl fmt::Display for Arrow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.open {
+            write!(f, "o")?;
+        }
+        if let Some(side) = &self.side {
+            write!(f, "{}", side)?;
+        }
+        write!(f, "{}", self.shape.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrowShape {
+    Box,
+    Crow,
+    Curve,
+    ICurve,
+    Diamond,
+    Dot,
+    Inv,
+    /// No shape, leaving a gap between the shapes around it.
+    None,
+    Normal,
+    Tee,
+    Vee,
+}
+
+impl ArrowShape {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArrowShape::Box => "box",
+            ArrowShape::Crow => "crow",
+            ArrowShape::Curve => "curve",
+            ArrowShape::ICurve => "icurve",
+            ArrowShape::Diamond => "diamond",
+            ArrowShape::Dot => "dot",
+            ArrowShape::Inv => "inv",
+            ArrowShape::None => "none",
+            ArrowShape::Normal => "normal",
+            ArrowShape::Tee => "tee",
+            ArrowShape::Vee => "vee",
+        }
+    }
+}
+
+/// A side of an edge, looking along it towards the node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrowSide {
+    Left,
+    Right,
+}
+
+impl ArrowSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArrowSide::Left => "l",
+            ArrowSide::Right => "r",
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rectangle {
-    lower_left: Point,
-    upper_right: Point,
+    pub(crate) lower_left: Point,
+    pub(crate) upper_right: Point,
 }
 
-#[derive(Debug)]
+impl Rectangle {
+    pub fn new(lower_left: Point, upper_right: Point) -> Self {
+        Rectangle {
+            lower_left,
+            upper_right,
+        }
+    }
+
+    pub fn lower_left(&self) -> &Point {
+        &self.lower_left
+    }
+
+    pub fn upper_right(&self) -> &Point {
+        &self.upper_right
+    }
+}
+
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.lower_left, self.upper_right)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Point {
-    x: f64,
-    y: f64,
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ColorAttribute {
     Color(Color),
-    ColorList(Vec<Color>),
+    ColorList(Box<[WeightedColor]>),
+}
+
+impl Default for ColorAttribute {
+    /// Black, the default `color` of edges and node and cluster outlines.
+    fn default() -> Self {
+        ColorAttribute::Color(Color::default())
+    }
+}
+
+impl fmt::Display for ColorAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorAttribute::Color(color) => write!(f, "{}", color),
+            ColorAttribute::ColorList(colors) => {
+                write!(f, "{}", join(colors, ":", WeightedColor::to_string))
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
+/// A color of a color list, with the fraction of a striped or wedged fill it takes, if it says,
+/// as in `red;0.3:blue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedColor {
+    pub color: Color,
+    pub weight: Option<Double>,
+}
+
+impl fmt::Display for WeightedColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.color)?;
+        if let Some(weight) = self.weight {
+            write!(f, ";{}", weight)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ClusterMode {
     Local,
     Global,
     None,
 }
 
-#[derive(Debug)]
+impl Default for ClusterMode {
+    /// `local`, under which subgraphs named `cluster*` are drawn as clusters.
+    fn default() -> Self {
+        ClusterMode::Local
+    }
+}
+
+impl ClusterMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClusterMode::Local => "local",
+            ClusterMode::Global => "global",
+            ClusterMode::None => "none",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum DirType {
     Forward,
     Back,
@@ -159,7 +1060,95 @@ pub enum DirType {
     None,
 }
 
-#[derive(Debug)]
+impl DirType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DirType::Forward => "forward",
+            DirType::Back => "back",
+            DirType::Both => "both",
+            DirType::None => "none",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "forward" => Some(DirType::Forward),
+            "back" => Some(DirType::Back),
+            "both" => Some(DirType::Both),
+            "none" => Some(DirType::None),
+            _ => None,
+        }
+    }
+}
+
+/// Where an edge meets a node: at the field or cell of a record or HTML label named `port`, if
+/// any, and at the side or corner `compass` of it, or of the node without a port.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortPos {
+    pub port: Option<String>,
+    pub compass: Option<CompassPoint>,
+}
+
+impl fmt::Display for PortPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.port, &self.compass) {
+            (Some(port), Some(compass)) => write!(f, "{}:{}", port, compass.as_str()),
+            (Some(port), None) => write!(f, "{}", port),
+            (None, Some(compass)) => write!(f, "{}", compass.as_str()),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompassPoint {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+    Center,
+    /// `_`, anywhere on the boundary.
+    Any,
+}
+
+impl CompassPoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompassPoint::North => "n",
+            CompassPoint::NorthEast => "ne",
+            CompassPoint::East => "e",
+            CompassPoint::SouthEast => "se",
+            CompassPoint::South => "s",
+            CompassPoint::SouthWest => "sw",
+            CompassPoint::West => "w",
+            CompassPoint::NorthWest => "nw",
+            CompassPoint::Center => "c",
+            CompassPoint::Any => "_",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "n" => Some(CompassPoint::North),
+            "ne" => Some(CompassPoint::NorthEast),
+            "e" => Some(CompassPoint::East),
+            "se" => Some(CompassPoint::SouthEast),
+            "s" => Some(CompassPoint::South),
+            "sw" => Some(CompassPoint::SouthWest),
+            "w" => Some(CompassPoint::West),
+            "nw" => Some(CompassPoint::NorthWest),
+            "c" => Some(CompassPoint::Center),
+            "_" => Some(CompassPoint::Any),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ImageScale {
     False,
     True,
@@ -168,50 +1157,334 @@ pub enum ImageScale {
     Both,
 }
 
-#[derive(Debug)]
+impl Default for ImageScale {
+    /// `false`, keeping images at their natural size.
+    fn default() -> Self {
+        ImageScale::False
+    }
+}
+
+impl ImageScale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageScale::False => "false",
+            ImageScale::True => "true",
+            ImageScale::Width => "width",
+            ImageScale::Height => "height",
+            ImageScale::Both => "both",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "false" => Some(ImageScale::False),
+            "true" => Some(ImageScale::True),
+            "width" => Some(ImageScale::Width),
+            "height" => Some(ImageScale::Height),
+            "both" => Some(ImageScale::Both),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TextJustification {
     Left,
     Right,
     Center,
 }
 
-#[derive(Debug)]
+impl Default for TextJustification {
+    /// `c`, centering labels.
+    fn default() -> Self {
+        TextJustification::Center
+    }
+}
+
+impl TextJustification {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextJustification::Left => "l",
+            TextJustification::Right => "r",
+            TextJustification::Center => "c",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "l" => Some(TextJustification::Left),
+            "r" => Some(TextJustification::Right),
+            "c" => Some(TextJustification::Center),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TextLocation {
     Top,
     Bottom,
     Center,
 }
 
-#[derive(Debug)]
+impl TextLocation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextLocation::Top => "t",
+            TextLocation::Bottom => "b",
+            TextLocation::Center => "c",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "t" => Some(TextLocation::Top),
+            "b" => Some(TextLocation::Bottom),
+            "c" => Some(TextLocation::Center),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Orientation {
     Landscape,
     Rotation(Double),
 }
 
-#[derive(Debug)]
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Orientation::Landscape => write!(f, "landscape"),
+            Orientation::Rotation(angle) => write!(f, "{}", angle),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Pack {
     True,
     False,
     Value(Int),
 }
 
+impl fmt::Display for Pack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pack::True => write!(f, "true"),
+            Pack::False => write!(f, "false"),
+            Pack::Value(margin) => write!(f, "{}", margin),
+        }
+    }
+}
+
 type LabelString = String;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FixedSize {
     True,
     False,
     Shape,
 }
 
-#[derive(Debug)]
+impl Default for FixedSize {
+    /// `false`, growing nodes to fit their labels.
+    fn default() -> Self {
+        FixedSize::False
+    }
+}
+
+impl FixedSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FixedSize::False => "false",
+            FixedSize::True => "true",
+            FixedSize::Shape => "shape",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "false" => Some(FixedSize::False),
+            "true" => Some(FixedSize::True),
+            "shape" => Some(FixedSize::Shape),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum OutputMode {
     BreadthFirst,
     NodesFirst,
     EdgesFirst,
 }
 
-#[derive(Debug)]
+impl Default for OutputMode {
+    /// `breadthfirst`, the default `outputorder`.
+    fn default() -> Self {
+        OutputMode::BreadthFirst
+    }
+}
+
+impl OutputMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputMode::BreadthFirst => "breadthfirst",
+            OutputMode::NodesFirst => "nodesfirst",
+            OutputMode::EdgesFirst => "edgesfirst",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "breadthfirst" => Some(OutputMode::BreadthFirst),
+            "nodesfirst" => Some(OutputMode::NodesFirst),
+            "edgesfirst" => Some(OutputMode::EdgesFirst),
+            _ => None,
+        }
+    }
+}
+
+/// Constraints on the ranks of nodes in layered layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankType {
+    /// All on the same rank.
+    Same,
+    /// On the minimum rank, along with any other nodes.
+    Min,
+    /// Alone on the minimum rank.
+    Source,
+    /// On the maximum rank, along with any other nodes.
+    Max,
+    /// Alone on the maximum rank.
+    Sink,
+}
+
+impl RankType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankType::Same => "same",
+            RankType::Min => "min",
+            RankType::Source => "source",
+            RankType::Max => "max",
+            RankType::Sink => "sink",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "same" => Some(RankType::Same),
+            "min" => Some(RankType::Min),
+            "source" => Some(RankType::Source),
+            "max" => Some(RankType::Max),
+            "sink" => Some(RankType::Sink),
+            _ => None,
+        }
+    }
+}
+
+/// The direction of edges between ranks in layered layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankDir {
+    TopToBottom,
+    LeftToRight,
+    BottomToTop,
+    RightToLeft,
+}
+
+impl Default for RankDir {
+    /// `TB`, ranking from top to bottom.
+    fn default() -> Self {
+        RankDir::TopToBottom
+    }
+}
+
+impl RankDir {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankDir::TopToBottom => "TB",
+            RankDir::LeftToRight => "LR",
+            RankDir::BottomToTop => "BT",
+            RankDir::RightToLeft => "RL",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "TB" => Some(RankDir::TopToBottom),
+            "LR" => Some(RankDir::LeftToRight),
+            "BT" => Some(RankDir::BottomToTop),
+            "RL" => Some(RankDir::RightToLeft),
+            _ => None,
+        }
+    }
+}
+
+impl core::str::FromStr for RankDir {
+    type Err = AttributeParseError;
+
+    /// Parses a `rankdir` value: `TB`, `LR`, `BT` or `RL`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_value(value, RankDir::from_keyword)
+    }
+}
+
+/// How layouts treat overlapping nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Overlaps are left as they are.
+    Retain,
+    /// Overlaps are removed, moving nodes as little as possible.
+    Remove,
+    /// The layout is scaled up uniformly until no nodes overlap.
+    Scale,
+    /// The layout is scaled up separately along each axis until no nodes overlap.
+    ScaleXY,
+    /// Overlapping neighbors are moved apart while keeping the distances between other
+    /// neighbors, like Graphviz's `prism`.
+    Prism,
+    /// Overlapping nodes are moved into the middle of their Voronoi cells.
+    Voronoi,
+}
+
+impl Default for Overlap {
+    /// `true`, leaving overlaps as they are.
+    fn default() -> Self {
+        Overlap::Retain
+    }
+}
+
+impl Overlap {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Overlap::Retain => "true",
+            Overlap::Remove => "false",
+            Overlap::Scale => "scale",
+            Overlap::ScaleXY => "scalexy",
+            Overlap::Prism => "prism",
+            Overlap::Voronoi => "voronoi",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "scale" => Some(Overlap::Scale),
+            "scalexy" => Some(Overlap::ScaleXY),
+            "prism" => Some(Overlap::Prism),
+            "voronoi" => Some(Overlap::Voronoi),
+            s => parse_bool(s).map(|retain| {
+                if retain {
+                    Overlap::Retain
+                } else {
+                    Overlap::Remove
+                }
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PackMode {
     Node,
     Clust,
@@ -219,7 +1492,40 @@ pub enum PackMode {
     Array { size: Int, flags: Vec<PackFlag> },
 }
 
-#[derive(Debug)]
+impl Default for PackMode {
+    /// `node`, packing components by the nodes and edges they are drawn with.
+    fn default() -> Self {
+        PackMode::Node
+    }
+}
+
+impl fmt::Display for PackMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackMode::Node => write!(f, "node"),
+            PackMode::Clust => write!(f, "clust"),
+            PackMode::Graph => write!(f, "graph"),
+            PackMode::Array { size, flags } => {
+                write!(f, "array")?;
+                if !flags.is_empty() {
+                    write!(f, "_{}", join(flags, "", PackFlag::as_str))?;
+                }
+                write!(f, "{}", size)
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for PackMode {
+    type Err = AttributeParseError;
+
+    /// Parses a `packmode` value, such as `graph` or `array_c4`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_value(value, parse_pack_mode)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PackFlag {
     ColumnMajor,
     Top,
@@ -229,37 +1535,154 @@ pub enum PackFlag {
     User,
 }
 
-#[derive(Debug)]
+impl PackFlag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PackFlag::ColumnMajor => "c",
+            PackFlag::Top => "t",
+            PackFlag::Bottom => "b",
+            PackFlag::Left => "l",
+            PackFlag::Right => "r",
+            PackFlag::User => "u",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "c" => Some(PackFlag::ColumnMajor),
+            "t" => Some(PackFlag::Top),
+            "b" => Some(PackFlag::Bottom),
+            "l" => Some(PackFlag::Left),
+            "r" => Some(PackFlag::Right),
+            "u" => Some(PackFlag::User),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PageDir {
-    primary: TraversalDir,
-    secondary: TraversalDir,
+    pub(crate) primary: TraversalDir,
+    pub(crate) secondary: TraversalDir,
+}
+
+impl PageDir {
+    /// The direction pages are traversed in first, such as `B` in `BL`.
+    pub fn primary(&self) -> &TraversalDir {
+        &self.primary
+    }
+
+    /// The direction rows or columns of pages are traversed in, such as `L` in `BL`.
+    pub fn secondary(&self) -> &TraversalDir {
+        &self.secondary
+    }
+}
+
+impl fmt::Display for PageDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.primary, self.secondary)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TraversalDir {
     Vertical(VerticalDir),
     Horizontal(HorizontalDir),
 }
 
-#[derive(Debug)]
+impl fmt::Display for TraversalDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraversalDir::Vertical(dir) => write!(f, "{}", dir.as_str()),
+            TraversalDir::Horizontal(dir) => write!(f, "{}", dir.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum VerticalDir {
     BottomToTop,
     TopToBottom,
 }
 
-#[derive(Debug)]
+impl VerticalDir {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerticalDir::BottomToTop => "B",
+            VerticalDir::TopToBottom => "T",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "B" => Some(VerticalDir::BottomToTop),
+            "T" => Some(VerticalDir::TopToBottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum HorizontalDir {
     LeftToRight,
     RightToLeft,
 }
 
-#[derive(Debug)]
+impl HorizontalDir {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HorizontalDir::LeftToRight => "L",
+            HorizontalDir::RightToLeft => "R",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "L" => Some(HorizontalDir::LeftToRight),
+            "R" => Some(HorizontalDir::RightToLeft),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Position {
     Point(Point),
+    /// A point the node is pinned to, written with a trailing `!`.
+    Pinned(Point),
     Spline(Vec<Point>),
 }
 
-#[derive(Debug)]
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::Point(point) => write!(f, "{}", point),
+            Position::Pinned(point) => write!(f, "{}!", point),
+            Position::Spline(points) => write!(f, "{}", join(points, " ", Point::to_string)),
+        }
+    }
+}
+
+/// Separation between ranks in inches: one value for all ranks, or, for radial layouts, one per
+/// ring, the last repeating. `equally` asks layered layouts for equally spaced ranks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankSeparation {
+    pub values: Vec<Double>,
+    pub equally: bool,
+}
+
+impl fmt::Display for RankSeparation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", join(&self.values, ":", Double::to_string))?;
+        match (self.equally, self.values.is_empty()) {
+            (true, true) => write!(f, "equally"),
+            (true, false) => write!(f, " equally"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Ratio {
     Numeric(Double),
     Fill,
@@ -268,8 +1691,20 @@ pub enum Ratio {
     Auto,
 }
 
-// only polygon shapes currently supported
-#[derive(Debug)]
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ratio::Numeric(ratio) => write!(f, "{}", ratio),
+            Ratio::Fill => write!(f, "fill"),
+            Ratio::Compress => write!(f, "compress"),
+            Ratio::Expand => write!(f, "expand"),
+            Ratio::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+// polygon shapes and records; user-defined shapes are not supported
+#[derive(Debug, Clone, PartialEq)]
 pub enum Shape {
     Box,
     Polygon,
@@ -330,20 +1765,162 @@ pub enum Shape {
     RArrow,
     LArrow,
     LPromoter,
+    Record,
+    MRecord,
 }
 
-fn shape_parser(input: &str) -> ParseResult<&str, Shape> {
-    ws(alt((
-        tag("box").map(|_| Shape::Box),
-        tag("polygon").map(|_| Shape::Polygon),
-        tag("ellipse").map(|_| Shape::Ellipse),
-        tag("oval").map(|_| Shape::Oval),
-        tag("circle").map(|_| Shape::Circle),
-        tag("point").map(|_| Shape::Point),
-    )))(input)
+impl Default for Shape {
+    /// `ellipse`, the default shape of nodes.
+    fn default() -> Self {
+        Shape::Ellipse
+    }
 }
 
-#[derive(Debug)]
+impl Shape {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Shape::Box => "box",
+            Shape::Polygon => "polygon",
+            Shape::Ellipse => "ellipse",
+            Shape::Oval => "oval",
+            Shape::Circle => "circle",
+            Shape::Point => "point",
+            Shape::Egg => "egg",
+            Shape::Triangle => "triangle",
+            Shape::Plaintext => "plaintext",
+            Shape::Plain => "plain",
+            Shape::Diamond => "diamond",
+            Shape::Trapezium => "trapezium",
+            Shape::Parallelogram => "parallelogram",
+            Shape::House => "house",
+            Shape::Pentagon => "pentagon",
+            Shape::Hexagon => "hexagon",
+            Shape::Septagon => "septagon",
+            Shape::Octagon => "octagon",
+            Shape::DoubleCircle => "doublecircle",
+            Shape::DoubleOctagon => "doubleoctagon",
+            Shape::TripleOctagon => "tripleoctagon",
+            Shape::InvTriangle => "invtriangle",
+            Shape::InvTrapezium => "invtrapezium",
+            Shape::InvHouse => "invhouse",
+            Shape::MDiamond => "Mdiamond",
+            Shape::MSquare => "Msquare",
+            Shape::MCircle => "Mcircle",
+            Shape::Rect => "rect",
+            Shape::Rectangle => "rectangle",
+            Shape::Square => "square",
+            Shape::Star => "star",
+            Shape::None => "none",
+            Shape::Underline => "underline",
+            Shape::Cylinder => "cylinder",
+            Shape::Note => "note",
+            Shape::Tab => "tab",
+            Shape::Folder => "folder",
+            Shape::Box3d => "box3d",
+            Shape::Component => "component",
+            Shape::Promoter => "promoter",
+            Shape::Cds => "cds",
+            Shape::Terminator => "terminator",
+            Shape::Utr => "utr",
+            Shape::PrimerSite => "primersite",
+            Shape::RestrictionSite => "restrictionsite",
+            Shape::FivePOverhang => "fivepoverhang",
+            Shape::ThreePOverhang => "threepoverhang",
+            Shape::NoOverhang => "noverhang",
+            Shape::Assembly => "assembly",
+            Shape::Signature => "signature",
+            Shape::Insulator => "insulator",
+            Shape::Ribosite => "ribosite",
+            Shape::RnaStab => "rnastab",
+            Shape::ProteaseSite => "proteasesite",
+            Shape::ProteinStab => "proteinstab",
+            Shape::RPromoter => "rpromoter",
+            Shape::RArrow => "rarrow",
+            Shape::LArrow => "larrow",
+            Shape::LPromoter => "lpromoter",
+            Shape::Record => "record",
+            Shape::MRecord => "Mrecord",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "box" => Some(Shape::Box),
+            "polygon" => Some(Shape::Polygon),
+            "ellipse" => Some(Shape::Ellipse),
+            "oval" => Some(Shape::Oval),
+            "circle" => Some(Shape::Circle),
+            "point" => Some(Shape::Point),
+            "egg" => Some(Shape::Egg),
+            "triangle" => Some(Shape::Triangle),
+            "plaintext" => Some(Shape::Plaintext),
+            "plain" => Some(Shape::Plain),
+            "diamond" => Some(Shape::Diamond),
+            "trapezium" => Some(Shape::Trapezium),
+            "parallelogram" => Some(Shape::Parallelogram),
+            "house" => Some(Shape::House),
+            "pentagon" => Some(Shape::Pentagon),
+            "hexagon" => Some(Shape::Hexagon),
+            "septagon" => Some(Shape::Septagon),
+            "octagon" => Some(Shape::Octagon),
+            "doublecircle" => Some(Shape::DoubleCircle),
+            "doubleoctagon" => Some(Shape::DoubleOctagon),
+            "tripleoctagon" => Some(Shape::TripleOctagon),
+            "invtriangle" => Some(Shape::InvTriangle),
+            "invtrapezium" => Some(Shape::InvTrapezium),
+            "invhouse" => Some(Shape::InvHouse),
+            "Mdiamond" => Some(Shape::MDiamond),
+            "Msquare" => Some(Shape::MSquare),
+            "Mcircle" => Some(Shape::MCircle),
+            "rect" => Some(Shape::Rect),
+            "rectangle" => Some(Shape::Rectangle),
+            "square" => Some(Shape::Square),
+            "star" => Some(Shape::Star),
+            "none" => Some(Shape::None),
+            "underline" => Some(Shape::Underline),
+            "cylinder" => Some(Shape::Cylinder),
+            "note" => Some(Shape::Note),
+            "tab" => Some(Shape::Tab),
+            "folder" => Some(Shape::Folder),
+            "box3d" => Some(Shape::Box3d),
+            "component" => Some(Shape::Component),
+            "promoter" => Some(Shape::Promoter),
+            "cds" => Some(Shape::Cds),
+            "terminator" => Some(Shape::Terminator),
+            "utr" => Some(Shape::Utr),
+            "primersite" => Some(Shape::PrimerSite),
+            "restrictionsite" => Some(Shape::RestrictionSite),
+            "fivepoverhang" => Some(Shape::FivePOverhang),
+            "threepoverhang" => Some(Shape::ThreePOverhang),
+            "noverhang" => Some(Shape::NoOverhang),
+            "assembly" => Some(Shape::Assembly),
+            "signature" => Some(Shape::Signature),
+            "insulator" => Some(Shape::Insulator),
+            "ribosite" => Some(Shape::Ribosite),
+            "rnastab" => Some(Shape::RnaStab),
+            "proteasesite" => Some(Shape::ProteaseSite),
+            "proteinstab" => Some(Shape::ProteinStab),
+            "rpromoter" => Some(Shape::RPromoter),
+            "rarrow" => Some(Shape::RArrow),
+            "larrow" => Some(Shape::LArrow),
+            "lpromoter" => Some(Shape::LPromoter),
+            "record" => Some(Shape::Record),
+            "Mrecord" => Some(Shape::MRecord),
+            _ => None,
+        }
+    }
+}
+
+impl core::str::FromStr for Shape {
+    type Err = AttributeParseError;
+
+    /// Parses a `shape` value, such as `box` or `Mrecord`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_value(value, Shape::from_keyword)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum EdgeRespresentation {
     Spline,
     LineSegment,
@@ -353,7 +1930,32 @@ pub enum EdgeRespresentation {
     Curved,
 }
 
-#[derive(Debug)]
+impl EdgeRespresentation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeRespresentation::Spline => "spline",
+            EdgeRespresentation::LineSegment => "line",
+            EdgeRespresentation::Off => "none",
+            EdgeRespresentation::Polyline => "polyline",
+            EdgeRespresentation::Ortho => "ortho",
+            EdgeRespresentation::Curved => "curved",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "spline" | "true" => Some(EdgeRespresentation::Spline),
+            "line" | "false" => Some(EdgeRespresentation::LineSegment),
+            "none" | "" => Some(EdgeRespresentation::Off),
+            "polyline" => Some(EdgeRespresentation::Polyline),
+            "ortho" => Some(EdgeRespresentation::Ortho),
+            "curved" => Some(EdgeRespresentation::Curved),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Style {
     Dashed,
     Dotted,
@@ -368,58 +1970,421 @@ pub enum Style {
     Rounded,
 }
 
-fn style_parser(input: &str) -> ParseResult<&str, Style> {
-    ws(alt((
-        tag("dashed").map(|_| Style::Dashed),
-        tag("dotted").map(|_| Style::Dotted),
-        tag("solid").map(|_| Style::Solid),
-        tag("invis").map(|_| Style::Invis),
-        tag("bold").map(|_| Style::Bold),
-        tag("tapered").map(|_| Style::Tapered),
-        tag("filled").map(|_| Style::Filled),
-        tag("striped").map(|_| Style::Striped),
-        tag("wedged").map(|_| Style::Wedged),
-        tag("diagonals").map(|_| Style::Diagonals),
-        tag("rounded").map(|_| Style::Rounded),
-    )))(input)
+impl Style {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Style::Dashed => "dashed",
+            Style::Dotted => "dotted",
+            Style::Solid => "solid",
+            Style::Invis => "invis",
+            Style::Bold => "bold",
+            Style::Tapered => "tapered",
+            Style::Filled => "filled",
+            Style::Striped => "striped",
+            Style::Wedged => "wedged",
+            Style::Diagonals => "diagonals",
+            Style::Rounded => "rounded",
+        }
+    }
+
+    pub(crate) fn from_keyword(s: &str) -> Option<Self> {
+        match s {
+            "dashed" => Some(Style::Dashed),
+            "dotted" => Some(Style::Dotted),
+            "solid" => Some(Style::Solid),
+            "invis" => Some(Style::Invis),
+            "bold" => Some(Style::Bold),
+            "tapered" => Some(Style::Tapered),
+            "filled" => Some(Style::Filled),
+            "striped" => Some(Style::Striped),
+            "wedged" => Some(Style::Wedged),
+            "diagonals" => Some(Style::Diagonals),
+            "rounded" => Some(Style::Rounded),
+            _ => None,
+        }
+    }
 }
 
-fn styles_parser(input: &str) -> ParseResult<&str, Vec<Style>> {
-    separated_list1(char(','), style_parser)(input)
+impl core::str::FromStr for Style {
+    type Err = AttributeParseError;
+
+    /// Parses one of the comma-separated keywords of a `style` value, such as `dashed`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_value(value, Style::from_keyword)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ViewPort {
-    width: Double,
-    height: Double,
-    zoom: Double,
-    center: ViewPortCenter,
+    pub(crate) width: Double,
+    pub(crate) height: Double,
+    pub(crate) zoom: Double,
+    pub(crate) center: ViewPortCenter,
 }
 
-#[derive(Debug)]
+impl ViewPort {
+    pub fn width(&self) -> Double {
+        self.width
+    }
+
+    pub fn height(&self) -> Double {
+        self.height
+    }
+
+    pub fn zoom(&self) -> Double {
+        self.zoom
+    }
+
+    pub fn center(&self) -> &ViewPortCenter {
+        &self.center
+    }
+}
+
+impl fmt::Display for ViewPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{}",
+            self.width, self.height, self.zoom, self.center
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ViewPortCenter {
     Position(Point),
     NodeName(String),
 }
 
+impl fmt::Display for ViewPortCenter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewPortCenter::Position(point) => write!(f, "{}", point),
+            ViewPortCenter::NodeName(name) => write!(f, "'{}'", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum AttributeParseError {
     AttributeNameNotFound,
     InvalidAttribueValue,
 }
 
-pub fn attribute_parser(input: &str) -> ParseResult<&str, Attribute> {
-    let (rest, (attr, _)) = pair(
-        alt((
-            map(
-                separated_pair(ws(tag("style")), char('='), ws(styles_parser)),
-                |(_, style)| Attribute::Style(style),
-            ),
-            map(
-                separated_pair(ws(tag("shape")), char('='), ws(shape_parser)),
-                |(_, shape)| Attribute::Shape(shape),
-            ),
-        )),
-        opt(ws(alt((char(','), char(';'))))),
+impl fmt::Display for AttributeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AttributeParseError::AttributeNameNotFound => "unknown attribute",
+            AttributeParseError::InvalidAttribueValue => "invalid attribute value",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AttributeParseError {}
+
+fn parse_value<T>(value: &str, f: impl Fn(&str) -> Option<T>) -> Result<T, AttributeParseError> {
+    f(value.trim()).ok_or(AttributeParseError::InvalidAttribueValue)
+}
+
+fn parse_number<T: core::str::FromStr>(value: &str) -> Result<T, AttributeParseError> {
+    parse_value(value, |value| value.parse().ok())
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        value => value.parse::<Int>().ok().map(|value| value != 0),
+    }
+}
+
+fn parse_numbers(value: &str, count: usize) -> Option<Vec<Double>> {
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<Double>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    (numbers.len() == count).then_some(numbers)
+}
+
+fn parse_point(value: &str) -> Option<Point> {
+    let numbers = parse_numbers(value, 2)?;
+    Some(Point {
+        x: numbers[0],
+        y: numbers[1],
+    })
+}
+
+fn parse_points(value: &str) -> Option<Vec<Point>> {
+    value.split_whitespace().map(parse_point).collect()
+}
+
+fn parse_rectangle(value: &str) -> Option<Rectangle> {
+    let numbers = parse_numbers(value, 4)?;
+    Some(Rectangle {
+        lower_left: Point {
+            x: numbers[0],
+            y: numbers[1],
+        },
+        upper_right: Point {
+            x: numbers[2],
+            y: numbers[3],
+        },
+    })
+}
+
+fn parse_color_attribute(value: &str) -> Option<ColorAttribute> {
+    if value.contains(':') || value.contains(';') {
+        value
+            .split(':')
+            .map(|color| {
+                let (color, weight) = match color.split_once(';') {
+                    Some((color, weight)) => (color, Some(weight.parse().ok()?)),
+                    None => (color, None),
+                };
+                Some(WeightedColor {
+                    color: parse_color(color)?,
+                    weight,
+                })
+            })
+            .collect::<Option<Box<[_]>>>()
+            .map(ColorAttribute::ColorList)
+    } else {
+        parse_color(value).map(ColorAttribute::Color)
+    }
+}
+
+fn parse_orientation(value: &str) -> Option<Orientation> {
+    match value {
+        "landscape" | "l" | "L" => Some(Orientation::Landscape),
+        value => value.parse().ok().map(Orientation::Rotation),
+    }
+}
+
+fn parse_pack(value: &str) -> Option<Pack> {
+    match parse_bool(value) {
+        _ if value.parse::<Int>().is_ok() => value.parse().ok().map(Pack::Value),
+        Some(true) => Some(Pack::True),
+        Some(false) => Some(Pack::False),
+        None => None,
+    }
+}
+
+/// Parses up to four shapes of an arrowhead, each a shape name after optional `o` and `l` or `r`
+/// modifiers.
+fn parse_arrows(mut value: &str) -> Option<Vec<Arrow>> {
+    const SHAPES: [ArrowShape; 11] = [
+        ArrowShape::Box,
+        ArrowShape::Crow,
+        ArrowShape::Curve,
+        ArrowShape::ICurve,
+        ArrowShape::Diamond,
+        ArrowShape::Dot,
+        ArrowShape::Inv,
+        ArrowShape::None,
+        ArrowShape::Normal,
+        ArrowShape::Tee,
+        ArrowShape::Vee,
+    ];
+    let mut arrows = vec![];
+    while !value.is_empty() && arrows.len() < 4 {
+        let open = value.starts_with('o');
+        if open {
+            value = &value[1..];
+        }
+        let side = match value.chars().next() {
+            Some('l') => Some(ArrowSide::Left),
+            Some('r') => Some(ArrowSide::Right),
+            _ => None,
+        };
+        if side.is_some() {
+            value = &value[1..];
+        }
+        let shape = *SHAPES
+            .iter()
+            .find(|shape| value.starts_with(shape.as_str()))?;
+        value = &value[shape.as_str().len()..];
+        arrows.push(Arrow { shape, open, side });
+    }
+    if value.is_empty() && !arrows.is_empty() {
+        Some(arrows)
+    } else {
+        None
+    }
+}
+
+fn parse_pack_mode(value: &str) -> Option<PackMode> {
+    match value {
+        "node" => Some(PackMode::Node),
+        "clust" => Some(PackMode::Clust),
+        "graph" => Some(PackMode::Graph),
+        value => {
+            let rest = value.strip_prefix("array")?;
+            let (flags, size) = match rest.strip_prefix('_') {
+                Some(rest) => rest.split_at(
+                    rest.find(|c: char| c.is_ascii_digit())
+                        .unwrap_or(rest.len()),
+                ),
+                None => ("", rest),
+            };
+            Some(PackMode::Array {
+                size: if size.is_empty() {
+                    0
+                } else {
+                    size.parse().ok()?
+                },
+                flags: flags
+                    .chars()
+                    .map(|flag| PackFlag::from_keyword(&flag.to_string()))
+                    .collect::<Option<Vec<_>>>()?,
+            })
+        }
+    }
+}
+
+fn parse_traversal_dir(value: char) -> Option<TraversalDir> {
+    let value = value.to_string();
+    VerticalDir::from_keyword(&value)
+        .map(TraversalDir::Vertical)
+        .or_else(|| HorizontalDir::from_keyword(&value).map(TraversalDir::Horizontal))
+}
+
+fn parse_page_dir(value: &str) -> Option<PageDir> {
+    let mut chars = value.chars();
+    let page_dir = PageDir {
+        primary: parse_traversal_dir(chars.next()?)?,
+        secondary: parse_traversal_dir(chars.next()?)?,
+    };
+    chars.next().is_none().then_some(page_dir)
+}
+
+fn parse_position(value: &str) -> Option<Position> {
+    if let Some(point) = value.strip_suffix('!') {
+        return parse_point(point).map(Position::Pinned);
+    }
+    parse_point(value)
+        .map(Position::Point)
+        .or_else(|| parse_points(value).map(Position::Spline))
+}
+
+fn parse_rank_separation(value: &str) -> Option<RankSeparation> {
+    let (values, equally) = match value.strip_suffix("equally") {
+        Some(values) => (values.trim(), true),
+        None => (value, false),
+    };
+    let values = if values.is_empty() {
+        if !equally {
+            return None;
+        }
+        vec![]
+    } else {
+        values
+            .split(':')
+            .map(|value| value.trim().parse().ok())
+            .collect::<Option<_>>()?
+    };
+    Some(RankSeparation { values, equally })
+}
+
+fn parse_ratio(value: &str) -> Option<Ratio> {
+    match value {
+        "fill" => Some(Ratio::Fill),
+        "compress" => Some(Ratio::Compress),
+        "expand" => Some(Ratio::Expand),
+        "auto" => Some(Ratio::Auto),
+        value => value.parse().ok().map(Ratio::Numeric),
+    }
+}
+
+/// A port name, a compass point, or both as `port:compass`. A name which is also a compass point
+/// is taken as a compass point, as fields are rarely named so.
+fn parse_port_pos(value: &str) -> Option<PortPos> {
+    if value.is_empty() {
+        return None;
+    }
+    if let Some(compass) = CompassPoint::from_keyword(value) {
+        return Some(PortPos {
+            port: None,
+            compass: Some(compass),
+        });
+    }
+    let (port, compass) = match value.rsplit_once(':') {
+        Some((port, compass)) => match CompassPoint::from_keyword(compass) {
+            Some(compass) => (port, Some(compass)),
+            None => (value, None),
+        },
+        None => (value, None),
+    };
+    Some(PortPos {
+        port: Some(port.to_owned()),
+        compass,
+    })
+}
+
+fn parse_view_port(value: &str) -> Option<ViewPort> {
+    let fields = value.splitn(4, ',').collect::<Vec<_>>();
+    let number = |i: usize| fields.get(i)?.trim().parse::<Double>().ok();
+    Some(ViewPort {
+        width: number(0)?,
+        height: number(1)?,
+        zoom: number(2)?,
+        center: match fields.get(3) {
+            Some(center) => parse_point(center)
+                .map(ViewPortCenter::Position)
+                .unwrap_or_else(|| ViewPortCenter::NodeName(center.trim_matches('\'').to_owned())),
+            None => return None,
+        },
+    })
+}
+
+fn parse_layers(value: &str) -> Vec<String> {
+    value
+        .split(|c| " :\t".contains(c))
+        .filter(|layer| !layer.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn parse_styles(value: &str) -> Option<Vec<Style>> {
+    value
+        .split(',')
+        .map(|style| Style::from_keyword(style.trim()))
+        .collect()
+}
+
+/// Parses a single `name = value` entry of an attribute list, along with its optional separator,
+/// without checking the name or value.
+pub(crate) fn attribute_pair_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, (Cow<'a, str>, Cow<'a, str>), E> {
+    let (rest, (pair, _)) = pair(
+        separated_pair(
+            dot_ws(ident_str_parser),
+            char('='),
+            cut(dot_ws(ident_str_parser)),
+        ),
+        opt(dot_ws(alt((char(','), char(';'))))),
     )(input)?;
-    Ok((rest, attr))
+    Ok((rest, pair))
+}
+
+/// Parses a single `name = value` entry of an attribute list, along with its optional separator.
+pub(crate) fn attribute_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Attribute, E> {
+    let (rest, (name, value)) = attribute_pair_parser(input)?;
+    match Attribute::from_pair(&name, &value) {
+        Ok(attribute) => Ok((rest, attribute)),
+        Err(e) => {
+            let input = input.trim_start();
+            Err(nom::Err::Failure(E::add_context(
+                input,
+                match e {
+                    AttributeParseError::AttributeNameNotFound => "expected known attribute name",
+                    AttributeParseError::InvalidAttribueValue => "expected valid attribute value",
+                },
+                E::from_error_kind(input, ErrorKind::Verify),
+            )))
+        }
+    }
 }
@@ -0,0 +1,59 @@
+//! `simpledot acyclic`: breaks cycles in digraphs by reversing edges, written as DOT, like
+//! Graphviz's `acyclic`. With `--check`, writes nothing but a `cycle` warning for each cycle it
+//! would break, and exits with status 1 if any graph has cycles.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::acyclic::make_acyclic,
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::{cycles, parse_error},
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE, EXIT_WARNINGS};
+
+const USAGE: &str = "usage: simpledot acyclic [--check] [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut check = false;
+    let options = Options::parse(args, USAGE, |option, _| {
+        match option {
+            "--check" => check = true,
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        if check {
+            warnings.extend(cycles(&graph).into_iter().map(|cycle| (name, cycle)));
+        } else {
+            make_acyclic(&mut graph);
+            dot::write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+        }
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &[errors.as_slice(), &warnings].concat())
+        .unwrap_or_else(|e| fail(e));
+    if !errors.is_empty() {
+        EXIT_PARSE
+    } else if !warnings.is_empty() {
+        EXIT_WARNINGS
+    } else {
+        0
+    }
+}
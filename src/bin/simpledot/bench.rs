@@ -0,0 +1,251 @@
+//! `simpledot bench`: parses every graph file of a corpus, reporting the time taken, throughput
+//! and memory use for each file and overall, to evaluate performance on real data and catch
+//! regressions.
+//!
+//! Directories are searched recursively for files of a readable format, by extension.
+//!
+//! - `-n`, `--runs N`: parse each file `N` times and report the fastest run, 1 by default.
+//! - `--ir`: parse only DOT files, to their intermediate representation rather than the graph
+//!   model.
+//!
+//! Peak memory is the most heap memory allocated at once while parsing, and held memory that
+//! still allocated for the parsed graph once done, both counted by the global allocator of the
+//! tool. It only counts while this command runs, leaving the other commands at the speed of the
+//! system allocator.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    io::{self, Write},
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicIsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use simpledot::{
+    diagnostic::Diagnostic,
+    format::{self, Format},
+    ir::parse_graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot bench [-n N] [--ir] [OPTIONS] DIR|FILE...";
+
+/// The system allocator, keeping track of the memory allocated once [`COUNTING`] is set.
+pub struct Counting;
+
+/// Whether [`Counting`] counts allocations, which only `bench` needs.
+static COUNTING: AtomicBool = AtomicBool::new(false);
+/// Memory allocated since counting started, less that freed, which may have been allocated
+/// before.
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+/// Counts `size` more bytes allocated, or fewer if negative, when counting.
+fn count(size: isize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            count(layout.size() as isize);
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            count(layout.size() as isize);
+        }
+        pointer
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        let pointer = System.realloc(pointer, layout, size);
+        if !pointer.is_null() {
+            count(size as isize - layout.size() as isize);
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        count(-(layout.size() as isize));
+    }
+}
+
+/// A measured run: how long it took, the most memory it allocated at once, and the memory its
+/// result holds.
+struct Run {
+    time: Duration,
+    peak: usize,
+    held: usize,
+}
+
+/// Runs `f`, measuring it and dropping its result.
+fn measure<T, E>(f: impl FnOnce() -> Result<T, E>) -> Result<Run, E> {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f()?;
+    let time = start.elapsed();
+    let held = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop(result);
+    Ok(Run {
+        time,
+        peak: (PEAK.load(Ordering::Relaxed) - before) as usize,
+        held: held.max(0) as usize,
+    })
+}
+
+/// The files of a readable format in `path`, or of DOT only with `ir`, searching directories
+/// recursively in name order.
+fn corpus(path: &str, ir: bool, files: &mut Vec<(String, &'static Format)>) {
+    if Path::new(path).is_dir() {
+        let mut entries = fs::read_dir(path)
+            .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            corpus(&entry.path().to_string_lossy(), ir, files);
+        }
+    } else if let Some(format) = format::by_extension(path)
+        .filter(|format| format.read.is_some() && (!ir || format.name == "dot"))
+    {
+        files.push((path.to_owned(), format));
+    }
+}
+
+/// Formats a number of bytes with a binary unit.
+fn bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    for unit in &["KiB", "MiB"] {
+        if size < 1024.0 {
+            return format!("{:.1} {}", size, unit);
+        }
+        size /= 1024.0;
+    }
+    format!("{:.1} GiB", size)
+}
+
+fn throughput(bytes: usize, time: Duration) -> f64 {
+    bytes as f64 / 1e6 / time.as_secs_f64().max(1e-9)
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    COUNTING.store(true, Ordering::Relaxed);
+    let mut runs = 1;
+    let mut ir = false;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-n" | "--runs" => {
+                let value = value();
+                runs = value
+                    .parse()
+                    .ok()
+                    .filter(|runs| *runs > 0)
+                    .unwrap_or_else(|| fail(format_args!("invalid run count `{}`", value)));
+            }
+            "--ir" => ir = true,
+            _ => return false,
+        }
+        true
+    });
+    if options.files == ["-"] {
+        fail(USAGE);
+    }
+    let mut files = vec![];
+    for path in &options.files {
+        corpus(path, ir, &mut files);
+    }
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    let (mut total_size, mut total_time, mut total_peak, mut total_held) =
+        (0, Duration::ZERO, 0, 0);
+    let widest = files.iter().map(|(file, _)| file.len()).max().unwrap_or(0);
+    writeln!(
+        writer,
+        "{:<widest$}  {:>11}  {:>10}  {:>8}  {:>11}  {:>11}",
+        "file",
+        "size",
+        "time",
+        "MB/s",
+        "peak",
+        "held",
+        widest = widest
+    )
+    .unwrap_or_else(|e| fail(e));
+    for (file, format) in &files {
+        let input = read_input(file);
+        let read = format.read.unwrap();
+        let (mut time, mut peak, mut held) = (Duration::MAX, 0, 0);
+        for _ in 0..runs {
+            let run: Result<Run, Diagnostic> = if ir {
+                measure(|| parse_graph(&input).map_err(parse_error))
+            } else {
+                measure(|| read(&input))
+            };
+            let run = match run {
+                Ok(run) => run,
+                Err(diagnostic) => {
+                    errors.push((display_name(file), diagnostic));
+                    break;
+                }
+            };
+            time = time.min(run.time);
+            peak = peak.max(run.peak);
+            held = run.held;
+        }
+        if time == Duration::MAX {
+            continue;
+        }
+        total_size += input.len();
+        total_time += time;
+        total_peak = total_peak.max(peak);
+        total_held += held;
+        writeln!(
+            writer,
+            "{:<widest$}  {:>11}  {:>7.2} ms  {:>8.2}  {:>11}  {:>11}",
+            file,
+            bytes(input.len()),
+            time.as_secs_f64() * 1e3,
+            throughput(input.len(), time),
+            bytes(peak),
+            bytes(held),
+            widest = widest
+        )
+        .unwrap_or_else(|e| fail(e));
+    }
+    writeln!(
+        writer,
+        "{} files, {} in {:.2} ms: {:.2} MB/s, peak {}, held {}",
+        files.len() - errors.len(),
+        bytes(total_size),
+        total_time.as_secs_f64() * 1e3,
+        throughput(total_size, total_time),
+        bytes(total_peak),
+        bytes(total_held)
+    )
+    .and_then(|_| writer.flush())
+    .unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
@@ -0,0 +1,123 @@
+//! `simpledot build`: runs the pipeline described by a configuration file, `simpledot.toml` by
+//! default; see the `pipeline` module of the library for its contents.
+//!
+//! - `-c`, `--config FILE`: read the pipeline from `FILE`.
+//!
+//! Input files and the output path of the configuration are relative to its directory, and output
+//! directories are created as needed. Files given on the command line replace the configured
+//! inputs, and `--output` the configured output path.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use simpledot::pipeline::{Pipeline, PipelineError};
+
+use crate::{display_name, fail, glob, read_input, Options, EXIT_ERRORS, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot build [-c FILE] [OPTIONS] [FILE...]";
+
+/// `path` relative to the directory `base`, unless it's absolute.
+fn resolve(base: &Path, path: &str) -> String {
+    base.join(path).to_string_lossy().into_owned()
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut config = "simpledot.toml".to_owned();
+    let mut options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-c" | "--config" => config = value(),
+            _ => return false,
+        }
+        true
+    });
+    let mut pipeline = Pipeline::from_config(&read_input(&config))
+        .unwrap_or_else(|e| fail(format_args!("{}: {}", config, e)));
+    let base = Path::new(&config).parent().unwrap_or(Path::new(""));
+    if options.files == ["-"] && !pipeline.files.is_empty() {
+        options.files = vec![];
+        for pattern in &pipeline.files {
+            let files = glob::expand(&resolve(base, pattern));
+            if files.is_empty() {
+                fail(format_args!("no files match `{}`", pattern));
+            }
+            options.files.extend(files);
+        }
+    }
+    match options.output.take() {
+        Some(output) => pipeline.output = Some(output),
+        None => pipeline.output = pipeline.output.map(|output| resolve(base, &output)),
+    }
+    // without `{name}`, all graphs go to the one output
+    if !pipeline
+        .output
+        .as_ref()
+        .is_some_and(|output| output.contains("{name}"))
+    {
+        options.output = pipeline.output.clone();
+    }
+    let write = pipeline
+        .output_format()
+        .unwrap_or_else(|e| fail(e))
+        .write
+        .unwrap();
+
+    let mut shared = None;
+    let mut errors = vec![];
+    let (mut unparsed, mut invalid) = (false, false);
+    for file in &options.files {
+        let name = display_name(file);
+        let graph = match pipeline
+            .read(file, &read_input(file))
+            .and_then(|graph| pipeline.transform(graph))
+        {
+            Ok(graph) => graph,
+            Err(PipelineError::Input(diagnostic)) => {
+                errors.push((name, diagnostic));
+                unparsed = true;
+                continue;
+            }
+            Err(PipelineError::Invalid(diagnostics)) => {
+                errors.extend(diagnostics.into_iter().map(|diagnostic| (name, diagnostic)));
+                invalid = true;
+                continue;
+            }
+            Err(e) => fail(format_args!("{}: {}", name, e)),
+        };
+        let result = match pipeline.output_path(file) {
+            Some(path) if options.output.is_none() => {
+                if let Some(directory) = Path::new(&path).parent() {
+                    fs::create_dir_all(directory)
+                        .unwrap_or_else(|e| fail(format_args!("{}: {}", path, e)));
+                }
+                fs::File::create(&path)
+                    .map(io::BufWriter::new)
+                    .and_then(|mut writer| {
+                        write(&graph, &mut writer)?;
+                        writer.flush()
+                    })
+                    .map_err(|e| format!("{}: {}", path, e))
+            }
+            _ => {
+                let writer = shared.get_or_insert_with(|| options.writer());
+                write(&graph, writer).map_err(|e| e.to_string())
+            }
+        };
+        result.unwrap_or_else(|e| fail(e));
+    }
+    if let Some(mut writer) = shared {
+        writer.flush().unwrap_or_else(|e| fail(e));
+    }
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if unparsed {
+        EXIT_PARSE
+    } else if invalid {
+        EXIT_ERRORS
+    } else {
+        0
+    }
+}
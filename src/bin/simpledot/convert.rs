@@ -0,0 +1,87 @@
+//! `simpledot convert`: converts graphs between the supported formats.
+//!
+//! The input format is detected from each file's extension, and the output format from the
+//! `--output` file's extension; `--from` and `--to` override either. `--list-formats` lists the
+//! formats along with their extensions.
+
+use std::io::{self, Write};
+
+use simpledot::format::{self, Format, FORMATS};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str =
+    "usage: simpledot convert [--from FORMAT] [--to FORMAT] [--list-formats] [OPTIONS] [FILE...]";
+
+fn by_name(name: &str) -> &'static Format {
+    format::by_name(name).unwrap_or_else(|| fail(format_args!("unknown format `{}`", name)))
+}
+
+fn list_formats(mut writer: impl Write) -> io::Result<()> {
+    for format in FORMATS {
+        let modes = match (format.read.is_some(), format.write.is_some()) {
+            (true, true) => "read, write",
+            (true, false) => "read",
+            _ => "write",
+        };
+        let extensions = format
+            .extensions
+            .iter()
+            .map(|extension| format!(".{}", extension))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let line = format!("{:<12}{:<14}{}", format.name, modes, extensions);
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    writer.flush()
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut from = None;
+    let mut to = None;
+    let mut list = false;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "--from" => from = Some(by_name(&value())),
+            "-t" | "--to" => to = Some(by_name(&value())),
+            "--list-formats" => list = true,
+            _ => return false,
+        }
+        true
+    });
+    if list {
+        list_formats(options.writer()).unwrap_or_else(|e| fail(e));
+        return 0;
+    }
+    let to = to
+        .or_else(|| options.output.as_deref().and_then(format::by_extension))
+        .unwrap_or_else(|| fail("no output format; pass --to"));
+    let write = to
+        .write
+        .unwrap_or_else(|| fail(format_args!("{} can't be written", to.name)));
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let from = from
+            .or_else(|| format::by_extension(file))
+            .unwrap_or_else(|| fail(format_args!("{}: unknown input format; pass --from", name)));
+        let read = from
+            .read
+            .unwrap_or_else(|| fail(format_args!("{} can't be read", from.name)));
+        match read(&read_input(file)) {
+            Ok(graph) => write(&graph, &mut writer).unwrap_or_else(|e| fail(e)),
+            Err(diagnostic) => errors.push((name, diagnostic)),
+        }
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
@@ -0,0 +1,48 @@
+//! `simpledot corpus`: writes a random DOT graph of the given size, the same for the same
+//! options, to measure performance with `bench` on graphs of any size; see the `corpus` module of
+//! the library for what is generated.
+//!
+//! - `-n`, `--nodes N`: the number of nodes, 1000 by default.
+//! - `-e`, `--edges N`: the number of edges, 2000 by default.
+//! - `-d`, `--density D`: the probability of a statement setting attributes, 0.5 by default.
+//! - `-c`, `--chain N`: the most nodes of an edge statement, 3 by default.
+//! - `-u`, `--undirected`: write an undirected graph.
+//! - `-s`, `--seed N`: seed the random choices with `N`, 0 by default.
+
+use std::io::Write;
+
+use simpledot::corpus::{self, CorpusOptions};
+
+use crate::{fail, Options};
+
+const USAGE: &str = "usage: simpledot corpus [-n N] [-e N] [-d D] [-c N] [-u] [-s N] [-o FILE]";
+
+fn number<T: std::str::FromStr>(option: &str, value: String) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| fail(format_args!("invalid number `{}` for {}", value, option)))
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut corpus_options = CorpusOptions::default();
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-n" | "--nodes" => corpus_options.nodes = number(option, value()),
+            "-e" | "--edges" => corpus_options.edges = number(option, value()),
+            "-d" | "--density" => corpus_options.density = number(option, value()),
+            "-c" | "--chain" => corpus_options.chain = number(option, value()),
+            "-u" | "--undirected" => corpus_options.directed = false,
+            "-s" | "--seed" => corpus_options.seed = number(option, value()),
+            _ => return false,
+        }
+        true
+    });
+    if options.files != ["-"] {
+        fail(USAGE);
+    }
+    let mut writer = options.writer();
+    corpus::write(&corpus_options, &mut writer)
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| fail(e));
+    0
+}
@@ -0,0 +1,104 @@
+//! `simpledot diff`: compares two versions of a graph.
+//!
+//! Lists added (`+`), removed (`-`) and changed (`~`) nodes and edges, colored when writing to a
+//! terminal or with `--color always`. With `--emit dot`, writes the new graph along with the
+//! removed elements instead, colored to show the changes. Exits with status 1 if the graphs
+//! differ, like `diff`.
+
+use std::io::{self, IsTerminal, Write};
+
+use simpledot::{
+    diff::{merge, GraphDiff},
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE, EXIT_WARNINGS};
+
+const USAGE: &str =
+    "usage: simpledot diff [--emit text|dot] [--color auto|always|never] [OPTIONS] OLD NEW";
+
+/// ANSI escape codes coloring lines by their first character, and the code resetting colors.
+const COLORS: &[(char, &str)] = &[('+', "\x1b[32m"), ('-', "\x1b[31m"), ('~', "\x1b[33m")];
+const RESET: &str = "\x1b[0m";
+
+fn write_text<W: Write>(mut writer: W, diff: &GraphDiff, color: bool) -> io::Result<()> {
+    for line in diff.to_string().lines() {
+        let code = COLORS
+            .iter()
+            .find(|(sign, _)| line.starts_with(*sign))
+            .map(|(_, code)| *code)
+            .filter(|_| color);
+        match code {
+            Some(code) => writeln!(writer, "{}{}{}", code, line, RESET)?,
+            None => writeln!(writer, "{}", line)?,
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut emit_dot = false;
+    let mut color = None;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "--emit" => {
+                emit_dot = match value().as_str() {
+                    "text" => false,
+                    "dot" => true,
+                    _ => fail(USAGE),
+                }
+            }
+            "--color" => {
+                color = match value().as_str() {
+                    "auto" => None,
+                    "always" => Some(true),
+                    "never" => Some(false),
+                    _ => fail(USAGE),
+                }
+            }
+            _ => return false,
+        }
+        true
+    });
+    if options.files.len() != 2 {
+        fail(USAGE);
+    }
+
+    let mut graphs = vec![];
+    let mut errors = vec![];
+    for file in &options.files {
+        match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => graphs.push(Graph::from_ir(&graph)),
+            Err(e) => errors.push((display_name(file), parse_error(e))),
+        }
+    }
+    let status = match &graphs[..] {
+        [old, new] => {
+            let mut writer = options.writer();
+            let diff = GraphDiff::new(old, new);
+            let written = if emit_dot {
+                dot::write(&merge(old, new), &mut writer)
+            } else {
+                let color =
+                    color.unwrap_or_else(|| options.output.is_none() && io::stdout().is_terminal());
+                write_text(&mut writer, &diff, color)
+            };
+            written
+                .and_then(|_| writer.flush())
+                .unwrap_or_else(|e| fail(e));
+            if diff.is_empty() {
+                0
+            } else {
+                EXIT_WARNINGS
+            }
+        }
+        _ => EXIT_PARSE,
+    };
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    status
+}
@@ -0,0 +1,46 @@
+//! `simpledot explore`: browses a graph interactively from the terminal, reading commands from
+//! standard input. Type `help` for the commands.
+
+use std::io::{self, BufRead, Write};
+
+use simpledot::{explore::Explorer, ir::parse_graph, model::Graph, validate::parse_error};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot explore [OPTIONS] FILE";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let options = Options::parse(args, USAGE, |_, _| false);
+    let file = match &options.files[..] {
+        [file] if file != "-" => file,
+        _ => fail(USAGE),
+    };
+    let graph = match parse_graph(read_input(file).as_str()) {
+        Ok(graph) => Graph::from_ir(&graph),
+        Err(e) => {
+            options
+                .write_diagnostics(io::stderr(), &[(display_name(file), parse_error(e))])
+                .unwrap_or_else(|e| fail(e));
+            return EXIT_PARSE;
+        }
+    };
+
+    let mut explorer = Explorer::new(&graph);
+    let mut writer = options.writer();
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        eprint!("{}", explorer.prompt());
+        let line = match lines.next() {
+            Some(line) => line.unwrap_or_else(|e| fail(e)),
+            None => break,
+        };
+        let more = explorer
+            .execute(&line, &mut writer)
+            .and_then(|more| writer.flush().map(|_| more))
+            .unwrap_or_else(|e| fail(e));
+        if !more {
+            break;
+        }
+    }
+    0
+}
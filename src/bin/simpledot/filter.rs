@@ -0,0 +1,108 @@
+//! `simpledot filter`: trims graphs down to the parts of interest, written as DOT.
+//!
+//! - `--seed ID`: keep only the neighborhood of the node `ID`, which may be given several times.
+//! - `--hops N`: the size of the neighborhood, in edges from a seed, 1 by default, or `all` for
+//!   everything reachable, such as everything downstream of a seed with `--direction out`.
+//! - `--direction out|in|both`: follow edges from tail to head, head to tail, or both, the
+//!   default.
+//! - `--keep SELECTOR`: keep only the elements matching `SELECTOR`, with the edges between kept
+//!   nodes.
+//! - `--remove SELECTOR`: remove the elements matching `SELECTOR`, with the edges of removed
+//!   nodes.
+//!
+//! The neighborhood is taken first, then elements kept, then removed.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::neighborhood::{neighborhood_subgraph, Direction},
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    select::Selector,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str =
+    "usage: simpledot filter [--seed ID]... [--hops N|all] [--direction out|in|both] \
+                     [--keep SELECTOR] [--remove SELECTOR] [OPTIONS] [FILE...]";
+
+fn selector(value: String) -> Option<Selector> {
+    Some(value.parse().unwrap_or_else(|e| fail(e)))
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut seeds = vec![];
+    let mut hops = 1;
+    let mut direction = Direction::Both;
+    let mut keep = None;
+    let mut remove = None;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "--seed" => seeds.push(value()),
+            "--hops" => {
+                let value = value();
+                hops = match value.as_str() {
+                    "all" => usize::MAX,
+                    _ => value
+                        .parse()
+                        .unwrap_or_else(|_| fail(format_args!("invalid hop count `{}`", value))),
+                };
+            }
+            "--direction" => {
+                direction = match value().as_str() {
+                    "out" => Direction::Out,
+                    "in" => Direction::In,
+                    "both" => Direction::Both,
+                    _ => fail(USAGE),
+                }
+            }
+            "--keep" => keep = selector(value()),
+            "--remove" => remove = selector(value()),
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        if !seeds.is_empty() {
+            let seeds = seeds
+                .iter()
+                .map(|seed| {
+                    graph
+                        .node_index(seed)
+                        .unwrap_or_else(|| fail(format_args!("{}: no node `{}`", name, seed)))
+                })
+                .collect::<Vec<_>>();
+            graph = neighborhood_subgraph(&graph, &seeds, hops, direction);
+        }
+        if let Some(keep) = &keep {
+            graph = keep.select(&graph);
+        }
+        if let Some(remove) = &remove {
+            graph = remove.remove(&graph);
+        }
+        dot::write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
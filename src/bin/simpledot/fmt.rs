@@ -0,0 +1,65 @@
+//! `simpledot fmt`: formats graphs, the DOT equivalent of rustfmt.
+//!
+//! Writes each formatted graph to the output or, with `--in-place`, back to its file. `--check`
+//! writes nothing, but lists the files which aren't formatted.
+
+use std::io::{self, Write};
+
+use simpledot::{export::dot::format, validate::parse_error};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE, EXIT_WARNINGS};
+
+const USAGE: &str = "usage: simpledot fmt [--check | --in-place] [OPTIONS] [FILE...]";
+
+#[derive(PartialEq)]
+enum Mode {
+    Print,
+    Check,
+    InPlace,
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut mode = Mode::Print;
+    let options = Options::parse(args, USAGE, |option, _| {
+        match option {
+            "--check" if mode == Mode::Print => mode = Mode::Check,
+            "-i" | "--in-place" if mode == Mode::Print => mode = Mode::InPlace,
+            _ => return false,
+        }
+        true
+    });
+    if mode == Mode::InPlace && (options.files == ["-"] || options.output.is_some()) {
+        fail("--in-place needs files, and no --output");
+    }
+
+    let mut writer = options.writer();
+    let mut status = 0;
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let input = read_input(file);
+        let formatted = match format(&input) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                status = EXIT_PARSE;
+                continue;
+            }
+        };
+        let written = match mode {
+            Mode::Print => writer.write_all(formatted.as_bytes()),
+            Mode::Check if formatted != input => {
+                status = status.max(EXIT_WARNINGS);
+                writeln!(writer, "{}", name)
+            }
+            Mode::InPlace if formatted != input => std::fs::write(file, formatted),
+            Mode::Check | Mode::InPlace => Ok(()),
+        };
+        written.unwrap_or_else(|e| fail(format_args!("{}: {}", name, e)));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    status
+}
@@ -0,0 +1,57 @@
+//! Glob expansion for file arguments, for shells which don't expand them.
+
+use std::path::Path;
+
+/// Whether `name` matches `pattern`, where `*` matches any characters and `?` any one character.
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| matches(rest, &name[skip..])),
+        Some((c, rest)) => match name.split_first() {
+            Some((n, name)) if *c == '?' || c == n => matches(rest, name),
+            _ => false,
+        },
+    }
+}
+
+/// The files matching a glob pattern, in order, or the pattern itself if it has no wildcards.
+pub fn expand(pattern: &str) -> Vec<String> {
+    if !pattern.contains(['*', '?']) {
+        return vec![pattern.to_owned()];
+    }
+    let (mut paths, components) = match pattern.strip_prefix('/') {
+        Some(relative) => (vec!["/".to_owned()], relative),
+        None => (vec![String::new()], pattern),
+    };
+    for component in components.split('/').filter(|c| !c.is_empty()) {
+        let join = |path: &str, name: &str| format!("{}{}/", path, name);
+        if !component.contains(['*', '?']) {
+            paths = paths.iter().map(|path| join(path, component)).collect();
+            continue;
+        }
+        let pattern = component.chars().collect::<Vec<_>>();
+        let mut matched = vec![];
+        for path in &paths {
+            let entries = match Path::new(if path.is_empty() { "." } else { path }).read_dir() {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for name in entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()) {
+                // as in shells, wildcards don't match hidden files
+                if name.starts_with('.') && !component.starts_with('.') {
+                    continue;
+                }
+                if matches(&pattern, &name.chars().collect::<Vec<_>>()) {
+                    matched.push(join(path, &name));
+                }
+            }
+        }
+        matched.sort();
+        paths = matched;
+    }
+    paths
+        .into_iter()
+        .map(|path| path.trim_end_matches('/').to_owned())
+        .filter(|path| Path::new(path).is_file())
+        .collect()
+}
@@ -0,0 +1,111 @@
+//! `simpledot layout`: lays graphs out, writing them with node positions and bounding boxes,
+//! like running Graphviz with `-Tdot`. `simpledot render` is the same command writing SVG by
+//! default, like running Graphviz with `-Tsvg`.
+//!
+//! - `-K`, `--engine ENGINE`: the layout engine, instead of each graph's `layout` attribute.
+//! - `-T`, `--to FORMAT`: the output format, DOT by default; see `simpledot convert
+//!   --list-formats`.
+//! - `-i`, `--incremental`: update the positions nodes already have instead of laying out
+//!   afresh, so that a graph edited after layout keeps its shape.
+//! - `--theme THEME`: give the graph, its nodes and its edges the colors, fonts and pen widths
+//!   of a built-in theme (`dark`, `light`, `high-contrast` or `colorblind`) or of a theme file,
+//!   a DOT graph of attribute statements, where their source doesn't set them.
+//! - `--graphviz PROGRAM`: lay out with an installed Graphviz program, such as `dot`, instead
+//!   of the native engines, asking it for the `-K` engine if any.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    format,
+    ir::parse_graph,
+    layout::{graphviz::Graphviz, LayoutEngine, Native, ENGINES},
+    model::Graph,
+    render::theme::Theme,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str =
+    "usage: simpledot layout [-K ENGINE] [-T FORMAT] [-i] [--theme THEME] [--graphviz PROGRAM] [OPTIONS] [FILE...]";
+
+const RENDER_USAGE: &str =
+    "usage: simpledot render [-K ENGINE] [-T FORMAT] [-i] [--theme THEME] [--graphviz PROGRAM] [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    lay_out(args, "dot", USAGE)
+}
+
+/// `simpledot render`: `layout` writing SVG unless `-T` says otherwise.
+pub fn render(args: Vec<String>) -> i32 {
+    lay_out(args, "svg", RENDER_USAGE)
+}
+
+fn lay_out(args: Vec<String>, format: &str, usage: &str) -> i32 {
+    let mut engine = None;
+    let mut to = format.to_owned();
+    let mut incremental = false;
+    let mut graphviz = None;
+    let mut theme = None;
+    let options = Options::parse(args, usage, |option, value| {
+        match option {
+            "-K" | "--engine" => engine = Some(value()),
+            "-T" | "--to" => to = value(),
+            "-i" | "--incremental" => incremental = true,
+            "--theme" => theme = Some(value()),
+            "--graphviz" => graphviz = Some(value()),
+            _ => return false,
+        }
+        true
+    });
+    let engine: Box<dyn LayoutEngine> = match graphviz {
+        Some(program) => Box::new(Graphviz { program, engine }),
+        None => {
+            if let Some(engine) = engine.as_deref().filter(|engine| !ENGINES.contains(engine)) {
+                fail(format_args!(
+                    "unknown layout engine `{}`; expected one of {}",
+                    engine,
+                    ENGINES.join(", ")
+                ));
+            }
+            Box::new(Native {
+                engine,
+                incremental,
+            })
+        }
+    };
+    let theme = theme.map(|theme| Theme::load(&theme).unwrap_or_else(|e| fail(e)));
+    let to = format::by_name(&to).unwrap_or_else(|| fail(format_args!("unknown format `{}`", to)));
+    let write = to
+        .write
+        .unwrap_or_else(|| fail(format_args!("{} can't be written", to.name)));
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        if let Some(theme) = &theme {
+            theme.apply(&mut graph);
+        }
+        engine
+            .layout(&mut graph)
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", name, e)));
+        write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
@@ -0,0 +1,76 @@
+//! `simpledot lint`: checks graphs like `validate`, along with style checks.
+//!
+//! Rules are configured in a TOML file, by default `dotlint.toml` in the current directory if
+//! there is one:
+//!
+//! ```toml
+//! warnings-as-errors = true
+//!
+//! [rules]
+//! unreachable-node = "off"        # or "warning" or "error"
+//! ```
+
+use std::{io::Write, path::Path};
+
+use simpledot::{
+    diagnostic::DiagnosticOptions,
+    validate::{lint, RULES},
+};
+
+use crate::{display_name, fail, read_input, status, Options};
+
+const USAGE: &str = "usage: simpledot lint [--config FILE] [--list-rules] [OPTIONS] [FILE...]";
+
+const DEFAULT_CONFIG: &str = "dotlint.toml";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut config = None;
+    let mut list_rules = false;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "--config" => config = Some(value()),
+            "--list-rules" => list_rules = true,
+            _ => return false,
+        }
+        true
+    });
+    let mut writer = options.writer();
+    if list_rules {
+        for (code, description) in RULES {
+            writeln!(writer, "{:<24}{}", code, description).unwrap_or_else(|e| fail(e));
+        }
+        writer.flush().unwrap_or_else(|e| fail(e));
+        return 0;
+    }
+
+    let config = config.or_else(|| {
+        Path::new(DEFAULT_CONFIG)
+            .is_file()
+            .then(|| DEFAULT_CONFIG.to_owned())
+    });
+    let diagnostic_options = match config {
+        Some(config) => std::fs::read_to_string(&config)
+            .map_err(|e| e.to_string())
+            .and_then(|input| DiagnosticOptions::from_config(&input).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| fail(format_args!("{}: {}", config, e))),
+        None => DiagnosticOptions::default(),
+    };
+
+    let mut exit_status = 0;
+    let mut reported = vec![];
+    for file in &options.files {
+        let diagnostics = lint(&read_input(file), diagnostic_options.clone());
+        exit_status = exit_status.max(status(&diagnostics));
+        reported.extend(
+            diagnostics
+                .into_vec()
+                .into_iter()
+                .map(|diagnostic| (display_name(file), diagnostic)),
+        );
+    }
+    options
+        .write_diagnostics(&mut writer, &reported)
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| fail(e));
+    exit_status
+}
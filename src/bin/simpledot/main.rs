@@ -0,0 +1,313 @@
+//! The `simpledot` command line tool, bundling the crate's tools as subcommands.
+//!
+//! ```text
+//! simpledot COMMAND [OPTIONS] [FILE...]
+//! ```
+//!
+//! Commands read the given files, or standard input without files or for `-`. File arguments may
+//! be glob patterns using `*` and `?`. Options shared by all commands:
+//!
+//! - `-o`, `--output FILE`: write to `FILE` instead of standard output.
+//! - `--diagnostics text|json`: write diagnostics as `FILE: severity[code]: message` lines, the
+//!   default, or as one JSON array of objects with `file`, `severity`, `code` and `message`
+//!   members.
+//! - `--watch`: keep running, and run the command again whenever one of its input files
+//!   changes.
+//!
+//! Diagnostics are the output of `lint` and `validate`; other commands write them to standard
+//! error. The exit status tells the worst outcome over all files:
+//!
+//! | status | meaning                        |
+//! |--------|--------------------------------|
+//! | 0      | success                        |
+//! | 1      | warnings, or differences found |
+//! | 2      | errors                         |
+//! | 3      | a file could not be parsed     |
+//! | 4      | usage or I/O errors            |
+//!
+//! Differences found are unformatted input for `fmt --check`, cycles for `acyclic --check` and
+//! differing graphs for `diff`.
+//!
+//! Run `simpledot COMMAND --help` for the options of each command.
+
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{self, Read, Write},
+    process::exit,
+    sync::Mutex,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use simpledot::diagnostic::{write_json, Diagnostic, Diagnostics};
+
+mod acyclic;
+mod bench;
+mod build;
+mod ccomps;
+mod convert;
+mod corpus;
+mod diff;
+mod explore;
+mod filter;
+mod fmt;
+mod glob;
+mod layout;
+mod lint;
+mod pack;
+mod parse;
+mod query;
+mod sccmap;
+mod script;
+mod stats;
+mod tred;
+mod unflatten;
+mod validate;
+
+const USAGE: &str = "usage: simpledot COMMAND [OPTIONS] [FILE...]
+
+commands:
+    parse       print the intermediate representation of graphs
+    convert     convert graphs between formats
+    fmt         format graphs
+    lint        check graphs for errors and style issues
+    validate    check graphs for errors
+    stats       summarize graphs
+    query       select nodes and edges
+    filter      trim graphs to selected elements or neighborhoods
+    script      edit graphs with a script
+    ccomps      split graphs into connected components
+    acyclic     break cycles by reversing edges
+    tred        remove edges implied by longer paths
+    unflatten   stagger leaves to narrow wide graphs
+    pack        combine graphs into one
+    sccmap      collapse strongly connected components
+    diff        compare two versions of a graph
+    explore     browse a graph interactively
+    layout      compute node positions
+    render      lay out and draw graphs, as SVG by default
+    build       run the pipeline of simpledot.toml
+    bench       time parsing a corpus of graphs
+    corpus      generate a random graph of a given size
+
+options for all commands:
+    -o, --output FILE           write to FILE instead of standard output
+    --diagnostics text|json     format of diagnostics
+    --watch                     run again whenever an input file changes";
+
+const EXIT_WARNINGS: i32 = 1;
+const EXIT_ERRORS: i32 = 2;
+const EXIT_PARSE: i32 = 3;
+const EXIT_FAILURE: i32 = 4;
+
+/// Codes of the diagnostics reporting that a graph failed to parse.
+const PARSE_CODES: &[&str] = &["syntax", "unknown-attribute"];
+
+/// How often `--watch` checks input files for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The files read by the command, with their modification times when read, for `--watch`.
+static INPUTS: Mutex<Vec<(String, Option<SystemTime>)>> = Mutex::new(Vec::new());
+
+#[global_allocator]
+static ALLOCATOR: bench::Counting = bench::Counting;
+
+/// Prints `message` and exits with [`EXIT_FAILURE`].
+fn fail(message: impl Display) -> ! {
+    eprintln!("simpledot: {}", message);
+    exit(EXIT_FAILURE);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiagnosticFormat {
+    Text,
+    Json,
+}
+
+/// The options shared by all commands.
+struct Options {
+    files: Vec<String>,
+    output: Option<String>,
+    diagnostics: DiagnosticFormat,
+}
+
+impl Options {
+    /// Parses the shared options and file arguments. Other options are passed to `option` along
+    /// with a function returning the option's value, and `option` returns whether it knows them.
+    fn parse(
+        args: Vec<String>,
+        usage: &str,
+        mut option: impl FnMut(&str, &mut dyn FnMut() -> String) -> bool,
+    ) -> Self {
+        let mut options = Options {
+            files: vec![],
+            output: None,
+            diagnostics: DiagnosticFormat::Text,
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().unwrap_or_else(|| fail(usage));
+            match arg.as_str() {
+                "-o" | "--output" => options.output = Some(value()),
+                "--diagnostics" => {
+                    options.diagnostics = match value().as_str() {
+                        "text" => DiagnosticFormat::Text,
+                        "json" => DiagnosticFormat::Json,
+                        _ => fail(usage),
+                    }
+                }
+                // handled by `main`
+                "--watch" => {}
+                "-h" | "--help" => {
+                    println!("{}", usage);
+                    exit(0);
+                }
+                arg if arg.starts_with('-') && arg != "-" => {
+                    if !option(arg, &mut value) {
+                        fail(usage)
+                    }
+                }
+                pattern => {
+                    let files = glob::expand(pattern);
+                    if files.is_empty() {
+                        fail(format_args!("no files match `{}`", pattern));
+                    }
+                    options.files.extend(files);
+                }
+            }
+        }
+        if options.files.is_empty() {
+            options.files.push("-".to_owned());
+        }
+        options
+    }
+
+    /// The writer for the command's output.
+    fn writer(&self) -> Box<dyn Write> {
+        match &self.output {
+            Some(output) => Box::new(io::BufWriter::new(
+                File::create(output).unwrap_or_else(|e| fail(format_args!("{}: {}", output, e))),
+            )),
+            None => Box::new(io::stdout().lock()),
+        }
+    }
+
+    /// Writes diagnostics, each along with the name of the file it concerns.
+    fn write_diagnostics<W: Write>(
+        &self,
+        mut writer: W,
+        diagnostics: &[(&str, Diagnostic)],
+    ) -> io::Result<()> {
+        match self.diagnostics {
+            DiagnosticFormat::Text => diagnostics
+                .iter()
+                .try_for_each(|(file, diagnostic)| writeln!(writer, "{}: {}", file, diagnostic)),
+            DiagnosticFormat::Json => {
+                write_json(writer, diagnostics.iter().map(|(file, d)| (*file, d)))
+            }
+        }
+    }
+}
+
+/// Modification time of `file`, if it can be read.
+fn modified(file: &str) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reads a file, or standard input for `-`.
+fn read_input(file: &str) -> String {
+    INPUTS
+        .lock()
+        .unwrap()
+        .push((file.to_owned(), modified(file)));
+    let input = if file == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input).map(|_| input)
+    } else {
+        std::fs::read_to_string(file)
+    };
+    input.unwrap_or_else(|e| fail(format_args!("{}: {}", display_name(file), e)))
+}
+
+/// The name to report a file under.
+fn display_name(file: &str) -> &str {
+    if file == "-" {
+        "<stdin>"
+    } else {
+        file
+    }
+}
+
+/// The exit status for the diagnostics of one file.
+fn status(diagnostics: &Diagnostics) -> i32 {
+    if diagnostics.iter().any(|d| PARSE_CODES.contains(&d.code)) {
+        EXIT_PARSE
+    } else if diagnostics.has_errors() {
+        EXIT_ERRORS
+    } else if diagnostics.warnings() > 0 {
+        EXIT_WARNINGS
+    } else {
+        0
+    }
+}
+
+fn run(command: Option<&str>, args: Vec<String>) -> i32 {
+    match command {
+        Some("parse") => parse::run(args),
+        Some("convert") => convert::run(args),
+        Some("fmt") => fmt::run(args),
+        Some("lint") => lint::run(args),
+        Some("validate") => validate::run(args),
+        Some("stats") => stats::run(args),
+        Some("query") => query::run(args),
+        Some("filter") => filter::run(args),
+        Some("script") => script::run(args),
+        Some("ccomps") => ccomps::run(args),
+        Some("acyclic") => acyclic::run(args),
+        Some("tred") => tred::run(args),
+        Some("unflatten") => unflatten::run(args),
+        Some("pack") => pack::run(args),
+        Some("sccmap") => sccmap::run(args),
+        Some("diff") => diff::run(args),
+        Some("explore") => explore::run(args),
+        Some("layout") => layout::run(args),
+        Some("render") => layout::render(args),
+        Some("build") => build::run(args),
+        Some("bench") => bench::run(args),
+        Some("corpus") => corpus::run(args),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            0
+        }
+        Some(command) => fail(format_args!("unknown command `{}`\n{}", command, USAGE)),
+        None => fail(USAGE),
+    }
+}
+
+/// Waits until one of the files read by the last run changes.
+fn wait_for_change() {
+    let inputs = std::mem::take(&mut *INPUTS.lock().unwrap());
+    if inputs.is_empty() || inputs.iter().any(|(file, _)| file == "-") {
+        fail("--watch needs input files");
+    }
+    eprintln!("simpledot: waiting for changes");
+    while inputs.iter().all(|(file, time)| modified(file) == *time) {
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+    let args = args.collect::<Vec<_>>();
+    if !args.iter().any(|arg| arg == "--watch") {
+        exit(run(command.as_deref(), args));
+    }
+    loop {
+        run(command.as_deref(), args.clone());
+        wait_for_change();
+    }
+}
@@ -0,0 +1,80 @@
+//! `simpledot pack`: combines graphs into one, written as DOT, like Graphviz's `gvpack`. Laid
+//! out graphs are arranged side by side.
+//!
+//! Packing follows the `pack` and `packmode` attributes of the first graph, overridden by:
+//!
+//! - `-m`, `--margin N`: leave `N` points around each graph.
+//! - `--packmode MODE`: pack as by a `packmode` attribute, such as `array_c4`.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::pack::{pack, PackOptions},
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot pack [--margin N] [--packmode MODE] [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut margin = None;
+    let mut mode = None;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-m" | "--margin" => {
+                let value = value();
+                margin = Some(
+                    value
+                        .parse()
+                        .unwrap_or_else(|_| fail(format_args!("invalid margin `{}`", value))),
+                );
+            }
+            "--packmode" => {
+                let value = value();
+                mode = Some(
+                    value
+                        .parse()
+                        .unwrap_or_else(|_| fail(format_args!("invalid pack mode `{}`", value))),
+                );
+            }
+            _ => return false,
+        }
+        true
+    });
+
+    let mut graphs = vec![];
+    let mut errors = vec![];
+    for file in &options.files {
+        match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => graphs.push(Graph::from_ir(&graph)),
+            Err(e) => errors.push((display_name(file), parse_error(e))),
+        }
+    }
+    let mut pack_options = graphs
+        .first()
+        .map(PackOptions::from_graph)
+        .unwrap_or_default();
+    if let Some(margin) = margin {
+        pack_options.margin = margin;
+    }
+    if let Some(mode) = mode {
+        pack_options.mode = mode;
+    }
+    let packed = pack(&graphs, &pack_options).unwrap_or_else(|e| fail(e));
+
+    let mut writer = options.writer();
+    dot::write(&packed, &mut writer).unwrap_or_else(|e| fail(e));
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
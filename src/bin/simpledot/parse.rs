@@ -0,0 +1,75 @@
+//! `simpledot parse`: prints the intermediate representation of graphs, as its debug printout,
+//! Graphviz JSON (as `dot -Tdot_json`), or canonical DOT.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    export::{
+        dot,
+        json::{self, JsonFormat},
+    },
+    ir::parse_graph,
+    model,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot parse [--format debug|json|dot] [OPTIONS] [FILE...]";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Debug,
+    Json,
+    Dot,
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut format = Format::Debug;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-f" | "--format" => {
+                format = match value().as_str() {
+                    "debug" => Format::Debug,
+                    "json" => Format::Json,
+                    "dot" => Format::Dot,
+                    _ => fail(USAGE),
+                }
+            }
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let input = read_input(file);
+        let graph = match parse_graph(input.as_str()) {
+            Ok(graph) => graph,
+            Err(e) => {
+                errors.push((display_name(file), parse_error(e)));
+                continue;
+            }
+        };
+        let written = match format {
+            Format::Debug => writeln!(writer, "{:?}", graph),
+            Format::Json => json::write(
+                &model::Graph::from_ir(&graph),
+                &mut writer,
+                JsonFormat::DotJson,
+            ),
+            Format::Dot => dot::write_ir(&graph, &mut writer),
+        };
+        written.unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
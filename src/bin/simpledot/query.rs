@@ -0,0 +1,103 @@
+//! `simpledot query`: selects nodes and edges with a selector such as `node[shape=box]`.
+//!
+//! Lists the IDs of matching nodes and the ends of matching edges, prefixed with their file if
+//! there are several, or with `--emit dot`, writes the subgraph of the matching elements.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    export::dot,
+    ir::{parse_graph, GraphKind},
+    model::Graph,
+    select::Selector,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot query SELECTOR [--emit list|dot] [OPTIONS] [FILE...]";
+
+fn list<W: Write>(
+    mut writer: W,
+    prefix: &str,
+    graph: &Graph,
+    selector: &Selector,
+) -> io::Result<()> {
+    for node in selector.nodes(graph) {
+        writeln!(writer, "{}{}", prefix, graph.nodes()[node].id)?;
+    }
+    let op = match graph.kind {
+        GraphKind::Directed => "->",
+        GraphKind::Undirected => "--",
+    };
+    for edge in selector.edges(graph) {
+        let edge = &graph.edges()[edge];
+        writeln!(
+            writer,
+            "{}{} {} {}",
+            prefix,
+            graph.nodes()[edge.tail].id,
+            op,
+            graph.nodes()[edge.head].id
+        )?;
+    }
+    Ok(())
+}
+
+pub fn run(mut args: Vec<String>) -> i32 {
+    if args.first().is_none_or(|arg| arg.starts_with('-')) {
+        return match args.first().map(String::as_str) {
+            Some("-h" | "--help") => {
+                println!("{}", USAGE);
+                0
+            }
+            _ => fail(USAGE),
+        };
+    }
+    let selector = args.remove(0);
+    let selector = selector.parse::<Selector>().unwrap_or_else(|e| fail(e));
+    let mut emit_dot = false;
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "--emit" => {
+                emit_dot = match value().as_str() {
+                    "list" => false,
+                    "dot" => true,
+                    _ => fail(USAGE),
+                }
+            }
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        let written = if emit_dot {
+            dot::write(&selector.select(&graph), &mut writer)
+        } else if options.files.len() > 1 {
+            list(&mut writer, &format!("{}: ", name), &graph, &selector)
+        } else {
+            list(&mut writer, "", &graph, &selector)
+        };
+        written.unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
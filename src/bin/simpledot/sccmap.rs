@@ -0,0 +1,61 @@
+//! `simpledot sccmap`: collapses the strongly connected components of graphs into single nodes,
+//! written as DOT, like Graphviz's `sccmap`. Each component's `comment` lists its nodes. With
+//! `--count`, only prints the number of components.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::scc::{condensation, strongly_connected_components},
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot sccmap [--count] [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut count = false;
+    let options = Options::parse(args, USAGE, |option, _| {
+        match option {
+            "--count" => count = true,
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        let written = if count {
+            writeln!(
+                writer,
+                "{}: {} strongly connected components",
+                name,
+                strongly_connected_components(&graph).len()
+            )
+        } else {
+            dot::write(&condensation(&graph).graph, &mut writer)
+        };
+        written.unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
@@ -0,0 +1,63 @@
+//! `simpledot script`: runs a script over graphs, gvpr style, and writes the results as DOT.
+//!
+//! The script is given as the first argument, or read from a file with `--file`.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    export::dot, ir::parse_graph, model::Graph, script::Script, validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_ERRORS, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot script (SCRIPT | --file FILE) [OPTIONS] [FILE...]";
+
+pub fn run(mut args: Vec<String>) -> i32 {
+    let mut source = match args.first() {
+        Some(arg) if !arg.starts_with('-') => Some(args.remove(0)),
+        _ => None,
+    };
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-f" | "--file" if source.is_none() => {
+                let file = value();
+                source = Some(
+                    std::fs::read_to_string(&file)
+                        .unwrap_or_else(|e| fail(format_args!("{}: {}", file, e))),
+                );
+            }
+            _ => return false,
+        }
+        true
+    });
+    let script = source
+        .unwrap_or_else(|| fail(USAGE))
+        .parse::<Script>()
+        .unwrap_or_else(|e| fail(format_args!("script: {}", e)));
+
+    let mut writer = options.writer();
+    let mut status = 0;
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                status = EXIT_PARSE;
+                continue;
+            }
+        };
+        if let Err(e) = script.run(&mut graph) {
+            eprintln!("{}: {}", name, e);
+            status = status.max(EXIT_ERRORS);
+            continue;
+        }
+        dot::write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    status
+}
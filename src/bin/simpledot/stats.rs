@@ -0,0 +1,92 @@
+//! `simpledot stats`: prints node and edge counts, the degree distribution, the number of
+//! connected components, and of strongly connected components of digraphs, and attribute usage
+//! of graphs.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    ir::parse_graph,
+    model::Graph,
+    stats::{write_json, Stats},
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot stats [--json] [OPTIONS] [FILE...]";
+
+fn write_text<W: Write>(mut writer: W, file: &str, stats: &Stats) -> io::Result<()> {
+    let counts = |counts: Vec<String>| counts.join(", ");
+    writeln!(writer, "{}", file)?;
+    writeln!(writer, "    nodes       {}", stats.nodes)?;
+    writeln!(writer, "    edges       {}", stats.edges)?;
+    writeln!(writer, "    components  {}", stats.components)?;
+    if let Some(components) = stats.strong_components {
+        writeln!(writer, "    strong      {}", components)?;
+    }
+    writeln!(
+        writer,
+        "    degrees     {}",
+        counts(
+            stats
+                .degrees
+                .iter()
+                .map(|(degree, count)| format!("{}: {}", degree, count))
+                .collect()
+        )
+    )?;
+    writeln!(
+        writer,
+        "    attributes  {}",
+        counts(
+            stats
+                .attributes
+                .iter()
+                .map(|(name, count)| format!("{}: {}", name, count))
+                .collect()
+        )
+    )
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut json = false;
+    let options = Options::parse(args, USAGE, |option, _| {
+        match option {
+            "--json" => json = true,
+            _ => return false,
+        }
+        true
+    });
+
+    let mut stats = vec![];
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => stats.push((name, Stats::new(&Graph::from_ir(&graph)))),
+            Err(e) => errors.push((name, parse_error(e))),
+        }
+    }
+    let mut writer = options.writer();
+    let written = if json {
+        write_json(
+            &mut writer,
+            stats.iter().map(|(file, stats)| (*file, stats)),
+        )
+    } else {
+        stats
+            .iter()
+            .try_for_each(|(file, stats)| write_text(&mut writer, file, stats))
+    };
+    written
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
@@ -0,0 +1,58 @@
+//! `simpledot tred`: removes the edges of digraphs implied by longer paths, written as DOT, like
+//! Graphviz's `tred`. Graphs with cycles are reduced too, with a warning that the result isn't
+//! unique.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::{acyclic::has_cycles, reduction::transitive_reduction},
+    diagnostic::Diagnostic,
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE, EXIT_WARNINGS, PARSE_CODES};
+
+const USAGE: &str = "usage: simpledot tred [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let options = Options::parse(args, USAGE, |_, _| false);
+
+    let mut writer = options.writer();
+    let mut diagnostics = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                diagnostics.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        if has_cycles(&graph) {
+            let warning = Diagnostic::warning(
+                "cycle",
+                "graph has cycles, so its transitive reduction isn't unique",
+            );
+            diagnostics.push((name, warning));
+        }
+        transitive_reduction(&mut graph);
+        dot::write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &diagnostics)
+        .unwrap_or_else(|e| fail(e));
+    if diagnostics
+        .iter()
+        .any(|(_, diagnostic)| PARSE_CODES.contains(&diagnostic.code))
+    {
+        EXIT_PARSE
+    } else if !diagnostics.is_empty() {
+        EXIT_WARNINGS
+    } else {
+        0
+    }
+}
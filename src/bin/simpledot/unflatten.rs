@@ -0,0 +1,64 @@
+//! `simpledot unflatten`: staggers the leaves of wide, shallow graphs over several ranks,
+//! written as DOT, like Graphviz's `unflatten`.
+//!
+//! - `-l`, `--stagger N`: give edges to leaves `minlen`s cycling from 1 to `N`.
+//! - `-f`, `--fans`: with `-l`, stagger edges to nodes with one in-edge and one out-edge too.
+//! - `-c`, `--chain N`: stack isolated nodes into chains of up to `N` nodes.
+
+use std::io::{self, Write};
+
+use simpledot::{
+    algorithm::unflatten::{unflatten, UnflattenOptions},
+    export::dot,
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+use crate::{display_name, fail, read_input, Options, EXIT_PARSE};
+
+const USAGE: &str = "usage: simpledot unflatten [-l N] [-f] [-c N] [OPTIONS] [FILE...]";
+
+fn count(option: &str, value: String) -> Option<usize> {
+    match value.parse() {
+        Ok(count) => Some(count),
+        Err(_) => fail(format_args!("invalid count `{}` for {}", value, option)),
+    }
+}
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut unflatten_options = UnflattenOptions::default();
+    let options = Options::parse(args, USAGE, |option, value| {
+        match option {
+            "-l" | "--stagger" => unflatten_options.stagger = count(option, value()),
+            "-f" | "--fans" => unflatten_options.fans = true,
+            "-c" | "--chain" => unflatten_options.chain = count(option, value()),
+            _ => return false,
+        }
+        true
+    });
+
+    let mut writer = options.writer();
+    let mut errors = vec![];
+    for file in &options.files {
+        let name = display_name(file);
+        let mut graph = match parse_graph(read_input(file).as_str()) {
+            Ok(graph) => Graph::from_ir(&graph),
+            Err(e) => {
+                errors.push((name, parse_error(e)));
+                continue;
+            }
+        };
+        unflatten(&mut graph, &unflatten_options);
+        dot::write(&graph, &mut writer).unwrap_or_else(|e| fail(e));
+    }
+    writer.flush().unwrap_or_else(|e| fail(e));
+    options
+        .write_diagnostics(io::stderr(), &errors)
+        .unwrap_or_else(|e| fail(e));
+    if errors.is_empty() {
+        0
+    } else {
+        EXIT_PARSE
+    }
+}
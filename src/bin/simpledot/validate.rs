@@ -0,0 +1,39 @@
+//! `simpledot validate`: parses and checks graphs, for use in CI.
+
+use std::io::Write;
+
+use simpledot::{diagnostic::DiagnosticOptions, validate::check};
+
+use crate::{display_name, fail, read_input, status, Options};
+
+const USAGE: &str = "usage: simpledot validate [--warnings-as-errors] [OPTIONS] [FILE...]";
+
+pub fn run(args: Vec<String>) -> i32 {
+    let mut diagnostic_options = DiagnosticOptions::default();
+    let options = Options::parse(args, USAGE, |option, _| {
+        match option {
+            "--warnings-as-errors" => diagnostic_options.warnings_as_errors = true,
+            _ => return false,
+        }
+        true
+    });
+
+    let mut exit_status = 0;
+    let mut reported = vec![];
+    for file in &options.files {
+        let diagnostics = check(&read_input(file), diagnostic_options.clone());
+        exit_status = exit_status.max(status(&diagnostics));
+        reported.extend(
+            diagnostics
+                .into_vec()
+                .into_iter()
+                .map(|diagnostic| (display_name(file), diagnostic)),
+        );
+    }
+    let mut writer = options.writer();
+    options
+        .write_diagnostics(&mut writer, &reported)
+        .and_then(|_| writer.flush())
+        .unwrap_or_else(|e| fail(e));
+    exit_status
+}
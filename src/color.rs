@@ -1,16 +1,221 @@
-#[derive(Debug)]
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::OnceLock};
+
+use crate::attribute::AttributeParseError;
+
+/// A color value; HSV components are single precision, keeping colors to three words.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Color {
     Rgb(Rgb),
     Rgba { r: u8, g: u8, b: u8, a: u8 },
-    Hsv { h: f64, s: f64, v: f64 },
+    Hsv { h: f32, s: f32, v: f32 },
     Name(ColorName),
 }
 
-#[derive(Debug)]
+impl Default for Color {
+    /// Black, the default `color`, `fontcolor` and `pencolor`.
+    fn default() -> Self {
+        Color::Name(ColorName {
+            scheme: ColorScheme::default(),
+            name: "black".into(),
+        })
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Rgb(rgb) => write!(f, "{}", rgb),
+            Color::Rgba { r, g, b, a } => write!(f, "#{:02x}{:02x}{:02x}{:02x}", r, g, b, a),
+            Color::Hsv { h, s, v } => write!(f, "{},{},{}", h, s, v),
+            Color::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = AttributeParseError;
+
+    /// Parses a color as [`parse_color`] does.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        parse_color(value).ok_or(AttributeParseError::InvalidAttribueValue)
+    }
+}
+
+impl Color {
+    /// The color as red, green, blue and alpha, or `None` for an unknown name. Names of the SVG
+    /// scheme are those of X11 but for the few where the two disagree. Needs the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn to_rgba(&self) -> Option<Rgba> {
+        Some(match self {
+            Color::Rgb(Rgb { r, g, b }) => Rgba::opaque(*r, *g, *b),
+            Color::Rgba { r, g, b, a } => Rgba {
+                r: *r,
+                g: *g,
+                b: *b,
+                a: *a,
+            },
+            Color::Hsv { h, s, v } => hsv_to_rgba((*h).into(), (*s).into(), (*v).into()),
+            Color::Name(name) => {
+                // as Graphviz, which has transparent as a nearly white color
+                if matches!(&*name.name, "none" | "transparent") {
+                    return Some(Rgba {
+                        r: 255,
+                        g: 255,
+                        b: 254,
+                        a: 0,
+                    });
+                }
+                let svg = match (&name.scheme, &*name.name) {
+                    (ColorScheme::Svg, "gray" | "grey") => Some("808080"),
+                    (ColorScheme::Svg, "green") => Some("008000"),
+                    (ColorScheme::Svg, "maroon") => Some("800000"),
+                    (ColorScheme::Svg, "purple") => Some("800080"),
+                    _ => None,
+                };
+                let hex = svg.or_else(|| x11_colors().get(&*name.name).copied())?;
+                let Rgb { r, g, b } = hexcode_to_rgb(hex);
+                Rgba::opaque(r, g, b)
+            }
+        })
+    }
+}
+
+/// Converts a color given by hue, saturation and value in `[0, 1]`.
+#[cfg(feature = "std")]
+fn hsv_to_rgba(h: f64, s: f64, v: f64) -> Rgba {
+    let h = (h.clamp(0.0, 1.0) * 6.0) % 6.0;
+    let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+    let sector = h.floor();
+    let f = h - sector;
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - s * f), v * (1.0 - s * (1.0 - f)));
+    let (r, g, b) = match sector as u8 {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    let byte = |value: f64| (value * 255.0).round() as u8;
+    Rgba::opaque(byte(r), byte(g), byte(b))
+}
+
+/// The X11 color names, with their hex codes.
+#[cfg(feature = "std")]
+fn x11_colors() -> &'static HashMap<&'static str, &'static str> {
+    static COLORS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    COLORS.get_or_init(|| {
+        include_str!("color_defs/x11.toml")
+            .lines()
+            .filter_map(|line| {
+                let (name, hex) = line.split_once('=')?;
+                Some((name.trim(), hex.trim().trim_matches('"')))
+            })
+            .collect()
+    })
+}
+
+/// A color as red, green, blue and alpha, as renderers use them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const BLACK: Rgba = Rgba::opaque(0, 0, 0);
+    pub const WHITE: Rgba = Rgba::opaque(255, 255, 255);
+
+    pub const fn opaque(r: u8, g: u8, b: u8) -> Self {
+        Rgba { r, g, b, a: 255 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rgb {
-    r: u8,
-    g: u8,
-    b: u8,
+    pub(crate) r: u8,
+    pub(crate) g: u8,
+    pub(crate) b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Parses a DOT color value: `#rrggbb`, `#rrggbbaa`, an HSV triple of values in `[0, 1]`, or a
+/// color name, optionally qualified by its scheme (`/svg/red`).
+pub(crate) fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return match hex.len() {
+            6 => Some(Color::Rgb(hexcode_to_rgb(hex))),
+            8 => {
+                let Rgb { r, g, b } = hexcode_to_rgb(hex);
+                Some(Color::Rgba {
+                    r,
+                    g,
+                    b,
+                    a: convert_hex(&hex[6..8]),
+                })
+            }
+            _ => None,
+        };
+    }
+    if value.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        let hsv = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        return match hsv.as_slice() {
+            &[h, s, v] => Some(Color::Hsv { h, s, v }),
+            _ => None,
+        };
+    }
+    let (scheme, name) = match value.strip_prefix('/') {
+        Some(qualified) => qualified.split_once('/')?,
+        None => ("x11", value),
+    };
+    let scheme = match scheme {
+        "" | "x11" => ColorScheme::X11,
+        "svg" => ColorScheme::Svg,
+        _ => return None,
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(Color::Name(ColorName {
+        scheme,
+        name: name.to_ascii_lowercase().into(),
+    }))
 }
 
 fn convert_hex(s: &str) -> u8 {
@@ -26,18 +231,68 @@ pub fn hexcode_to_rgb(s: &str) -> Rgb {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColorName {
-    scheme: ColorScheme,
-    name: String,
+    pub(crate) scheme: ColorScheme,
+    pub(crate) name: Box<str>,
+}
+
+impl ColorName {
+    pub fn new(scheme: ColorScheme, name: &str) -> Self {
+        ColorName {
+            scheme,
+            name: name.to_ascii_lowercase().into(),
+        }
+    }
+
+    pub fn scheme(&self) -> &ColorScheme {
+        &self.scheme
+    }
+
+    /// The name of the color within its scheme, in lowercase.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ColorName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scheme {
+            ColorScheme::X11 => write!(f, "{}", self.name),
+            scheme => write!(f, "/{}/{}", scheme, self.name),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ColorScheme {
     X11,
     Svg,
     Brewer(BrewerScheme),
 }
 
-#[derive(Debug)]
+impl Default for ColorScheme {
+    /// The X11 color scheme, which names without a scheme are read in.
+    fn default() -> Self {
+        ColorScheme::X11
+    }
+}
+
+impl ColorScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorScheme::X11 => "x11",
+            ColorScheme::Svg => "svg",
+            ColorScheme::Brewer(scheme) => match *scheme {},
+        }
+    }
+}
+
+impl fmt::Display for ColorScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum BrewerScheme {}
@@ -0,0 +1,191 @@
+//! Generating large random DOT graphs, to measure parsing and layout on inputs of any size, the
+//! same for everyone given the same options.
+//!
+//! Every node is declared, named `n0`, `n1`, ..., then edges join random nodes in chains such as
+//! `n3 -> n8 -> n1` of up to `chain` nodes. Each statement sets one to three attributes with
+//! probability `density`: labels, some quoted with escapes and some HTML, colors, shapes, sizes
+//! and weights.
+
+use std::io::{self, Write};
+
+/// What to generate; see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusOptions {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Probability of a statement setting attributes, in `[0, 1]`.
+    pub density: f64,
+    /// Most nodes of an edge statement, at least 2.
+    pub chain: usize,
+    pub directed: bool,
+    /// Seed of the random choices, giving the same graph for the same options.
+    pub seed: u64,
+}
+
+impl Default for CorpusOptions {
+    fn default() -> Self {
+        CorpusOptions {
+            nodes: 1000,
+            edges: 2000,
+            density: 0.5,
+            chain: 3,
+            directed: true,
+            seed: 0,
+        }
+    }
+}
+
+/// The xorshift64* generator, seeded through splitmix64 so that any seed works.
+struct Random(u64);
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Random((z ^ (z >> 31)).max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len())]
+    }
+}
+
+const WORDS: &[&str] = &[
+    "alpha", "beta", "gamma", "delta", "service", "queue", "store", "cache", "node", "edge",
+];
+const COLORS: &[&str] = &[
+    "red",
+    "blue",
+    "darkgreen",
+    "gray50",
+    "\"#1f77b4\"",
+    "\"/svg/orange\"",
+];
+
+/// A label: a quoted string, sometimes with escapes, or an HTML string.
+fn label(random: &mut Random) -> String {
+    let (first, second) = (random.pick(WORDS), random.pick(WORDS));
+    match random.below(4) {
+        0 => format!("<<b>{}</b> {}>", first, second),
+        1 => format!("\"{} \\\"{}\\\"\"", first, second),
+        _ => format!("\"{} {} {}\"", first, second, random.below(1000)),
+    }
+}
+
+/// Writes an attribute list of one to three attributes of a node or an edge.
+fn attributes<W: Write>(writer: &mut W, random: &mut Random, node: bool) -> io::Result<()> {
+    let count = 1 + random.below(3);
+    write!(writer, " [")?;
+    for index in 0..count {
+        if index > 0 {
+            write!(writer, ", ")?;
+        }
+        match (random.below(4), node) {
+            (0, _) => write!(writer, "label={}", label(random))?,
+            (1, _) => write!(writer, "color={}", random.pick(COLORS))?,
+            (2, true) => write!(
+                writer,
+                "shape={}",
+                random.pick(&["box", "ellipse", "circle", "record"])
+            )?,
+            (2, false) => write!(writer, "weight={}", 1 + random.below(10))?,
+            (_, true) => write!(writer, "fontsize={}", 8 + random.below(12))?,
+            (_, false) => write!(writer, "penwidth={}.5", random.below(4))?,
+        }
+    }
+    write!(writer, "]")
+}
+
+/// Writes the random graph given by `options` to `writer`.
+pub fn write<W: Write>(options: &CorpusOptions, mut writer: W) -> io::Result<()> {
+    let mut random = Random::new(options.seed);
+    let (keyword, op) = if options.directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    writeln!(writer, "{} G {{", keyword)?;
+    for node in 0..options.nodes {
+        write!(writer, "  n{}", node)?;
+        if random.chance(options.density) {
+            attributes(&mut writer, &mut random, true)?;
+        }
+        writeln!(writer)?;
+    }
+    let mut edges = if options.nodes == 0 { 0 } else { options.edges };
+    while edges > 0 {
+        let length = (2 + random.below(options.chain.max(2) - 1)).min(edges + 1);
+        edges -= length - 1;
+        write!(writer, "  n{}", random.below(options.nodes))?;
+        for _ in 1..length {
+            write!(writer, " {} n{}", op, random.below(options.nodes))?;
+        }
+        if random.chance(options.density) {
+            attributes(&mut writer, &mut random, false)?;
+        }
+        writeln!(writer)?;
+    }
+    writeln!(writer, "}}")
+}
+
+/// The random graph given by `options`, as DOT.
+pub fn generate(options: &CorpusOptions) -> String {
+    let mut output = vec![];
+    write(options, &mut output).expect("writing to memory");
+    String::from_utf8(output).expect("generated DOT is UTF-8")
+}
+
+#[cfg(all(test, feature = "model"))]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, model::Graph};
+
+    #[test]
+    fn generate() {
+        let options = CorpusOptions {
+            nodes: 300,
+            edges: 1000,
+            density: 0.8,
+            chain: 4,
+            ..CorpusOptions::default()
+        };
+        let input = super::generate(&options);
+        assert_eq!(input, super::generate(&options));
+        assert_ne!(
+            input,
+            super::generate(&CorpusOptions {
+                seed: 1,
+                ..options.clone()
+            })
+        );
+        let graph = Graph::from_ir(&parse_graph(&input).unwrap());
+        assert_eq!(graph.nodes().len(), 300);
+        assert_eq!(graph.edges().len(), 1000);
+
+        let undirected = super::generate(&CorpusOptions {
+            directed: false,
+            density: 0.0,
+            ..options
+        });
+        let graph = Graph::from_ir(&parse_graph(&undirected).unwrap());
+        assert!(!graph.is_directed());
+        assert!(graph.nodes().iter().all(|node| node.attributes.is_empty()));
+    }
+}
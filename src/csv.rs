@@ -0,0 +1,107 @@
+//! Minimal delimited-text reader and writer, following RFC 4180 quoting, shared by the tabular
+//! formats.
+
+use std::io::{self, Write};
+
+/// A record along with the line it starts on.
+pub(crate) type Record = (usize, Vec<String>);
+
+/// Splits `input` into records of fields. Fields may be quoted with `"`, in which case they may
+/// contain the delimiter, newlines and doubled quotes. Blank lines are skipped. Returns the line of
+/// an unterminated quoted field on failure.
+pub(crate) fn parse(input: &str, delimiter: char) -> Result<Vec<Record>, usize> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut line = 1;
+    let mut start = 1;
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            c if c == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push((start, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                start = line;
+            }
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(start);
+    }
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+/// Writes one record, quoting fields where needed.
+pub(crate) fn write_record<W: Write, S: AsRef<str>>(
+    writer: &mut W,
+    fields: &[S],
+    delimiter: char,
+) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(writer, "{}", delimiter)?;
+        }
+        let field = field.as_ref();
+        if field.contains([delimiter, '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(writer, "{}", field)?;
+        }
+    }
+    writeln!(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let records = vec![vec!["a", "b, \"c\"", ""], vec!["multi\nline", "x", "y"]];
+        let mut output = vec![];
+        for record in &records {
+            write_record(&mut output, record, ',').unwrap();
+        }
+        let parsed = parse(std::str::from_utf8(&output).unwrap(), ',').unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                (1, records[0].iter().map(|f| f.to_string()).collect()),
+                (2, records[1].iter().map(|f| f.to_string()).collect()),
+            ]
+        );
+        assert_eq!(
+            parse("a\tb\r\n\r\nc\td", '\t').unwrap(),
+            vec![
+                (1, vec!["a".to_owned(), "b".to_owned()]),
+                (3, vec!["c".to_owned(), "d".to_owned()]),
+            ]
+        );
+        assert_eq!(parse("a\n\"b\nc", ','), Err(2));
+    }
+}
@@ -0,0 +1,302 @@
+//! Diagnostics reported while checking a graph, and the options controlling how many are
+//! collected.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    ops::ControlFlow,
+};
+
+use thiserror::Error;
+
+use crate::{json::JsonValue, toml};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short, stable identifier of the check which produced this diagnostic.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// Options controlling diagnostic collection.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticOptions {
+    /// Maximum number of diagnostics to keep. Diagnostics past the limit are still counted, but
+    /// dropped.
+    pub max_diagnostics: Option<usize>,
+    /// Report warnings with [`Severity::Error`].
+    pub warnings_as_errors: bool,
+    /// Stop checking after the first error.
+    pub fail_fast: bool,
+    /// Severity overrides by diagnostic code; `None` disables the check.
+    pub rules: HashMap<String, Option<Severity>>,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("malformed TOML at line {0}")]
+    Syntax(usize),
+    #[error("unknown configuration key `{0}`")]
+    UnknownKey(String),
+    #[error("expected {expected} for `{key}`")]
+    InvalidValue { key: String, expected: &'static str },
+}
+
+impl DiagnosticOptions {
+    /// Reads options from a TOML configuration:
+    ///
+    /// ```toml
+    /// max-diagnostics = 50
+    /// warnings-as-errors = false
+    /// fail-fast = false
+    ///
+    /// [rules]
+    /// duplicate-node = "off"  # or "warning" or "error"
+    /// ```
+    pub fn from_config(input: &str) -> Result<Self, ConfigError> {
+        let root = toml::parse(input).map_err(ConfigError::Syntax)?;
+        let mut options = DiagnosticOptions::default();
+        for (key, value) in root.as_object().unwrap_or_default() {
+            let invalid = |expected| ConfigError::InvalidValue {
+                key: key.clone(),
+                expected,
+            };
+            match key.as_str() {
+                "max-diagnostics" => {
+                    let max = value
+                        .as_f64()
+                        .filter(|max| *max >= 0.0)
+                        .ok_or_else(|| invalid("non-negative integer"))?;
+                    options.max_diagnostics = Some(max as usize);
+                }
+                "warnings-as-errors" => {
+                    options.warnings_as_errors =
+                        value.as_bool().ok_or_else(|| invalid("boolean"))?
+                }
+                "fail-fast" => {
+                    options.fail_fast = value.as_bool().ok_or_else(|| invalid("boolean"))?
+                }
+                "rules" => {
+                    for (code, level) in value.as_object().ok_or_else(|| invalid("table"))? {
+                        let severity = match level.as_str() {
+                            Some("off") => None,
+                            Some("warning") => Some(Severity::Warning),
+                            Some("error") => Some(Severity::Error),
+                            _ => {
+                                return Err(ConfigError::InvalidValue {
+                                    key: format!("rules.{}", code),
+                                    expected: "\"off\", \"warning\" or \"error\"",
+                                })
+                            }
+                        };
+                        options.rules.insert(code.clone(), severity);
+                    }
+                }
+                _ => return Err(ConfigError::UnknownKey(key.clone())),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Collection of diagnostics, honoring [`DiagnosticOptions`].
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    options: DiagnosticOptions,
+    reported: Vec<Diagnostic>,
+    suppressed: usize,
+    errors: usize,
+    warnings: usize,
+}
+
+impl Diagnostics {
+    pub fn new(options: DiagnosticOptions) -> Self {
+        Diagnostics {
+            options,
+            ..Diagnostics::default()
+        }
+    }
+
+    /// Records a diagnostic, returning [`ControlFlow::Break`] if checking should stop.
+    pub fn report(&mut self, mut diagnostic: Diagnostic) -> ControlFlow<()> {
+        match self.options.rules.get(diagnostic.code) {
+            Some(None) => return ControlFlow::Continue(()),
+            Some(Some(severity)) => diagnostic.severity = *severity,
+            None => {}
+        }
+        if self.options.warnings_as_errors {
+            diagnostic.severity = Severity::Error;
+        }
+        match diagnostic.severity {
+            Severity::Warning => self.warnings += 1,
+            Severity::Error => self.errors += 1,
+        }
+        if self
+            .options
+            .max_diagnostics
+            .is_none_or(|max| self.reported.len() < max)
+        {
+            self.reported.push(diagnostic);
+        } else {
+            self.suppressed += 1;
+        }
+        if self.options.fail_fast && self.errors > 0 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.reported.iter()
+    }
+
+    /// Number of diagnostics dropped due to [`DiagnosticOptions::max_diagnostics`].
+    pub fn suppressed(&self) -> usize {
+        self.suppressed
+    }
+
+    /// Number of errors encountered, including suppressed ones.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Number of warnings encountered, including suppressed ones.
+    pub fn warnings(&self) -> usize {
+        self.warnings
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors == 0 && self.warnings == 0
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.reported
+    }
+}
+
+/// Writes diagnostics, each along with the file it concerns, as a JSON array of objects with
+/// `file`, `severity`, `code` and `message` members.
+pub fn write_json<'a, W: Write>(
+    mut writer: W,
+    diagnostics: impl IntoIterator<Item = (&'a str, &'a Diagnostic)>,
+) -> io::Result<()> {
+    JsonValue::Array(
+        diagnostics
+            .into_iter()
+            .map(|(file, diagnostic)| {
+                JsonValue::Object(vec![
+                    ("file".to_owned(), file.into()),
+                    (
+                        "severity".to_owned(),
+                        diagnostic.severity.to_string().into(),
+                    ),
+                    ("code".to_owned(), diagnostic.code.into()),
+                    ("message".to_owned(), diagnostic.message.as_str().into()),
+                ])
+            })
+            .collect(),
+    )
+    .write_pretty(&mut writer)
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.reported.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config() {
+        let options = DiagnosticOptions::from_config(
+            "max-diagnostics = 10\nfail-fast = true\n\n[rules]\nduplicate-node = \"off\"\nduplicate-edge = \"error\"\n",
+        )
+        .unwrap();
+        assert_eq!(options.max_diagnostics, Some(10));
+        assert!(options.fail_fast);
+
+        let mut diagnostics = Diagnostics::new(options);
+        let _ = diagnostics.report(Diagnostic::warning("duplicate-node", "a"));
+        let _ = diagnostics.report(Diagnostic::warning("duplicate-edge", "b"));
+        assert_eq!((diagnostics.errors(), diagnostics.warnings()), (1, 0));
+        assert_eq!(diagnostics.iter().count(), 1);
+
+        assert!(matches!(
+            DiagnosticOptions::from_config("max-diagnostic = 1\n"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            DiagnosticOptions::from_config("[rules]\nsyntax = \"loud\"\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn json() {
+        let diagnostic = Diagnostic::error("syntax", "unexpected eof");
+        let mut output = vec![];
+        write_json(&mut output, vec![("a.dot", &diagnostic)]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            crate::json::parse(&output).unwrap(),
+            JsonValue::Array(vec![JsonValue::Object(vec![
+                ("file".to_owned(), "a.dot".into()),
+                ("severity".to_owned(), "error".into()),
+                ("code".to_owned(), "syntax".into()),
+                ("message".to_owned(), "unexpected eof".into()),
+            ])])
+        );
+    }
+}
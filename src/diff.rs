@@ -0,0 +1,351 @@
+//! Structural differences between two versions of a graph.
+//!
+//! Nodes are matched by ID, and edges by the IDs of their ends, in either order in undirected
+//! graphs; several edges between the same nodes are matched in order. Matched elements differ
+//! if any of their attributes do, comparing attributes as set rather than with defaults
+//! applied.
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    attribute::Attribute,
+    ir::{quote_id, GraphKind},
+    model::{set_attribute, Graph},
+};
+
+/// A difference in one attribute, with its values before and after, `None` where it isn't set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub name: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for AttributeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => {
+                write!(f, "{}={} -> {}", self.name, quote_id(old), quote_id(new))
+            }
+            (None, Some(new)) => write!(f, "+{}={}", self.name, quote_id(new)),
+            (Some(old), None) => write!(f, "-{}={}", self.name, quote_id(old)),
+            (None, None) => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed(Vec<AttributeChange>),
+}
+
+/// What changed about an element: a node, with just its ID, or an edge, with the IDs of its ends.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementChange {
+    pub tail: String,
+    pub head: Option<String>,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphDiff {
+    pub kind: GraphKind,
+    /// Changes to graph attributes.
+    pub attributes: Vec<AttributeChange>,
+    /// Changed nodes, in the order of the new graph followed by removed nodes.
+    pub nodes: Vec<ElementChange>,
+    /// Changed edges, in the order of the new graph followed by removed edges.
+    pub edges: Vec<ElementChange>,
+}
+
+/// Differences between two attribute lists, in the order of `old` followed by new attributes.
+fn attribute_changes(old: &[Attribute], new: &[Attribute]) -> Vec<AttributeChange> {
+    let find = |attributes: &[Attribute], name: &str| {
+        attributes
+            .iter()
+            .find(|attribute| attribute.name() == name)
+            .cloned()
+    };
+    let mut changes = vec![];
+    for attribute in old {
+        let name = attribute.name();
+        match find(new, name) {
+            Some(new) if new == *attribute => {}
+            new => changes.push(AttributeChange {
+                name,
+                old: Some(attribute.value_string()),
+                new: new.map(|new| new.value_string()),
+            }),
+        }
+    }
+    for attribute in new {
+        if find(old, attribute.name()).is_none() {
+            changes.push(AttributeChange {
+                name: attribute.name(),
+                old: None,
+                new: Some(attribute.value_string()),
+            });
+        }
+    }
+    changes
+}
+
+/// How each edge of `new` changed from the matching edge of `old`, `None` if it didn't, along
+/// with the indices of the edges of `old` without a match.
+fn edge_changes(old: &Graph, new: &Graph) -> (Vec<Option<Change>>, Vec<usize>) {
+    let directed = old.is_directed() && new.is_directed();
+    let key = |graph: &Graph, index: usize| {
+        let edge = &graph.edges()[index];
+        let (tail, head) = (&graph.nodes()[edge.tail].id, &graph.nodes()[edge.head].id);
+        if directed || tail <= head {
+            (tail.clone(), head.clone())
+        } else {
+            (head.clone(), tail.clone())
+        }
+    };
+    let mut unmatched: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for index in (0..old.edges().len()).rev() {
+        unmatched.entry(key(old, index)).or_default().push(index);
+    }
+    let changes = (0..new.edges().len())
+        .map(
+            |index| match unmatched.get_mut(&key(new, index)).and_then(Vec::pop) {
+                None => Some(Change::Added),
+                Some(old_index) => {
+                    let changes = attribute_changes(
+                        &old.edges()[old_index].attributes,
+                        &new.edges()[index].attributes,
+                    );
+                    if changes.is_empty() {
+                        None
+                    } else {
+                        Some(Change::Changed(changes))
+                    }
+                }
+            },
+        )
+        .collect();
+    let mut removed = unmatched.into_values().flatten().collect::<Vec<_>>();
+    removed.sort_unstable();
+    (changes, removed)
+}
+
+impl GraphDiff {
+    pub fn new(old: &Graph, new: &Graph) -> Self {
+        let mut nodes = vec![];
+        for node in new.nodes() {
+            let change = match old.node(&node.id) {
+                None => Change::Added,
+                Some(old) => match attribute_changes(&old.attributes, &node.attributes) {
+                    changes if changes.is_empty() => continue,
+                    changes => Change::Changed(changes),
+                },
+            };
+            nodes.push(ElementChange {
+                tail: node.id.clone(),
+                head: None,
+                change,
+            });
+        }
+        for node in old.nodes() {
+            if new.node(&node.id).is_none() {
+                nodes.push(ElementChange {
+                    tail: node.id.clone(),
+                    head: None,
+                    change: Change::Removed,
+                });
+            }
+        }
+
+        let ends = |graph: &Graph, index: usize| {
+            let edge = &graph.edges()[index];
+            (
+                graph.nodes()[edge.tail].id.clone(),
+                Some(graph.nodes()[edge.head].id.clone()),
+            )
+        };
+        let (changes, removed) = edge_changes(old, new);
+        let mut edges = vec![];
+        for (index, change) in changes.into_iter().enumerate() {
+            if let Some(change) = change {
+                let (tail, head) = ends(new, index);
+                edges.push(ElementChange { tail, head, change });
+            }
+        }
+        for index in removed {
+            let (tail, head) = ends(old, index);
+            edges.push(ElementChange {
+                tail,
+                head,
+                change: Change::Removed,
+            });
+        }
+
+        GraphDiff {
+            kind: new.kind,
+            attributes: attribute_changes(&old.attributes, &new.attributes),
+            nodes,
+            edges,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.nodes.is_empty() && self.edges.is_empty()
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    /// Formats the differences one per line, graph attributes first. Lines start with `+`, `-`
+    /// or `~` for added, removed and changed elements, followed by the element and any
+    /// attribute changes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.attributes {
+            writeln!(f, "~ graph: {}", change)?;
+        }
+        let op = match self.kind {
+            GraphKind::Directed => "->",
+            GraphKind::Undirected => "--",
+        };
+        for element in self.nodes.iter().chain(&self.edges) {
+            let sign = match element.change {
+                Change::Added => '+',
+                Change::Removed => '-',
+                Change::Changed(_) => '~',
+            };
+            match &element.head {
+                None => write!(f, "{} node {}", sign, element.tail)?,
+                Some(head) => write!(f, "{} edge {} {} {}", sign, element.tail, op, head)?,
+            }
+            if let Change::Changed(changes) = &element.change {
+                let changes = changes.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, ": {}", changes.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Colors of added, removed and changed elements in a [`merge`]d graph.
+const COLORS: [&str; 3] = ["green", "red", "orange"];
+
+/// A graph showing the differences between `old` and `new` for review: `new`, along with the
+/// nodes and edges removed from `old`, with added elements colored green, removed ones red and
+/// dashed, and changed ones orange.
+pub fn merge(old: &Graph, new: &Graph) -> Graph {
+    let diff = GraphDiff::new(old, new);
+    let mut merged = new.clone();
+    let marks = |change: &Change| {
+        let color = match change {
+            Change::Added => COLORS[0],
+            Change::Removed => COLORS[1],
+            Change::Changed(_) => COLORS[2],
+        };
+        let mut marks = vec![Attribute::from_pair("color", color).unwrap()];
+        if *change == Change::Removed {
+            marks.push(Attribute::from_pair("style", "dashed").unwrap());
+        }
+        marks
+    };
+
+    for change in &diff.nodes {
+        let index = match change.change {
+            Change::Removed => {
+                let index = merged.add_node(&change.tail);
+                merged.node_mut(index).attributes =
+                    old.node(&change.tail).unwrap().attributes.clone();
+                index
+            }
+            _ => merged.node_index(&change.tail).unwrap(),
+        };
+        for mark in marks(&change.change) {
+            set_attribute(&mut merged.node_mut(index).attributes, mark);
+        }
+    }
+
+    let (changes, removed) = edge_changes(old, new);
+    for (index, change) in changes.iter().enumerate() {
+        for mark in change.iter().flat_map(marks) {
+            set_attribute(&mut merged.edge_mut(index).attributes, mark);
+        }
+    }
+    for index in removed {
+        let edge = &old.edges()[index];
+        let tail = merged.add_node(&old.nodes()[edge.tail].id);
+        let head = merged.add_node(&old.nodes()[edge.head].id);
+        let mut attributes = edge.attributes.clone();
+        for mark in marks(&Change::Removed) {
+            set_attribute(&mut attributes, mark);
+        }
+        merged.add_edge(tail, head, attributes);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn graph(input: &str) -> Graph {
+        Graph::from_ir(&parse_graph(input).unwrap())
+    }
+
+    #[test]
+    fn diff() {
+        let old =
+            graph(r#"digraph { label=G a [color=red] b c a -> b a -> b [color=blue] b -> c }"#);
+        let new = graph(
+            r#"digraph { label="G 2" a [color=green, shape=box] b d a -> b [color=blue] b -> d }"#,
+        );
+        let diff = GraphDiff::new(&old, &new);
+        assert_eq!(
+            diff.to_string(),
+            r#"~ graph: label=G -> "G 2"
+~ node a: color=red -> green, +shape=box
++ node d
+- node c
+~ edge a -> b: +color=blue
++ edge b -> d
+- edge a -> b
+- edge b -> c
+"#
+        );
+        assert!(GraphDiff::new(&old, &old).is_empty());
+        assert!(GraphDiff::new(&graph("graph { a -- b }"), &graph("graph { b -- a }")).is_empty());
+
+        let merged = merge(&old, &new);
+        let color = |color: &str| Attribute::from_pair("color", color).unwrap();
+        let nodes = merged
+            .nodes()
+            .iter()
+            .map(|node| (node.id.as_str(), node.attributes.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            nodes,
+            vec![
+                (
+                    "a",
+                    vec![
+                        color("orange"),
+                        Attribute::from_pair("shape", "box").unwrap()
+                    ]
+                ),
+                ("b", vec![]),
+                ("d", vec![color("green")]),
+                (
+                    "c",
+                    vec![
+                        color("red"),
+                        Attribute::from_pair("style", "dashed").unwrap()
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(merged.edges().len(), 4);
+        assert_eq!(merged.edges()[0].attributes, vec![color("orange")]);
+        assert_eq!(merged.edges()[1].attributes, vec![color("green")]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Interactive exploration of a graph through typed commands, for browsing graphs from a
+//! terminal without rendering them, over SSH for example.
+//!
+//! An [`Explorer`] keeps track of a current node and runs one command line at a time:
+//!
+//! | command           | effect                                                         |
+//! |-------------------|----------------------------------------------------------------|
+//! | `graph`           | show the graph attributes and size                             |
+//! | `nodes`           | list the nodes, with their labels                              |
+//! | `go ID`           | make `ID` the current node and show it                         |
+//! | `show`            | show the attributes and numbered edges of the current node     |
+//! | `N`               | follow edge number `N` of the current node                     |
+//! | `back`            | return to the previous current node                            |
+//! | `find TEXT`       | list the nodes whose ID or label contains `TEXT`, in any case  |
+//! | `query SELECTOR`  | list the nodes and edges matching a [`Selector`]               |
+//! | `help`            | list the commands                                              |
+//! | `quit`            | stop exploring                                                 |
+//!
+//! Attributes shown are the effective attributes of nodes and edges, with defaults applied.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::Attribute,
+    ir::{quote_id, GraphKind},
+    model::{Graph, NodeIndex},
+    select::Selector,
+};
+
+const HELP: &str = "commands:
+    graph           show the graph attributes and size
+    nodes           list the nodes
+    go ID           go to node ID
+    show            show the current node
+    N               follow edge N of the current node
+    back            go back to the previous node
+    find TEXT       list nodes whose ID or label contains TEXT
+    query SELECTOR  list nodes and edges matching SELECTOR
+    help            show this help
+    quit            stop exploring";
+
+pub struct Explorer<'a> {
+    graph: &'a Graph,
+    current: Option<NodeIndex>,
+    /// Previous current nodes, the latest last.
+    history: Vec<NodeIndex>,
+}
+
+/// Formats attributes as in a DOT attribute list, without the brackets.
+fn attribute_list(attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .map(|attribute| {
+            format!(
+                "{}={}",
+                attribute.name(),
+                quote_id(&attribute.value_string())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes attributes one per line, indented.
+fn write_attributes<W: Write>(mut writer: W, attributes: &[Attribute]) -> io::Result<()> {
+    for attribute in attributes {
+        writeln!(
+            writer,
+            "    {} = {}",
+            attribute.name(),
+            quote_id(&attribute.value_string())
+        )?;
+    }
+    Ok(())
+}
+
+impl<'a> Explorer<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        Explorer {
+            graph,
+            current: None,
+            history: vec![],
+        }
+    }
+
+    /// The prompt to show before reading a command: the ID of the current node, if any.
+    pub fn prompt(&self) -> String {
+        match self.current {
+            Some(node) => format!("{}> ", self.graph.nodes()[node].id),
+            None => "> ".to_owned(),
+        }
+    }
+
+    fn label(&self, node: NodeIndex) -> Option<String> {
+        self.graph.nodes()[node]
+            .attributes
+            .iter()
+            .find(|attribute| matches!(attribute, Attribute::Label(_)))
+            .map(Attribute::value_string)
+    }
+
+    fn write_node<W: Write>(&self, mut writer: W, node: NodeIndex) -> io::Result<()> {
+        match self.label(node) {
+            Some(label) => writeln!(writer, "{} ({})", self.graph.nodes()[node].id, label),
+            None => writeln!(writer, "{}", self.graph.nodes()[node].id),
+        }
+    }
+
+    /// The edges of `node`, with the node at their other end, out-edges first.
+    fn edges(&self, node: NodeIndex) -> Vec<(usize, NodeIndex)> {
+        let edges = self.graph.edges().iter().enumerate();
+        let outgoing = edges
+            .clone()
+            .filter(|(_, edge)| edge.tail == node)
+            .map(|(index, edge)| (index, edge.head));
+        let incoming = edges
+            .filter(|(_, edge)| edge.head == node && edge.tail != node)
+            .map(|(index, edge)| (index, edge.tail));
+        outgoing.chain(incoming).collect()
+    }
+
+    fn show<W: Write>(&self, mut writer: W, node: NodeIndex) -> io::Result<()> {
+        let graph = self.graph;
+        writeln!(writer, "node {}", quote_id(&graph.nodes()[node].id))?;
+        write_attributes(&mut writer, &graph.nodes()[node].attributes)?;
+        for (number, (index, other)) in self.edges(node).into_iter().enumerate() {
+            let arrow = match (graph.kind, graph.edges()[index].tail == node) {
+                (GraphKind::Undirected, _) => "--",
+                (GraphKind::Directed, true) => "->",
+                (GraphKind::Directed, false) => "<-",
+            };
+            write!(
+                writer,
+                "  {}. {} {}",
+                number + 1,
+                arrow,
+                quote_id(&graph.nodes()[other].id)
+            )?;
+            let attributes = &graph.edges()[index].attributes;
+            if attributes.is_empty() {
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, " [{}]", attribute_list(attributes))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn go<W: Write>(&mut self, writer: W, node: NodeIndex) -> io::Result<()> {
+        if let Some(current) = self.current.replace(node) {
+            self.history.push(current);
+        }
+        self.show(writer, node)
+    }
+
+    /// Runs one command line, writing its output to `writer`. Returns whether to go on
+    /// exploring, `false` after `quit`.
+    pub fn execute<W: Write>(&mut self, line: &str, mut writer: W) -> io::Result<bool> {
+        let line = line.trim();
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        match command {
+            "" => {}
+            "quit" | "exit" => return Ok(false),
+            "help" | "?" => writeln!(writer, "{}", HELP)?,
+            "graph" => {
+                let kind = match self.graph.kind {
+                    GraphKind::Directed => "digraph",
+                    GraphKind::Undirected => "graph",
+                };
+                let name = match &self.graph.id {
+                    Some(id) => format!("{} {}", kind, quote_id(id)),
+                    None => kind.to_owned(),
+                };
+                writeln!(
+                    writer,
+                    "{}: {} nodes, {} edges",
+                    name,
+                    self.graph.nodes().len(),
+                    self.graph.edges().len()
+                )?;
+                write_attributes(&mut writer, &self.graph.attributes)?;
+            }
+            "nodes" => {
+                for node in 0..self.graph.nodes().len() {
+                    self.write_node(&mut writer, node)?;
+                }
+            }
+            "go" => match self.graph.node_index(argument) {
+                Some(node) => self.go(writer, node)?,
+                None => writeln!(writer, "no node `{}`", argument)?,
+            },
+            "show" => match self.current {
+                Some(node) => self.show(writer, node)?,
+                None => writeln!(writer, "no current node; use `go ID`")?,
+            },
+            "back" => match self.history.pop() {
+                Some(node) => {
+                    self.current = Some(node);
+                    self.show(writer, node)?;
+                }
+                None => writeln!(writer, "no previous node")?,
+            },
+            "find" => {
+                let text = argument.to_lowercase();
+                for node in 0..self.graph.nodes().len() {
+                    let id = self.graph.nodes()[node].id.to_lowercase();
+                    let label = self.label(node).unwrap_or_default().to_lowercase();
+                    if id.contains(&text) || label.contains(&text) {
+                        self.write_node(&mut writer, node)?;
+                    }
+                }
+            }
+            "query" => match argument.parse::<Selector>() {
+                Ok(selector) => {
+                    for node in selector.nodes(self.graph) {
+                        self.write_node(&mut writer, node)?;
+                    }
+                    let op = match self.graph.kind {
+                        GraphKind::Directed => "->",
+                        GraphKind::Undirected => "--",
+                    };
+                    for index in selector.edges(self.graph) {
+                        let edge = &self.graph.edges()[index];
+                        writeln!(
+                            writer,
+                            "{} {} {}",
+                            quote_id(&self.graph.nodes()[edge.tail].id),
+                            op,
+                            quote_id(&self.graph.nodes()[edge.head].id)
+                        )?;
+                    }
+                }
+                Err(e) => writeln!(writer, "{}", e)?,
+            },
+            number => match (number.parse::<usize>(), self.current) {
+                (Ok(number), Some(node)) => {
+                    match number
+                        .checked_sub(1)
+                        .and_then(|number| self.edges(node).get(number).copied())
+                    {
+                        Some((_, other)) => self.go(writer, other)?,
+                        None => writeln!(writer, "no edge {}", number)?,
+                    }
+                }
+                (Ok(_), None) => writeln!(writer, "no current node; use `go ID`")?,
+                (Err(_), _) => writeln!(writer, "unknown command `{}`; try `help`", command)?,
+            },
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn explore() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph G { node [shape=box] web [label="Web frontend"] web -> db [color=red] cache -> web }"#,
+            )
+            .unwrap(),
+        );
+        let mut explorer = Explorer::new(&graph);
+        let mut run = |line: &str| {
+            let mut output = vec![];
+            assert!(explorer.execute(line, &mut output).unwrap());
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(run("graph"), "digraph G: 3 nodes, 2 edges\n");
+        assert_eq!(run("find FRONT"), "web (Web frontend)\n");
+        assert_eq!(
+            run("go web"),
+            r#"node web
+    shape = box
+    label = "Web frontend"
+  1. -> db [color=red]
+  2. <- cache
+"#
+        );
+        assert_eq!(run("2"), "node cache\n    shape = box\n  1. -> web\n");
+        assert_eq!(run("back").lines().next(), Some("node web"));
+        assert_eq!(run("3"), "no edge 3\n");
+        assert_eq!(run("query edge[color=red]"), "web -> db\n");
+        assert_eq!(
+            run("frobnicate"),
+            "unknown command `frobnicate`; try `help`\n"
+        );
+        assert_eq!(explorer.prompt(), "web> ");
+        assert!(!explorer.execute("quit", io::sink()).unwrap());
+    }
+}
@@ -0,0 +1,174 @@
+//! DOT output.
+//!
+//! [`write_ir`] pretty-prints the intermediate representation in a canonical layout: one statement
+//! per line, indented by four spaces, with `name=value` attributes separated by `, `. Attribute
+//! lists which would make a line longer than [`MAX_WIDTH`] are broken onto one attribute per line.
+//! Only IDs that need it are quoted. Attribute values are written in their normalized form, so
+//! e.g. `width=1.50` becomes `width=1.5`. [`write`] does the same for a [`model::Graph`], declaring
+//! every node and edge with its effective attributes.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::Attribute,
+    ir::{self, parse_graph, quote_id, AttributeKind, GraphKind, GraphParseError, Statement},
+    model,
+};
+
+/// Line width past which attribute lists are broken onto several lines.
+pub const MAX_WIDTH: usize = 100;
+
+const INDENT: &str = "    ";
+
+fn attribute(attribute: &Attribute) -> String {
+    format!(
+        "{}={}",
+        attribute.name(),
+        quote_id(&attribute.value_string())
+    )
+}
+
+/// Writes `head` followed by its attribute list, if any, on one line or, if that is too long, with
+/// one attribute per line.
+fn write_statement<W: Write>(
+    writer: &mut W,
+    head: &str,
+    attributes: &[Attribute],
+) -> io::Result<()> {
+    if attributes.is_empty() {
+        return writeln!(writer, "{}{}", INDENT, head);
+    }
+    let attributes = attributes.iter().map(attribute).collect::<Vec<_>>();
+    let line = format!("{}{} [{}]", INDENT, head, attributes.join(", "));
+    if line.chars().count() <= MAX_WIDTH || attributes.len() == 1 {
+        return writeln!(writer, "{}", line);
+    }
+    writeln!(writer, "{}{} [", INDENT, head)?;
+    for attribute in attributes {
+        writeln!(writer, "{}{}{},", INDENT, INDENT, attribute)?;
+    }
+    writeln!(writer, "{}]", INDENT)
+}
+
+/// Pretty-prints `graph` as DOT.
+pub fn write_ir<W: Write>(graph: &ir::Graph, mut writer: W) -> io::Result<()> {
+    if graph.strict {
+        write!(writer, "strict ")?;
+    }
+    match graph.kind {
+        GraphKind::Directed => write!(writer, "digraph ")?,
+        GraphKind::Undirected => write!(writer, "graph ")?,
+    }
+    if let Some(id) = &graph.id {
+        write!(writer, "{} ", quote_id(id))?;
+    }
+    if graph.statements.is_empty() {
+        return writeln!(writer, "{{}}");
+    }
+    writeln!(writer, "{{")?;
+    for statement in &graph.statements {
+        match statement {
+            Statement::Attribute(statement) if statement.attributes.is_empty() => {}
+            Statement::Attribute(statement) => {
+                let kind = match statement.kind {
+                    AttributeKind::Graph => "graph",
+                    AttributeKind::Node => "node",
+                    AttributeKind::Edge => "edge",
+                };
+                write_statement(&mut writer, kind, &statement.attributes)?;
+            }
+            Statement::Node(statement) => write_statement(
+                &mut writer,
+                &quote_id(&statement.name),
+                &statement.attributes,
+            )?,
+            Statement::Edge(statement) => {
+                let end = |index: usize| {
+                    let id = quote_id(&statement.list[index]);
+                    match statement.ports.get(index).and_then(Option::as_ref) {
+                        Some(port) => format!("{}:{}", id, quote_id(port)),
+                        None => id.into_owned(),
+                    }
+                };
+                let mut head = end(0);
+                for (index, op) in statement.ops.iter().enumerate() {
+                    head = format!("{} {} {}", head, op, end(index + 1));
+                }
+                write_statement(&mut writer, &head, &statement.attributes)?;
+            }
+            Statement::Definition(statement) => writeln!(
+                writer,
+                "{}{}={}",
+                INDENT,
+                quote_id(&statement.lhs),
+                quote_id(&statement.rhs)
+            )?,
+        }
+    }
+    writeln!(writer, "}}")
+}
+
+/// Writes `graph` as DOT.
+pub fn write<W: Write>(graph: &model::Graph, writer: W) -> io::Result<()> {
+    write_ir(&graph.to_ir(), writer)
+}
+
+/// Parses `input` and pretty-prints it with [`write_ir`].
+pub fn format(input: &str) -> Result<String, GraphParseError<&str>> {
+    let graph = parse_graph(input)?;
+    let mut output = vec![];
+    write_ir(&graph, &mut output).expect("writing to a vector doesn't fail");
+    Ok(String::from_utf8(output).expect("DOT output is UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pretty_print() {
+        let input = r#"strict digraph "my graph" {rankdir=LR node[shape=box]
+a[label="A \"quoted\" label",width=1.50]   a->b->"c d"[color=red]
+graph [label="a fairly long graph label, which together with the other attributes" fontsize=12 fontname=Helvetica]
+}"#;
+        let formatted = format(input).unwrap();
+        assert_eq!(
+            formatted,
+            r#"strict digraph "my graph" {
+    rankdir=LR
+    node [shape=box]
+    a [label="A \"quoted\" label", width=1.5]
+    a -> b -> "c d" [color=red]
+    graph [
+        label="a fairly long graph label, which together with the other attributes",
+        fontsize=12,
+        fontname=Helvetica,
+    ]
+}
+"#
+        );
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(format("graph{}").unwrap(), "graph {}\n");
+    }
+
+    #[test]
+    fn model() {
+        let graph = model::Graph::from_ir(
+            &parse_graph("digraph { label=G node [shape=box] a -> b -> c [color=red] }").unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "digraph {
+    graph [label=G]
+    a [shape=box]
+    b [shape=box]
+    c [shape=box]
+    a -> b [color=red]
+    b -> c [color=red]
+}
+"
+        );
+    }
+}
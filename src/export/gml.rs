@@ -0,0 +1,102 @@
+//! GML export.
+//!
+//! Nodes are numbered by their index, with their ID written as `name`. Attributes are written as
+//! keys with numeric values where possible, except that node positions are written as `graphics`
+//! `x` and `y` (with `y` pointing down). Attributes whose DOT names aren't valid GML keys (such
+//! as `_background`) are omitted.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::{Attribute, Position},
+    gml::{write_list, GmlValue},
+    model::Graph,
+};
+
+fn is_key(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn value(text: String) -> GmlValue {
+    if let Ok(value) = text.parse() {
+        GmlValue::Integer(value)
+    } else if let Ok(value) = text.parse::<f64>() {
+        GmlValue::Real(value)
+    } else {
+        GmlValue::String(text)
+    }
+}
+
+fn attribute_pairs(attributes: &[Attribute]) -> impl Iterator<Item = (String, GmlValue)> + '_ {
+    attributes
+        .iter()
+        .filter(|attribute| is_key(attribute.name()))
+        .map(|attribute| (attribute.name().to_owned(), value(attribute.value_string())))
+}
+
+/// Writes `graph` as a GML document.
+pub fn write<W: Write>(graph: &Graph, mut writer: W) -> io::Result<()> {
+    let mut list = vec![(
+        "directed".to_owned(),
+        GmlValue::Integer(graph.is_directed().into()),
+    )];
+    list.extend(attribute_pairs(&graph.attributes));
+    for (i, node) in graph.nodes().iter().enumerate() {
+        let mut pairs = vec![
+            ("id".to_owned(), GmlValue::Integer(i as i64)),
+            ("name".to_owned(), GmlValue::String(node.id.clone())),
+        ];
+        let mut graphics = vec![];
+        for attribute in &node.attributes {
+            if let Attribute::Pos(Position::Point(point) | Position::Pinned(point)) = attribute {
+                graphics.push(("x".to_owned(), GmlValue::Real(point.x)));
+                graphics.push(("y".to_owned(), GmlValue::Real(-point.y)));
+            }
+        }
+        pairs.extend(attribute_pairs(&node.attributes).filter(|(key, _)| key != "pos"));
+        if !graphics.is_empty() {
+            pairs.push(("graphics".to_owned(), GmlValue::List(graphics)));
+        }
+        list.push(("node".to_owned(), GmlValue::List(pairs)));
+    }
+    for edge in graph.edges() {
+        let mut pairs = vec![
+            ("source".to_owned(), GmlValue::Integer(edge.tail as i64)),
+            ("target".to_owned(), GmlValue::Integer(edge.head as i64)),
+        ];
+        pairs.extend(attribute_pairs(&edge.attributes));
+        list.push(("edge".to_owned(), GmlValue::List(pairs)));
+    }
+    write_list(
+        &mut writer,
+        &[("graph".to_owned(), GmlValue::List(list))],
+        0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{import::gml::read, ir::parse_graph};
+
+    #[test]
+    fn round_trip() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph { label="say \"hi\"" a [pos="1,2", shape=box] a -> b [weight=2] }"#,
+            )
+            .unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("  label \"say &quot;hi&quot;\"\n"));
+        assert!(output.contains("    graphics [\n      x 1.0\n      y -2.0\n    ]\n"));
+        assert!(output.contains("    weight 2\n"));
+        let read = read(&output).unwrap();
+        assert_eq!(read.attributes, graph.attributes);
+        assert_eq!(read.node("a").unwrap().attributes.len(), 2);
+        assert_eq!(read.edges(), graph.edges());
+    }
+}
@@ -0,0 +1,195 @@
+//! graph6 and sparse6 export.
+//!
+//! Nodes are numbered in order. graph6 encodes simple graphs, so parallel edges are merged and
+//! graphs with loops are rejected; sparse6 encodes both. Directed graphs are rejected by either.
+
+use std::io::{self, Write};
+
+use crate::{import::graph6::bit_width, model::Graph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graph6Format {
+    Graph6,
+    Sparse6,
+}
+
+/// Packs bits into 6-bit groups, most significant first.
+#[derive(Default)]
+struct Bits {
+    groups: Vec<u8>,
+    count: usize,
+}
+
+impl Bits {
+    fn push(&mut self, bit: bool) {
+        if self.count.is_multiple_of(6) {
+            self.groups.push(0);
+        }
+        if bit {
+            *self.groups.last_mut().unwrap() |= 1 << (5 - self.count % 6);
+        }
+        self.count += 1;
+    }
+
+    fn push_value(&mut self, value: usize, width: usize) {
+        for i in (0..width).rev() {
+            self.push(value >> i & 1 == 1);
+        }
+    }
+
+    /// Bits needed to complete the last group.
+    fn padding(&self) -> usize {
+        (6 - self.count % 6) % 6
+    }
+
+    fn into_string(self) -> String {
+        self.groups
+            .into_iter()
+            .map(|g| char::from(g + 63))
+            .collect()
+    }
+}
+
+fn vertex_count(n: usize) -> String {
+    let mut bits = Bits::default();
+    let prefix = if n <= 62 {
+        bits.push_value(n, 6);
+        ""
+    } else if n <= 258047 {
+        bits.push_value(n, 18);
+        "~"
+    } else {
+        bits.push_value(n, 36);
+        "~~"
+    };
+    format!("{}{}", prefix, bits.into_string())
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn encode_graph6(graph: &Graph) -> io::Result<String> {
+    let n = graph.nodes().len();
+    let mut adjacent = vec![false; n * n];
+    for edge in graph.edges() {
+        if edge.tail == edge.head {
+            return Err(invalid("graph6 can't encode loops; use sparse6"));
+        }
+        adjacent[edge.tail * n + edge.head] = true;
+        adjacent[edge.head * n + edge.tail] = true;
+    }
+    let mut bits = Bits::default();
+    for j in 1..n {
+        for i in 0..j {
+            bits.push(adjacent[i * n + j]);
+        }
+    }
+    for _ in 0..bits.padding() {
+        bits.push(false);
+    }
+    Ok(vertex_count(n) + &bits.into_string())
+}
+
+fn encode_sparse6(graph: &Graph) -> String {
+    let n = graph.nodes().len();
+    let k = bit_width(n);
+    let mut edges = graph
+        .edges()
+        .iter()
+        .map(|edge| (edge.tail.max(edge.head), edge.tail.min(edge.head)))
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+    let mut bits = Bits::default();
+    let mut v = 0;
+    for (j, i) in edges {
+        if j == v {
+            bits.push(false);
+        } else {
+            bits.push(true);
+            if j > v + 1 {
+                bits.push_value(j, k);
+                bits.push(false);
+            }
+            v = j;
+        }
+        bits.push_value(i, k);
+    }
+    let padding = bits.padding();
+    // padding with ones could otherwise decode as an extra edge on the last vertex
+    if k < 6 && n == 1 << k && padding > k && v == n - 2 {
+        bits.push(false);
+    }
+    for _ in 0..bits.padding() {
+        bits.push(true);
+    }
+    format!(":{}{}", vertex_count(n), bits.into_string())
+}
+
+/// Encodes an undirected graph as a graph6 or sparse6 string.
+pub fn encode(graph: &Graph, format: Graph6Format) -> io::Result<String> {
+    if graph.is_directed() {
+        return Err(invalid("graph6 and sparse6 only encode undirected graphs"));
+    }
+    match format {
+        Graph6Format::Graph6 => encode_graph6(graph),
+        Graph6Format::Sparse6 => Ok(encode_sparse6(graph)),
+    }
+}
+
+/// Writes `graph` as a line in the given format.
+pub fn write<W: Write>(graph: &Graph, mut writer: W, format: Graph6Format) -> io::Result<()> {
+    writeln!(writer, "{}", encode(graph, format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        import::graph6::{decode, read},
+        ir::parse_graph,
+    };
+
+    /// Edges as `(tail, head)` pairs, in the order they were added.
+    fn edges(graph: &Graph) -> Vec<(usize, usize)> {
+        graph.edges().iter().map(|e| (e.tail, e.head)).collect()
+    }
+
+    fn sorted(mut edges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        edges.sort_unstable();
+        edges
+    }
+
+    #[test]
+    fn known_strings() {
+        // examples from the format description
+        let graph = decode("DQc").unwrap();
+        assert_eq!(graph.nodes().len(), 5);
+        assert_eq!(edges(&graph), vec![(0, 2), (1, 3), (0, 4), (3, 4)]);
+        assert_eq!(encode(&graph, Graph6Format::Graph6).unwrap(), "DQc");
+
+        let graph = decode(":Fa@x^").unwrap();
+        assert_eq!(graph.nodes().len(), 7);
+        assert_eq!(edges(&graph), vec![(0, 1), (0, 2), (1, 2), (5, 6)]);
+        assert_eq!(encode(&graph, Graph6Format::Sparse6).unwrap(), ":Fa@x^");
+        assert_eq!(vertex_count(258047), "~}~~");
+    }
+
+    #[test]
+    fn round_trip() {
+        let graph =
+            Graph::from_ir(&parse_graph("graph { a -- b b -- c c -- c a -- b d e -- f }").unwrap());
+        let sparse6 = encode(&graph, Graph6Format::Sparse6).unwrap();
+        let read = read(&format!(">>sparse6<<{}\n", sparse6)).unwrap();
+        assert_eq!(sorted(edges(&read[0])), sorted(edges(&graph)));
+        assert!(encode(&graph, Graph6Format::Graph6).is_err());
+
+        // the padding special case: n = 4, edge on vertex 2 but not 3
+        let graph = Graph::from_ir(&parse_graph("graph { 0 -- 2 1 3 }").unwrap());
+        let graph = decode(&encode(&graph, Graph6Format::Sparse6).unwrap()).unwrap();
+        assert_eq!(graph.edges().len(), 1);
+
+        let directed = Graph::from_ir(&parse_graph("digraph { a -> b }").unwrap());
+        assert!(encode(&directed, Graph6Format::Graph6).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! GraphML export, suitable for loading into tools like yEd and Gephi.
+//!
+//! Attributes are written as GraphML `data` elements, with one `key` declared per attribute name
+//! and element kind.
+
+use std::io::{self, Write};
+
+use crate::{attribute::Attribute, model::Graph};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Domain {
+    Graph,
+    Node,
+    Edge,
+}
+
+impl Domain {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Domain::Graph => "graph",
+            Domain::Node => "node",
+            Domain::Edge => "edge",
+        }
+    }
+}
+
+/// Escapes text for use in XML attribute values and character data.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Keys(Vec<(Domain, &'static str)>);
+
+impl Keys {
+    fn collect(graph: &Graph) -> Self {
+        let mut keys = Keys(vec![]);
+        keys.add(Domain::Graph, &graph.attributes);
+        for node in graph.nodes() {
+            keys.add(Domain::Node, &node.attributes);
+        }
+        for edge in graph.edges() {
+            keys.add(Domain::Edge, &edge.attributes);
+        }
+        keys
+    }
+
+    fn add(&mut self, domain: Domain, attributes: &[Attribute]) {
+        for attribute in attributes {
+            if self.id(domain, attribute).is_none() {
+                self.0.push((domain, attribute.name()));
+            }
+        }
+    }
+
+    fn id(&self, domain: Domain, attribute: &Attribute) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|&(d, name)| d == domain && name == attribute.name())
+    }
+}
+
+fn write_data<W: Write>(
+    writer: &mut W,
+    keys: &Keys,
+    domain: Domain,
+    attributes: &[Attribute],
+    indent: &str,
+) -> io::Result<()> {
+    for attribute in attributes {
+        if let Some(id) = keys.id(domain, attribute) {
+            writeln!(
+                writer,
+                "{}<data key=\"d{}\">{}</data>",
+                indent,
+                id,
+                escape(&attribute.value_string())
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `graph` as a GraphML document.
+pub fn write<W: Write>(graph: &Graph, mut writer: W) -> io::Result<()> {
+    let keys = Keys::collect(graph);
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+    )?;
+    for (id, (domain, name)) in keys.0.iter().enumerate() {
+        writeln!(
+            writer,
+            r#"  <key id="d{}" for="{}" attr.name="{}" attr.type="string"/>"#,
+            id,
+            domain.as_str(),
+            name
+        )?;
+    }
+    writeln!(
+        writer,
+        r#"  <graph id="{}" edgedefault="{}">"#,
+        escape(graph.id.as_deref().unwrap_or("G")),
+        if graph.is_directed() {
+            "directed"
+        } else {
+            "undirected"
+        }
+    )?;
+    write_data(&mut writer, &keys, Domain::Graph, &graph.attributes, "    ")?;
+    for node in graph.nodes() {
+        if node.attributes.is_empty() {
+            writeln!(writer, r#"    <node id="{}"/>"#, escape(&node.id))?;
+        } else {
+            writeln!(writer, r#"    <node id="{}">"#, escape(&node.id))?;
+            write_data(&mut writer, &keys, Domain::Node, &node.attributes, "      ")?;
+            writeln!(writer, "    </node>")?;
+        }
+    }
+    for (id, edge) in graph.edges().iter().enumerate() {
+        let (source, target) = (&graph.nodes()[edge.tail].id, &graph.nodes()[edge.head].id);
+        write!(
+            writer,
+            r#"    <edge id="e{}" source="{}" target="{}""#,
+            id,
+            escape(source),
+            escape(target)
+        )?;
+        if edge.attributes.is_empty() {
+            writeln!(writer, "/>")?;
+        } else {
+            writeln!(writer, ">")?;
+            write_data(&mut writer, &keys, Domain::Edge, &edge.attributes, "      ")?;
+            writeln!(writer, "    </edge>")?;
+        }
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn graphml() {
+        let graph = Graph::from_ir(
+            &parse_graph(r#"digraph { "a&b" [shape=box] "a&b" -> c [style=dashed] }"#).unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let body = output.lines().skip(2).collect::<Vec<_>>().join("\n");
+        assert_eq!(
+            body,
+            r#"  <key id="d0" for="node" attr.name="shape" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="style" attr.type="string"/>
+  <graph id="G" edgedefault="directed">
+    <node id="a&amp;b">
+      <data key="d0">box</data>
+    </node>
+    <node id="c"/>
+    <edge id="e0" source="a&amp;b" target="c">
+      <data key="d1">dashed</data>
+    </edge>
+  </graph>
+</graphml>"#
+        );
+    }
+}
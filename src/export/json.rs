@@ -0,0 +1,173 @@
+//! Graphviz-compatible JSON output, following the structure produced by `dot -Tdot_json` and
+//! `dot -Tjson`: graph attributes and metadata at the top level, nodes under `objects`, and edges
+//! referring to nodes by their `_gvid`.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::{Attribute, Rectangle},
+    json::JsonValue,
+    model::Graph,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonFormat {
+    /// Graph structure and attributes as given, like `-Tdot_json`.
+    DotJson,
+    /// Graph structure along with layout information (`bb`, and node `pos`, `width` and
+    /// `height`), like `-Tjson`. Every node must have a `pos` attribute, e.g. from running a
+    /// layout.
+    Json,
+}
+
+fn attribute_members(attributes: &[Attribute]) -> impl Iterator<Item = (String, JsonValue)> + '_ {
+    attributes
+        .iter()
+        .map(|attribute| (attribute.name().to_owned(), attribute.value_string().into()))
+}
+
+/// Bounding box of the laid out nodes, in points.
+fn bounding_box(graph: &Graph) -> io::Result<Rectangle> {
+    graph.bounding_box().ok_or_else(|| {
+        let node = graph.nodes().iter().find(|node| node.position().is_none());
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "node `{}` has no position; lay out the graph first",
+                node.map_or("", |node| node.id.as_str())
+            ),
+        )
+    })
+}
+
+pub(crate) fn to_json(graph: &Graph, format: JsonFormat) -> io::Result<JsonValue> {
+    let mut members = vec![
+        (
+            "name".to_owned(),
+            graph.id.as_deref().unwrap_or("%0").into(),
+        ),
+        ("directed".to_owned(), graph.is_directed().into()),
+        ("strict".to_owned(), graph.strict.into()),
+    ];
+    members.extend(attribute_members(&graph.attributes));
+    if format == JsonFormat::Json
+        && !graph
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::Bb(_)))
+    {
+        members.push(("bb".to_owned(), bounding_box(graph)?.to_string().into()));
+    }
+    members.push(("_subgraph_cnt".to_owned(), 0.into()));
+
+    let objects = graph
+        .nodes()
+        .iter()
+        .enumerate()
+        .map(|(gvid, node)| {
+            let mut members = vec![
+                ("_gvid".to_owned(), gvid.into()),
+                ("name".to_owned(), node.id.as_str().into()),
+            ];
+            members.extend(attribute_members(&node.attributes));
+            if format == JsonFormat::Json {
+                let (width, height) = node.size();
+                for (name, value) in [("width", width), ("height", height)] {
+                    if !members.iter().any(|(n, _)| n == name) {
+                        members.push((name.to_owned(), value.to_string().into()));
+                    }
+                }
+            }
+            JsonValue::Object(members)
+        })
+        .collect::<Vec<_>>();
+    if !objects.is_empty() {
+        members.push(("objects".to_owned(), JsonValue::Array(objects)));
+    }
+
+    let edges = graph
+        .edges()
+        .iter()
+        .enumerate()
+        .map(|(gvid, edge)| {
+            let mut members = vec![
+                ("_gvid".to_owned(), gvid.into()),
+                ("tail".to_owned(), edge.tail.into()),
+                ("head".to_owned(), edge.head.into()),
+            ];
+            members.extend(attribute_members(&edge.attributes));
+            JsonValue::Object(members)
+        })
+        .collect::<Vec<_>>();
+    if !edges.is_empty() {
+        members.push(("edges".to_owned(), JsonValue::Array(edges)));
+    }
+    Ok(JsonValue::Object(members))
+}
+
+/// Writes `graph` as Graphviz JSON in the given format.
+pub fn write<W: Write>(graph: &Graph, mut writer: W, format: JsonFormat) -> io::Result<()> {
+    to_json(graph, format)?.write_pretty(&mut writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn write_str(input: &str, format: JsonFormat) -> io::Result<String> {
+        let graph = Graph::from_ir(&parse_graph(input).unwrap());
+        let mut output = vec![];
+        write(&graph, &mut output, format)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn dot_json() {
+        assert_eq!(
+            write_str(
+                r#"digraph G { a [label="A \"1\""] a -> b }"#,
+                JsonFormat::DotJson
+            )
+            .unwrap(),
+            r#"{
+  "name": "G",
+  "directed": true,
+  "strict": false,
+  "_subgraph_cnt": 0,
+  "objects": [
+    {
+      "_gvid": 0,
+      "name": "a",
+      "label": "A \"1\""
+    },
+    {
+      "_gvid": 1,
+      "name": "b"
+    }
+  ],
+  "edges": [
+    {
+      "_gvid": 0,
+      "tail": 0,
+      "head": 1
+    }
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn json() {
+        let output = write_str(
+            r#"graph { a [pos="27,18"] b [pos="99,18", width=1] }"#,
+            JsonFormat::Json,
+        )
+        .unwrap();
+        assert!(output.contains(r#""bb": "0,0,135,36""#));
+        assert!(output.contains(r#""width": "0.75""#));
+        assert!(output.contains(r#""height": "0.5""#));
+        assert!(write_str("graph { a }", JsonFormat::Json).is_err());
+    }
+}
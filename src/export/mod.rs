@@ -0,0 +1,11 @@
+//! Writers serializing graphs into other graph formats.
+
+pub mod csv;
+pub mod dot;
+pub mod gml;
+pub mod graph6;
+pub mod graphml;
+pub mod json;
+pub mod pajek;
+pub mod plain;
+pub mod tgf;
@@ -0,0 +1,131 @@
+//! Pajek `.net` export.
+//!
+//! Nodes are written as vertices labeled with their ID, along with their coordinates if every node
+//! has a `pos`, normalized to `[0, 1]` with `y` pointing down. `box`, `ellipse`, `diamond` and
+//! `triangle` shapes are kept. Edges are written under `*Arcs` or `*Edges`, depending on the graph
+//! kind, with their weight and label. Pajek labels can't contain `"`, so it is replaced by `'`.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::{Attribute, Position, Shape},
+    model::Graph,
+};
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'"))
+}
+
+/// Node coordinates normalized to the unit square, if every node has a position.
+fn coordinates(graph: &Graph) -> Option<Vec<(f64, f64)>> {
+    let points = graph
+        .nodes()
+        .iter()
+        .map(|node| {
+            node.attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::Pos(Position::Point(point) | Position::Pinned(point)) => {
+                        Some((point.x, point.y))
+                    }
+                    _ => None,
+                })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &(x, y) in &points {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let span = (max_x - min_x).max(max_y - min_y);
+    let span = if span > 0.0 { span } else { 1.0 };
+    Some(
+        points
+            .into_iter()
+            .map(|(x, y)| ((x - min_x) / span, 1.0 - (y - min_y) / span))
+            .collect(),
+    )
+}
+
+/// Writes `graph` as a Pajek network.
+pub fn write<W: Write>(graph: &Graph, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "*Vertices {}", graph.nodes().len())?;
+    let coordinates = coordinates(graph);
+    for (i, node) in graph.nodes().iter().enumerate() {
+        write!(writer, "{} {}", i + 1, quote(&node.id))?;
+        if let Some(coordinates) = &coordinates {
+            let (x, y) = coordinates[i];
+            write!(writer, " {:.4} {:.4}", x, y)?;
+        }
+        for attribute in &node.attributes {
+            if let Attribute::Shape(
+                shape @ (Shape::Box | Shape::Ellipse | Shape::Diamond | Shape::Triangle),
+            ) = attribute
+            {
+                write!(writer, " {}", shape.as_str())?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    if !graph.edges().is_empty() {
+        writeln!(
+            writer,
+            "{}",
+            if graph.is_directed() {
+                "*Arcs"
+            } else {
+                "*Edges"
+            }
+        )?;
+    }
+    for edge in graph.edges() {
+        let mut weight = 1.0;
+        let mut label = None;
+        for attribute in &edge.attributes {
+            match attribute {
+                Attribute::Weight(value) => weight = *value,
+                Attribute::Label(value) => label = Some(value),
+                _ => {}
+            }
+        }
+        write!(writer, "{} {} {}", edge.tail + 1, edge.head + 1, weight)?;
+        if let Some(label) = label {
+            write!(writer, " l {}", quote(label))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{import::pajek::read, ir::parse_graph};
+
+    #[test]
+    fn round_trip() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph { a [shape=box, pos="0,0"] b [pos="72,36"] a -> b [weight=2, label="say \"hi\""] }"#,
+            )
+            .unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "*Vertices 2\n1 \"a\" 0.0000 1.0000 box\n2 \"b\" 1.0000 0.5000\n*Arcs\n1 2 2 l \"say 'hi'\"\n"
+        );
+        let read = read(&output).unwrap();
+        assert!(read.is_directed());
+        assert_eq!(
+            read.node("a").unwrap().attributes[1..],
+            graph.nodes()[0].attributes[..1]
+        );
+        assert_eq!(read.edges()[0].attributes[0], Attribute::Weight(2.0));
+    }
+}
@@ -0,0 +1,165 @@
+//! Graphviz `plain` and `plain-ext` output, describing a laid out graph one line per element:
+//!
+//! ```text
+//! graph scale width height
+//! node name x y width height label style shape color fillcolor
+//! edge tail head n x₁ y₁ .. xₙ yₙ [label xl yl] style color
+//! stop
+//! ```
+//!
+//! Coordinates and sizes are in inches, relative to the lower left corner of the drawing. Edges
+//! are drawn through the points of their `pos` attribute, or as straight lines between the node
+//! centers if they have none; edge labels are placed at the middle of the edge.
+
+use std::io::{self, Write};
+
+use crate::{
+    attribute::{Attribute, Point, Position},
+    ir::quote_id,
+    model::{Graph, POINTS_PER_INCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlainFormat {
+    Plain,
+    /// Like `plain`, but with ports in edge tails and heads. SimpleDOT has no ports, so this is
+    /// currently identical to `plain`.
+    PlainExt,
+}
+
+/// Formats a number with up to five decimals, like Graphviz.
+fn number(value: f64) -> String {
+    let text = format!("{:.5}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_owned(),
+        text => text.to_owned(),
+    }
+}
+
+fn value_or(attributes: &[Attribute], name: &str, default: &str) -> String {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name() == name)
+        .map_or_else(|| default.to_owned(), Attribute::value_string)
+}
+
+fn label(attributes: &[Attribute]) -> Option<&str> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Label(label) => Some(label.as_str()),
+        _ => None,
+    })
+}
+
+/// Writes the laid out `graph`. Fails if any node has no `pos` attribute.
+pub fn write<W: Write>(graph: &Graph, mut writer: W, _format: PlainFormat) -> io::Result<()> {
+    let bb = graph.bounding_box().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "every node needs a position; lay out the graph first",
+        )
+    })?;
+    let origin = &bb.lower_left;
+    let inches = |point: &Point| {
+        format!(
+            "{} {}",
+            number((point.x - origin.x) / POINTS_PER_INCH),
+            number((point.y - origin.y) / POINTS_PER_INCH)
+        )
+    };
+    writeln!(
+        writer,
+        "graph 1 {} {}",
+        number((bb.upper_right.x - bb.lower_left.x) / POINTS_PER_INCH),
+        number((bb.upper_right.y - bb.lower_left.y) / POINTS_PER_INCH)
+    )?;
+    for node in graph.nodes() {
+        let (width, height) = node.size();
+        let text = label(&node.attributes)
+            .map_or_else(|| node.id.clone(), |label| label.replace("\\N", &node.id));
+        // the bounding box exists, so every node has a position
+        let pos = node.position().unwrap();
+        writeln!(
+            writer,
+            "node {} {} {} {} {} {} {} {} {}",
+            quote_id(&node.id),
+            inches(pos),
+            number(width),
+            number(height),
+            quote_id(&text),
+            value_or(&node.attributes, "style", "solid"),
+            value_or(&node.attributes, "shape", "ellipse"),
+            quote_id(&value_or(&node.attributes, "color", "black")),
+            quote_id(&value_or(&node.attributes, "fillcolor", "lightgrey")),
+        )?;
+    }
+    for edge in graph.edges() {
+        let (tail, head) = (&graph.nodes()[edge.tail], &graph.nodes()[edge.head]);
+        let points = edge
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Pos(Position::Spline(points)) => Some(points.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                let (from, to) = (tail.position().unwrap(), head.position().unwrap());
+                (0..4)
+                    .map(|i| Point {
+                        x: from.x + (to.x - from.x) * i as f64 / 3.0,
+                        y: from.y + (to.y - from.y) * i as f64 / 3.0,
+                    })
+                    .collect()
+            });
+        write!(
+            writer,
+            "edge {} {} {}",
+            quote_id(&tail.id),
+            quote_id(&head.id),
+            points.len()
+        )?;
+        for point in &points {
+            write!(writer, " {}", inches(point))?;
+        }
+        if let Some(text) = label(&edge.attributes) {
+            let middle = &points[points.len() / 2];
+            write!(writer, " {} {}", quote_id(text), inches(middle))?;
+        }
+        writeln!(
+            writer,
+            " {} {}",
+            value_or(&edge.attributes, "style", "solid"),
+            quote_id(&value_or(&edge.attributes, "color", "black"))
+        )?;
+    }
+    writeln!(writer, "stop")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn plain() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph { a [pos="27,18"] b [pos="27,90", label="B \N", shape=box] a -> b [label=x] }"#,
+            )
+            .unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output, PlainFormat::Plain).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"graph 1 0.75 1.5
+node a 0.375 0.25 0.75 0.5 a solid ellipse black lightgrey
+node b 0.375 1.25 0.75 0.5 "B b" solid box black lightgrey
+edge a b 4 0.375 0.25 0.375 0.58333 0.375 0.91667 0.375 1.25 x 0.375 0.91667 solid black
+stop
+"#
+        );
+        let unplaced = Graph::from_ir(&parse_graph("graph { a }").unwrap());
+        assert!(write(&unplaced, vec![], PlainFormat::PlainExt).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Trivial Graph Format export.
+//!
+//! Node IDs are written as TGF IDs when none contain whitespace, so that they survive a round
+//! trip; otherwise nodes are numbered, and the ID is written as the label of nodes without a
+//! `label` attribute. Labels are the only attributes written.
+
+use std::io::{self, Write};
+
+use crate::{attribute::Attribute, model::Graph};
+
+fn label(attributes: &[Attribute]) -> Option<&str> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Label(label) => Some(label.as_str()),
+        _ => None,
+    })
+}
+
+/// Writes `graph` as a TGF document.
+pub fn write<W: Write>(graph: &Graph, mut writer: W) -> io::Result<()> {
+    let numbered = graph
+        .nodes()
+        .iter()
+        .any(|node| node.id.is_empty() || node.id.contains(char::is_whitespace));
+    let ids = graph
+        .nodes()
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            if numbered {
+                (i + 1).to_string()
+            } else {
+                node.id.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    for (node, id) in graph.nodes().iter().zip(&ids) {
+        let label = label(&node.attributes).or(if numbered { Some(&node.id) } else { None });
+        match label {
+            Some(label) => writeln!(writer, "{} {}", id, label.replace('\n', " "))?,
+            None => writeln!(writer, "{}", id)?,
+        }
+    }
+    writeln!(writer, "#")?;
+    for edge in graph.edges() {
+        write!(writer, "{} {}", ids[edge.tail], ids[edge.head])?;
+        if let Some(label) = label(&edge.attributes) {
+            write!(writer, " {}", label.replace('\n', " "))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn write_str(input: &str) -> String {
+        let graph = Graph::from_ir(&parse_graph(input).unwrap());
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn tgf() {
+        assert_eq!(
+            write_str(r#"digraph { a [label="A node"] a -> b [label=x] }"#),
+            "a A node\nb\n#\na b x\n"
+        );
+        assert_eq!(
+            write_str(r#"digraph { "a b" -> c }"#),
+            "1 a b\n2 c\n#\n1 2\n"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! Fonts of labels, for the room their text takes: [`find`] resolves a `fontname` to a [`Font`],
+//! whose [`Font::width`] measures text set in it, for sizing nodes to their labels and placing
+//! text when rendering.
+//!
+//! Fonts are looked up among the TrueType and OpenType files in the directories of the graph's
+//! `fontpath` and of the `DOTFONTPATH` environment variable and, with the `fonts` feature, in the
+//! system's font directories. A name such as `Helvetica-Bold` or `DejaVu Sans:bold` gives the
+//! family of the font and whether it's bold or italic. A family that isn't found falls back to
+//! the fonts made to stand in for the closest standard PostScript font, such as Arial or
+//! Liberation Sans for Helvetica, and in the end to the metrics of that standard font, which are
+//! built in; without fonts on disk, these are the metrics of all text.
+//!
+//! Text is measured in the forms its characters are drawn in, joined with the `shaping` feature,
+//! and without its combining marks; [`visual`] gives the order they're drawn in.
+
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::{attribute::Attribute, model::Graph};
+
+mod shaping;
+mod standard;
+mod truetype;
+
+pub use shaping::{is_mark, is_rtl, visual};
+use truetype::{Description, Face};
+
+/// Distance between baselines of successive lines of a label, relative to the font size.
+pub const LINE_HEIGHT: f64 = 1.2;
+
+/// Fonts made with the metrics of each standard family, to fall back to, most faithful first.
+const SUBSTITUTES: &[(&str, &[&str])] = &[
+    (
+        "Times",
+        &[
+            "Times New Roman",
+            "Nimbus Roman",
+            "Nimbus Roman No9 L",
+            "Liberation Serif",
+            "Tinos",
+            "TeX Gyre Termes",
+            "DejaVu Serif",
+        ],
+    ),
+    (
+        "Helvetica",
+        &[
+            "Arial",
+            "Nimbus Sans",
+            "Nimbus Sans L",
+            "Liberation Sans",
+            "Arimo",
+            "TeX Gyre Heros",
+            "DejaVu Sans",
+        ],
+    ),
+    (
+        "Courier",
+        &[
+            "Courier New",
+            "Nimbus Mono PS",
+            "Nimbus Mono L",
+            "Liberation Mono",
+            "Cousine",
+            "TeX Gyre Cursor",
+            "DejaVu Sans Mono",
+        ],
+    ),
+];
+
+/// Words of font names giving the style of a font rather than its family.
+const STYLE_WORDS: &[&str] = &[
+    "bold", "italic", "oblique", "roman", "regular", "book", "normal", "medium",
+];
+
+/// How deep font directories are searched for fonts.
+const MAX_DEPTH: usize = 8;
+
+/// The name of the one of the standard PostScript fonts, which every PDF and PostScript reader
+/// has, closest to the font `name`: Times by default, or Helvetica or Courier, in bold and italic
+/// as `name` says.
+pub fn standard_font(name: &str) -> &'static str {
+    let name = name.to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+    let (bold, italic) = (has(&["bold"]), has(&["italic", "oblique"]));
+    if has(&["symbol"]) {
+        return "Symbol";
+    }
+    let fonts = if has(&["courier", "mono", "fixed", "consol"]) {
+        [
+            "Courier",
+            "Courier-Bold",
+            "Courier-Oblique",
+            "Courier-BoldOblique",
+        ]
+    } else if has(&["helvetica", "arial", "sans", "verdana"]) {
+        [
+            "Helvetica",
+            "Helvetica-Bold",
+            "Helvetica-Oblique",
+            "Helvetica-BoldOblique",
+        ]
+    } else {
+        [
+            "Times-Roman",
+            "Times-Bold",
+            "Times-Italic",
+            "Times-BoldItalic",
+        ]
+    };
+    fonts[usize::from(bold) + 2 * usize::from(italic)]
+}
+
+/// A font to measure text with.
+#[derive(Debug)]
+pub struct Font {
+    name: String,
+    metrics: Metrics,
+}
+
+#[derive(Debug)]
+enum Metrics {
+    Standard(&'static [u16; 95]),
+    Face(Face),
+}
+
+impl Font {
+    /// The built-in metrics of the standard PostScript font closest to `name`.
+    pub fn standard(name: &str) -> Font {
+        let name = standard_font(name);
+        Font {
+            name: name.to_owned(),
+            metrics: Metrics::Standard(standard::widths(name)),
+        }
+    }
+
+    /// The full name of the font, or the name of the standard font whose metrics it has.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the font is one of the standard fonts with built-in metrics, rather than a font
+    /// found on disk.
+    pub fn is_standard(&self) -> bool {
+        matches!(self.metrics, Metrics::Standard(_))
+    }
+
+    /// The width in points of `text` set in the font at `size` points.
+    pub fn width(&self, text: &str, size: f64) -> f64 {
+        let text = shaping::shape(text);
+        let text = text.chars().filter(|&c| !is_mark(c));
+        match &self.metrics {
+            Metrics::Standard(widths) => {
+                let width = text.map(|c| u32::from(standard::width(widths, c)));
+                f64::from(width.sum::<u32>()) * size / 1000.0
+            }
+            Metrics::Face(face) => text.map(|c| face.advance(c)).sum::<f64>() * size,
+        }
+    }
+}
+
+/// The directories to look for fonts in for `graph`: those of its `fontpath`, then those of the
+/// `DOTFONTPATH` environment variable, both lists separated as the system separates `PATH`.
+pub fn font_path(graph: &Graph) -> Vec<PathBuf> {
+    let mut directories = vec![];
+    for attribute in &graph.attributes {
+        if let Attribute::FontPath(path) = attribute {
+            directories.extend(env::split_paths(path));
+        }
+    }
+    if let Some(path) = env::var_os("DOTFONTPATH") {
+        directories.extend(env::split_paths(&path));
+    }
+    directories
+}
+
+/// The directories the system keeps fonts in.
+#[cfg(feature = "fonts")]
+fn system_directories() -> Vec<PathBuf> {
+    let mut directories = [
+        "/usr/share/fonts",
+        "/usr/local/share/fonts",
+        "/Library/Fonts",
+        "/System/Library/Fonts",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect::<Vec<_>>();
+    if let Some(home) = env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        directories.push(home.join(".fonts"));
+        directories.push(home.join(".local/share/fonts"));
+        directories.push(home.join("Library/Fonts"));
+    }
+    if let Some(windows) = env::var_os("WINDIR") {
+        directories.push(PathBuf::from(windows).join("Fonts"));
+    }
+    directories
+}
+
+#[cfg(not(feature = "fonts"))]
+fn system_directories() -> Vec<PathBuf> {
+    vec![]
+}
+
+/// `name` in lowercase without spaces and punctuation, to compare names by.
+fn key(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Whether `word` is made of [`STYLE_WORDS`] only, such as `BoldOblique`.
+fn is_style(word: &str) -> bool {
+    let mut rest = word.to_ascii_lowercase();
+    while let Some(style) = STYLE_WORDS.iter().find(|style| rest.starts_with(*style)) {
+        rest.drain(..style.len());
+    }
+    rest.is_empty() && !word.is_empty()
+}
+
+/// The family of the font `name`, and whether it's bold and italic: `Helvetica-BoldOblique`, or
+/// `Helvetica:bold:italic` as in fontconfig patterns.
+fn parse_name(name: &str) -> (String, bool, bool) {
+    let lower = name.to_ascii_lowercase();
+    let bold = ["bold", "black", "heavy", "demi"]
+        .iter()
+        .any(|word| lower.contains(word));
+    let italic = lower.contains("italic") || lower.contains("oblique");
+    let family = name.split(':').next().unwrap_or_default();
+    let words = family
+        .split(&['-', ' ', ','][..])
+        .filter(|word| !word.is_empty() && !is_style(word))
+        .collect::<Vec<_>>();
+    (words.join(" "), bold, italic)
+}
+
+/// The fonts in the files in `directory` and the directories in it, scanned once per process.
+fn scan(directory: &Path) -> Arc<Vec<Description>> {
+    static SCANNED: OnceLock<Mutex<HashMap<PathBuf, Arc<Vec<Description>>>>> = OnceLock::new();
+    let scanned = SCANNED.get_or_init(Default::default);
+    if let Some(descriptions) = scanned.lock().unwrap().get(directory) {
+        return descriptions.clone();
+    }
+    fn walk(directory: &Path, depth: usize, descriptions: &mut Vec<Description>) {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        let mut paths = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .collect::<Vec<_>>();
+        // in the same order everywhere, for the same choices between equal fonts
+        paths.sort();
+        for path in paths {
+            let extension = path.extension().and_then(|extension| extension.to_str());
+            let extension = extension.map(str::to_ascii_lowercase);
+            if path.is_dir() {
+                if depth < MAX_DEPTH {
+                    walk(&path, depth + 1, descriptions);
+                }
+            } else if matches!(extension.as_deref(), Some("ttf" | "otf" | "ttc" | "otc")) {
+                descriptions.extend(truetype::describe(&path).unwrap_or_default());
+            }
+        }
+    }
+    let mut descriptions = vec![];
+    walk(directory, 0, &mut descriptions);
+    let descriptions = Arc::new(descriptions);
+    scanned
+        .lock()
+        .unwrap()
+        .insert(directory.to_owned(), descriptions.clone());
+    descriptions
+}
+
+/// The font of the family `family` among `descriptions` closest to the `bold` and `italic` style,
+/// or the font named `name` in full.
+fn choose<'a>(
+    descriptions: &'a [Description],
+    name: &str,
+    family: &str,
+    bold: bool,
+    italic: bool,
+) -> Option<&'a Description> {
+    let (name, family) = (key(name), key(family));
+    descriptions
+        .iter()
+        .filter_map(|description| {
+            let keys = description.names.iter().map(|name| key(name));
+            let keys = keys.collect::<Vec<_>>();
+            let rank = if keys.contains(&name) {
+                0
+            } else if keys.contains(&family) {
+                1 + usize::from(description.bold != bold)
+                    + usize::from(description.italic != italic)
+            } else {
+                return None;
+            };
+            Some((rank, description))
+        })
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, description)| description)
+}
+
+/// The font to set text of the font `name` in, looked up in the directories of `font_path` and
+/// the system's; see the [module documentation](self). Fonts are loaded once per process.
+pub fn find(name: &str, font_path: &[PathBuf]) -> Arc<Font> {
+    type Found = HashMap<(String, Vec<PathBuf>), Arc<Font>>;
+    static FOUND: OnceLock<Mutex<Found>> = OnceLock::new();
+    let found = FOUND.get_or_init(Default::default);
+    let cache_key = (name.to_owned(), font_path.to_vec());
+    if let Some(font) = found.lock().unwrap().get(&cache_key) {
+        return font.clone();
+    }
+
+    let (family, bold, italic) = parse_name(name);
+    let standard = standard_font(name);
+    let substitutes = SUBSTITUTES
+        .iter()
+        .filter(|(family, _)| standard.starts_with(family))
+        .flat_map(|(_, substitutes)| substitutes.iter().map(|&name| name.to_owned()));
+    let families = std::iter::once(family).chain(substitutes);
+    let directories = font_path.iter().cloned().chain(system_directories());
+    let descriptions = directories
+        .flat_map(|directory| scan(&directory).to_vec())
+        .collect::<Vec<_>>();
+    let font = families
+        .filter_map(|family| {
+            let description = choose(&descriptions, name, &family, bold, italic)?;
+            let face = Face::load(&description.path, description.index).ok()?;
+            Some(Font {
+                name: description.name.clone(),
+                metrics: Metrics::Face(face),
+            })
+        })
+        .next()
+        .unwrap_or_else(|| Font::standard(name));
+    let font = Arc::new(font);
+    found.lock().unwrap().insert(cache_key, font.clone());
+    font
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            parse_name("Helvetica-BoldOblique"),
+            ("Helvetica".to_owned(), true, true)
+        );
+        assert_eq!(
+            parse_name("DejaVu Sans:bold"),
+            ("DejaVu Sans".to_owned(), true, false)
+        );
+        assert_eq!(
+            parse_name("Times-Roman"),
+            ("Times".to_owned(), false, false)
+        );
+        assert_eq!(standard_font("Arial Bold Italic"), "Helvetica-BoldOblique");
+    }
+
+    #[test]
+    fn standard() {
+        let times = Font::standard("Times-Roman");
+        assert!(times.is_standard());
+        // 722 + 444 + 500 + 500, in thousandths of 10 points
+        assert_eq!(times.width("Node", 10.0), 21.66);
+        assert_eq!(Font::standard("Courier").width("iiii", 10.0), 24.0);
+        // combining marks take no room
+        assert_eq!(
+            times.width("Ne\u{301}e\u{308}", 10.0),
+            times.width("Nee", 10.0)
+        );
+    }
+
+    #[test]
+    fn font_path() {
+        let directory = env::temp_dir().join(format!("simpledot-font-{}", std::process::id()));
+        fs::create_dir_all(directory.join("nested")).unwrap();
+        fs::write(
+            directory.join("nested/TestSans-Bold.ttf"),
+            truetype::tests::sample(),
+        )
+        .unwrap();
+        let font_path = [directory.clone()];
+        for name in &["Test Sans", "Test Sans:bold", "TestSans-Bold"] {
+            let font = find(name, &font_path);
+            assert_eq!(font.name(), "Test Sans Bold");
+            assert_eq!(font.width("AB", 10.0), 5.0);
+        }
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
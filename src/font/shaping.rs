@@ -0,0 +1,424 @@
+//! Text in the order and forms it's drawn in: [`visual`] reorders text mixing left-to-right and
+//! right-to-left scripts, such as Hebrew and Arabic, as the Unicode bidirectional algorithm does,
+//! and with the `shaping` feature joins Arabic letters in their contextual forms.
+//!
+//! Labels are kept in logical order, the order they're typed in, and measured in it; only the
+//! backends that place characters themselves reorder them. Combining marks stay with the
+//! character before them, take no room of their own and don't break the joining of letters.
+//!
+//! The bidirectional algorithm is that of a single paragraph without explicit embeddings: each
+//! line of a label is a paragraph whose direction is that of its first strong character.
+//! Brackets are mirrored in right-to-left runs.
+
+use std::borrow::Cow;
+
+/// The bidirectional classes of characters, as far as they're told apart here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Left,
+    Right,
+    /// Arabic letters, which are right-to-left and turn the numbers after them Arabic.
+    Arabic,
+    European,
+    ArabicNumber,
+    /// Separators within numbers, such as `.` and `,`.
+    Separator,
+    /// Characters ending or starting numbers, such as `%` and `$`.
+    Terminator,
+    Neutral,
+}
+
+/// Whether `c` is a combining mark or a zero-width format character, drawn over or between the
+/// characters around it.
+pub fn is_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{300}'..='\u{36f}'
+            | '\u{483}'..='\u{489}'
+            | '\u{591}'..='\u{5bd}'
+            | '\u{5bf}'
+            | '\u{5c1}'..='\u{5c2}'
+            | '\u{5c4}'..='\u{5c5}'
+            | '\u{5c7}'
+            | '\u{610}'..='\u{61a}'
+            | '\u{64b}'..='\u{65f}'
+            | '\u{670}'
+            | '\u{6d6}'..='\u{6dc}'
+            | '\u{6df}'..='\u{6e4}'
+            | '\u{6e7}'..='\u{6e8}'
+            | '\u{6ea}'..='\u{6ed}'
+            | '\u{900}'..='\u{902}'
+            | '\u{93a}'..='\u{94f}'
+            | '\u{e31}'
+            | '\u{e34}'..='\u{e3a}'
+            | '\u{e47}'..='\u{e4e}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{fe20}'..='\u{fe2f}'
+    )
+}
+
+fn class(c: char) -> Class {
+    match c {
+        '0'..='9' | '\u{6f0}'..='\u{6f9}' => Class::European,
+        '\u{660}'..='\u{669}' | '\u{66b}'..='\u{66c}' => Class::ArabicNumber,
+        '.' | ',' | ':' | '/' | '\u{a0}' | '+' | '-' => Class::Separator,
+        '#' | '$' | '%' | '\u{a2}'..='\u{a5}' | '\u{b0}' | '\u{20a0}'..='\u{20cf}' => {
+            Class::Terminator
+        }
+        '\u{590}'..='\u{5ff}' | '\u{7c0}'..='\u{85f}' | '\u{fb1d}'..='\u{fb4f}' => Class::Right,
+        '\u{600}'..='\u{6ff}'
+        | '\u{750}'..='\u{77f}'
+        | '\u{860}'..='\u{8ff}'
+        | '\u{fb50}'..='\u{fdff}'
+        | '\u{fe70}'..='\u{feff}' => Class::Arabic,
+        c if c.is_alphabetic() => Class::Left,
+        _ => Class::Neutral,
+    }
+}
+
+/// `c` as it's drawn in right-to-left text.
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        c => c,
+    }
+}
+
+/// `text` split into characters with the marks after them.
+fn clusters(text: &str) -> Vec<&str> {
+    let mut clusters = vec![];
+    let mut start = 0;
+    for (index, c) in text.char_indices().skip(1) {
+        if !is_mark(c) {
+            clusters.push(&text[start..index]);
+            start = index;
+        }
+    }
+    if !text.is_empty() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Whether `text` is a right-to-left paragraph, whose first strong character is right-to-left.
+pub fn is_rtl(text: &str) -> bool {
+    text.chars()
+        .map(class)
+        .find(|&class| matches!(class, Class::Left | Class::Right | Class::Arabic))
+        .is_some_and(|class| class != Class::Left)
+}
+
+/// The embedding levels of `classes`, in a paragraph of direction `right`: even for
+/// left-to-right and odd for right-to-left.
+fn levels(mut classes: Vec<Class>, right: bool) -> Vec<u8> {
+    let start = if right { Class::Right } else { Class::Left };
+    let strong = |class: Class| matches!(class, Class::Left | Class::Right | Class::Arabic);
+    // numbers after Arabic letters are Arabic, and Arabic letters are right-to-left
+    let mut last = start;
+    for class in &mut classes {
+        match *class {
+            c if strong(c) => last = c,
+            Class::European if last == Class::Arabic => *class = Class::ArabicNumber,
+            _ => {}
+        }
+        if *class == Class::Arabic {
+            *class = Class::Right;
+        }
+    }
+    // a separator between two numbers of the same kind, and terminators next to European
+    // numbers, are part of the number
+    for index in 1..classes.len().saturating_sub(1) {
+        let (before, after) = (classes[index - 1], classes[index + 1]);
+        if classes[index] == Class::Separator
+            && before == after
+            && matches!(before, Class::European | Class::ArabicNumber)
+        {
+            classes[index] = before;
+        }
+    }
+    for index in 0..classes.len() {
+        if classes[index] != Class::Terminator {
+            continue;
+        }
+        let end = (index..classes.len())
+            .find(|&end| classes[end] != Class::Terminator)
+            .unwrap_or(classes.len());
+        let next = classes.get(end) == Some(&Class::European);
+        let previous = index > 0 && classes[index - 1] == Class::European;
+        if next || previous {
+            classes[index..end].fill(Class::European);
+        }
+    }
+    // European numbers after left-to-right text are left-to-right
+    let mut last = start;
+    for class in &mut classes {
+        match *class {
+            c if strong(c) => last = c,
+            Class::European if last == Class::Left => *class = Class::Left,
+            _ => {}
+        }
+    }
+    // neutrals between text of the same direction take it, and others that of the paragraph
+    let direction = |class: Class| match class {
+        Class::Left => Some(Class::Left),
+        Class::Right | Class::European | Class::ArabicNumber => Some(Class::Right),
+        _ => None,
+    };
+    let mut index = 0;
+    while index < classes.len() {
+        if direction(classes[index]).is_some() {
+            index += 1;
+            continue;
+        }
+        let end = (index..classes.len())
+            .find(|&end| direction(classes[end]).is_some())
+            .unwrap_or(classes.len());
+        let before = index
+            .checked_sub(1)
+            .map_or(Some(start), |i| direction(classes[i]));
+        let after = classes
+            .get(end)
+            .map_or(Some(start), |&class| direction(class));
+        let resolved = if before == after { before } else { Some(start) };
+        classes[index..end].fill(resolved.unwrap_or(start));
+        index = end;
+    }
+    classes
+        .into_iter()
+        .map(|class| match (right, class) {
+            (false, Class::Left) => 0,
+            (false, Class::Right) => 1,
+            (false, _) => 2,
+            (true, Class::Right) => 1,
+            (true, _) => 2,
+        })
+        .collect()
+}
+
+/// `text` in the order its characters are drawn in from the left, with the forms they take;
+/// see the [module documentation](self).
+pub fn visual(text: &str) -> String {
+    let text = join(text);
+    let clusters = clusters(&text);
+    let classes = clusters
+        .iter()
+        .map(|cluster| class(cluster.chars().next().unwrap()))
+        .collect::<Vec<_>>();
+    if !classes
+        .iter()
+        .any(|class| matches!(class, Class::Right | Class::Arabic | Class::ArabicNumber))
+    {
+        return text.into_owned();
+    }
+    let levels = levels(classes, is_rtl(&text));
+    let mut order = (0..clusters.len()).collect::<Vec<_>>();
+    // from the highest level down, reverse each run at that level or higher
+    for level in (1..=levels.iter().copied().max().unwrap_or(0)).rev() {
+        let mut index = 0;
+        while index < order.len() {
+            if levels[order[index]] < level {
+                index += 1;
+                continue;
+            }
+            let end = (index..order.len())
+                .find(|&end| levels[order[end]] < level)
+                .unwrap_or(order.len());
+            order[index..end].reverse();
+            index = end;
+        }
+    }
+    let mut visual = String::with_capacity(text.len());
+    for index in order {
+        let mut chars = clusters[index].chars();
+        let base = chars.next().unwrap();
+        visual.push(if levels[index] % 2 == 1 {
+            mirror(base)
+        } else {
+            base
+        });
+        visual.extend(chars);
+    }
+    visual
+}
+
+/// How Arabic letters join the letters next to them.
+#[cfg(feature = "shaping")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    /// On both sides, in isolated, final, initial and medial forms.
+    Dual,
+    /// To the letter before only, in isolated and final forms.
+    Right,
+    /// Joining letters on both sides without changing, as the tatweel.
+    Causing,
+    Neither,
+}
+
+/// The joining of `c`, and its isolated form, the first of its consecutive presentation forms.
+#[cfg(feature = "shaping")]
+fn joining(c: char) -> (Joining, Option<u32>) {
+    use Joining::*;
+    // the letters from hamza to ghain, then from feh to yeh
+    const LETTERS: [(Joining, u32); 26] = [
+        (Neither, 0xfe80),
+        (Right, 0xfe81),
+        (Right, 0xfe83),
+        (Right, 0xfe85),
+        (Right, 0xfe87),
+        (Dual, 0xfe89),
+        (Right, 0xfe8d),
+        (Dual, 0xfe8f),
+        (Right, 0xfe93),
+        (Dual, 0xfe95),
+        (Dual, 0xfe99),
+        (Dual, 0xfe9d),
+        (Dual, 0xfea1),
+        (Dual, 0xfea5),
+        (Right, 0xfea9),
+        (Right, 0xfeab),
+        (Right, 0xfead),
+        (Right, 0xfeaf),
+        (Dual, 0xfeb1),
+        (Dual, 0xfeb5),
+        (Dual, 0xfeb9),
+        (Dual, 0xfebd),
+        (Dual, 0xfec1),
+        (Dual, 0xfec5),
+        (Dual, 0xfec9),
+        (Dual, 0xfecd),
+    ];
+    const MORE: [(Joining, u32); 10] = [
+        (Dual, 0xfed1),
+        (Dual, 0xfed5),
+        (Dual, 0xfed9),
+        (Dual, 0xfedd),
+        (Dual, 0xfee1),
+        (Dual, 0xfee5),
+        (Dual, 0xfee9),
+        (Right, 0xfeed),
+        (Right, 0xfeef),
+        (Dual, 0xfef1),
+    ];
+    let (joining, form) = match c {
+        '\u{621}'..='\u{63a}' => LETTERS[c as usize - 0x621],
+        '\u{641}'..='\u{64a}' => MORE[c as usize - 0x641],
+        '\u{640}' => return (Causing, None),
+        _ => return (Neither, None),
+    };
+    (joining, Some(form))
+}
+
+/// `text` with its Arabic letters in the forms they take next to the letters around them, and
+/// lam followed by alef as their ligature.
+#[cfg(feature = "shaping")]
+fn join(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| ('\u{621}'..='\u{64a}').contains(&c)) {
+        return Cow::Borrowed(text);
+    }
+    let chars = text.chars().collect::<Vec<_>>();
+    // the joining of the letters before and after each, over marks
+    let joins = |index: usize, step: isize| {
+        let mut index = index as isize + step;
+        while index >= 0 && (index as usize) < chars.len() && is_mark(chars[index as usize]) {
+            index += step;
+        }
+        if index < 0 || index as usize >= chars.len() {
+            return Joining::Neither;
+        }
+        joining(chars[index as usize]).0
+    };
+    let mut joined = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        let (kind, form) = joining(c);
+        let form = match form {
+            Some(form) if kind != Joining::Neither => form,
+            _ => {
+                joined.push(c);
+                index += 1;
+                continue;
+            }
+        };
+        let before = matches!(joins(index, -1), Joining::Dual | Joining::Causing);
+        let after = kind == Joining::Dual
+            && matches!(
+                joins(index, 1),
+                Joining::Dual | Joining::Right | Joining::Causing
+            );
+        // lam-alef, isolated or final
+        let ligature = match chars.get(index + 1) {
+            Some(&alef) if c == '\u{644}' => match alef {
+                '\u{622}' => Some(0xfef5),
+                '\u{623}' => Some(0xfef7),
+                '\u{625}' => Some(0xfef9),
+                '\u{627}' => Some(0xfefb),
+                _ => None,
+            },
+            _ => None,
+        };
+        let (form, length) = match ligature {
+            Some(ligature) => (ligature + u32::from(before), 2),
+            None => (form + u32::from(before) + 2 * u32::from(after), 1),
+        };
+        joined.extend(char::from_u32(form));
+        index += length;
+    }
+    Cow::Owned(joined)
+}
+
+#[cfg(not(feature = "shaping"))]
+fn join(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+/// The characters `text` is drawn with, in logical order, for measuring it.
+pub(super) fn shape(text: &str) -> Cow<'_, str> {
+    join(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bidi() {
+        assert_eq!(visual("plain text (1.5%)"), "plain text (1.5%)");
+        assert_eq!(visual("שלום"), "םולש");
+        assert_eq!(visual("abc שלום עולם def"), "abc םלוע םולש def");
+        // numbers keep their order, and brackets face the way the text reads
+        assert_eq!(visual("שלום 1,000 (א)"), "(א) 1,000 םולש");
+        assert_eq!(visual("שלום abc 12"), "abc 12 םולש");
+        // marks stay after their letters
+        assert_eq!(visual("שָׁלוֹם"), "םוֹלשָׁ");
+        assert!(is_rtl("שלום abc") && !is_rtl("abc שלום") && !is_rtl("123"));
+    }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn arabic() {
+        // beh initial, yeh medial, teh final; drawn from the right
+        assert_eq!(shape("بيت"), "\u{fe91}\u{fef4}\u{fe96}");
+        assert_eq!(visual("بيت"), "\u{fe96}\u{fef4}\u{fe91}");
+        // lam-alef, isolated and after seen, and a mark not breaking the joining of beh and teh
+        assert_eq!(shape("لا"), "\u{fefb}");
+        assert_eq!(shape("سلام"), "\u{feb3}\u{fefc}\u{fee1}");
+        assert_eq!(shape("بَت"), "\u{fe91}\u{64e}\u{fe96}");
+        assert_eq!(shape("abc"), "abc");
+    }
+}
@@ -0,0 +1,399 @@
+//! Just enough of TrueType and OpenType fonts to measure text: the names of the fonts in a file,
+//! read without loading the whole file, and the advance widths of the glyphs of characters.
+//!
+//! Collections of fonts, `.ttc` files, are read as the fonts they hold, numbered from 0.
+
+use std::{
+    convert::TryFrom,
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// The most fonts in a collection, and tables in a font, that are read, well above what real
+/// files have, against corrupt ones.
+const MAX_COUNT: usize = 1024;
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "not a TrueType or OpenType font",
+    )
+}
+
+/// The offsets of the fonts in a file that starts with `header`, which holds at least 12 bytes,
+/// reading the rest of the list of a collection with `read`.
+fn font_offsets(
+    header: &[u8],
+    read: &mut dyn FnMut(u64, usize) -> io::Result<Vec<u8>>,
+) -> io::Result<Vec<u64>> {
+    if &header[..4] != b"ttcf" {
+        return Ok(vec![0]);
+    }
+    let count = u32_at(header, 8).ok_or_else(invalid)? as usize;
+    if count > MAX_COUNT {
+        return Err(invalid());
+    }
+    let offsets = read(12, 4 * count)?;
+    Ok((0..count)
+        .filter_map(|index| u32_at(&offsets, 4 * index))
+        .map(u64::from)
+        .collect())
+}
+
+/// The tables of the font whose table directory is `directory`, by tag, as their offsets and
+/// lengths in the file.
+fn table(directory: &[u8], tag: &[u8; 4]) -> Option<(u64, usize)> {
+    let count = usize::from(u16_at(directory, 4)?);
+    (0..count.min(MAX_COUNT)).find_map(|index| {
+        let record = 12 + 16 * index;
+        if directory.get(record..record + 4)? != tag {
+            return None;
+        }
+        let offset = u32_at(directory, record + 8)?;
+        let length = u32_at(directory, record + 12)?;
+        Some((u64::from(offset), length as usize))
+    })
+}
+
+/// A font in a file, as named in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Description {
+    pub(super) path: PathBuf,
+    /// The number of the font in a collection, 0 otherwise.
+    pub(super) index: usize,
+    /// Its full name, as shown to users.
+    pub(super) name: String,
+    /// The names of its family, and its own PostScript and full names.
+    pub(super) names: Vec<String>,
+    pub(super) bold: bool,
+    pub(super) italic: bool,
+}
+
+/// The text of the name records of the `name` table `table` with the ID `id`, as UTF-16 for
+/// Unicode and Windows platforms and as Latin-1 for the Macintosh, English first.
+fn names(table: &[u8], id: u16) -> Vec<String> {
+    let (count, storage) = match (u16_at(table, 2), u16_at(table, 4)) {
+        (Some(count), Some(storage)) => (usize::from(count), usize::from(storage)),
+        _ => return vec![],
+    };
+    let mut names = (0..count)
+        .filter_map(|index| {
+            let record = 6 + 12 * index;
+            let platform = u16_at(table, record)?;
+            let language = u16_at(table, record + 4)?;
+            if u16_at(table, record + 6)? != id {
+                return None;
+            }
+            let length = usize::from(u16_at(table, record + 8)?);
+            let start = storage + usize::from(u16_at(table, record + 10)?);
+            let bytes = table.get(start..start + length)?;
+            let text = match platform {
+                0 | 3 => {
+                    let units = bytes
+                        .chunks_exact(2)
+                        .map(|c| u16::from_be_bytes([c[0], c[1]]));
+                    String::from_utf16_lossy(&units.collect::<Vec<_>>())
+                }
+                1 => bytes.iter().map(|&byte| char::from(byte)).collect(),
+                _ => return None,
+            };
+            let english = language == 0x409 || (platform == 1 && language == 0);
+            Some((!english, text))
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    let mut texts = names.into_iter().map(|(_, text)| text).collect::<Vec<_>>();
+    texts.dedup();
+    texts
+}
+
+/// The fonts in the file at `path`, read from their `name` and `head` tables only.
+pub(super) fn describe(path: &Path) -> io::Result<Vec<Description>> {
+    let mut file = File::open(path)?;
+    let mut read = |offset: u64, length: usize| -> io::Result<Vec<u8>> {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buffer = vec![];
+        (&mut file).take(length as u64).read_to_end(&mut buffer)?;
+        if buffer.len() < length {
+            return Err(invalid());
+        }
+        Ok(buffer)
+    };
+    let header = read(0, 12)?;
+    let mut descriptions = vec![];
+    for (index, offset) in font_offsets(&header, &mut read)?.into_iter().enumerate() {
+        let header = read(offset, 12)?;
+        let count = usize::from(u16_at(&header, 4).ok_or_else(invalid)?).min(MAX_COUNT);
+        let mut directory = header;
+        directory.extend(read(offset + 12, 16 * count)?);
+        let (name, head) = match (table(&directory, b"name"), table(&directory, b"head")) {
+            (Some(name), Some(head)) => (name, head),
+            _ => return Err(invalid()),
+        };
+        let name = read(name.0, name.1)?;
+        let head = read(head.0, head.1.min(54))?;
+        let style = u16_at(&head, 44).unwrap_or(0);
+        let subfamily = names(&name, 2).join(" ").to_ascii_lowercase();
+        let mut family = names(&name, 16);
+        family.extend(names(&name, 1));
+        family.extend(names(&name, 6));
+        let full = names(&name, 4);
+        family.extend(full.iter().cloned());
+        descriptions.push(Description {
+            path: path.to_owned(),
+            index,
+            name: full.into_iter().next().unwrap_or_default(),
+            names: family,
+            bold: style & 1 != 0 || subfamily.contains("bold"),
+            italic: style & 2 != 0 || subfamily.contains("italic") || subfamily.contains("oblique"),
+        });
+    }
+    Ok(descriptions)
+}
+
+/// A font loaded to measure text with.
+#[derive(Debug)]
+pub(super) struct Face {
+    data: Vec<u8>,
+    units_per_em: f64,
+    /// Offset of the `hmtx` table, of the advance widths of the glyphs.
+    metrics: usize,
+    /// The number of glyphs with their own advance width, the others having the last one.
+    metrics_count: usize,
+    /// Offset of the subtable of the `cmap` table mapping Unicode characters to glyphs.
+    map: usize,
+}
+
+impl Face {
+    /// Loads the font numbered `index` in the file at `path`.
+    pub(super) fn load(path: &Path, index: usize) -> io::Result<Face> {
+        let data = fs::read(path)?;
+        let header = data.get(..12).ok_or_else(invalid)?;
+        let offsets = font_offsets(header, &mut |offset, length| {
+            let start = offset as usize;
+            let bytes = data.get(start..start + length).ok_or_else(invalid)?;
+            Ok(bytes.to_vec())
+        })?;
+        let offset = *offsets.get(index).ok_or_else(invalid)? as usize;
+        let directory = data.get(offset..).ok_or_else(invalid)?;
+        let find = |tag| table(directory, tag).map(|(offset, _)| offset as usize);
+        let (head, hhea, metrics, cmap) =
+            match (find(b"head"), find(b"hhea"), find(b"hmtx"), find(b"cmap")) {
+                (Some(head), Some(hhea), Some(hmtx), Some(cmap)) => (head, hhea, hmtx, cmap),
+                _ => return Err(invalid()),
+            };
+        let units_per_em = u16_at(&data, head + 18).filter(|&units| units > 0);
+        let metrics_count = u16_at(&data, hhea + 34).filter(|&count| count > 0);
+        let (units_per_em, metrics_count) = match (units_per_em, metrics_count) {
+            (Some(units), Some(count)) => (f64::from(units), usize::from(count)),
+            _ => return Err(invalid()),
+        };
+        if data.len() < metrics + 4 * metrics_count {
+            return Err(invalid());
+        }
+        // the best map of Unicode: of all characters, else of the basic multilingual plane
+        let count = usize::from(u16_at(&data, cmap + 2).ok_or_else(invalid)?);
+        let map = (0..count)
+            .filter_map(|index| {
+                let record = cmap + 4 + 8 * index;
+                let platform = u16_at(&data, record)?;
+                let encoding = u16_at(&data, record + 2)?;
+                let subtable = cmap + u32_at(&data, record + 4)? as usize;
+                let rank = match (platform, encoding, u16_at(&data, subtable)?) {
+                    (0, _, 12) | (3, 10, 12) => 0,
+                    (0, _, 4) | (3, 1, 4) => 1,
+                    _ => return None,
+                };
+                Some((rank, subtable))
+            })
+            .min()
+            .map(|(_, subtable)| subtable)
+            .ok_or_else(invalid)?;
+        Ok(Face {
+            data,
+            units_per_em,
+            metrics,
+            metrics_count,
+            map,
+        })
+    }
+
+    /// The glyph of `c`, or 0, the glyph of missing characters.
+    fn glyph(&self, c: char) -> usize {
+        let glyph = match u16_at(&self.data, self.map) {
+            Some(4) => u16::try_from(u32::from(c))
+                .ok()
+                .and_then(|c| self.segment_glyph(c)),
+            Some(12) => self.group_glyph(u32::from(c)),
+            _ => None,
+        };
+        glyph.unwrap_or(0)
+    }
+
+    /// The glyph of `c` in a map of format 4, of segments of consecutive characters.
+    fn segment_glyph(&self, c: u16) -> Option<usize> {
+        let (data, map) = (&self.data, self.map);
+        let segments = usize::from(u16_at(data, map + 6)? / 2);
+        let ends = map + 14;
+        let (starts, deltas, ranges) = (
+            ends + 2 * segments + 2,
+            ends + 4 * segments + 2,
+            ends + 6 * segments + 2,
+        );
+        let segment = (0..segments).find(|&i| u16_at(data, ends + 2 * i) >= Some(c))?;
+        let start = u16_at(data, starts + 2 * segment)?;
+        if c < start {
+            return None;
+        }
+        let delta = u16_at(data, deltas + 2 * segment)?;
+        let range = ranges + 2 * segment;
+        let glyph = match u16_at(data, range)? {
+            0 => c,
+            offset => {
+                let index = range + usize::from(offset) + 2 * usize::from(c - start);
+                u16_at(data, index).filter(|&glyph| glyph != 0)?
+            }
+        };
+        Some(usize::from(glyph.wrapping_add(delta)))
+    }
+
+    /// The glyph of `c` in a map of format 12, of groups of consecutive characters and glyphs.
+    fn group_glyph(&self, c: u32) -> Option<usize> {
+        let (data, map) = (&self.data, self.map);
+        let group = |index: usize| {
+            let group = map + 16 + 12 * index;
+            Some((
+                u32_at(data, group)?,
+                u32_at(data, group + 4)?,
+                u32_at(data, group + 8)?,
+            ))
+        };
+        let (mut low, mut high) = (0, u32_at(data, map + 12)? as usize);
+        while low < high {
+            let middle = (low + high) / 2;
+            let (start, end, glyph) = group(middle)?;
+            if c < start {
+                high = middle;
+            } else if c > end {
+                low = middle + 1;
+            } else {
+                return Some((glyph + (c - start)) as usize);
+            }
+        }
+        None
+    }
+
+    /// The advance width of `c`, relative to the font size.
+    pub(super) fn advance(&self, c: char) -> f64 {
+        let glyph = self.glyph(c).min(self.metrics_count - 1);
+        let advance = u16_at(&self.data, self.metrics + 4 * glyph).unwrap_or(0);
+        f64::from(advance) / self.units_per_em
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// A font of the family `Test Sans`, bold, whose capital letters are a quarter of an em wide
+    /// and whose other characters are missing, half an em wide.
+    fn u16s(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    pub(in crate::font) fn sample() -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        head[44..46].copy_from_slice(&1u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[34..36].copy_from_slice(&2u16.to_be_bytes());
+        let hmtx = u16s(&[500, 0, 250, 0]);
+        // one segment for A to Z, to glyph 1, and the final one
+        let mut cmap = u16s(&[0, 1, 3, 1, 0, 12]);
+        cmap.extend(u16s(&[4, 32, 0, 4, 0, 0, 0]));
+        cmap.extend(u16s(&[
+            90,
+            0xffff,
+            0,
+            65,
+            0xffff,
+            1u16.wrapping_sub(65),
+            1,
+            0,
+            0,
+        ]));
+        let names = ["Test Sans", "Test Sans Bold"]
+            .iter()
+            .map(|name| name.encode_utf16().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let mut name = u16s(&[0, 2, 30]);
+        let mut strings = vec![];
+        for (id, text) in [1, 4].iter().zip(&names) {
+            let offset = strings.len() as u16;
+            name.extend(u16s(&[3, 1, 0x409, *id, 2 * text.len() as u16, offset]));
+            strings.extend(u16s(text));
+        }
+        name.extend(strings);
+
+        let tables: [(&[u8; 4], Vec<u8>); 5] = [
+            (b"cmap", cmap),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"name", name),
+        ];
+        let mut font = u16s(&[1, 0, tables.len() as u16, 0, 0, 0]);
+        let mut offset = 12 + 16 * tables.len();
+        let mut data = vec![];
+        for (tag, table) in &tables {
+            font.extend_from_slice(*tag);
+            font.extend_from_slice(&[0; 4]);
+            font.extend_from_slice(&(offset as u32).to_be_bytes());
+            font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            offset += table.len();
+            data.extend_from_slice(table);
+        }
+        font.extend(data);
+        font
+    }
+
+    #[test]
+    fn truetype() {
+        let directory =
+            std::env::temp_dir().join(format!("simpledot-truetype-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("TestSans-Bold.ttf");
+        fs::write(&path, sample()).unwrap();
+        let descriptions = describe(&path).unwrap();
+        assert_eq!(
+            descriptions,
+            [Description {
+                path: path.clone(),
+                index: 0,
+                name: "Test Sans Bold".to_owned(),
+                names: vec!["Test Sans".to_owned(), "Test Sans Bold".to_owned()],
+                bold: true,
+                italic: false,
+            }]
+        );
+        let face = Face::load(&path, 0).unwrap();
+        assert_eq!(face.advance('Q'), 0.25);
+        assert_eq!(face.advance('q'), 0.5);
+        assert_eq!(face.advance('\u{1f600}'), 0.5);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+//! The graph formats which can be read or written, by name and file extension.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+#[cfg(feature = "render-svg")]
+use crate::render;
+#[cfg(feature = "render")]
+use crate::render::ps::PsFormat;
+use crate::{
+    diagnostic::Diagnostic,
+    export::{self, graph6::Graph6Format, json::JsonFormat, plain::PlainFormat},
+    import::{self, csv::CsvOptions},
+    ir::parse_graph,
+    model::Graph,
+    validate::parse_error,
+};
+
+pub type Reader = fn(&str) -> Result<Graph, Diagnostic>;
+pub type Writer = fn(&Graph, &mut dyn Write) -> io::Result<()>;
+
+#[derive(Debug)]
+pub struct Format {
+    pub name: &'static str,
+    /// Extensions of files in the format, lowercase and without the dot.
+    pub extensions: &'static [&'static str],
+    pub read: Option<Reader>,
+    pub write: Option<Writer>,
+}
+
+/// A diagnostic for an input which failed to read.
+fn invalid(e: impl ToString) -> Diagnostic {
+    Diagnostic::error("syntax", e.to_string())
+}
+
+fn read_graph6(input: &str) -> Result<Graph, Diagnostic> {
+    let mut graphs = import::graph6::read(input).map_err(invalid)?;
+    match graphs.len() {
+        1 => Ok(graphs.remove(0)),
+        n => Err(invalid(format_args!("expected one graph, found {}", n))),
+    }
+}
+
+fn read_csv(input: &str, delimiter: char) -> Result<Graph, Diagnostic> {
+    let options = CsvOptions {
+        delimiter,
+        ..CsvOptions::default()
+    };
+    import::csv::read(input, &options).map_err(invalid)
+}
+
+pub const FORMATS: &[Format] = &[
+    Format {
+        name: "dot",
+        extensions: &["dot", "gv"],
+        read: Some(|input| {
+            parse_graph(input)
+                .map(|graph| Graph::from_ir(&graph))
+                .map_err(parse_error)
+        }),
+        write: Some(|graph, writer| export::dot::write(graph, writer)),
+    },
+    Format {
+        name: "json",
+        extensions: &["json"],
+        read: Some(|input| {
+            import::json::read(input)
+                .map(|graph| Graph::from_ir(&graph))
+                .map_err(invalid)
+        }),
+        write: Some(|graph, writer| export::json::write(graph, writer, JsonFormat::DotJson)),
+    },
+    Format {
+        name: "json-layout",
+        extensions: &[],
+        read: None,
+        write: Some(|graph, writer| export::json::write(graph, writer, JsonFormat::Json)),
+    },
+    Format {
+        name: "graphml",
+        extensions: &["graphml"],
+        read: Some(|input| import::graphml::read(input).map_err(invalid)),
+        write: Some(|graph, writer| export::graphml::write(graph, writer)),
+    },
+    Format {
+        name: "gml",
+        extensions: &["gml"],
+        read: Some(|input| import::gml::read(input).map_err(invalid)),
+        write: Some(|graph, writer| export::gml::write(graph, writer)),
+    },
+    Format {
+        name: "pajek",
+        extensions: &["net", "paj"],
+        read: Some(|input| import::pajek::read(input).map_err(invalid)),
+        write: Some(|graph, writer| export::pajek::write(graph, writer)),
+    },
+    Format {
+        name: "tgf",
+        extensions: &["tgf"],
+        read: Some(|input| import::tgf::read(input).map_err(invalid)),
+        write: Some(|graph, writer| export::tgf::write(graph, writer)),
+    },
+    Format {
+        name: "csv",
+        extensions: &["csv"],
+        read: Some(|input| read_csv(input, ',')),
+        write: Some(|graph, writer| export::csv::write(graph, writer, ',')),
+    },
+    Format {
+        name: "tsv",
+        extensions: &["tsv"],
+        read: Some(|input| read_csv(input, '\t')),
+        write: Some(|graph, writer| export::csv::write(graph, writer, '\t')),
+    },
+    Format {
+        name: "graph6",
+        extensions: &["g6"],
+        read: Some(read_graph6),
+        write: Some(|graph, writer| export::graph6::write(graph, writer, Graph6Format::Graph6)),
+    },
+    Format {
+        name: "sparse6",
+        extensions: &["s6"],
+        read: Some(read_graph6),
+        write: Some(|graph, writer| export::graph6::write(graph, writer, Graph6Format::Sparse6)),
+    },
+    Format {
+        name: "mermaid",
+        extensions: &["mmd", "mermaid"],
+        read: Some(|input| {
+            import::mermaid::read(input)
+                .map(|graph| Graph::from_ir(&graph))
+                .map_err(invalid)
+        }),
+        write: None,
+    },
+    Format {
+        name: "yaml",
+        extensions: &["yaml", "yml"],
+        read: Some(|input| {
+            import::declarative::read_yaml(input)
+                .map(|graph| Graph::from_ir(&graph))
+                .map_err(invalid)
+        }),
+        write: None,
+    },
+    Format {
+        name: "toml",
+        extensions: &["toml"],
+        read: Some(|input| {
+            import::declarative::read_toml(input)
+                .map(|graph| Graph::from_ir(&graph))
+                .map_err(invalid)
+        }),
+        write: None,
+    },
+    #[cfg(feature = "rdf")]
+    Format {
+        name: "turtle",
+        extensions: &["ttl", "nt"],
+        read: Some(|input| import::turtle::read(input).map_err(invalid)),
+        write: None,
+    },
+    Format {
+        name: "plain",
+        extensions: &["plain"],
+        read: None,
+        write: Some(|graph, writer| export::plain::write(graph, writer, PlainFormat::Plain)),
+    },
+    Format {
+        name: "plain-ext",
+        extensions: &["plain-ext"],
+        read: None,
+        write: Some(|graph, writer| export::plain::write(graph, writer, PlainFormat::PlainExt)),
+    },
+    #[cfg(feature = "render-svg")]
+    Format {
+        name: "svg",
+        extensions: &["svg"],
+        read: None,
+        write: Some(|graph, writer| render::svg::write(graph, writer)),
+    },
+    #[cfg(feature = "render-svg")]
+    Format {
+        name: "html",
+        extensions: &["html", "htm"],
+        read: None,
+        write: Some(|graph, writer| render::html::write(graph, writer)),
+    },
+    #[cfg(feature = "render")]
+    Format {
+        name: "cmapx",
+        extensions: &["map"],
+        read: None,
+        write: Some(|graph, writer| render::cmapx::write(graph, writer)),
+    },
+    #[cfg(feature = "render")]
+    Format {
+        name: "ps",
+        extensions: &["ps"],
+        read: None,
+        write: Some(|graph, writer| render::ps::write(graph, writer, PsFormat::PostScript)),
+    },
+    #[cfg(feature = "render")]
+    Format {
+        name: "eps",
+        extensions: &["eps"],
+        read: None,
+        write: Some(|graph, writer| render::ps::write(graph, writer, PsFormat::Eps)),
+    },
+    #[cfg(feature = "render")]
+    Format {
+        name: "pdf",
+        extensions: &["pdf"],
+        read: None,
+        write: Some(|graph, writer| render::pdf::write(graph, writer)),
+    },
+    #[cfg(feature = "png")]
+    Format {
+        name: "png",
+        extensions: &["png"],
+        read: None,
+        write: Some(|graph, writer| {
+            let options = render::png::PngOptions::from_graph(graph);
+            render::png::write(graph, writer, &options)
+        }),
+    },
+];
+
+pub fn by_name(name: &str) -> Option<&'static Format> {
+    FORMATS.iter().find(|format| format.name == name)
+}
+
+/// The format of a file, from its extension.
+pub fn by_extension(file: &str) -> Option<&'static Format> {
+    let extension = Path::new(file).extension()?.to_str()?.to_ascii_lowercase();
+    FORMATS
+        .iter()
+        .find(|format| format.extensions.contains(&extension.as_str()))
+}
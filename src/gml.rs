@@ -0,0 +1,219 @@
+//! Minimal GML reader and writer: nested lists of key-value pairs, whose values are integers,
+//! reals, strings or lists. Lines starting with `#` are comments.
+
+use std::io::{self, Write};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{take_while, take_while1},
+    character::complete::{char, multispace0},
+    combinator::{cut, map, map_res, recognize},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::many0,
+    number::complete::recognize_float,
+    sequence::{delimited, pair, preceded, terminated},
+};
+
+type GmlResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum GmlValue {
+    Integer(i64),
+    Real(f64),
+    String(String),
+    List(Vec<(String, GmlValue)>),
+}
+
+impl GmlValue {
+    pub fn as_list(&self) -> Option<&[(String, GmlValue)]> {
+        match self {
+            GmlValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            GmlValue::Integer(value) => Some(*value as f64),
+            GmlValue::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Text of a scalar value.
+    pub fn scalar_string(&self) -> Option<String> {
+        match self {
+            GmlValue::Integer(value) => Some(value.to_string()),
+            GmlValue::Real(value) => Some(value.to_string()),
+            GmlValue::String(value) => Some(value.clone()),
+            GmlValue::List(_) => None,
+        }
+    }
+}
+
+/// Looks up the first value for `key` in a list.
+pub(crate) fn get<'a>(list: &'a [(String, GmlValue)], key: &str) -> Option<&'a GmlValue> {
+    list.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct GmlError {
+    pub expected: &'static str,
+    pub line: usize,
+}
+
+/// GML has no escape sequences; quotes are written as HTML entities by convention.
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('"', "&quot;")
+}
+
+/// Whitespace and comments.
+fn space_parser(input: &str) -> GmlResult<'_, ()> {
+    map(
+        pair(
+            multispace0,
+            many0(terminated(
+                preceded(char('#'), take_while(|c| c != '\n')),
+                multispace0,
+            )),
+        ),
+        |_| (),
+    )(input)
+}
+
+fn key_parser(input: &str) -> GmlResult<'_, &str> {
+    context(
+        "expected key",
+        recognize(pair(
+            take_while1(|c: char| c.is_ascii_alphabetic() || c == '_'),
+            take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+        )),
+    )(input)
+}
+
+fn value_parser(input: &str) -> GmlResult<'_, GmlValue> {
+    context(
+        "expected value",
+        alt((
+            map(
+                delimited(char('"'), take_while(|c| c != '"'), cut(char('"'))),
+                |text: &str| GmlValue::String(unescape(text)),
+            ),
+            map(
+                preceded(
+                    char('['),
+                    cut(terminated(list_parser, context("expected `]`", char(']')))),
+                ),
+                GmlValue::List,
+            ),
+            map_res(recognize_float, |number: &str| {
+                if number.contains(['.', 'e', 'E']) {
+                    number.parse().map(GmlValue::Real).map_err(|_| ())
+                } else {
+                    number.parse().map(GmlValue::Integer).map_err(|_| ())
+                }
+            }),
+        )),
+    )(input)
+}
+
+fn list_parser(input: &str) -> GmlResult<'_, Vec<(String, GmlValue)>> {
+    preceded(
+        space_parser,
+        many0(terminated(
+            pair(
+                map(key_parser, str::to_owned),
+                preceded(space_parser, cut(value_parser)),
+            ),
+            space_parser,
+        )),
+    )(input)
+}
+
+/// Parses a document into its top-level list.
+pub(crate) fn parse(input: &str) -> Result<Vec<(String, GmlValue)>, GmlError> {
+    let (remaining, expected) = match list_parser(input) {
+        Ok(("", list)) => return Ok(list),
+        Ok((rest, _)) => (rest, "expected key"),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e
+            .errors
+            .iter()
+            .find_map(|(remaining, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some((*remaining, *context)),
+                _ => None,
+            })
+            .unwrap_or((input, "expected key")),
+        Err(nom::Err::Incomplete(_)) => ("", "expected value"),
+    };
+    Err(GmlError {
+        expected,
+        line: input[..input.len() - remaining.len()].matches('\n').count() + 1,
+    })
+}
+
+/// Writes a list of key-value pairs, one per line, with lists indented by two spaces per level.
+pub(crate) fn write_list<W: Write>(
+    writer: &mut W,
+    list: &[(String, GmlValue)],
+    depth: usize,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    for (key, value) in list {
+        match value {
+            GmlValue::Integer(value) => writeln!(writer, "{}{} {}", indent, key, value)?,
+            GmlValue::Real(value) => writeln!(writer, "{}{} {:?}", indent, key, value)?,
+            GmlValue::String(value) => writeln!(writer, "{}{} \"{}\"", indent, key, escape(value))?,
+            GmlValue::List(list) => {
+                writeln!(writer, "{}{} [", indent, key)?;
+                write_list(writer, list, depth + 1)?;
+                writeln!(writer, "{}]", indent)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let input = "# comment\nCreator \"x &quot;y&quot;\"\ngraph [\n  directed 1\n  node [ id 1 x -2.5e1 ]\n]\n";
+        let list = parse(input).unwrap();
+        assert_eq!(
+            list[0],
+            ("Creator".to_owned(), GmlValue::String("x \"y\"".to_owned()))
+        );
+        let graph = list[1].1.as_list().unwrap();
+        assert_eq!(get(graph, "directed"), Some(&GmlValue::Integer(1)));
+        let node = get(graph, "node").unwrap().as_list().unwrap();
+        assert_eq!(get(node, "x"), Some(&GmlValue::Real(-25.0)));
+
+        let mut output = vec![];
+        write_list(&mut output, &list, 0).unwrap();
+        assert_eq!(parse(std::str::from_utf8(&output).unwrap()), Ok(list));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("graph [\n  node [ id ]\n"),
+            Err(GmlError {
+                expected: "expected value",
+                line: 2
+            })
+        );
+        assert_eq!(
+            parse("graph [\n  directed 1\n"),
+            Err(GmlError {
+                expected: "expected `]`",
+                line: 3
+            })
+        );
+    }
+}
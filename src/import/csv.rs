@@ -0,0 +1,218 @@
+//! CSV and TSV edge list import.
+//!
+//! Each record describes an edge from its source to its target column, with further columns read
+//! as edge attributes. Records with an empty target declare an isolated node, as written by
+//! [`export::csv`](crate::export::csv).
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError},
+    csv,
+    ir::GraphKind,
+    model::{set_attribute, Graph},
+};
+
+#[derive(Debug, Error)]
+pub enum CsvError {
+    #[error("unterminated quoted field starting at line {0}")]
+    UnterminatedQuote(usize),
+    #[error("no column named `{0}` in header")]
+    MissingColumn(String),
+    #[error("missing {column} at line {line}")]
+    MissingField { column: &'static str, line: usize },
+    #[error("invalid value `{value}` for attribute `{name}` at line {line}")]
+    InvalidValue {
+        name: String,
+        value: String,
+        line: usize,
+    },
+}
+
+/// A column, by position or by header name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+/// Options describing the layout of the input.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Whether the first record is a header naming the columns.
+    pub header: bool,
+    pub directed: bool,
+    pub source: Column,
+    pub target: Column,
+    /// Columns read as edge attributes, along with the DOT attribute each maps to. If `None`, every
+    /// other column is read as the attribute named by its header, or without a header, the third
+    /// and fourth columns are read as `weight` and `label`. Columns not naming a known DOT
+    /// attribute are ignored.
+    pub attributes: Option<Vec<(Column, String)>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            header: true,
+            directed: true,
+            source: Column::Index(0),
+            target: Column::Index(1),
+            attributes: None,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Options for tab-separated input.
+    pub fn tsv() -> Self {
+        CsvOptions {
+            delimiter: '\t',
+            ..CsvOptions::default()
+        }
+    }
+}
+
+fn resolve(column: &Column, header: &[String]) -> Result<usize, CsvError> {
+    match column {
+        Column::Index(index) => Ok(*index),
+        Column::Name(name) => header
+            .iter()
+            .position(|h| h.trim() == name)
+            .ok_or_else(|| CsvError::MissingColumn(name.clone())),
+    }
+}
+
+/// Reads an edge list into a graph.
+pub fn read(input: &str, options: &CsvOptions) -> Result<Graph, CsvError> {
+    let mut records = csv::parse(input, options.delimiter)
+        .map_err(CsvError::UnterminatedQuote)?
+        .into_iter();
+    let header = if options.header {
+        records.next().map(|(_, header)| header).unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let source = resolve(&options.source, &header)?;
+    let target = resolve(&options.target, &header)?;
+    let attributes = match &options.attributes {
+        Some(attributes) => attributes
+            .iter()
+            .map(|(column, name)| Ok((resolve(column, &header)?, name.clone())))
+            .collect::<Result<Vec<_>, CsvError>>()?,
+        None if options.header => header
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != source && *i != target)
+            .map(|(i, name)| (i, name.trim().to_owned()))
+            .collect(),
+        None => vec![(2, "weight".to_owned()), (3, "label".to_owned())],
+    };
+
+    let kind = if options.directed {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let mut graph = Graph::new(kind, false);
+    for (line, record) in records {
+        let field = |index: usize| record.get(index).map(|f| f.trim()).unwrap_or_default();
+        let tail = match field(source) {
+            "" => {
+                return Err(CsvError::MissingField {
+                    column: "source",
+                    line,
+                })
+            }
+            id => graph.add_node(id),
+        };
+        let head = match field(target) {
+            "" => continue,
+            id => graph.add_node(id),
+        };
+        let mut edge_attributes = vec![];
+        for (index, name) in &attributes {
+            let value = field(*index);
+            if value.is_empty() {
+                continue;
+            }
+            match Attribute::from_pair(name, value) {
+                Ok(attribute) => set_attribute(&mut edge_attributes, attribute),
+                Err(AttributeParseError::AttributeNameNotFound) => {}
+                Err(_) => {
+                    return Err(CsvError::InvalidValue {
+                        name: name.clone(),
+                        value: value.to_owned(),
+                        line,
+                    })
+                }
+            }
+        }
+        graph.add_edge(tail, head, edge_attributes);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapping() {
+        let graph = read(
+            "from\tnote\tto\tcost\na\t\"x\"\tb\t2\nb\t\tc\t\nd\t\t\t\n",
+            &CsvOptions {
+                source: Column::Name("from".to_owned()),
+                target: Column::Name("to".to_owned()),
+                attributes: Some(vec![
+                    (Column::Name("cost".to_owned()), "weight".to_owned()),
+                    (Column::Index(1), "label".to_owned()),
+                ]),
+                ..CsvOptions::tsv()
+            },
+        )
+        .unwrap();
+        assert_eq!(graph.nodes().len(), 4);
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Weight(2.0), Attribute::Label("x".to_owned())]
+        );
+        assert!(graph.edges()[1].attributes.is_empty());
+
+        let graph = read(
+            "a,b,1.5,first\nb,a",
+            &CsvOptions {
+                header: false,
+                directed: false,
+                ..CsvOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(graph.kind, GraphKind::Undirected);
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Weight(1.5), Attribute::Label("first".to_owned())]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            read("a,b\n", &CsvOptions {
+                source: Column::Name("source".to_owned()),
+                ..CsvOptions::default()
+            }),
+            Err(CsvError::MissingColumn(column)) if column == "source"
+        ));
+        assert!(matches!(
+            read("source,target,weight\na,b,heavy\n", &CsvOptions::default()),
+            Err(CsvError::InvalidValue { line: 2, .. })
+        ));
+        assert!(matches!(
+            read("source,target\n,b\n", &CsvOptions::default()),
+            Err(CsvError::MissingField { line: 2, .. })
+        ));
+    }
+}
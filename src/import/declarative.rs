@@ -0,0 +1,401 @@
+//! Graphs declared as data in YAML or TOML.
+//!
+//! Both formats share one schema. Every member is optional:
+//!
+//! ```yaml
+//! name: services
+//! directed: true          # the default; `strict` is also read
+//! graph: { rankdir: LR }  # default graph, node and edge attributes
+//! node: { shape: box }
+//! edge: { color: gray }
+//! nodes:
+//!   - web                 # a node ID,
+//!   - id: db              # or an `id` and attributes
+//!     shape: cylinder
+//! edges:
+//!   - [web, api, db]      # a path,
+//!   - from: web           # or `from` and `to`, which may list several targets, and attributes
+//!     to: [cache, db]
+//!     style: dashed
+//! clusters:
+//!   - name: backend
+//!     node: { color: blue }
+//!     nodes: [api, cache]
+//! ```
+//!
+//! `nodes` may also map IDs to attributes (`nodes: { db: { shape: cylinder } }`), which in TOML
+//! reads as `[nodes.db]` tables; `[[nodes]]` and `[[edges]]` tables give the list forms. Lists of
+//! attribute values, such as `style: [filled, rounded]`, are joined with commas. As with the other
+//! importers, attributes not naming a known DOT attribute are ignored.
+//!
+//! Clusters are flattened: their nodes are declared with the cluster's `node` attributes, and
+//! other cluster members are ignored.
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError},
+    ir::{
+        AttributeKind, AttributeStatement, Attributes, EdgeOp, EdgeStatement, Graph, GraphKind,
+        NodeStatement, Statement,
+    },
+    json::JsonValue,
+    toml, yaml,
+};
+
+#[derive(Debug, Error)]
+pub enum DeclarativeError {
+    #[error("malformed {format} at line {line}")]
+    Syntax { format: &'static str, line: usize },
+    #[error("expected {expected} for `{member}`")]
+    InvalidMember {
+        member: &'static str,
+        expected: &'static str,
+    },
+    #[error("invalid value `{value}` for attribute `{name}`")]
+    InvalidValue { name: String, value: String },
+}
+
+/// Reads a graph declared in YAML.
+pub fn read_yaml(input: &str) -> Result<Graph, DeclarativeError> {
+    let root = yaml::parse(input).map_err(|line| DeclarativeError::Syntax {
+        format: "YAML",
+        line,
+    })?;
+    build(&root)
+}
+
+/// Reads a graph declared in TOML.
+pub fn read_toml(input: &str) -> Result<Graph, DeclarativeError> {
+    let root = toml::parse(input).map_err(|line| DeclarativeError::Syntax {
+        format: "TOML",
+        line,
+    })?;
+    build(&root)
+}
+
+fn invalid(member: &'static str, expected: &'static str) -> DeclarativeError {
+    DeclarativeError::InvalidMember { member, expected }
+}
+
+/// Reads the members of `object` not in `reserved` as attributes.
+fn attributes(
+    object: &JsonValue,
+    reserved: &[&str],
+    member: &'static str,
+) -> Result<Vec<Attribute>, DeclarativeError> {
+    let members = match object {
+        JsonValue::Null => return Ok(vec![]),
+        object => object
+            .as_object()
+            .ok_or_else(|| invalid(member, "mapping"))?,
+    };
+    let mut attributes = vec![];
+    for (name, value) in members {
+        if reserved.contains(&name.as_str()) {
+            continue;
+        }
+        let value = match value {
+            JsonValue::Array(values) => values
+                .iter()
+                .filter_map(JsonValue::scalar_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            value => match value.scalar_string() {
+                Some(value) => value,
+                None => continue,
+            },
+        };
+        match Attribute::from_pair(name, &value) {
+            Ok(attribute) => attributes.push(attribute),
+            Err(AttributeParseError::AttributeNameNotFound) => {}
+            Err(_) => {
+                return Err(DeclarativeError::InvalidValue {
+                    name: name.clone(),
+                    value,
+                })
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+/// Node statements for a `nodes` member, with `defaults` preceding each node's own attributes.
+fn nodes(
+    nodes: Option<&JsonValue>,
+    defaults: &[Attribute],
+) -> Result<Vec<Statement>, DeclarativeError> {
+    let declared = match nodes {
+        None => vec![],
+        Some(JsonValue::Array(nodes)) => nodes
+            .iter()
+            .map(|node| match node {
+                JsonValue::Object(_) => Ok((
+                    node.get("id")
+                        .and_then(JsonValue::scalar_string)
+                        .ok_or_else(|| invalid("id", "string"))?,
+                    attributes(node, &["id"], "nodes")?,
+                )),
+                node => Ok((
+                    node.scalar_string()
+                        .ok_or_else(|| invalid("nodes", "node ID"))?,
+                    vec![],
+                )),
+            })
+            .collect::<Result<_, DeclarativeError>>()?,
+        Some(JsonValue::Object(nodes)) => nodes
+            .iter()
+            .map(|(name, node)| Ok((name.clone(), attributes(node, &[], "nodes")?)))
+            .collect::<Result<_, DeclarativeError>>()?,
+        Some(_) => return Err(invalid("nodes", "list or mapping")),
+    };
+    Ok(declared
+        .into_iter()
+        .map(|(name, attributes)| {
+            Statement::Node(NodeStatement {
+                name,
+                attributes: defaults.iter().cloned().chain(attributes).collect(),
+            })
+        })
+        .collect())
+}
+
+fn edges(edges: Option<&JsonValue>, op: EdgeOp) -> Result<Vec<Statement>, DeclarativeError> {
+    let edges = match edges {
+        None => return Ok(vec![]),
+        Some(edges) => edges.as_array().ok_or_else(|| invalid("edges", "list"))?,
+    };
+    let mut statements = vec![];
+    for edge in edges {
+        let (tails, heads, attributes) = match edge {
+            JsonValue::Array(path) => {
+                let list = path
+                    .iter()
+                    .map(JsonValue::scalar_string)
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|list| list.len() >= 2)
+                    .ok_or_else(|| invalid("edges", "path of at least two node IDs"))?;
+                statements.push(Statement::Edge(EdgeStatement {
+                    ops: vec![op; list.len() - 1],
+                    ports: vec![None; list.len()],
+                    list,
+                    attributes: Attributes::new(),
+                }));
+                continue;
+            }
+            edge => {
+                let ends = |member| match edge.get(member) {
+                    Some(JsonValue::Array(ids)) => ids
+                        .iter()
+                        .map(JsonValue::scalar_string)
+                        .collect::<Option<Vec<_>>>(),
+                    Some(value) => value.scalar_string().map(|id| vec![id]),
+                    None => None,
+                };
+                (
+                    ends("from").ok_or_else(|| invalid("from", "node ID or list of node IDs"))?,
+                    ends("to").ok_or_else(|| invalid("to", "node ID or list of node IDs"))?,
+                    attributes(edge, &["from", "to"], "edges")?,
+                )
+            }
+        };
+        for tail in &tails {
+            for head in &heads {
+                statements.push(Statement::Edge(EdgeStatement {
+                    list: vec![tail.clone(), head.clone()],
+                    ports: vec![None, None],
+                    ops: vec![op],
+                    attributes: attributes.clone().into(),
+                }));
+            }
+        }
+    }
+    Ok(statements)
+}
+
+fn build(root: &JsonValue) -> Result<Graph, DeclarativeError> {
+    let empty = JsonValue::Object(vec![]);
+    let root = match root {
+        // an empty document declares an empty graph
+        JsonValue::Null => &empty,
+        JsonValue::Object(_) => root,
+        _ => return Err(invalid("graph", "mapping")),
+    };
+    let flag = |member| match root.get(member) {
+        None => Ok(None),
+        Some(value) => value
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| invalid(member, "boolean")),
+    };
+    let kind = if flag("directed")?.unwrap_or(true) {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let strict = flag("strict")?.unwrap_or(false);
+    let id = match root.get("name") {
+        None => None,
+        Some(name) => Some(
+            name.scalar_string()
+                .ok_or_else(|| invalid("name", "string"))?,
+        ),
+    };
+
+    let mut statements = vec![];
+    for (member, kind) in [
+        ("graph", AttributeKind::Graph),
+        ("node", AttributeKind::Node),
+        ("edge", AttributeKind::Edge),
+    ] {
+        if let Some(defaults) = root.get(member) {
+            statements.push(Statement::Attribute(AttributeStatement {
+                kind,
+                attributes: attributes(defaults, &[], member)?.into(),
+            }));
+        }
+    }
+    statements.extend(nodes(root.get("nodes"), &[])?);
+    let clusters = match root.get("clusters") {
+        None => vec![],
+        Some(JsonValue::Array(clusters)) => clusters.iter().collect(),
+        Some(JsonValue::Object(clusters)) => clusters.iter().map(|(_, c)| c).collect(),
+        Some(_) => return Err(invalid("clusters", "list or mapping")),
+    };
+    for cluster in clusters {
+        if cluster.as_object().is_none() {
+            return Err(invalid("clusters", "mapping"));
+        }
+        let defaults = match cluster.get("node") {
+            Some(defaults) => attributes(defaults, &[], "node")?,
+            None => vec![],
+        };
+        statements.extend(nodes(cluster.get("nodes"), &defaults)?);
+    }
+    let op = match kind {
+        GraphKind::Directed => EdgeOp::Directed,
+        GraphKind::Undirected => EdgeOp::Undirected,
+    };
+    statements.extend(edges(root.get("edges"), op)?);
+
+    Ok(Graph {
+        kind,
+        strict,
+        id,
+        statements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::{Shape, Style},
+        model,
+    };
+
+    #[test]
+    fn yaml() {
+        let graph = read_yaml(
+            r#"
+name: services
+graph: { rankdir: LR }
+node: { shape: box }
+nodes:
+  - web
+  - id: db
+    shape: cylinder
+    owner: ops
+edges:
+  - [web, api, db]
+  - from: web
+    to: [cache, db]
+    style: [dashed, bold]
+clusters:
+  - name: backend
+    node: { shape: ellipse }
+    nodes: [api, cache]
+"#,
+        )
+        .unwrap();
+        assert_eq!(graph.id.as_deref(), Some("services"));
+        let graph = model::Graph::from_ir(&graph);
+        assert!(graph.is_directed());
+        let ids = graph
+            .nodes()
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["web", "db", "api", "cache"]);
+        assert_eq!(
+            graph.node("db").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Cylinder)]
+        );
+        assert_eq!(
+            graph.node("cache").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Ellipse)]
+        );
+        assert_eq!(graph.edges().len(), 4);
+        assert_eq!(
+            graph.edges()[3].attributes,
+            vec![Attribute::Style(vec![Style::Dashed, Style::Bold])]
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let graph = read_toml(
+            r#"
+directed = false
+
+[nodes.a]
+label = "A"
+
+[nodes.b]
+
+[[edges]]
+from = "a"
+to = "b"
+weight = 2
+"#,
+        )
+        .unwrap();
+        let graph = model::Graph::from_ir(&graph);
+        assert_eq!(graph.kind, GraphKind::Undirected);
+        assert_eq!(
+            graph.node("a").unwrap().attributes,
+            vec![Attribute::Label("A".to_owned())]
+        );
+        assert_eq!(graph.edges()[0].attributes, vec![Attribute::Weight(2.0)]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            read_yaml("nodes:\n  - id: a\n  -\n    label: b\n"),
+            Err(DeclarativeError::InvalidMember { member: "id", .. })
+        ));
+        assert!(matches!(
+            read_yaml("edges:\n  - [a]\n"),
+            Err(DeclarativeError::InvalidMember {
+                member: "edges",
+                ..
+            })
+        ));
+        assert!(matches!(
+            read_toml("[[edges]]\nfrom = \"a\"\nto = \"b\"\nweight = \"heavy\"\n"),
+            Err(DeclarativeError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            read_toml("directed = \"yes\"\n"),
+            Err(DeclarativeError::InvalidMember {
+                member: "directed",
+                ..
+            })
+        ));
+        assert!(matches!(
+            read_yaml("a: [\n"),
+            Err(DeclarativeError::Syntax { line: 1, .. })
+        ));
+    }
+}
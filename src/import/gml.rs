@@ -0,0 +1,241 @@
+//! GML import.
+//!
+//! Node keys and values are read as DOT attributes by name, with keys which don't name a known
+//! DOT attribute ignored. Nodes are identified by their `name` if present, as written by
+//! [`export::gml`](crate::export::gml), and by their `id` otherwise. The common `graphics` keys are
+//! translated: node `x` and `y` (with `y` pointing down) become `pos`, `w` and `h` (in points)
+//! become `width` and `height`, `type` becomes `shape`, and `fill` and `outline` become
+//! `fillcolor` and `color`; edge `fill` and `width` become `color` and `penwidth`.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError, Point, Position, Shape, Style},
+    gml::{self, get, GmlValue},
+    ir::GraphKind,
+    model::{set_attribute, Graph},
+};
+
+#[derive(Debug, Error)]
+pub enum GmlError {
+    #[error("malformed GML: {expected} at line {line}")]
+    Syntax { expected: &'static str, line: usize },
+    #[error("expected top-level `graph` list")]
+    MissingGraph,
+    #[error("`{element}` missing `{key}`")]
+    MissingKey {
+        element: &'static str,
+        key: &'static str,
+    },
+    #[error("edge refers to unknown node `{0}`")]
+    UnknownNode(String),
+    #[error("invalid value `{value}` for attribute `{name}`")]
+    InvalidValue { name: String, value: String },
+}
+
+/// Keys describing the graph structure rather than attributes.
+const RESERVED: &[&str] = &[
+    "id", "name", "source", "target", "directed", "node", "edge", "graphics",
+];
+
+const POINTS_PER_INCH: f64 = 72.0;
+
+fn attribute(attributes: &mut Vec<Attribute>, name: &str, value: &str) -> Result<(), GmlError> {
+    match Attribute::from_pair(name, value) {
+        Ok(attribute) => set_attribute(attributes, attribute),
+        Err(AttributeParseError::AttributeNameNotFound) => {}
+        Err(_) => {
+            return Err(GmlError::InvalidValue {
+                name: name.to_owned(),
+                value: value.to_owned(),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn attributes(list: &[(String, GmlValue)]) -> Result<Vec<Attribute>, GmlError> {
+    let mut attributes = vec![];
+    for (key, value) in list {
+        if RESERVED.contains(&key.as_str()) {
+            continue;
+        }
+        if let Some(value) = value.scalar_string() {
+            attribute(&mut attributes, key, &value)?;
+        }
+    }
+    Ok(attributes)
+}
+
+fn shape(name: &str) -> Option<Shape> {
+    Some(match name {
+        "rectangle" | "rect" | "box" => Shape::Box,
+        "ellipse" | "oval" => Shape::Ellipse,
+        "circle" => Shape::Circle,
+        "diamond" => Shape::Diamond,
+        "triangle" => Shape::Triangle,
+        "hexagon" => Shape::Hexagon,
+        "octagon" => Shape::Octagon,
+        _ => return None,
+    })
+}
+
+fn node_graphics(
+    graphics: &[(String, GmlValue)],
+    attributes: &mut Vec<Attribute>,
+) -> Result<(), GmlError> {
+    let number = |key| get(graphics, key).and_then(GmlValue::as_f64);
+    if let (Some(x), Some(y)) = (number("x"), number("y")) {
+        set_attribute(
+            attributes,
+            Attribute::Pos(Position::Point(Point { x, y: -y })),
+        );
+    }
+    if let Some(w) = number("w") {
+        set_attribute(attributes, Attribute::Width(w / POINTS_PER_INCH));
+    }
+    if let Some(h) = number("h") {
+        set_attribute(attributes, Attribute::Height(h / POINTS_PER_INCH));
+    }
+    let string = |key| get(graphics, key).and_then(GmlValue::scalar_string);
+    if let Some(shape) = string("type").as_deref().and_then(shape) {
+        set_attribute(attributes, Attribute::Shape(shape));
+    }
+    if let Some(fill) = string("fill") {
+        attribute(attributes, "fillcolor", &fill)?;
+        set_attribute(attributes, Attribute::Style(vec![Style::Filled]));
+    }
+    if let Some(outline) = string("outline") {
+        attribute(attributes, "color", &outline)?;
+    }
+    Ok(())
+}
+
+fn edge_graphics(
+    graphics: &[(String, GmlValue)],
+    attributes: &mut Vec<Attribute>,
+) -> Result<(), GmlError> {
+    if let Some(fill) = get(graphics, "fill").and_then(GmlValue::scalar_string) {
+        attribute(attributes, "color", &fill)?;
+    }
+    if let Some(width) = get(graphics, "width").and_then(GmlValue::as_f64) {
+        set_attribute(attributes, Attribute::PenWidth(width));
+    }
+    Ok(())
+}
+
+/// Reads a GML document into a graph.
+pub fn read(input: &str) -> Result<Graph, GmlError> {
+    let document = gml::parse(input).map_err(|e| GmlError::Syntax {
+        expected: e.expected,
+        line: e.line,
+    })?;
+    let list = get(&document, "graph")
+        .and_then(GmlValue::as_list)
+        .ok_or(GmlError::MissingGraph)?;
+    let kind = match get(list, "directed").and_then(GmlValue::as_f64) {
+        Some(directed) if directed != 0.0 => GraphKind::Directed,
+        _ => GraphKind::Undirected,
+    };
+    let mut graph = Graph::new(kind, false);
+    graph.attributes = attributes(list)?;
+
+    let mut ids = HashMap::new();
+    let lists = |key: &'static str| {
+        list.iter()
+            .filter(move |(k, _)| k == key)
+            .filter_map(|(_, value)| value.as_list())
+    };
+    for node in lists("node") {
+        let id = get(node, "id")
+            .and_then(GmlValue::scalar_string)
+            .ok_or(GmlError::MissingKey {
+                element: "node",
+                key: "id",
+            })?;
+        let name = match get(node, "name") {
+            Some(GmlValue::String(name)) => name.clone(),
+            _ => id.clone(),
+        };
+        let index = graph.add_node(&name);
+        ids.insert(id, index);
+        let mut attributes = attributes(node)?;
+        if let Some(graphics) = get(node, "graphics").and_then(GmlValue::as_list) {
+            node_graphics(graphics, &mut attributes)?;
+        }
+        for attribute in attributes {
+            set_attribute(&mut graph.node_mut(index).attributes, attribute);
+        }
+    }
+    for edge in lists("edge") {
+        let end = |key| {
+            let id =
+                get(edge, key)
+                    .and_then(GmlValue::scalar_string)
+                    .ok_or(GmlError::MissingKey {
+                        element: "edge",
+                        key,
+                    })?;
+            ids.get(&id).copied().ok_or(GmlError::UnknownNode(id))
+        };
+        let (tail, head) = (end("source")?, end("target")?);
+        let mut attributes = attributes(edge)?;
+        if let Some(graphics) = get(edge, "graphics").and_then(GmlValue::as_list) {
+            edge_graphics(graphics, &mut attributes)?;
+        }
+        graph.add_edge(tail, head, attributes);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gephi_style() {
+        let graph = read(
+            r##"Creator "Gephi"
+graph [
+  directed 1
+  label "network"
+  node [
+    id 1
+    label "Alice"
+    graphics [ x 10.0 y -20.0 w 36 h 72 type "ellipse" fill "#FF0000" ]
+  ]
+  node [ id 2 label "Bob" ]
+  edge [ source 1 target 2 weight 2.5 graphics [ width 2 ] ]
+]"##,
+        )
+        .unwrap();
+        assert!(graph.is_directed());
+        assert_eq!(
+            graph.attributes,
+            vec![Attribute::Label("network".to_owned())]
+        );
+        let alice = graph.node("1").unwrap();
+        assert_eq!(
+            alice.attributes,
+            vec![
+                Attribute::Label("Alice".to_owned()),
+                Attribute::Pos(Position::Point(Point { x: 10.0, y: 20.0 })),
+                Attribute::Width(0.5),
+                Attribute::Height(1.0),
+                Attribute::Shape(Shape::Ellipse),
+                Attribute::from_pair("fillcolor", "#FF0000").unwrap(),
+                Attribute::Style(vec![Style::Filled]),
+            ]
+        );
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Weight(2.5), Attribute::PenWidth(2.0)]
+        );
+        assert!(matches!(
+            read("graph [ node [ id 1 ] edge [ source 1 target 3 ] ]"),
+            Err(GmlError::UnknownNode(id)) if id == "3"
+        ));
+    }
+}
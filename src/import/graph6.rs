@@ -0,0 +1,149 @@
+//! graph6 and sparse6 import.
+//!
+//! Both formats encode undirected graphs on vertices `0..n`, which become nodes with those numbers
+//! as IDs. sparse6 strings start with `:`; anything else is read as graph6. Optional
+//! `>>graph6<<` and `>>sparse6<<` headers are skipped.
+
+use thiserror::Error;
+
+use crate::{ir::GraphKind, model::Graph};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Graph6Error {
+    #[error("invalid character `{0}`; expected printable ASCII in `?`..=`~`")]
+    InvalidCharacter(char),
+    #[error("input ends before the vertex count or adjacency data is complete")]
+    Truncated,
+}
+
+/// Values of the 6-bit groups of `input`.
+fn groups(input: &str) -> Result<Vec<u8>, Graph6Error> {
+    input
+        .chars()
+        .map(|c| match c {
+            '?'..='~' => Ok(c as u8 - 63),
+            c => Err(Graph6Error::InvalidCharacter(c)),
+        })
+        .collect()
+}
+
+/// Decodes the vertex count at the start of `groups`, returning it with the remaining groups.
+fn vertex_count(groups: &[u8]) -> Result<(usize, &[u8]), Graph6Error> {
+    let (len, start) = match groups {
+        [63, 63, ..] => (6, 2),
+        [63, ..] => (3, 1),
+        [_, ..] => (1, 0),
+        [] => return Err(Graph6Error::Truncated),
+    };
+    let digits = groups
+        .get(start..start + len)
+        .ok_or(Graph6Error::Truncated)?;
+    let n = digits
+        .iter()
+        .fold(0, |n, &digit| (n << 6) | usize::from(digit));
+    Ok((n, &groups[start + len..]))
+}
+
+/// Reads groups of 6 bits, most significant first.
+struct Bits<'a> {
+    groups: &'a [u8],
+    position: usize,
+}
+
+impl Bits<'_> {
+    fn remaining(&self) -> usize {
+        self.groups.len() * 6 - self.position
+    }
+
+    fn next(&mut self) -> Option<bool> {
+        let group = self.groups.get(self.position / 6)?;
+        let bit = group >> (5 - self.position % 6) & 1 == 1;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read(&mut self, count: usize) -> Option<usize> {
+        (0..count).try_fold(0, |value, _| Some(value << 1 | usize::from(self.next()?)))
+    }
+}
+
+fn new_graph(n: usize) -> Graph {
+    let mut graph = Graph::new(GraphKind::Undirected, false);
+    for i in 0..n {
+        graph.add_node(&i.to_string());
+    }
+    graph
+}
+
+fn decode_graph6(groups: &[u8]) -> Result<Graph, Graph6Error> {
+    let (n, groups) = vertex_count(groups)?;
+    let mut graph = new_graph(n);
+    let mut bits = Bits {
+        groups,
+        position: 0,
+    };
+    for j in 1..n {
+        for i in 0..j {
+            if bits.next().ok_or(Graph6Error::Truncated)? {
+                graph.add_edge(i, j, vec![]);
+            }
+        }
+    }
+    Ok(graph)
+}
+
+fn decode_sparse6(groups: &[u8]) -> Result<Graph, Graph6Error> {
+    let (n, groups) = vertex_count(groups)?;
+    let mut graph = new_graph(n);
+    let k = bit_width(n);
+    let mut bits = Bits {
+        groups,
+        position: 0,
+    };
+    let mut v = 0;
+    while bits.remaining() > k {
+        let (b, x) = match (bits.next(), bits.read(k)) {
+            (Some(b), Some(x)) => (b, x),
+            _ => break,
+        };
+        if b {
+            v += 1;
+        }
+        if v >= n {
+            break;
+        }
+        if x > v {
+            v = x;
+        } else {
+            graph.add_edge(x, v, vec![]);
+        }
+    }
+    Ok(graph)
+}
+
+/// Number of bits needed to represent `n - 1`.
+pub(crate) fn bit_width(n: usize) -> usize {
+    (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// Decodes a single graph6 or sparse6 string.
+pub fn decode(input: &str) -> Result<Graph, Graph6Error> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix(">>graph6<<")
+        .or_else(|| input.strip_prefix(">>sparse6<<"))
+        .unwrap_or(input);
+    match input.strip_prefix(':') {
+        Some(sparse) => decode_sparse6(&groups(sparse)?),
+        None => decode_graph6(&groups(input)?),
+    }
+}
+
+/// Reads a file of graph6 or sparse6 strings, one graph per line.
+pub fn read(input: &str) -> Result<Vec<Graph>, Graph6Error> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(decode)
+        .collect()
+}
@@ -0,0 +1,215 @@
+//! GraphML import.
+//!
+//! `data` values are parsed as DOT attributes by their key's `attr.name`; keys which don't name a
+//! known DOT attribute (e.g. yEd graphics) are ignored. Nested graphs are flattened into the top
+//! level graph.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    attribute::Attribute,
+    ir::GraphKind,
+    model::{set_attribute, Graph},
+    xml::{self, Element},
+};
+
+#[derive(Debug, Error)]
+pub enum GraphMlError {
+    #[error("malformed XML: {expected} at line {line}")]
+    Xml { expected: &'static str, line: usize },
+    #[error("expected `graphml` document with a `graph` element")]
+    MissingGraph,
+    #[error("`{element}` element missing `{attribute}` attribute")]
+    MissingAttribute {
+        element: &'static str,
+        attribute: &'static str,
+    },
+    #[error("invalid value `{value}` for attribute `{name}`")]
+    InvalidValue { name: String, value: String },
+}
+
+struct Key {
+    domain: String,
+    name: String,
+    default: Option<String>,
+}
+
+struct Reader {
+    keys: HashMap<String, Key>,
+    graph: Graph,
+}
+
+fn required<'a>(
+    element: &'a Element,
+    name: &'static str,
+    attribute: &'static str,
+) -> Result<&'a str, GraphMlError> {
+    element
+        .attribute(attribute)
+        .ok_or(GraphMlError::MissingAttribute {
+            element: name,
+            attribute,
+        })
+}
+
+impl Reader {
+    /// Collects the attributes of `element` from its `data` children and the defaults of keys
+    /// declared for `domain`.
+    fn attributes(&self, element: &Element, domain: &str) -> Result<Vec<Attribute>, GraphMlError> {
+        let mut values = self
+            .keys
+            .iter()
+            .filter(|(_, key)| key.domain == domain || key.domain == "all")
+            .filter_map(|(id, key)| {
+                key.default
+                    .as_ref()
+                    .map(|default| (id.as_str(), default.clone()))
+            })
+            .collect::<Vec<_>>();
+        for data in element.children_named("data") {
+            values.push((required(data, "data", "key")?, data.text()));
+        }
+        let mut attributes = vec![];
+        for (id, value) in values {
+            let name = match self.keys.get(id) {
+                Some(key) => key.name.as_str(),
+                None => continue,
+            };
+            match Attribute::from_pair(name, value.trim()) {
+                Ok(attribute) => set_attribute(&mut attributes, attribute),
+                Err(crate::attribute::AttributeParseError::AttributeNameNotFound) => {}
+                Err(_) => {
+                    return Err(GraphMlError::InvalidValue {
+                        name: name.to_owned(),
+                        value,
+                    })
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn read_graph(&mut self, element: &Element, directed: bool) -> Result<(), GraphMlError> {
+        let directed = match element.attribute("edgedefault") {
+            Some(default) => default == "directed",
+            None => directed,
+        };
+        for node in element.children_named("node") {
+            let id = required(node, "node", "id")?;
+            let index = self.graph.add_node(id);
+            for attribute in self.attributes(node, "node")? {
+                set_attribute(&mut self.graph.node_mut(index).attributes, attribute);
+            }
+            for nested in node.children_named("graph") {
+                self.read_graph(nested, directed)?;
+            }
+        }
+        for edge in element.children_named("edge") {
+            let tail = self.graph.add_node(required(edge, "edge", "source")?);
+            let head = self.graph.add_node(required(edge, "edge", "target")?);
+            let attributes = self.attributes(edge, "edge")?;
+            self.graph.add_edge(tail, head, attributes);
+        }
+        Ok(())
+    }
+}
+
+/// Reads a GraphML document into a graph.
+pub fn read(input: &str) -> Result<Graph, GraphMlError> {
+    let root = xml::parse(input).map_err(|e| GraphMlError::Xml {
+        expected: e.expected,
+        line: e.line,
+    })?;
+    if root.local_name() != "graphml" {
+        return Err(GraphMlError::MissingGraph);
+    }
+    let mut keys = HashMap::new();
+    for key in root.children_named("key") {
+        let id = required(key, "key", "id")?;
+        keys.insert(
+            id.to_owned(),
+            Key {
+                domain: key.attribute("for").unwrap_or("all").to_owned(),
+                name: key.attribute("attr.name").unwrap_or(id).to_owned(),
+                default: key.children_named("default").next().map(Element::text),
+            },
+        );
+    }
+    let element = root
+        .children_named("graph")
+        .next()
+        .ok_or(GraphMlError::MissingGraph)?;
+    let directed = element.attribute("edgedefault") == Some("directed");
+    let kind = if directed {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let mut reader = Reader {
+        keys,
+        graph: Graph::new(kind, false),
+    };
+    reader.graph.id = element.attribute("id").map(str::to_owned);
+    reader.graph.attributes = reader.attributes(element, "graph")?;
+    reader.read_graph(element, directed)?;
+    Ok(reader.graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::{Shape, Style},
+        export::graphml::write,
+        ir::parse_graph,
+    };
+
+    #[test]
+    fn round_trip() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph { bgcolor=red node [shape=box] a [label="A & B"] a -> b [style="dashed,bold"] }"#,
+            )
+            .unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output).unwrap();
+        let read = read(std::str::from_utf8(&output).unwrap()).unwrap();
+        assert_eq!(read.kind, GraphKind::Directed);
+        assert_eq!(read.attributes, graph.attributes);
+        assert_eq!(read.nodes(), graph.nodes());
+        assert_eq!(read.edges(), graph.edges());
+        assert_eq!(
+            read.edges()[0].attributes,
+            vec![Attribute::Style(vec![Style::Dashed, Style::Bold])]
+        );
+    }
+
+    #[test]
+    fn defaults_and_unknown_keys() {
+        let graph = read(
+            r#"<graphml>
+  <key id="shape" for="node" attr.name="shape"><default>box</default></key>
+  <key id="g" for="node" yfiles.type="nodegraphics"/>
+  <graph edgedefault="undirected">
+    <node id="a"><data key="g"><y:ShapeNode/></data></node>
+    <node id="b"><data key="shape">circle</data></node>
+    <edge source="a" target="c"/>
+  </graph>
+</graphml>"#,
+        )
+        .unwrap();
+        assert_eq!(graph.kind, GraphKind::Undirected);
+        assert_eq!(
+            graph.node("a").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Box)]
+        );
+        assert_eq!(
+            graph.node("b").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Circle)]
+        );
+        assert!(graph.node("c").unwrap().attributes.is_empty());
+    }
+}
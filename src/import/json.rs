@@ -0,0 +1,246 @@
+//! Graphviz JSON import.
+//!
+//! Reads the structure written by [`export::json`](crate::export::json) and `dot -Tdot_json`
+//! back into the intermediate representation: graph attributes become a `graph` attribute
+//! statement, each entry of `objects` a node statement and each entry of `edges` an edge statement.
+//! Graph names starting with `%` are the names Graphviz generates for anonymous graphs, and are
+//! read as no ID. As with the other importers, members which don't name a known DOT attribute
+//! (including Graphviz's `_`-prefixed drawing members) are ignored.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError},
+    ir::{
+        AttributeKind, AttributeStatement, EdgeOp, EdgeStatement, Graph, GraphKind, NodeStatement,
+        Statement,
+    },
+    json::{self, JsonValue},
+};
+
+#[derive(Debug, Error)]
+pub enum JsonError {
+    #[error("malformed JSON at line {0}")]
+    Syntax(usize),
+    #[error("expected {expected} for `{member}`")]
+    InvalidMember {
+        member: &'static str,
+        expected: &'static str,
+    },
+    #[error("edge refers to unknown object {0}")]
+    UnknownObject(usize),
+    #[error("subgraphs are not supported")]
+    Subgraph,
+    #[error("invalid value `{value}` for attribute `{name}`")]
+    InvalidValue { name: String, value: String },
+}
+
+/// Members describing the graph structure rather than attributes.
+const RESERVED: &[&str] = &[
+    "name", "directed", "strict", "objects", "edges", "tail", "head",
+];
+
+fn member<'a, T>(
+    object: &'a JsonValue,
+    member: &'static str,
+    expected: &'static str,
+    convert: impl FnOnce(&'a JsonValue) -> Option<T>,
+) -> Result<T, JsonError> {
+    object
+        .get(member)
+        .and_then(convert)
+        .ok_or(JsonError::InvalidMember { member, expected })
+}
+
+/// Array member which is omitted when empty, as `objects` and `edges` are.
+fn optional_array<'a>(
+    object: &'a JsonValue,
+    member: &'static str,
+) -> Result<&'a [JsonValue], JsonError> {
+    match object.get(member) {
+        Some(value) => value.as_array().ok_or(JsonError::InvalidMember {
+            member,
+            expected: "array",
+        }),
+        None => Ok(&[]),
+    }
+}
+
+fn attributes(object: &JsonValue) -> Result<Vec<Attribute>, JsonError> {
+    let mut attributes = vec![];
+    for (name, value) in object.as_object().unwrap_or_default() {
+        if name.starts_with('_') || RESERVED.contains(&name.as_str()) {
+            continue;
+        }
+        let value = match value.scalar_string() {
+            Some(value) => value,
+            None => continue,
+        };
+        match Attribute::from_pair(name, &value) {
+            Ok(attribute) => attributes.push(attribute),
+            Err(AttributeParseError::AttributeNameNotFound) => {}
+            Err(_) => {
+                return Err(JsonError::InvalidValue {
+                    name: name.clone(),
+                    value,
+                })
+            }
+        }
+    }
+    Ok(attributes)
+}
+
+/// Reads a Graphviz JSON document into a graph.
+pub fn read(input: &str) -> Result<Graph, JsonError> {
+    let root = json::parse(input).map_err(JsonError::Syntax)?;
+    if root.as_object().is_none() {
+        return Err(JsonError::InvalidMember {
+            member: "graph",
+            expected: "object",
+        });
+    }
+    if root
+        .get("_subgraph_cnt")
+        .and_then(JsonValue::as_f64)
+        .is_some_and(|count| count > 0.0)
+    {
+        return Err(JsonError::Subgraph);
+    }
+    let kind = if member(&root, "directed", "boolean", JsonValue::as_bool)? {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let strict = root
+        .get("strict")
+        .and_then(JsonValue::as_bool)
+        .unwrap_or(false);
+    let id = root
+        .get("name")
+        .and_then(JsonValue::as_str)
+        .filter(|name| !name.starts_with('%'))
+        .map(str::to_owned);
+
+    let mut statements = vec![];
+    let graph_attributes = attributes(&root)?;
+    if !graph_attributes.is_empty() {
+        statements.push(Statement::Attribute(AttributeStatement {
+            kind: AttributeKind::Graph,
+            attributes: graph_attributes.into(),
+        }));
+    }
+
+    let mut names = HashMap::new();
+    for (i, object) in optional_array(&root, "objects")?.iter().enumerate() {
+        if object.get("nodes").is_some() || object.get("subgraphs").is_some() {
+            return Err(JsonError::Subgraph);
+        }
+        let name = member(object, "name", "string", JsonValue::as_str)?;
+        let gvid = object
+            .get("_gvid")
+            .and_then(JsonValue::as_f64)
+            .map_or(i, |gvid| gvid as usize);
+        names.insert(gvid, name.to_owned());
+        statements.push(Statement::Node(NodeStatement {
+            name: name.to_owned(),
+            attributes: attributes(object)?.into(),
+        }));
+    }
+
+    let op = match kind {
+        GraphKind::Directed => EdgeOp::Directed,
+        GraphKind::Undirected => EdgeOp::Undirected,
+    };
+    for edge in optional_array(&root, "edges")? {
+        let mut list = vec![];
+        for end in ["tail", "head"] {
+            let gvid = member(edge, end, "object index", JsonValue::as_f64)? as usize;
+            list.push(
+                names
+                    .get(&gvid)
+                    .cloned()
+                    .ok_or(JsonError::UnknownObject(gvid))?,
+            );
+        }
+        statements.push(Statement::Edge(EdgeStatement {
+            ports: vec![None; list.len()],
+            list,
+            ops: vec![op],
+            attributes: attributes(edge)?.into(),
+        }));
+    }
+
+    Ok(Graph {
+        kind,
+        strict,
+        id,
+        statements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::Shape,
+        export::json::{write, JsonFormat},
+        ir::parse_graph,
+        model,
+    };
+
+    #[test]
+    fn round_trip() {
+        let graph = model::Graph::from_ir(
+            &parse_graph(
+                r#"strict digraph G { bgcolor=red node [shape=box] a [label="A \"1\""] a -> b [weight=2] }"#,
+            )
+            .unwrap(),
+        );
+        let mut output = vec![];
+        write(&graph, &mut output, JsonFormat::DotJson).unwrap();
+        let ir = read(std::str::from_utf8(&output).unwrap()).unwrap();
+        assert_eq!(ir.id.as_deref(), Some("G"));
+        assert!(ir.strict);
+        let read = model::Graph::from_ir(&ir);
+        assert_eq!(read.kind, GraphKind::Directed);
+        assert_eq!(read.attributes, graph.attributes);
+        assert_eq!(read.nodes(), graph.nodes());
+        assert_eq!(read.edges(), graph.edges());
+        assert_eq!(
+            read.node("b").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Box)]
+        );
+    }
+
+    #[test]
+    fn graphviz_output() {
+        let graph = read(
+            r#"{
+  "name": "%3", "directed": false, "strict": false, "_draw_": [], "xdotversion": "1.7",
+  "_subgraph_cnt": 0,
+  "objects": [{"_gvid": 0, "name": "a", "shape": "circle"}, {"_gvid": 1, "name": "b"}],
+  "edges": [{"_gvid": 0, "tail": 1, "head": 0}]
+}"#,
+        )
+        .unwrap();
+        assert_eq!(graph.id, None);
+        assert_eq!(graph.statements.len(), 3);
+        match &graph.statements[2] {
+            Statement::Edge(edge) => {
+                assert_eq!(edge.list, vec!["b", "a"]);
+                assert_eq!(edge.ops, vec![EdgeOp::Undirected]);
+            }
+            statement => panic!("expected edge, found {:?}", statement),
+        }
+        assert!(matches!(
+            read(r#"{"directed": true, "edges": [{"tail": 0, "head": 1}]}"#),
+            Err(JsonError::UnknownObject(0))
+        ));
+        assert!(matches!(
+            read(r#"{"directed": true, "_subgraph_cnt": 1}"#),
+            Err(JsonError::Subgraph)
+        ));
+    }
+}
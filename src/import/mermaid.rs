@@ -0,0 +1,519 @@
+//! Mermaid flowchart import.
+//!
+//! Reads `graph` and `flowchart` diagrams into the intermediate representation. The mapping is
+//! best-effort:
+//!
+//! * nodes default to `shape=box`, as Mermaid nodes do; bracket shapes map to the closest DOT
+//!   shape (`(text)` and `([text])` become rounded boxes, `[[text]]` a double-bordered box and
+//!   `>text]` a `cds`);
+//! * dotted (`-.->`), thick (`==>`) and invisible (`~~~`) links map to the `dotted`, `bold` and
+//!   `invis` styles, links without arrowheads (`---`) to `dir=none`, and `o` and `x` heads to `odot`
+//!   and `tee`;
+//! * `style` statements are translated for the `fill`, `stroke`, `stroke-width`,
+//!   `stroke-dasharray` and `color` properties;
+//! * the diagram direction becomes a `rankdir` definition;
+//! * subgraphs are flattened, and `classDef`, `class`, `click` and `linkStyle` statements are
+//!   ignored.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_until, take_while1, take_while_m_n},
+    character::complete::{char, multispace0, multispace1, one_of, satisfy},
+    combinator::{cut, map, not, opt, peek},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
+};
+use thiserror::Error;
+
+use crate::{
+    attribute::{ArrowType, Attribute, DirType, Shape, Style},
+    ir::{
+        AttributeKind, AttributeStatement, DefinitionStatement, EdgeOp, EdgeStatement, Graph,
+        GraphKind, NodeStatement, Statement,
+    },
+    ws::ws,
+};
+
+type MermaidResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+#[derive(Debug, Error)]
+pub enum MermaidError {
+    #[error("unsupported diagram type `{0}`; expected `graph` or `flowchart`")]
+    UnsupportedDiagram(String),
+    #[error("{expected} at line {line}")]
+    Syntax { expected: &'static str, line: usize },
+}
+
+/// Mermaid node shapes by opening and closing delimiter, with the longest delimiters first.
+const SHAPES: &[(&str, &str, Shape)] = &[
+    ("(((", ")))", Shape::DoubleCircle),
+    ("((", "))", Shape::Circle),
+    ("([", "])", Shape::Box),
+    ("[[", "]]", Shape::Box),
+    ("[(", ")]", Shape::Cylinder),
+    ("{{", "}}", Shape::Hexagon),
+    ("[/", "/]", Shape::Parallelogram),
+    ("[/", "\\]", Shape::Trapezium),
+    ("[\\", "\\]", Shape::Parallelogram),
+    ("[\\", "/]", Shape::InvTrapezium),
+    ("[", "]", Shape::Box),
+    ("(", ")", Shape::Box),
+    ("{", "}", Shape::Diamond),
+    (">", "]", Shape::Cds),
+];
+
+/// Statements which have no DOT equivalent.
+const IGNORED: &[&str] = &[
+    "subgraph",
+    "end",
+    "direction",
+    "classDef",
+    "class",
+    "click",
+    "linkStyle",
+    "accTitle",
+    "accDescr",
+];
+
+struct NodeRef {
+    id: String,
+    attributes: Vec<Attribute>,
+}
+
+struct Chain {
+    groups: Vec<Vec<NodeRef>>,
+    links: Vec<Vec<Attribute>>,
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn id_parser(input: &str) -> MermaidResult<'_, &str> {
+    context("expected node", take_while1(is_id_char))(input)
+}
+
+/// Converts label text to a DOT label, translating Mermaid's line breaks.
+fn label(text: &str) -> Attribute {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text);
+    Attribute::Label(
+        text.replace("<br>", "\\n")
+            .replace("<br/>", "\\n")
+            .replace("<br />", "\\n"),
+    )
+}
+
+/// Text between `open` and `close`. Unquoted text may not contain the last character of `close`,
+/// so that e.g. `[/a\]` isn't read as the start of a `[/a/]` shape ending further on.
+fn delimited_text<'a>(input: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    let inner = input.strip_prefix(open)?;
+    let (text, rest) = match inner.trim_start().strip_prefix('"') {
+        Some(quoted) => {
+            let end = quoted.find('"')?;
+            (&quoted[..end], quoted[end + 1..].trim_start())
+        }
+        None => {
+            let end = inner.find(close)?;
+            let text = &inner[..end];
+            if text.contains(close.chars().last()?) {
+                return None;
+            }
+            (text, &inner[end..])
+        }
+    };
+    Some((rest.strip_prefix(close)?, text))
+}
+
+fn shape_parser(input: &str) -> MermaidResult<'_, Vec<Attribute>> {
+    for (open, close, shape) in SHAPES {
+        if let Some((rest, text)) = delimited_text(input, open, close) {
+            let mut attributes = vec![label(text)];
+            if *shape != Shape::Box {
+                attributes.push(Attribute::Shape(shape.clone()));
+            }
+            match *open {
+                "(" | "([" => attributes.push(Attribute::Style(vec![Style::Rounded])),
+                "[[" => attributes.push(Attribute::Peripheries(2)),
+                _ => {}
+            }
+            return Ok((rest, attributes));
+        }
+    }
+    Err(nom::Err::Error(VerboseError {
+        errors: vec![(input, VerboseErrorKind::Context("expected node shape"))],
+    }))
+}
+
+fn node_ref_parser(input: &str) -> MermaidResult<'_, NodeRef> {
+    map(
+        terminated(
+            pair(id_parser, opt(shape_parser)),
+            opt(preceded(tag(":::"), id_parser)),
+        ),
+        |(id, attributes)| NodeRef {
+            id: id.to_owned(),
+            attributes: attributes.unwrap_or_default(),
+        },
+    )(input)
+}
+
+fn node_group_parser(input: &str) -> MermaidResult<'_, Vec<NodeRef>> {
+    separated_list1(ws(char('&')), node_ref_parser)(input)
+}
+
+/// Arrowhead at the end of a link. `o` and `x` only count as heads when not followed by more of
+/// an ID.
+fn head_parser(input: &str) -> MermaidResult<'_, char> {
+    alt((
+        char('>'),
+        terminated(one_of("ox"), not(satisfy(is_id_char))),
+    ))(input)
+}
+
+fn arrow_type(head: char) -> Option<ArrowType> {
+    match head {
+        'o' => Some(ArrowType::ODot),
+        'x' => Some(ArrowType::Tee),
+        _ => None,
+    }
+}
+
+fn line_parser(input: &str) -> MermaidResult<'_, &str> {
+    take_while_m_n(2, usize::MAX, |c| "-.=~".contains(c))(input)
+}
+
+/// Link with its text between the two halves of the line, e.g. `-. text .->`.
+fn text_link_parser(input: &str) -> MermaidResult<'_, (&str, &str)> {
+    let (rest, open) = terminated(alt((tag("--"), tag("-."), tag("=="))), multispace1)(input)?;
+    let closer = if open == "-." { ".-" } else { open };
+    let (rest, text) = terminated(take_until(closer), line_parser)(rest)?;
+    Ok((rest, (open, text)))
+}
+
+/// Parses a link, e.g. `-->`, `-. text .->` or `==>|text|`, into edge attributes.
+fn link_parser(input: &str) -> MermaidResult<'_, Vec<Attribute>> {
+    let (rest, start) = opt(terminated(one_of("<ox"), peek(one_of("-=.~"))))(input)?;
+    let (rest, (line, text)) = alt((
+        map(text_link_parser, |(line, text)| (line, Some(text))),
+        map(line_parser, |line| (line, None)),
+    ))(rest)?;
+    let (rest, end) = opt(head_parser)(rest)?;
+    let (rest, piped) = opt(preceded(
+        multispace0,
+        delimited(char('|'), take_until("|"), char('|')),
+    ))(rest)?;
+
+    let mut attributes = vec![];
+    if let Some(text) = text.or(piped) {
+        attributes.push(label(text));
+    }
+    let style = if line.contains('.') {
+        Some(Style::Dotted)
+    } else if line.starts_with('=') {
+        Some(Style::Bold)
+    } else if line.starts_with('~') {
+        Some(Style::Invis)
+    } else {
+        None
+    };
+    attributes.extend(style.map(|style| Attribute::Style(vec![style])));
+    match (start, end) {
+        (None, Some(_)) => {}
+        (None, None) => attributes.push(Attribute::Dir(DirType::None)),
+        (Some(_), None) => attributes.push(Attribute::Dir(DirType::Back)),
+        (Some(_), Some(_)) => attributes.push(Attribute::Dir(DirType::Both)),
+    }
+    attributes.extend(start.and_then(arrow_type).map(Attribute::ArrowTail));
+    attributes.extend(end.and_then(arrow_type).map(Attribute::ArrowHead));
+    Ok((rest, attributes))
+}
+
+fn chain_parser(input: &str) -> MermaidResult<'_, Chain> {
+    map(
+        pair(
+            node_group_parser,
+            many0(pair(ws(link_parser), cut(node_group_parser))),
+        ),
+        |(first, rest)| {
+            let mut groups = vec![first];
+            let mut links = vec![];
+            for (link, group) in rest {
+                links.push(link);
+                groups.push(group);
+            }
+            Chain { groups, links }
+        },
+    )(input)
+}
+
+fn expand_hex(color: &str) -> String {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 3 || hex.len() == 4 => {
+            let mut expanded = "#".to_owned();
+            for c in hex.chars() {
+                expanded.push(c);
+                expanded.push(c);
+            }
+            expanded
+        }
+        _ => color.to_owned(),
+    }
+}
+
+/// Translates the CSS-like properties of a `style` statement.
+fn style_attributes(properties: &str) -> Vec<Attribute> {
+    let mut attributes = vec![];
+    let mut styles = vec![];
+    for property in properties.split(',') {
+        let (name, value) = match property.split_once(':') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => continue,
+        };
+        let attribute = match name {
+            "fill" => {
+                styles.push(Style::Filled);
+                Attribute::from_pair("fillcolor", &expand_hex(value))
+            }
+            "stroke" => Attribute::from_pair("color", &expand_hex(value)),
+            "color" => Attribute::from_pair("fontcolor", &expand_hex(value)),
+            "stroke-width" => Attribute::from_pair("penwidth", value.trim_end_matches("px")),
+            "stroke-dasharray" => {
+                styles.push(Style::Dashed);
+                continue;
+            }
+            _ => continue,
+        };
+        attributes.extend(attribute.ok());
+    }
+    if !styles.is_empty() {
+        attributes.push(Attribute::Style(styles));
+    }
+    attributes
+}
+
+/// Adds the statements for a chain of links, in source order. Nodes are only declared separately
+/// when they carry attributes or stand alone.
+fn push_chain(statements: &mut Vec<Statement>, chain: Chain) {
+    let push_nodes = |statements: &mut Vec<Statement>, group: &[NodeRef]| {
+        for node in group {
+            if !node.attributes.is_empty() || chain.links.is_empty() {
+                statements.push(Statement::Node(NodeStatement {
+                    name: node.id.clone(),
+                    attributes: node.attributes.clone().into(),
+                }));
+            }
+        }
+    };
+    push_nodes(statements, &chain.groups[0]);
+    for (i, link) in chain.links.iter().enumerate() {
+        for tail in &chain.groups[i] {
+            for head in &chain.groups[i + 1] {
+                statements.push(Statement::Edge(EdgeStatement {
+                    list: vec![tail.id.clone(), head.id.clone()],
+                    ports: vec![None, None],
+                    ops: vec![EdgeOp::Directed],
+                    attributes: link.clone().into(),
+                }));
+            }
+        }
+        push_nodes(statements, &chain.groups[i + 1]);
+    }
+}
+
+/// Reads a Mermaid flowchart into a graph.
+pub fn read(input: &str) -> Result<Graph, MermaidError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| MermaidError::UnsupportedDiagram(String::new()))?;
+    let mut words = header.split(|c: char| c.is_whitespace() || c == ';');
+    let diagram = words.next().unwrap_or_default();
+    if diagram != "graph" && diagram != "flowchart" {
+        return Err(MermaidError::UnsupportedDiagram(diagram.to_owned()));
+    }
+    let mut statements = vec![Statement::Attribute(AttributeStatement {
+        kind: AttributeKind::Node,
+        attributes: vec![Attribute::Shape(Shape::Box)].into(),
+    })];
+    match words.find(|word| !word.is_empty()) {
+        Some("TB" | "TD") | None => {}
+        Some(direction @ ("BT" | "LR" | "RL")) => {
+            statements.push(Statement::Definition(DefinitionStatement {
+                lhs: "rankdir".to_owned(),
+                rhs: direction.to_owned(),
+            }))
+        }
+        Some(_) => {
+            return Err(MermaidError::Syntax {
+                expected: "expected diagram direction",
+                line: 1,
+            })
+        }
+    }
+
+    for (number, line) in lines {
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+        if IGNORED.contains(&keyword) {
+            continue;
+        }
+        if keyword == "style" {
+            let mut parts = line.split_whitespace().skip(1);
+            if let Some(id) = parts.next() {
+                statements.push(Statement::Node(NodeStatement {
+                    name: id.to_owned(),
+                    attributes: style_attributes(&parts.collect::<Vec<_>>().join(" ")).into(),
+                }));
+            }
+            continue;
+        }
+        let result = terminated(separated_list0(char(';'), ws(chain_parser)), opt(char(';')))(line);
+        let expected = match result {
+            Ok(("", chains)) => {
+                for chain in chains {
+                    push_chain(&mut statements, chain);
+                }
+                continue;
+            }
+            Ok((rest, _)) if rest == line => "expected node",
+            Ok(_) => "expected link, `&` or end of statement",
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e
+                .errors
+                .iter()
+                .find_map(|(_, kind)| match kind {
+                    VerboseErrorKind::Context(context) => Some(*context),
+                    _ => None,
+                })
+                .unwrap_or("expected statement"),
+            Err(nom::Err::Incomplete(_)) => "expected statement",
+        };
+        return Err(MermaidError::Syntax {
+            expected,
+            line: number,
+        });
+    }
+
+    Ok(Graph {
+        kind: GraphKind::Directed,
+        strict: false,
+        id: None,
+        statements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model;
+
+    const INPUT: &str = r#"%% migrated from the docs
+flowchart LR
+    A[Start] --> B{Is it?}
+    B -->|Yes| C((OK)) & D[(Store)]
+    B -. "No" .-> E([Retry]):::warn
+    E == again ==> A
+    C --- F>flag]; F ~~~ G[/in\]
+    subgraph sg [Group]
+        G <--> H
+    end
+    classDef warn fill:#f96
+    style H fill:#f9f,stroke:#333,stroke-width:4px
+"#;
+
+    #[test]
+    fn flowchart() {
+        let ir = read(INPUT).unwrap();
+        assert!(ir.statements.iter().any(|statement| matches!(
+            statement,
+            Statement::Definition(d) if d.lhs == "rankdir" && d.rhs == "LR"
+        )));
+        let graph = model::Graph::from_ir(&ir);
+        let ids = graph
+            .nodes()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["A", "B", "C", "D", "E", "F", "G", "H"]);
+        assert_eq!(
+            graph.node("B").unwrap().attributes,
+            vec![
+                Attribute::Shape(Shape::Diamond),
+                Attribute::Label("Is it?".to_owned()),
+            ]
+        );
+        assert_eq!(
+            graph.node("E").unwrap().attributes[1..],
+            [
+                Attribute::Label("Retry".to_owned()),
+                Attribute::Style(vec![Style::Rounded]),
+            ]
+        );
+        assert_eq!(
+            graph.node("G").unwrap().attributes[0],
+            Attribute::Shape(Shape::Trapezium)
+        );
+        assert_eq!(
+            graph.node("H").unwrap().attributes[1..],
+            [
+                Attribute::from_pair("fillcolor", "#ff99ff").unwrap(),
+                Attribute::from_pair("color", "#333333").unwrap(),
+                Attribute::PenWidth(4.0),
+                Attribute::Style(vec![Style::Filled]),
+            ]
+        );
+
+        let edge = |tail: &str, head: &str| {
+            let (tail, head) = (
+                graph.node_index(tail).unwrap(),
+                graph.node_index(head).unwrap(),
+            );
+            &graph.edges()[graph.find_edge(tail, head).unwrap()].attributes
+        };
+        assert_eq!(edge("B", "C"), &vec![Attribute::Label("Yes".to_owned())]);
+        assert_eq!(edge("B", "D"), edge("B", "C"));
+        assert_eq!(
+            edge("B", "E"),
+            &vec![
+                Attribute::Label("No".to_owned()),
+                Attribute::Style(vec![Style::Dotted]),
+            ]
+        );
+        assert_eq!(
+            edge("E", "A"),
+            &vec![
+                Attribute::Label("again".to_owned()),
+                Attribute::Style(vec![Style::Bold]),
+            ]
+        );
+        assert_eq!(edge("C", "F"), &vec![Attribute::Dir(DirType::None)]);
+        assert_eq!(edge("G", "H"), &vec![Attribute::Dir(DirType::Both)]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            read("sequenceDiagram\n A->>B: hi"),
+            Err(MermaidError::UnsupportedDiagram(diagram)) if diagram == "sequenceDiagram"
+        ));
+        assert!(matches!(
+            read("graph TD\n A --> B\n A -->"),
+            Err(MermaidError::Syntax {
+                expected: "expected node",
+                line: 3
+            })
+        ));
+        assert!(matches!(
+            read("graph TD\n A B"),
+            Err(MermaidError::Syntax { line: 2, .. })
+        ));
+    }
+}
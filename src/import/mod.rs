@@ -0,0 +1,13 @@
+//! Readers constructing graphs from other graph formats.
+
+pub mod csv;
+pub mod declarative;
+pub mod gml;
+pub mod graph6;
+pub mod graphml;
+pub mod json;
+pub mod mermaid;
+pub mod pajek;
+pub mod tgf;
+#[cfg(feature = "rdf")]
+pub mod turtle;
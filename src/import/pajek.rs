@@ -0,0 +1,343 @@
+//! Pajek `.net` import.
+//!
+//! Vertex labels become node IDs; vertices without a label, or whose label is already taken, use
+//! their number as ID and keep the label as a `label` attribute. Coordinates, which Pajek
+//! normalizes to `[0, 1]` with `y` pointing down, are scaled to [`SCALE`] points as `pos`. The
+//! `box`, `ellipse`, `diamond` and `triangle` vertex shapes and the `ic` (fill) and `bc` (border)
+//! colors are read; other vertex parameters are ignored. Arcs and edges read their weight and the
+//! `l` (label) and `c` (color) parameters.
+//!
+//! Networks with any arcs are read as directed graphs, with `*Edges` entries becoming edges with
+//! `dir=none`.
+
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, DirType, Point, Position, Shape, Style},
+    ir::GraphKind,
+    model::{set_attribute, Graph, NodeIndex},
+};
+
+/// Size, in points, of the square that Pajek coordinates are scaled to.
+pub const SCALE: f64 = 720.0;
+
+#[derive(Debug, Error)]
+pub enum PajekError {
+    #[error("expected `*Vertices` before line {0}")]
+    MissingVertices(usize),
+    #[error("unexpected `{section}` section at line {line}")]
+    UnknownSection { section: String, line: usize },
+    #[error("expected number, found `{value}` at line {line}")]
+    InvalidNumber { value: String, line: usize },
+    #[error("vertex {vertex} out of range at line {line}")]
+    InvalidVertex { vertex: usize, line: usize },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Vertices,
+    Arcs,
+    Edges,
+    ArcsList,
+    EdgesList,
+    Matrix(usize),
+}
+
+/// Splits a line into whitespace-separated tokens, keeping quoted tokens together.
+fn tokens(line: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let end = match rest.strip_prefix('"') {
+            Some(quoted) => quoted.find('"').map_or(rest.len(), |end| end + 2),
+            None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        tokens.push(rest[..end].trim_matches('"'));
+        rest = rest[end..].trim_start();
+    }
+    tokens
+}
+
+fn number<T: std::str::FromStr>(value: &str, line: usize) -> Result<T, PajekError> {
+    value.parse().map_err(|_| PajekError::InvalidNumber {
+        value: value.to_owned(),
+        line,
+    })
+}
+
+/// Pajek color names are capitalized X11 names.
+fn color(name: &str, attribute: &str) -> Option<Attribute> {
+    Attribute::from_pair(attribute, &name.to_lowercase()).ok()
+}
+
+#[derive(Default)]
+struct Vertex {
+    label: Option<String>,
+    attributes: Vec<Attribute>,
+}
+
+struct Reader {
+    graph: Graph,
+    /// Vertices read so far, by vertex number less one, until they are added to the graph.
+    pending: Vec<Vertex>,
+    /// Node index of each vertex, by vertex number less one.
+    vertices: Vec<NodeIndex>,
+}
+
+impl Reader {
+    fn vertex(&self, token: &str, line: usize) -> Result<NodeIndex, PajekError> {
+        self.vertex_index(number(token, line)?, line)
+    }
+
+    fn vertex_index(&self, vertex: usize, line: usize) -> Result<NodeIndex, PajekError> {
+        vertex
+            .checked_sub(1)
+            .and_then(|i| self.vertices.get(i))
+            .copied()
+            .ok_or(PajekError::InvalidVertex { vertex, line })
+    }
+
+    fn read_vertex(&mut self, tokens: &[&str], line: usize) -> Result<(), PajekError> {
+        let number = number::<usize>(tokens[0], line)?;
+        let vertex = number
+            .checked_sub(1)
+            .and_then(|i| self.pending.get_mut(i))
+            .ok_or(PajekError::InvalidVertex {
+                vertex: number,
+                line,
+            })?;
+        vertex.label = tokens.get(1).map(|label| (*label).to_owned());
+        let coordinates = tokens
+            .iter()
+            .skip(2)
+            .take(2)
+            .map(|token| token.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>();
+        let mut parameters = tokens.iter().skip(2);
+        if let Ok([x, y]) = coordinates.as_deref() {
+            vertex
+                .attributes
+                .push(Attribute::Pos(Position::Point(Point {
+                    x: x * SCALE,
+                    y: (1.0 - y) * SCALE,
+                })));
+            parameters = tokens.iter().skip(4);
+        }
+        while let Some(parameter) = parameters.next() {
+            let attribute = match *parameter {
+                "box" => Some(Attribute::Shape(Shape::Box)),
+                "ellipse" => Some(Attribute::Shape(Shape::Ellipse)),
+                "diamond" => Some(Attribute::Shape(Shape::Diamond)),
+                "triangle" => Some(Attribute::Shape(Shape::Triangle)),
+                "ic" => {
+                    vertex
+                        .attributes
+                        .push(Attribute::Style(vec![Style::Filled]));
+                    parameters.next().and_then(|name| color(name, "fillcolor"))
+                }
+                "bc" => parameters.next().and_then(|name| color(name, "color")),
+                _ => None,
+            };
+            vertex.attributes.extend(attribute);
+        }
+        Ok(())
+    }
+
+    /// Adds the vertices read to the graph, in vertex number order.
+    fn add_vertices(&mut self) {
+        for (i, vertex) in self.pending.drain(..).enumerate() {
+            let mut attributes = vertex.attributes;
+            let id = match vertex.label {
+                Some(label) if self.graph.node(&label).is_none() => label,
+                label => {
+                    attributes.extend(label.map(Attribute::Label));
+                    (i + 1).to_string()
+                }
+            };
+            let node = self.graph.add_node(&id);
+            for attribute in attributes {
+                set_attribute(&mut self.graph.node_mut(node).attributes, attribute);
+            }
+            self.vertices.push(node);
+        }
+    }
+
+    fn read_edge(
+        &mut self,
+        tokens: &[&str],
+        line: usize,
+        undirected: bool,
+    ) -> Result<(), PajekError> {
+        let tail = self.vertex(tokens[0], line)?;
+        let head = self.vertex(tokens.get(1).copied().unwrap_or_default(), line)?;
+        let mut attributes = vec![];
+        let mut parameters = tokens.iter().skip(2).peekable();
+        if let Some(weight) = parameters.peek().and_then(|w| w.parse::<f64>().ok()) {
+            parameters.next();
+            attributes.push(Attribute::Weight(weight));
+        }
+        while let Some(parameter) = parameters.next() {
+            let attribute = match *parameter {
+                "l" => parameters
+                    .next()
+                    .map(|label| Attribute::Label((*label).to_owned())),
+                "c" => parameters.next().and_then(|name| color(name, "color")),
+                _ => None,
+            };
+            attributes.extend(attribute);
+        }
+        if undirected && self.graph.is_directed() {
+            attributes.push(Attribute::Dir(DirType::None));
+        }
+        self.graph.add_edge(tail, head, attributes);
+        Ok(())
+    }
+}
+
+/// Reads a Pajek network into a graph.
+pub fn read(input: &str) -> Result<Graph, PajekError> {
+    let lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('%'))
+        .collect::<Vec<_>>();
+    let directed = lines.iter().any(|(_, line)| {
+        let line = line.to_lowercase();
+        line.starts_with("*arcs") || line.starts_with("*matrix")
+    });
+    let kind = if directed {
+        GraphKind::Directed
+    } else {
+        GraphKind::Undirected
+    };
+    let mut reader = Reader {
+        graph: Graph::new(kind, false),
+        pending: vec![],
+        vertices: vec![],
+    };
+    let mut section = None;
+    for (line, text) in lines {
+        let tokens = tokens(text);
+        if let Some(keyword) = tokens[0].strip_prefix('*') {
+            reader.add_vertices();
+            section = Some(match keyword.to_lowercase().as_str() {
+                "vertices" => {
+                    let count = number::<usize>(tokens.get(1).unwrap_or(&""), line)?;
+                    reader.pending.resize_with(count, Vertex::default);
+                    Section::Vertices
+                }
+                "arcs" => Section::Arcs,
+                "edges" => Section::Edges,
+                "arcslist" => Section::ArcsList,
+                "edgeslist" => Section::EdgesList,
+                "matrix" => Section::Matrix(0),
+                "network" => continue,
+                _ => {
+                    return Err(PajekError::UnknownSection {
+                        section: tokens[0].to_owned(),
+                        line,
+                    })
+                }
+            });
+            continue;
+        }
+        match section {
+            None => return Err(PajekError::MissingVertices(line)),
+            Some(Section::Vertices) => reader.read_vertex(&tokens, line)?,
+            Some(Section::Arcs) => reader.read_edge(&tokens, line, false)?,
+            Some(Section::Edges) => reader.read_edge(&tokens, line, true)?,
+            Some(Section::ArcsList | Section::EdgesList) => {
+                for head in &tokens[1..] {
+                    reader.read_edge(
+                        &[tokens[0], head],
+                        line,
+                        section == Some(Section::EdgesList),
+                    )?;
+                }
+            }
+            Some(Section::Matrix(row)) => {
+                let tail = reader.vertex_index(row + 1, line)?;
+                for (column, value) in tokens.iter().enumerate() {
+                    let value = number::<f64>(value, line)?;
+                    if value != 0.0 {
+                        let head = reader.vertex_index(column + 1, line)?;
+                        let attributes = if value == 1.0 {
+                            vec![]
+                        } else {
+                            vec![Attribute::Weight(value)]
+                        };
+                        reader.graph.add_edge(tail, head, attributes);
+                    }
+                }
+                section = Some(Section::Matrix(row + 1));
+            }
+        }
+    }
+    reader.add_vertices();
+    Ok(reader.graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network() {
+        let graph = read(
+            r#"% a small network
+*Vertices 4
+1 "a" 0.0 1.0 0.5 box ic Red
+2 "b b" 0.5 0.5
+3 "a"
+*Arcs
+1 2 2.5 l "first"
+2 3
+*Edges
+3 4 c Blue
+*Arcslist
+4 1 2
+"#,
+        )
+        .unwrap();
+        let ids = graph
+            .nodes()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b b", "3", "4"]);
+        assert_eq!(
+            graph.nodes()[0].attributes,
+            vec![
+                Attribute::Pos(Position::Point(Point { x: 0.0, y: 0.0 })),
+                Attribute::Shape(Shape::Box),
+                Attribute::Style(vec![Style::Filled]),
+                Attribute::from_pair("fillcolor", "red").unwrap(),
+            ]
+        );
+        assert_eq!(
+            graph.nodes()[2].attributes,
+            vec![Attribute::Label("a".to_owned())]
+        );
+        assert!(graph.is_directed());
+        assert_eq!(graph.edges().len(), 5);
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Weight(2.5), Attribute::Label("first".to_owned())]
+        );
+        assert_eq!(
+            graph.edges()[2].attributes,
+            vec![
+                Attribute::from_pair("color", "blue").unwrap(),
+                Attribute::Dir(DirType::None)
+            ]
+        );
+
+        let graph = read("*Vertices 2\n*Matrix\n0 3\n1 0\n").unwrap();
+        assert_eq!(graph.edges()[0].attributes, vec![Attribute::Weight(3.0)]);
+        assert!(matches!(
+            read("*Vertices 2\n*Edges\n1 3\n"),
+            Err(PajekError::InvalidVertex { vertex: 3, line: 3 })
+        ));
+    }
+}
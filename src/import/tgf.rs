@@ -0,0 +1,86 @@
+//! Trivial Graph Format import.
+//!
+//! Node lines (`id [label]`) precede a `#` line, after which edge lines (`tail head [label]`)
+//! follow. Labels become `label` attributes. TGF doesn't record edge direction; graphs are read as
+//! directed, as yEd does.
+
+use thiserror::Error;
+
+use crate::{
+    attribute::Attribute,
+    ir::GraphKind,
+    model::{set_attribute, Graph},
+};
+
+#[derive(Debug, Error)]
+pub enum TgfError {
+    #[error("expected edge target at line {0}")]
+    MissingTarget(usize),
+}
+
+/// Splits off the first whitespace-separated word of `line`.
+fn word(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    let end = line.find(char::is_whitespace).unwrap_or(line.len());
+    (&line[..end], line[end..].trim())
+}
+
+/// Reads a TGF document into a graph.
+pub fn read(input: &str) -> Result<Graph, TgfError> {
+    let mut graph = Graph::new(GraphKind::Directed, false);
+    let mut edges = false;
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('#') {
+            edges = true;
+            continue;
+        }
+        let (first, rest) = word(line);
+        if !edges {
+            let node = graph.add_node(first);
+            if !rest.is_empty() {
+                set_attribute(
+                    &mut graph.node_mut(node).attributes,
+                    Attribute::Label(rest.to_owned()),
+                );
+            }
+            continue;
+        }
+        let (second, label) = word(rest);
+        if second.is_empty() {
+            return Err(TgfError::MissingTarget(i + 1));
+        }
+        let tail = graph.add_node(first);
+        let head = graph.add_node(second);
+        let attributes = if label.is_empty() {
+            vec![]
+        } else {
+            vec![Attribute::Label(label.to_owned())]
+        };
+        graph.add_edge(tail, head, attributes);
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tgf() {
+        let graph = read("1 First node\n2\n#\n1 2 Edge label\n2 3\n").unwrap();
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(
+            graph.node("1").unwrap().attributes,
+            vec![Attribute::Label("First node".to_owned())]
+        );
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Label("Edge label".to_owned())]
+        );
+        assert!(matches!(read("1\n#\n1\n"), Err(TgfError::MissingTarget(3))));
+    }
+}
@@ -0,0 +1,661 @@
+//! RDF import from Turtle, and so also N-Triples, which is a subset of it.
+//!
+//! Each triple becomes an edge from its subject to its object, labeled with its predicate.
+//! IRI nodes use the full IRI as ID, labeled with its prefixed form when a declared prefix covers
+//! it; predicates are labeled the same way. Blank nodes keep their `_:` labels as IDs, with
+//! anonymous blank nodes (`[ ... ]` and collection cells) numbered `_:genid1`, `_:genid2`, ...
+//! Literals become box-shaped nodes labeled with their lexical form, shared between triples with
+//! the same literal object and identified by their N-Triples form (e.g. `"chat"@fr`).
+//!
+//! Relative IRIs are appended to the base IRI, without further resolution.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_while, take_while_m_n},
+    character::complete::{char, digit1, multispace0, none_of, one_of, satisfy},
+    combinator::{cut, map, map_opt, not, opt, peek, recognize, value},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::{many0, many1, separated_list1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, Shape},
+    ir::GraphKind,
+    model::{set_attribute, Graph, NodeIndex},
+};
+
+type TurtleResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+#[derive(Debug, Error)]
+pub enum TurtleError {
+    #[error("{expected} at line {line}")]
+    Syntax { expected: &'static str, line: usize },
+    #[error("undeclared prefix `{0}:`")]
+    UndeclaredPrefix(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Iri(String),
+    Prefixed(String, String),
+    Blank(String),
+    /// A blank node property list, `[ predicate object ... ]`.
+    Anonymous(Vec<Predicate>),
+    Collection(Vec<Term>),
+    Literal {
+        lexical: String,
+        language: Option<String>,
+        datatype: Option<Box<Term>>,
+    },
+}
+
+/// A predicate along with its objects.
+type Predicate = (Term, Vec<Term>);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Prefix(String, String),
+    Base(String),
+    Triples(Term, Vec<Predicate>),
+}
+
+/// Whitespace and comments.
+fn space_parser(input: &str) -> TurtleResult<'_, ()> {
+    value(
+        (),
+        pair(
+            multispace0,
+            many0(terminated(
+                preceded(char('#'), take_while(|c| c != '\n')),
+                multispace0,
+            )),
+        ),
+    )(input)
+}
+
+fn token<'a, F, O>(inner: F) -> impl FnMut(&'a str) -> TurtleResult<'a, O>
+where
+    F: FnMut(&'a str) -> TurtleResult<'a, O>,
+{
+    terminated(inner, space_parser)
+}
+
+fn iri_parser(input: &str) -> TurtleResult<'_, String> {
+    map(
+        delimited(
+            char('<'),
+            take_while(|c: char| !"<>\"{}|^`\\".contains(c) && !c.is_whitespace()),
+            cut(context("expected `>`", char('>'))),
+        ),
+        str::to_owned,
+    )(input)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Matches `keyword` unless it is the start of a longer name, as in `a:b`.
+fn keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> TurtleResult<'a, &'a str> {
+    terminated(
+        tag(keyword),
+        peek(not(satisfy(|c| is_name_char(c) || c == ':'))),
+    )
+}
+
+/// Name characters, which may include but not end with `.`, as in `ex:a.b.`, which is followed by
+/// the statement terminator.
+fn name_parser(input: &str) -> TurtleResult<'_, &str> {
+    let (_, name) =
+        take_while(|c: char| is_name_char(c) || c == '.' || c == ':' || c == '%')(input)?;
+    let name = name.trim_end_matches('.');
+    Ok((&input[name.len()..], name))
+}
+
+fn prefix_parser(input: &str) -> TurtleResult<'_, &str> {
+    terminated(
+        recognize(opt(pair(
+            satisfy(char::is_alphabetic),
+            take_while(|c: char| is_name_char(c) || c == '.'),
+        ))),
+        char(':'),
+    )(input)
+}
+
+fn prefixed_name_parser(input: &str) -> TurtleResult<'_, Term> {
+    map(pair(prefix_parser, name_parser), |(prefix, local)| {
+        Term::Prefixed(prefix.to_owned(), local.to_owned())
+    })(input)
+}
+
+fn blank_parser(input: &str) -> TurtleResult<'_, Term> {
+    map(preceded(tag("_:"), cut(name_parser)), |label| {
+        Term::Blank(label.to_owned())
+    })(input)
+}
+
+fn escape_parser(input: &str) -> TurtleResult<'_, char> {
+    preceded(
+        char('\\'),
+        cut(context(
+            "expected escape sequence",
+            alt((
+                map_opt(
+                    alt((
+                        preceded(
+                            char('u'),
+                            take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+                        ),
+                        preceded(
+                            char('U'),
+                            take_while_m_n(8, 8, |c: char| c.is_ascii_hexdigit()),
+                        ),
+                    )),
+                    |hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                ),
+                map_opt(one_of("tbnrf\"'\\"), |c| {
+                    Some(match c {
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        'f' => '\u{c}',
+                        c => c,
+                    })
+                }),
+            )),
+        )),
+    )(input)
+}
+
+/// A string quoted with `quote`, which for long strings is tripled and may span lines.
+fn quoted_parser<'a>(quote: &'static str) -> impl FnMut(&'a str) -> TurtleResult<'a, String> {
+    let long = quote.len() == 3;
+    move |input| {
+        let (mut input, _) = tag(quote)(input)?;
+        let mut text = String::new();
+        loop {
+            if let Ok((rest, _)) = tag::<_, _, VerboseError<&str>>(quote)(input) {
+                return Ok((rest, text));
+            }
+            match escape_parser(input) {
+                Ok((rest, c)) => {
+                    text.push(c);
+                    input = rest;
+                    continue;
+                }
+                Err(nom::Err::Error(_)) => {}
+                Err(e) => return Err(e),
+            }
+            let (rest, c) = context("expected closing quote", cut(none_of("\\")))(input)?;
+            if !long && (c == '\n' || c == '\r') {
+                return Err(nom::Err::Failure(VerboseError {
+                    errors: vec![(input, VerboseErrorKind::Context("expected closing quote"))],
+                }));
+            }
+            text.push(c);
+            input = rest;
+        }
+    }
+}
+
+fn number_parser(input: &str) -> TurtleResult<'_, Term> {
+    map(
+        recognize(tuple((
+            opt(one_of("+-")),
+            alt((
+                recognize(pair(digit1, opt(pair(char('.'), digit1)))),
+                recognize(pair(char('.'), digit1)),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        |number: &str| {
+            let datatype = if number.contains(['e', 'E']) {
+                "double"
+            } else if number.contains('.') {
+                "decimal"
+            } else {
+                "integer"
+            };
+            Term::Literal {
+                lexical: number.to_owned(),
+                language: None,
+                datatype: Some(Box::new(Term::Iri(format!("{}{}", XSD, datatype)))),
+            }
+        },
+    )(input)
+}
+
+fn literal_parser(input: &str) -> TurtleResult<'_, Term> {
+    alt((
+        map(
+            pair(
+                alt((
+                    quoted_parser("\"\"\""),
+                    quoted_parser("'''"),
+                    quoted_parser("\""),
+                    quoted_parser("'"),
+                )),
+                opt(alt((
+                    map(
+                        preceded(
+                            char('@'),
+                            recognize(pair(
+                                take_while(|c: char| c.is_ascii_alphabetic()),
+                                take_while(|c: char| c.is_ascii_alphanumeric() || c == '-'),
+                            )),
+                        ),
+                        |language: &str| (Some(language.to_owned()), None),
+                    ),
+                    map(
+                        preceded(
+                            tag("^^"),
+                            cut(context(
+                                "expected datatype",
+                                alt((map(iri_parser, Term::Iri), prefixed_name_parser)),
+                            )),
+                        ),
+                        |datatype| (None, Some(Box::new(datatype))),
+                    ),
+                ))),
+            ),
+            |(lexical, suffix)| {
+                let (language, datatype) = suffix.unwrap_or_default();
+                Term::Literal {
+                    lexical,
+                    language,
+                    datatype,
+                }
+            },
+        ),
+        number_parser,
+        map(alt((keyword("true"), keyword("false"))), |lexical: &str| {
+            Term::Literal {
+                lexical: lexical.to_owned(),
+                language: None,
+                datatype: Some(Box::new(Term::Iri(format!("{}boolean", XSD)))),
+            }
+        }),
+    ))(input)
+}
+
+fn term_parser(input: &str) -> TurtleResult<'_, Term> {
+    token(alt((
+        map(iri_parser, Term::Iri),
+        blank_parser,
+        map(
+            preceded(
+                token(char('[')),
+                cut(terminated(
+                    map(opt(predicates_parser), Option::unwrap_or_default),
+                    context("expected `]`", char(']')),
+                )),
+            ),
+            Term::Anonymous,
+        ),
+        map(
+            preceded(
+                token(char('(')),
+                cut(terminated(
+                    many0(term_parser),
+                    context("expected `)`", char(')')),
+                )),
+            ),
+            Term::Collection,
+        ),
+        literal_parser,
+        prefixed_name_parser,
+    )))(input)
+}
+
+fn verb_parser(input: &str) -> TurtleResult<'_, Term> {
+    context(
+        "expected predicate",
+        alt((
+            token(value(Term::Iri(format!("{}type", RDF)), keyword("a"))),
+            token(map(iri_parser, Term::Iri)),
+            token(prefixed_name_parser),
+        )),
+    )(input)
+}
+
+fn predicates_parser(input: &str) -> TurtleResult<'_, Vec<Predicate>> {
+    terminated(
+        separated_list1(
+            many1(token(char(';'))),
+            pair(
+                verb_parser,
+                cut(separated_list1(
+                    token(char(',')),
+                    context("expected object", term_parser),
+                )),
+            ),
+        ),
+        many0(token(char(';'))),
+    )(input)
+}
+
+/// Matches the case-insensitive SPARQL-style directive `keyword`.
+fn sparql_keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> TurtleResult<'a, &'a str> {
+    terminated(tag_no_case(keyword), peek(satisfy(char::is_whitespace)))
+}
+
+fn directive_parser(input: &str) -> TurtleResult<'_, Statement> {
+    let prefix = |input| {
+        pair(
+            token(prefix_parser),
+            cut(context("expected namespace IRI", token(iri_parser))),
+        )(input)
+    };
+    let base = |input| cut(context("expected base IRI", token(iri_parser)))(input);
+    let terminator = |input| cut(context("expected `.`", token(char('.'))))(input);
+    alt((
+        map(
+            preceded(token(tag("@prefix")), cut(terminated(prefix, terminator))),
+            |(prefix, namespace)| Statement::Prefix(prefix.to_owned(), namespace),
+        ),
+        map(
+            preceded(token(tag("@base")), cut(terminated(base, terminator))),
+            Statement::Base,
+        ),
+        map(
+            preceded(token(sparql_keyword("PREFIX")), prefix),
+            |(prefix, namespace)| Statement::Prefix(prefix.to_owned(), namespace),
+        ),
+        map(
+            preceded(token(sparql_keyword("BASE")), base),
+            Statement::Base,
+        ),
+    ))(input)
+}
+
+fn statement_parser(input: &str) -> TurtleResult<'_, Statement> {
+    alt((
+        directive_parser,
+        map(
+            terminated(
+                pair(
+                    context("expected subject", term_parser),
+                    map(opt(predicates_parser), Option::unwrap_or_default),
+                ),
+                cut(context("expected `.`", token(char('.')))),
+            ),
+            |(subject, predicates)| Statement::Triples(subject, predicates),
+        ),
+    ))(input)
+}
+
+/// Quotes and escapes `text` as in N-Triples.
+fn ntriples_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Reader {
+    graph: Graph,
+    prefixes: Vec<(String, String)>,
+    base: String,
+    anonymous: usize,
+}
+
+impl Reader {
+    fn iri(&self, term: &Term) -> Result<String, TurtleError> {
+        match term {
+            Term::Prefixed(prefix, local) => self
+                .prefixes
+                .iter()
+                .rev()
+                .find(|(p, _)| p == prefix)
+                .map(|(_, namespace)| format!("{}{}", namespace, local))
+                .ok_or_else(|| TurtleError::UndeclaredPrefix(prefix.clone())),
+            Term::Iri(iri) if iri.contains(':') => Ok(iri.clone()),
+            Term::Iri(iri) => Ok(format!("{}{}", self.base, iri)),
+            _ => unreachable!("only IRIs are parsed as predicates and datatypes"),
+        }
+    }
+
+    /// The prefixed form of `iri`, using the longest matching namespace, or the IRI itself.
+    fn compact(&self, iri: &str) -> String {
+        self.prefixes
+            .iter()
+            .filter_map(|(prefix, namespace)| {
+                let local = iri.strip_prefix(namespace.as_str())?;
+                (!local.contains(['/', '#', '?'])).then_some((namespace.len(), prefix, local))
+            })
+            .max_by_key(|(length, ..)| *length)
+            .map_or_else(
+                || iri.to_owned(),
+                |(_, prefix, local)| format!("{}:{}", prefix, local),
+            )
+    }
+
+    fn add_node(&mut self, id: &str, attributes: Vec<Attribute>) -> NodeIndex {
+        let node = self.graph.add_node(id);
+        for attribute in attributes {
+            set_attribute(&mut self.graph.node_mut(node).attributes, attribute);
+        }
+        node
+    }
+
+    fn add_edge(&mut self, tail: NodeIndex, predicate: &str, head: NodeIndex) {
+        let label = Attribute::Label(self.compact(predicate));
+        self.graph.add_edge(tail, head, vec![label]);
+    }
+
+    fn anonymous_node(&mut self) -> NodeIndex {
+        self.anonymous += 1;
+        self.graph.add_node(&format!("_:genid{}", self.anonymous))
+    }
+
+    fn node(&mut self, term: &Term) -> Result<NodeIndex, TurtleError> {
+        Ok(match term {
+            Term::Iri(_) | Term::Prefixed(..) => {
+                let iri = self.iri(term)?;
+                let label = self.compact(&iri);
+                let attributes = if label == iri {
+                    vec![]
+                } else {
+                    vec![Attribute::Label(label)]
+                };
+                self.add_node(&iri, attributes)
+            }
+            Term::Blank(label) => self.graph.add_node(&format!("_:{}", label)),
+            Term::Anonymous(predicates) => {
+                let node = self.anonymous_node();
+                self.triples(node, predicates)?;
+                node
+            }
+            Term::Collection(items) => {
+                let nil = format!("{}nil", RDF);
+                let mut rest = self.node(&Term::Iri(nil))?;
+                for item in items.iter().rev() {
+                    let cell = self.anonymous_node();
+                    let item = self.node(item)?;
+                    self.add_edge(cell, &format!("{}first", RDF), item);
+                    self.add_edge(cell, &format!("{}rest", RDF), rest);
+                    rest = cell;
+                }
+                rest
+            }
+            Term::Literal {
+                lexical,
+                language,
+                datatype,
+            } => {
+                let mut id = ntriples_string(lexical);
+                if let Some(language) = language {
+                    id = format!("{}@{}", id, language);
+                }
+                if let Some(datatype) = datatype {
+                    id = format!("{}^^<{}>", id, self.iri(datatype)?);
+                }
+                let attributes = vec![
+                    Attribute::Label(lexical.clone()),
+                    Attribute::Shape(Shape::Box),
+                ];
+                self.add_node(&id, attributes)
+            }
+        })
+    }
+
+    fn triples(&mut self, subject: NodeIndex, predicates: &[Predicate]) -> Result<(), TurtleError> {
+        for (predicate, objects) in predicates {
+            let predicate = self.iri(predicate)?;
+            for object in objects {
+                let object = self.node(object)?;
+                self.add_edge(subject, &predicate, object);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a Turtle document into a directed graph.
+pub fn read(input: &str) -> Result<Graph, TurtleError> {
+    let statements = match preceded(space_parser, many0(statement_parser))(input) {
+        Ok(("", statements)) => statements,
+        result => {
+            let (remaining, expected) = match result {
+                Ok((rest, _)) => (rest, "expected statement"),
+                Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e
+                    .errors
+                    .iter()
+                    .find_map(|(remaining, kind)| match kind {
+                        VerboseErrorKind::Context(context) => Some((*remaining, *context)),
+                        _ => None,
+                    })
+                    .unwrap_or((input, "expected statement")),
+                Err(nom::Err::Incomplete(_)) => ("", "expected statement"),
+            };
+            return Err(TurtleError::Syntax {
+                expected,
+                line: input[..input.len() - remaining.len()].matches('\n').count() + 1,
+            });
+        }
+    };
+
+    let mut reader = Reader {
+        graph: Graph::new(GraphKind::Directed, false),
+        prefixes: vec![],
+        base: String::new(),
+        anonymous: 0,
+    };
+    for statement in statements {
+        match statement {
+            Statement::Prefix(prefix, namespace) => {
+                let namespace = reader.iri(&Term::Iri(namespace))?;
+                reader.prefixes.push((prefix, namespace));
+            }
+            Statement::Base(base) => reader.base = reader.iri(&Term::Iri(base))?,
+            Statement::Triples(subject, predicates) => {
+                let subject = reader.node(&subject)?;
+                reader.triples(subject, &predicates)?;
+            }
+        }
+    }
+    Ok(reader.graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(attributes: &[Attribute]) -> Option<&str> {
+        attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Label(label) => Some(label.as_str()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn turtle() {
+        let graph = read(
+            r#"# a small ontology
+@prefix ex: <http://example.org/> .
+PREFIX foaf: <http://xmlns.com/foaf/0.1/>
+@base <http://example.org/people/> .
+
+<alice> a foaf:Person ;
+    foaf:name "Alice"@en, 'Alice' ;
+    foaf:age 42 ;
+    foaf:knows [ foaf:name """Bob
+the builder""" ], _:carol ;
+.
+_:carol ex:likes ( ex:tea ex:cake ) .
+<http://example.org/people/bob> ex:rating "4.5"^^<http://www.w3.org/2001/XMLSchema#decimal> .
+"#,
+        )
+        .unwrap();
+        assert!(graph.is_directed());
+        let alice = graph.node("http://example.org/people/alice").unwrap();
+        assert!(alice.attributes.is_empty());
+        let person = graph.node("http://xmlns.com/foaf/0.1/Person").unwrap();
+        assert_eq!(label(&person.attributes), Some("foaf:Person"));
+        let english = graph.node("\"Alice\"@en").unwrap();
+        assert_eq!(
+            english.attributes,
+            vec![
+                Attribute::Label("Alice".to_owned()),
+                Attribute::Shape(Shape::Box)
+            ]
+        );
+        assert!(graph.node("\"Alice\"").is_some());
+        assert!(graph
+            .node("\"42\"^^<http://www.w3.org/2001/XMLSchema#integer>")
+            .is_some());
+        assert!(graph.node("\"Bob\\nthe builder\"").is_some());
+        assert!(graph.node("_:carol").is_some());
+        assert!(graph.node("_:genid1").is_some());
+        assert_eq!(
+            label(&graph.edges()[0].attributes),
+            Some(format!("{}type", RDF).as_str())
+        );
+        assert_eq!(label(&graph.edges()[1].attributes), Some("foaf:name"));
+        // 7 triples about alice and bob, 1 for the blank node, 5 for the collection
+        assert_eq!(graph.edges().len(), 13);
+    }
+
+    #[test]
+    fn ntriples() {
+        let graph =
+            read("<http://a> <http://p> <http://b> .\n<http://b> <http://p> \"x\\u0021\\n\" .\n")
+                .unwrap();
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.nodes()[2].id, "\"x!\\n\"");
+        assert_eq!(label(&graph.edges()[0].attributes), Some("http://p"));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            read("@prefix ex: <http://example.org/> .\nex:a ex:b ex:c"),
+            Err(TurtleError::Syntax {
+                expected: "expected `.`",
+                line: 2
+            })
+        ));
+        assert!(matches!(
+            read("ex:a ex:b ex:c ."),
+            Err(TurtleError::UndeclaredPrefix(prefix)) if prefix == "ex"
+        ));
+        assert!(matches!(
+            read("<a> <b> \"unterminated\n\" ."),
+            Err(TurtleError::Syntax { line: 1, .. })
+        ));
+    }
+}
@@ -1,27 +1,52 @@
 //! Intermediate representation
 
-use std::fmt::{Debug, Display};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Debug, Display};
 
+use crate::{
+    attribute::{attribute_pair_parser, attribute_parser},
+    scan,
+    ws::{dot_leading, dot_ws},
+};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until1, take_while1},
-    character::complete::{alpha1, alphanumeric1, char, digit0, digit1},
-    combinator::{map, opt, recognize, value},
-    error::{ParseError, VerboseError},
+    bytes::complete::tag,
+    character::complete::{char, digit0, digit1},
+    combinator::{cut, opt, recognize},
+    error::{context, ContextError, ParseError, VerboseError, VerboseErrorKind},
     multi::{many0, many1},
-    sequence::{delimited, pair, separated_pair, tuple},
-    AsChar, InputTakeAtPosition, Parser,
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Parser,
 };
-use thiserror::Error;
 
-use crate::{
-    attribute::{attribute_parser, Attribute},
-    ws::ws,
-};
+mod attributes;
+#[cfg(feature = "std")]
+mod chunks;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+mod stream;
 
-pub type ParseResult<I, O> = nom::IResult<I, O, nom::error::VerboseError<I>>;
+pub use attributes::Attributes;
+#[cfg(feature = "std")]
+pub use file::{parse_graph_path, ReadError, Utf8};
+#[cfg(feature = "std")]
+pub use stream::{Header, StatementStream};
 
-#[derive(Debug)]
+pub(crate) type ParseResult<I, O, E = VerboseError<I>> = nom::IResult<I, O, E>;
+
+/// The errors of the DOT parsers: `()` to learn cheaply that parsing failed, or [`VerboseError`]
+/// to learn where and why, which is only worth building once parsing has failed.
+pub(crate) trait DotError<'a>: ParseError<&'a str> + ContextError<&'a str> + 'a {}
+
+impl<'a, E: ParseError<&'a str> + ContextError<&'a str> + 'a> DotError<'a> for E {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GraphKind {
     Directed,
     Undirected,
@@ -31,6 +56,7 @@ pub enum GraphKind {
 pub struct Graph {
     pub kind: GraphKind,
     pub strict: bool,
+    pub id: Option<Ident>,
     pub statements: Vec<Statement>,
 }
 
@@ -51,22 +77,34 @@ pub enum AttributeKind {
 
 #[derive(Debug)]
 pub struct AttributeStatement {
-    kind: AttributeKind,
-    attributes: Vec<Attribute>,
+    pub kind: AttributeKind,
+    pub attributes: Attributes,
 }
 
 pub type Ident = String;
 
 #[derive(Debug)]
 pub struct NodeStatement {
-    name: Ident,
-    attributes: Vec<Attribute>,
+    pub name: Ident,
+    pub attributes: Attributes,
 }
 
 #[derive(Debug)]
 pub struct EdgeStatement {
-    list: Vec<Ident>,
-    attributes: Vec<Attribute>,
+    pub list: Vec<Ident>,
+    /// The ports of the nodes of `list` the edges meet, as `port`, `compass` or `port:compass`.
+    pub ports: Vec<Option<Ident>>,
+    /// Edge operators as written, where `ops[i]` connects `list[i]` to `list[i + 1]`.
+    pub ops: Vec<EdgeOp>,
+    pub attributes: Attributes,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeOp {
+    /// `->`
+    Directed,
+    /// `--`
+    Undirected,
 }
 
 #[derive(Debug)]
@@ -76,195 +114,479 @@ pub enum EdgeTarget {
 
 #[derive(Debug)]
 pub struct DefinitionStatement {
-    lhs: Ident,
-    rhs: Ident,
+    pub lhs: Ident,
+    pub rhs: Ident,
+}
+
+impl Graph {
+    /// The attribute statements, `graph [...]`, `node [...]` and `edge [...]`, in order.
+    pub fn attribute_statements(&self) -> impl Iterator<Item = &AttributeStatement> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Attribute(statement) => Some(statement),
+                _ => None,
+            })
+    }
+
+    /// The node statements, in order. Nodes only named in edges have none.
+    pub fn node_statements(&self) -> impl Iterator<Item = &NodeStatement> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Node(statement) => Some(statement),
+                _ => None,
+            })
+    }
+
+    /// The edge statements, in order, each of which may chain several edges.
+    pub fn edge_statements(&self) -> impl Iterator<Item = &EdgeStatement> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Edge(statement) => Some(statement),
+                _ => None,
+            })
+    }
+
+    /// The `ID = ID` statements, in order.
+    pub fn definitions(&self) -> impl Iterator<Item = &DefinitionStatement> {
+        self.statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Definition(statement) => Some(statement),
+                _ => None,
+            })
+    }
 }
 
-/// Parser that mathces characters in the range of octal values `[\200-\377]`.
-fn highbit<I, E>(input: I) -> nom::IResult<I, I, E>
-where
-    E: ParseError<I>,
-    I: InputTakeAtPosition,
-    <I as InputTakeAtPosition>::Item: AsChar,
-{
-    take_while1(|c: <I as InputTakeAtPosition>::Item| {
-        let c = c.as_char() as i32;
-        c >= 0o200 && c <= 0o377
-    })(input)
+impl Display for EdgeOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeOp::Directed => write!(f, "->"),
+            EdgeOp::Undirected => write!(f, "--"),
+        }
+    }
 }
 
 /// Any string of alphabetic ([a-zA-Z\200-\377]) characters, underscores ('_') or digits([0-9]),
 /// not beginning with a digit
-fn string_ident_parser(input: &str) -> ParseResult<&str, Ident> {
-    let start_char = alt((alpha1, highbit, tag("_")));
-    let continue_char = alt((alphanumeric1, highbit, tag("_")));
-    recognize(pair(start_char, many0(continue_char)))(input).map(|(i, o)| (i, o.to_owned()))
+fn string_ident_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Cow<'a, str>, E> {
+    match scan::ident_run(input) {
+        length if length > 0 && !input.starts_with(|c: char| c.is_ascii_digit()) => {
+            Ok((&input[length..], Cow::Borrowed(&input[..length])))
+        }
+        _ => Err(nom::Err::Error(E::from_error_kind(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
 }
 
 /// a numeral [-]?(.[0-9]⁺ | [0-9]⁺(.[0-9]*)? )
-fn num_ident_parser(input: &str) -> ParseResult<&str, Ident> {
-    recognize(tuple((opt(tag("-")), digit1, tag("."), digit0)))(input)
-        .map(|(i, o)| (i, o.to_owned()))
-}
-
-fn quote_string_fragment_parser(input: &str) -> ParseResult<&str, &str> {
-    let escaped_quote = value(r#"""#, tag(r#"\""#));
-    let string_fragment = alt((take_until1(r#"\""#), take_until1(r#"""#)));
-    alt((escaped_quote, string_fragment))(input)
-}
-
-/// any double-quoted string ("...") possibly containing escaped quotes (\")
-fn quote_string_ident_parser(input: &str) -> ParseResult<&str, Ident> {
-    delimited(
-        char('"'),
-        // |i| {
-        //     let mut input = i;
-        //     let mut result = String::new();
-        //     loop {
-        //         match quote_string_fragment_parser(input) {
-        //             Ok((rest, parsed)) => {
-        //                 println!("rest: ##{}## parsed: ##{}##", rest, parsed);
-        //                 input = rest;
-        //                 result.push_str(parsed);
-        //                 println!("result: ##{}##", result);
-        //             }
-        //             Err(nom::Err::Error(_)) => {
-        //                 println!("soft failure ##{}##", result);
-        //                 return Ok((input, result));
-        //             }
-        //             Err(e) => {
-        //                 return Err(e);
-        //             }
-        //         }
-        //     }
-        // },
-        map(
-            many0(
-                quote_string_fragment_parser,
-                // String::new,
-                // |mut string, fragment| {
-                //     string.push_str(fragment);
-                //     string
-                // },
-            ),
-            |v| v.join(""),
-        ),
-        char('"'),
-    )(input)
+fn num_ident_parser<'a, E: DotError<'a>>(input: &'a str) -> ParseResult<&'a str, Cow<'a, str>, E> {
+    recognize(pair(
+        opt(tag("-")),
+        alt((
+            recognize(pair(tag("."), digit1)),
+            recognize(pair(digit1, opt(pair(tag("."), digit0)))),
+        )),
+    ))(input)
+    .map(|(i, o)| (i, Cow::Borrowed(o)))
+}
+
+/// any double-quoted string ("...") possibly containing escaped quotes (\"). Only escaped quotes
+/// and line continuations are unescaped; other backslashes are kept, as their meaning depends on
+/// the attribute (e.g. `\N` in labels).
+fn quote_string_ident_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Cow<'a, str>, E> {
+    let (rest, kind) = match scan::quoted(input) {
+        Some(Ok((contents, length))) => return Ok((&input[length..], contents)),
+        Some(Err(length)) => (&input[length..], nom::error::ErrorKind::Char),
+        None => (input, nom::error::ErrorKind::Char),
+    };
+    Err(nom::Err::Error(E::from_error_kind(rest, kind)))
+}
+
+/// an HTML string (<...>) with balanced angle brackets inside, kept with its outer brackets to
+/// tell it apart from other strings
+fn html_ident_parser<'a, E: DotError<'a>>(input: &'a str) -> ParseResult<&'a str, Cow<'a, str>, E> {
+    match scan::html(input) {
+        Some(length) => Ok((&input[length..], Cow::Borrowed(&input[..length]))),
+        None => Err(nom::Err::Error(E::from_error_kind(
+            input,
+            nom::error::ErrorKind::Char,
+        ))),
+    }
+}
+
+/// Whether `id` is an HTML string, `<...>`, as parsed from DOT.
+pub(crate) fn is_html(id: &str) -> bool {
+    matches!(html_ident_parser::<()>(id), Ok(("", _)))
+}
+
+/// Quotes `id` for output as a DOT ID unless it is a plain identifier, a numeral or an HTML
+/// string.
+pub(crate) fn quote_id(id: &str) -> Cow<'_, str> {
+    let plain = matches!(string_ident_parser::<()>(id), Ok(("", _)))
+        && !["node", "edge", "graph", "digraph", "subgraph", "strict"]
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(id));
+    if plain || matches!(num_ident_parser::<()>(id), Ok(("", _))) || is_html(id) {
+        Cow::Borrowed(id)
+    } else {
+        Cow::Owned(format!("\"{}\"", id.replace('"', "\\\"")))
+    }
 }
 
 /// An ID is one of the following:
 /// * Any string of alphabetic ([a-zA-Z\200-\377]) characters, underscores ('_') or digits([0-9]),
 ///   not beginning with a digit;
 /// * a numeral [-]?(.[0-9]⁺ | [0-9]⁺(.[0-9]*)? );
-/// * any double-quoted string ("...") possibly containing escaped quotes (\")¹.
-fn ident_parser(input: &str) -> ParseResult<&str, Ident> {
-    alt((
-        string_ident_parser,
-        num_ident_parser,
-        quote_string_ident_parser,
-    ))(input)
+/// * any double-quoted string ("...") possibly containing escaped quotes (\")¹;
+/// * an HTML string (<...>).
+pub(crate) fn ident_parser<'a, E: DotError<'a>>(input: &'a str) -> ParseResult<&'a str, Ident, E> {
+    ident_str_parser(input).map(|(rest, id)| (rest, id.into_owned()))
 }
 
-fn a_list_parser(input: &str) -> ParseResult<&str, Vec<Attribute>> {
-    many1(attribute_parser)(input)
+/// An ID as [`ident_parser`] parses it, borrowed from `input` unless unescaped.
+pub(crate) fn ident_str_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Cow<'a, str>, E> {
+    context(
+        "expected identifier",
+        alt((
+            string_ident_parser,
+            num_ident_parser,
+            quote_string_ident_parser,
+            html_ident_parser,
+        )),
+    )(input)
 }
 
-fn attr_list_parser(input: &str) -> ParseResult<&str, Vec<Attribute>> {
-    let (rest, mut lists) = many1(ws(delimited(char('['), ws(a_list_parser), char(']'))))(input)?;
-    return Ok((rest, lists.drain(..).flatten().collect::<Vec<_>>()));
+/// One or more bracketed lists of attributes parsed by `item`, joined.
+fn attr_lists_parser<'a, O: 'a, E: DotError<'a>>(
+    item: fn(&'a str) -> ParseResult<&'a str, O, E>,
+    input: &'a str,
+) -> ParseResult<&'a str, Vec<O>, E> {
+    let (rest, lists) = many1(dot_ws(delimited(
+        char('['),
+        cut(context("expected attribute", dot_ws(many1(item)))),
+        cut(context("expected `]`", char(']'))),
+    )))(input)?;
+    Ok((rest, lists.into_iter().flatten().collect()))
 }
 
-fn edge_statement_parser(input: &str) -> ParseResult<&str, EdgeStatement> {
-    let (rest, (id, mut rhs_list, attributes)) = tuple((
-        ws(ident_parser),
-        many1(pair(ws(alt((tag("--"), tag("->")))), ws(ident_parser))),
-        opt(attr_list_parser),
+/// Attribute lists, typed, or kept as written until used if `LAZY`.
+fn attr_list_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Attributes, E> {
+    if LAZY {
+        let (rest, pairs) = attr_lists_parser(attribute_pair_parser, input)?;
+        let pairs = pairs.iter().map(|(name, value)| (&**name, &**value));
+        Ok((rest, Attributes::lazy(pairs)))
+    } else {
+        let (rest, attributes) = attr_lists_parser(attribute_parser, input)?;
+        Ok((rest, attributes.into()))
+    }
+}
+
+fn edge_op_parser<'a, E: DotError<'a>>(input: &'a str) -> ParseResult<&'a str, EdgeOp, E> {
+    context(
+        "expected edge operator",
+        dot_ws(alt((
+            tag("--").map(|_| EdgeOp::Undirected),
+            tag("->").map(|_| EdgeOp::Directed),
+        ))),
+    )(input)
+}
+
+/// a node ID, with the port of it an edge meets if any: `ID[:port[:compass]]`
+fn node_port_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, (Ident, Option<Ident>), E> {
+    let port = preceded(
+        char(':'),
+        cut(pair(
+            dot_ws(ident_parser),
+            opt(preceded(char(':'), dot_ws(ident_parser))),
+        )),
+    );
+    let (rest, (id, port)) = pair(dot_ws(ident_parser), opt(port))(input)?;
+    let port = port.map(|(port, compass)| match compass {
+        Some(compass) => format!("{}:{}", port, compass),
+        None => port,
+    });
+    Ok((rest, (id, port)))
+}
+
+fn edge_statement_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, EdgeStatement, E> {
+    let (rest, (first, rhs_list, attributes)) = tuple((
+        node_port_parser,
+        many1(pair(edge_op_parser, cut(node_port_parser))),
+        opt(attr_list_parser::<LAZY, E>),
     ))(input)?;
+    let (ops, ends): (Vec<_>, Vec<_>) = rhs_list.into_iter().unzip();
+    let (list, ports) = core::iter::once(first).chain(ends).unzip();
     Ok((
         rest,
         EdgeStatement {
-            list: vec![id]
-                .drain(..)
-                .chain(rhs_list.drain(..).map(|(_, id)| id))
-                .collect::<Vec<_>>(),
-            attributes: attributes.unwrap_or(vec![]),
+            list,
+            ports,
+            ops,
+            attributes: attributes.unwrap_or_default(),
         },
     ))
 }
 
-fn node_statement_parser(input: &str) -> ParseResult<&str, NodeStatement> {
-    let (rest, (id, attributes)) = tuple((ws(ident_parser), opt(attr_list_parser)))(input)?;
+fn node_statement_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, NodeStatement, E> {
+    let (rest, (id, attributes)) =
+        tuple((dot_ws(ident_parser), opt(attr_list_parser::<LAZY, E>)))(input)?;
     Ok((
         rest,
         NodeStatement {
             name: id,
-            attributes: attributes.unwrap_or(vec![]),
+            attributes: attributes.unwrap_or_default(),
         },
     ))
 }
 
-fn attribute_statement_parser(input: &str) -> ParseResult<&str, AttributeStatement> {
+fn attribute_statement_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, AttributeStatement, E> {
     let (rest, (kind, attributes)) = pair(
-        ws(alt((
+        dot_ws(alt((
             tag("graph").map(|_| AttributeKind::Graph),
             tag("node").map(|_| AttributeKind::Node),
             tag("edge").map(|_| AttributeKind::Edge),
         ))),
-        attr_list_parser,
+        context("expected attribute list", attr_list_parser::<LAZY, E>),
     )(input)?;
     Ok((rest, AttributeStatement { kind, attributes }))
 }
 
-fn definition_statement_parser(input: &str) -> ParseResult<&str, DefinitionStatement> {
-    let (rest, (lhs, rhs)) = separated_pair(ws(ident_parser), char('='), ws(ident_parser))(input)?;
+fn definition_statement_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, DefinitionStatement, E> {
+    let (rest, (lhs, rhs)) =
+        separated_pair(dot_ws(ident_parser), char('='), cut(dot_ws(ident_parser)))(input)?;
     Ok((rest, DefinitionStatement { lhs, rhs }))
 }
 
-fn statement_parser(input: &str) -> ParseResult<&str, Statement> {
-    ws(alt((
-        edge_statement_parser.map(|s| Statement::Edge(s)),
-        node_statement_parser.map(|s| Statement::Node(s)),
-        definition_statement_parser.map(|s| Statement::Definition(s)),
-        attribute_statement_parser.map(|s| Statement::Attribute(s)),
+fn statement_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Statement, E> {
+    // attribute statements must be tried before the ID-led statements so that their keywords
+    // aren't mistaken for node names, and definitions before nodes for the same reason
+    dot_ws(alt((
+        attribute_statement_parser::<LAZY, E>.map(Statement::Attribute),
+        edge_statement_parser::<LAZY, E>.map(Statement::Edge),
+        definition_statement_parser.map(Statement::Definition),
+        node_statement_parser::<LAZY, E>.map(Statement::Node),
     )))(input)
 }
 
-fn statements_parser(input: &str) -> ParseResult<&str, Vec<Statement>> {
-    many0(statement_parser)(input)
+fn statements_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Vec<Statement>, E> {
+    many0(statement_parser::<LAZY, E>)(input)
 }
 
-pub fn graph_parser(input: &str) -> ParseResult<&str, Graph> {
-    let (rest, (strict, graph_kind, statements)) = tuple((
-        ws(opt(tag("strict"))),
-        ws(alt((
-            tag("graph").map(|_| GraphKind::Undirected),
-            tag("digraph").map(|_| GraphKind::Directed),
-        ))),
-        delimited(ws(char('{')), statements_parser, ws(char('}'))),
+/// The start of a graph up to its opening `{`: whether it is strict, its kind and its ID.
+fn header_parser<'a, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, (bool, GraphKind, Option<Ident>), E> {
+    let (rest, (_, strict, kind, id, _)) = tuple((
+        dot_leading,
+        dot_ws(opt(tag("strict"))),
+        context(
+            "expected `graph` or `digraph`",
+            dot_ws(alt((
+                tag("graph").map(|_| GraphKind::Undirected),
+                tag("digraph").map(|_| GraphKind::Directed),
+            ))),
+        ),
+        opt(dot_ws(ident_parser)),
+        context("expected `{`", dot_ws(char('{'))),
     ))(input)?;
+    Ok((rest, (strict.is_some(), kind, id)))
+}
+
+/// The statements of a graph after its opening `{`, up to and including its closing `}`.
+fn body_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Vec<Statement>, E> {
+    terminated(
+        statements_parser::<LAZY, E>,
+        context("expected statement or `}`", dot_ws(char('}'))),
+    )(input)
+}
+
+fn graph_parser<'a, const LAZY: bool, E: DotError<'a>>(
+    input: &'a str,
+) -> ParseResult<&'a str, Graph, E> {
+    let (rest, ((strict, kind, id), statements)) =
+        pair(header_parser, body_parser::<LAZY, E>)(input)?;
     Ok((
         rest,
         Graph {
-            kind: graph_kind,
-            strict: strict.is_some(),
+            kind,
+            strict,
+            id,
             statements,
         },
     ))
 }
 
-#[derive(Debug, Error)]
+/// Errors encountered when parsing a graph. Syntax errors report the innermost expectation the
+/// parser failed on along with the location of the offending input.
+#[derive(Debug)]
 pub enum GraphParseError<I: Debug + Display> {
-    #[error("unexpected eof")]
     UnexpectedEof,
-    #[error("unexpected additional input: {0}")]
     UnexpectedInput(I),
-    #[error("parse error: {0}")]
-    ParseError(VerboseError<I>),
+    Syntax {
+        expected: &'static str,
+        line: usize,
+        column: usize,
+        found: I,
+    },
+}
+
+impl<I: Debug + Display> Display for GraphParseError<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphParseError::UnexpectedEof => write!(f, "unexpected eof"),
+            GraphParseError::UnexpectedInput(rest) => {
+                write!(f, "unexpected additional input: {}", rest)
+            }
+            GraphParseError::Syntax {
+                expected,
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "{} at line {}, column {}, found `{}`",
+                expected, line, column, found
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: Debug + Display> std::error::Error for GraphParseError<I> {}
+
+/// A [`GraphParseError`] which owns its offending input snippets, and so can be returned from
+/// functions which own the parsed string or sent across threads.
+pub type OwnedGraphParseError = GraphParseError<String>;
+
+impl<I: Debug + Display> GraphParseError<I> {
+    /// Copies any borrowed input snippets into an owned error, detaching it from the input's
+    /// lifetime.
+    pub fn into_owned(self) -> OwnedGraphParseError {
+        match self {
+            GraphParseError::UnexpectedEof => GraphParseError::UnexpectedEof,
+            GraphParseError::UnexpectedInput(rest) => {
+                GraphParseError::UnexpectedInput(rest.to_string())
+            }
+            GraphParseError::Syntax {
+                expected,
+                line,
+                column,
+                found,
+            } => GraphParseError::Syntax {
+                expected,
+                line,
+                column,
+                found: found.to_string(),
+            },
+        }
+    }
+}
+
+impl<'a> From<GraphParseError<&'a str>> for OwnedGraphParseError {
+    fn from(error: GraphParseError<&'a str>) -> Self {
+        error.into_owned()
+    }
+}
+
+impl<'a> GraphParseError<&'a str> {
+    /// Converts the nom error tree into a syntax error, pointing at the innermost labeled
+    /// expectation.
+    pub(crate) fn from_verbose(input: &'a str, error: VerboseError<&'a str>) -> Self {
+        let (remaining, expected) = error
+            .errors
+            .iter()
+            .find_map(|(remaining, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some((*remaining, *context)),
+                _ => None,
+            })
+            .or_else(|| {
+                error
+                    .errors
+                    .first()
+                    .map(|(remaining, _)| (*remaining, "invalid syntax"))
+            })
+            .unwrap_or((input, "invalid syntax"));
+        if remaining.trim().is_empty() {
+            return GraphParseError::UnexpectedEof;
+        }
+        let consumed = &input[..input.len() - remaining.len()];
+        let line = consumed.matches('\n').count() + 1;
+        let column = consumed
+            .rfind('\n')
+            .map_or(consumed, |newline| &consumed[newline + 1..])
+            .chars()
+            .count()
+            + 1;
+        let found = remaining.lines().next().unwrap_or_default();
+        GraphParseError::Syntax {
+            expected,
+            line,
+            column,
+            found,
+        }
+    }
+}
+
+pub fn parse_graph(input: &str) -> Result<Graph, GraphParseError<&str>> {
+    parse::<false>(input)
+}
+
+/// Parses a graph as [`parse_graph`] does, but for the names and values of attributes, which are
+/// only checked and parsed into typed attributes when first used; see [`Attributes`]. Graphs
+/// whose attributes are mostly never read parse faster this way.
+pub fn parse_graph_lazy(input: &str) -> Result<Graph, GraphParseError<&str>> {
+    parse::<true>(input)
+}
+
+impl core::str::FromStr for Graph {
+    type Err = OwnedGraphParseError;
+
+    /// Parses a graph as [`parse_graph`] does, with an error owning its input.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(parse_graph(input)?)
+    }
 }
 
-pub fn parse_graph<'a>(input: &'a str) -> Result<Graph, GraphParseError<&'a str>> {
-    match graph_parser(input) {
+fn parse<const LAZY: bool>(input: &str) -> Result<Graph, GraphParseError<&str>> {
+    #[cfg(feature = "std")]
+    {
+        let chunks = chunks::chunks(input);
+        if chunks > 1 {
+            if let Some(graph) = chunks::parse_chunked::<LAZY>(input, chunks) {
+                return Ok(graph);
+            }
+        }
+    }
+    match graph_parser::<LAZY, ()>(input) {
         Ok((rest, graph)) => {
             if !rest.trim().is_empty() {
                 Err(GraphParseError::UnexpectedInput(rest))
@@ -272,8 +594,13 @@ pub fn parse_graph<'a>(input: &'a str) -> Result<Graph, GraphParseError<&'a str>
                 Ok(graph)
             }
         }
-        Err(nom::Err::Incomplete(_)) => Err(GraphParseError::UnexpectedEof),
-        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(GraphParseError::ParseError(e)),
+        // parse again, building the errors this time, to report where and why parsing failed
+        Err(_) => match graph_parser::<LAZY, VerboseError<&str>>(input) {
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                Err(GraphParseError::from_verbose(input, e))
+            }
+            _ => Err(GraphParseError::UnexpectedEof),
+        },
     }
 }
 
@@ -283,6 +610,7 @@ mod tests {
     use std::fmt::{Debug, Display};
 
     use super::*;
+    use crate::attribute::Attribute;
 
     fn test_parse_result<I: Debug, O: Debug>(
         s: I,
@@ -324,6 +652,26 @@ mod tests {
         }
     }
 
+    fn test_parse_invalid_context<I: Display + Debug + Default + Copy, O: Debug>(
+        s: I,
+        expected: &'static str,
+        f: impl Fn(I) -> ParseResult<I, O>,
+    ) {
+        match f(s) {
+            Ok(_) => panic!("expected to fail parsing of '{}', but succeeded", s),
+            Err(nom::Err::Error(nom::error::VerboseError { errors })) => {
+                assert!(errors.iter().any(|e| {
+                    if let nom::error::VerboseErrorKind::Context(context) = e.1 {
+                        context == expected
+                    } else {
+                        false
+                    }
+                }))
+            }
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+
     fn valid_string_idents() -> Vec<&'static str> {
         vec![
             "Howdy_there",
@@ -338,8 +686,9 @@ mod tests {
         vec![("5cantstartwithnumber", nom::error::ErrorKind::Tag)]
     }
 
-    fn expected_rest_string_idents() -> Vec<(&'static str, ParseResult<&'static str, Ident>)> {
-        vec![("no-kebab-case", Ok(("-kebab-case", "no".to_owned())))]
+    fn expected_rest_string_idents(
+    ) -> Vec<(&'static str, ParseResult<&'static str, Cow<'static, str>>)> {
+        vec![("no-kebab-case", Ok(("-kebab-case", "no".into())))]
     }
 
     fn valid_quoted_string_idents() -> Vec<(&'static str, &'static str)> {
@@ -352,6 +701,8 @@ mod tests {
                 r#"lots"of"extra"escaped"quotes"#,
             ),
             (r#""""#, ""),
+            (r#""\N: \l""#, r#"\N: \l"#),
+            ("\"split \\\nline\"", "split line"),
         ]
     }
 
@@ -364,7 +715,7 @@ mod tests {
     #[test]
     fn string_ident() {
         for s in valid_string_idents() {
-            test_parse_valid(s, s.to_owned(), string_ident_parser);
+            test_parse_valid(s, s.into(), string_ident_parser);
         }
         for (s, kind) in invalid_string_idents() {
             test_parse_invalid(s, kind, string_ident_parser);
@@ -381,7 +732,7 @@ mod tests {
         }
         println!(
             "testing: {:?}",
-            quote_string_ident_parser(r#""missing ending quote"#)
+            quote_string_ident_parser::<VerboseError<_>>(r#""missing ending quote"#)
         );
     }
 
@@ -394,11 +745,273 @@ mod tests {
         for (s, o) in valid_quoted_string_idents() {
             test_parse_valid(s, o.to_owned(), ident_parser);
         }
-        for (s, kind) in invalid_string_idents() {
-            test_parse_invalid(s, kind, ident_parser);
+        // numerals may be directly followed by another ID
+        test_parse_result(
+            "5cantstartwithnumber",
+            Ok(("cantstartwithnumber", "5".to_owned())),
+            ident_parser,
+        );
+        for s in ["-", "\"unterminated", "[]"] {
+            test_parse_invalid_context(s, "expected identifier", ident_parser);
+        }
+        for s in ["1", "-2.5", ".5", "3."] {
+            test_parse_valid(s, s.to_owned(), ident_parser);
         }
         for (s, result) in expected_rest_string_idents() {
-            test_parse_result(s, result, ident_parser);
+            test_parse_result(s, result, ident_str_parser);
+        }
+    }
+
+    #[test]
+    fn html_ident() {
+        let html = "<<b>bold</b> &amp; <i>x</i>>";
+        test_parse_valid(html, html.to_owned(), ident_parser);
+        test_parse_result("<a<b>> ]", Ok((" ]", "<a<b>>".to_owned())), ident_parser);
+        test_parse_invalid_context("<unbalanced", "expected identifier", ident_parser);
+        assert!(is_html(html) && !is_html("<f0> a|<f1> b"));
+        assert_eq!(quote_id(html), html);
+        assert_eq!(quote_id("<f0> a|<f1> b"), "\"<f0> a|<f1> b\"");
+    }
+
+    fn syntax_error(input: &str) -> (&'static str, usize, usize, &str) {
+        match parse_graph(input) {
+            Err(GraphParseError::Syntax {
+                expected,
+                line,
+                column,
+                found,
+            }) => (expected, line, column, found),
+            other => panic!("expected syntax error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn statements() {
+        let graph = parse_graph("digraph { node [shape=box] a -> b a [style=dashed] }").unwrap();
+        assert!(matches!(
+            graph.statements.as_slice(),
+            [
+                Statement::Attribute(_),
+                Statement::Edge(_),
+                Statement::Node(_)
+            ]
+        ));
+    }
+
+    #[test]
+    fn comments() {
+        let input =
+            "# generated\n/* a\n graph */ digraph { // b -> c\n a /* [x=1] */ -> b\n# c\n}\n// end";
+        let graph = parse_graph(input).unwrap();
+        assert_eq!(graph.edge_statements().count(), 1);
+        assert_eq!(graph.statements.len(), 1);
+        assert!(matches!(
+            parse_graph("digraph { a /* b }"),
+            Err(GraphParseError::UnexpectedEof)
+        ));
+        // `#` is only a comment at the start of a line
+        assert!(parse_graph("digraph { a # b\n}").is_err());
+    }
+
+    #[test]
+    fn node_ports() {
+        let graph = parse_graph("digraph { a:f0:n -> b:s -> \"c\" : \"f 1\" }").unwrap();
+        match &graph.statements[0] {
+            Statement::Edge(edge) => {
+                assert_eq!(edge.list, ["a", "b", "c"]);
+                assert_eq!(
+                    edge.ports,
+                    [
+                        Some("f0:n".to_owned()),
+                        Some("s".to_owned()),
+                        Some("f 1".to_owned())
+                    ]
+                );
+            }
+            statement => panic!("unexpected statement {:?}", statement),
         }
+        assert_eq!(syntax_error("digraph { a: -> b }").0, "expected identifier");
+    }
+
+    #[test]
+    fn quoted_attributes() {
+        let graph =
+            parse_graph(r#"graph { a [label="x"] b [label="y\"z", fontsize=14] }"#).unwrap();
+        match &graph.statements[1] {
+            Statement::Node(node) => assert_eq!(
+                *node.attributes,
+                [
+                    Attribute::Label(r#"y"z"#.to_owned()),
+                    Attribute::FontSize(14.0)
+                ]
+            ),
+            statement => panic!("unexpected statement {:?}", statement),
+        }
+    }
+
+    #[test]
+    fn lazy_attributes() {
+        let input = r#"graph { node [shape=box] a [label="x" weight=y] a -- b [color=red] }"#;
+        assert!(parse_graph(input).is_err());
+        let graph = parse_graph_lazy(input).unwrap();
+        match &graph.statements[1] {
+            Statement::Node(node) => {
+                assert_eq!(*node.attributes, [Attribute::Label("x".to_owned())]);
+                assert_eq!(
+                    node.attributes.invalid().collect::<Vec<_>>(),
+                    [("weight", "y")]
+                );
+            }
+            statement => panic!("unexpected statement {:?}", statement),
+        }
+
+        let input = input.replace("weight=y", "width=2");
+        assert_eq!(
+            format!("{:?}", parse_graph_lazy(&input).unwrap()),
+            format!("{:?}", parse_graph(&input).unwrap())
+        );
+        assert!(parse_graph_lazy("graph { a [label] }").is_err());
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(
+            syntax_error("graph {\n  a -> \n}"),
+            ("expected identifier", 3, 1, "}")
+        );
+        assert_eq!(
+            syntax_error("graph { a [shape=box }"),
+            ("expected `]`", 1, 22, "}")
+        );
+        assert_eq!(
+            syntax_error("graph { node [] }"),
+            ("expected identifier", 1, 15, "] }")
+        );
+        assert_eq!(
+            syntax_error("tree { a }"),
+            ("expected `graph` or `digraph`", 1, 1, "tree { a }")
+        );
+        assert_eq!(
+            syntax_error("graph { a - b }"),
+            ("expected statement or `}`", 1, 11, "- b }")
+        );
+        assert_eq!(
+            syntax_error("graph { a [shape=blob] }"),
+            ("expected valid attribute value", 1, 12, "shape=blob] }")
+        );
+        assert_eq!(
+            syntax_error("graph { a [colour=red] }"),
+            ("expected known attribute name", 1, 12, "colour=red] }")
+        );
+        assert!(matches!(
+            parse_graph("digraph { a -> b"),
+            Err(GraphParseError::UnexpectedEof)
+        ));
+    }
+
+    #[test]
+    fn statement_iterators() {
+        let graph =
+            parse_graph("digraph { node [shape=box] a a -> b -> c rank = same d b -> d }").unwrap();
+        assert_eq!(graph.attribute_statements().count(), 1);
+        let nodes = graph
+            .node_statements()
+            .map(|node| node.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, vec!["a", "d"]);
+        let edges = graph
+            .edge_statements()
+            .map(|edge| edge.list.len())
+            .collect::<Vec<_>>();
+        assert_eq!(edges, vec![3, 2]);
+        let definitions = graph
+            .definitions()
+            .map(|definition| (definition.lhs.as_str(), definition.rhs.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(definitions, vec![("rank", "same")]);
+    }
+
+    #[test]
+    fn attribute_pairs() {
+        let pairs = [
+            ("label", r#"say "hi"\n"#),
+            ("color", "red:blue;0.3"),
+            ("style", "dashed,bold"),
+            ("pos", "1,2!"),
+            ("arrowhead", "lteeoldiamond"),
+            ("packmode", "array_c4"),
+            ("rankdir", "LR"),
+            ("viewport", "100,50,2,'a'"),
+            ("headport", "p:ne"),
+            ("weight", "2.5"),
+        ];
+        for (name, value) in pairs {
+            let attribute = Attribute::from_pair(name, value).unwrap();
+            assert_eq!(attribute.name(), name);
+            assert_eq!(
+                Attribute::from_pair(attribute.name(), &attribute.value_string()),
+                Ok(attribute)
+            );
+        }
+        assert!(Attribute::from_pair("colour", "red").is_err());
+    }
+
+    #[test]
+    fn from_str() {
+        use crate::{
+            attribute::{ArrowType, RankDir, Shape, Style},
+            color::Color,
+        };
+
+        let graph = "digraph { a -> b [weight=2] }".parse::<Graph>().unwrap();
+        assert_eq!(graph.kind, GraphKind::Directed);
+        assert_eq!(graph.statements.len(), 1);
+        assert!(matches!(
+            "digraph { a -> }".parse::<Graph>(),
+            Err(GraphParseError::Syntax { ref found, .. }) if found == "}"
+        ));
+        assert_eq!(
+            r#"label="a b""#.parse::<Attribute>(),
+            Ok(Attribute::Label("a b".to_owned()))
+        );
+        assert_eq!(
+            " shape = box ".parse::<Attribute>(),
+            Ok(Attribute::Shape(Shape::Box))
+        );
+        assert!("label".parse::<Attribute>().is_err());
+        assert!("weight=heavy".parse::<Attribute>().is_err());
+        assert_eq!("/svg/Red".parse::<Color>().unwrap().to_string(), "/svg/red");
+        assert_eq!("Mrecord".parse(), Ok(Shape::MRecord));
+        assert_eq!(
+            "lteeoldiamond".parse::<ArrowType>().unwrap().to_string(),
+            "lteeoldiamond"
+        );
+        assert_eq!("dashed".parse(), Ok(Style::Dashed));
+        assert_eq!("LR".parse(), Ok(RankDir::LeftToRight));
+        assert!("up".parse::<RankDir>().is_err());
+        for keyword in ["box", "Mrecord", "plaintext", "none"] {
+            assert_eq!(keyword.parse::<Shape>().unwrap().to_string(), keyword);
+        }
+        assert_eq!(Style::Dashed.to_string(), "dashed");
+        assert_eq!(
+            r#"style="dashed,bold""#.parse::<Attribute>().unwrap().value_string(),
+            "dashed,bold"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn owned_error() {
+        fn parse_owned(input: String) -> Result<Graph, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(parse_graph(&input).map_err(OwnedGraphParseError::from)?)
+        }
+
+        let error = parse_owned("graph { a -> }".to_owned()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected identifier at line 1, column 14, found `}`"
+        );
+        let error = error.downcast::<OwnedGraphParseError>().unwrap();
+        assert!(matches!(*error, GraphParseError::Syntax { ref found, .. } if found == "}"));
     }
 }
@@ -0,0 +1,305 @@
+//! Compact attribute lists for statements.
+//!
+//! Most statements set no attributes or a single one, for which a `Vec` takes three words and a
+//! separate allocation. [`Attributes`] holds a single attribute inline, in the room of the
+//! attribute itself, and more in a `Vec` as before.
+//!
+//! Lists read by [`parse_graph_lazy`](super::parse_graph_lazy) keep their names and values as
+//! written, and are parsed into typed attributes the first time they are read, once. Attributes
+//! that don't parse are left out of the typed list, and given by [`Attributes::invalid`].
+//! Changing a lazy list parses it, dropping the invalid attributes.
+
+use alloc::{boxed::Box, vec, vec::Vec};
+#[cfg(not(feature = "std"))]
+use core::cell::OnceCell as OnceLock;
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+    str,
+};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
+
+use crate::attribute::Attribute;
+
+/// The attributes of a statement, in order; see the [module documentation](self).
+#[derive(Clone, Default)]
+pub struct Attributes(Repr);
+
+#[derive(Clone, Default)]
+enum Repr {
+    #[default]
+    Empty,
+    One(Attribute),
+    Many(Vec<Attribute>),
+    Lazy(Box<Lazy>),
+}
+
+/// The names and values of a list as written, in one allocation, and the list parsed on first
+/// use. Each name and value is in `text` as its length, in four bytes, followed by itself.
+#[derive(Clone)]
+struct Lazy {
+    text: Vec<u8>,
+    parsed: OnceLock<Parsed>,
+}
+
+#[derive(Clone)]
+struct Parsed {
+    attributes: Vec<Attribute>,
+    /// The offsets in the text of the attributes that didn't parse.
+    invalid: Vec<usize>,
+}
+
+impl Lazy {
+    /// The name or value at `offset` in the text, and the offset after it.
+    fn field(&self, offset: usize) -> (&str, usize) {
+        let start = offset + 4;
+        let length = u32::from_le_bytes(self.text[offset..start].try_into().unwrap());
+        let end = start + length as usize;
+        (str::from_utf8(&self.text[start..end]).unwrap(), end)
+    }
+
+    /// The name and value at `offset` in the text, and the offset after them.
+    fn pair(&self, offset: usize) -> ((&str, &str), usize) {
+        let (name, offset) = self.field(offset);
+        let (value, offset) = self.field(offset);
+        ((name, value), offset)
+    }
+
+    fn parsed(&self) -> &Parsed {
+        self.parsed.get_or_init(|| {
+            let mut parsed = Parsed {
+                attributes: vec![],
+                invalid: vec![],
+            };
+            let mut offset = 0;
+            while offset < self.text.len() {
+                let ((name, value), next) = self.pair(offset);
+                match Attribute::from_pair(name, value) {
+                    Ok(attribute) => parsed.attributes.push(attribute),
+                    Err(_) => parsed.invalid.push(offset),
+                }
+                offset = next;
+            }
+            parsed
+        })
+    }
+}
+
+impl Attributes {
+    pub fn new() -> Self {
+        Attributes(Repr::Empty)
+    }
+
+    /// A list of the `name = value` pairs given, parsed on first use.
+    pub(crate) fn lazy<'a, I>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+        I::IntoIter: Clone,
+    {
+        let fields = pairs.into_iter().flat_map(|(name, value)| [name, value]);
+        let mut text = Vec::with_capacity(fields.clone().map(|field| 4 + field.len()).sum());
+        for field in fields {
+            let length = u32::try_from(field.len()).expect("attribute shorter than 4 GiB");
+            text.extend_from_slice(&length.to_le_bytes());
+            text.extend_from_slice(field.as_bytes());
+        }
+        if text.is_empty() {
+            return Attributes::new();
+        }
+        Attributes(Repr::Lazy(Box::new(Lazy {
+            text,
+            parsed: OnceLock::new(),
+        })))
+    }
+
+    /// The names and values of the attributes of a lazy list which didn't parse, as written.
+    pub fn invalid(&self) -> impl Iterator<Item = (&str, &str)> {
+        let lazy = match &self.0 {
+            Repr::Lazy(lazy) => Some(&**lazy),
+            _ => None,
+        };
+        lazy.into_iter().flat_map(|lazy| {
+            let invalid = &lazy.parsed().invalid;
+            invalid.iter().map(move |&offset| lazy.pair(offset).0)
+        })
+    }
+
+    pub fn push(&mut self, attribute: Attribute) {
+        self.parse();
+        self.0 = match core::mem::take(&mut self.0) {
+            Repr::Empty => Repr::One(attribute),
+            Repr::One(first) => Repr::Many(vec![first, attribute]),
+            Repr::Many(mut attributes) => {
+                attributes.push(attribute);
+                Repr::Many(attributes)
+            }
+            Repr::Lazy(_) => unreachable!("parsed"),
+        };
+    }
+
+    /// Replaces a lazy list by its typed attributes.
+    fn parse(&mut self) {
+        if let Repr::Lazy(lazy) = &mut self.0 {
+            lazy.parsed();
+            let attributes = lazy.parsed.take().unwrap().attributes;
+            *self = Attributes::from(attributes);
+        }
+    }
+
+    pub fn into_vec(mut self) -> Vec<Attribute> {
+        self.parse();
+        match self.0 {
+            Repr::Empty => vec![],
+            Repr::One(attribute) => vec![attribute],
+            Repr::Many(attributes) => attributes,
+            Repr::Lazy(_) => unreachable!("parsed"),
+        }
+    }
+}
+
+impl Deref for Attributes {
+    type Target = [Attribute];
+
+    fn deref(&self) -> &[Attribute] {
+        match &self.0 {
+            Repr::Empty => &[],
+            Repr::One(attribute) => core::slice::from_ref(attribute),
+            Repr::Many(attributes) => attributes,
+            Repr::Lazy(lazy) => &lazy.parsed().attributes,
+        }
+    }
+}
+
+impl DerefMut for Attributes {
+    fn deref_mut(&mut self) -> &mut [Attribute] {
+        self.parse();
+        match &mut self.0 {
+            Repr::Empty => &mut [],
+            Repr::One(attribute) => core::slice::from_mut(attribute),
+            Repr::Many(attributes) => attributes,
+            Repr::Lazy(_) => unreachable!("parsed"),
+        }
+    }
+}
+
+impl PartialEq for Attributes {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl fmt::Debug for Attributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl From<Vec<Attribute>> for Attributes {
+    fn from(mut attributes: Vec<Attribute>) -> Self {
+        Attributes(match attributes.len() {
+            0 => Repr::Empty,
+            1 => Repr::One(attributes.pop().unwrap()),
+            _ => Repr::Many(attributes),
+        })
+    }
+}
+
+impl Extend<Attribute> for Attributes {
+    fn extend<T: IntoIterator<Item = Attribute>>(&mut self, attributes: T) {
+        for attribute in attributes {
+            self.push(attribute);
+        }
+    }
+}
+
+impl FromIterator<Attribute> for Attributes {
+    fn from_iter<T: IntoIterator<Item = Attribute>>(attributes: T) -> Self {
+        let mut list = Attributes::new();
+        list.extend(attributes);
+        list
+    }
+}
+
+impl IntoIterator for Attributes {
+    type Item = Attribute;
+    type IntoIter = core::iter::Chain<core::option::IntoIter<Attribute>, vec::IntoIter<Attribute>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.parse();
+        let (one, many) = match self.0 {
+            Repr::Empty => (None, vec![]),
+            Repr::One(attribute) => (Some(attribute), vec![]),
+            Repr::Many(attributes) => (None, attributes),
+            Repr::Lazy(_) => unreachable!("parsed"),
+        };
+        one.into_iter().chain(many)
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = core::slice::Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Attributes {
+    type Item = &'a mut Attribute;
+    type IntoIter = core::slice::IterMut<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use super::*;
+
+    #[test]
+    fn attributes() {
+        assert_eq!(size_of::<Attributes>(), size_of::<Attribute>());
+        let label = |label: &str| Attribute::Label(label.to_owned());
+        let mut attributes = Attributes::new();
+        assert!(attributes.is_empty());
+        attributes.push(label("a"));
+        assert_eq!(&*attributes, [label("a")]);
+        attributes.extend([label("b"), label("c")]);
+        attributes[1] = label("x");
+        assert_eq!(&*attributes, [label("a"), label("x"), label("c")]);
+        assert_eq!(
+            attributes.into_iter().collect::<Vec<_>>(),
+            [label("a"), label("x"), label("c")]
+        );
+        assert_eq!(Attributes::from(vec![label("a")]).into_vec(), [label("a")]);
+    }
+
+    #[test]
+    fn lazy() {
+        let mut attributes = Attributes::lazy(vec![
+            ("label", "a"),
+            ("weight", "x"),
+            ("color", "red"),
+            ("size", "1,2"),
+            ("shapes", "box"),
+        ]);
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes[0], Attribute::Label("a".to_owned()));
+        assert_eq!(
+            attributes.invalid().collect::<Vec<_>>(),
+            [("weight", "x"), ("shapes", "box")]
+        );
+        let copy = attributes.clone();
+        attributes.push(Attribute::Label("b".to_owned()));
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes.invalid().count(), 0);
+        assert_eq!(copy.into_vec().len(), 3);
+    }
+}
@@ -0,0 +1,227 @@
+//! Parsing large graph bodies in chunks, on all cores with the `parallel` feature.
+//!
+//! Statements have no terminators, so the body is split where one must end: before an ID
+//! following an ID or an attribute list, outside quotes, HTML strings, attribute lists and
+//! comments. Each chunk is parsed on its own thread and the statements joined in order. A chunk
+//! that fails to parse makes the whole input be parsed again in one piece, so that errors are
+//! reported exactly as without chunks.
+
+use super::{header_parser, statements_parser, Graph, Statement};
+use crate::scan::{comment, separator};
+
+/// Fewest bytes of statements per chunk worth parsing on a thread of its own.
+const MIN_CHUNK: usize = 1 << 16;
+
+/// Number of threads to parse on, all cores with the `parallel` feature.
+fn threads() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        std::thread::available_parallelism().map_or(1, usize::from)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+/// Whether `c` can be part of an unquoted ID: a name or a numeral.
+fn is_id_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'.' || c >= 0x80
+}
+
+/// The offsets in `body` statements can be split at, and the offset of the `}` closing it, if
+/// any. See the [module documentation](self).
+pub(super) fn boundaries(body: &str) -> (Vec<usize>, Option<usize>) {
+    let bytes = body.as_bytes();
+    let mut boundaries = vec![];
+    // whether the last token could end a statement
+    let mut ends = false;
+    let mut index = 0;
+    while index < bytes.len() {
+        let line_start = index > 0 && bytes[index - 1] == b'\n';
+        if let Some(length) = comment(&bytes[index..], line_start) {
+            index += length;
+            continue;
+        }
+        let start = index;
+        match bytes[index] {
+            b'}' => return (boundaries, Some(index)),
+            c if c.is_ascii_whitespace() => {
+                index += 1;
+                continue;
+            }
+            b'-' if matches!(bytes.get(index + 1), Some(b'>' | b'-')) => {
+                index += 2;
+                ends = false;
+                continue;
+            }
+            b'"' => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                index += 1;
+            }
+            b'<' => {
+                let mut depth = 0;
+                while index < bytes.len() {
+                    match bytes[index] {
+                        b'<' => depth += 1,
+                        b'>' => depth -= 1,
+                        _ => {}
+                    }
+                    index += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            b'[' => {
+                let mut depth = 0;
+                while index < bytes.len() && (depth > 0 || bytes[index] != b']') {
+                    match bytes[index] {
+                        b'"' if depth == 0 => {
+                            index += 1;
+                            while index < bytes.len() && bytes[index] != b'"' {
+                                index += if bytes[index] == b'\\' { 2 } else { 1 };
+                            }
+                        }
+                        b'<' => depth += 1,
+                        b'>' if depth > 0 => depth -= 1,
+                        _ => {}
+                    }
+                    index += 1;
+                }
+                index += 1;
+                ends = true;
+                continue;
+            }
+            b'-' => {
+                index += 1;
+                while index < bytes.len() && is_id_char(bytes[index]) {
+                    index += 1;
+                }
+            }
+            c if is_id_char(c) => {
+                while index < bytes.len() && is_id_char(bytes[index]) {
+                    index += 1;
+                }
+            }
+            _ => {
+                index += 1;
+                ends = false;
+                continue;
+            }
+        }
+        // an ID
+        if ends {
+            boundaries.push(start);
+        }
+        ends = true;
+    }
+    (boundaries, None)
+}
+
+/// Parses `input` as [`parse_graph`](super::parse_graph) does, or as
+/// [`parse_graph_lazy`](super::parse_graph_lazy) if `LAZY`, the body in `chunks` pieces at most,
+/// or `None` if it doesn't parse that way.
+pub(super) fn parse_chunked<const LAZY: bool>(input: &str, chunks: usize) -> Option<Graph> {
+    let (body, (strict, kind, id)) = header_parser::<()>(input).ok()?;
+    let (boundaries, end) = boundaries(body);
+    let end = end?;
+    let rest = &body[end + 1..];
+    if separator(rest, false) < rest.len() {
+        return None;
+    }
+
+    // split at the boundaries nearest past each equal share of the body
+    let mut pieces = vec![];
+    let mut start = 0;
+    for share in 1..chunks {
+        let target = end * share / chunks;
+        if let Some(&split) = boundaries.iter().find(|&&split| split > start.max(target)) {
+            pieces.push(&body[start..split]);
+            start = split;
+        }
+    }
+    pieces.push(&body[start..end]);
+
+    let parse = |piece: &str| -> Option<Vec<Statement>> {
+        match statements_parser::<LAZY, ()>(piece) {
+            Ok((rest, statements)) if rest.trim().is_empty() => Some(statements),
+            _ => None,
+        }
+    };
+    let parsed = if pieces.len() == 1 {
+        vec![parse(pieces[0])]
+    } else {
+        std::thread::scope(|scope| {
+            let handles = pieces
+                .iter()
+                .map(|piece| scope.spawn(move || parse(piece)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().ok().flatten())
+                .collect()
+        })
+    };
+    let mut statements = vec![];
+    for piece in parsed {
+        statements.extend(piece?);
+    }
+    Some(Graph {
+        kind,
+        strict,
+        id,
+        statements,
+    })
+}
+
+/// The number of chunks to parse `input` in, 1 for inputs too small to be worth splitting.
+pub(super) fn chunks(input: &str) -> usize {
+    threads().min(input.len() / MIN_CHUNK).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundaries() {
+        let body = r#"a -> b c [x="]" y=1] [z=2 l=<]>] d:n -> -1 e = f <g<b>h</b>> "i j" } k"#;
+        let (boundaries, end) = super::boundaries(body);
+        let starts = boundaries
+            .iter()
+            .map(|&index| &body[index..index + 1])
+            .collect::<Vec<_>>();
+        assert_eq!(starts, vec!["c", "d", "e", "<", "\""]);
+        assert_eq!(end, Some(body.len() - 3));
+        // nothing in comments starts statements or closes the body
+        let body = "a // b }\n c /* d } */ e\n# f }\n g";
+        let (boundaries, end) = super::boundaries(body);
+        let starts = boundaries.iter().map(|&index| &body[index..index + 1]);
+        assert_eq!(starts.collect::<Vec<_>>(), vec!["c", "e", "g"]);
+        assert_eq!(end, None);
+    }
+
+    #[test]
+    fn chunks() {
+        let mut input = "strict digraph G {\n".to_owned();
+        for index in 0..200 {
+            input.push_str(&format!(
+                "n{0} -> n{1} [label=\"{0} ]\"] \"x {0}\" node [shape=box] n{1}:p -> y{0} // }}\n",
+                index,
+                index + 1
+            ));
+        }
+        input.push_str("}\n");
+        let whole = format!("{:?}", super::super::parse_graph(&input).unwrap());
+        for chunks in [1, 2, 7, 64] {
+            let graph = parse_chunked::<false>(&input, chunks).unwrap();
+            assert_eq!(format!("{:?}", graph), whole);
+        }
+        assert!(parse_chunked::<false>("digraph { a -> b c -> }", 2).is_none());
+        assert!(parse_chunked::<false>("digraph { a -> b } c", 2).is_none());
+    }
+}
@@ -0,0 +1,89 @@
+//! Parsing graphs straight from files.
+//!
+//! The file is read in one allocation which becomes the input text itself when it is valid
+//! UTF-8, without copying it into a `String`. Input that isn't UTF-8 is an error, replaced
+//! character by character, or read as Latin-1 as Graphviz reads files with `charset=latin1`, by
+//! the [`Utf8`] strategy given.
+
+use std::{io, path::Path};
+
+use thiserror::Error;
+
+use super::{parse_graph, Graph, OwnedGraphParseError};
+
+/// What to do with input that isn't valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Utf8 {
+    /// Fail with the offset of the first invalid byte.
+    #[default]
+    Strict,
+    /// Replace invalid sequences with U+FFFD.
+    Lossy,
+    /// Read the input as Latin-1, each byte a character. Input that is valid UTF-8 is read as
+    /// UTF-8 all the same.
+    Latin1,
+}
+
+#[derive(Debug, Error)]
+pub enum ReadError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("invalid UTF-8 at byte {0}")]
+    InvalidUtf8(usize),
+    #[error(transparent)]
+    Parse(#[from] OwnedGraphParseError),
+}
+
+/// Decodes `bytes` by `utf8`; see the [module documentation](self).
+fn decode(bytes: Vec<u8>, utf8: Utf8) -> Result<String, ReadError> {
+    String::from_utf8(bytes).or_else(|error| match utf8 {
+        Utf8::Strict => Err(ReadError::InvalidUtf8(error.utf8_error().valid_up_to())),
+        Utf8::Lossy => Ok(String::from_utf8_lossy(error.as_bytes()).into_owned()),
+        Utf8::Latin1 => Ok(error.as_bytes().iter().map(|&byte| byte as char).collect()),
+    })
+}
+
+/// Reads and parses the graph in the file at `path`, decoding it by `utf8`.
+pub fn parse_graph_path(path: impl AsRef<Path>, utf8: Utf8) -> Result<Graph, ReadError> {
+    let input = decode(std::fs::read(path)?, utf8)?;
+    Ok(parse_graph(&input).map_err(OwnedGraphParseError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::ir::Statement;
+
+    #[test]
+    fn parse_path() {
+        let directory = env::temp_dir().join(format!("simpledot-ir-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("latin1.dot");
+        fs::write(&path, b"digraph { \"caf\xe9\" -> b }").unwrap();
+        assert!(matches!(
+            parse_graph_path(&path, Utf8::Strict),
+            Err(ReadError::InvalidUtf8(14))
+        ));
+        let ids = |utf8| match parse_graph_path(&path, utf8).unwrap().statements.remove(0) {
+            Statement::Edge(edge) => edge.list,
+            _ => unreachable!(),
+        };
+        assert_eq!(ids(Utf8::Latin1), ["café", "b"]);
+        assert_eq!(ids(Utf8::Lossy), ["caf\u{fffd}", "b"]);
+        fs::write(&path, "digraph { \"café\" -> b }").unwrap();
+        assert_eq!(ids(Utf8::Latin1), ["café", "b"]);
+
+        fs::write(&path, "digraph { a -> }").unwrap();
+        assert!(matches!(
+            parse_graph_path(&path, Utf8::Strict),
+            Err(ReadError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_graph_path(directory.join("missing.dot"), Utf8::Strict),
+            Err(ReadError::Io(_))
+        ));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+//! Reading the statements of a graph one at a time, in memory bounded by the longest statement
+//! rather than the whole input.
+//!
+//! A [`StatementStream`] reads its input in blocks, and parses the statements of each once the
+//! start of the statement after them is read, found as for [chunked parsing](super::chunks).
+//! Syntax errors are located in the whole input, as by [`parse_graph`](super::parse_graph), though
+//! a statement spanning several lines may report its error elsewhere within them.
+
+use std::{collections::VecDeque, io::Read};
+
+use nom::{error::VerboseError, Err};
+
+use crate::scan::separator;
+
+use super::{
+    body_parser, chunks::boundaries, header_parser, statements_parser, GraphKind, GraphParseError,
+    Ident, ReadError, Statement,
+};
+
+/// Bytes read from the input at a time.
+const BLOCK: usize = 1 << 16;
+
+/// The start of a graph, before its statements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub kind: GraphKind,
+    pub strict: bool,
+    pub id: Option<Ident>,
+}
+
+/// An iterator over the statements of the graph read from a reader, parsing them as they are
+/// read rather than reading the whole input first.
+pub struct StatementStream<R> {
+    reader: R,
+    header: Option<Header>,
+    /// The text read but not parsed yet, from `start`.
+    text: String,
+    start: usize,
+    /// Bytes read after `text` which don't make a whole character yet.
+    pending: Vec<u8>,
+    /// The bytes, lines and columns of the last line of the text parsed and dropped.
+    consumed: usize,
+    lines: usize,
+    column: usize,
+    statements: VecDeque<Statement>,
+    eof: bool,
+    done: bool,
+}
+
+impl<R: Read> StatementStream<R> {
+    pub fn new(reader: R) -> Self {
+        StatementStream {
+            reader,
+            header: None,
+            text: String::new(),
+            start: 0,
+            pending: vec![],
+            consumed: 0,
+            lines: 0,
+            column: 0,
+            statements: VecDeque::new(),
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// The start of the graph, reading it first if need be.
+    pub fn header(&mut self) -> Result<&Header, ReadError> {
+        while self.header.is_none() {
+            let text = &self.text[self.start..];
+            match header_parser::<VerboseError<&str>>(text) {
+                Ok((rest, (strict, kind, id))) => {
+                    self.header = Some(Header { kind, strict, id });
+                    self.consume(text.len() - rest.len());
+                }
+                Err(Err::Error(e) | Err::Failure(e)) if self.eof => {
+                    return Err(self.error(GraphParseError::from_verbose(text, e)))
+                }
+                Err(Err::Incomplete(_)) if self.eof => {
+                    return Err(ReadError::Parse(GraphParseError::UnexpectedEof))
+                }
+                // the header may be cut short
+                Err(_) => self.read()?,
+            }
+        }
+        Ok(self.header.as_ref().unwrap())
+    }
+
+    /// Reads a block of input into the text.
+    fn read(&mut self) -> Result<(), ReadError> {
+        if self.start > self.text.len() / 2 {
+            self.text.drain(..self.start);
+            self.start = 0;
+        }
+        let mut block = vec![0; BLOCK];
+        let read = self.reader.read(&mut block)?;
+        self.eof = read == 0;
+        self.pending.extend_from_slice(&block[..read]);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(error) if error.error_len().is_none() && !self.eof => error.valid_up_to(),
+            Err(error) => {
+                let offset = self.consumed + self.text.len() - self.start + error.valid_up_to();
+                return Err(ReadError::InvalidUtf8(offset));
+            }
+        };
+        self.text
+            .push_str(std::str::from_utf8(&self.pending[..valid]).unwrap());
+        self.pending.drain(..valid);
+        Ok(())
+    }
+
+    /// Drops the next `length` bytes of the text, counting their lines.
+    fn consume(&mut self, length: usize) {
+        let text = &self.text[self.start..self.start + length];
+        match text.rfind('\n') {
+            Some(newline) => {
+                self.lines += text.matches('\n').count();
+                self.column = text[newline + 1..].chars().count();
+            }
+            None => self.column += text.chars().count(),
+        }
+        self.start += length;
+        self.consumed += length;
+    }
+
+    /// An error in the text from `start`, located in the whole input.
+    fn error(&self, error: GraphParseError<&str>) -> ReadError {
+        ReadError::Parse(match error.into_owned() {
+            GraphParseError::Syntax {
+                expected,
+                line,
+                column,
+                found,
+            } => GraphParseError::Syntax {
+                expected,
+                line: line + self.lines,
+                column: if line == 1 {
+                    column + self.column
+                } else {
+                    column
+                },
+                found,
+            },
+            error => error,
+        })
+    }
+
+    /// The error in `text` from `start`, which doesn't parse as statements up to a closing `}`,
+    /// as parsing the whole input gives.
+    fn syntax_error(&self, text: &str) -> ReadError {
+        match body_parser::<false, VerboseError<&str>>(text) {
+            Err(Err::Error(e) | Err::Failure(e)) => {
+                self.error(GraphParseError::from_verbose(text, e))
+            }
+            _ => ReadError::Parse(GraphParseError::UnexpectedEof),
+        }
+    }
+
+    /// Parses the next statements of the text, if the next is whole, reading more if need be.
+    fn parse(&mut self) -> Result<(), ReadError> {
+        loop {
+            let text = &self.text[self.start..];
+            let (boundaries, end) = boundaries(text);
+            // the statements before the last boundary are whole, though the token at the last
+            // might yet be cut short, as `-` of `->`
+            let length = match (end, boundaries.len()) {
+                (Some(end), _) => end,
+                (None, count @ 2..) => boundaries[count - 2],
+                (None, _) if self.eof => return Err(self.syntax_error(text)),
+                (None, _) => {
+                    self.read()?;
+                    continue;
+                }
+            };
+            let text = &text[..length];
+            let statements = match statements_parser::<false, ()>(text) {
+                Ok((rest, statements)) if rest.trim().is_empty() => statements,
+                _ => return Err(self.syntax_error(text)),
+            };
+            self.statements.extend(statements);
+            if end.is_some() {
+                // the closing `}`, after which only whitespace and comments may follow
+                self.consume(length + 1);
+                while !self.eof {
+                    self.read()?;
+                }
+                let rest = &self.text[self.start..];
+                if separator(rest, false) < rest.len() {
+                    return Err(ReadError::Parse(GraphParseError::UnexpectedInput(
+                        rest.trim_start().to_owned(),
+                    )));
+                }
+                self.done = true;
+            } else {
+                self.consume(length);
+            }
+            return Ok(());
+        }
+    }
+}
+
+impl<R: Read> Iterator for StatementStream<R> {
+    type Item = Result<Statement, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(statement) = self.statements.pop_front() {
+                return Some(Ok(statement));
+            }
+            if self.done {
+                return None;
+            }
+            let parsed = match self.header() {
+                Ok(_) => self.parse(),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = parsed {
+                self.done = true;
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    /// A reader giving out a few bytes at a time, to cut tokens and characters short.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            let length = self.1.min(self.0.len()).min(buffer.len());
+            buffer[..length].copy_from_slice(&self.0[..length]);
+            self.0 = &self.0[length..];
+            Ok(length)
+        }
+    }
+
+    #[test]
+    fn stream() {
+        let input = "strict digraph \"G\" {\n  a -> b -> c [label=\"x ]\"] [color=red]\n  \
+                     node [shape=box] \"é\" -- d:n:s e = f <g<b>x</b>>\n  \
+                     // g }\n  h /* i } */ -> j\n# k }\n} /* l */\n";
+        let whole = parse_graph(input).unwrap();
+        for step in [1, 2, 3, 7, 1000] {
+            let mut stream = StatementStream::new(Trickle(input.as_bytes(), step));
+            let header = stream.header().unwrap().clone();
+            assert_eq!(header.id.as_deref(), Some("G"));
+            assert!(header.strict && header.kind == GraphKind::Directed);
+            let statements = stream.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(
+                format!("{:?}", statements),
+                format!("{:?}", whole.statements),
+                "{}",
+                step
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for input in [
+            "digraph {\n  a -> b\n  c -> [x=1]\n}",
+            "digraph {\n  a -> b c [x=1] ]\n}",
+            "digraph { a -> b } c",
+            "digraph { a -> b",
+            "digraph {\n  a [x=1\n}",
+            "graf { }",
+        ] {
+            let expected = parse_graph(input).unwrap_err().into_owned().to_string();
+            let error = StatementStream::new(Trickle(input.as_bytes(), 3))
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(error.to_string(), expected, "{}", input);
+        }
+        let error = StatementStream::new(&b"digraph { a\xff }"[..])
+            .find_map(Result::err)
+            .unwrap();
+        assert!(matches!(error, ReadError::InvalidUtf8(11)));
+    }
+}
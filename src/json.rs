@@ -0,0 +1,307 @@
+//! Minimal JSON value model, reader and writer shared by the JSON-based formats.
+
+use std::io::{self, Write};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while_m_n},
+    character::complete::{char, multispace0},
+    combinator::{cut, map, map_opt, value},
+    multi::{many0, separated_list0},
+    number::complete::double,
+    sequence::{delimited, preceded, separated_pair},
+};
+
+type JsonResult<'a, O> = nom::IResult<&'a str, O, nom::error::Error<&'a str>>;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) enum JsonValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Object members, in insertion order.
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+impl JsonValue {
+    /// Looks up a member of an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Text of a scalar value, as used for attribute values.
+    pub fn scalar_string(&self) -> Option<String> {
+        match self {
+            JsonValue::String(value) => Some(value.clone()),
+            JsonValue::Number(value) => Some(value.to_string()),
+            JsonValue::Bool(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Writes this value with two-space indentation, one member or element per line.
+    pub fn write_pretty<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_indented(writer, 0)?;
+        writeln!(writer)
+    }
+
+    fn write_indented<W: Write>(&self, writer: &mut W, depth: usize) -> io::Result<()> {
+        let indent = "  ".repeat(depth + 1);
+        match self {
+            JsonValue::Null => write!(writer, "null"),
+            JsonValue::Bool(value) => write!(writer, "{}", value),
+            JsonValue::Number(value) if value.is_finite() => write!(writer, "{}", value),
+            JsonValue::Number(_) => write!(writer, "null"),
+            JsonValue::String(value) => write_string(writer, value),
+            JsonValue::Array(values) if values.is_empty() => write!(writer, "[]"),
+            JsonValue::Array(values) => {
+                writeln!(writer, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    write!(writer, "{}", indent)?;
+                    value.write_indented(writer, depth + 1)?;
+                    writeln!(writer, "{}", if i + 1 < values.len() { "," } else { "" })?;
+                }
+                write!(writer, "{}]", &indent[2..])
+            }
+            JsonValue::Object(members) if members.is_empty() => write!(writer, "{{}}"),
+            JsonValue::Object(members) => {
+                writeln!(writer, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    write!(writer, "{}", indent)?;
+                    write_string(writer, key)?;
+                    write!(writer, ": ")?;
+                    value.write_indented(writer, depth + 1)?;
+                    writeln!(writer, "{}", if i + 1 < members.len() { "," } else { "" })?;
+                }
+                write!(writer, "{}}}", &indent[2..])
+            }
+        }
+    }
+}
+
+fn hex_escape_parser(input: &str) -> JsonResult<'_, u32> {
+    map_opt(
+        preceded(
+            tag("\\u"),
+            take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+        ),
+        |hex| u32::from_str_radix(hex, 16).ok(),
+    )(input)
+}
+
+fn string_parser(input: &str) -> JsonResult<'_, String> {
+    let unicode_escape = map_opt(
+        |input| {
+            let (rest, high) = hex_escape_parser(input)?;
+            if !(0xd800..0xdc00).contains(&high) {
+                return Ok((rest, high));
+            }
+            // combine UTF-16 surrogate pairs
+            let (rest, low) = hex_escape_parser(rest)?;
+            Ok((
+                rest,
+                0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff),
+            ))
+        },
+        |code| char::from_u32(code).map(String::from),
+    );
+    let escape = preceded(
+        char('\\'),
+        alt((
+            value("\"", char('"')),
+            value("\\", char('\\')),
+            value("/", char('/')),
+            value("\u{8}", char('b')),
+            value("\u{c}", char('f')),
+            value("\n", char('n')),
+            value("\r", char('r')),
+            value("\t", char('t')),
+        )),
+    );
+    delimited(
+        char('"'),
+        map(
+            many0(alt((
+                unicode_escape,
+                map(escape, str::to_owned),
+                map(is_not("\"\\"), str::to_owned),
+            ))),
+            |fragments| fragments.concat(),
+        ),
+        cut(char('"')),
+    )(input)
+}
+
+fn value_parser(input: &str) -> JsonResult<'_, JsonValue> {
+    delimited(
+        multispace0,
+        alt((
+            value(JsonValue::Null, tag("null")),
+            value(JsonValue::Bool(true), tag("true")),
+            value(JsonValue::Bool(false), tag("false")),
+            map(string_parser, JsonValue::String),
+            map(
+                preceded(
+                    char('['),
+                    cut(terminated_list(
+                        separated_list0(char(','), value_parser),
+                        ']',
+                    )),
+                ),
+                JsonValue::Array,
+            ),
+            map(
+                preceded(
+                    char('{'),
+                    cut(terminated_list(
+                        separated_list0(
+                            char(','),
+                            separated_pair(
+                                delimited(multispace0, string_parser, multispace0),
+                                cut(char(':')),
+                                value_parser,
+                            ),
+                        ),
+                        '}',
+                    )),
+                ),
+                JsonValue::Object,
+            ),
+            map(double, JsonValue::Number),
+        )),
+        multispace0,
+    )(input)
+}
+
+fn terminated_list<'a, O>(
+    list: impl FnMut(&'a str) -> JsonResult<'a, Vec<O>>,
+    end: char,
+) -> impl FnMut(&'a str) -> JsonResult<'a, Vec<O>> {
+    nom::sequence::terminated(list, preceded(multispace0, char(end)))
+}
+
+/// Parses a JSON document, returning the line of the first error on failure.
+pub(crate) fn parse(input: &str) -> Result<JsonValue, usize> {
+    let line = |rest: &str| input[..input.len() - rest.len()].matches('\n').count() + 1;
+    match value_parser(input) {
+        Ok(("", value)) => Ok(value),
+        Ok((rest, _)) => Err(line(rest)),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(line(e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(line("")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let input = r#"{"a": [1, -2.5e1, true, null], "b": {"c": "x\"\u00e9\ud83d\ude00\n"}, "d": [], "e": {}}"#;
+        let value = parse(input).unwrap();
+        assert_eq!(
+            value
+                .get("b")
+                .and_then(|b| b.get("c"))
+                .and_then(JsonValue::as_str),
+            Some("x\"é😀\n")
+        );
+        let mut output = vec![];
+        value.write_pretty(&mut output).unwrap();
+        assert_eq!(parse(std::str::from_utf8(&output).unwrap()), Ok(value));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("{\n\"a\": }"), Err(2));
+        assert_eq!(parse("[1, 2"), Err(1));
+        assert_eq!(parse("[1] 2"), Err(1));
+    }
+}
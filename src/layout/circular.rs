@@ -0,0 +1,206 @@
+//! Circular layouts, for ring and peer-to-peer topologies, like Graphviz's `circo`.
+//!
+//! Each biconnected component, or block, is placed on a circle, its nodes in the order of a
+//! depth-first traversal of the block so that neighbors tend to sit next to each other. The
+//! largest block of each connected component is placed first, and the other blocks hang off the
+//! cut vertices they share with blocks already placed, on circles extending outwards. Connected
+//! components are laid out separately and placed side by side.
+//!
+//! Nodes next to each other on a circle are [`CircularOptions::min_distance`] apart, or further if
+//! the nodes are wider.
+
+use std::{collections::VecDeque, f64::consts::PI};
+
+use crate::{
+    algorithm::components::{biconnected_components, connected_components},
+    attribute::{Attribute, EdgeRespresentation, Overlap},
+    model::{Graph, NodeIndex, POINTS_PER_INCH},
+};
+
+use super::{arrange_components, half_sizes, overlap, write_positions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircularOptions {
+    /// Distance in inches between nodes next to each other on a circle, from the `mindist`
+    /// attribute.
+    pub min_distance: f64,
+    /// From the `overlap` attribute.
+    pub overlap: Overlap,
+}
+
+impl Default for CircularOptions {
+    fn default() -> Self {
+        CircularOptions {
+            min_distance: 1.0,
+            overlap: Overlap::Remove,
+        }
+    }
+}
+
+impl CircularOptions {
+    /// Options from the attributes of `graph`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut options = CircularOptions::default();
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::MinDist(distance) => options.min_distance = *distance,
+                Attribute::Overlap(overlap) => options.overlap = *overlap,
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+/// The radius of a circle of `nodes` nodes `spacing` apart.
+fn radius(nodes: usize, spacing: f64) -> f64 {
+    if nodes < 2 {
+        0.0
+    } else {
+        spacing / (2.0 * (PI / nodes as f64).sin())
+    }
+}
+
+/// The nodes of `block` in depth-first order from `start`, following edges within the block.
+fn circle_order(
+    block: &[NodeIndex],
+    start: NodeIndex,
+    neighbors: &[Vec<NodeIndex>],
+    member: &mut [bool],
+) -> Vec<NodeIndex> {
+    for &node in block {
+        member[node] = true;
+    }
+    let mut order = vec![];
+    let mut stack = vec![start];
+    while let Some(node) = stack.pop() {
+        if !member[node] {
+            continue;
+        }
+        member[node] = false;
+        order.push(node);
+        stack.extend(neighbors[node].iter().rev().filter(|&&next| member[next]));
+    }
+    order
+}
+
+/// Lays out `graph`, setting the `pos` of its nodes and its `bb`.
+pub fn layout(graph: &mut Graph, options: &CircularOptions) {
+    let n = graph.nodes().len();
+    let sizes = half_sizes(graph);
+    let widest = sizes
+        .iter()
+        .map(|&(dx, dy)| 2.0 * dx.max(dy))
+        .fold(0.0, f64::max);
+    let spacing = (options.min_distance * POINTS_PER_INCH).max(widest + overlap::SEPARATION);
+
+    let mut neighbors = vec![vec![]; n];
+    for edge in graph.edges() {
+        if edge.tail != edge.head {
+            neighbors[edge.tail].push(edge.head);
+            neighbors[edge.head].push(edge.tail);
+        }
+    }
+    let blocks = biconnected_components(graph);
+    let mut blocks_of = vec![vec![]; n];
+    for (block, nodes) in blocks.iter().enumerate() {
+        for &node in nodes {
+            blocks_of[node].push(block);
+        }
+    }
+
+    let mut positions = vec![(0.0, 0.0); n];
+    let mut placed = vec![false; blocks.len()];
+    let mut member = vec![false; n];
+    let components = connected_components(graph);
+    for component in &components {
+        let root = component
+            .iter()
+            .flat_map(|&node| blocks_of[node].iter().copied())
+            .fold(None, |largest: Option<usize>, block| match largest {
+                Some(largest) if blocks[largest].len() >= blocks[block].len() => Some(largest),
+                _ => Some(block),
+            });
+        let root = match root {
+            Some(root) => root,
+            None => continue,
+        };
+        placed[root] = true;
+        // blocks to place, with the center of their circle, the node they hang off and its angle
+        let mut queue = VecDeque::new();
+        queue.push_back((root, (0.0, 0.0), blocks[root][0], PI / 2.0));
+        while let Some((block, center, start, angle)) = queue.pop_front() {
+            let order = circle_order(&blocks[block], start, &neighbors, &mut member);
+            let circle = radius(order.len(), spacing);
+            for (i, &node) in order.iter().enumerate() {
+                let angle = angle + 2.0 * PI * i as f64 / order.len() as f64;
+                positions[node] = (
+                    center.0 + circle * angle.cos(),
+                    center.1 + circle * angle.sin(),
+                );
+            }
+            for &node in &order {
+                let children = blocks_of[node]
+                    .iter()
+                    .copied()
+                    .filter(|&child| !placed[child])
+                    .collect::<Vec<_>>();
+                // children fan out away from the center of this block
+                let outwards = (positions[node].1 - center.1).atan2(positions[node].0 - center.0);
+                let step = PI / (children.len() as f64 + 1.0);
+                let middle = (children.len() as f64 - 1.0) / 2.0;
+                for (i, &child) in children.iter().enumerate() {
+                    placed[child] = true;
+                    let direction = outwards + (i as f64 - middle) * step;
+                    let distance = radius(blocks[child].len(), spacing);
+                    let child_center = (
+                        positions[node].0 + distance * direction.cos(),
+                        positions[node].1 + distance * direction.sin(),
+                    );
+                    queue.push_back((child, child_center, node, direction + PI));
+                }
+            }
+        }
+    }
+    arrange_components(&mut positions, &sizes, &components);
+    overlap::adjust(&mut positions, &sizes, options.overlap, &[]);
+    write_positions(
+        graph,
+        &positions,
+        &[],
+        EdgeRespresentation::LineSegment,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn circular() {
+        let mut graph = Graph::from_ir(
+            &parse_graph("graph { a -- b -- c -- d -- e -- f -- a a -- x -- y z }").unwrap(),
+        );
+        layout(&mut graph, &CircularOptions::default());
+        let position = |id: &str| {
+            let point = graph.node(id).unwrap().position().unwrap();
+            (point.x, point.y)
+        };
+        let distance = |a: &str, b: &str| {
+            let (a, b) = (position(a), position(b));
+            (a.0 - b.0).hypot(a.1 - b.1)
+        };
+        // the ring is regular, with neighbors an inch apart
+        let ring = ["a", "b", "c", "d", "e", "f", "a"];
+        for pair in ring.windows(2) {
+            assert!((distance(pair[0], pair[1]) - 72.0).abs() < 0.1);
+        }
+        assert!((distance("a", "d") - 144.0).abs() < 0.1);
+        // the chain hangs off the ring, away from it
+        assert!((distance("a", "x") - 72.0).abs() < 0.1);
+        assert!(distance("d", "y") > distance("d", "x"));
+        assert!(position("z").0 > position("y").0.max(position("d").0));
+    }
+}
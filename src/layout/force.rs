@@ -0,0 +1,651 @@
+//! Force-directed layouts, for undirected graphs or directed graphs without a natural hierarchy,
+//! like Graphviz's `neato` and `fdp`.
+//!
+//! Edges pull their ends towards an ideal distance, from the edge's `len` attribute in inches,
+//! and nodes push each other apart. Two models are available:
+//!
+//! - [`Model::Stress`] minimizes the stress of the layout by majorization, bringing the distance
+//!   between every pair of nodes close to the length of the shortest path between them.
+//! - [`Model::Spring`] simulates the spring embedder of Fruchterman and Reingold, with the
+//!   movement of nodes decreasing over the iterations. It scales to large graphs: beyond
+//!   [`BARNES_HUT_NODES`] nodes, the repulsion of distant groups of nodes is approximated by
+//!   that of their center, found in a quadtree, and with the `parallel` feature the forces on
+//!   nodes are computed on all cores. Each node's force is computed on its own, so layouts don't
+//!   depend on the number of threads.
+//!
+//! Nodes start from pseudo-random positions drawn from [`ForceOptions::seed`], so layouts are
+//! the same from one run to the next. Overlaps are then removed following
+//! [`ForceOptions::overlap`].
+//!
+//! Pinned nodes, with a `pos` ending in `!` or a true `pin`, start and stay at their `pos`, the
+//! drawing then keeping their coordinates rather than moving to the origin. With
+//! [`ForceOptions::incremental`], the other nodes with a `pos` start from it too and are held
+//! close to it, like springs to their old positions, so that a small change to the graph only
+//! moves the nodes around it; new nodes start among their placed neighbors.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    attribute::{Attribute, EdgeRespresentation, Overlap},
+    model::{Graph, Node, POINTS_PER_INCH},
+};
+
+use super::{half_sizes, overlap, write_positions, Random};
+
+/// Movement in points under which stress majorization is considered converged.
+const EPSILON: f64 = 0.01;
+
+/// How strongly incremental layouts hold nodes to their old positions, relative to the pull of
+/// the other nodes.
+const ANCHOR_STRENGTH: f64 = 1.0;
+
+/// Number of nodes beyond which the spring embedder approximates the repulsion between nodes.
+pub const BARNES_HUT_NODES: usize = 1000;
+
+/// Ratio of the size of a group of nodes to its distance under which its repulsion is
+/// approximated by that of its center.
+const THETA: f64 = 0.8;
+
+/// Depth of the quadtree past which nodes, close to coinciding, share a leaf.
+const MAX_DEPTH: usize = 32;
+
+/// Fewest nodes per thread worth computing forces in parallel.
+const NODES_PER_THREAD: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// Stress majorization, as `neato`.
+    Stress,
+    /// A spring embedder, as `fdp`.
+    Spring,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForceOptions {
+    pub model: Model,
+    /// Most iterations, from the `maxiter` attribute.
+    pub iterations: usize,
+    /// Length in inches of edges without a `len`.
+    pub edge_length: f64,
+    /// From the `overlap` attribute.
+    pub overlap: Overlap,
+    pub seed: u64,
+    /// Whether to start from the positions nodes already have and keep them close, rather than
+    /// lay out afresh.
+    pub incremental: bool,
+}
+
+impl ForceOptions {
+    /// The default options of `model`, as in Graphviz.
+    pub fn new(model: Model) -> Self {
+        ForceOptions {
+            model,
+            iterations: 200,
+            edge_length: match model {
+                Model::Stress => 1.0,
+                Model::Spring => 0.3,
+            },
+            overlap: Overlap::Remove,
+            seed: 1,
+            incremental: false,
+        }
+    }
+
+    /// The options of `model` given by the attributes of `graph`.
+    pub fn from_graph(graph: &Graph, model: Model) -> Self {
+        let mut options = ForceOptions::new(model);
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::MaxIter(iterations) => {
+                    options.iterations = (*iterations).max(0) as usize
+                }
+                Attribute::Overlap(overlap) => options.overlap = *overlap,
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+/// The ideal length of each edge in points.
+fn edge_lengths(graph: &Graph, options: &ForceOptions) -> Vec<f64> {
+    graph
+        .edges()
+        .iter()
+        .map(|edge| {
+            let length = edge
+                .attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::Len(length) => Some(*length),
+                    _ => None,
+                })
+                .unwrap_or(options.edge_length);
+            length * POINTS_PER_INCH
+        })
+        .collect()
+}
+
+/// The lengths of the shortest paths between all pairs of nodes, following edges either way.
+/// Nodes in different components are put a little further apart than the furthest connected
+/// nodes.
+fn distances(graph: &Graph, lengths: &[f64]) -> Vec<Vec<f64>> {
+    let n = graph.nodes().len();
+    let mut neighbors = vec![vec![]; n];
+    for (edge, &length) in graph.edges().iter().zip(lengths) {
+        neighbors[edge.tail].push((edge.head, length));
+        neighbors[edge.head].push((edge.tail, length));
+    }
+    let mut distances = vec![vec![f64::INFINITY; n]; n];
+    for (source, distances) in distances.iter_mut().enumerate() {
+        // the bits of non-negative floats order like the floats
+        let mut queue = BinaryHeap::new();
+        distances[source] = 0.0;
+        queue.push(Reverse((0f64.to_bits(), source)));
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            let distance = f64::from_bits(distance);
+            if distance > distances[node] {
+                continue;
+            }
+            for &(neighbor, length) in &neighbors[node] {
+                let through = distance + length;
+                if through < distances[neighbor] {
+                    distances[neighbor] = through;
+                    queue.push(Reverse((through.to_bits(), neighbor)));
+                }
+            }
+        }
+    }
+    let furthest = distances
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|distance| distance.is_finite())
+        .fold(0.0, f64::max);
+    let mean = if lengths.is_empty() {
+        POINTS_PER_INCH
+    } else {
+        lengths.iter().sum::<f64>() / lengths.len() as f64
+    };
+    for distance in distances.iter_mut().flatten() {
+        if distance.is_infinite() {
+            *distance = furthest + mean;
+        }
+    }
+    distances
+}
+
+/// Moves each node in turn to the position minimizing the stress against all others, and
+/// against its anchor if any, until nodes barely move. Pinned nodes don't move.
+fn majorize(
+    positions: &mut [(f64, f64)],
+    distances: &[Vec<f64>],
+    iterations: usize,
+    pinned: &[bool],
+    anchors: &[Option<(f64, f64)>],
+) {
+    let n = positions.len();
+    for _ in 0..iterations {
+        let mut moved: f64 = 0.0;
+        for i in (0..n).filter(|&i| !pinned[i]) {
+            let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+            for j in (0..n).filter(|&j| j != i && distances[i][j] > 0.0) {
+                let ideal = distances[i][j];
+                let weight = 1.0 / (ideal * ideal);
+                let (dx, dy) = (
+                    positions[i].0 - positions[j].0,
+                    positions[i].1 - positions[j].1,
+                );
+                let distance = dx.hypot(dy);
+                let (ux, uy) = if distance > 0.0 {
+                    (dx / distance, dy / distance)
+                } else {
+                    (0.0, 0.0)
+                };
+                x += weight * (positions[j].0 + ideal * ux);
+                y += weight * (positions[j].1 + ideal * uy);
+                total += weight;
+            }
+            if let Some(anchor) = anchors[i] {
+                let weight = total * ANCHOR_STRENGTH;
+                x += weight * anchor.0;
+                y += weight * anchor.1;
+                total += weight;
+            }
+            if total > 0.0 {
+                let next = (x / total, y / total);
+                moved = moved.max((next.0 - positions[i].0).hypot(next.1 - positions[i].1));
+                positions[i] = next;
+            }
+        }
+        if moved < EPSILON {
+            break;
+        }
+    }
+}
+
+/// Runs the spring embedder: nodes repel each other with a force inversely proportional to
+/// their distance, edges and anchors attract their ends with a force proportional to the square
+/// of their length, and movement is capped by a temperature cooling down linearly, from lower
+/// when anchored. Pinned nodes don't move.
+fn spring(
+    graph: &Graph,
+    positions: &mut [(f64, f64)],
+    lengths: &[f64],
+    iterations: usize,
+    pinned: &[bool],
+    anchors: &[Option<(f64, f64)>],
+) {
+    let n = positions.len();
+    let k = lengths.iter().sum::<f64>() / lengths.len().max(1) as f64;
+    let k = if k > 0.0 { k } else { POINTS_PER_INCH };
+    let start = if anchors.iter().any(Option::is_some) {
+        k
+    } else {
+        k * (n as f64).sqrt()
+    };
+    for iteration in 0..iterations {
+        let temperature = start * (1.0 - iteration as f64 / iterations as f64);
+        let mut displacements = repulsion(positions, k, threads());
+        for (edge, &length) in graph.edges().iter().zip(lengths) {
+            let (tail, head) = (edge.tail, edge.head);
+            if tail == head || length <= 0.0 {
+                continue;
+            }
+            let (dx, dy) = (
+                positions[tail].0 - positions[head].0,
+                positions[tail].1 - positions[head].1,
+            );
+            let distance = dx.hypot(dy).max(0.01);
+            let force = distance / length;
+            displacements[tail].0 -= dx * force;
+            displacements[tail].1 -= dy * force;
+            displacements[head].0 += dx * force;
+            displacements[head].1 += dy * force;
+        }
+        for (i, anchor) in anchors.iter().enumerate() {
+            if let Some(anchor) = anchor {
+                let (dx, dy) = (positions[i].0 - anchor.0, positions[i].1 - anchor.1);
+                let force = ANCHOR_STRENGTH * dx.hypot(dy) / k;
+                displacements[i].0 -= dx * force;
+                displacements[i].1 -= dy * force;
+            }
+        }
+        for ((position, (dx, dy)), &pinned) in positions.iter_mut().zip(displacements).zip(pinned) {
+            let length = f64::hypot(dx, dy);
+            if length > 0.0 && !pinned {
+                let step = length.min(temperature) / length;
+                position.0 += dx * step;
+                position.1 += dy * step;
+            }
+        }
+    }
+}
+
+/// A cell of a quadtree over node positions, with the number of nodes in it and their center.
+struct Cell {
+    size: f64,
+    count: f64,
+    center: (f64, f64),
+    children: Vec<usize>,
+    /// The nodes of a leaf.
+    nodes: Vec<usize>,
+}
+
+/// Builds the cell of `nodes` within the square at `corner` of side `size`, and those under it,
+/// returning its index in `cells`.
+fn build(
+    cells: &mut Vec<Cell>,
+    positions: &[(f64, f64)],
+    nodes: Vec<usize>,
+    corner: (f64, f64),
+    size: f64,
+    depth: usize,
+) -> usize {
+    let count = nodes.len() as f64;
+    let center = (
+        nodes.iter().map(|&node| positions[node].0).sum::<f64>() / count,
+        nodes.iter().map(|&node| positions[node].1).sum::<f64>() / count,
+    );
+    let index = cells.len();
+    cells.push(Cell {
+        size,
+        count,
+        center,
+        children: vec![],
+        nodes: vec![],
+    });
+    if nodes.len() == 1 || depth == MAX_DEPTH {
+        cells[index].nodes = nodes;
+        return index;
+    }
+    let half = size / 2.0;
+    let mut quadrants = vec![vec![]; 4];
+    for node in nodes {
+        let right = positions[node].0 >= corner.0 + half;
+        let top = positions[node].1 >= corner.1 + half;
+        quadrants[usize::from(right) + 2 * usize::from(top)].push(node);
+    }
+    for (quadrant, nodes) in quadrants.into_iter().enumerate() {
+        if !nodes.is_empty() {
+            let corner = (
+                corner.0 + half * (quadrant % 2) as f64,
+                corner.1 + half * (quadrant / 2) as f64,
+            );
+            let child = build(cells, positions, nodes, corner, half, depth + 1);
+            cells[index].children.push(child);
+        }
+    }
+    index
+}
+
+/// The push of the node at `position` away from a node, or `count` nodes, at `other`.
+fn push((x, y): (f64, f64), other: (f64, f64), count: f64, k: f64) -> (f64, f64) {
+    let (dx, dy) = (x - other.0, y - other.1);
+    let distance = dx.hypot(dy).max(0.01);
+    let force = count * k * k / distance / distance;
+    (dx * force, dy * force)
+}
+
+/// The displacement of each node by the repulsion of all others, exact for small graphs and by
+/// Barnes-Hut approximation for large ones, computed on `threads` threads.
+fn repulsion(positions: &[(f64, f64)], k: f64, threads: usize) -> Vec<(f64, f64)> {
+    let n = positions.len();
+    let mut cells = vec![];
+    if n > BARNES_HUT_NODES {
+        let (mut left, mut bottom) = (f64::INFINITY, f64::INFINITY);
+        let (mut right, mut top) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in positions {
+            left = left.min(x);
+            bottom = bottom.min(y);
+            right = right.max(x);
+            top = top.max(y);
+        }
+        let size = (right - left).max(top - bottom).max(1.0);
+        build(
+            &mut cells,
+            positions,
+            (0..n).collect(),
+            (left, bottom),
+            size,
+            0,
+        );
+    }
+    let force = |i: usize| {
+        let mut total = (0.0, 0.0);
+        let mut add = |(dx, dy): (f64, f64)| {
+            total.0 += dx;
+            total.1 += dy;
+        };
+        if cells.is_empty() {
+            for j in (0..n).filter(|&j| j != i) {
+                add(push(positions[i], positions[j], 1.0, k));
+            }
+            return total;
+        }
+        let mut stack = vec![0];
+        while let Some(cell) = stack.pop() {
+            let cell = &cells[cell];
+            let (dx, dy) = (
+                positions[i].0 - cell.center.0,
+                positions[i].1 - cell.center.1,
+            );
+            if cell.children.is_empty() {
+                for &j in cell.nodes.iter().filter(|&&j| j != i) {
+                    add(push(positions[i], positions[j], 1.0, k));
+                }
+            } else if cell.size < THETA * dx.hypot(dy) {
+                add(push(positions[i], cell.center, cell.count, k));
+            } else {
+                stack.extend(&cell.children);
+            }
+        }
+        total
+    };
+    let threads = threads.min(n / NODES_PER_THREAD).max(1);
+    if threads == 1 {
+        return (0..n).map(force).collect();
+    }
+    let chunk = n.div_ceil(threads);
+    let mut displacements = vec![(0.0, 0.0); n];
+    std::thread::scope(|scope| {
+        for (index, displacements) in displacements.chunks_mut(chunk).enumerate() {
+            let force = &force;
+            scope.spawn(move || {
+                for (offset, displacement) in displacements.iter_mut().enumerate() {
+                    *displacement = force(index * chunk + offset);
+                }
+            });
+        }
+    });
+    displacements
+}
+
+/// Number of threads to compute forces on, all cores with the `parallel` feature.
+fn threads() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        std::thread::available_parallelism().map_or(1, usize::from)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+/// Lays out `graph`, setting the `pos` of its nodes and its `bb`.
+pub fn layout(graph: &mut Graph, options: &ForceOptions) {
+    let n = graph.nodes().len();
+    let lengths = edge_lengths(graph, options);
+    let mut random = Random(options.seed.max(1));
+    let side = POINTS_PER_INCH * options.edge_length * (n as f64).sqrt();
+    let mut positions = (0..n)
+        .map(|_| (random.next() * side, random.next() * side))
+        .collect::<Vec<_>>();
+    let pinned = graph
+        .nodes()
+        .iter()
+        .map(Node::is_pinned)
+        .collect::<Vec<_>>();
+    let anchors = graph
+        .nodes()
+        .iter()
+        .map(|node| {
+            let position = node.position().map(|point| (point.x, point.y));
+            position.filter(|_| options.incremental || node.is_pinned())
+        })
+        .collect::<Vec<_>>();
+    for (position, anchor) in positions.iter_mut().zip(&anchors) {
+        if let Some(anchor) = anchor {
+            *position = *anchor;
+        }
+    }
+    if options.incremental {
+        // new nodes start a little off the middle of their placed neighbors
+        for node in (0..n).filter(|&node| anchors[node].is_none()) {
+            let neighbors = graph
+                .edges()
+                .iter()
+                .filter_map(|edge| match (edge.tail == node, edge.head == node) {
+                    (true, false) => anchors[edge.head],
+                    (false, true) => anchors[edge.tail],
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if !neighbors.is_empty() {
+                let count = neighbors.len() as f64;
+                let jitter = POINTS_PER_INCH * options.edge_length / 2.0;
+                positions[node] = (
+                    neighbors.iter().map(|point| point.0).sum::<f64>() / count
+                        + (random.next() - 0.5) * jitter,
+                    neighbors.iter().map(|point| point.1).sum::<f64>() / count
+                        + (random.next() - 0.5) * jitter,
+                );
+            }
+        }
+    }
+    // pins hold by not moving at all, and need no anchor
+    let anchors = anchors
+        .into_iter()
+        .zip(&pinned)
+        .map(|(anchor, &pinned)| anchor.filter(|_| !pinned))
+        .collect::<Vec<_>>();
+    match options.model {
+        Model::Stress => majorize(
+            &mut positions,
+            &distances(graph, &lengths),
+            options.iterations,
+            &pinned,
+            &anchors,
+        ),
+        Model::Spring => spring(
+            graph,
+            &mut positions,
+            &lengths,
+            options.iterations,
+            &pinned,
+            &anchors,
+        ),
+    }
+    overlap::adjust(&mut positions, &half_sizes(graph), options.overlap, &pinned);
+    let translate = !options.incremental && !pinned.contains(&true);
+    write_positions(
+        graph,
+        &positions,
+        &[],
+        EdgeRespresentation::LineSegment,
+        translate,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::{Point, Position},
+        ir::parse_graph,
+    };
+
+    fn distance(graph: &Graph, a: &str, b: &str) -> f64 {
+        let (a, b) = (
+            graph.node(a).unwrap().position().unwrap(),
+            graph.node(b).unwrap().position().unwrap(),
+        );
+        (a.x - b.x).hypot(a.y - b.y)
+    }
+
+    #[test]
+    fn stress() {
+        let mut graph = Graph::from_ir(
+            &parse_graph("graph { a -- b -- c -- d -- a a -- e [len=2] x }").unwrap(),
+        );
+        layout(&mut graph, &ForceOptions::new(Model::Stress));
+        // the square's sides are about an inch, and its diagonals longer
+        assert!((distance(&graph, "a", "b") - 72.0).abs() < 8.0);
+        assert!(distance(&graph, "a", "c") > 90.0);
+        assert!((distance(&graph, "a", "e") - 144.0).abs() < 16.0);
+        let bb = graph.bounding_box().unwrap();
+        assert_eq!(
+            graph.attributes.last(),
+            Some(&Attribute::Bb(Box::new(bb.clone()))),
+            "{:?}",
+            graph.attributes
+        );
+        assert_eq!((bb.lower_left.x, bb.lower_left.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn spring() {
+        let mut graph =
+            Graph::from_ir(&parse_graph("graph { a -- b -- c a -- c c -- d }").unwrap());
+        let options = ForceOptions::new(Model::Spring);
+        layout(&mut graph, &options);
+        let positions = graph
+            .nodes()
+            .iter()
+            .map(|node| node.position().cloned())
+            .collect::<Vec<_>>();
+        let mut again = graph.clone();
+        layout(&mut again, &options);
+        let again = again
+            .nodes()
+            .iter()
+            .map(|node| node.position().cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(positions, again);
+        assert!(distance(&graph, "a", "b") < distance(&graph, "a", "d"));
+    }
+
+    #[test]
+    fn pinned() {
+        let mut graph = Graph::from_ir(
+            &parse_graph(
+                r#"graph { a [pos="100,100!"] b [pos="300,100" pin=true] c [pos="0,0"] a -- c -- b }"#,
+            )
+            .unwrap(),
+        );
+        for &model in &[Model::Stress, Model::Spring] {
+            layout(&mut graph, &ForceOptions::new(model));
+            let a = graph.node("a").unwrap();
+            assert!(a
+                .attributes
+                .contains(&Attribute::Pos(Position::Pinned(Point {
+                    x: 100.0,
+                    y: 100.0
+                }))));
+            let b = graph.node("b").unwrap();
+            assert_eq!(b.position(), Some(&Point { x: 300.0, y: 100.0 }));
+            assert!(b.is_pinned());
+            // `c` is free, and ends up between the pins
+            let c = graph.node("c").unwrap().position().unwrap();
+            assert!(c.x > 120.0 && c.x < 280.0, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn incremental() {
+        let source = "graph { a -- b -- c -- d -- a b -- e }";
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout(&mut graph, &ForceOptions::new(Model::Stress));
+        let before = graph.clone();
+        let f = graph.add_node("f");
+        let c = graph.node_index("c").unwrap();
+        graph.add_edge(c, f, vec![]);
+        let options = ForceOptions {
+            incremental: true,
+            ..ForceOptions::new(Model::Stress)
+        };
+        layout(&mut graph, &options);
+        for node in before.nodes() {
+            let (old, new) = (
+                node.position().unwrap(),
+                graph.node(&node.id).unwrap().position().unwrap(),
+            );
+            assert!((old.x - new.x).hypot(old.y - new.y) < 24.0, "{}", node.id);
+        }
+        assert!((distance(&graph, "c", "f") - 72.0).abs() < 16.0);
+    }
+
+    #[test]
+    fn barnes_hut() {
+        let mut random = Random(7);
+        let n = BARNES_HUT_NODES + 200;
+        let positions = (0..n)
+            .map(|_| (random.next() * 1000.0, random.next() * 1000.0))
+            .collect::<Vec<_>>();
+        let approximate = repulsion(&positions, 10.0, 1);
+        assert_eq!(repulsion(&positions, 10.0, 4), approximate);
+        let (mut error, mut total) = (0.0, 0.0);
+        for (i, &(x, y)) in approximate.iter().enumerate() {
+            let (mut ex, mut ey) = (0.0, 0.0);
+            for j in (0..n).filter(|&j| j != i) {
+                let (dx, dy) = push(positions[i], positions[j], 1.0, 10.0);
+                ex += dx;
+                ey += dy;
+            }
+            error += (x - ex).hypot(y - ey);
+            total += ex.hypot(ey);
+        }
+        assert!(error / total < 0.05, "{}", error / total);
+    }
+}
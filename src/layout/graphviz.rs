@@ -0,0 +1,250 @@
+//! Layout by an installed Graphviz, for production-quality layouts while the native engines
+//! mature.
+//!
+//! The graph is written as DOT to the standard input of a Graphviz program, `dot` by default,
+//! which is asked for `plain` output: one line per node and edge with its position, in inches.
+//! The positions and sizes are read back into the graph as `pos`, `width` and `height`
+//! attributes, and the drawing's size as `bb`, just as the native engines write them. `plain`
+//! rather than `xdot` output is read, as it carries every position without the drawing
+//! operations and the Graphviz-only attributes of `xdot`.
+//!
+//! Graphviz writes edges grouped by tail, so edges are matched back by their ends, the `n`th
+//! edge between two nodes in the output being the `n`th between them in the graph.
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{
+    attribute::{Attribute, Point, Position, Rectangle},
+    export,
+    model::{set_attribute, Graph, NodeIndex, POINTS_PER_INCH},
+};
+
+use super::{round, LayoutEngine, LayoutError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graphviz {
+    /// The program to run, found on the `PATH` unless a path.
+    pub program: String,
+    /// The engine to ask for with `-K`, or else the one named by the graph's `layout` attribute,
+    /// or `program` itself.
+    pub engine: Option<String>,
+}
+
+impl Default for Graphviz {
+    fn default() -> Self {
+        Graphviz {
+            program: "dot".to_owned(),
+            engine: None,
+        }
+    }
+}
+
+impl LayoutEngine for Graphviz {
+    fn layout(&self, graph: &mut Graph) -> Result<(), LayoutError> {
+        let mut input = vec![];
+        export::dot::write(graph, &mut input).expect("writing to memory");
+        let mut command = Command::new(&self.program);
+        command.arg("-Tplain");
+        if let Some(engine) = &self.engine {
+            command.arg(format!("-K{}", engine));
+        }
+        let run = |source| LayoutError::Run {
+            program: self.program.clone(),
+            source,
+        };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(run)?;
+        let mut stdin = child.stdin.take().expect("piped stdin");
+        // written on the side, so that neither end blocks on a full pipe
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output().map_err(run)?;
+        writer.join().expect("writer thread").map_err(run)?;
+        if !output.status.success() {
+            return Err(LayoutError::Failed {
+                program: self.program.clone(),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+            });
+        }
+        read_plain(graph, &String::from_utf8_lossy(&output.stdout)).map_err(|message| {
+            LayoutError::Output {
+                program: self.program.clone(),
+                message,
+            }
+        })
+    }
+}
+
+/// Splits a line of `plain` output into words, unquoting quoted ones.
+fn words(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut word = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' if chars.peek() == Some(&'"') => word.push(chars.next().unwrap()),
+                    c => word.push(c),
+                }
+            }
+            words.push(word);
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                word.push(c);
+                chars.next();
+            }
+            words.push(word);
+        }
+    }
+    words
+}
+
+/// Sets the positions and sizes of the nodes and edges of `graph`, and its `bb`, from the
+/// `plain` output of Graphviz for it.
+fn read_plain(graph: &mut Graph, output: &str) -> Result<(), String> {
+    let number = |word: Option<&String>| {
+        word.and_then(|word| word.parse::<f64>().ok())
+            .ok_or_else(|| format!("expected a number, found {:?}", word))
+    };
+    let point = |x: f64, y: f64| Point {
+        x: round(x * POINTS_PER_INCH),
+        y: round(y * POINTS_PER_INCH),
+    };
+    let node = |graph: &Graph, id: Option<&String>| {
+        id.and_then(|id| graph.node_index(id))
+            .ok_or_else(|| format!("unknown node {:?}", id))
+    };
+    // the edges of the graph between each pair of nodes, in order
+    let mut edges = HashMap::<(NodeIndex, NodeIndex), Vec<usize>>::new();
+    for (index, edge) in graph.edges().iter().enumerate().rev() {
+        edges.entry((edge.tail, edge.head)).or_default().push(index);
+    }
+    let mut stopped = false;
+    for line in output.lines() {
+        let words = words(line);
+        match words.first().map(String::as_str) {
+            Some("graph") => {
+                let bb = Rectangle {
+                    lower_left: point(0.0, 0.0),
+                    upper_right: point(number(words.get(2))?, number(words.get(3))?),
+                };
+                set_attribute(&mut graph.attributes, Attribute::Bb(Box::new(bb)));
+            }
+            Some("node") => {
+                let index = node(graph, words.get(1))?;
+                let pos = point(number(words.get(2))?, number(words.get(3))?);
+                let (width, height) = (number(words.get(4))?, number(words.get(5))?);
+                let attributes = &mut graph.node_mut(index).attributes;
+                set_attribute(attributes, Attribute::Pos(Position::Point(pos)));
+                set_attribute(attributes, Attribute::Width(width));
+                set_attribute(attributes, Attribute::Height(height));
+            }
+            Some("edge") => {
+                let (tail, head) = (node(graph, words.get(1))?, node(graph, words.get(2))?);
+                let count = number(words.get(3))? as usize;
+                let points = (0..count)
+                    .map(|i| {
+                        Ok(point(
+                            number(words.get(4 + 2 * i))?,
+                            number(words.get(5 + 2 * i))?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                let undirected = !graph.is_directed();
+                let index = edges
+                    .get_mut(&(tail, head))
+                    .and_then(Vec::pop)
+                    .or_else(|| {
+                        let reversed = edges.get_mut(&(head, tail)).filter(|_| undirected)?;
+                        reversed.pop()
+                    })
+                    .ok_or_else(|| format!("unknown edge from {:?} to {:?}", words[1], words[2]))?;
+                let pos = Position::Spline(points);
+                set_attribute(&mut graph.edge_mut(index).attributes, Attribute::Pos(pos));
+            }
+            Some("stop") => {
+                stopped = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+    if stopped {
+        Ok(())
+    } else {
+        Err("output ends before `stop`".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export::plain::PlainFormat, ir::parse_graph};
+
+    fn close(a: &Point, b: &Point) -> bool {
+        (a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01
+    }
+
+    #[test]
+    fn plain() {
+        let source = r#"graph { a -- "b c" -- d d -- "b c" [label="x y"] }"#;
+        let mut laid_out = Graph::from_ir(&parse_graph(source).unwrap());
+        super::super::layout(&mut laid_out, Some("dot")).unwrap();
+        let mut output = vec![];
+        export::plain::write(&laid_out, &mut output, PlainFormat::Plain).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        read_plain(&mut graph, &output).unwrap();
+        let (bb, expected) = (
+            graph.bounding_box().unwrap(),
+            laid_out.bounding_box().unwrap(),
+        );
+        assert!(close(&bb.upper_right, &expected.upper_right));
+        for (node, expected) in graph.nodes().iter().zip(laid_out.nodes()) {
+            let (point, expected) = (node.position().unwrap(), expected.position().unwrap());
+            assert!(close(point, expected), "{}", node.id);
+        }
+        for (edge, expected) in graph.edges().iter().zip(laid_out.edges()) {
+            let pos = |attributes: &[Attribute]| {
+                attributes.iter().find_map(|attribute| match attribute {
+                    Attribute::Pos(Position::Spline(points)) => Some(points.clone()),
+                    _ => None,
+                })
+            };
+            let (points, expected) = (pos(&edge.attributes).unwrap(), pos(&expected.attributes));
+            assert_eq!(points.len(), expected.as_ref().unwrap().len());
+            for (point, expected) in points.iter().zip(&expected.unwrap()) {
+                assert!(close(point, expected));
+            }
+        }
+        assert!(read_plain(&mut graph, "graph 1 2 3\n").is_err());
+    }
+
+    #[test]
+    fn missing_program() {
+        let graphviz = Graphviz {
+            program: "simpledot-no-such-program".to_owned(),
+            engine: None,
+        };
+        let mut graph = Graph::from_ir(&parse_graph("graph { a }").unwrap());
+        assert!(matches!(
+            graphviz.layout(&mut graph),
+            Err(LayoutError::Run { .. })
+        ));
+    }
+}
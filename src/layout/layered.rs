@@ -0,0 +1,717 @@
+//! Layered layouts, for directed graphs with a hierarchy, like Graphviz's `dot`.
+//!
+//! Nodes are put on ranks by [`rank`](super::rank), drawn from the top down, so that edges point
+//! down. Edges spanning several ranks are split into chains of virtual nodes, one on each rank
+//! they cross, so that every edge joins successive ranks. The nodes of each rank are then ordered
+//! to reduce edge crossings by an [`Ordering`] heuristic, and placed side by side in that order,
+//! `nodesep` apart, each pulled towards its neighbors on the ranks above and below. Ranks are
+//! `ranksep` apart, going down or in the direction given by `rankdir`. Edges are drawn as splines
+//! through their virtual nodes unless `splines` says otherwise.
+//!
+//! The ordering heuristic starts from the order of a breadth-first traversal of the graph, or
+//! from a pseudo-random order drawn from [`LayeredOptions::seed`], so layouts are the same from
+//! one run to the next either way.
+
+use std::collections::VecDeque;
+
+use crate::{
+    attribute::{Attribute, EdgeRespresentation, RankDir},
+    model::{Graph, POINTS_PER_INCH},
+};
+
+use super::{
+    half_sizes,
+    rank::{self, RankOptions},
+    transform::Transform,
+    write_positions, Random,
+};
+
+/// Space between nodes on a rank in inches, Graphviz's default `nodesep`.
+const NODE_SEPARATION: f64 = 0.25;
+
+/// Space between ranks in inches, Graphviz's default `ranksep`.
+const RANK_SEPARATION: f64 = 0.5;
+
+/// The least `nodesep` and `ranksep` in inches, as in Graphviz.
+const MIN_SEPARATION: f64 = 0.02;
+
+/// Sweeps pulling nodes towards their neighbors in [`place_across`].
+const PLACEMENT_PASSES: usize = 8;
+
+/// Most passes of swapping neighboring nodes in [`LayeredGraph::transpose`].
+const MAX_TRANSPOSE_PASSES: usize = 10;
+
+/// A heuristic ordering the nodes of each rank of a [`LayeredGraph`] to reduce edge crossings.
+pub trait Ordering {
+    /// Reorders the nodes of each rank of `graph`, starting from their current order.
+    fn order(&self, graph: &mut LayeredGraph);
+}
+
+/// A graph with its nodes on ranks and every edge joining successive ranks.
+///
+/// Nodes are numbered from the nodes of the original graph, by their
+/// [`NodeIndex`](crate::model::NodeIndex), followed by the virtual nodes on edges spanning
+/// several ranks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredGraph {
+    /// The nodes of each rank, in order from left to right.
+    pub ranks: Vec<Vec<usize>>,
+    /// The number of nodes of the original graph.
+    real: usize,
+    rank_of: Vec<usize>,
+    above: Vec<Vec<usize>>,
+    below: Vec<Vec<usize>>,
+    /// The virtual nodes on each edge of the original graph, from its tail to its head.
+    chains: Vec<Vec<usize>>,
+}
+
+impl LayeredGraph {
+    /// Splits the edges of `graph` across the ranks `ranks` of its nodes, ignoring edges between
+    /// nodes on the same rank. The ranks are left empty, to be filled by
+    /// [`LayeredGraph::initial_order`].
+    fn new(graph: &Graph, ranks: &[usize]) -> Self {
+        let real = graph.nodes().len();
+        let mut rank_of = ranks.to_vec();
+        let mut above = vec![vec![]; real];
+        let mut below = vec![vec![]; real];
+        let mut chains = vec![];
+        for edge in graph.edges() {
+            let reversed = ranks[edge.tail] > ranks[edge.head];
+            let (mut upper, lower) = if reversed {
+                (edge.head, edge.tail)
+            } else {
+                (edge.tail, edge.head)
+            };
+            let mut chain = vec![];
+            if ranks[upper] != ranks[lower] {
+                for rank in ranks[upper] + 1..ranks[lower] {
+                    let node = rank_of.len();
+                    rank_of.push(rank);
+                    above.push(vec![upper]);
+                    below.push(vec![]);
+                    below[upper].push(node);
+                    chain.push(node);
+                    upper = node;
+                }
+                below[upper].push(lower);
+                above[lower].push(upper);
+            }
+            if reversed {
+                chain.reverse();
+            }
+            chains.push(chain);
+        }
+        let count = ranks.iter().max().map_or(0, |&last| last + 1);
+        LayeredGraph {
+            ranks: vec![vec![]; count],
+            real,
+            rank_of,
+            above,
+            below,
+            chains,
+        }
+    }
+
+    /// Puts the nodes on their ranks in the order of a breadth-first traversal, from the nodes
+    /// without edges from above in order, or shuffled by `seed`.
+    fn initial_order(&mut self, seed: Option<u64>) {
+        let n = self.rank_of.len();
+        let mut visited = vec![false; n];
+        let mut queue = VecDeque::new();
+        for start in 0..n {
+            if visited[start] || !self.above[start].is_empty() {
+                continue;
+            }
+            visited[start] = true;
+            queue.push_back(start);
+            while let Some(node) = queue.pop_front() {
+                self.ranks[self.rank_of[node]].push(node);
+                for &next in self.below[node].iter().chain(&self.above[node]) {
+                    if !visited[next] {
+                        visited[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        if let Some(seed) = seed {
+            let mut random = Random(seed.max(1));
+            for rank in &mut self.ranks {
+                for i in (1..rank.len()).rev() {
+                    let j = (random.next() * (i + 1) as f64) as usize;
+                    rank.swap(i, j);
+                }
+            }
+        }
+    }
+
+    /// The number of nodes, including virtual nodes.
+    pub fn len(&self) -> usize {
+        self.rank_of.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rank_of.is_empty()
+    }
+
+    /// Whether `node` is a virtual node on an edge spanning several ranks.
+    pub fn is_virtual(&self, node: usize) -> bool {
+        node >= self.real
+    }
+
+    pub fn rank(&self, node: usize) -> usize {
+        self.rank_of[node]
+    }
+
+    /// The nodes joined to `node` on the rank above it.
+    pub fn above(&self, node: usize) -> &[usize] {
+        &self.above[node]
+    }
+
+    /// The nodes joined to `node` on the rank below it.
+    pub fn below(&self, node: usize) -> &[usize] {
+        &self.below[node]
+    }
+
+    /// The position of each node within its rank.
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.len()];
+        for rank in &self.ranks {
+            for (position, &node) in rank.iter().enumerate() {
+                positions[node] = position;
+            }
+        }
+        positions
+    }
+
+    /// The number of pairs of crossing edges.
+    pub fn crossings(&self) -> usize {
+        let positions = self.positions();
+        let mut crossings = 0;
+        for rank in &self.ranks {
+            // the lower ends of the edges, by their upper ends from left to right
+            let mut ends = vec![];
+            for &node in rank {
+                let start = ends.len();
+                ends.extend(self.below[node].iter().map(|&lower| positions[lower]));
+                ends[start..].sort_unstable();
+            }
+            crossings += inversions(&mut ends);
+        }
+        crossings
+    }
+
+    /// Swaps neighboring nodes on each rank while that reduces crossings.
+    pub fn transpose(&mut self) {
+        let mut positions = self.positions();
+        for _ in 0..MAX_TRANSPOSE_PASSES {
+            let mut improved = false;
+            for rank in 0..self.ranks.len() {
+                for i in 1..self.ranks[rank].len() {
+                    let (v, w) = (self.ranks[rank][i - 1], self.ranks[rank][i]);
+                    if self.pair_crossings(v, w, &positions) > self.pair_crossings(w, v, &positions)
+                    {
+                        self.ranks[rank].swap(i - 1, i);
+                        positions[v] = i;
+                        positions[w] = i - 1;
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// The crossings between the edges of `v` and those of `w`, with `v` left of `w`.
+    fn pair_crossings(&self, v: usize, w: usize, positions: &[usize]) -> usize {
+        let mut crossings = 0;
+        for ends in &[&self.above, &self.below] {
+            for &a in &ends[v] {
+                crossings += ends[w]
+                    .iter()
+                    .filter(|&&b| positions[a] > positions[b])
+                    .count();
+            }
+        }
+        crossings
+    }
+}
+
+/// The number of pairs out of order in `values`, which are sorted along the way.
+fn inversions(values: &mut [usize]) -> usize {
+    if values.len() < 2 {
+        return 0;
+    }
+    let middle = values.len() / 2;
+    let mut count = inversions(&mut values[..middle]) + inversions(&mut values[middle..]);
+    let mut merged = Vec::with_capacity(values.len());
+    let (mut i, mut j) = (0, middle);
+    while i < middle && j < values.len() {
+        if values[j] < values[i] {
+            count += middle - i;
+            merged.push(values[j]);
+            j += 1;
+        } else {
+            merged.push(values[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..middle]);
+    merged.extend_from_slice(&values[j..]);
+    values.copy_from_slice(&merged);
+    count
+}
+
+/// Sweeps down and up the ranks `iterations` times, sorting each rank by the `value` of its nodes
+/// from the sorted positions of their neighbors on the rank just swept, and keeps the order with
+/// the fewest crossings. Nodes without neighbors there keep their place.
+fn sweep(
+    graph: &mut LayeredGraph,
+    iterations: usize,
+    transpose: bool,
+    value: fn(&[f64]) -> Option<f64>,
+) {
+    let mut best = graph.ranks.clone();
+    let mut fewest = graph.crossings();
+    for iteration in 0..iterations {
+        if fewest == 0 {
+            break;
+        }
+        let downwards = iteration % 2 == 0;
+        let mut positions = graph.positions();
+        let order = if downwards {
+            (1..graph.ranks.len()).collect::<Vec<_>>()
+        } else {
+            (0..graph.ranks.len().saturating_sub(1)).rev().collect()
+        };
+        for rank in order {
+            let values = graph.ranks[rank]
+                .iter()
+                .map(|&node| {
+                    let neighbors = if downwards {
+                        &graph.above[node]
+                    } else {
+                        &graph.below[node]
+                    };
+                    let mut neighbors = neighbors
+                        .iter()
+                        .map(|&neighbor| positions[neighbor] as f64)
+                        .collect::<Vec<_>>();
+                    neighbors.sort_by(f64::total_cmp);
+                    value(&neighbors)
+                })
+                .collect::<Vec<_>>();
+            let mut sorted = graph.ranks[rank]
+                .iter()
+                .zip(&values)
+                .filter_map(|(&node, value)| value.map(|value| (value, node)))
+                .collect::<Vec<_>>();
+            sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let mut sorted = sorted.into_iter();
+            for (slot, value) in graph.ranks[rank].iter_mut().zip(&values) {
+                if value.is_some() {
+                    *slot = sorted.next().unwrap().1;
+                }
+            }
+            for (position, &node) in graph.ranks[rank].iter().enumerate() {
+                positions[node] = position;
+            }
+        }
+        if transpose {
+            graph.transpose();
+        }
+        let crossings = graph.crossings();
+        if crossings < fewest {
+            fewest = crossings;
+            best = graph.ranks.clone();
+        }
+    }
+    graph.ranks = best;
+}
+
+/// Orders ranks by the weighted median of the positions of the neighbors of each node, as
+/// Graphviz's `dot` does, swapping neighboring nodes after each sweep if `transpose` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Median {
+    pub iterations: usize,
+    pub transpose: bool,
+}
+
+impl Default for Median {
+    fn default() -> Self {
+        Median {
+            iterations: 24,
+            transpose: true,
+        }
+    }
+}
+
+impl Ordering for Median {
+    fn order(&self, graph: &mut LayeredGraph) {
+        sweep(graph, self.iterations, self.transpose, |positions| {
+            let m = positions.len() / 2;
+            match positions.len() {
+                0 => None,
+                2 => Some((positions[0] + positions[1]) / 2.0),
+                n if n % 2 == 1 => Some(positions[m]),
+                n => {
+                    // between the middle two, closer to the side where neighbors are packed
+                    let left = positions[m - 1] - positions[0];
+                    let right = positions[n - 1] - positions[m];
+                    if left + right == 0.0 {
+                        Some((positions[m - 1] + positions[m]) / 2.0)
+                    } else {
+                        Some((positions[m - 1] * right + positions[m] * left) / (left + right))
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Orders ranks by the mean position of the neighbors of each node. By default it sweeps fewer
+/// times than [`Median`] and doesn't swap neighboring nodes, trading crossings for speed on wide
+/// graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Barycenter {
+    pub iterations: usize,
+    pub transpose: bool,
+}
+
+impl Default for Barycenter {
+    fn default() -> Self {
+        Barycenter {
+            iterations: 8,
+            transpose: false,
+        }
+    }
+}
+
+impl Ordering for Barycenter {
+    fn order(&self, graph: &mut LayeredGraph) {
+        sweep(graph, self.iterations, self.transpose, |positions| {
+            if positions.is_empty() {
+                None
+            } else {
+                Some(positions.iter().sum::<f64>() / positions.len() as f64)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredOptions {
+    /// Groups of nodes constrained in rank, none by default.
+    pub rank: RankOptions,
+    /// Seed of a pseudo-random initial order of each rank, instead of a breadth-first order.
+    pub seed: Option<u64>,
+    /// The direction of edges, from the `rankdir` attribute.
+    pub direction: RankDir,
+    /// Space in inches between nodes on a rank, from the `nodesep` attribute.
+    pub node_separation: f64,
+    /// Space in inches between successive ranks, from the `ranksep` attribute, the last
+    /// repeating for further ranks. Graphviz only uses the first.
+    pub rank_separation: Vec<f64>,
+    /// Whether ranks are spaced equally, as far apart as the furthest, from `ranksep`.
+    pub equally: bool,
+}
+
+impl Default for LayeredOptions {
+    fn default() -> Self {
+        LayeredOptions {
+            rank: RankOptions::default(),
+            seed: None,
+            direction: RankDir::TopToBottom,
+            node_separation: NODE_SEPARATION,
+            rank_separation: vec![RANK_SEPARATION],
+            equally: false,
+        }
+    }
+}
+
+impl LayeredOptions {
+    /// Options from the attributes of `graph`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut options = LayeredOptions::default();
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::NodeSep(separation) => options.node_separation = *separation,
+                Attribute::RankDir(direction) => options.direction = *direction,
+                Attribute::RankSep(separation) => {
+                    if !separation.values.is_empty() {
+                        options.rank_separation = separation.values.clone();
+                    }
+                    options.equally = separation.equally;
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+/// Ranks the nodes of `graph` and orders them within their ranks with `ordering`.
+pub fn layers(graph: &Graph, options: &LayeredOptions, ordering: &dyn Ordering) -> LayeredGraph {
+    let ranks = rank::rank(graph, &options.rank);
+    let mut layered = LayeredGraph::new(graph, &ranks);
+    layered.initial_order(options.seed);
+    ordering.order(&mut layered);
+    layered
+}
+
+/// Positions in order, at least `gaps` apart, as close as they can be to their `targets`, in
+/// the least squares sense weighted by the second of each target. The gaps turn into an order on
+/// positions less the gaps before them, which is solved by pooling adjacent violators.
+fn pack(targets: &[(f64, f64)], gaps: &[f64]) -> Vec<f64> {
+    let mut offsets = vec![0.0];
+    for gap in gaps {
+        offsets.push(offsets[offsets.len() - 1] + gap);
+    }
+    // runs of positions moved together, with their weighted sum of targets, weight and length
+    let mut blocks: Vec<(f64, f64, usize)> = vec![];
+    for (&(target, weight), offset) in targets.iter().zip(&offsets) {
+        let mut block = (weight * (target - offset), weight, 1);
+        while let Some(&last) = blocks.last() {
+            if last.0 / last.1 <= block.0 / block.1 {
+                break;
+            }
+            blocks.pop();
+            block = (last.0 + block.0, last.1 + block.1, last.2 + block.2);
+        }
+        blocks.push(block);
+    }
+    let mut positions = Vec::with_capacity(targets.len());
+    for (sum, weight, length) in blocks {
+        for _ in 0..length {
+            positions.push(sum / weight + offsets[positions.len()]);
+        }
+    }
+    positions
+}
+
+/// Positions of the nodes across their ranks. Nodes start packed side by side and are then
+/// repeatedly pulled towards their neighbors on the ranks above and below, keeping their order and
+/// their distance. Edges between virtual nodes pull hardest, to keep long edges straight.
+fn place_across(layered: &LayeredGraph, sizes: &[f64], separation: f64) -> Vec<f64> {
+    let mut positions = vec![0.0; layered.len()];
+    for rank in &layered.ranks {
+        let width = rank.iter().map(|&node| 2.0 * sizes[node]).sum::<f64>()
+            + separation * rank.len().saturating_sub(1) as f64;
+        let mut x = -width / 2.0;
+        for &node in rank {
+            x += sizes[node];
+            positions[node] = x;
+            x += sizes[node] + separation;
+        }
+    }
+    let weight = |a: usize, b: usize| match (layered.is_virtual(a), layered.is_virtual(b)) {
+        (false, false) => 1.0,
+        (true, true) => 8.0,
+        _ => 2.0,
+    };
+    let ranks = layered.ranks.len();
+    for pass in 0..=PLACEMENT_PASSES {
+        // sweeps alternately down and up, then once more towards both sides
+        let (order, above, below) = if pass == PLACEMENT_PASSES {
+            ((0..ranks).collect::<Vec<_>>(), true, true)
+        } else if pass % 2 == 0 {
+            ((1..ranks).collect(), true, false)
+        } else {
+            ((0..ranks.saturating_sub(1)).rev().collect(), false, true)
+        };
+        for rank in order {
+            let nodes = &layered.ranks[rank];
+            let targets = nodes
+                .iter()
+                .map(|&node| {
+                    let neighbors = layered.above[node]
+                        .iter()
+                        .filter(|_| above)
+                        .chain(layered.below[node].iter().filter(|_| below));
+                    let (mut sum, mut total) = (0.0, 0.0);
+                    for &neighbor in neighbors {
+                        sum += weight(node, neighbor) * positions[neighbor];
+                        total += weight(node, neighbor);
+                    }
+                    if total == 0.0 {
+                        // nodes without neighbors stay put unless pushed
+                        (positions[node], 0.001)
+                    } else {
+                        (sum / total, total)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let gaps = nodes
+                .windows(2)
+                .map(|pair| sizes[pair[0]] + sizes[pair[1]] + separation)
+                .collect::<Vec<_>>();
+            for (&node, position) in nodes.iter().zip(pack(&targets, &gaps)) {
+                positions[node] = position;
+            }
+        }
+    }
+    positions
+}
+
+/// Positions of the ranks along the direction of edges, from the first rank at 0.
+fn place_along(layered: &LayeredGraph, sizes: &[f64], options: &LayeredOptions) -> Vec<f64> {
+    let extents = layered
+        .ranks
+        .iter()
+        .map(|rank| rank.iter().map(|&node| sizes[node]).fold(0.0, f64::max))
+        .collect::<Vec<_>>();
+    let separation = |rank: usize| {
+        let values = &options.rank_separation;
+        let value = values
+            .get(rank.min(values.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or(RANK_SEPARATION);
+        value.max(MIN_SEPARATION) * POINTS_PER_INCH
+    };
+    let mut gaps = extents
+        .windows(2)
+        .enumerate()
+        .map(|(rank, pair)| pair[0] + separation(rank) + pair[1])
+        .collect::<Vec<_>>();
+    if options.equally {
+        let widest = gaps.iter().copied().fold(0.0, f64::max);
+        gaps.iter_mut().for_each(|gap| *gap = widest);
+    }
+    let mut positions = vec![0.0];
+    for gap in gaps {
+        positions.push(positions[positions.len() - 1] + gap);
+    }
+    positions
+}
+
+/// Positions of the nodes of `layered`, including virtual nodes, with the ranks going in the
+/// direction of [`LayeredOptions::direction`].
+fn coordinates(graph: &Graph, layered: &LayeredGraph, options: &LayeredOptions) -> Vec<(f64, f64)> {
+    let vertical = matches!(
+        options.direction,
+        RankDir::TopToBottom | RankDir::BottomToTop
+    );
+    // half the size of each node across and along the ranks
+    let (mut across, mut along): (Vec<_>, Vec<_>) = half_sizes(graph)
+        .into_iter()
+        .map(|(dx, dy)| if vertical { (dx, dy) } else { (dy, dx) })
+        .unzip();
+    across.resize(layered.len(), 0.0);
+    along.resize(layered.len(), 0.0);
+    let separation = options.node_separation.max(MIN_SEPARATION) * POINTS_PER_INCH;
+    let across = place_across(layered, &across, separation);
+    let depths = place_along(layered, &along, options);
+    across
+        .into_iter()
+        .enumerate()
+        .map(|(node, u)| {
+            let v = depths[layered.rank(node)];
+            Transform::from_rank_dir(options.direction).apply((u, -v))
+        })
+        .collect()
+}
+
+/// Lays out `graph`, ordering ranks with `ordering`, and sets the `pos` of its nodes and its
+/// `bb`.
+pub fn layout(graph: &mut Graph, options: &LayeredOptions, ordering: &dyn Ordering) {
+    let layered = layers(graph, options, ordering);
+    let positions = coordinates(graph, &layered, options);
+    let waypoints = layered
+        .chains
+        .iter()
+        .map(|chain| chain.iter().map(|&node| positions[node]).collect())
+        .collect::<Vec<_>>();
+    write_positions(
+        graph,
+        &positions[..graph.nodes().len()],
+        &waypoints,
+        EdgeRespresentation::Spline,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn layers() {
+        let graph = Graph::from_ir(
+            &parse_graph("digraph { a -> d b -> c a -> c b -> d a -> e -> f b -> f }").unwrap(),
+        );
+        let index = |id| graph.node_index(id).unwrap();
+        let mut layered = LayeredGraph::new(&graph, &[0, 1, 0, 1, 1, 2]);
+        // the edge from `b` to `f` gets a virtual node
+        assert_eq!(layered.len(), 7);
+        assert!(layered.is_virtual(6));
+        assert_eq!(layered.above(6), &[index("b")]);
+        layered.ranks = vec![
+            vec![index("a"), index("b")],
+            vec![index("d"), index("c"), index("e"), 6],
+            vec![index("f")],
+        ];
+        assert_eq!(layered.crossings(), 3);
+
+        let tree = "digraph { r -> a r -> b a -> a1 a -> a2 b -> b1 b -> b2 a2 -> x b1 -> x }";
+        let graph = Graph::from_ir(&parse_graph(tree).unwrap());
+        let orderings: [&dyn Ordering; 2] = [&Median::default(), &Barycenter::default()];
+        for ordering in &orderings {
+            for seed in 1..10 {
+                let options = LayeredOptions {
+                    seed: Some(seed),
+                    ..LayeredOptions::default()
+                };
+                assert_eq!(super::layers(&graph, &options, *ordering).crossings(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn layered() {
+        let positions = |input: &str| {
+            let mut graph = Graph::from_ir(&parse_graph(input).unwrap());
+            let options = LayeredOptions::from_graph(&graph);
+            layout(&mut graph, &options, &Median::default());
+            graph
+                .nodes()
+                .iter()
+                .map(|node| {
+                    let point = node.position().unwrap();
+                    (point.x, point.y)
+                })
+                .collect::<Vec<_>>()
+        };
+        // ranks go down by the separation plus half the height of the nodes on either side, and
+        // parents are centered over their children
+        assert_eq!(
+            positions("digraph { a -> b a -> c a [height=1] }"),
+            vec![(63.0, 108.0), (27.0, 18.0), (99.0, 18.0)]
+        );
+        assert_eq!(
+            positions("digraph { nodesep=1 rankdir=LR a -> b a -> c }"),
+            vec![(27.0, 72.0), (117.0, 126.0), (117.0, 18.0)]
+        );
+        assert_eq!(
+            positions("digraph { rankdir=BT a -> b }"),
+            vec![(27.0, 18.0), (27.0, 90.0)]
+        );
+        assert_eq!(
+            positions("digraph { rankdir=RL ranksep=1 a -> b }"),
+            vec![(153.0, 18.0), (27.0, 18.0)]
+        );
+        // listed separations, then spaced as far apart as the furthest ranks
+        let chain = "a -> b -> c -> d b [height=1]";
+        let depths = |ranksep: &str| {
+            let input = format!("digraph {{ ranksep=\"{}\" {} }}", ranksep, chain);
+            positions(&input)
+                .iter()
+                .map(|&(_, y)| y)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(depths("0.5:1"), vec![342.0, 252.0, 126.0, 18.0]);
+        assert_eq!(depths("0.5:1 equally"), vec![396.0, 270.0, 144.0, 18.0]);
+    }
+}
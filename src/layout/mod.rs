@@ -0,0 +1,563 @@
+//! Layout engines, placing the nodes of a [`Graph`] and writing their positions back as `pos`
+//! attributes, in points with the y axis pointing up, along with the bounding box of the drawing
+//! as the graph's `bb`, starting at the origin.
+//!
+//! | engine  | layout                                                              |
+//! |---------|---------------------------------------------------------------------|
+//! | `dot`   | layered, with edges pointing down between ranks; see [`layered`]    |
+//! | `neato` | force-directed, by stress majorization; see [`force`]               |
+//! | `fdp`   | force-directed, by a spring embedder; see [`force`]                 |
+//! | `circo` | circular, with biconnected components on circles; see [`circular`]  |
+//! | `twopi` | radial, with nodes on circles around a root; see [`radial`]         |
+//!
+//! The engine is chosen by the caller, or else by the graph's `layout` attribute, as with
+//! Graphviz's `-K` option. Engines are also available as [`LayoutEngine`]s, to be chosen and
+//! passed around like any other value: [`Native`] runs the engines above, and
+//! [`graphviz::Graphviz`] an installed Graphviz.
+//!
+//! The force-directed engines keep nodes pinned by a `pos` ending in `!`, or by `pin=true`, where
+//! they are, and then leave the drawing where the pins put it rather than at the origin. To
+//! update a drawing after a small change to the graph without reshuffling it, lay it out again
+//! with [`relayout`].
+//!
+//! A graph with a `pack` attribute, or a `packmode`, has its connected components laid out
+//! separately and then packed together as [`pack`](crate::algorithm::pack) packs graphs, unless
+//! it has pinned nodes or is laid out incrementally.
+//!
+//! Before that, nodes too small for their labels are made bigger, setting their `width` and
+//! `height`, as measured in their fonts (see [`font`](crate::font)), unless their `fixedsize`
+//! says otherwise.
+//!
+//! Edges are then routed between the nodes as the `splines` attribute asks, as splines for `dot`
+//! and straight lines for the other engines by default; see [`routing`]. The ranking phase of
+//! layered layouts is also available on its own, in [`rank`], and laid out graphs can be turned
+//! to another `rankdir` with [`transform`].
+
+use thiserror::Error;
+
+use crate::{
+    algorithm::{
+        components::connected_components,
+        pack::{arrange, for_each_point, PackOptions},
+    },
+    attribute::{
+        Attribute, EdgeRespresentation, FixedSize, Pack, Point, Position, Rectangle, Shape,
+    },
+    font::{self, LINE_HEIGHT},
+    model::{set_attribute, Graph, Node, NodeIndex, POINTS_PER_INCH},
+    render::{label::Structure, DEFAULT_FONT, DEFAULT_FONT_SIZE},
+};
+
+pub mod circular;
+pub mod force;
+pub mod graphviz;
+pub mod layered;
+pub mod overlap;
+pub mod radial;
+pub mod rank;
+pub mod routing;
+pub mod transform;
+
+#[derive(Debug, Error)]
+pub enum LayoutError {
+    #[error("unknown layout engine `{0}`")]
+    UnknownEngine(String),
+    #[error("failed to run `{program}`: {source}")]
+    Run {
+        program: String,
+        source: std::io::Error,
+    },
+    #[error("`{program}` failed: {message}")]
+    Failed { program: String, message: String },
+    #[error("unexpected output from `{program}`: {message}")]
+    Output { program: String, message: String },
+}
+
+/// A way of laying out graphs, setting the `pos` of their nodes and edges and their `bb`.
+pub trait LayoutEngine {
+    fn layout(&self, graph: &mut Graph) -> Result<(), LayoutError>;
+}
+
+/// The engines of this crate, as [`layout`] and [`relayout`] run them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Native {
+    /// One of the [`ENGINES`], or else the one named by the graph's `layout` attribute.
+    pub engine: Option<String>,
+    /// Whether to update the existing layout, as [`relayout`].
+    pub incremental: bool,
+}
+
+impl LayoutEngine for Native {
+    fn layout(&self, graph: &mut Graph) -> Result<(), LayoutError> {
+        run(graph, self.engine.as_deref(), self.incremental)
+    }
+}
+
+/// Names of the engines [`layout`] knows.
+pub const ENGINES: &[&str] = &["dot", "neato", "fdp", "circo", "twopi"];
+
+/// The engine used when neither the caller nor the graph chooses one.
+pub const DEFAULT_ENGINE: &str = "neato";
+
+/// Space left around the labels of nodes without a `margin`, in inches.
+pub const DEFAULT_MARGIN: (f64, f64) = (0.11, 0.055);
+
+/// Lays out `graph` with `engine`, or else the engine named by its `layout` attribute.
+pub fn layout(graph: &mut Graph, engine: Option<&str>) -> Result<(), LayoutError> {
+    run(graph, engine, false)
+}
+
+/// Lays out `graph` like [`layout`], but as an update of the positions its nodes already have, so
+/// that adding a node or an edge to a laid out graph only moves the nodes around it.
+///
+/// The force-directed engines start from the existing positions and hold nodes close to them,
+/// placing new nodes next to their neighbors. The other engines lay the graph out afresh, then
+/// move the drawing to line up with the existing positions as well as it can.
+pub fn relayout(graph: &mut Graph, engine: Option<&str>) -> Result<(), LayoutError> {
+    run(graph, engine, true)
+}
+
+fn run(graph: &mut Graph, engine: Option<&str>, incremental: bool) -> Result<(), LayoutError> {
+    let engine = engine
+        .map(str::to_owned)
+        .or_else(|| {
+            graph
+                .attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::Layout(engine) => Some(engine.clone()),
+                    _ => None,
+                })
+        })
+        .unwrap_or_else(|| DEFAULT_ENGINE.to_owned());
+    if !ENGINES.contains(&engine.as_str()) {
+        return Err(LayoutError::UnknownEngine(engine));
+    }
+    fit_labels(graph);
+    let pinned = graph.nodes().iter().any(Node::is_pinned);
+    if let Some(options) = packing(graph).filter(|_| !incremental && !pinned) {
+        let components = connected_components(graph);
+        if components.len() > 1 {
+            pack_components(graph, &engine, &components, &options);
+            return Ok(());
+        }
+    }
+    let previous = graph
+        .nodes()
+        .iter()
+        .map(|node| node.position().map(|point| (point.x, point.y)))
+        .collect::<Vec<_>>();
+    dispatch(graph, &engine, incremental);
+    if incremental && !matches!(engine.as_str(), "neato" | "fdp") {
+        align(graph, &previous);
+    }
+    Ok(())
+}
+
+/// Makes the nodes of `graph` big enough for their labels, with their `margin` around them: boxes
+/// fit the label, and other shapes the box around it. Records fit their fields, which have
+/// margins of their own.
+fn fit_labels(graph: &mut Graph) {
+    let font_path = font::font_path(graph);
+    let name = graph.id.clone().unwrap_or_default();
+    for index in 0..graph.nodes().len() {
+        let node = &graph.nodes()[index];
+        let (mut label, mut font_name, mut size) =
+            ("\\N".to_owned(), DEFAULT_FONT, DEFAULT_FONT_SIZE);
+        let (mut margin, mut shape, mut fixed) = (DEFAULT_MARGIN, Shape::Ellipse, false);
+        for attribute in &node.attributes {
+            match attribute {
+                Attribute::Label(value) => label = value.clone(),
+                Attribute::FontName(value) => font_name = value,
+                Attribute::FontSize(value) => size = *value,
+                Attribute::Margin(value) => margin = (value.x, value.y),
+                Attribute::Shape(value) => shape = value.clone(),
+                Attribute::FixedSize(value) => fixed = *value != FixedSize::False,
+                _ => {}
+            }
+        }
+        if fixed || shape == Shape::Point {
+            continue;
+        }
+        let (width, height) = match Structure::of(node, graph, &font_path) {
+            Some(structure) if structure.is_record() => {
+                let (width, height) = structure.size();
+                (width / POINTS_PER_INCH, height / POINTS_PER_INCH)
+            }
+            Some(structure) => {
+                let (width, height) = structure.size();
+                (
+                    width / POINTS_PER_INCH + 2.0 * margin.0,
+                    height / POINTS_PER_INCH + 2.0 * margin.1,
+                )
+            }
+            None => {
+                let label = label.replace("\\N", &node.id).replace("\\G", &name);
+                let lines = label
+                    .split("\\n")
+                    .flat_map(|line| line.split('\n'))
+                    .collect::<Vec<_>>();
+                let font = font::find(font_name, &font_path);
+                let width = lines.iter().map(|line| font.width(line, size));
+                (
+                    width.fold(0.0, f64::max) / POINTS_PER_INCH + 2.0 * margin.0,
+                    lines.len() as f64 * LINE_HEIGHT * size / POINTS_PER_INCH + 2.0 * margin.1,
+                )
+            }
+        };
+        let boxed = matches!(
+            shape,
+            Shape::Box
+                | Shape::Rect
+                | Shape::Rectangle
+                | Shape::Square
+                | Shape::Plaintext
+                | Shape::Plain
+                | Shape::None
+                | Shape::Underline
+                | Shape::Note
+                | Shape::Tab
+                | Shape::Folder
+                | Shape::Box3d
+                | Shape::Component
+                | Shape::Record
+                | Shape::MRecord
+        );
+        let (mut width, mut height) = if boxed {
+            (width, height)
+        } else {
+            (
+                width * std::f64::consts::SQRT_2,
+                height * std::f64::consts::SQRT_2,
+            )
+        };
+        if matches!(shape, Shape::Square | Shape::Circle | Shape::DoubleCircle) {
+            width = width.max(height);
+            height = width;
+        }
+        let current = node.size();
+        let node = graph.node_mut(index);
+        if width > current.0 {
+            set_attribute(&mut node.attributes, Attribute::Width(width));
+        }
+        if height > current.1 {
+            set_attribute(&mut node.attributes, Attribute::Height(height));
+        }
+    }
+}
+
+/// Lays out `graph` with `engine`, one of the [`ENGINES`].
+fn dispatch(graph: &mut Graph, engine: &str, incremental: bool) {
+    let force = |graph: &Graph, model| force::ForceOptions {
+        incremental,
+        ..force::ForceOptions::from_graph(graph, model)
+    };
+    match engine {
+        "dot" => layered::layout(
+            graph,
+            &layered::LayeredOptions::from_graph(graph),
+            &layered::Median::default(),
+        ),
+        "neato" => force::layout(graph, &force(graph, force::Model::Stress)),
+        "fdp" => force::layout(graph, &force(graph, force::Model::Spring)),
+        "circo" => circular::layout(graph, &circular::CircularOptions::from_graph(graph)),
+        "twopi" => radial::layout(graph, &radial::RadialOptions::from_graph(graph)),
+        _ => unreachable!("unknown engine `{}`", engine),
+    }
+}
+
+/// How to pack the connected components of `graph`, if its `pack` attribute asks for it, or its
+/// `packmode` attribute without a `pack`.
+fn packing(graph: &Graph) -> Option<PackOptions> {
+    let pack = graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Pack(pack) => Some(pack),
+            _ => None,
+        });
+    let mode = graph
+        .attributes
+        .iter()
+        .any(|attribute| matches!(attribute, Attribute::PackMode(_)));
+    match pack {
+        Some(Pack::True) => Some(PackOptions::from_graph(graph)),
+        Some(Pack::Value(margin)) if *margin >= 0 => Some(PackOptions::from_graph(graph)),
+        None if mode => Some(PackOptions::from_graph(graph)),
+        _ => None,
+    }
+}
+
+/// Lays out each of the `components` of `graph` on its own with `engine`, then packs their
+/// drawings together following `options`, as [`pack`](crate::algorithm::pack) packs graphs.
+fn pack_components(
+    graph: &mut Graph,
+    engine: &str,
+    components: &[Vec<NodeIndex>],
+    options: &PackOptions,
+) {
+    let mut parts = vec![];
+    let mut sizes = vec![];
+    for component in components {
+        let mut nodes = component.clone();
+        nodes.sort_unstable();
+        let mut part = graph.induced_subgraph(nodes.iter().copied());
+        dispatch(&mut part, engine, false);
+        let bb = part
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Bb(bb) => Some(bb.clone()),
+                _ => None,
+            })
+            .expect("engines set `bb`");
+        sizes.push((
+            bb.upper_right.x - bb.lower_left.x,
+            bb.upper_right.y - bb.lower_left.y,
+        ));
+        parts.push((nodes, part, bb.lower_left));
+    }
+    let (corners, (width, height)) = arrange(&sizes, &vec![0; sizes.len()], options);
+    let mut component_of = vec![0; graph.nodes().len()];
+    for (index, (nodes, _, _)) in parts.iter().enumerate() {
+        for &node in nodes {
+            component_of[node] = index;
+        }
+    }
+    let mut edges = vec![vec![]; parts.len()];
+    for (index, edge) in graph.edges().iter().enumerate() {
+        edges[component_of[edge.tail]].push(index);
+    }
+    for ((nodes, part, lower_left), (corner, edges)) in
+        parts.into_iter().zip(corners.into_iter().zip(edges))
+    {
+        let (dx, dy) = (corner.0 - lower_left.x, corner.1 - lower_left.y);
+        let shift = |point: &mut Point| {
+            point.x = round(point.x + dx);
+            point.y = round(point.y + dy);
+        };
+        for (&node, laid_out) in nodes.iter().zip(part.nodes()) {
+            let attributes = &mut graph.node_mut(node).attributes;
+            *attributes = laid_out.attributes.clone();
+            for_each_point(attributes, shift);
+        }
+        for (&edge, laid_out) in edges.iter().zip(part.edges()) {
+            let attributes = &mut graph.edge_mut(edge).attributes;
+            *attributes = laid_out.attributes.clone();
+            for_each_point(attributes, shift);
+        }
+    }
+    let bb = Rectangle {
+        lower_left: Point { x: 0.0, y: 0.0 },
+        upper_right: Point {
+            x: round(width),
+            y: round(height),
+        },
+    };
+    set_attribute(&mut graph.attributes, Attribute::Bb(Box::new(bb)));
+}
+
+/// Moves the drawing of `graph` by the mean offset of its nodes from their `previous` positions.
+fn align(graph: &mut Graph, previous: &[Option<(f64, f64)>]) {
+    let (mut dx, mut dy, mut count) = (0.0, 0.0, 0.0);
+    for (node, previous) in graph.nodes().iter().zip(previous) {
+        if let (Some(point), Some((x, y))) = (node.position(), previous) {
+            dx += x - point.x;
+            dy += y - point.y;
+            count += 1.0;
+        }
+    }
+    if count == 0.0 {
+        return;
+    }
+    let (dx, dy) = (dx / count, dy / count);
+    let shift = |point: &mut Point| {
+        point.x = round(point.x + dx);
+        point.y = round(point.y + dy);
+    };
+    for_each_point(&mut graph.attributes, shift);
+    for node in 0..graph.nodes().len() {
+        for_each_point(&mut graph.node_mut(node).attributes, shift);
+    }
+    for edge in 0..graph.edges().len() {
+        for_each_point(&mut graph.edge_mut(edge).attributes, shift);
+    }
+}
+
+/// A xorshift generator, for reproducible layouts.
+pub(crate) struct Random(pub(crate) u64);
+
+impl Random {
+    /// A number in `[0, 1)`.
+    pub(crate) fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Half the width and height of each node in points.
+pub(crate) fn half_sizes(graph: &Graph) -> Vec<(f64, f64)> {
+    graph
+        .nodes()
+        .iter()
+        .map(|node| {
+            let (width, height) = node.size();
+            (
+                width * POINTS_PER_INCH / 2.0,
+                height * POINTS_PER_INCH / 2.0,
+            )
+        })
+        .collect()
+}
+
+/// Places the separately laid out `components` of a graph side by side, left to right in order,
+/// their bottoms aligned and separated by twice the [`overlap::SEPARATION`].
+pub(crate) fn arrange_components(
+    positions: &mut [(f64, f64)],
+    sizes: &[(f64, f64)],
+    components: &[Vec<NodeIndex>],
+) {
+    let mut left = 0.0;
+    for component in components {
+        let (mut min_x, mut min_y, mut max_x) = (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+        for &node in component {
+            min_x = min_x.min(positions[node].0 - sizes[node].0);
+            min_y = min_y.min(positions[node].1 - sizes[node].1);
+            max_x = max_x.max(positions[node].0 + sizes[node].0);
+        }
+        for &node in component {
+            positions[node].0 += left - min_x;
+            positions[node].1 -= min_y;
+        }
+        left += max_x - min_x + 2.0 * overlap::SEPARATION;
+    }
+}
+
+/// Rounds a coordinate to hundredths of a point, as Graphviz writes them.
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Sets the `pos` of each node from `positions` and routes edges between them, through the
+/// `waypoints` of each edge if any, following the `splines` attribute or else `default`. The
+/// drawing is moved to start at the origin if `translate`, and its bounding box set as the
+/// graph's `bb`. Nodes left at the position they were pinned to stay pinned.
+pub(crate) fn write_positions(
+    graph: &mut Graph,
+    positions: &[(f64, f64)],
+    waypoints: &[Vec<(f64, f64)>],
+    default: EdgeRespresentation,
+    translate: bool,
+) {
+    let mode = routing::mode(graph, default);
+    let routes = routing::routes(graph, positions, waypoints, &mode);
+    let sizes = half_sizes(graph);
+    let (mut left, mut bottom) = (f64::INFINITY, f64::INFINITY);
+    let (mut right, mut top) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    let points = routes.iter().flatten().flatten();
+    for (&(x, y), &(dx, dy)) in positions
+        .iter()
+        .zip(&sizes)
+        .chain(points.map(|point| (point, &(0.0, 0.0))))
+    {
+        left = left.min(x - dx);
+        bottom = bottom.min(y - dy);
+        right = right.max(x + dx);
+        top = top.max(y + dy);
+    }
+    if positions.is_empty() {
+        left = 0.0;
+        bottom = 0.0;
+        right = 0.0;
+        top = 0.0;
+    }
+    let origin = if translate {
+        (left, bottom)
+    } else {
+        (0.0, 0.0)
+    };
+    let point = |(x, y): (f64, f64)| Point {
+        x: round(x - origin.0),
+        y: round(y - origin.1),
+    };
+    for (node, &position) in positions.iter().enumerate() {
+        let attributes = &mut graph.node_mut(node).attributes;
+        let point = point(position);
+        // pins the engine kept
+        let kept = attributes.iter().any(|attribute| match attribute {
+            Attribute::Pos(Position::Pinned(pin)) => {
+                round(pin.x) == point.x && round(pin.y) == point.y
+            }
+            _ => false,
+        });
+        let pos = if kept {
+            Position::Pinned(point)
+        } else {
+            Position::Point(point)
+        };
+        set_attribute(attributes, Attribute::Pos(pos));
+    }
+    for (edge, route) in routes.into_iter().enumerate() {
+        let attributes = &mut graph.edge_mut(edge).attributes;
+        match route {
+            Some(route) => {
+                let pos = Position::Spline(route.into_iter().map(point).collect());
+                set_attribute(attributes, Attribute::Pos(pos));
+            }
+            None => attributes.retain(|attribute| !matches!(attribute, Attribute::Pos(_))),
+        }
+    }
+    let bb = Rectangle {
+        lower_left: point((left, bottom)),
+        upper_right: point((right, top)),
+    };
+    set_attribute(&mut graph.attributes, Attribute::Bb(Box::new(bb)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn fit_labels() {
+        let source = r#"graph {
+            a [label="Wide\nlabel"]
+            b [shape=box label="Wide" fontsize=28]
+            c [fixedsize=true label="Wide"]
+            d
+        }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout(&mut graph, Some("neato")).unwrap();
+        let size = |id: &str| graph.node(id).unwrap().size();
+        let wide = font::find(DEFAULT_FONT, &[]).width("Wide", 14.0) / 72.0;
+        let a = size("a");
+        assert!((a.0 - (wide + 0.22) * 2f64.sqrt()).abs() < 1e-9);
+        assert!((a.1 - (2.0 * 1.2 * 14.0 / 72.0 + 0.11) * 2f64.sqrt()).abs() < 1e-9);
+        assert!((size("b").0 - (2.0 * wide + 0.22)).abs() < 1e-9);
+        assert!((size("b").1 - (1.2 * 28.0 / 72.0 + 0.11)).abs() < 1e-9);
+        assert_eq!(size("c"), (0.75, 0.5));
+        assert_eq!(size("d"), (0.75, 0.5));
+    }
+
+    #[test]
+    fn pack_components() {
+        let source = "digraph { pack=10 a -> b c -> d -> e }";
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout(&mut graph, Some("dot")).unwrap();
+        let x = |id: &str| graph.node(id).unwrap().position().unwrap().x;
+        let y = |id: &str| graph.node(id).unwrap().position().unwrap().y;
+        // the components sit side by side, with their nodes 54 points wide and margins around
+        assert_eq!(x("a"), x("b"));
+        assert_eq!(x("c") - x("a"), 54.0 + 2.0 * 10.0);
+        assert_eq!((y("a"), y("b")), (y("c") - 36.0, y("d") - 36.0));
+        assert!(graph
+            .attributes
+            .contains(&Attribute::Bb(Box::new(Rectangle {
+                lower_left: Point { x: 0.0, y: 0.0 },
+                upper_right: Point { x: 148.0, y: 200.0 },
+            }))));
+    }
+}
@@ -0,0 +1,338 @@
+//! Removal of overlaps between nodes once they are placed, following the `overlap` attribute.
+//!
+//! Besides scaling and pushing overlapping nodes apart, two modes keep the shape of the layout
+//! better, each finished by pushing apart whatever overlaps remain:
+//!
+//! - [`Overlap::Prism`] follows Graphviz's `prism`, with the pairs of nodes close to each other
+//!   standing in for the edges of a proximity graph: the distance wanted between overlapping
+//!   neighbors is grown until they no longer overlap, within a limit, and that of others kept,
+//!   and the layout is fitted to these distances by stress majorization, in rounds.
+//! - [`Overlap::Voronoi`] moves each overlapping node to the center of its Voronoi cell within
+//!   the drawing, in rounds, the cells being approximated on a grid.
+
+use crate::attribute::Overlap;
+
+/// Space left between nodes when removing overlaps, in points, like Graphviz's default `sep`.
+pub(crate) const SEPARATION: f64 = 4.0;
+
+/// Most passes over all pairs of nodes when removing overlaps.
+const MAX_PASSES: usize = 100;
+
+/// Most rounds of `prism` and `voronoi` overlap removal.
+const MAX_ROUNDS: usize = 30;
+
+/// Most a `prism` round grows the distance between two nodes by.
+const MAX_GROWTH: f64 = 1.5;
+
+/// Majorization sweeps in each `prism` round.
+const SWEEPS: usize = 10;
+
+/// Squares along each side of the grid approximating Voronoi cells.
+const GRID: usize = 64;
+
+/// How far the boxes of two nodes overlap along each axis, counting [`SEPARATION`], or `None`
+/// if they don't.
+fn overlap(
+    (a, b): ((f64, f64), (f64, f64)),
+    (size_a, size_b): ((f64, f64), (f64, f64)),
+) -> Option<(f64, f64)> {
+    let x = size_a.0 + size_b.0 + SEPARATION - (a.0 - b.0).abs();
+    let y = size_a.1 + size_b.1 + SEPARATION - (a.1 - b.1).abs();
+    (x > 0.0 && y > 0.0).then_some((x, y))
+}
+
+/// The pairs of overlapping nodes.
+fn overlapping(positions: &[(f64, f64)], sizes: &[(f64, f64)]) -> Vec<(usize, usize)> {
+    let mut pairs = vec![];
+    for a in 0..positions.len() {
+        for b in a + 1..positions.len() {
+            if overlap((positions[a], positions[b]), (sizes[a], sizes[b])).is_some() {
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+/// The factors to scale each axis by to separate `a` and `b`, which are infinite along axes where
+/// the nodes are aligned.
+fn factors(
+    (a, b): ((f64, f64), (f64, f64)),
+    (size_a, size_b): ((f64, f64), (f64, f64)),
+) -> (f64, f64) {
+    (
+        (size_a.0 + size_b.0 + SEPARATION) / (a.0 - b.0).abs(),
+        (size_a.1 + size_b.1 + SEPARATION) / (a.1 - b.1).abs(),
+    )
+}
+
+/// Moves the nodes at `positions`, with the half sizes `sizes`, so they don't overlap. Nodes
+/// marked in `pinned` stay where they are, the others moving around them.
+pub(crate) fn adjust(
+    positions: &mut [(f64, f64)],
+    sizes: &[(f64, f64)],
+    mode: Overlap,
+    pinned: &[bool],
+) {
+    let pinned = |node: usize| pinned.get(node).copied().unwrap_or(false);
+    if positions.is_empty() {
+        return;
+    }
+    match mode {
+        Overlap::Retain => {}
+        Overlap::Scale => {
+            let scale = overlapping(positions, sizes)
+                .into_iter()
+                .map(|(a, b)| {
+                    let (x, y) = factors((positions[a], positions[b]), (sizes[a], sizes[b]));
+                    x.min(y)
+                })
+                .filter(|scale| scale.is_finite())
+                .fold(1.0, f64::max);
+            for (node, position) in positions.iter_mut().enumerate() {
+                if !pinned(node) {
+                    *position = (position.0 * scale, position.1 * scale);
+                }
+            }
+            push_apart(positions, sizes, pinned);
+        }
+        Overlap::ScaleXY => {
+            for _ in 0..MAX_PASSES {
+                let (mut scale_x, mut scale_y) = (1.0, 1.0);
+                let pairs = overlapping(positions, sizes);
+                if pairs.is_empty() {
+                    break;
+                }
+                for (a, b) in pairs {
+                    let (x, y) = factors((positions[a], positions[b]), (sizes[a], sizes[b]));
+                    if x <= y {
+                        scale_x = f64::max(scale_x, x);
+                    } else if y.is_finite() {
+                        scale_y = f64::max(scale_y, y);
+                    }
+                }
+                if scale_x == 1.0 && scale_y == 1.0 {
+                    break;
+                }
+                for (node, position) in positions.iter_mut().enumerate() {
+                    if !pinned(node) {
+                        *position = (position.0 * scale_x, position.1 * scale_y);
+                    }
+                }
+            }
+            push_apart(positions, sizes, pinned);
+        }
+        Overlap::Remove => push_apart(positions, sizes, pinned),
+        Overlap::Prism => {
+            prism(positions, sizes, &pinned);
+            push_apart(positions, sizes, pinned);
+        }
+        Overlap::Voronoi => {
+            voronoi(positions, sizes, &pinned);
+            push_apart(positions, sizes, pinned);
+        }
+    }
+}
+
+/// Moves overlapping nodes apart by growing the distances wanted between close pairs, each
+/// round fitting the layout to them by stress majorization.
+fn prism(positions: &mut [(f64, f64)], sizes: &[(f64, f64)], pinned: &impl Fn(usize) -> bool) {
+    let n = positions.len();
+    for _ in 0..MAX_ROUNDS {
+        if overlapping(positions, sizes).is_empty() {
+            break;
+        }
+        // neighbors within twice their combined size, with the distance wanted between them
+        let mut neighbors = vec![vec![]; n];
+        for a in 0..n {
+            for b in a + 1..n {
+                let pair = (positions[a], positions[b]);
+                let reach = 2.0 * (sizes[a].0 + sizes[b].0).max(sizes[a].1 + sizes[b].1);
+                let distance = (pair.0 .0 - pair.1 .0).hypot(pair.0 .1 - pair.1 .1);
+                if distance > reach {
+                    continue;
+                }
+                let ideal = match overlap(pair, (sizes[a], sizes[b])) {
+                    Some(_) if distance > 0.0 => {
+                        let (x, y) = factors(pair, (sizes[a], sizes[b]));
+                        distance * x.min(y).clamp(1.0, MAX_GROWTH)
+                    }
+                    Some(_) => SEPARATION,
+                    None => distance,
+                };
+                neighbors[a].push((b, ideal));
+                neighbors[b].push((a, ideal));
+            }
+        }
+        for _ in 0..SWEEPS {
+            for i in (0..n).filter(|&i| !pinned(i)) {
+                let (mut x, mut y, mut total) = (0.0, 0.0, 0.0);
+                for &(j, ideal) in &neighbors[i] {
+                    let (dx, dy) = (
+                        positions[i].0 - positions[j].0,
+                        positions[i].1 - positions[j].1,
+                    );
+                    let distance = dx.hypot(dy);
+                    // nodes at the same place are separated in order along the x axis
+                    let (ux, uy) = if distance > 0.0 {
+                        (dx / distance, dy / distance)
+                    } else if i < j {
+                        (-1.0, 0.0)
+                    } else {
+                        (1.0, 0.0)
+                    };
+                    let weight = 1.0 / (ideal * ideal);
+                    x += weight * (positions[j].0 + ideal * ux);
+                    y += weight * (positions[j].1 + ideal * uy);
+                    total += weight;
+                }
+                if total > 0.0 {
+                    positions[i] = (x / total, y / total);
+                }
+            }
+        }
+    }
+}
+
+/// Moves each overlapping node to the centroid of its Voronoi cell within the bounding box of
+/// the drawing, grown by a tenth each round, with cells approximated by the nearest node to the
+/// center of each square of a grid.
+fn voronoi(positions: &mut [(f64, f64)], sizes: &[(f64, f64)], pinned: &impl Fn(usize) -> bool) {
+    let n = positions.len();
+    let (mut left, mut bottom) = (f64::INFINITY, f64::INFINITY);
+    let (mut right, mut top) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for (&(x, y), &(dx, dy)) in positions.iter().zip(sizes) {
+        left = left.min(x - dx);
+        bottom = bottom.min(y - dy);
+        right = right.max(x + dx);
+        top = top.max(y + dy);
+    }
+    for _ in 0..MAX_ROUNDS {
+        let pairs = overlapping(positions, sizes);
+        if pairs.is_empty() {
+            break;
+        }
+        let mut moving = vec![false; n];
+        for (a, b) in pairs {
+            moving[a] = !pinned(a);
+            moving[b] = !pinned(b);
+        }
+        let (grow_x, grow_y) = ((right - left) / 20.0, (top - bottom) / 20.0);
+        left -= grow_x;
+        right += grow_x;
+        bottom -= grow_y;
+        top += grow_y;
+        let (step_x, step_y) = ((right - left) / GRID as f64, (top - bottom) / GRID as f64);
+        let mut centroids = vec![(0.0, 0.0, 0.0); n];
+        for row in 0..GRID {
+            for column in 0..GRID {
+                let point = (
+                    left + (column as f64 + 0.5) * step_x,
+                    bottom + (row as f64 + 0.5) * step_y,
+                );
+                let distance =
+                    |node: usize| (positions[node].0 - point.0).hypot(positions[node].1 - point.1);
+                let nearest = (0..n)
+                    .min_by(|&a, &b| distance(a).total_cmp(&distance(b)))
+                    .unwrap();
+                let centroid = &mut centroids[nearest];
+                centroid.0 += point.0;
+                centroid.1 += point.1;
+                centroid.2 += 1.0;
+            }
+        }
+        for (node, (x, y, count)) in centroids.into_iter().enumerate() {
+            if moving[node] && count > 0.0 {
+                positions[node] = (x / count, y / count);
+            }
+        }
+    }
+}
+
+/// Removes overlaps by repeatedly pushing overlapping pairs apart, each node of a pair moving
+/// half the way along the axis needing the least movement, or all the way if the other is
+/// pinned.
+fn push_apart(positions: &mut [(f64, f64)], sizes: &[(f64, f64)], pinned: impl Fn(usize) -> bool) {
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+        for a in 0..positions.len() {
+            for b in a + 1..positions.len() {
+                let (x, y) = match overlap((positions[a], positions[b]), (sizes[a], sizes[b])) {
+                    Some(overlap) => overlap,
+                    None => continue,
+                };
+                // the share of the movement of each node
+                let (share_a, share_b) = match (pinned(a), pinned(b)) {
+                    (true, true) => continue,
+                    (true, false) => (0.0, 1.0),
+                    (false, true) => (1.0, 0.0),
+                    (false, false) => (0.5, 0.5),
+                };
+                moved = true;
+                // nodes at the same place are separated in order along the x axis
+                if x <= y {
+                    let sign = if positions[a].0 <= positions[b].0 {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    positions[a].0 += sign * x * share_a;
+                    positions[b].0 -= sign * x * share_b;
+                } else {
+                    let sign = if positions[a].1 <= positions[b].1 {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    positions[a].1 += sign * y * share_a;
+                    positions[b].1 -= sign * y * share_b;
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjust() {
+        let sizes = vec![(27.0, 18.0); 3];
+        for &mode in &[
+            Overlap::Remove,
+            Overlap::Scale,
+            Overlap::ScaleXY,
+            Overlap::Prism,
+            Overlap::Voronoi,
+        ] {
+            let mut positions = vec![(0.0, 0.0), (10.0, 5.0), (0.0, 0.0)];
+            super::adjust(&mut positions, &sizes, mode, &[]);
+            assert!(overlapping(&positions, &sizes).is_empty(), "{:?}", mode);
+            let mut positions = vec![(0.0, 0.0), (10.0, 5.0), (0.0, 0.0)];
+            super::adjust(&mut positions, &sizes, mode, &[false, true]);
+            assert!(overlapping(&positions, &sizes).is_empty(), "{:?}", mode);
+            assert_eq!(positions[1], (10.0, 5.0), "{:?}", mode);
+        }
+        let mut positions = vec![(0.0, 0.0), (10.0, 5.0)];
+        super::adjust(&mut positions, &sizes, Overlap::Retain, &[]);
+        assert_eq!(positions, vec![(0.0, 0.0), (10.0, 5.0)]);
+    }
+    #[test]
+    fn prism() {
+        let sizes = vec![(27.0, 18.0); 4];
+        let mut positions = vec![(0.0, 0.0), (20.0, 0.0), (40.0, 0.0), (60.0, 0.0)];
+        super::adjust(&mut positions, &sizes, Overlap::Prism, &[]);
+        assert!(overlapping(&positions, &sizes).is_empty());
+        // the nodes stay in a row, in order
+        assert!(
+            positions.iter().all(|&(_, y)| y.abs() < 1e-9),
+            "{:?}",
+            positions
+        );
+        assert!(positions.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
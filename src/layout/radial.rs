@@ -0,0 +1,239 @@
+//! Radial layouts, like Graphviz's `twopi`.
+//!
+//! The root node sits at the center, and the other nodes on concentric circles by their distance
+//! from it in edges, ignoring edge direction. Each node gets a share of the angle of its parent
+//! in a breadth-first tree from the root, in proportion to the leaves under it, and sits in the
+//! middle of its share, so that subtrees fan out without crossing.
+//!
+//! The root is the node named by the graph's `root` attribute, or else the first node with a
+//! true `root` attribute, or else a central node, with the least distance to the furthest
+//! node. Connected components are laid out separately around their own root, and placed side by
+//! side.
+
+use std::{collections::VecDeque, f64::consts::PI};
+
+use crate::{
+    algorithm::components::connected_components,
+    attribute::{parse_bool, Attribute, EdgeRespresentation, Overlap},
+    model::{Graph, NodeIndex, POINTS_PER_INCH},
+};
+
+use super::{arrange_components, half_sizes, overlap, write_positions};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadialOptions {
+    /// The root node, by ID.
+    pub root: Option<String>,
+    /// Distances in inches between successive circles, from the `ranksep` attribute, the last
+    /// repeating for further circles.
+    pub separation: Vec<f64>,
+    /// From the `overlap` attribute.
+    pub overlap: Overlap,
+}
+
+impl Default for RadialOptions {
+    fn default() -> Self {
+        RadialOptions {
+            root: None,
+            separation: vec![1.0],
+            overlap: Overlap::Remove,
+        }
+    }
+}
+
+impl RadialOptions {
+    /// Options from the attributes of `graph` and of its nodes.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut options = RadialOptions::default();
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::Root(root) => options.root = Some(root.clone()),
+                Attribute::RankSep(separation) if !separation.values.is_empty() => {
+                    options.separation = separation.values.clone()
+                }
+                Attribute::Overlap(overlap) => options.overlap = *overlap,
+                _ => {}
+            }
+        }
+        if options.root.is_none() {
+            options.root = graph
+                .nodes()
+                .iter()
+                .find(|node| {
+                    node.attributes.iter().any(|attribute| {
+                        matches!(attribute, Attribute::Root(root) if parse_bool(root) == Some(true))
+                    })
+                })
+                .map(|node| node.id.clone());
+        }
+        options
+    }
+}
+
+/// Distances in edges from `root` to the nodes reachable from it, and the parent of each node
+/// in a breadth-first tree, in the order reached.
+fn breadth_first(
+    neighbors: &[Vec<NodeIndex>],
+    root: NodeIndex,
+) -> (Vec<NodeIndex>, Vec<Option<usize>>, Vec<Option<NodeIndex>>) {
+    let mut order = vec![root];
+    let mut depths = vec![None; neighbors.len()];
+    let mut parents = vec![None; neighbors.len()];
+    depths[root] = Some(0);
+    let mut queue = VecDeque::from(vec![root]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &neighbors[node] {
+            if depths[next].is_none() {
+                depths[next] = Some(depths[node].unwrap() + 1);
+                parents[next] = Some(node);
+                order.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    (order, depths, parents)
+}
+
+/// The node of `component` with the least distance to the furthest node, the first on ties.
+fn center(neighbors: &[Vec<NodeIndex>], component: &[NodeIndex]) -> NodeIndex {
+    let eccentricity = |node| {
+        let (_, depths, _) = breadth_first(neighbors, node);
+        depths.into_iter().flatten().max().unwrap_or(0)
+    };
+    let mut best = (component[0], eccentricity(component[0]));
+    for &node in &component[1..] {
+        let eccentricity = eccentricity(node);
+        if eccentricity < best.1 {
+            best = (node, eccentricity);
+        }
+    }
+    best.0
+}
+
+/// Lays out `graph`, setting the `pos` of its nodes and its `bb`.
+pub fn layout(graph: &mut Graph, options: &RadialOptions) {
+    let n = graph.nodes().len();
+    let sizes = half_sizes(graph);
+    let widest = sizes
+        .iter()
+        .map(|&(dx, dy)| 2.0 * dx.max(dy))
+        .fold(0.0, f64::max);
+    let spacing = widest + overlap::SEPARATION;
+    let separation = |ring: usize| {
+        let values = &options.separation;
+        let last = values.len().saturating_sub(1);
+        values.get(ring.min(last)).unwrap_or(&1.0) * POINTS_PER_INCH
+    };
+
+    let mut neighbors = vec![vec![]; n];
+    for edge in graph.edges() {
+        if edge.tail != edge.head {
+            neighbors[edge.tail].push(edge.head);
+            neighbors[edge.head].push(edge.tail);
+        }
+    }
+    let chosen = options
+        .root
+        .as_deref()
+        .and_then(|root| graph.node_index(root));
+    let mut positions = vec![(0.0, 0.0); n];
+    let components = connected_components(graph);
+    for component in &components {
+        let root = chosen
+            .filter(|root| component.contains(root))
+            .unwrap_or_else(|| center(&neighbors, component));
+        let (order, depths, parents) = breadth_first(&neighbors, root);
+
+        // leaves under each node, counting leaves as their own
+        let mut leaves = vec![0.0; n];
+        for &node in order.iter().rev() {
+            if leaves[node] == 0.0 {
+                leaves[node] = 1.0;
+            }
+            if let Some(parent) = parents[node] {
+                leaves[parent] += leaves[node];
+            }
+        }
+
+        // radii of the circles, far enough apart and large enough for their nodes to fit
+        let rings = depths.iter().flatten().max().copied().unwrap_or(0);
+        let mut counts = vec![0; rings + 1];
+        for &node in &order {
+            counts[depths[node].unwrap()] += 1;
+        }
+        let mut radii = vec![0.0];
+        for ring in 1..=rings {
+            let fit = counts[ring] as f64 * spacing / (2.0 * PI);
+            radii.push((radii[ring - 1] + separation(ring - 1)).max(fit));
+        }
+
+        // the size of the angle of each node, and the start of the angle of its next child
+        let mut spans = vec![2.0 * PI; n];
+        let mut next_start = vec![0.0; n];
+        for &node in &order {
+            let (start, size) = match parents[node] {
+                Some(parent) => {
+                    let size = spans[parent] * leaves[node] / leaves[parent];
+                    let start = next_start[parent];
+                    next_start[parent] += size;
+                    (start, size)
+                }
+                None => (0.0, 2.0 * PI),
+            };
+            spans[node] = size;
+            next_start[node] = start;
+            let angle = start + size / 2.0;
+            let radius = radii[depths[node].unwrap()];
+            positions[node] = (radius * angle.cos(), radius * angle.sin());
+        }
+    }
+    arrange_components(&mut positions, &sizes, &components);
+    overlap::adjust(&mut positions, &sizes, options.overlap, &[]);
+    write_positions(
+        graph,
+        &positions,
+        &[],
+        EdgeRespresentation::LineSegment,
+        true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn radial() {
+        let mut graph = Graph::from_ir(
+            &parse_graph(
+                "graph { ranksep=2 a -- b -- c a -- d -- e d -- f hub -- a x [root=true] }",
+            )
+            .unwrap(),
+        );
+        let options = RadialOptions::from_graph(&graph);
+        assert_eq!(options.root.as_deref(), Some("x"));
+        layout(
+            &mut graph,
+            &RadialOptions {
+                root: None,
+                ..options
+            },
+        );
+        let position = |id: &str| {
+            let point = graph.node(id).unwrap().position().unwrap();
+            (point.x, point.y)
+        };
+        let distance = |a: &str, b: &str| {
+            let (a, b) = (position(a), position(b));
+            (a.0 - b.0).hypot(a.1 - b.1)
+        };
+        // `a` is central, with its neighbors on the first circle and theirs on the second
+        for id in &["b", "d", "hub"] {
+            assert!((distance("a", id) - 144.0).abs() < 0.1, "{}", id);
+        }
+        for id in &["c", "e", "f"] {
+            assert!((distance("a", id) - 288.0).abs() < 0.1, "{}", id);
+        }
+    }
+}
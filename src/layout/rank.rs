@@ -0,0 +1,658 @@
+//! Rank assignment, the first phase of layered layouts like Graphviz's `dot`, usable on its own.
+//!
+//! Each node gets a rank such that the head of every edge is at least the edge's `minlen` ranks
+//! (1 by default) after its tail, minimizing the sum over edges of their length in ranks times
+//! their `weight` (1 by default), by the network simplex method of Gansner et al. Edges closing
+//! cycles are turned around first, as by [`acyclic`](crate::algorithm::acyclic), and loops and
+//! edges with `constraint=false` are ignored. Nodes with as much weight on their in-edges as on
+//! their out-edges are then moved to the least crowded of the ranks they could take, to balance
+//! the ranks.
+//!
+//! Nodes can also be constrained in the [`RankGroup`]s of the [`RankOptions`], to share a rank or
+//! to take the minimum or maximum rank, as the `rank` attribute of a subgraph constrains its
+//! nodes in Graphviz.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    attribute::{Attribute, RankType},
+    model::{Graph, NodeIndex},
+};
+
+/// Most exchanges of tree edges by the network simplex method, guarding against cycling.
+const MAX_ITERATIONS: usize = 10_000;
+
+/// Nodes constrained together.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankGroup {
+    pub kind: RankType,
+    pub nodes: Vec<NodeIndex>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankOptions {
+    pub groups: Vec<RankGroup>,
+    /// Whether to balance nodes between ranks once the ranking is optimal.
+    pub balance: bool,
+}
+
+impl Default for RankOptions {
+    fn default() -> Self {
+        RankOptions {
+            groups: vec![],
+            balance: true,
+        }
+    }
+}
+
+/// An edge of the graph being ranked, requiring `head` to be at least `min_length` ranks after
+/// `tail`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Constraint {
+    tail: usize,
+    head: usize,
+    min_length: i64,
+    weight: f64,
+}
+
+/// The representative of the set of `node`, halving paths along the way.
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parents, a), find(parents, b));
+    parents[b] = a;
+}
+
+/// The rank of each node of `graph`, by [`NodeIndex`], the least rank of each connected
+/// component being 0.
+pub fn rank(graph: &Graph, options: &RankOptions) -> Vec<usize> {
+    let n = graph.nodes().len();
+
+    // grouped nodes are ranked as one
+    let mut parents = (0..n).collect::<Vec<_>>();
+    let (mut minimum, mut maximum) = (None, None);
+    let (mut source, mut sink) = (false, false);
+    for group in &options.groups {
+        let first = match group.nodes.first() {
+            Some(&first) => first,
+            None => continue,
+        };
+        for &node in &group.nodes[1..] {
+            union(&mut parents, first, node);
+        }
+        // all the groups at the minimum or maximum rank share it
+        let extreme = match group.kind {
+            RankType::Same => continue,
+            RankType::Min | RankType::Source => {
+                source |= group.kind == RankType::Source;
+                &mut minimum
+            }
+            RankType::Max | RankType::Sink => {
+                sink |= group.kind == RankType::Sink;
+                &mut maximum
+            }
+        };
+        if let Some(extreme) = *extreme {
+            union(&mut parents, extreme, first);
+        }
+        *extreme = Some(first);
+    }
+    let mut class = vec![usize::MAX; n];
+    let mut classes = 0;
+    for node in 0..n {
+        let root = find(&mut parents, node);
+        if class[root] == usize::MAX {
+            class[root] = classes;
+            classes += 1;
+        }
+        class[node] = class[root];
+    }
+    let minimum = minimum.map(|node| class[node]);
+    let maximum = maximum
+        .map(|node| class[node])
+        .filter(|&maximum| Some(maximum) != minimum);
+
+    // parallel edges are merged, and edges into the minimum rank or out of the maximum turned
+    // around
+    let mut merged = BTreeMap::new();
+    for edge in graph.edges() {
+        let (mut tail, mut head) = (class[edge.tail], class[edge.head]);
+        if tail == head {
+            continue;
+        }
+        if Some(head) == minimum || Some(tail) == maximum {
+            std::mem::swap(&mut tail, &mut head);
+        }
+        let (mut min_length, mut weight, mut constraint) = (1, 1.0, true);
+        for attribute in &edge.attributes {
+            match attribute {
+                Attribute::MinLen(value) => min_length = (*value).max(0),
+                Attribute::Weight(value) => weight = value.max(0.0),
+                Attribute::Constraint(value) => constraint = *value,
+                _ => {}
+            }
+        }
+        if !constraint {
+            continue;
+        }
+        merge(&mut merged, tail, head, min_length, weight);
+    }
+    let mut constraints = merged
+        .into_iter()
+        .map(|((tail, head), (min_length, weight))| Constraint {
+            tail,
+            head,
+            min_length,
+            weight,
+        })
+        .collect::<Vec<_>>();
+    break_cycles(classes, &mut constraints);
+
+    // everything else is kept after the minimum rank and before the maximum
+    let mut merged = BTreeMap::new();
+    for constraint in &constraints {
+        let Constraint {
+            tail,
+            head,
+            min_length,
+            weight,
+        } = *constraint;
+        merge(&mut merged, tail, head, min_length, weight);
+    }
+    for other in 0..classes {
+        if let Some(minimum) = minimum.filter(|&minimum| minimum != other) {
+            merge(&mut merged, minimum, other, source as i64, 0.0);
+        }
+        if let Some(maximum) = maximum.filter(|&maximum| maximum != other) {
+            merge(&mut merged, other, maximum, sink as i64, 0.0);
+        }
+    }
+    let constraints = merged
+        .into_iter()
+        .map(|((tail, head), (min_length, weight))| Constraint {
+            tail,
+            head,
+            min_length,
+            weight,
+        })
+        .collect::<Vec<_>>();
+
+    // components are ranked separately
+    let mut components = (0..classes).collect::<Vec<_>>();
+    for constraint in &constraints {
+        union(&mut components, constraint.tail, constraint.head);
+    }
+    let mut members = BTreeMap::new();
+    for node in 0..classes {
+        let root = find(&mut components, node);
+        members.entry(root).or_insert_with(Vec::new).push(node);
+    }
+    let mut local = vec![0; classes];
+    let mut edges_of = BTreeMap::new();
+    for nodes in members.values() {
+        for (index, &node) in nodes.iter().enumerate() {
+            local[node] = index;
+        }
+    }
+    for constraint in &constraints {
+        let root = find(&mut components, constraint.tail);
+        edges_of
+            .entry(root)
+            .or_insert_with(Vec::new)
+            .push(Constraint {
+                tail: local[constraint.tail],
+                head: local[constraint.head],
+                ..*constraint
+            });
+    }
+    let mut ranks = vec![0; classes];
+    for (root, nodes) in &members {
+        let edges = edges_of.remove(root).unwrap_or_default();
+        let solved = solve(nodes.len(), &edges, options.balance);
+        for (&node, rank) in nodes.iter().zip(solved) {
+            ranks[node] = rank;
+        }
+    }
+    class
+        .into_iter()
+        .map(|class| ranks[class] as usize)
+        .collect()
+}
+
+/// Adds an edge to `merged`, keeping the longest minimum length and the sum of the weights of
+/// parallel edges.
+fn merge(
+    merged: &mut BTreeMap<(usize, usize), (i64, f64)>,
+    tail: usize,
+    head: usize,
+    min_length: i64,
+    weight: f64,
+) {
+    let entry = merged.entry((tail, head)).or_insert((min_length, 0.0));
+    entry.0 = entry.0.max(min_length);
+    entry.1 += weight;
+}
+
+/// Turns around the edges closing a cycle in a depth-first search, in node order.
+fn break_cycles(n: usize, constraints: &mut [Constraint]) {
+    let mut out_edges = vec![vec![]; n];
+    for (index, constraint) in constraints.iter().enumerate() {
+        out_edges[constraint.tail].push(index);
+    }
+    // 0 unvisited, 1 on the search path, 2 done
+    let mut state = vec![0; n];
+    let mut back = vec![];
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match out_edges[node].get(*next) {
+                Some(&index) => {
+                    *next += 1;
+                    let head = constraints[index].head;
+                    match state[head] {
+                        0 => {
+                            state[head] = 1;
+                            stack.push((head, 0));
+                        }
+                        1 => back.push(index),
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = 2;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    for index in back {
+        let constraint = &mut constraints[index];
+        std::mem::swap(&mut constraint.tail, &mut constraint.head);
+    }
+}
+
+fn slack(edge: &Constraint, ranks: &[i64]) -> i64 {
+    ranks[edge.head] - ranks[edge.tail] - edge.min_length
+}
+
+/// Optimal ranks of the `n` nodes of a connected acyclic graph with the edges `edges`, the least
+/// being 0.
+fn solve(n: usize, edges: &[Constraint], balance: bool) -> Vec<i64> {
+    let mut incident = vec![vec![]; n];
+    for (index, edge) in edges.iter().enumerate() {
+        incident[edge.tail].push(index);
+        incident[edge.head].push(index);
+    }
+    let mut ranks = longest_paths(n, edges);
+    let mut tree = feasible_tree(n, edges, &incident, &mut ranks);
+    let mut start = 0;
+    for _ in 0..MAX_ITERATIONS {
+        let order = Postorder::new(n, edges, &incident, &tree);
+        let cut_values = order.cut_values(edges, &incident, &tree);
+        let leaving = (0..edges.len())
+            .map(|offset| (start + offset) % edges.len())
+            .find(|&index| tree[index] && cut_values[index] < 0.0);
+        let leaving = match leaving {
+            Some(leaving) => leaving,
+            None => break,
+        };
+        start = leaving + 1;
+        let entering = match order.entering(edges, &ranks, leaving) {
+            Some(entering) => entering,
+            None => break,
+        };
+        tree[leaving] = false;
+        tree[entering] = true;
+        ranks = tree_ranks(n, edges, &incident, &tree);
+    }
+    let least = ranks.iter().copied().min().unwrap_or(0);
+    for rank in &mut ranks {
+        *rank -= least;
+    }
+    if balance {
+        balance_ranks(edges, &incident, &mut ranks);
+    }
+    ranks
+}
+
+/// Initial ranks putting each node just after the furthest of its predecessors.
+fn longest_paths(n: usize, edges: &[Constraint]) -> Vec<i64> {
+    let mut in_degrees = vec![0; n];
+    let mut out_edges = vec![vec![]; n];
+    for edge in edges {
+        in_degrees[edge.head] += 1;
+        out_edges[edge.tail].push(edge);
+    }
+    let mut ranks = vec![0; n];
+    let mut ready = (0..n)
+        .filter(|&node| in_degrees[node] == 0)
+        .collect::<Vec<_>>();
+    while let Some(node) = ready.pop() {
+        for edge in &out_edges[node] {
+            ranks[edge.head] = ranks[edge.head].max(ranks[node] + edge.min_length);
+            in_degrees[edge.head] -= 1;
+            if in_degrees[edge.head] == 0 {
+                ready.push(edge.head);
+            }
+        }
+    }
+    ranks
+}
+
+/// A spanning tree of edges without slack, adjusting `ranks` to make one.
+fn feasible_tree(
+    n: usize,
+    edges: &[Constraint],
+    incident: &[Vec<usize>],
+    ranks: &mut [i64],
+) -> Vec<bool> {
+    let mut tree = vec![false; edges.len()];
+    let mut in_tree = vec![false; n];
+    in_tree[0] = true;
+    let mut size = 1;
+    loop {
+        // grow the tree along edges without slack
+        let mut stack = (0..n).filter(|&node| in_tree[node]).collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            for &index in &incident[node] {
+                let edge = &edges[index];
+                let other = edge.tail + edge.head - node;
+                if !in_tree[other] && slack(edge, ranks) == 0 {
+                    in_tree[other] = true;
+                    tree[index] = true;
+                    size += 1;
+                    stack.push(other);
+                }
+            }
+        }
+        if size == n {
+            return tree;
+        }
+        // then move the tree to take up the slack of the nearest edge leaving it
+        let (index, _) = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| in_tree[edge.tail] != in_tree[edge.head])
+            .map(|(index, edge)| (index, slack(edge, ranks)))
+            .min_by_key(|&(_, slack)| slack)
+            .expect("the graph is connected");
+        let edge = &edges[index];
+        let delta = if in_tree[edge.tail] {
+            slack(edge, ranks)
+        } else {
+            -slack(edge, ranks)
+        };
+        for node in 0..n {
+            if in_tree[node] {
+                ranks[node] += delta;
+            }
+        }
+    }
+}
+
+/// Ranks making every edge of `tree` tight, from the first node at rank 0.
+fn tree_ranks(n: usize, edges: &[Constraint], incident: &[Vec<usize>], tree: &[bool]) -> Vec<i64> {
+    let mut ranks = vec![0; n];
+    let mut visited = vec![false; n];
+    visited[0] = true;
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        for &index in &incident[node] {
+            let edge = &edges[index];
+            let other = edge.tail + edge.head - node;
+            if tree[index] && !visited[other] {
+                visited[other] = true;
+                ranks[other] = if other == edge.head {
+                    ranks[node] + edge.min_length
+                } else {
+                    ranks[node] - edge.min_length
+                };
+                stack.push(other);
+            }
+        }
+    }
+    ranks
+}
+
+/// A postorder numbering of a spanning tree rooted at the first node, where the nodes under a
+/// node are those numbered from its `low` to its `lim`.
+struct Postorder {
+    low: Vec<usize>,
+    lim: Vec<usize>,
+    parent_edges: Vec<Option<usize>>,
+    nodes: Vec<usize>,
+}
+
+impl Postorder {
+    fn new(n: usize, edges: &[Constraint], incident: &[Vec<usize>], tree: &[bool]) -> Self {
+        let mut low = vec![0; n];
+        let mut lim = vec![0; n];
+        let mut parent_edges = vec![None; n];
+        let mut nodes = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        visited[0] = true;
+        let mut stack = vec![(0, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match incident[node].get(*next) {
+                Some(&index) => {
+                    *next += 1;
+                    let edge = &edges[index];
+                    let other = edge.tail + edge.head - node;
+                    if tree[index] && !visited[other] {
+                        visited[other] = true;
+                        parent_edges[other] = Some(index);
+                        low[other] = nodes.len();
+                        stack.push((other, 0));
+                    }
+                }
+                None => {
+                    lim[node] = nodes.len();
+                    nodes.push(node);
+                    stack.pop();
+                }
+            }
+        }
+        Postorder {
+            low,
+            lim,
+            parent_edges,
+            nodes,
+        }
+    }
+
+    /// Whether `node` is under `root`.
+    fn under(&self, node: usize, root: usize) -> bool {
+        self.low[root] <= self.lim[node] && self.lim[node] <= self.lim[root]
+    }
+
+    /// The weight of the edges from the tail side of each tree edge to its head side, less the
+    /// weight of those the other way, worked out from the bottom of the tree up.
+    fn cut_values(&self, edges: &[Constraint], incident: &[Vec<usize>], tree: &[bool]) -> Vec<f64> {
+        let mut cut_values = vec![0.0; edges.len()];
+        for &child in &self.nodes {
+            let parent_edge = match self.parent_edges[child] {
+                Some(parent_edge) => parent_edge,
+                None => continue,
+            };
+            let child_is_tail = edges[parent_edge].tail == child;
+            let mut cut_value = edges[parent_edge].weight;
+            for &index in &incident[child] {
+                if index == parent_edge {
+                    continue;
+                }
+                let edge = &edges[index];
+                let towards_head = (edge.tail == child) == child_is_tail;
+                let (weight, below) = if towards_head {
+                    (edge.weight, -cut_values[index])
+                } else {
+                    (-edge.weight, cut_values[index])
+                };
+                cut_value += weight;
+                if tree[index] {
+                    cut_value += below;
+                }
+            }
+            cut_values[parent_edge] = cut_value;
+        }
+        cut_values
+    }
+
+    /// The edge with the least slack from the head side of the tree edge `leaving` to its tail
+    /// side.
+    fn entering(&self, edges: &[Constraint], ranks: &[i64], leaving: usize) -> Option<usize> {
+        let edge = &edges[leaving];
+        let child = if self.parent_edges[edge.tail] == Some(leaving) {
+            edge.tail
+        } else {
+            edge.head
+        };
+        let tail_under = child == edge.tail;
+        edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| {
+                self.under(edge.tail, child) != tail_under
+                    && self.under(edge.head, child) == tail_under
+            })
+            .min_by_key(|(_, edge)| slack(edge, ranks))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Moves nodes with as much weight in as out to the least crowded rank they can take.
+fn balance_ranks(edges: &[Constraint], incident: &[Vec<usize>], ranks: &mut [i64]) {
+    let last = ranks.iter().copied().max().unwrap_or(0);
+    let mut counts = vec![0; last as usize + 1];
+    for &rank in ranks.iter() {
+        counts[rank as usize] += 1;
+    }
+    for node in 0..ranks.len() {
+        let (mut weight_in, mut weight_out) = (0.0, 0.0);
+        let (mut low, mut high) = (0, last);
+        for &index in &incident[node] {
+            let edge = &edges[index];
+            if edge.head == node {
+                weight_in += edge.weight;
+                low = low.max(ranks[edge.tail] + edge.min_length);
+            } else {
+                weight_out += edge.weight;
+                high = high.min(ranks[edge.head] - edge.min_length);
+            }
+        }
+        if weight_in != weight_out {
+            continue;
+        }
+        let mut best = ranks[node];
+        for rank in low..=high {
+            if counts[rank as usize] < counts[best as usize] {
+                best = rank;
+            }
+        }
+        counts[ranks[node] as usize] -= 1;
+        counts[best as usize] += 1;
+        ranks[node] = best;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    /// The ranks of the nodes of `input`, constrained in groups of the given kinds and node IDs.
+    fn grouped_ranks(input: &str, groups: &[(RankType, &[&str])]) -> Vec<(String, usize)> {
+        let graph = Graph::from_ir(&parse_graph(input).unwrap());
+        let groups = groups
+            .iter()
+            .map(|&(kind, ids)| RankGroup {
+                kind,
+                nodes: ids.iter().map(|id| graph.node_index(id).unwrap()).collect(),
+            })
+            .collect();
+        let options = RankOptions {
+            groups,
+            ..RankOptions::default()
+        };
+        let ranks = rank(&graph, &options);
+        graph
+            .nodes()
+            .iter()
+            .map(|node| node.id.clone())
+            .zip(ranks)
+            .collect()
+    }
+
+    fn ranks(input: &str) -> Vec<(String, usize)> {
+        grouped_ranks(input, &[])
+    }
+
+    fn expect(ranks: Vec<(String, usize)>, expected: &[(&str, usize)]) {
+        let expected = expected
+            .iter()
+            .map(|&(id, rank)| (id.to_owned(), rank))
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, expected);
+    }
+
+    #[test]
+    fn network_simplex() {
+        expect(
+            ranks("digraph { a -> b -> c a -> c b -> a d }"),
+            &[("a", 0), ("b", 1), ("c", 2), ("d", 0)],
+        );
+        // `e` is pulled down next to `d` rather than left at the top
+        expect(
+            ranks("digraph { a -> b -> d a -> c [minlen=2] c -> d e -> d }"),
+            &[("a", 0), ("b", 1), ("d", 3), ("c", 2), ("e", 2)],
+        );
+        // a heavy edge is kept short at the cost of lighter ones
+        expect(
+            ranks("digraph { a -> b -> c -> d x -> d [weight=3] a -> x }"),
+            &[("a", 0), ("b", 1), ("c", 2), ("d", 3), ("x", 2)],
+        );
+        // edges without constraint don't rank their heads after their tails
+        expect(
+            ranks("digraph { a -> b -> c c -> d [constraint=false] }"),
+            &[("a", 0), ("b", 1), ("c", 2), ("d", 0)],
+        );
+    }
+
+    #[test]
+    fn rank_groups() {
+        expect(
+            grouped_ranks(
+                "digraph { a -> b -> c x -> y q -> a }",
+                &[(RankType::Same, &["c", "y"]), (RankType::Max, &["q"])],
+            ),
+            &[("a", 0), ("b", 1), ("c", 2), ("x", 1), ("y", 2), ("q", 2)],
+        );
+        // separate groups of the same kind are ranked apart
+        expect(
+            grouped_ranks(
+                "digraph { a -> b -> c x -> y }",
+                &[(RankType::Same, &["a", "x"]), (RankType::Same, &["c", "y"])],
+            ),
+            &[("a", 0), ("b", 1), ("c", 2), ("x", 0), ("y", 2)],
+        );
+        // sources and sinks have their ranks to themselves
+        expect(
+            grouped_ranks(
+                "digraph { a -> b q -> a s }",
+                &[(RankType::Sink, &["q"]), (RankType::Source, &["s"])],
+            ),
+            &[("a", 1), ("b", 2), ("q", 3), ("s", 0)],
+        );
+    }
+}
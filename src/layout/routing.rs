@@ -0,0 +1,588 @@
+//! Edge routing, drawing the edges between placed nodes as the `splines` attribute asks.
+//!
+//! Edges are written as `pos` splines, the control points of cubic Bézier curves joined end to
+//! end, from the boundary of the tail node to that of the head node, or from their centers when
+//! `tailclip` or `headclip` is false. Boxes and box-like shapes are clipped as rectangles, and
+//! other shapes as the ellipse inscribed in their box. By `splines`:
+//!
+//! - `spline` draws a smooth curve through the waypoints of the edge, the virtual nodes of layered
+//!   layouts;
+//! - `polyline` draws straight segments through the waypoints;
+//! - `line` draws a straight line, ignoring waypoints;
+//! - `curved` draws an arc bowing to the left of the straight line;
+//! - `ortho` draws axis-aligned segments, leaving and entering nodes along the axis on which they
+//!   are furthest apart, turning halfway;
+//! - `none` leaves edges without a `pos`.
+//!
+//! An edge with a `tailport` or `headport` naming a record field or HTML table cell ends at the
+//! border of that field instead of the node, and one with a compass point ends at that side or
+//! corner of the field, or of the node.
+//!
+//! Loops are drawn on the right of their node, each loop reaching further out than the last.
+//! Parallel edges between the same two nodes are spread apart, to either side of the line between
+//! them.
+
+use crate::{
+    attribute::{Attribute, CompassPoint, EdgeRespresentation, Point, PortPos, Position, Shape},
+    font,
+    model::{set_attribute, Graph, NodeIndex, POINTS_PER_INCH},
+    render::label::Structure,
+};
+
+/// How much further out each loop on a node reaches, in points.
+const LOOP_SIZE: f64 = 18.0;
+
+/// Distance between parallel edges at their middle, in points.
+const PARALLEL_SPACING: f64 = 12.0;
+
+/// How far `curved` edges bow out, relative to their length.
+const CURVATURE: f64 = 0.2;
+
+/// The `splines` attribute of `graph`, or else `default`.
+pub fn mode(graph: &Graph, default: EdgeRespresentation) -> EdgeRespresentation {
+    graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Splines(mode) => Some(mode.clone()),
+            _ => None,
+        })
+        .unwrap_or(default)
+}
+
+/// The outline of a placed node.
+#[derive(Debug, Clone, Copy)]
+struct Outline {
+    center: (f64, f64),
+    /// Half the width and height.
+    half: (f64, f64),
+    boxed: bool,
+    /// Where edges end instead of where they leave the node, at a compass point.
+    port: Option<(f64, f64)>,
+}
+
+impl Outline {
+    /// The point edges aim from.
+    fn anchor(&self) -> (f64, f64) {
+        self.port.unwrap_or(self.center)
+    }
+
+    /// Where a ray from the center towards `toward` leaves the node, or the center if `toward`
+    /// is inside it, or else the port the edge ends at.
+    fn clip(&self, toward: (f64, f64)) -> (f64, f64) {
+        if let Some(port) = self.port {
+            return port;
+        }
+        let (dx, dy) = (toward.0 - self.center.0, toward.1 - self.center.1);
+        if dx == 0.0 && dy == 0.0 {
+            return self.center;
+        }
+        let t = if self.boxed {
+            let along = |distance: f64, half: f64| {
+                if distance == 0.0 {
+                    f64::INFINITY
+                } else {
+                    half / distance.abs()
+                }
+            };
+            along(dx, self.half.0).min(along(dy, self.half.1))
+        } else {
+            1.0 / ((dx / self.half.0).powi(2) + (dy / self.half.1).powi(2)).sqrt()
+        };
+        if t < 1.0 {
+            (self.center.0 + t * dx, self.center.1 + t * dy)
+        } else {
+            self.center
+        }
+    }
+
+    fn moved(&self, by: (f64, f64)) -> Outline {
+        let moved = |(x, y): (f64, f64)| (x + by.0, y + by.1);
+        Outline {
+            center: moved(self.center),
+            port: self.port.map(moved),
+            ..*self
+        }
+    }
+
+    /// The outline edges meet at `port`: the field or cell of `structure` it names, as a box, or
+    /// a compass point of that or of the node.
+    fn at(&self, port: &PortPos, structure: Option<&Structure>) -> Outline {
+        let size = (2.0 * self.half.0, 2.0 * self.half.1);
+        let field = |name: &str| {
+            let ((x, y), half) = structure?.port(name, size)?;
+            // the y axis of labels points down
+            Some(Outline {
+                center: (self.center.0 + x, self.center.1 - y),
+                half,
+                boxed: true,
+                port: None,
+            })
+        };
+        // a lone compass point may name a field too, which comes first
+        let (field, compass) = match (&port.port, port.compass) {
+            (Some(name), compass) => (field(name), compass),
+            (None, Some(compass)) => match field(compass.as_str()) {
+                Some(field) => (Some(field), None),
+                None => (None, Some(compass)),
+            },
+            (None, None) => (None, None),
+        };
+        let outline = field.unwrap_or(*self);
+        let direction = match compass {
+            Some(CompassPoint::North) => (0.0, 1.0),
+            Some(CompassPoint::NorthEast) => (1.0, 1.0),
+            Some(CompassPoint::East) => (1.0, 0.0),
+            Some(CompassPoint::SouthEast) => (1.0, -1.0),
+            Some(CompassPoint::South) => (0.0, -1.0),
+            Some(CompassPoint::SouthWest) => (-1.0, -1.0),
+            Some(CompassPoint::West) => (-1.0, 0.0),
+            Some(CompassPoint::NorthWest) => (-1.0, 1.0),
+            Some(CompassPoint::Center) => (0.0, 0.0),
+            Some(CompassPoint::Any) | None => return outline,
+        };
+        let point = if field.is_some() {
+            (
+                outline.center.0 + direction.0 * outline.half.0,
+                outline.center.1 + direction.1 * outline.half.1,
+            )
+        } else {
+            self.clip((
+                self.center.0 + 2.0 * direction.0 * self.half.0,
+                self.center.1 + 2.0 * direction.1 * self.half.1,
+            ))
+        };
+        Outline {
+            port: Some(point),
+            ..outline
+        }
+    }
+}
+
+/// Whether `shape` is clipped as a rectangle.
+fn boxed(shape: &Shape) -> bool {
+    matches!(
+        shape,
+        Shape::Box
+            | Shape::Rect
+            | Shape::Rectangle
+            | Shape::Square
+            | Shape::Plaintext
+            | Shape::Plain
+            | Shape::None
+            | Shape::Underline
+            | Shape::Note
+            | Shape::Tab
+            | Shape::Folder
+            | Shape::Box3d
+            | Shape::Component
+            | Shape::MSquare
+            | Shape::Cylinder
+            | Shape::Record
+            | Shape::MRecord
+    )
+}
+
+/// The unit vector a quarter turn counterclockwise from the direction from `from` to `to`.
+fn normal(from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        (0.0, 1.0)
+    } else {
+        (-dy / length, dx / length)
+    }
+}
+
+fn offset(point: (f64, f64), direction: (f64, f64), distance: f64) -> (f64, f64) {
+    (
+        point.0 + direction.0 * distance,
+        point.1 + direction.1 * distance,
+    )
+}
+
+/// Control points of straight segments through `points`.
+fn straight(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut controls = vec![points[0]];
+    for pair in points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let third = ((to.0 - from.0) / 3.0, (to.1 - from.1) / 3.0);
+        controls.push((from.0 + third.0, from.1 + third.1));
+        controls.push((to.0 - third.0, to.1 - third.1));
+        controls.push(to);
+    }
+    controls
+}
+
+/// Control points of a smooth curve through `points`, tangent at each point to the line between
+/// the points either side of it, and at the ends to the line to the next point.
+fn smooth(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let reflect =
+        |point: (f64, f64), about: (f64, f64)| (2.0 * about.0 - point.0, 2.0 * about.1 - point.1);
+    let mut controls = vec![points[0]];
+    let last = points.len() - 1;
+    for i in 0..last {
+        let (from, to) = (points[i], points[i + 1]);
+        let before = if i == 0 {
+            reflect(to, from)
+        } else {
+            points[i - 1]
+        };
+        let after = if i + 1 == last {
+            reflect(from, to)
+        } else {
+            points[i + 2]
+        };
+        controls.push((
+            from.0 + (to.0 - before.0) / 6.0,
+            from.1 + (to.1 - before.1) / 6.0,
+        ));
+        controls.push((
+            to.0 - (after.0 - from.0) / 6.0,
+            to.1 - (after.1 - from.1) / 6.0,
+        ));
+        controls.push(to);
+    }
+    controls
+}
+
+/// Control points of an edge from `tail` to `head` through `waypoints`, moved `spread` points to
+/// the left to keep it apart from parallel edges, which are clipped at the ends flagged by `clip`.
+fn route_edge(
+    mode: &EdgeRespresentation,
+    (tail, head): (Outline, Outline),
+    waypoints: &[(f64, f64)],
+    spread: f64,
+    clip: (bool, bool),
+) -> Vec<(f64, f64)> {
+    let ends = |tail: Outline, head: Outline, from: (f64, f64), to: (f64, f64)| {
+        (
+            if clip.0 {
+                tail.clip(from)
+            } else {
+                tail.anchor()
+            },
+            if clip.1 { head.clip(to) } else { head.anchor() },
+        )
+    };
+    let side = normal(tail.center, head.center);
+    match mode {
+        EdgeRespresentation::Off => vec![],
+        EdgeRespresentation::LineSegment => {
+            let shift = offset((0.0, 0.0), side, spread);
+            let (tail, head) = (tail.moved(shift), head.moved(shift));
+            let (start, end) = ends(tail, head, head.anchor(), tail.anchor());
+            straight(&[start, end])
+        }
+        EdgeRespresentation::Curved => {
+            let (start, end) = ends(tail, head, head.anchor(), tail.anchor());
+            let length = (end.0 - start.0).hypot(end.1 - start.1);
+            let bow = length * CURVATURE + spread;
+            let third = ((end.0 - start.0) / 3.0, (end.1 - start.1) / 3.0);
+            vec![
+                start,
+                offset((start.0 + third.0, start.1 + third.1), side, bow),
+                offset((end.0 - third.0, end.1 - third.1), side, bow),
+                end,
+            ]
+        }
+        EdgeRespresentation::Ortho => {
+            let (dx, dy) = (head.center.0 - tail.center.0, head.center.1 - tail.center.1);
+            let corners = if dy.abs() >= dx.abs() {
+                let middle = (tail.center.1 + head.center.1) / 2.0 + spread;
+                [(tail.center.0, middle), (head.center.0, middle)]
+            } else {
+                let middle = (tail.center.0 + head.center.0) / 2.0 - spread;
+                [(middle, tail.center.1), (middle, head.center.1)]
+            };
+            let (start, end) = ends(tail, head, corners[0], corners[1]);
+            let mut points = vec![start];
+            for &point in corners.iter().chain(&[end]) {
+                if points[points.len() - 1] != point {
+                    points.push(point);
+                }
+            }
+            if points.len() < 2 {
+                points.push(end);
+            }
+            straight(&points)
+        }
+        EdgeRespresentation::Spline | EdgeRespresentation::Polyline => {
+            let mut stops = waypoints.to_vec();
+            if stops.is_empty() && spread != 0.0 {
+                let middle = (
+                    (tail.center.0 + head.center.0) / 2.0,
+                    (tail.center.1 + head.center.1) / 2.0,
+                );
+                stops.push(offset(middle, side, spread));
+            }
+            let first = stops.first().copied().unwrap_or(head.anchor());
+            let last = stops.last().copied().unwrap_or(tail.anchor());
+            let (start, end) = ends(tail, head, first, last);
+            let mut points = vec![start];
+            points.extend(stops);
+            points.push(end);
+            if *mode == EdgeRespresentation::Spline {
+                smooth(&points)
+            } else {
+                straight(&points)
+            }
+        }
+    }
+}
+
+/// Control points of the `count`th loop on `node`, counting from 0.
+fn route_loop(node: Outline, count: usize) -> Vec<(f64, f64)> {
+    let reach = LOOP_SIZE * (count + 1) as f64;
+    let (x, y) = node.center;
+    let (dx, dy) = node.half;
+    let angle = std::f64::consts::PI / 6.0;
+    // aiming beyond the boundary, so that the ends are on it
+    let start = node.clip((x + 2.0 * dx * angle.cos(), y + 2.0 * dy * angle.sin()));
+    let end = node.clip((x + 2.0 * dx * angle.cos(), y - 2.0 * dy * angle.sin()));
+    vec![
+        start,
+        (x + dx + reach, y + dy / 2.0 + reach),
+        (x + dx + reach, y - dy / 2.0 - reach),
+        end,
+    ]
+}
+
+/// Routes of the edges of `graph` between its nodes at `positions`, through the `waypoints` of
+/// each edge if there are any, or `None` for edges left without a route.
+pub(crate) fn routes(
+    graph: &Graph,
+    positions: &[(f64, f64)],
+    waypoints: &[Vec<(f64, f64)>],
+    mode: &EdgeRespresentation,
+) -> Vec<Option<Vec<(f64, f64)>>> {
+    let outlines = graph
+        .nodes()
+        .iter()
+        .zip(positions)
+        .map(|(node, &center)| {
+            let (width, height) = node.size();
+            let shape = node
+                .attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::Shape(shape) => Some(boxed(shape)),
+                    _ => None,
+                });
+            Outline {
+                center,
+                half: (
+                    width * POINTS_PER_INCH / 2.0,
+                    height * POINTS_PER_INCH / 2.0,
+                ),
+                boxed: shape.unwrap_or(false),
+                port: None,
+            }
+        })
+        .collect::<Vec<_>>();
+    // the structured labels of nodes with ports, once each
+    let font_path = font::font_path(graph);
+    let mut structures = vec![None; graph.nodes().len()];
+    let mut anchored = |node: NodeIndex, port: Option<&PortPos>| match port {
+        Some(port) => {
+            let structure = structures[node]
+                .get_or_insert_with(|| Structure::of(&graph.nodes()[node], graph, &font_path));
+            outlines[node].at(port, structure.as_ref())
+        }
+        None => outlines[node],
+    };
+
+    // parallel edges between the same nodes, either way, and loops on each node
+    let mut groups = std::collections::BTreeMap::new();
+    for (index, edge) in graph.edges().iter().enumerate() {
+        let key = (edge.tail.min(edge.head), edge.tail.max(edge.head));
+        groups.entry(key).or_insert_with(Vec::new).push(index);
+    }
+    let mut places = vec![(0, 1); graph.edges().len()];
+    for edges in groups.values() {
+        for (place, &index) in edges.iter().enumerate() {
+            places[index] = (place, edges.len());
+        }
+    }
+
+    if *mode == EdgeRespresentation::Off {
+        return vec![None; graph.edges().len()];
+    }
+    graph
+        .edges()
+        .iter()
+        .enumerate()
+        .map(|(index, edge)| {
+            let (place, count) = places[index];
+            if edge.tail == edge.head {
+                return Some(route_loop(outlines[edge.tail], place));
+            }
+            // spread around the middle, to the same side whichever way edges go
+            let mut spread = (place as f64 - (count - 1) as f64 / 2.0) * PARALLEL_SPACING;
+            if edge.tail > edge.head {
+                spread = -spread;
+            }
+            let mut clip = (true, true);
+            let mut ports = (None, None);
+            for attribute in &edge.attributes {
+                match attribute {
+                    Attribute::TailClip(value) => clip.0 = *value,
+                    Attribute::HeadClip(value) => clip.1 = *value,
+                    Attribute::TailPort(port) => ports.0 = Some(&**port),
+                    Attribute::HeadPort(port) => ports.1 = Some(&**port),
+                    _ => {}
+                }
+            }
+            let waypoints = waypoints.get(index).map_or(&[][..], Vec::as_slice);
+            Some(route_edge(
+                mode,
+                (anchored(edge.tail, ports.0), anchored(edge.head, ports.1)),
+                waypoints,
+                spread,
+                clip,
+            ))
+        })
+        .collect()
+}
+
+/// Routes the edges of a laid out `graph` between its nodes again, following its `splines`
+/// attribute or else `default`, and grows its `bb` to fit them. Edges touching nodes without a
+/// position are left alone.
+pub fn route(graph: &mut Graph, default: EdgeRespresentation) {
+    let mode = mode(graph, default);
+    let positions = graph
+        .nodes()
+        .iter()
+        .map(|node| node.position().map(|point| (point.x, point.y)))
+        .collect::<Vec<_>>();
+    let placed = positions
+        .iter()
+        .map(|position| position.unwrap_or((0.0, 0.0)))
+        .collect::<Vec<_>>();
+    let routes = routes(graph, &placed, &[], &mode);
+    let mut bb = graph.bounding_box();
+    for (index, route) in routes.into_iter().enumerate() {
+        let edge = &graph.edges()[index];
+        if positions[edge.tail].is_none() || positions[edge.head].is_none() {
+            continue;
+        }
+        let attributes = &mut graph.edge_mut(index).attributes;
+        match route {
+            Some(route) => {
+                let points = route
+                    .into_iter()
+                    .map(|(x, y)| Point { x, y })
+                    .collect::<Vec<_>>();
+                if let Some(bb) = &mut bb {
+                    for point in &points {
+                        bb.lower_left.x = bb.lower_left.x.min(point.x);
+                        bb.lower_left.y = bb.lower_left.y.min(point.y);
+                        bb.upper_right.x = bb.upper_right.x.max(point.x);
+                        bb.upper_right.y = bb.upper_right.y.max(point.y);
+                    }
+                }
+                set_attribute(attributes, Attribute::Pos(Position::Spline(points)));
+            }
+            None => attributes.retain(|attribute| !matches!(attribute, Attribute::Pos(_))),
+        }
+    }
+    if let Some(bb) = bb {
+        set_attribute(&mut graph.attributes, Attribute::Bb(Box::new(bb)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout::layout};
+
+    fn edge_points(graph: &Graph, index: usize) -> Vec<(f64, f64)> {
+        graph.edges()[index]
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Pos(Position::Spline(points)) => {
+                    Some(points.iter().map(|point| (point.x, point.y)).collect())
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn routes() {
+        let mut graph = Graph::from_ir(
+            &parse_graph("digraph { a -> b a -> b b -> a b -> b b -> b a -> c [headclip=false] }")
+                .unwrap(),
+        );
+        let positions = vec![(0.0, 100.0), (0.0, 0.0), (200.0, 100.0)];
+        let routes = super::routes(&graph, &positions, &[], &EdgeRespresentation::Spline);
+        // parallel edges spread to both sides of the straight line between the boundaries
+        let (first, second, third) = (
+            routes[0].as_ref().unwrap(),
+            routes[1].as_ref().unwrap(),
+            routes[2].as_ref().unwrap(),
+        );
+        assert_eq!(second, &straight(&[(0.0, 82.0), (0.0, 18.0)]));
+        assert_eq!(first[3], (-12.0, 50.0));
+        assert_eq!(third[3], (12.0, 50.0));
+        // loops reach further and further to the right
+        let (inner, outer) = (routes[3].as_ref().unwrap(), routes[4].as_ref().unwrap());
+        assert_eq!(inner.len(), 4);
+        assert!(outer[1].0 > inner[1].0 && inner[1].0 > 27.0);
+        // unclipped ends are at the center
+        let last = routes[5].as_ref().unwrap();
+        assert_eq!(
+            (last[0], last[last.len() - 1]),
+            ((27.0, 100.0), (200.0, 100.0))
+        );
+
+        let routes = super::routes(&graph, &positions, &[], &EdgeRespresentation::Ortho);
+        let orthogonal = routes[5].as_ref().unwrap();
+        assert!(orthogonal
+            .windows(2)
+            .all(|pair| pair[0].0 == pair[1].0 || pair[0].1 == pair[1].1));
+
+        graph = Graph::from_ir(
+            &parse_graph("digraph { splines=polyline a -> b -> c a -> c }").unwrap(),
+        );
+        layout(&mut graph, Some("dot")).unwrap();
+        // the long edge goes through its virtual node, straight segment by segment
+        let long = edge_points(&graph, 2);
+        assert_eq!(long.len(), 7);
+        let b = graph.node("b").unwrap().position().unwrap();
+        assert_eq!(long[3].1, b.y);
+        assert!(graph.edges().iter().all(|edge| edge
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::Pos(Position::Spline(_))))));
+    }
+
+    #[test]
+    fn ports() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph {
+                    a [shape=record label="<l> left|<r> right" width=2]
+                    a:r -> b a:l:w -> c d -> a:e
+                }"#,
+            )
+            .unwrap(),
+        );
+        let positions = vec![(0.0, 100.0), (0.0, 0.0), (-200.0, 100.0), (200.0, 100.0)];
+        let routes = super::routes(&graph, &positions, &[], &EdgeRespresentation::LineSegment);
+        let ends = routes
+            .iter()
+            .map(|route| {
+                let route = route.as_ref().unwrap();
+                (route[0], route[route.len() - 1])
+            })
+            .collect::<Vec<_>>();
+        // from the bottom of the right field, the left side of the left field, and into the
+        // right side of the node
+        assert!(ends[0].0 .0 > 0.0 && ends[0].0 .0 < 72.0);
+        assert_eq!(ends[0].0 .1, 82.0);
+        assert_eq!(ends[1].0, (-72.0, 100.0));
+        assert_eq!(ends[2].1, (72.0, 100.0));
+    }
+}
@@ -0,0 +1,273 @@
+//! Flips and quarter turns of laid out graphs, as `rankdir` needs.
+//!
+//! Layered layouts place ranks from top to bottom, then turn the drawing to the direction of
+//! the graph's `rankdir` with the [`Transform`] from [`Transform::from_rank_dir`]. The same
+//! transforms re-orient graphs which are already laid out: [`transform`] applies one to every
+//! position of a graph, and [`reorient`] turns a drawing from its `rankdir` to another without
+//! laying it out again. Nodes keep their shape, and labels stay upright.
+
+use crate::{
+    algorithm::pack::for_each_point,
+    attribute::{Attribute, Point, RankDir, Rectangle},
+    model::{set_attribute, Graph},
+};
+
+use super::round;
+
+/// One of the eight flips and quarter turns of the plane, mapping `(x, y)` to
+/// `(xx * x + xy * y, yx * x + yy * y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    xx: i8,
+    xy: i8,
+    yx: i8,
+    yy: i8,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        xx: 1,
+        xy: 0,
+        yx: 0,
+        yy: 1,
+    };
+
+    /// Mirrors left and right.
+    pub const FLIP_X: Transform = Transform {
+        xx: -1,
+        xy: 0,
+        yx: 0,
+        yy: 1,
+    };
+
+    /// Mirrors top and bottom.
+    pub const FLIP_Y: Transform = Transform {
+        xx: 1,
+        xy: 0,
+        yx: 0,
+        yy: -1,
+    };
+
+    /// Turns a quarter counterclockwise.
+    pub const ROTATE: Transform = Transform {
+        xx: 0,
+        xy: -1,
+        yx: 1,
+        yy: 0,
+    };
+
+    /// Turns `quarters` quarters counterclockwise, or clockwise if negative.
+    pub fn rotate(quarters: i32) -> Self {
+        (0..quarters.rem_euclid(4)).fold(Transform::IDENTITY, |transform, _| {
+            transform.then(Transform::ROTATE)
+        })
+    }
+
+    /// The transform from a drawing with ranks from top to bottom to one with ranks in
+    /// `direction`, keeping the first node of each rank on the left, or at the top when ranks
+    /// go sideways.
+    pub fn from_rank_dir(direction: RankDir) -> Self {
+        match direction {
+            RankDir::TopToBottom => Transform::IDENTITY,
+            RankDir::BottomToTop => Transform::FLIP_Y,
+            RankDir::LeftToRight => Transform {
+                xx: 0,
+                xy: -1,
+                yx: -1,
+                yy: 0,
+            },
+            RankDir::RightToLeft => Transform {
+                xx: 0,
+                xy: 1,
+                yx: -1,
+                yy: 0,
+            },
+        }
+    }
+
+    /// The transform from a drawing with ranks in direction `from` to one with ranks in `to`.
+    pub fn between(from: RankDir, to: RankDir) -> Self {
+        Transform::from_rank_dir(from)
+            .inverse()
+            .then(Transform::from_rank_dir(to))
+    }
+
+    /// This transform followed by `next`.
+    pub fn then(self, next: Transform) -> Self {
+        Transform {
+            xx: next.xx * self.xx + next.xy * self.yx,
+            xy: next.xx * self.xy + next.xy * self.yy,
+            yx: next.yx * self.xx + next.yy * self.yx,
+            yy: next.yx * self.xy + next.yy * self.yy,
+        }
+    }
+
+    /// The transform undoing this one.
+    pub fn inverse(self) -> Self {
+        // flips and turns are orthogonal, so their inverse is their transpose
+        Transform {
+            xx: self.xx,
+            xy: self.yx,
+            yx: self.xy,
+            yy: self.yy,
+        }
+    }
+
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            f64::from(self.xx) * x + f64::from(self.xy) * y,
+            f64::from(self.yx) * x + f64::from(self.yy) * y,
+        )
+    }
+
+    /// Whether the transform swaps the axes, turning widths into heights.
+    pub fn swaps_axes(&self) -> bool {
+        self.xx == 0
+    }
+}
+
+/// Applies `transform` to every position of the laid out `graph`: node and edge `pos`, label
+/// positions and record fields. The drawing is then moved back to start at the origin, with its
+/// `bb` updated. Does nothing if `graph` isn't laid out.
+pub fn transform(graph: &mut Graph, transform: Transform) {
+    let bb = graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Bb(bb) => Some((**bb).clone()),
+            _ => None,
+        })
+        .or_else(|| graph.bounding_box());
+    let bb = match bb {
+        Some(bb) => bb,
+        None => return,
+    };
+    let corners = (
+        transform.apply((bb.lower_left.x, bb.lower_left.y)),
+        transform.apply((bb.upper_right.x, bb.upper_right.y)),
+    );
+    let (left, bottom) = (
+        f64::min((corners.0).0, (corners.1).0),
+        f64::min((corners.0).1, (corners.1).1),
+    );
+    let (right, top) = (
+        f64::max((corners.0).0, (corners.1).0),
+        f64::max((corners.0).1, (corners.1).1),
+    );
+    let shift = |point: &mut Point| {
+        let (x, y) = transform.apply((point.x, point.y));
+        point.x = round(x - left);
+        point.y = round(y - bottom);
+    };
+    for node in 0..graph.nodes().len() {
+        let attributes = &mut graph.node_mut(node).attributes;
+        for_each_point(attributes, shift);
+        for attribute in attributes.iter_mut() {
+            if let Attribute::Rects(rectangle) = attribute {
+                **rectangle = normalized(rectangle);
+            }
+        }
+    }
+    for edge in 0..graph.edges().len() {
+        for_each_point(&mut graph.edge_mut(edge).attributes, shift);
+    }
+    for_each_point(&mut graph.attributes, shift);
+    let bb = Rectangle {
+        lower_left: Point { x: 0.0, y: 0.0 },
+        upper_right: Point {
+            x: round(right - left),
+            y: round(top - bottom),
+        },
+    };
+    set_attribute(&mut graph.attributes, Attribute::Bb(Box::new(bb)));
+}
+
+/// The rectangle with the corners of `rectangle`, its lower left corner first.
+fn normalized(rectangle: &Rectangle) -> Rectangle {
+    let (a, b) = (&rectangle.lower_left, &rectangle.upper_right);
+    Rectangle {
+        lower_left: Point {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+        },
+        upper_right: Point {
+            x: a.x.max(b.x),
+            y: a.y.max(b.y),
+        },
+    }
+}
+
+/// Turns the laid out `graph` from the direction of its `rankdir`, top to bottom by default, to
+/// `direction`, and sets its `rankdir` to it.
+pub fn reorient(graph: &mut Graph, direction: RankDir) {
+    let from = graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::RankDir(direction) => Some(*direction),
+            _ => None,
+        })
+        .unwrap_or(RankDir::TopToBottom);
+    transform(graph, Transform::between(from, direction));
+    set_attribute(&mut graph.attributes, Attribute::RankDir(direction));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::parse_graph,
+        layout::layered::{self, LayeredOptions, Median},
+    };
+
+    #[test]
+    fn transforms() {
+        assert_eq!(Transform::rotate(4), Transform::IDENTITY);
+        assert_eq!(Transform::rotate(-1), Transform::rotate(3));
+        assert_eq!(
+            Transform::FLIP_X.then(Transform::FLIP_Y),
+            Transform::rotate(2)
+        );
+        for &direction in &[
+            RankDir::TopToBottom,
+            RankDir::BottomToTop,
+            RankDir::LeftToRight,
+            RankDir::RightToLeft,
+        ] {
+            let transform = Transform::from_rank_dir(direction);
+            assert_eq!(transform.then(transform.inverse()), Transform::IDENTITY);
+            assert_eq!(
+                transform.swaps_axes(),
+                matches!(direction, RankDir::LeftToRight | RankDir::RightToLeft)
+            );
+        }
+        assert_eq!(Transform::ROTATE.apply((1.0, 2.0)), (-2.0, 1.0));
+    }
+
+    #[test]
+    fn reorient() {
+        // square nodes, so that turning a drawing matches laying it out turned
+        let source = "digraph { node [width=0.5 height=0.5] a -> b -> c a -> c }";
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        let options = LayeredOptions::from_graph(&graph);
+        layered::layout(&mut graph, &options, &Median::default());
+        for &direction in &[
+            RankDir::LeftToRight,
+            RankDir::BottomToTop,
+            RankDir::RightToLeft,
+            RankDir::TopToBottom,
+        ] {
+            let mut expected = graph.clone();
+            let options = LayeredOptions {
+                direction,
+                ..LayeredOptions::from_graph(&expected)
+            };
+            layered::layout(&mut expected, &options, &Median::default());
+            super::reorient(&mut graph, direction);
+            for (node, expected) in graph.nodes().iter().zip(expected.nodes()) {
+                assert_eq!(node.position(), expected.position(), "{:?}", direction);
+            }
+            assert_eq!(graph.bounding_box(), expected.bounding_box());
+        }
+    }
+}
@@ -1,6 +1,85 @@
+//! Parsing, laying out and rendering graphs in the DOT language.
+//!
+//! The parser and the IR, [`ir`] with [`attribute`] and [`color`], are always built, and need
+//! only `alloc`. The rest is split into features, each building on the one before, so that
+//! programs only compile what they use:
+//!
+//! | feature      | adds                                                                    |
+//! |--------------|-------------------------------------------------------------------------|
+//! | `std`        | reading graphs from files and streams, and parsing large ones in chunks |
+//! | `model`      | the semantic model, algorithms, queries, scripts, and most formats      |
+//! | `layout`     | layout engines, fonts, and the drawings renderers share                 |
+//! | `render-svg` | SVG images, and HTML pages of them                                      |
+//! | `render`     | PDF, PostScript and image maps                                          |
+//! | `cli`        | the `simpledot` command, and all of the above; the default              |
+//!
+//! Subgraphs, and with them clusters, aren't supported yet: DOT using them doesn't parse, and
+//! importers flatten the nested graphs of other formats.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// the helpers the parser shares with the model go unused without it
+#![cfg_attr(not(feature = "model"), allow(dead_code))]
+
+extern crate alloc;
+
+#[cfg(feature = "model")]
+pub mod algorithm;
+#[cfg(feature = "std")]
 mod ast;
+// the syntax tree has no items of its own yet
+#[cfg(feature = "std")]
+#[allow(unused_imports)]
+pub use ast::*;
 pub mod attribute;
 pub mod color;
+#[cfg(feature = "std")]
+pub mod corpus;
+#[cfg(feature = "model")]
+pub(crate) mod csv;
+#[cfg(feature = "model")]
+pub mod diagnostic;
+#[cfg(feature = "model")]
+pub mod diff;
+#[cfg(feature = "model")]
+pub mod explore;
+#[cfg(feature = "model")]
+pub mod export;
+#[cfg(feature = "layout")]
+pub mod font;
+#[cfg(feature = "model")]
+pub mod format;
+#[cfg(feature = "model")]
+pub(crate) mod gml;
+#[cfg(feature = "model")]
+pub mod import;
 pub mod ir;
-pub use ast::*;
+#[cfg(feature = "model")]
+pub(crate) mod json;
+#[cfg(feature = "layout")]
+pub mod layout;
+#[cfg(feature = "model")]
+pub mod matrix;
+#[cfg(feature = "model")]
+pub mod model;
+#[cfg(feature = "layout")]
+pub mod pipeline;
+#[cfg(feature = "model")]
+pub mod prelude;
+#[cfg(feature = "layout")]
+pub mod render;
+pub(crate) mod scan;
+#[cfg(feature = "model")]
+pub mod script;
+#[cfg(feature = "model")]
+pub mod select;
+#[cfg(feature = "model")]
+pub mod stats;
+#[cfg(feature = "model")]
+pub(crate) mod toml;
+#[cfg(feature = "model")]
+pub mod validate;
 pub(crate) mod ws;
+#[cfg(feature = "model")]
+pub(crate) mod xml;
+#[cfg(feature = "model")]
+pub(crate) mod yaml;
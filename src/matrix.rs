@@ -0,0 +1,261 @@
+//! Adjacency matrix conversions, for handing graphs to numerical code.
+//!
+//! Entries are edge weights: the `weight` attribute of an edge, or 1 if it has none, summed over
+//! parallel edges. Both matrix kinds carry the node IDs in row (and column) order. The dense
+//! matrix is stored row-major, so it can be handed to e.g. `ndarray::Array2::from_shape_vec`
+//! without copying.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use thiserror::Error;
+
+use crate::{
+    attribute::Attribute,
+    csv,
+    ir::{GraphKind, Ident},
+    model::Graph,
+};
+
+#[derive(Debug, Error)]
+pub enum MatrixError {
+    #[error("unterminated quoted field starting at line {0}")]
+    UnterminatedQuote(usize),
+    #[error("expected header row listing the node IDs")]
+    MissingHeader,
+    #[error("row at line {line} doesn't match the header")]
+    InvalidRow { line: usize },
+    #[error("invalid matrix entry `{value}` at line {line}")]
+    InvalidValue { value: String, line: usize },
+}
+
+/// Dense adjacency matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdjacencyMatrix {
+    pub nodes: Vec<Ident>,
+    /// Entries in row-major order; the entry for the edge from node `i` to node `j` is at
+    /// `i * nodes.len() + j`.
+    pub values: Vec<f64>,
+}
+
+/// Sparse adjacency matrix in coordinate format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix {
+    pub nodes: Vec<Ident>,
+    /// Non-zero entries as `(row, column, value)`, sorted by row and then column.
+    pub entries: Vec<(usize, usize, f64)>,
+}
+
+fn edge_weight(attributes: &[Attribute]) -> f64 {
+    attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Weight(weight) => Some(*weight),
+            _ => None,
+        })
+        .unwrap_or(1.0)
+}
+
+/// Builds a graph with the given nodes and an edge per entry. For undirected graphs, only entries
+/// on or above the diagonal are read.
+fn build_graph(
+    nodes: &[Ident],
+    entries: impl Iterator<Item = (usize, usize, f64)>,
+    kind: GraphKind,
+) -> Graph {
+    let mut graph = Graph::new(kind, false);
+    for node in nodes {
+        graph.add_node(node);
+    }
+    for (row, column, value) in entries {
+        if value == 0.0 || (kind == GraphKind::Undirected && column < row) {
+            continue;
+        }
+        let attributes = if value == 1.0 {
+            vec![]
+        } else {
+            vec![Attribute::Weight(value)]
+        };
+        graph.add_edge(row, column, attributes);
+    }
+    graph
+}
+
+impl SparseMatrix {
+    /// Builds the adjacency matrix of `graph`. Undirected edges contribute to both `(i, j)` and
+    /// `(j, i)`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let mut entries = BTreeMap::new();
+        for edge in graph.edges() {
+            let weight = edge_weight(&edge.attributes);
+            *entries.entry((edge.tail, edge.head)).or_insert(0.0) += weight;
+            if !graph.is_directed() && edge.tail != edge.head {
+                *entries.entry((edge.head, edge.tail)).or_insert(0.0) += weight;
+            }
+        }
+        SparseMatrix {
+            nodes: graph.nodes().iter().map(|node| node.id.clone()).collect(),
+            entries: entries
+                .into_iter()
+                .filter(|(_, value)| *value != 0.0)
+                .map(|((row, column), value)| (row, column, value))
+                .collect(),
+        }
+    }
+
+    /// Builds a graph with an edge per non-zero entry, weighted by the entry unless it is 1.
+    pub fn to_graph(&self, kind: GraphKind) -> Graph {
+        build_graph(&self.nodes, self.entries.iter().copied(), kind)
+    }
+
+    pub fn to_dense(&self) -> AdjacencyMatrix {
+        let n = self.nodes.len();
+        let mut values = vec![0.0; n * n];
+        for &(row, column, value) in &self.entries {
+            values[row * n + column] = value;
+        }
+        AdjacencyMatrix {
+            nodes: self.nodes.clone(),
+            values,
+        }
+    }
+}
+
+impl AdjacencyMatrix {
+    /// Builds the adjacency matrix of `graph`. Undirected edges contribute to both `(i, j)` and
+    /// `(j, i)`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        SparseMatrix::from_graph(graph).to_dense()
+    }
+
+    /// Builds a graph with an edge per non-zero entry, weighted by the entry unless it is 1.
+    pub fn to_graph(&self, kind: GraphKind) -> Graph {
+        build_graph(&self.nodes, self.entries(), kind)
+    }
+
+    pub fn to_sparse(&self) -> SparseMatrix {
+        SparseMatrix {
+            nodes: self.nodes.clone(),
+            entries: self
+                .entries()
+                .filter(|(_, _, value)| *value != 0.0)
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        self.values[row * self.nodes.len() + column]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[f64]> {
+        self.values.chunks(self.nodes.len().max(1))
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        let n = self.nodes.len();
+        self.values
+            .iter()
+            .enumerate()
+            .map(move |(i, value)| (i / n, i % n, *value))
+    }
+
+    /// Writes the matrix as delimited text, with a header row and a leading column of node IDs.
+    pub fn write_csv<W: Write>(&self, mut writer: W, delimiter: char) -> io::Result<()> {
+        let mut header = vec![""];
+        header.extend(self.nodes.iter().map(String::as_str));
+        csv::write_record(&mut writer, &header, delimiter)?;
+        for (node, row) in self.nodes.iter().zip(self.rows()) {
+            let mut record = vec![node.clone()];
+            record.extend(row.iter().map(f64::to_string));
+            csv::write_record(&mut writer, &record, delimiter)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a matrix written by [`AdjacencyMatrix::write_csv`]. Rows must be in header order.
+    pub fn read_csv(input: &str, delimiter: char) -> Result<Self, MatrixError> {
+        let mut records = csv::parse(input, delimiter)
+            .map_err(MatrixError::UnterminatedQuote)?
+            .into_iter();
+        let (_, header) = records.next().ok_or(MatrixError::MissingHeader)?;
+        let nodes = header
+            .into_iter()
+            .skip(1)
+            .map(|id| id.trim().to_owned())
+            .collect::<Vec<_>>();
+        let mut values = Vec::with_capacity(nodes.len() * nodes.len());
+        let mut count = 0;
+        for (line, record) in records {
+            if count == nodes.len()
+                || record.len() != nodes.len() + 1
+                || record[0].trim() != nodes[count]
+            {
+                return Err(MatrixError::InvalidRow { line });
+            }
+            for value in &record[1..] {
+                let value = value.trim();
+                values.push(match value {
+                    "" => 0.0,
+                    value => value.parse().map_err(|_| MatrixError::InvalidValue {
+                        value: value.to_owned(),
+                        line,
+                    })?,
+                });
+            }
+            count += 1;
+        }
+        if count != nodes.len() {
+            return Err(MatrixError::InvalidRow {
+                line: input.lines().count() + 1,
+            });
+        }
+        Ok(AdjacencyMatrix { nodes, values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn conversions() {
+        let graph = Graph::from_ir(
+            &parse_graph("graph { a -- b [weight=2.5] b -- c b -- c a -- a d }").unwrap(),
+        );
+        let matrix = AdjacencyMatrix::from_graph(&graph);
+        assert_eq!(matrix.nodes, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            matrix.rows().collect::<Vec<_>>(),
+            vec![
+                &[1.0, 2.5, 0.0, 0.0][..],
+                &[2.5, 0.0, 2.0, 0.0],
+                &[0.0, 2.0, 0.0, 0.0],
+                &[0.0, 0.0, 0.0, 0.0],
+            ]
+        );
+        let sparse = matrix.to_sparse();
+        assert_eq!(sparse, SparseMatrix::from_graph(&graph));
+        assert_eq!(sparse.entries.len(), 5);
+        assert_eq!(sparse.to_dense(), matrix);
+
+        let mut output = vec![];
+        matrix.write_csv(&mut output, ',').unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().next(), Some(",a,b,c,d"));
+        let read = AdjacencyMatrix::read_csv(&output, ',').unwrap();
+        assert_eq!(read, matrix);
+
+        let graph = read.to_graph(GraphKind::Undirected);
+        assert_eq!(graph.nodes().len(), 4);
+        assert_eq!(graph.edges().len(), 3);
+        assert_eq!(graph.edges()[1].attributes, vec![Attribute::Weight(2.5)]);
+        assert_eq!(graph.edges()[2].attributes, vec![Attribute::Weight(2.0)]);
+        assert!(matches!(
+            AdjacencyMatrix::read_csv(",a,b\na,0,1\nc,1,0\n", ','),
+            Err(MatrixError::InvalidRow { line: 3 })
+        ));
+    }
+}
@@ -0,0 +1,734 @@
+//! Semantic graph model. Resolves the statements of the intermediate representation into nodes and
+//! edges carrying their effective attributes, with `node` and `edge` defaults applied.
+
+use std::{borrow::Cow, collections::HashMap, mem::discriminant, ops::Index};
+
+use crate::{
+    attribute::{
+        ArrowType, Attribute, ColorAttribute, DirType, Point, Position, Rectangle, Shape, Style,
+    },
+    color::Color,
+    ir::{
+        self, AttributeKind, AttributeStatement, EdgeOp, EdgeStatement, GraphKind, Ident,
+        NodeStatement, Statement,
+    },
+};
+
+/// Index of a node within a [`Graph`].
+pub type NodeIndex = usize;
+
+/// Default node width and height in inches.
+pub const DEFAULT_NODE_SIZE: (f64, f64) = (0.75, 0.5);
+pub const POINTS_PER_INCH: f64 = 72.0;
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    pub kind: GraphKind,
+    pub strict: bool,
+    pub id: Option<Ident>,
+    pub attributes: Vec<Attribute>,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    index: HashMap<Ident, NodeIndex>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub id: Ident,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub tail: NodeIndex,
+    pub head: NodeIndex,
+    pub attributes: Vec<Attribute>,
+}
+
+/// The attribute named `name` in DOT, such as `label`, if set.
+fn find<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attributes.iter().find(|attribute| attribute.name() == name)
+}
+
+fn get_or_default<'a>(
+    attributes: &'a [Attribute],
+    name: &str,
+    kind: &AttributeKind,
+) -> Option<Cow<'a, Attribute>> {
+    match find(attributes, name) {
+        Some(attribute) => Some(Cow::Borrowed(attribute)),
+        None => Attribute::default_value(name, kind).map(Cow::Owned),
+    }
+}
+
+/// A method giving the value of the attribute `$variant`, named `$attribute` in DOT, falling back
+/// to its default as [`Attribute::default_value`] gives it if not set: borrowed, or copied for
+/// numbers and flags. The `node` and `edge` defaults before a node or an edge are among its
+/// attributes.
+macro_rules! accessor {
+    ($name:ident: $variant:ident $attribute:literal -> &$ty:ty) => {
+        pub fn $name(&self) -> Option<Cow<'_, $ty>> {
+            match get_or_default(&self.attributes, $attribute, &Self::KIND)? {
+                Cow::Borrowed(Attribute::$variant(value)) => {
+                    let value: &$ty = value;
+                    Some(Cow::Borrowed(value))
+                }
+                Cow::Owned(Attribute::$variant(value)) => Some(Cow::Owned(value)),
+                _ => None,
+            }
+        }
+    };
+    ($name:ident: $variant:ident $attribute:literal -> $ty:ty) => {
+        pub fn $name(&self) -> Option<$ty> {
+            match get_or_default(&self.attributes, $attribute, &Self::KIND).as_deref() {
+                Some(Attribute::$variant(value)) => Some(*value),
+                _ => None,
+            }
+        }
+    };
+}
+
+impl Node {
+    const KIND: AttributeKind = AttributeKind::Node;
+
+    accessor!(label: Label "label" -> &str);
+    accessor!(xlabel: XLabel "xlabel" -> &str);
+    accessor!(shape: Shape "shape" -> &Shape);
+    accessor!(color: Color "color" -> &ColorAttribute);
+    accessor!(fill_color: FillColor "fillcolor" -> &ColorAttribute);
+    accessor!(font_color: FontColor "fontcolor" -> &Color);
+    accessor!(font_name: FontName "fontname" -> &str);
+    accessor!(font_size: FontSize "fontsize" -> f64);
+    accessor!(style: Style "style" -> &[Style]);
+    accessor!(pen_width: PenWidth "penwidth" -> f64);
+    accessor!(peripheries: Peripheries "peripheries" -> i64);
+    accessor!(image: Image "image" -> &str);
+    accessor!(tooltip: Tooltip "tooltip" -> &str);
+    accessor!(url: Url "URL" -> &str);
+    accessor!(comment: Comment "comment" -> &str);
+
+    /// The attribute of the node named `name` in DOT, such as `label`, if set.
+    pub fn get(&self, name: &str) -> Option<&Attribute> {
+        find(&self.attributes, name)
+    }
+
+    /// The attribute of the node named `name`, falling back to Graphviz's default for nodes, as
+    /// [`Attribute::default_value`] gives it, if not set.
+    pub fn get_or_default(&self, name: &str) -> Option<Cow<'_, Attribute>> {
+        get_or_default(&self.attributes, name, &Self::KIND)
+    }
+
+    /// Position of the center of the node in points, from its `pos` attribute.
+    pub fn position(&self) -> Option<&Point> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Pos(Position::Point(point) | Position::Pinned(point)) => Some(point),
+                _ => None,
+            })
+    }
+
+    /// Whether the node keeps its position through layout, from a `pos` ending in `!` or a true
+    /// `pin` attribute.
+    pub fn is_pinned(&self) -> bool {
+        self.position().is_some()
+            && self.attributes.iter().any(|attribute| {
+                matches!(
+                    attribute,
+                    Attribute::Pos(Position::Pinned(_)) | Attribute::Pin(true)
+                )
+            })
+    }
+
+    /// Width and height of the node in inches, from its `width` and `height` attributes.
+    pub fn size(&self) -> (f64, f64) {
+        let mut size = DEFAULT_NODE_SIZE;
+        for attribute in &self.attributes {
+            match attribute {
+                Attribute::Width(width) => size.0 = *width,
+                Attribute::Height(height) => size.1 = *height,
+                _ => {}
+            }
+        }
+        size
+    }
+}
+
+/// The attribute named `name`, as [`Node::get`] gives it; panics if the node doesn't set it.
+impl Index<&str> for Node {
+    type Output = Attribute;
+
+    fn index(&self, name: &str) -> &Attribute {
+        self.get(name)
+            .unwrap_or_else(|| panic!("node `{}` has no attribute `{}`", self.id, name))
+    }
+}
+
+impl Edge {
+    const KIND: AttributeKind = AttributeKind::Edge;
+
+    accessor!(label: Label "label" -> &str);
+    accessor!(xlabel: XLabel "xlabel" -> &str);
+    accessor!(head_label: HeadLabel "headlabel" -> &str);
+    accessor!(tail_label: TailLabel "taillabel" -> &str);
+    accessor!(color: Color "color" -> &ColorAttribute);
+    accessor!(font_color: FontColor "fontcolor" -> &Color);
+    accessor!(font_name: FontName "fontname" -> &str);
+    accessor!(font_size: FontSize "fontsize" -> f64);
+    accessor!(style: Style "style" -> &[Style]);
+    accessor!(pen_width: PenWidth "penwidth" -> f64);
+    accessor!(weight: Weight "weight" -> f64);
+    accessor!(min_len: MinLen "minlen" -> i64);
+    accessor!(constraint: Constraint "constraint" -> bool);
+    accessor!(arrow_head: ArrowHead "arrowhead" -> &ArrowType);
+    accessor!(arrow_tail: ArrowTail "arrowtail" -> &ArrowType);
+    accessor!(tooltip: Tooltip "tooltip" -> &str);
+    accessor!(url: Url "URL" -> &str);
+
+    /// The attribute of the edge named `name` in DOT, such as `label`, if set.
+    pub fn get(&self, name: &str) -> Option<&Attribute> {
+        find(&self.attributes, name)
+    }
+
+    /// The attribute of the edge named `name`, falling back to Graphviz's default for edges in
+    /// graphs of the given kind, as [`Attribute::default_value`] gives it, if not set; that of
+    /// `dir` is `none` in undirected graphs.
+    pub fn get_or_default(&self, name: &str, graph: GraphKind) -> Option<Cow<'_, Attribute>> {
+        if name == "dir" && graph == GraphKind::Undirected && self.get(name).is_none() {
+            return Some(Cow::Owned(Attribute::Dir(DirType::None)));
+        }
+        get_or_default(&self.attributes, name, &Self::KIND)
+    }
+
+    /// The `dir` of the edge, falling back to its default in graphs of the given kind, as
+    /// [`Edge::get_or_default`] gives it.
+    pub fn dir(&self, graph: GraphKind) -> Option<Cow<'_, DirType>> {
+        match self.get_or_default("dir", graph)? {
+            Cow::Borrowed(Attribute::Dir(dir)) => Some(Cow::Borrowed(dir)),
+            Cow::Owned(Attribute::Dir(dir)) => Some(Cow::Owned(dir)),
+            _ => None,
+        }
+    }
+}
+
+/// The attribute named `name`, as [`Edge::get`] gives it; panics if the edge doesn't set it.
+impl Index<&str> for Edge {
+    type Output = Attribute;
+
+    fn index(&self, name: &str) -> &Attribute {
+        self.get(name)
+            .unwrap_or_else(|| panic!("edge has no attribute `{}`", name))
+    }
+}
+
+/// Sets `attribute` in `attributes`, replacing any existing value for the same attribute.
+pub(crate) fn set_attribute(attributes: &mut Vec<Attribute>, attribute: Attribute) {
+    match attributes
+        .iter_mut()
+        .find(|existing| discriminant(*existing) == discriminant(&attribute))
+    {
+        Some(existing) => *existing = attribute,
+        None => attributes.push(attribute),
+    }
+}
+
+impl std::str::FromStr for Graph {
+    type Err = ir::OwnedGraphParseError;
+
+    /// Parses a graph from DOT and resolves its statements, as [`Graph::from_ir`] does.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ok(Graph::from_ir(&input.parse()?))
+    }
+}
+
+/// The node with ID `id`, as [`Graph::node`] gives it; panics if there is none, so that
+/// `graph["a"]["label"]` reads the label of node `a`.
+impl Index<&str> for Graph {
+    type Output = Node;
+
+    fn index(&self, id: &str) -> &Node {
+        self.node(id).unwrap_or_else(|| panic!("no node `{}`", id))
+    }
+}
+
+impl Graph {
+    pub fn new(kind: GraphKind, strict: bool) -> Self {
+        Graph {
+            kind,
+            strict,
+            id: None,
+            attributes: vec![],
+            nodes: vec![],
+            edges: vec![],
+            index: HashMap::new(),
+        }
+    }
+
+    /// Resolves the statements of a parsed graph. Node and edge attribute statements apply to
+    /// nodes and edges created after them, as in Graphviz.
+    pub fn from_ir(graph: &ir::Graph) -> Self {
+        let mut model = Graph::new(graph.kind, graph.strict);
+        model.id = graph.id.clone();
+        let mut node_defaults = vec![];
+        let mut edge_defaults = vec![];
+        for statement in &graph.statements {
+            match statement {
+                Statement::Attribute(statement) => {
+                    let target = match statement.kind {
+                        AttributeKind::Graph => &mut model.attributes,
+                        AttributeKind::Node => &mut node_defaults,
+                        AttributeKind::Edge => &mut edge_defaults,
+                    };
+                    for attribute in &statement.attributes {
+                        set_attribute(target, attribute.clone());
+                    }
+                }
+                Statement::Node(statement) => {
+                    let node = model.add_node_with_defaults(&statement.name, &node_defaults);
+                    for attribute in &statement.attributes {
+                        set_attribute(&mut model.nodes[node].attributes, attribute.clone());
+                    }
+                }
+                Statement::Edge(statement) => {
+                    for (index, pair) in statement.list.windows(2).enumerate() {
+                        let tail = model.add_node_with_defaults(&pair[0], &node_defaults);
+                        let head = model.add_node_with_defaults(&pair[1], &node_defaults);
+                        let mut attributes = edge_defaults.clone();
+                        for attribute in &statement.attributes {
+                            set_attribute(&mut attributes, attribute.clone());
+                        }
+                        // ports written with the nodes, as `tail:port -> head:port`
+                        let port = |index: usize, name| {
+                            let port = statement.ports.get(index)?.as_ref()?;
+                            Attribute::from_pair(name, port).ok()
+                        };
+                        let ports = port(index, "tailport").into_iter();
+                        for port in ports.chain(port(index + 1, "headport")) {
+                            set_attribute(&mut attributes, port);
+                        }
+                        model.add_edge(tail, head, attributes);
+                    }
+                }
+                Statement::Definition(statement) => {
+                    // definitions which don't name a known attribute have no effect
+                    if let Ok(attribute) = Attribute::from_pair(&statement.lhs, &statement.rhs) {
+                        set_attribute(&mut model.attributes, attribute);
+                    }
+                }
+            }
+        }
+        model
+    }
+
+    /// Writes the graph back as statements: a `graph` attribute statement, then one statement per
+    /// node and per edge, each with its effective attributes.
+    pub fn to_ir(&self) -> ir::Graph {
+        let mut statements = vec![];
+        if !self.attributes.is_empty() {
+            statements.push(Statement::Attribute(AttributeStatement {
+                kind: AttributeKind::Graph,
+                attributes: self.attributes.clone().into(),
+            }));
+        }
+        statements.extend(self.nodes.iter().map(|node| {
+            Statement::Node(NodeStatement {
+                name: node.id.clone(),
+                attributes: node.attributes.clone().into(),
+            })
+        }));
+        let op = match self.kind {
+            GraphKind::Directed => EdgeOp::Directed,
+            GraphKind::Undirected => EdgeOp::Undirected,
+        };
+        statements.extend(self.edges.iter().map(|edge| {
+            Statement::Edge(EdgeStatement {
+                list: vec![
+                    self.nodes[edge.tail].id.clone(),
+                    self.nodes[edge.head].id.clone(),
+                ],
+                ports: vec![None, None],
+                ops: vec![op],
+                attributes: edge.attributes.clone().into(),
+            })
+        }));
+        ir::Graph {
+            kind: self.kind,
+            strict: self.strict,
+            id: self.id.clone(),
+            statements,
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        matches!(self.kind, GraphKind::Directed)
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    pub fn node_index(&self, id: &str) -> Option<NodeIndex> {
+        self.index.get(id).copied()
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.node_index(id).map(|index| &self.nodes[index])
+    }
+
+    pub fn node_mut(&mut self, index: NodeIndex) -> &mut Node {
+        &mut self.nodes[index]
+    }
+
+    pub fn edge_mut(&mut self, index: usize) -> &mut Edge {
+        &mut self.edges[index]
+    }
+
+    /// Adds a node with the given ID, returning the index of the existing node if already
+    /// present.
+    pub fn add_node(&mut self, id: &str) -> NodeIndex {
+        self.add_node_with_defaults(id, &[])
+    }
+
+    fn add_node_with_defaults(&mut self, id: &str, defaults: &[Attribute]) -> NodeIndex {
+        if let Some(index) = self.node_index(id) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            id: id.to_owned(),
+            attributes: defaults.to_vec(),
+        });
+        self.index.insert(id.to_owned(), index);
+        index
+    }
+
+    /// Adds an edge between two existing nodes. In strict graphs, an edge duplicating an existing
+    /// one is merged into it instead, and the index of the existing edge is returned.
+    pub fn add_edge(
+        &mut self,
+        tail: NodeIndex,
+        head: NodeIndex,
+        attributes: Vec<Attribute>,
+    ) -> usize {
+        if self.strict {
+            if let Some(existing) = self.find_edge(tail, head) {
+                for attribute in attributes {
+                    set_attribute(&mut self.edges[existing].attributes, attribute);
+                }
+                return existing;
+            }
+        }
+        self.edges.push(Edge {
+            tail,
+            head,
+            attributes,
+        });
+        self.edges.len() - 1
+    }
+
+    /// Removes the edges for which `keep` is false, given their index, keeping the order of the
+    /// others.
+    pub fn retain_edges(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let mut index = 0;
+        self.edges.retain(|_| {
+            index += 1;
+            keep(index - 1)
+        });
+    }
+
+    /// The subgraph of the given nodes and edges, along with the ends of the edges, keeping graph
+    /// attributes and the order of nodes and edges.
+    pub fn subgraph(
+        &self,
+        nodes: impl IntoIterator<Item = NodeIndex>,
+        edges: impl IntoIterator<Item = usize>,
+    ) -> Graph {
+        let mut keep_nodes = vec![false; self.nodes.len()];
+        let mut keep_edges = vec![false; self.edges.len()];
+        for node in nodes {
+            keep_nodes[node] = true;
+        }
+        for edge in edges {
+            keep_edges[edge] = true;
+            keep_nodes[self.edges[edge].tail] = true;
+            keep_nodes[self.edges[edge].head] = true;
+        }
+        let mut subgraph = Graph::new(self.kind, self.strict);
+        subgraph.id = self.id.clone();
+        subgraph.attributes = self.attributes.clone();
+        for (node, _) in self.nodes.iter().zip(keep_nodes).filter(|(_, keep)| *keep) {
+            let index = subgraph.add_node(&node.id);
+            subgraph.nodes[index].attributes = node.attributes.clone();
+        }
+        for (edge, _) in self.edges.iter().zip(keep_edges).filter(|(_, keep)| *keep) {
+            let tail = subgraph.index[&self.nodes[edge.tail].id];
+            let head = subgraph.index[&self.nodes[edge.head].id];
+            subgraph.add_edge(tail, head, edge.attributes.clone());
+        }
+        subgraph
+    }
+
+    /// The subgraph of the given nodes with all edges between them.
+    pub fn induced_subgraph(&self, nodes: impl IntoIterator<Item = NodeIndex>) -> Graph {
+        let mut keep = vec![false; self.nodes.len()];
+        for node in nodes {
+            keep[node] = true;
+        }
+        let edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| keep[edge.tail] && keep[edge.head])
+            .map(|(index, _)| index);
+        self.subgraph((0..self.nodes.len()).filter(|&node| keep[node]), edges)
+    }
+
+    /// The subgraph of the nodes for which `keep` is true with all edges between them. Nodes and
+    /// edges keep the attributes they have from the defaults of the graph they came from.
+    pub fn induced_subgraph_by(&self, mut keep: impl FnMut(&Node) -> bool) -> Graph {
+        let nodes = (0..self.nodes.len()).filter(|&node| keep(&self.nodes[node]));
+        self.induced_subgraph(nodes.collect::<Vec<_>>())
+    }
+
+    /// Bounding box of the nodes in points, or `None` if any node has no position.
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        let mut bb: Option<Rectangle> = None;
+        for node in &self.nodes {
+            let pos = node.position()?;
+            let (width, height) = node.size();
+            let (dx, dy) = (
+                width * POINTS_PER_INCH / 2.0,
+                height * POINTS_PER_INCH / 2.0,
+            );
+            let (lower_left, upper_right) = match &bb {
+                Some(bb) => (&bb.lower_left, &bb.upper_right),
+                None => (pos, pos),
+            };
+            bb = Some(Rectangle {
+                lower_left: Point {
+                    x: lower_left.x.min(pos.x - dx),
+                    y: lower_left.y.min(pos.y - dy),
+                },
+                upper_right: Point {
+                    x: upper_right.x.max(pos.x + dx),
+                    y: upper_right.y.max(pos.y + dy),
+                },
+            });
+        }
+        Some(bb.unwrap_or(Rectangle {
+            lower_left: Point { x: 0.0, y: 0.0 },
+            upper_right: Point { x: 0.0, y: 0.0 },
+        }))
+    }
+
+    /// Finds the first edge from `tail` to `head` (in either direction for undirected graphs).
+    pub fn find_edge(&self, tail: NodeIndex, head: NodeIndex) -> Option<usize> {
+        self.edges.iter().position(|edge| {
+            (edge.tail == tail && edge.head == head)
+                || (!self.is_directed() && edge.tail == head && edge.head == tail)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attribute::{RankDir, Shape},
+        ir::parse_graph,
+    };
+
+    #[test]
+    fn resolve() {
+        let graph = Graph::from_ir(
+            &parse_graph("strict graph { a node [shape=box] a -- b b -- a [shape=circle] c }")
+                .unwrap(),
+        );
+        let ids = graph
+            .nodes()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert!(graph.node("a").unwrap().attributes.is_empty());
+        assert_eq!(
+            graph.node("b").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Box)]
+        );
+        assert_eq!(graph.edges().len(), 1);
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Shape(Shape::Circle)]
+        );
+
+        let parsed = "strict graph { a node [shape=box] a -- b b -- a [shape=circle] c }"
+            .parse::<Graph>()
+            .unwrap();
+        assert_eq!(parsed.edges().len(), 1);
+        assert!("graph { a -- }".parse::<Graph>().is_err());
+    }
+
+    #[test]
+    fn index() {
+        let graph = "digraph { a [label=\"A\", shape=box] a -> b [weight=2] }"
+            .parse::<Graph>()
+            .unwrap();
+        assert_eq!(graph["a"]["label"], Attribute::Label("A".to_owned()));
+        assert_eq!(graph["a"]["shape"], Attribute::Shape(Shape::Box));
+        assert_eq!(graph["b"].get("label"), None);
+        assert_eq!(graph.edges()[0]["weight"], Attribute::Weight(2.0));
+        assert!(std::panic::catch_unwind(|| &graph["c"]).is_err());
+    }
+
+    #[test]
+    fn accessors() {
+        let graph = "digraph { node [shape=box] a [label=\"A\", style=\"filled,bold\"] \
+                     a -> b [weight=2, dir=back, color=red] }"
+            .parse::<Graph>()
+            .unwrap();
+        let (a, b) = (&graph["a"], &graph["b"]);
+        assert_eq!(a.label().as_deref(), Some("A"));
+        assert_eq!(a.shape().as_deref(), Some(&Shape::Box));
+        assert_eq!(b.shape().as_deref(), Some(&Shape::Box));
+        assert_eq!(
+            a.style().as_deref(),
+            Some(&[Style::Filled, Style::Bold][..])
+        );
+        let edge = &graph.edges()[0];
+        assert_eq!(edge.weight(), Some(2.0));
+        assert_eq!(edge.dir(graph.kind).as_deref(), Some(&DirType::Back));
+        assert_eq!(
+            edge.color().map(|color| color.to_string()).as_deref(),
+            Some("red")
+        );
+
+        // unset attributes have Graphviz's defaults
+        let graph = "graph { a -- b }".parse::<Graph>().unwrap();
+        let a = &graph["a"];
+        assert_eq!(a.label().as_deref(), Some("\\N"));
+        assert_eq!(a.shape().as_deref(), Some(&Shape::Ellipse));
+        assert_eq!(a.style().as_deref(), Some(&[][..]));
+        assert_eq!(a.font_name().as_deref(), Some("Times-Roman"));
+        assert_eq!(a.font_size(), Some(14.0));
+        assert_eq!(a.peripheries(), Some(1));
+        assert_eq!(
+            a.fill_color().map(|color| color.to_string()).as_deref(),
+            Some("lightgrey")
+        );
+        let edge = &graph.edges()[0];
+        assert_eq!(edge.label().as_deref(), Some(""));
+        assert_eq!(
+            edge.color().map(|color| color.to_string()).as_deref(),
+            Some("black")
+        );
+        assert_eq!(edge.weight(), Some(1.0));
+        assert_eq!(edge.min_len(), Some(1));
+        assert_eq!(edge.constraint(), Some(true));
+        assert_eq!(edge.arrow_head().as_deref(), Some(&ArrowType::Normal));
+        assert_eq!(edge.dir(graph.kind).as_deref(), Some(&DirType::None));
+    }
+
+    #[test]
+    fn defaults() {
+        let graph = "digraph { a [shape=box] a -> b [label=x] }"
+            .parse::<Graph>()
+            .unwrap();
+        let (a, b) = (&graph["a"], &graph["b"]);
+        let shape = |node: &Node| node.get_or_default("shape").map(Cow::into_owned);
+        assert_eq!(shape(a), Some(Attribute::Shape(Shape::Box)));
+        assert_eq!(shape(b), Some(Attribute::Shape(Shape::default())));
+        assert_eq!(
+            b.get_or_default("fontsize").as_deref(),
+            Some(&Attribute::FontSize(14.0))
+        );
+        assert_eq!(
+            b.get_or_default("label").as_deref(),
+            Some(&Attribute::Label("\\N".to_owned()))
+        );
+        assert_eq!(b.get_or_default("arrowhead"), None);
+        assert_eq!(b.get_or_default("pos"), None);
+        let edge = &graph.edges()[0];
+        assert_eq!(
+            edge.get_or_default("label", graph.kind).as_deref(),
+            Some(&Attribute::Label("x".to_owned()))
+        );
+        assert_eq!(
+            edge.get_or_default("arrowhead", graph.kind).as_deref(),
+            Some(&Attribute::ArrowHead(ArrowType::Normal))
+        );
+        assert_eq!(
+            edge.get_or_default("fillcolor", graph.kind)
+                .map(|fill| fill.value_string()),
+            Some("black".to_owned())
+        );
+        assert_eq!(
+            Attribute::default_value("fillcolor", &AttributeKind::Node)
+                .map(|fill| fill.value_string()),
+            Some("lightgrey".to_owned())
+        );
+        assert_eq!(
+            Attribute::default_value("rankdir", &AttributeKind::Graph),
+            Some(Attribute::RankDir(RankDir::TopToBottom))
+        );
+        assert_eq!(
+            Attribute::default_value("nonsense", &AttributeKind::Graph),
+            None
+        );
+        // edges point forward by default only in directed graphs
+        let dir = |graph: &Graph| {
+            let edge = &graph.edges()[0];
+            edge.get_or_default("dir", graph.kind).map(Cow::into_owned)
+        };
+        assert_eq!(dir(&graph), Some(Attribute::Dir(DirType::Forward)));
+        let undirected = "graph { a -- b }".parse::<Graph>().unwrap();
+        assert_eq!(dir(&undirected), Some(Attribute::Dir(DirType::None)));
+        let back = "graph { a -- b [dir=back] }".parse::<Graph>().unwrap();
+        assert_eq!(dir(&back), Some(Attribute::Dir(DirType::Back)));
+        // structured defaults are spelled as DOT parses them
+        for name in [
+            "pagedir",
+            "ranksep",
+            "headport",
+            "orientation",
+            "pad",
+            "packmode",
+        ] {
+            let kind = if name == "headport" {
+                AttributeKind::Edge
+            } else {
+                AttributeKind::Graph
+            };
+            let default = Attribute::default_value(name, &kind).unwrap();
+            assert_eq!(
+                Attribute::from_pair(name, &default.value_string()),
+                Ok(default)
+            );
+        }
+    }
+
+    #[test]
+    fn induced_subgraph_by() {
+        let graph = Graph::from_ir(
+            &parse_graph("digraph { node [shape=box] a -> b -> c a -> c [style=dashed] d }")
+                .unwrap(),
+        );
+        let subgraph = graph.induced_subgraph_by(|node| node.id != "b");
+        let ids = subgraph
+            .nodes()
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "c", "d"]);
+        assert_eq!(
+            subgraph.node("d").unwrap().attributes,
+            vec![Attribute::Shape(Shape::Box)]
+        );
+        assert_eq!(subgraph.edges().len(), 1);
+        let edge = &subgraph.edges()[0];
+        assert_eq!((edge.tail, edge.head), (0, 1));
+    }
+}
@@ -0,0 +1,710 @@
+//! Processing pipelines: reading graphs, transforming them and writing the result, as described
+//! by a `simpledot.toml` configuration:
+//!
+//! ```toml
+//! [input]
+//! files = ["graphs/*.dot"]
+//! format = "dot"          # from each file's extension by default
+//! validate = true         # stop on graphs with errors
+//!
+//! [[transform]]
+//! type = "filter"
+//! remove = "node[style=invis]"
+//!
+//! [[transform]]
+//! type = "unflatten"
+//! stagger = 3
+//!
+//! [output]
+//! format = "json"         # from the path's extension by default, or DOT
+//! path = "build/{name}.json"
+//! ```
+//!
+//! Transforms run in order, with their options named as in the matching commands:
+//!
+//! | type         | options                                                     |
+//! |--------------|-------------------------------------------------------------|
+//! | `acyclic`    |                                                             |
+//! | `tred`       |                                                             |
+//! | `reverse`    |                                                             |
+//! | `complement` |                                                             |
+//! | `closure`    | `style`, of the edges added                                 |
+//! | `spanning`   | `weight`, the edge attribute; `style`, of the tree's edges  |
+//! | `unflatten`  | `stagger`, `fans`, `chain`                                  |
+//! | `sccmap`     |                                                             |
+//! | `contract`   | `attribute`, whose values name groups of nodes to collapse  |
+//! | `centrality` | `measure`, `width`, `fill` (or `false`)                     |
+//! | `bipartite`  | `fill`, a color for each side                               |
+//! | `filter`     | `seeds`, `hops` (or `"all"`), `direction`, `keep`, `remove` |
+//! | `script`     | `source`                                                    |
+//! | `theme`      | `theme`, a built-in theme's name or a theme file's path     |
+//! | `layout`     | `engine`, from the graph's `layout` attribute by default    |
+//!
+//! `{name}` in the output path stands for the name of each input file without its extension.
+//! Without an output path, or with one without `{name}`, all graphs are written to the same
+//! output.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use crate::{
+    algorithm::{
+        acyclic::make_acyclic,
+        bipartite::{bipartition, OddCycleError},
+        centrality::{Centrality, Heatmap},
+        closure::transitive_closure,
+        complement::complement,
+        contract::contract_by_attribute,
+        neighborhood::{neighborhood_subgraph, Direction},
+        path::{Cost, PathError},
+        reduction::transitive_reduction,
+        reverse::reverse,
+        scc::condensation,
+        spanning::{mark_spanning_tree, spanning_subgraph},
+        unflatten::{unflatten, UnflattenOptions},
+    },
+    attribute::{Attribute, ColorAttribute, Style},
+    color::{parse_color, Color, Rgb, Rgba},
+    diagnostic::{ConfigError, Diagnostic, DiagnosticOptions},
+    format::{self, Format},
+    json::JsonValue,
+    layout::{self, LayoutError, ENGINES},
+    model::Graph,
+    render::theme::Theme,
+    script::{Script, ScriptError},
+    select::Selector,
+    toml, validate,
+};
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("unknown format of `{0}`")]
+    UnknownFormat(String),
+    #[error("{0} can't be read")]
+    Unreadable(&'static str),
+    #[error("{0} can't be written")]
+    Unwritable(&'static str),
+    /// The input failed to read.
+    #[error("{0}")]
+    Input(Diagnostic),
+    /// The graph has errors, and the pipeline validates its input.
+    #[error("graph has {} errors", .0.len())]
+    Invalid(Vec<Diagnostic>),
+    #[error("no node `{0}`")]
+    UnknownNode(String),
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error(transparent)]
+    OddCycle(#[from] OddCycleError),
+    #[error("script error: {0}")]
+    Script(#[from] ScriptError),
+    #[error(transparent)]
+    Layout(#[from] LayoutError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// One step of a [`Pipeline`], transforming a graph like the command of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transform {
+    Acyclic,
+    Tred,
+    /// Turns the edges of digraphs around.
+    Reverse,
+    Complement,
+    /// Adds the edges of the transitive closure, with the given attributes.
+    Closure(Vec<Attribute>),
+    /// Keeps only the edges of the minimum spanning tree by the given edge attribute, or the
+    /// number of edges, or else gives them the given attributes.
+    Spanning {
+        weight: Option<String>,
+        attributes: Vec<Attribute>,
+    },
+    Unflatten(UnflattenOptions),
+    Sccmap,
+    /// Collapses the nodes sharing the value of the attribute of this name into single nodes.
+    Contract(String),
+    /// Sets the width or fill color of nodes by their centrality.
+    Centrality(Centrality, Heatmap),
+    /// Splits the nodes in two sides, failing on graphs with odd cycles, and fills them with a
+    /// color for each side.
+    Bipartite {
+        fill: Option<(Rgb, Rgb)>,
+    },
+    /// Takes the neighborhood of the seeds, if any, then keeps and removes elements.
+    Filter {
+        seeds: Vec<String>,
+        hops: usize,
+        direction: Direction,
+        keep: Option<Selector>,
+        remove: Option<Selector>,
+    },
+    Script(Script),
+    /// Gives the graph, its nodes and its edges the attributes of the theme they don't have.
+    Theme(Theme),
+    /// Lays the graph out with the given engine, or else the one of its `layout` attribute.
+    Layout(Option<String>),
+}
+
+impl Transform {
+    pub fn apply(&self, mut graph: Graph) -> Result<Graph, PipelineError> {
+        match self {
+            Transform::Acyclic => {
+                make_acyclic(&mut graph);
+            }
+            Transform::Tred => {
+                transitive_reduction(&mut graph);
+            }
+            Transform::Reverse => reverse(&mut graph),
+            Transform::Complement => graph = complement(&graph),
+            Transform::Closure(attributes) => {
+                transitive_closure(&mut graph, attributes);
+            }
+            Transform::Spanning { weight, attributes } => {
+                let cost = weight.as_deref().map_or(Cost::Hops, Cost::Attribute);
+                if attributes.is_empty() {
+                    graph = spanning_subgraph(&graph, cost)?;
+                } else {
+                    mark_spanning_tree(&mut graph, cost, attributes)?;
+                }
+            }
+            Transform::Unflatten(options) => unflatten(&mut graph, options),
+            Transform::Sccmap => graph = condensation(&graph).graph,
+            Transform::Contract(attribute) => {
+                graph = contract_by_attribute(&graph, attribute).graph
+            }
+            Transform::Centrality(measure, heatmap) => {
+                let scores = measure.scores(&graph);
+                heatmap.apply(&mut graph, &scores);
+            }
+            Transform::Bipartite { fill } => {
+                let bipartition = bipartition(&graph)?;
+                if let Some((first, second)) = fill {
+                    let filled = |color: &Rgb| {
+                        [
+                            Attribute::FillColor(ColorAttribute::Color(Color::Rgb(color.clone()))),
+                            Attribute::Style(vec![Style::Filled]),
+                        ]
+                    };
+                    bipartition.mark(&mut graph, &filled(first), &filled(second));
+                }
+            }
+            Transform::Filter {
+                seeds,
+                hops,
+                direction,
+                keep,
+                remove,
+            } => {
+                if !seeds.is_empty() {
+                    let seeds = seeds
+                        .iter()
+                        .map(|seed| {
+                            graph
+                                .node_index(seed)
+                                .ok_or_else(|| PipelineError::UnknownNode(seed.clone()))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    graph = neighborhood_subgraph(&graph, &seeds, *hops, *direction);
+                }
+                if let Some(keep) = keep {
+                    graph = keep.select(&graph);
+                }
+                if let Some(remove) = remove {
+                    graph = remove.remove(&graph);
+                }
+            }
+            Transform::Script(script) => script.run(&mut graph)?,
+            Transform::Theme(theme) => theme.apply(&mut graph),
+            Transform::Layout(engine) => layout::layout(&mut graph, engine.as_deref())?,
+        }
+        Ok(graph)
+    }
+
+    fn from_config(key: &str, table: &JsonValue) -> Result<Self, ConfigError> {
+        let table = table.as_object().ok_or_else(|| ConfigError::InvalidValue {
+            key: key.to_owned(),
+            expected: "table",
+        })?;
+        let mut kind = None;
+        let mut unflatten = UnflattenOptions::default();
+        let (mut seeds, mut hops, mut direction) = (vec![], 1, Direction::Both);
+        let (mut keep, mut remove, mut source, mut theme) = (None, None, None, None);
+        let (mut engine, mut style, mut weight) = (None, None, None);
+        let (mut measure, mut heatmap) = (Centrality::Degree, Heatmap::default());
+        let (mut fill, mut attribute) = (None, None);
+        for (name, value) in table {
+            let invalid = |expected| ConfigError::InvalidValue {
+                key: format!("{}.{}", key, name),
+                expected,
+            };
+            let count = || {
+                value
+                    .as_f64()
+                    .filter(|count| *count >= 0.0 && count.fract() == 0.0)
+                    .map(|count| count as usize)
+                    .ok_or_else(|| invalid("non-negative integer"))
+            };
+            let selector = || {
+                value
+                    .as_str()
+                    .and_then(|selector| selector.parse::<Selector>().ok())
+                    .ok_or_else(|| invalid("selector"))
+            };
+            match name.as_str() {
+                "type" => kind = Some(value.as_str().ok_or_else(|| invalid("string"))?),
+                "stagger" => unflatten.stagger = Some(count()?),
+                "fans" => unflatten.fans = value.as_bool().ok_or_else(|| invalid("boolean"))?,
+                "chain" => unflatten.chain = Some(count()?),
+                "seeds" => {
+                    seeds = value
+                        .as_array()
+                        .and_then(|seeds| {
+                            seeds
+                                .iter()
+                                .map(|seed| seed.as_str().map(str::to_owned))
+                                .collect()
+                        })
+                        .ok_or_else(|| invalid("array of strings"))?
+                }
+                "hops" if value.as_str() == Some("all") => hops = usize::MAX,
+                "hops" => hops = count()?,
+                "direction" => {
+                    direction = match value.as_str() {
+                        Some("out") => Direction::Out,
+                        Some("in") => Direction::In,
+                        Some("both") => Direction::Both,
+                        _ => return Err(invalid("\"out\", \"in\" or \"both\"")),
+                    }
+                }
+                "keep" => keep = Some(selector()?),
+                "remove" => remove = Some(selector()?),
+                "source" => {
+                    source = Some(
+                        value
+                            .as_str()
+                            .and_then(|source| source.parse::<Script>().ok())
+                            .ok_or_else(|| invalid("script"))?,
+                    )
+                }
+                "theme" => {
+                    theme = Some(
+                        value
+                            .as_str()
+                            .and_then(|theme| Theme::load(theme).ok())
+                            .ok_or_else(|| invalid("theme name or file"))?,
+                    )
+                }
+                "style" => {
+                    style = Some(
+                        value
+                            .as_str()
+                            .and_then(|style| Attribute::from_pair("style", style).ok())
+                            .ok_or_else(|| invalid("edge style"))?,
+                    )
+                }
+                "measure" => {
+                    measure = value
+                        .as_str()
+                        .and_then(Centrality::from_name)
+                        .ok_or_else(|| invalid("\"degree\", \"betweenness\" or \"pagerank\""))?
+                }
+                "width" => {
+                    heatmap.width = match value.as_array() {
+                        Some([low, high]) => low.as_f64().zip(high.as_f64()),
+                        _ => None,
+                    }
+                    .filter(|(low, high)| *low > 0.0 && *high > 0.0)
+                    .map(Some)
+                    .ok_or_else(|| invalid("array of two positive numbers"))?
+                }
+                "fill" if value.as_bool() == Some(false) => fill = Some(None),
+                "fill" => {
+                    let color = |color: &JsonValue| {
+                        color
+                            .as_str()
+                            .and_then(parse_color)
+                            .and_then(|color| color.to_rgba())
+                            .map(|Rgba { r, g, b, .. }| Rgb { r, g, b })
+                    };
+                    let colors = match value.as_array() {
+                        Some([low, high]) => color(low).zip(color(high)),
+                        _ => None,
+                    };
+                    fill = Some(Some(
+                        colors.ok_or_else(|| invalid("array of two colors, or false"))?,
+                    ))
+                }
+                "attribute" => {
+                    attribute = Some(value.as_str().ok_or_else(|| invalid("string"))?.to_owned())
+                }
+                "weight" => {
+                    weight = Some(value.as_str().ok_or_else(|| invalid("string"))?.to_owned())
+                }
+                "engine" => {
+                    engine = Some(
+                        value
+                            .as_str()
+                            .filter(|engine| ENGINES.contains(engine))
+                            .ok_or_else(|| invalid("layout engine"))?
+                            .to_owned(),
+                    )
+                }
+                _ => return Err(ConfigError::UnknownKey(format!("{}.{}", key, name))),
+            }
+        }
+        // options of other transforms are unknown keys
+        let mut options = table
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| *name != "type");
+        let allowed: &[&str] = match kind {
+            Some("closure") => &["style"],
+            Some("spanning") => &["weight", "style"],
+            Some("contract") => &["attribute"],
+            Some("centrality") => &["measure", "width", "fill"],
+            Some("bipartite") => &["fill"],
+            Some("unflatten") => &["stagger", "fans", "chain"],
+            Some("filter") => &["seeds", "hops", "direction", "keep", "remove"],
+            Some("script") => &["source"],
+            Some("theme") => &["theme"],
+            Some("layout") => &["engine"],
+            _ => &[],
+        };
+        if let Some(name) = options.find(|name| !allowed.contains(name)) {
+            return Err(ConfigError::UnknownKey(format!("{}.{}", key, name)));
+        }
+        Ok(match kind {
+            Some("acyclic") => Transform::Acyclic,
+            Some("tred") => Transform::Tred,
+            Some("reverse") => Transform::Reverse,
+            Some("complement") => Transform::Complement,
+            Some("closure") => Transform::Closure(style.into_iter().collect()),
+            Some("spanning") => Transform::Spanning {
+                weight,
+                attributes: style.into_iter().collect(),
+            },
+            Some("unflatten") => Transform::Unflatten(unflatten),
+            Some("sccmap") => Transform::Sccmap,
+            Some("contract") => {
+                Transform::Contract(attribute.ok_or(ConfigError::InvalidValue {
+                    key: format!("{}.attribute", key),
+                    expected: "string",
+                })?)
+            }
+            Some("centrality") => {
+                if let Some(fill) = fill {
+                    heatmap.fill = fill;
+                }
+                Transform::Centrality(measure, heatmap)
+            }
+            Some("bipartite") => Transform::Bipartite {
+                fill: fill.flatten(),
+            },
+            Some("filter") => Transform::Filter {
+                seeds,
+                hops,
+                direction,
+                keep,
+                remove,
+            },
+            Some("script") => Transform::Script(source.ok_or(ConfigError::InvalidValue {
+                key: format!("{}.source", key),
+                expected: "script",
+            })?),
+            Some("theme") => Transform::Theme(theme.ok_or(ConfigError::InvalidValue {
+                key: format!("{}.theme", key),
+                expected: "theme name or file",
+            })?),
+            Some("layout") => Transform::Layout(engine),
+            _ => {
+                return Err(ConfigError::InvalidValue {
+                    key: format!("{}.type", key),
+                    expected: "transform type",
+                })
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    /// Input files or glob patterns.
+    pub files: Vec<String>,
+    /// The input format, or `None` to tell it from each file's extension.
+    pub from: Option<&'static Format>,
+    /// Whether to check graphs and stop on errors before transforming them.
+    pub validate: bool,
+    pub transforms: Vec<Transform>,
+    /// The output format, or `None` to tell it from the output path's extension, falling back to
+    /// DOT.
+    pub to: Option<&'static Format>,
+    /// The output path, where `{name}` stands for the input file name without its extension, or
+    /// `None` to write to a single output.
+    pub output: Option<String>,
+}
+
+impl Pipeline {
+    /// Reads a pipeline from a TOML configuration, as described in the
+    /// [module documentation](self).
+    pub fn from_config(input: &str) -> Result<Self, ConfigError> {
+        let root = toml::parse(input).map_err(ConfigError::Syntax)?;
+        let mut pipeline = Pipeline::default();
+        for (key, value) in root.as_object().unwrap_or_default() {
+            let invalid = |key: &str, expected| ConfigError::InvalidValue {
+                key: key.to_owned(),
+                expected,
+            };
+            let format = |key: &str, value: &JsonValue| {
+                value
+                    .as_str()
+                    .and_then(format::by_name)
+                    .ok_or_else(|| invalid(key, "format name"))
+            };
+            match key.as_str() {
+                "input" => {
+                    for (name, value) in value.as_object().ok_or_else(|| invalid(key, "table"))? {
+                        let key = format!("input.{}", name);
+                        match name.as_str() {
+                            "files" => {
+                                pipeline.files = value
+                                    .as_array()
+                                    .and_then(|files| {
+                                        files
+                                            .iter()
+                                            .map(|file| file.as_str().map(str::to_owned))
+                                            .collect()
+                                    })
+                                    .ok_or_else(|| invalid(&key, "array of strings"))?
+                            }
+                            "format" => pipeline.from = Some(format(&key, value)?),
+                            "validate" => {
+                                pipeline.validate =
+                                    value.as_bool().ok_or_else(|| invalid(&key, "boolean"))?
+                            }
+                            _ => return Err(ConfigError::UnknownKey(key)),
+                        }
+                    }
+                }
+                "transform" => {
+                    let transforms = value
+                        .as_array()
+                        .ok_or_else(|| invalid(key, "array of tables"))?;
+                    for (index, transform) in transforms.iter().enumerate() {
+                        let key = format!("transform[{}]", index);
+                        pipeline
+                            .transforms
+                            .push(Transform::from_config(&key, transform)?);
+                    }
+                }
+                "output" => {
+                    for (name, value) in value.as_object().ok_or_else(|| invalid(key, "table"))? {
+                        let key = format!("output.{}", name);
+                        match name.as_str() {
+                            "format" => pipeline.to = Some(format(&key, value)?),
+                            "path" => {
+                                pipeline.output = Some(
+                                    value
+                                        .as_str()
+                                        .ok_or_else(|| invalid(&key, "string"))?
+                                        .to_owned(),
+                                )
+                            }
+                            _ => return Err(ConfigError::UnknownKey(key)),
+                        }
+                    }
+                }
+                _ => return Err(ConfigError::UnknownKey(key.clone())),
+            }
+        }
+        Ok(pipeline)
+    }
+
+    /// Reads the graph in `input`, the contents of `file`, validating it if the pipeline does.
+    pub fn read(&self, file: &str, input: &str) -> Result<Graph, PipelineError> {
+        let from = self
+            .from
+            .or_else(|| format::by_extension(file))
+            .ok_or_else(|| PipelineError::UnknownFormat(file.to_owned()))?;
+        let read = from.read.ok_or(PipelineError::Unreadable(from.name))?;
+        let graph = read(input).map_err(PipelineError::Input)?;
+        if self.validate {
+            let diagnostics = validate::validate(&graph.to_ir(), DiagnosticOptions::default());
+            if diagnostics.has_errors() {
+                return Err(PipelineError::Invalid(diagnostics.into_vec()));
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Applies the transforms to `graph`, in order.
+    pub fn transform(&self, graph: Graph) -> Result<Graph, PipelineError> {
+        self.transforms
+            .iter()
+            .try_fold(graph, |graph, transform| transform.apply(graph))
+    }
+
+    /// The output path for the graph of `file`, if the pipeline has one.
+    pub fn output_path(&self, file: &str) -> Option<String> {
+        let name = Path::new(file)
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or(file);
+        Some(self.output.as_ref()?.replace("{name}", name))
+    }
+
+    pub fn output_format(&self) -> Result<&'static Format, PipelineError> {
+        let to = self
+            .to
+            .or_else(|| self.output.as_deref().and_then(format::by_extension))
+            .unwrap_or(&format::FORMATS[0]);
+        to.write.ok_or(PipelineError::Unwritable(to.name))?;
+        Ok(to)
+    }
+
+    /// Runs the whole pipeline on `input`, the contents of `file`, writing the result to
+    /// `writer`.
+    pub fn run<W: Write>(
+        &self,
+        file: &str,
+        input: &str,
+        mut writer: W,
+    ) -> Result<(), PipelineError> {
+        let write = self.output_format()?.write.unwrap();
+        let graph = self.transform(self.read(file, input)?)?;
+        write(&graph, &mut writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pipeline() {
+        let pipeline = Pipeline::from_config(
+            r#"
+[input]
+files = ["a.dot"]
+validate = true
+
+[[transform]]
+type = "filter"
+remove = "node[color=red]"
+
+[[transform]]
+type = "tred"
+
+[output]
+path = "out/{name}.json"
+"#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.files, vec!["a.dot"]);
+        assert_eq!(pipeline.transforms.len(), 2);
+        assert_eq!(pipeline.output_path("in/a.dot").unwrap(), "out/a.json");
+        assert_eq!(pipeline.output_format().unwrap().name, "json");
+
+        let graph = pipeline
+            .read(
+                "a.dot",
+                "digraph { a -> b -> c a -> c x [color=red] x -> a }",
+            )
+            .unwrap();
+        let graph = pipeline.transform(graph).unwrap();
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.edges().len(), 2);
+        assert!(matches!(
+            pipeline.read("a.dot", "digraph {"),
+            Err(PipelineError::Input(_))
+        ));
+        assert!(matches!(
+            pipeline.read("a.txt", "digraph {}"),
+            Err(PipelineError::UnknownFormat(_))
+        ));
+
+        assert!(matches!(
+            Pipeline::from_config("[[transform]]\ntype = \"tred\"\nstagger = 2\n"),
+            Err(ConfigError::UnknownKey(key)) if key == "transform[0].stagger"
+        ));
+        let closure =
+            Pipeline::from_config("[[transform]]\ntype = \"closure\"\nstyle = \"dashed\"\n");
+        assert_eq!(
+            closure.unwrap().transforms,
+            [Transform::Closure(vec![Attribute::from_pair(
+                "style", "dashed"
+            )
+            .unwrap()])]
+        );
+        let spanning =
+            Pipeline::from_config("[[transform]]\ntype = \"spanning\"\nweight = \"weight\"\n")
+                .unwrap();
+        let graph = crate::ir::parse_graph("graph { a -- b [weight=3] b -- c a -- c }").unwrap();
+        let tree = spanning.transforms[0]
+            .apply(Graph::from_ir(&graph))
+            .unwrap();
+        assert_eq!(tree.edges().len(), 2);
+        assert!(tree.find_edge(0, 1).is_none());
+        let centrality = Pipeline::from_config(
+            "[[transform]]\ntype = \"centrality\"\nmeasure = \"pagerank\"\n\
+             width = [1, 3]\nfill = false\n",
+        );
+        assert_eq!(
+            centrality.unwrap().transforms,
+            [Transform::Centrality(
+                Centrality::PageRank,
+                Heatmap {
+                    width: Some((1.0, 3.0)),
+                    fill: None
+                }
+            )]
+        );
+        assert!(matches!(
+            Pipeline::from_config("[[transform]]\ntype = \"centrality\"\nfill = [\"red\"]\n"),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "transform[0].fill"
+        ));
+        let contract = Pipeline::from_config("[[transform]]\ntype = \"contract\"\n");
+        assert!(matches!(
+            contract,
+            Err(ConfigError::InvalidValue { key, .. }) if key == "transform[0].attribute"
+        ));
+        let bipartite = Pipeline::from_config(
+            "[[transform]]\ntype = \"bipartite\"\nfill = [\"white\", \"#808080\"]\n",
+        )
+        .unwrap();
+        let graph = crate::ir::parse_graph("graph { a -- b }").unwrap();
+        let graph = bipartite.transforms[0]
+            .apply(Graph::from_ir(&graph))
+            .unwrap();
+        assert_eq!(
+            graph.node("b").unwrap().attributes,
+            [
+                Attribute::from_pair("fillcolor", "#808080").unwrap(),
+                Attribute::from_pair("style", "filled").unwrap()
+            ]
+        );
+        let graph = crate::ir::parse_graph("graph { a -- b -- c -- a }").unwrap();
+        assert!(matches!(
+            bipartite.transforms[0].apply(Graph::from_ir(&graph)),
+            Err(PipelineError::OddCycle(_))
+        ));
+        assert!(matches!(
+            Pipeline::from_config("[output]\nformat = \"svgz\"\n"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        let themed = Pipeline::from_config("[[transform]]\ntype = \"theme\"\ntheme = \"dark\"\n");
+        assert_eq!(
+            themed.unwrap().transforms,
+            [Transform::Theme(Theme::named("dark").unwrap())]
+        );
+        assert!(matches!(
+            Pipeline::from_config("[[transform]]\ntype = \"theme\"\ntheme = \"no-such\"\n"),
+            Err(ConfigError::InvalidValue { key, .. }) if key == "transform[0].theme"
+        ));
+    }
+}
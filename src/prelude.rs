@@ -0,0 +1,37 @@
+//! The types and functions most programs need, in one import: `use simpledot::prelude::*;`.
+//!
+//! Graphs are parsed from DOT into the semantic model with `str::parse`, or read as written with
+//! [`parse_graph`] and resolved with [`Graph::from_ir`], built with [`Graph::new`],
+//! [`Graph::add_node`] and [`Graph::add_edge`], and laid out with [`layout`]. Writing them out is
+//! left to the [`export`](crate::export) and [`render`](crate::render) modules, whose writers
+//! share names across formats.
+
+#[cfg(feature = "layout")]
+pub use crate::layout::{layout, LayoutError};
+pub use crate::{
+    attribute::{ArrowType, Attribute, DirType, RankDir, Shape, Style},
+    color::Color,
+    ir::{parse_graph, GraphKind, GraphParseError, OwnedGraphParseError},
+    model::{Edge, Graph, Node, NodeIndex},
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prelude() {
+        let mut graph = "digraph { a [shape=box] a -> b }".parse::<Graph>().unwrap();
+        assert_eq!(graph.kind, GraphKind::Directed);
+        assert_eq!(graph["a"].shape().as_deref(), Some(&Shape::Box));
+        let c = graph.add_node("c");
+        graph.add_edge(0, c, vec![Attribute::Style(vec![Style::Dashed])]);
+        #[cfg(feature = "layout")]
+        {
+            layout(&mut graph, None).unwrap();
+            assert!(graph.nodes().iter().all(|node| node.position().is_some()));
+        }
+        let parsed = Graph::from_ir(&parse_graph("graph { a -- b }").unwrap());
+        assert_eq!(parsed.edges().len(), 1);
+    }
+}
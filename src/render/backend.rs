@@ -0,0 +1,527 @@
+//! Output formats as backends drawing a few primitives, and the layout of drawings on pages they
+//! share.
+//!
+//! A backend implements [`RenderBackend`]: it's told when the document, its pages and the
+//! elements on them start and end, which colors and line to paint with, and which paths and text
+//! to draw, all in the coordinates of the page with the y axis pointing down. [`render`] makes
+//! these calls for a [`Drawing`], laid on pages by a [`Layout`] following the graph's attributes:
+//!
+//! - a numeric `ratio` stretches the drawing across or down until its height is that many times
+//!   its width; with a `size`, `ratio=fill` stretches it to fill the size, and `ratio=expand`
+//!   scales it up until it fits the size across or down;
+//! - the drawing is then scaled down to fit the graph's `size` if it's larger;
+//! - `rotate=90`, `landscape=true` or `orientation=landscape` turn it a quarter counterclockwise;
+//! - formats with pages split it over pages of the graph's `page` size, each holding the part of
+//!   the drawing which fits within its `margin`s, in the order of the graph's `pagedir`, `BL` by
+//!   default: from the bottom left, left to right, then upwards. `center=true` centers a drawing
+//!   which fits across or down a page. Without a `page` size, the page is the size of the drawing
+//!   and its margins.
+//!
+//! Lines and text are scaled by the smaller of the scales across and down, so that stretching a
+//! drawing spreads it out rather than distorting its labels.
+
+use std::io;
+
+use crate::{
+    attribute::{
+        Attribute, HorizontalDir, Orientation, PageDir, Point, Ratio, TraversalDir, VerticalDir,
+    },
+    color::Rgba,
+    model::{Graph, POINTS_PER_INCH},
+};
+
+use super::{Drawing, Element, Item, Primitive, Text};
+
+/// A shape to draw, in the coordinates of the page.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    Ellipse {
+        center: (f64, f64),
+        radii: (f64, f64),
+    },
+    /// A closed polygon through its corners.
+    Polygon(Vec<(f64, f64)>),
+    /// Cubic Bézier curves through the first point and every third after it, the points in
+    /// between being control points.
+    Bezier(Vec<(f64, f64)>),
+}
+
+/// What a color is set for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Fill,
+    Stroke,
+}
+
+/// The colors and line paths are drawn with, for backends to keep track of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pen {
+    pub fill: Rgba,
+    pub stroke: Rgba,
+    /// Width of lines in points.
+    pub width: f64,
+    /// Lengths in points of alternating dashes and gaps, empty for a solid line.
+    pub dashes: Vec<f64>,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Pen {
+            fill: Rgba::BLACK,
+            stroke: Rgba::BLACK,
+            width: 1.0,
+            dashes: vec![],
+        }
+    }
+}
+
+impl Pen {
+    pub fn set_color(&mut self, paint: Paint, color: Rgba) {
+        match paint {
+            Paint::Fill => self.fill = color,
+            Paint::Stroke => self.stroke = color,
+        }
+    }
+
+    pub fn set_line(&mut self, width: f64, dashes: &[f64]) {
+        self.width = width;
+        self.dashes = dashes.to_vec();
+    }
+}
+
+/// The document a drawing is rendered as.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document<'a> {
+    /// The ID of the graph.
+    pub name: &'a str,
+    pub pages: usize,
+    /// Size of the pages in points.
+    pub size: (f64, f64),
+    /// The top left corner and size of the part of the pages within their margins.
+    pub area: ((f64, f64), (f64, f64)),
+    /// Whether the pages have the graph's `page` size, rather than the size of the drawing.
+    pub paged: bool,
+}
+
+/// A page of a document, and where the drawing is on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// The number of the page, from 0.
+    pub index: usize,
+    /// Size in points.
+    pub size: (f64, f64),
+    /// The top left corner and size of the part of the page within its margins, which the
+    /// drawing is clipped to.
+    pub area: ((f64, f64), (f64, f64)),
+    /// The affine map from drawing to page coordinates, `(a, b, c, d, e, f)` taking `(x, y)` to
+    /// `(a x + c y + e, b x + d y + f)`.
+    matrix: [f64; 6],
+    /// The factor lengths are scaled by.
+    scale: f64,
+}
+
+impl Page {
+    /// Where the point of the drawing at `(x, y)` is on the page.
+    pub fn point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.matrix;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Whether the drawing is turned a quarter on the page.
+    pub fn rotated(&self) -> bool {
+        self.matrix[0] == 0.0
+    }
+
+    /// `length` in the drawing, such as the width of a line, on the page.
+    pub fn length(&self, length: f64) -> f64 {
+        length * self.scale
+    }
+
+    fn path(&self, shape: &Primitive) -> Option<Path> {
+        let points =
+            |points: &[(f64, f64)]| points.iter().map(|&point| self.point(point)).collect();
+        Some(match shape {
+            Primitive::Ellipse { center, radii } => {
+                let [a, b, c, d, ..] = self.matrix;
+                let radii = if self.rotated() {
+                    (radii.1 * c.abs(), radii.0 * b.abs())
+                } else {
+                    (radii.0 * a.abs(), radii.1 * d.abs())
+                };
+                Path::Ellipse {
+                    center: self.point(*center),
+                    radii,
+                }
+            }
+            Primitive::Polygon(corners) => Path::Polygon(points(corners)),
+            Primitive::Bezier(curves) => Path::Bezier(points(curves)),
+            Primitive::Text(_) => return None,
+        })
+    }
+
+    fn text(&self, text: &Text) -> Text {
+        Text {
+            position: self.point(text.position),
+            size: self.length(text.size),
+            width: self.length(text.width),
+            ..text.clone()
+        }
+    }
+}
+
+/// A format drawings are rendered in.
+///
+/// [`render`] calls [`begin`](RenderBackend::begin) first, then for each page
+/// [`begin_page`](RenderBackend::begin_page), the drawing of each element between
+/// [`begin_element`](RenderBackend::begin_element) and
+/// [`end_element`](RenderBackend::end_element), and [`end_page`](RenderBackend::end_page), and
+/// [`finish`](RenderBackend::finish) last.
+pub trait RenderBackend {
+    fn begin(&mut self, document: &Document) -> io::Result<()>;
+
+    fn begin_page(&mut self, page: &Page) -> io::Result<()>;
+
+    /// Starts the shapes of a node, edge or graph.
+    fn begin_element(&mut self, _element: &Element) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Sets the color paths are filled or stroked with.
+    fn set_color(&mut self, paint: Paint, color: Rgba) -> io::Result<()>;
+
+    /// Sets the width in points and the dashes of the lines paths are stroked with.
+    fn set_line(&mut self, width: f64, dashes: &[f64]) -> io::Result<()>;
+
+    /// Draws `path`, filling it, then stroking its outline, as asked.
+    fn draw_path(&mut self, path: &Path, fill: bool, stroke: bool) -> io::Result<()>;
+
+    /// Draws `text`, in its own color, turned `angle` degrees counterclockwise around its
+    /// position.
+    fn draw_text(&mut self, text: &Text, angle: f64) -> io::Result<()>;
+
+    fn end_element(&mut self, _element: &Element) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn end_page(&mut self, page: &Page) -> io::Result<()>;
+
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// How a drawing is laid on pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// The scales of the drawing across and down.
+    pub scale: (f64, f64),
+    /// Whether the drawing is turned a quarter counterclockwise.
+    pub rotate: bool,
+    /// Margins across and down in points.
+    pub margin: (f64, f64),
+    /// The size of the pages the drawing is split over, if any.
+    pub page: Option<(f64, f64)>,
+    pub center: bool,
+    pub order: PageDir,
+    /// The color the drawing is laid on, if any.
+    pub background: Option<Rgba>,
+}
+
+impl Layout {
+    /// The layout of `drawing`, the drawing of `graph`, following its attributes, with margins
+    /// of `margin` points if the graph has no `margin`, and split over pages of its `page` size
+    /// if `paged`.
+    pub fn new(graph: &Graph, drawing: &Drawing, margin: f64, paged: bool) -> Self {
+        let mut layout = Layout {
+            scale: (1.0, 1.0),
+            rotate: false,
+            margin: (margin, margin),
+            page: None,
+            center: false,
+            order: PageDir {
+                primary: TraversalDir::Vertical(VerticalDir::BottomToTop),
+                secondary: TraversalDir::Horizontal(HorizontalDir::LeftToRight),
+            },
+            background: drawing.background,
+        };
+        let inches = |point: &Point| (point.x * POINTS_PER_INCH, point.y * POINTS_PER_INCH);
+        let (mut size, mut ratio) = (None, None);
+        for attribute in &graph.attributes {
+            match attribute {
+                Attribute::Size(value) => size = Some(inches(value)),
+                Attribute::Ratio(value) => ratio = Some(value),
+                Attribute::Margin(value) => layout.margin = inches(value),
+                Attribute::Page(value) if paged => layout.page = Some(inches(value)),
+                Attribute::PageDir(value) => {
+                    // only row or column orders
+                    let (primary, secondary) = (&value.primary, &value.secondary);
+                    if matches!(primary, TraversalDir::Vertical(_))
+                        != matches!(secondary, TraversalDir::Vertical(_))
+                    {
+                        layout.order = value.clone();
+                    }
+                }
+                Attribute::Rotate(angle) => layout.rotate = *angle == 90,
+                Attribute::Landscape(landscape) => layout.rotate = *landscape,
+                Attribute::Orientation(Orientation::Landscape) => layout.rotate = true,
+                Attribute::Center(center) => layout.center = *center,
+                _ => {}
+            }
+        }
+        let (width, height) = (drawing.width, drawing.height);
+        if width <= 0.0 || height <= 0.0 {
+            return layout;
+        }
+        let size = size.filter(|&(width, height)| width > 0.0 && height > 0.0);
+        let scale = &mut layout.scale;
+        match (ratio, size) {
+            (Some(Ratio::Numeric(ratio)), _) if *ratio > 0.0 => {
+                let actual = height / width;
+                if actual < *ratio {
+                    scale.1 = ratio / actual;
+                } else {
+                    scale.0 = actual / ratio;
+                }
+            }
+            (Some(Ratio::Fill), Some((across, down))) => *scale = (across / width, down / height),
+            (Some(Ratio::Expand), Some((across, down))) if width < across && height < down => {
+                let factor = f64::min(across / width, down / height);
+                *scale = (factor, factor);
+            }
+            _ => {}
+        }
+        if let Some((across, down)) = size {
+            let factor = f64::min(
+                1.0,
+                f64::min(across / (width * scale.0), down / (height * scale.1)),
+            );
+            *scale = (scale.0 * factor, scale.1 * factor);
+        }
+        layout
+    }
+
+    /// The size of the drawing on the page.
+    pub fn size(&self, drawing: &Drawing) -> (f64, f64) {
+        let (width, height) = (drawing.width * self.scale.0, drawing.height * self.scale.1);
+        if self.rotate {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// The size of the part of a page which is drawn on.
+    fn area(&self, drawing: &Drawing) -> (f64, f64) {
+        match self.page {
+            Some((width, height)) => (
+                (width - 2.0 * self.margin.0).max(1.0),
+                (height - 2.0 * self.margin.1).max(1.0),
+            ),
+            None => self.size(drawing),
+        }
+    }
+
+    /// The size of the pages.
+    fn page_size(&self, drawing: &Drawing) -> (f64, f64) {
+        self.page.unwrap_or_else(|| {
+            let (width, height) = self.size(drawing);
+            (width + 2.0 * self.margin.0, height + 2.0 * self.margin.1)
+        })
+    }
+
+    /// The pages `drawing` is drawn on, in order.
+    pub fn pages(&self, drawing: &Drawing) -> Vec<Page> {
+        let ((width, height), (across, down)) = (self.size(drawing), self.area(drawing));
+        let page = self.page_size(drawing);
+        let columns = (width / across).ceil().max(1.0) as usize;
+        let rows = (height / down).ceil().max(1.0) as usize;
+        let along = |direction: &TraversalDir, count: usize| {
+            let mut steps = (0..count).collect::<Vec<_>>();
+            if matches!(
+                direction,
+                TraversalDir::Vertical(VerticalDir::TopToBottom)
+                    | TraversalDir::Horizontal(HorizontalDir::RightToLeft)
+            ) {
+                steps.reverse();
+            }
+            steps
+        };
+        // columns and rows counted from the bottom left
+        let mut order = vec![];
+        match &self.order.primary {
+            TraversalDir::Vertical(_) => {
+                for row in along(&self.order.primary, rows) {
+                    for column in along(&self.order.secondary, columns) {
+                        order.push((column, row));
+                    }
+                }
+            }
+            TraversalDir::Horizontal(_) => {
+                for column in along(&self.order.primary, columns) {
+                    for row in along(&self.order.secondary, rows) {
+                        order.push((column, row));
+                    }
+                }
+            }
+        }
+        // the offset of a drawing centered on pages it fits on
+        let offset = |length: f64, area: f64| {
+            if self.center && length < area {
+                (area - length) / 2.0
+            } else {
+                0.0
+            }
+        };
+        let (sx, sy) = self.scale;
+        let turn = if self.rotate {
+            [0.0, -sx, sy, 0.0, 0.0, height]
+        } else {
+            [sx, 0.0, 0.0, sy, 0.0, 0.0]
+        };
+        let (margin_x, margin_y) = self.margin;
+        let top = page.1 - margin_y - down;
+        order
+            .into_iter()
+            .enumerate()
+            .map(|(index, (column, row))| {
+                let left = margin_x + offset(width, across) - column as f64 * across;
+                let bottom = margin_y + offset(height, down) - row as f64 * down;
+                let [a, b, c, d, e, f] = turn;
+                Page {
+                    index,
+                    size: page,
+                    area: ((margin_x, top), (across, down)),
+                    matrix: [a, b, c, d, e + left, f + page.1 - bottom - height],
+                    scale: sx.min(sy),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Draws `item` on `page`.
+fn item(backend: &mut dyn RenderBackend, page: &Page, item: &Item) -> io::Result<()> {
+    let path = match page.path(&item.shape) {
+        Some(path) => path,
+        None => {
+            if let Primitive::Text(text) = &item.shape {
+                let angle = if page.rotated() { 90.0 } else { 0.0 };
+                backend.draw_text(&page.text(text), angle)?;
+            }
+            return Ok(());
+        }
+    };
+    if let Some(fill) = item.fill {
+        backend.set_color(Paint::Fill, fill)?;
+    }
+    if let Some(stroke) = &item.stroke {
+        backend.set_color(Paint::Stroke, stroke.color)?;
+        let dashes = stroke.dashes.iter().map(|&length| page.length(length));
+        let dashes = dashes.collect::<Vec<_>>();
+        backend.set_line(page.length(stroke.width), &dashes)?;
+    }
+    if item.fill.is_some() || item.stroke.is_some() {
+        backend.draw_path(&path, item.fill.is_some(), item.stroke.is_some())?;
+    }
+    Ok(())
+}
+
+/// Renders `drawing`, the drawing of the graph named `name`, with `backend`, on the pages of
+/// `layout`.
+pub fn render(
+    drawing: &Drawing,
+    layout: &Layout,
+    name: &str,
+    backend: &mut dyn RenderBackend,
+) -> io::Result<()> {
+    let pages = layout.pages(drawing);
+    backend.begin(&Document {
+        name,
+        pages: pages.len(),
+        size: pages[0].size,
+        area: pages[0].area,
+        paged: layout.page.is_some(),
+    })?;
+    let background = layout.background.map(|color| Item {
+        shape: Primitive::Polygon(vec![
+            (0.0, 0.0),
+            (drawing.width, 0.0),
+            (drawing.width, drawing.height),
+            (0.0, drawing.height),
+        ]),
+        stroke: None,
+        fill: Some(color),
+    });
+    for page in &pages {
+        backend.begin_page(page)?;
+        if let Some(background) = &background {
+            item(backend, page, background)?;
+        }
+        for element in &drawing.elements {
+            backend.begin_element(element)?;
+            for shape in &element.items {
+                item(backend, page, shape)?;
+            }
+            backend.end_element(element)?;
+        }
+        backend.end_page(page)?;
+    }
+    backend.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn layout(attributes: &str, paged: bool) -> (Layout, Drawing) {
+        let graph = Graph::from_ir(&parse_graph(&format!("digraph {{ {} }}", attributes)).unwrap());
+        let drawing = Drawing {
+            width: 200.0,
+            height: 100.0,
+            background: None,
+            elements: vec![],
+        };
+        (Layout::new(&graph, &drawing, 10.0, paged), drawing)
+    }
+
+    #[test]
+    fn scale() {
+        assert_eq!(layout("size=\"1,10\"", false).0.scale, (0.36, 0.36));
+        assert_eq!(layout("size=\"10,10\"", false).0.scale, (1.0, 1.0));
+        assert_eq!(layout("ratio=1", false).0.scale, (1.0, 2.0));
+        assert_eq!(layout("ratio=0.25", false).0.scale, (2.0, 1.0));
+        assert_eq!(
+            layout("ratio=fill size=\"1,2\"", false).0.scale,
+            (0.36, 1.44)
+        );
+        assert_eq!(
+            layout("ratio=expand size=\"4,4\"", false).0.scale,
+            (1.44, 1.44)
+        );
+        // stretched, then scaled down to fit
+        assert_eq!(layout("ratio=1 size=\"1,10\"", false).0.scale, (0.36, 0.72));
+    }
+
+    #[test]
+    fn pages() {
+        let (single, drawing) = layout("page=\"2,2\" rotate=90", false);
+        let pages = single.pages(&drawing);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].size, (120.0, 220.0));
+        // turned counterclockwise, the top left corner of the drawing goes to the bottom left
+        assert_eq!(pages[0].point((0.0, 0.0)), (10.0, 210.0));
+        assert_eq!(pages[0].point((200.0, 0.0)), (10.0, 10.0));
+
+        // 200 points across pages with 124 points between their margins, from the top left
+        let (paged, drawing) = layout("page=\"2,2\" pagedir=TL", true);
+        let pages = paged.pages(&drawing);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].area, ((10.0, 10.0), (124.0, 124.0)));
+        assert_eq!(pages[0].point((0.0, 0.0)), (10.0, 34.0));
+        assert_eq!(pages[1].point((124.0, 0.0)), (10.0, 34.0));
+
+        let (centered, drawing) = layout("page=\"4,4\" center=true", true);
+        let pages = centered.pages(&drawing);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].point((0.0, 0.0)), (44.0, 94.0));
+    }
+}
@@ -0,0 +1,253 @@
+//! Client-side image maps of laid out graphs, as Graphviz's `-Tcmapx`: an HTML `<map>` with an
+//! `<area>` for each node, edge and graph with a `URL` or `tooltip`, for the raster image of the
+//! graph at the same `dpi`.
+//!
+//! Areas are scaled and turned with the image, as [`backend`](super::backend) describes. Nodes
+//! get the outline of their shape, as a rectangle or a polygon, and edges a polygon a few
+//! points wide around their spline. Browsers use the first area under the pointer, so areas are
+//! listed from the top of the drawing down: nodes, then edges, then the graph.
+
+use std::io::{self, Write};
+
+use crate::{export::graphml::escape, model::Graph};
+
+use super::{
+    backend::Layout, dpi, draw, ellipse, flatten, Drawing, Element, ElementKind, Primitive,
+};
+
+/// Half the width of the areas of edges, in points.
+const EDGE_WIDTH: f64 = 3.0;
+
+/// Length in points of the pieces edges and ellipses are approximated with.
+const FLATNESS: f64 = 8.0;
+
+/// Writes the image map of `graph`, which must be laid out.
+pub fn write(graph: &Graph, writer: &mut dyn Write) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let name = graph.id.as_deref().unwrap_or("G");
+    let layout = Layout::new(graph, &drawing, 0.0, false);
+    write_drawing(&drawing, &layout, name, dpi(graph), writer)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Area {
+    Rectangle((f64, f64), (f64, f64)),
+    Polygon(Vec<(f64, f64)>),
+}
+
+/// The rectangle around the shapes of `element`.
+fn bounds(element: &Element) -> Option<Area> {
+    let (mut left, mut top) = (f64::INFINITY, f64::INFINITY);
+    let (mut right, mut bottom) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    let mut add = |(x, y): (f64, f64)| {
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x);
+        bottom = bottom.max(y);
+    };
+    for item in &element.items {
+        match &item.shape {
+            Primitive::Ellipse { center, radii } => {
+                add((center.0 - radii.0, center.1 - radii.1));
+                add((center.0 + radii.0, center.1 + radii.1));
+            }
+            Primitive::Polygon(points) | Primitive::Bezier(points) => {
+                points.iter().copied().for_each(&mut add)
+            }
+            Primitive::Text(text) => {
+                let half = text.width / 2.0;
+                let (x, y) = text.position;
+                add((x - half, y - text.size));
+                add((x + half, y));
+            }
+        }
+    }
+    (left <= right).then_some(Area::Rectangle((left, top), (right, bottom)))
+}
+
+/// The area of a node: its outermost outline, or else the rectangle around its shapes.
+fn node_area(element: &Element) -> Option<Area> {
+    let outline = element.items.iter().find(|item| item.stroke.is_some());
+    match outline.map(|item| &item.shape) {
+        Some(Primitive::Polygon(points)) => {
+            let (a, c) = (points[0], points[2 % points.len()]);
+            let rectangle = points.len() == 4
+                && points[1].0 == c.0
+                && points[1].1 == a.1
+                && points[3].0 == a.0
+                && points[3].1 == c.1;
+            Some(if rectangle {
+                Area::Rectangle((a.0.min(c.0), a.1.min(c.1)), (a.0.max(c.0), a.1.max(c.1)))
+            } else {
+                Area::Polygon(points.clone())
+            })
+        }
+        Some(Primitive::Ellipse { center, radii }) => {
+            let mut points = flatten(&ellipse(*center, *radii), FLATNESS);
+            // without the end, which is the start again
+            points.pop();
+            Some(Area::Polygon(points))
+        }
+        _ => bounds(element),
+    }
+}
+
+/// The area of an edge: a band around its line, or the outline of a tapered one.
+fn edge_area(element: &Element) -> Option<Area> {
+    let line = element.items.iter().find_map(|item| match &item.shape {
+        Primitive::Bezier(points) => Some(Ok(flatten(points, FLATNESS))),
+        Primitive::Polygon(outline) => Some(Err(outline.clone())),
+        _ => None,
+    })?;
+    let line = match line {
+        Ok(line) => line,
+        Err(outline) => return Some(Area::Polygon(outline)),
+    };
+    let normal = |(ax, ay): (f64, f64), (bx, by): (f64, f64)| {
+        let length = (bx - ax).hypot(by - ay).max(f64::EPSILON);
+        (
+            -(by - ay) / length * EDGE_WIDTH,
+            (bx - ax) / length * EDGE_WIDTH,
+        )
+    };
+    let mut sides = (vec![], vec![]);
+    for (index, &point) in line.iter().enumerate() {
+        let (from, to) = (
+            line[index.saturating_sub(1)],
+            line[(index + 1).min(line.len() - 1)],
+        );
+        let (nx, ny) = normal(from, to);
+        sides.0.push((point.0 + nx, point.1 + ny));
+        sides.1.push((point.0 - nx, point.1 - ny));
+    }
+    sides.0.extend(sides.1.into_iter().rev());
+    Some(Area::Polygon(sides.0))
+}
+
+/// Writes the image map of `drawing`, the drawing of the graph `name`, for its image laid out as
+/// `layout` at `dpi`.
+pub fn write_drawing(
+    drawing: &Drawing,
+    layout: &Layout,
+    name: &str,
+    dpi: f64,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let scale = dpi / 72.0;
+    let page = &layout.pages(drawing)[0];
+    let pixel = |&(x, y): &(f64, f64)| format!("{},{}", (x * scale).round(), (y * scale).round());
+    writeln!(writer, "<map id=\"{0}\" name=\"{0}\">", escape(name))?;
+    // numbered as in SVG images
+    let count = |kind| {
+        drawing
+            .elements
+            .iter()
+            .filter(|element| element.kind == kind)
+            .count()
+    };
+    let (mut nodes, mut edges) = (count(ElementKind::Node), count(ElementKind::Edge));
+    for element in drawing.elements.iter().rev() {
+        let (id, area) = match element.kind {
+            ElementKind::Node => {
+                nodes -= 1;
+                (format!("node{}", nodes + 1), node_area(element))
+            }
+            ElementKind::Edge => {
+                edges -= 1;
+                (format!("edge{}", edges + 1), edge_area(element))
+            }
+            ElementKind::Graph => (
+                "graph0".to_owned(),
+                Some(Area::Rectangle((0.0, 0.0), (drawing.width, drawing.height))),
+            ),
+        };
+        let area = match area {
+            Some(area) if element.url.is_some() || element.tooltip.is_some() => area,
+            _ => continue,
+        };
+        let (shape, coordinates) = match area {
+            Area::Rectangle(a, b) => {
+                let (a, b) = (page.point(a), page.point(b));
+                let top_left = (a.0.min(b.0), a.1.min(b.1));
+                let bottom_right = (a.0.max(b.0), a.1.max(b.1));
+                (
+                    "rect",
+                    format!("{},{}", pixel(&top_left), pixel(&bottom_right)),
+                )
+            }
+            Area::Polygon(points) => {
+                let points = points.iter().map(|&point| pixel(&page.point(point)));
+                ("poly", points.collect::<Vec<_>>().join(","))
+            }
+        };
+        write!(writer, "<area shape=\"{}\" id=\"{}\"", shape, id)?;
+        for &(attribute, value) in &[
+            ("href", &element.url),
+            ("target", &element.target),
+            ("title", &element.tooltip),
+        ] {
+            if let Some(value) = value {
+                write!(writer, " {}=\"{}\"", attribute, escape(value))?;
+            }
+        }
+        writeln!(writer, " alt=\"\" coords=\"{}\"/>", coordinates)?;
+    }
+    writeln!(writer, "</map>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout, xml};
+
+    #[test]
+    fn cmapx() {
+        let source = r#"digraph G {
+            dpi=144 URL="http://example.com/\G"
+            a [shape=box URL="a.html" target=_blank]
+            b [tooltip="just b"]
+            c
+            a -> b [href="ab.html"]
+            b -> c
+        }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output).unwrap();
+        let map = xml::parse(&String::from_utf8(output).unwrap()).unwrap();
+        assert_eq!(map.attribute("name"), Some("G"));
+        let areas = map.children_named("area").collect::<Vec<_>>();
+        let ids = areas
+            .iter()
+            .map(|area| area.attribute("id").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["node2", "node1", "edge1", "graph0"]);
+        assert_eq!(areas[0].attribute("title"), Some("just b"));
+        assert_eq!(areas[0].attribute("shape"), Some("poly"));
+        assert_eq!(areas[3].attribute("href"), Some("http://example.com/G"));
+
+        // the rectangle of `a`, in pixels at 144 dpi
+        let a = &areas[1];
+        assert_eq!(a.attribute("shape"), Some("rect"));
+        assert_eq!(a.attribute("target"), Some("_blank"));
+        let coordinates = a.attribute("coords").unwrap().split(',');
+        let coordinates = coordinates
+            .map(|value| value.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        let position = graph.nodes()[0].position().unwrap();
+        let bb = graph.bounding_box().unwrap();
+        let center = (
+            (position.x + 4.0) * 2.0,
+            (bb.upper_right.y - position.y + 4.0) * 2.0,
+        );
+        assert_eq!(
+            coordinates,
+            [
+                (center.0 - 27.0 * 2.0).round(),
+                (center.1 - 18.0 * 2.0).round(),
+                (center.0 + 27.0 * 2.0).round(),
+                (center.1 + 18.0 * 2.0).round()
+            ]
+        );
+    }
+}
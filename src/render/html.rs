@@ -0,0 +1,134 @@
+//! Self-contained HTML pages showing laid out graphs, for exploring them in a browser.
+//!
+//! The page embeds the [SVG image](super::svg) of the graph, with a script to pan it by dragging,
+//! zoom it around the pointer with the mouse wheel, and return to the whole drawing on a double
+//! click. Hovering over a node or edge shows its `tooltip`, or else its name.
+
+use std::io::{self, Write};
+
+use crate::{export::graphml::escape, model::Graph};
+
+use super::{backend::Layout, draw, svg};
+
+const STYLE: &str = "\
+html, body { margin: 0; height: 100%; overflow: hidden; }
+#graph { width: 100%; height: 100%; }
+#graph svg { width: 100%; height: 100%; cursor: grab; user-select: none; }
+#tooltip {
+  position: fixed; display: none; pointer-events: none; padding: 2px 6px;
+  background: #ffffe0; border: 1px solid #888; font: 12px sans-serif; white-space: pre-wrap;
+}
+";
+
+const SCRIPT: &str = "\
+(function () {
+  const svg = document.querySelector('#graph svg');
+  const tooltip = document.getElementById('tooltip');
+  const box = svg.viewBox.baseVal;
+  const home = { x: box.x, y: box.y, width: box.width, height: box.height };
+  let view = Object.assign({}, home);
+  let drag = null;
+  function update() {
+    svg.setAttribute('viewBox', [view.x, view.y, view.width, view.height].join(' '));
+  }
+  // the point of the drawing under the pointer
+  function at(event) {
+    const point = svg.createSVGPoint();
+    point.x = event.clientX;
+    point.y = event.clientY;
+    return point.matrixTransform(svg.getScreenCTM().inverse());
+  }
+  svg.addEventListener('wheel', function (event) {
+    event.preventDefault();
+    const point = at(event);
+    const factor = Math.pow(1.002, event.deltaY);
+    view.x = point.x - (point.x - view.x) * factor;
+    view.y = point.y - (point.y - view.y) * factor;
+    view.width *= factor;
+    view.height *= factor;
+    update();
+  }, { passive: false });
+  svg.addEventListener('mousedown', function (event) {
+    drag = at(event);
+    svg.style.cursor = 'grabbing';
+  });
+  window.addEventListener('mouseup', function () {
+    drag = null;
+    svg.style.cursor = '';
+  });
+  window.addEventListener('mousemove', function (event) {
+    if (drag) {
+      const point = at(event);
+      view.x += drag.x - point.x;
+      view.y += drag.y - point.y;
+      update();
+    }
+    tooltip.style.left = event.clientX + 12 + 'px';
+    tooltip.style.top = event.clientY + 12 + 'px';
+  });
+  svg.addEventListener('dblclick', function () {
+    view = Object.assign({}, home);
+    update();
+  });
+  for (const group of svg.querySelectorAll('g.node, g.edge')) {
+    const title = group.querySelector(':scope > title');
+    const link = group.querySelector(':scope > a');
+    const text = (link && link.getAttributeNS('http://www.w3.org/1999/xlink', 'title'))
+      || (title && title.textContent);
+    // shown here rather than as the browser's own tooltip
+    if (title) {
+      title.remove();
+    }
+    group.addEventListener('mouseenter', function () {
+      tooltip.textContent = text;
+      tooltip.style.display = text ? 'block' : 'none';
+    });
+    group.addEventListener('mouseleave', function () {
+      tooltip.style.display = 'none';
+    });
+  }
+})();
+";
+
+/// Writes `graph`, which must be laid out, as an HTML page.
+pub fn write(graph: &Graph, writer: &mut dyn Write) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let name = graph.id.as_deref().unwrap_or("");
+    let mut image = vec![];
+    let layout = Layout::new(graph, &drawing, 0.0, false);
+    svg::write_drawing(&drawing, &layout, name, &mut image)?;
+    let image = String::from_utf8(image).expect("SVG is UTF-8");
+    // without the XML declaration, out of place in HTML
+    let image = image.split_once('\n').map_or("", |(_, image)| image);
+    writeln!(
+        writer,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+    )?;
+    writeln!(writer, "<title>{}</title>", escape(name))?;
+    writeln!(writer, "<style>\n{}</style>\n</head>\n<body>", STYLE)?;
+    write!(writer, "<div id=\"graph\">\n{}</div>\n", image)?;
+    writeln!(writer, "<div id=\"tooltip\"></div>")?;
+    writeln!(writer, "<script>\n{}</script>\n</body>\n</html>", SCRIPT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout};
+
+    #[test]
+    fn html() {
+        let source = r#"graph "a<b" { a [tooltip="the first"] a -- b }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output).unwrap();
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>a&lt;b</title>"));
+        assert!(!html.contains("<?xml"));
+        assert!(html.contains("<div id=\"graph\">\n<svg "));
+        assert!(html.contains("xlink:title=\"the first\""));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,944 @@
+//! Labels with structure: the fields of `record` and `Mrecord` nodes, and HTML-like labels.
+//!
+//! A record label, such as `<f0> left|{<f1> up|down}|right`, divides its node into fields
+//! separated by `|`, side by side at the top level, or stacked if the graph's `rankdir` is `LR`
+//! or `RL`, and turning the other way within each pair of braces. A field starting with `<name>`
+//! is the port `name` of the node, for the `tailport` and `headport` of edges. `\{`, `\}`, `\|`,
+//! `\<`, `\>` and `\\` stand for the characters themselves. Fields grow evenly to fill their
+//! node, with lines between them.
+//!
+//! HTML-like labels, `<...>` in DOT, are either text with `<B>`, `<I>`, `<FONT>` (with `COLOR`,
+//! `FACE` and `POINT-SIZE`) and `<BR/>`, or a `<TABLE>` of `<TR>` rows of `<TD>` cells. Tables
+//! take `BORDER`, `CELLBORDER`, `CELLSPACING`, `CELLPADDING`, `BGCOLOR`, `COLOR` and `PORT`;
+//! cells take `BORDER`, `CELLPADDING`, `BGCOLOR`, `COLOR`, `COLSPAN`, `ROWSPAN`, `WIDTH`,
+//! `HEIGHT` and `PORT`, and hold text or another table. Other elements stand for their contents.
+//! HTML-like labels are drawn at their own size, centered on their node or edge.
+//!
+//! Coordinates are in points from the center of the label, with the y axis pointing down as in
+//! [drawings](super::Drawing).
+
+use std::path::PathBuf;
+
+use crate::{
+    attribute::{Attribute, RankDir, Shape},
+    color::{parse_color, Rgba},
+    font::{self, LINE_HEIGHT},
+    ir::is_html,
+    model::{Graph, Node},
+    xml::{self, Content, Element},
+};
+
+use super::{Item, Look, Primitive, Stroke, Text};
+
+/// Space around the text of a record field, across and down, in points.
+const FIELD_PADDING: (f64, f64) = (8.0, 4.0);
+
+/// Width in points of the border of HTML tables and their cells without a `BORDER`.
+const TABLE_BORDER: f64 = 1.0;
+
+/// Space between the cells of HTML tables without a `CELLSPACING`, in points.
+const CELL_SPACING: f64 = 2.0;
+
+/// Space around the contents of HTML table cells without a `CELLPADDING`, in points.
+const CELL_PADDING: f64 = 2.0;
+
+/// A rectangle, as its center and half its width and height.
+pub type Bounds = ((f64, f64), (f64, f64));
+
+/// A record or HTML-like label, measured in its fonts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure {
+    kind: Kind,
+    /// The lines between record fields.
+    stroke: Option<Stroke>,
+    /// The color of the borders of tables without a `COLOR`.
+    color: Rgba,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Record(Field),
+    Html(Block),
+}
+
+/// A run of text in one font.
+#[derive(Debug, Clone, PartialEq)]
+struct Span {
+    text: String,
+    font: String,
+    size: f64,
+    color: Rgba,
+    width: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    spans: Vec<Span>,
+    /// The font size of the line if it has no text.
+    size: f64,
+}
+
+impl Line {
+    fn width(&self) -> f64 {
+        self.spans.iter().map(|span| span.width).sum()
+    }
+
+    fn height(&self) -> f64 {
+        let sizes = self.spans.iter().map(|span| span.size);
+        let size = sizes.fold(f64::NEG_INFINITY, f64::max);
+        LINE_HEIGHT * if size.is_finite() { size } else { self.size }
+    }
+}
+
+/// The size of `lines` of text.
+fn text_size(lines: &[Line]) -> (f64, f64) {
+    let width = lines.iter().map(Line::width).fold(0.0, f64::max);
+    (width, lines.iter().map(Line::height).sum())
+}
+
+/// The shapes drawing `lines` of text centered on `center`.
+fn draw_text(lines: &[Line], center: (f64, f64), items: &mut Vec<Item>) {
+    let mut top = center.1 - text_size(lines).1 / 2.0;
+    for line in lines {
+        let height = line.height();
+        // on the baseline of the biggest font, as `Look::text` places lines
+        let baseline = top + height / 2.0 + 0.3 * height / LINE_HEIGHT;
+        let mut left = center.0 - line.width() / 2.0;
+        for span in &line.spans {
+            items.push(Item {
+                shape: Primitive::Text(Text {
+                    position: (left + span.width / 2.0, baseline),
+                    text: span.text.clone(),
+                    font: span.font.clone(),
+                    size: span.size,
+                    width: span.width,
+                    color: span.color,
+                }),
+                stroke: None,
+                fill: None,
+            });
+            left += span.width;
+        }
+        top += height;
+    }
+}
+
+/// The outline of the rectangle from `corner` of size `size`, drawn inside it with a line
+/// `border` wide, or nothing if it has neither a border nor a fill.
+fn rectangle(
+    corner: (f64, f64),
+    size: (f64, f64),
+    border: f64,
+    color: Rgba,
+    fill: Option<Rgba>,
+) -> Option<Item> {
+    let inset = border / 2.0;
+    let (left, top) = (corner.0 + inset, corner.1 + inset);
+    let (right, bottom) = (corner.0 + size.0 - inset, corner.1 + size.1 - inset);
+    let stroke = (border > 0.0).then(|| Stroke {
+        color,
+        width: border,
+        dashes: vec![],
+    });
+    (stroke.is_some() || fill.is_some()).then(|| Item {
+        shape: Primitive::Polygon(vec![
+            (left, top),
+            (right, top),
+            (right, bottom),
+            (left, bottom),
+        ]),
+        stroke,
+        fill,
+    })
+}
+
+/// A field of a record.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    port: Option<String>,
+    content: Fields,
+    /// The size the field needs.
+    size: (f64, f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Fields {
+    Text(Vec<Line>),
+    /// Fields side by side if `across`, else stacked.
+    Fields {
+        across: bool,
+        fields: Vec<Field>,
+    },
+}
+
+/// A field of a record label as written: its port, and its text or its fields.
+#[derive(Debug, Clone, PartialEq)]
+enum Parsed {
+    Text(Option<String>, String),
+    Fields(Option<String>, Vec<Parsed>),
+}
+
+/// The fields of a record label up to its end, or up to the closing brace if `nested`, or `None`
+/// if its braces or ports aren't closed or braces follow text.
+fn parse_fields(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    nested: bool,
+) -> Option<Vec<Parsed>> {
+    let mut fields = vec![];
+    let (mut port, mut text, mut group) = (None, String::new(), None);
+    loop {
+        let c = chars.next();
+        if matches!(c, None | Some('|') | Some('}')) {
+            let port = port.take().map(|port: String| port.trim().to_owned());
+            fields.push(match group.take() {
+                Some(group) => Parsed::Fields(port, group),
+                None => Parsed::Text(port, text.trim().to_owned()),
+            });
+            text.clear();
+        }
+        match c {
+            None => return (!nested).then_some(fields),
+            Some('}') => return nested.then_some(fields),
+            Some('|') => {}
+            Some('{') if group.is_none() && text.trim().is_empty() => {
+                group = Some(parse_fields(chars, true)?)
+            }
+            Some('{') => return None,
+            Some('<') => {
+                let mut name = String::new();
+                loop {
+                    match chars.next()? {
+                        '>' => break,
+                        '\\' => name.push(chars.next()?),
+                        c => name.push(c),
+                    }
+                }
+                port = Some(name);
+            }
+            Some('\\') => match chars.peek() {
+                Some(&c) if "{}|<> \\".contains(c) => {
+                    text.push(c);
+                    chars.next();
+                }
+                _ => text.push('\\'),
+            },
+            Some(c) if group.is_some() && !c.is_whitespace() => return None,
+            Some(c) => text.push(c),
+        }
+    }
+}
+
+/// The lines of `text`, broken at newlines and at the `\n`, `\l` and `\r` escapes.
+fn split_lines(text: &str) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('n' | 'l' | 'r')) => {
+                chars.next();
+                lines.push(String::new());
+            }
+            ('\n', _) => lines.push(String::new()),
+            (c, _) => lines.last_mut().unwrap().push(c),
+        }
+    }
+    lines
+}
+
+/// `parsed` measured in the font of `look`, its fields side by side if `across`.
+fn measure_field(parsed: Parsed, across: bool, look: &Look, font: &font::Font) -> Field {
+    match parsed {
+        Parsed::Text(port, text) => {
+            let lines = split_lines(&text)
+                .into_iter()
+                .map(|line| Line {
+                    spans: vec![Span {
+                        width: font.width(&line, look.font_size),
+                        text: line,
+                        font: look.font.clone(),
+                        size: look.font_size,
+                        color: look.font_color,
+                    }],
+                    size: look.font_size,
+                })
+                .collect::<Vec<_>>();
+            let size = if text.is_empty() {
+                (0.0, 0.0)
+            } else {
+                let (width, height) = text_size(&lines);
+                (
+                    width + 2.0 * FIELD_PADDING.0,
+                    height + 2.0 * FIELD_PADDING.1,
+                )
+            };
+            Field {
+                port,
+                content: Fields::Text(lines),
+                size,
+            }
+        }
+        Parsed::Fields(port, fields) => {
+            let fields = fields
+                .into_iter()
+                .map(|field| measure_field(field, !across, look, font))
+                .collect::<Vec<_>>();
+            let (mut sum, mut max) = (0.0, 0.0);
+            for field in &fields {
+                let (along, other) = if across {
+                    field.size
+                } else {
+                    (field.size.1, field.size.0)
+                };
+                sum += along;
+                max = f64::max(max, other);
+            }
+            Field {
+                port,
+                content: Fields::Fields { across, fields },
+                size: if across { (sum, max) } else { (max, sum) },
+            }
+        }
+    }
+}
+
+/// Draws `field` resized to `size` with its top left corner at `corner`, the fields in it growing
+/// evenly, and notes the bounds of its ports.
+fn place_field(
+    field: &Field,
+    corner: (f64, f64),
+    size: (f64, f64),
+    stroke: &Option<Stroke>,
+    items: &mut Vec<Item>,
+    ports: &mut Vec<(String, Bounds)>,
+) {
+    let half = (size.0 / 2.0, size.1 / 2.0);
+    let center = (corner.0 + half.0, corner.1 + half.1);
+    if let Some(port) = &field.port {
+        ports.push((port.clone(), (center, half)));
+    }
+    match &field.content {
+        Fields::Text(lines) => draw_text(lines, center, items),
+        Fields::Fields { across, fields } => {
+            let extra = if *across {
+                size.0 - field.size.0
+            } else {
+                size.1 - field.size.1
+            } / fields.len() as f64;
+            let mut corner = corner;
+            for (index, child) in fields.iter().enumerate() {
+                let (size, end) = if *across {
+                    (
+                        (child.size.0 + extra, size.1),
+                        (corner.0, corner.1 + size.1),
+                    )
+                } else {
+                    (
+                        (size.0, child.size.1 + extra),
+                        (corner.0 + size.0, corner.1),
+                    )
+                };
+                if let Some(stroke) = stroke.clone().filter(|_| index > 0) {
+                    let points = (0..4).map(|i| {
+                        let t = f64::from(i) / 3.0;
+                        (
+                            corner.0 + (end.0 - corner.0) * t,
+                            corner.1 + (end.1 - corner.1) * t,
+                        )
+                    });
+                    items.push(Item {
+                        shape: Primitive::Bezier(points.collect()),
+                        stroke: Some(stroke),
+                        fill: None,
+                    });
+                }
+                place_field(child, corner, size, stroke, items, ports);
+                if *across {
+                    corner.0 += size.0;
+                } else {
+                    corner.1 += size.1;
+                }
+            }
+        }
+    }
+}
+
+/// The contents of an HTML-like label or table cell.
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Text(Vec<Line>),
+    Table(Table),
+}
+
+impl Block {
+    fn size(&self) -> (f64, f64) {
+        match self {
+            Block::Text(lines) => text_size(lines),
+            Block::Table(table) => table.size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Table {
+    port: Option<String>,
+    border: f64,
+    spacing: f64,
+    color: Option<Rgba>,
+    background: Option<Rgba>,
+    cells: Vec<Cell>,
+    /// The widths of the columns and the heights of the rows.
+    columns: Vec<f64>,
+    rows: Vec<f64>,
+}
+
+impl Table {
+    fn size(&self) -> (f64, f64) {
+        let size = |tracks: &[f64]| {
+            tracks.iter().sum::<f64>()
+                + (tracks.len() + 1) as f64 * self.spacing
+                + 2.0 * self.border
+        };
+        (size(&self.columns), size(&self.rows))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    /// The first row and column of the cell, and how many it spans.
+    row: usize,
+    column: usize,
+    rows: usize,
+    columns: usize,
+    port: Option<String>,
+    border: f64,
+    color: Option<Rgba>,
+    background: Option<Rgba>,
+    content: Block,
+}
+
+/// The font, size, color and weight text is written in.
+#[derive(Debug, Clone)]
+struct TextStyle {
+    font: String,
+    size: f64,
+    color: Rgba,
+    bold: bool,
+    italic: bool,
+}
+
+impl TextStyle {
+    /// The style of the contents of `element`, as `<B>`, `<I>` and `<FONT>` change it.
+    fn within(&self, element: &Element) -> TextStyle {
+        let mut style = self.clone();
+        match element.local_name().to_ascii_lowercase().as_str() {
+            "b" => style.bold = true,
+            "i" => style.italic = true,
+            "font" => {
+                if let Some(face) = attribute(element, "face") {
+                    style.font = face.to_owned();
+                }
+                if let Some(size) = number(element, "point-size") {
+                    style.size = size;
+                }
+                if let Some(color) = color(element, "color") {
+                    style.color = color;
+                }
+            }
+            _ => {}
+        }
+        style
+    }
+
+    /// The name of the font, with its weight and slant as in fontconfig patterns.
+    fn font(&self) -> String {
+        let mut font = self.font.clone();
+        if self.bold {
+            font += ":bold";
+        }
+        if self.italic {
+            font += ":italic";
+        }
+        font
+    }
+}
+
+/// The value of the attribute `name` of `element`, whatever its case.
+fn attribute<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    let mut attributes = element.attributes.iter();
+    let found = attributes.find(|(attribute, _)| attribute.eq_ignore_ascii_case(name));
+    found.map(|(_, value)| value.as_str())
+}
+
+fn number(element: &Element, name: &str) -> Option<f64> {
+    let value = attribute(element, name)?.trim().parse::<f64>().ok()?;
+    (value >= 0.0).then_some(value)
+}
+
+fn color(element: &Element, name: &str) -> Option<Rgba> {
+    parse_color(attribute(element, name)?)?.to_rgba()
+}
+
+/// The child elements of `element` named `name`, whatever its case.
+fn children<'a>(element: &'a Element, name: &'a str) -> impl Iterator<Item = &'a Element> {
+    element
+        .children
+        .iter()
+        .filter_map(move |child| match child {
+            Content::Element(child) if child.local_name().eq_ignore_ascii_case(name) => Some(child),
+            _ => None,
+        })
+}
+
+/// Lines of the text in `content`, broken at `<BR/>`, in the styles its elements give it, with
+/// runs of whitespace as single spaces.
+fn inline(content: &[Content], style: &TextStyle, lines: &mut Vec<Line>) {
+    for child in content {
+        match child {
+            Content::Text(text) => {
+                let mut words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.starts_with(char::is_whitespace) && !text.trim().is_empty() {
+                    words.insert(0, ' ');
+                }
+                if text.ends_with(char::is_whitespace) {
+                    words.push(' ');
+                }
+                let font = style.font();
+                let line = lines.last_mut().unwrap();
+                line.spans.push(Span {
+                    width: 0.0,
+                    text: words,
+                    font,
+                    size: style.size,
+                    color: style.color,
+                });
+            }
+            Content::Element(element) if element.local_name().eq_ignore_ascii_case("br") => lines
+                .push(Line {
+                    spans: vec![],
+                    size: style.size,
+                }),
+            Content::Element(element) => inline(&element.children, &style.within(element), lines),
+        }
+    }
+}
+
+/// The text of `content`, trimmed at the ends of lines, measured in the fonts found in
+/// `font_path`.
+fn text(content: &[Content], style: &TextStyle, font_path: &[PathBuf]) -> Vec<Line> {
+    let mut lines = vec![Line {
+        spans: vec![],
+        size: style.size,
+    }];
+    inline(content, style, &mut lines);
+    for line in &mut lines {
+        if let Some(first) = line.spans.first_mut() {
+            first.text = first.text.trim_start().to_owned();
+        }
+        if let Some(last) = line.spans.last_mut() {
+            last.text = last.text.trim_end().to_owned();
+        }
+        line.spans.retain(|span| !span.text.is_empty());
+        for span in &mut line.spans {
+            span.width = font::find(&span.font, font_path).width(&span.text, span.size);
+        }
+    }
+    lines
+}
+
+/// The contents of `element`: the table it holds, alone or within elements styling it, or else
+/// its text.
+fn block(element: &Element, style: &TextStyle, font_path: &[PathBuf]) -> Block {
+    let mut significant = element.children.iter().filter(|child| match child {
+        Content::Text(text) => !text.trim().is_empty(),
+        Content::Element(_) => true,
+    });
+    if let (Some(Content::Element(child)), None) = (significant.next(), significant.next()) {
+        if child.local_name().eq_ignore_ascii_case("table") {
+            return Block::Table(table(child, style, font_path));
+        }
+        if let Block::Table(table) = block(child, &style.within(child), font_path) {
+            return Block::Table(table);
+        }
+    }
+    Block::Text(text(&element.children, style, font_path))
+}
+
+/// The sizes of the columns or rows of a table with `count` of them, from where its cells
+/// start, how many they span and how big they are. Cells spanning several share what they need
+/// beyond the others evenly between them.
+fn tracks(count: usize, mut cells: Vec<(usize, usize, f64)>, spacing: f64) -> Vec<f64> {
+    let mut sizes = vec![0.0; count];
+    cells.sort_by_key(|&(_, span, _)| span);
+    for (start, span, size) in cells {
+        let tracks = &mut sizes[start..start + span];
+        let have = tracks.iter().sum::<f64>() + (span - 1) as f64 * spacing;
+        if size > have {
+            let extra = (size - have) / span as f64;
+            tracks.iter_mut().for_each(|track| *track += extra);
+        }
+    }
+    sizes
+}
+
+fn table(element: &Element, style: &TextStyle, font_path: &[PathBuf]) -> Table {
+    let border = number(element, "border").unwrap_or(TABLE_BORDER);
+    let cell_border = number(element, "cellborder").unwrap_or(border);
+    let spacing = number(element, "cellspacing").unwrap_or(CELL_SPACING);
+    let padding = number(element, "cellpadding").unwrap_or(CELL_PADDING);
+    let color = color(element, "color");
+    let (mut cells, mut sizes) = (vec![], vec![]);
+    // the cells of rows above reaching down into each row, by column
+    let mut taken: Vec<Vec<bool>> = vec![];
+    for (row, tr) in children(element, "tr").enumerate() {
+        taken.resize(taken.len().max(row + 1), vec![]);
+        let mut column = 0;
+        for td in children(tr, "td") {
+            while taken[row].get(column) == Some(&true) {
+                column += 1;
+            }
+            let span = |name: &str| number(td, name).map_or(1, |span| (span as usize).max(1));
+            let (rows, columns) = (span("rowspan"), span("colspan"));
+            taken.resize(taken.len().max(row + rows), vec![]);
+            for taken in &mut taken[row..row + rows] {
+                taken.resize(taken.len().max(column + columns), false);
+                taken[column..column + columns]
+                    .iter_mut()
+                    .for_each(|taken| *taken = true);
+            }
+            let border = number(td, "border").unwrap_or(cell_border);
+            let padding = number(td, "cellpadding").unwrap_or(padding);
+            let content = block(td, style, font_path);
+            let size = content.size();
+            let outside = 2.0 * (border + padding);
+            sizes.push((
+                (size.0 + outside).max(number(td, "width").unwrap_or(0.0)),
+                (size.1 + outside).max(number(td, "height").unwrap_or(0.0)),
+            ));
+            cells.push(Cell {
+                row,
+                column,
+                rows,
+                columns,
+                port: attribute(td, "port").map(str::to_owned),
+                border,
+                color: self::color(td, "color").or(color),
+                background: self::color(td, "bgcolor"),
+                content,
+            });
+            column += columns;
+        }
+    }
+    let count = taken.iter().map(Vec::len).max().unwrap_or(0);
+    let columns = cells.iter().zip(&sizes);
+    let columns = columns.map(|(cell, size)| (cell.column, cell.columns, size.0));
+    let rows = cells.iter().zip(&sizes);
+    let rows = rows.map(|(cell, size)| (cell.row, cell.rows, size.1));
+    Table {
+        port: attribute(element, "port").map(str::to_owned),
+        border,
+        spacing,
+        color,
+        background: self::color(element, "bgcolor"),
+        columns: tracks(count, columns.collect(), spacing),
+        rows: tracks(taken.len(), rows.collect(), spacing),
+        cells,
+    }
+}
+
+/// Draws `block` centered on `center`, with borders in `color` unless they have their own, and
+/// notes the bounds of its ports.
+fn place_block(
+    block: &Block,
+    center: (f64, f64),
+    color: Rgba,
+    items: &mut Vec<Item>,
+    ports: &mut Vec<(String, Bounds)>,
+) {
+    let table = match block {
+        Block::Text(lines) => return draw_text(lines, center, items),
+        Block::Table(table) => table,
+    };
+    let size = table.size();
+    let corner = (center.0 - size.0 / 2.0, center.1 - size.1 / 2.0);
+    let border = table.color.unwrap_or(color);
+    items.extend(rectangle(
+        corner,
+        size,
+        table.border,
+        border,
+        table.background,
+    ));
+    if let Some(port) = &table.port {
+        ports.push((port.clone(), (center, (size.0 / 2.0, size.1 / 2.0))));
+    }
+    let starts = |tracks: &[f64], from: f64| {
+        let mut start = from + table.border + table.spacing;
+        let mut starts = vec![];
+        for track in tracks {
+            starts.push(start);
+            start += track + table.spacing;
+        }
+        starts
+    };
+    let (lefts, tops) = (
+        starts(&table.columns, corner.0),
+        starts(&table.rows, corner.1),
+    );
+    for cell in &table.cells {
+        let extent = |tracks: &[f64], start: usize, span: usize| {
+            tracks[start..start + span].iter().sum::<f64>() + (span - 1) as f64 * table.spacing
+        };
+        let corner = (lefts[cell.column], tops[cell.row]);
+        let size = (
+            extent(&table.columns, cell.column, cell.columns),
+            extent(&table.rows, cell.row, cell.rows),
+        );
+        let half = (size.0 / 2.0, size.1 / 2.0);
+        let center = (corner.0 + half.0, corner.1 + half.1);
+        let color = cell.color.unwrap_or(border);
+        items.extend(rectangle(corner, size, cell.border, color, cell.background));
+        if let Some(port) = &cell.port {
+            ports.push((port.clone(), (center, half)));
+        }
+        place_block(&cell.content, center, color, items, ports);
+    }
+}
+
+/// Whether the top level fields of records in `graph` are side by side, as they are unless its
+/// ranks go across.
+fn across(graph: &Graph) -> bool {
+    !graph.attributes.iter().any(|attribute| {
+        matches!(
+            attribute,
+            Attribute::RankDir(RankDir::LeftToRight | RankDir::RightToLeft)
+        )
+    })
+}
+
+impl Structure {
+    /// The HTML-like `label` in the fonts of `look`, or `None` if it isn't an HTML string or
+    /// isn't well-formed.
+    pub(super) fn html(label: &str, look: &Look, font_path: &[PathBuf]) -> Option<Structure> {
+        if !is_html(label) {
+            return None;
+        }
+        let source = format!(
+            "<html>{}</html>",
+            label[1..label.len() - 1].replace("&nbsp;", "&#160;")
+        );
+        let root = xml::parse(&source).ok()?;
+        let style = TextStyle {
+            font: look.font.clone(),
+            size: look.font_size,
+            color: look.font_color,
+            bold: false,
+            italic: false,
+        };
+        let block = block(&root, &style, font_path);
+        Some(Structure {
+            kind: Kind::Html(block),
+            stroke: look.stroke(),
+            color: look.color,
+        })
+    }
+
+    /// The structured label of `node` in `graph`, with `\N` and `\G` in it replaced, or `None`
+    /// if it's plain text. Labels of records that aren't well-formed are a single field.
+    pub fn of(node: &Node, graph: &Graph, font_path: &[PathBuf]) -> Option<Structure> {
+        let look = Look::new(&node.attributes);
+        let label = look.label.as_deref().unwrap_or("\\N");
+        let label = label
+            .replace("\\N", &node.id)
+            .replace("\\G", graph.id.as_deref().unwrap_or_default());
+        if let Some(structure) = Structure::html(&label, &look, font_path) {
+            return Some(structure);
+        }
+        let record = node
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::Shape(Shape::Record | Shape::MRecord)));
+        if !record {
+            return None;
+        }
+        let across = across(graph);
+        let fields = parse_fields(&mut label.chars().peekable(), false)
+            .unwrap_or_else(|| vec![Parsed::Text(None, label.clone())]);
+        let font = font::find(&look.font, font_path);
+        let field = measure_field(Parsed::Fields(None, fields), across, &look, &font);
+        Some(Structure {
+            kind: Kind::Record(field),
+            stroke: look.stroke(),
+            color: look.color,
+        })
+    }
+
+    /// Whether the label is that of a record, which fills its node.
+    pub fn is_record(&self) -> bool {
+        matches!(self.kind, Kind::Record(_))
+    }
+
+    /// The size the label needs, in points.
+    pub fn size(&self) -> (f64, f64) {
+        match &self.kind {
+            Kind::Record(field) => field.size,
+            Kind::Html(block) => block.size(),
+        }
+    }
+
+    /// The shapes drawing the label centered on `center`, in a node of size `size` for records,
+    /// and the bounds of its ports.
+    fn place(&self, center: (f64, f64), size: (f64, f64)) -> (Vec<Item>, Vec<(String, Bounds)>) {
+        let (mut items, mut ports) = (vec![], vec![]);
+        match &self.kind {
+            Kind::Record(field) => {
+                let size = (size.0.max(field.size.0), size.1.max(field.size.1));
+                let corner = (center.0 - size.0 / 2.0, center.1 - size.1 / 2.0);
+                place_field(field, corner, size, &self.stroke, &mut items, &mut ports);
+            }
+            Kind::Html(block) => place_block(block, center, self.color, &mut items, &mut ports),
+        }
+        (items, ports)
+    }
+
+    /// The shapes drawing the label centered on `center`, in a node of size `size` in points.
+    pub fn draw(&self, center: (f64, f64), size: (f64, f64)) -> Vec<Item> {
+        self.place(center, size).0
+    }
+
+    /// The bounds of the field or cell named `port` in a node of size `size` in points, from its
+    /// center.
+    pub fn port(&self, port: &str, size: (f64, f64)) -> Option<Bounds> {
+        let (_, ports) = self.place((0.0, 0.0), size);
+        ports
+            .into_iter()
+            .find_map(|(name, bounds)| (name == port).then_some(bounds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    fn structure(source: &str) -> Structure {
+        let graph = Graph::from_ir(&parse_graph(source).unwrap());
+        Structure::of(&graph.nodes()[0], &graph, &[]).unwrap()
+    }
+
+    #[test]
+    fn records() {
+        assert_eq!(
+            parse_fields(&mut r"<f0> a\|b|{c|<f1>}|".chars().peekable(), false),
+            Some(vec![
+                Parsed::Text(Some("f0".to_owned()), "a|b".to_owned()),
+                Parsed::Fields(
+                    None,
+                    vec![
+                        Parsed::Text(None, "c".to_owned()),
+                        Parsed::Text(Some("f1".to_owned()), String::new())
+                    ]
+                ),
+                Parsed::Text(None, String::new()),
+            ])
+        );
+        for bad in &["{a", "a}", "a {b}", "<f0 a", "{a} b"] {
+            assert_eq!(parse_fields(&mut bad.chars().peekable(), false), None);
+        }
+
+        let record =
+            structure(r#"digraph { a [shape=record label="<l> left|{<u> up|<d> down}"] }"#);
+        assert!(record.is_record());
+        let font = font::find(crate::render::DEFAULT_FONT, &[]);
+        let width = |text| font.width(text, 14.0) + 16.0;
+        let (size, line) = (record.size(), 1.2 * 14.0 + 8.0);
+        assert!((size.0 - width("left") - width("down").max(width("up"))).abs() < 1e-9);
+        assert!((size.1 - 2.0 * line).abs() < 1e-9);
+
+        // fields grow evenly to fill the node, the first level side by side
+        let node = (size.0 + 20.0, size.1 + 10.0);
+        let ((x, y), (dx, dy)) = record.port("l", node).unwrap();
+        assert!((x - (-node.0 / 2.0 + (width("left") + 10.0) / 2.0)).abs() < 1e-9);
+        assert_eq!((y, dy), (0.0, node.1 / 2.0));
+        assert!((dx - (width("left") + 10.0) / 2.0).abs() < 1e-9);
+        let (up, down) = (
+            record.port("u", node).unwrap(),
+            record.port("d", node).unwrap(),
+        );
+        assert_eq!(((up.0).0, (up.1).1), ((down.0).0, node.1 / 4.0));
+        assert!((up.0).1 < 0.0 && (down.0).1 > 0.0);
+        assert_eq!(record.port("x", node), None);
+        // a line between each pair of fields, and the text of each
+        let items = record.draw((0.0, 0.0), node);
+        let lines = items
+            .iter()
+            .filter(|item| matches!(item.shape, Primitive::Bezier(_)));
+        assert_eq!(lines.count(), 2);
+
+        // stacked at the first level when ranks go left to right
+        let record = structure(r#"digraph { rankdir=LR a [shape=record label="<a> a|b"] }"#);
+        let ((_, y), (dx, _)) = record.port("a", (54.0, 60.0)).unwrap();
+        assert!((y - -15.0).abs() < 1e-9 && dx == 27.0);
+    }
+
+    #[test]
+    fn html() {
+        let label = structure(
+            r#"digraph { a [label=<<b>bold</b> plain<br/><font point-size="20" color="red">big</font>>] }"#,
+        );
+        let lines = match &label.kind {
+            Kind::Html(Block::Text(lines)) => lines,
+            kind => panic!("{:?}", kind),
+        };
+        fn texts(line: &Line) -> Vec<(&str, &str)> {
+            let spans = line.spans.iter();
+            spans
+                .map(|span| (span.text.as_str(), span.font.as_str()))
+                .collect()
+        }
+        assert_eq!(
+            texts(&lines[0]),
+            [("bold", "Times-Roman:bold"), (" plain", "Times-Roman")]
+        );
+        assert_eq!(lines[1].spans[0].color, Rgba::opaque(255, 0, 0));
+        assert_eq!(label.size().1, 1.2 * 14.0 + 1.2 * 20.0);
+
+        let table = structure(
+            r#"digraph { a [shape=plaintext label=<
+                <table border="0" cellspacing="4" port="t">
+                    <tr><td port="wide" colspan="2" width="100">a</td></tr>
+                    <tr><td rowspan="2" bgcolor="yellow">b</td><td port="c">c</td></tr>
+                    <tr><td height="30">d</td></tr>
+                </table>>] }"#,
+        );
+        let table_size = table.size();
+        let wide = table.port("wide", (0.0, 0.0)).unwrap();
+        assert_eq!((wide.1).0, 50.0);
+        // the spacing between the columns is within the spanning cell
+        assert_eq!(table_size.0, 100.0 + 2.0 * 4.0);
+        let (c, t) = (
+            table.port("c", (0.0, 0.0)).unwrap(),
+            table.port("t", (0.0, 0.0)).unwrap(),
+        );
+        assert_eq!(t, ((0.0, 0.0), (table_size.0 / 2.0, table_size.1 / 2.0)));
+        assert!((c.0).0 > 0.0 && (c.0).1 < 0.0 && (c.0).1 > (wide.0).1);
+        // the last row is as tall as the cell needs, and the spanning cell as both rows
+        let items = table.draw((0.0, 0.0), (0.0, 0.0));
+        let filled = items.iter().find(|item| item.fill.is_some()).unwrap();
+        match &filled.shape {
+            Primitive::Polygon(corners) => {
+                let height = corners[2].1 - corners[0].1;
+                assert!(height > 30.0 + 4.0);
+                assert!((corners[2].1 - (table_size.1 / 2.0 - 4.0)).abs() < 1e-9);
+            }
+            shape => panic!("{:?}", shape),
+        }
+        let texts = items
+            .iter()
+            .filter(|item| matches!(item.shape, Primitive::Text(_)));
+        assert_eq!(texts.count(), 4);
+
+        // plain labels have no structure
+        let graph = Graph::from_ir(&parse_graph("digraph { a [label=\"<f0> a|b\"] }").unwrap());
+        assert_eq!(Structure::of(&graph.nodes()[0], &graph, &[]), None);
+    }
+}
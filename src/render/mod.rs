@@ -0,0 +1,1412 @@
+//! Rendering of laid out graphs.
+//!
+//! [`draw`] turns a graph whose nodes and edges have positions into a [`Drawing`]: shapes,
+//! lines and text, grouped by the node, edge or graph they draw, in the coordinates of the
+//! image. Backends then only have to draw these few primitives in their own format, through
+//! [`backend::RenderBackend`], which lays drawings on pages for them.
+//!
+//! Coordinates are in points with the y axis pointing down from the top left corner of the
+//! drawing, as in SVG and raster images; backends for page description formats, with the y axis
+//! pointing up, flip them. The drawing covers the graph's `bb` with a margin of `pad` around it,
+//! and room for the graph's label.
+//!
+//! Nodes are drawn following their `shape`, `style`, `color`, `fillcolor`, `penwidth` and
+//! `peripheries`, shapes without an outline of their own being drawn as boxes. Edges are drawn
+//! along the splines of their `pos`, with arrowheads following `dir`, `arrowhead`, `arrowtail`
+//! and `arrowsize`. Labels are drawn with their `fontname`, `fontsize` and `fontcolor`, and
+//! measured in the font [`font::find`] finds for them; the fields of records and HTML-like
+//! labels are laid out by [`label`].
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use self::label::Structure;
+use crate::{
+    attribute::{
+        self, ArrowShape, ArrowSide, ArrowType, Attribute, ColorAttribute, DirType, Point,
+        Position, Rectangle, Shape, Style, TextLocation,
+    },
+    color::Rgba,
+    font::{self, LINE_HEIGHT},
+    model::{Edge, Graph, Node, POINTS_PER_INCH},
+};
+
+pub mod backend;
+#[cfg(feature = "render")]
+pub mod cmapx;
+#[cfg(feature = "render-svg")]
+pub mod html;
+pub mod label;
+#[cfg(feature = "render")]
+pub mod pdf;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "render")]
+pub mod ps;
+#[cfg(feature = "render-svg")]
+pub mod svg;
+pub mod theme;
+
+/// Margin around the drawing in points, when the graph has no `pad`.
+pub const DEFAULT_PAD: f64 = 4.0;
+
+/// Font size in points of labels without a `fontsize`.
+pub const DEFAULT_FONT_SIZE: f64 = attribute::DEFAULT_FONT_SIZE;
+
+/// Font of labels without a `fontname`.
+pub const DEFAULT_FONT: &str = attribute::DEFAULT_FONT_NAME;
+
+/// Resolution of images of graphs without a `dpi`, in pixels per inch.
+pub const DEFAULT_DPI: f64 = 96.0;
+
+/// Length in points of arrowheads of `arrowsize` 1.
+const ARROW_LENGTH: f64 = 10.0;
+
+/// Diameter of `point` nodes in points.
+const POINT_SIZE: f64 = 3.6;
+
+/// Space between the peripheries of a node in points.
+const PERIPHERY_GAP: f64 = 4.0;
+
+/// Radius of the corners of `rounded` nodes in points, for nodes big enough.
+const CORNER_RADIUS: f64 = 12.0;
+
+/// Length in points of the sides of polygons that `diagonals` cut off their corners, for nodes
+/// big enough.
+const DIAGONAL_LENGTH: f64 = 12.0;
+
+/// Color filling `filled` nodes without a `fillcolor` or a `color`.
+const LIGHT_GRAY: Rgba = Rgba::opaque(211, 211, 211);
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error("the graph isn't laid out; node `{0}` has no position")]
+    NotLaidOut(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drawing {
+    /// Width in points.
+    pub width: f64,
+    /// Height in points.
+    pub height: f64,
+    /// From the graph's `bgcolor`, or `None` for a transparent background.
+    pub background: Option<Rgba>,
+    /// The elements of the drawing, drawn in order: the graph's label, then edges, then nodes.
+    pub elements: Vec<Element>,
+}
+
+/// The shapes drawing one node, edge or graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Element {
+    pub kind: ElementKind,
+    /// The ID of the node, `tail -> head` for an edge (with `--` in undirected graphs), or the
+    /// ID of the graph.
+    pub name: String,
+    /// From the `tooltip` attribute, for backends which can show it.
+    pub tooltip: Option<String>,
+    /// The link of the element, from its `URL` or `href` attribute.
+    pub url: Option<String>,
+    /// The window or frame the link opens in, from the `target` attribute.
+    pub target: Option<String>,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementKind {
+    Graph,
+    Node,
+    Edge,
+}
+
+/// A shape, with the line around it and its filling, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub shape: Primitive,
+    pub stroke: Option<Stroke>,
+    pub fill: Option<Rgba>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    pub color: Rgba,
+    /// Width in points.
+    pub width: f64,
+    /// Lengths in points of alternating dashes and gaps, empty for a solid line.
+    pub dashes: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive {
+    Ellipse {
+        center: (f64, f64),
+        radii: (f64, f64),
+    },
+    /// A closed polygon through its corners.
+    Polygon(Vec<(f64, f64)>),
+    /// Cubic Bézier curves through the first point and every third after it, the points in
+    /// between being control points.
+    Bezier(Vec<(f64, f64)>),
+    Text(Text),
+}
+
+/// A line of text, centered on its position horizontally, with its baseline on it vertically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text {
+    pub position: (f64, f64),
+    pub text: String,
+    pub font: String,
+    /// Font size in points.
+    pub size: f64,
+    /// Width in points, as measured in the font [`font::find`] finds for `font`.
+    pub width: f64,
+    pub color: Rgba,
+}
+
+/// The polyline through the cubic Bézier curves through `points`, in pieces about `flatness`
+/// long.
+pub(crate) fn flatten(points: &[(f64, f64)], flatness: f64) -> Vec<(f64, f64)> {
+    let mut line = vec![points[0]];
+    for curve in points[1..].chunks_exact(3) {
+        let p = [*line.last().unwrap(), curve[0], curve[1], curve[2]];
+        let length: f64 = p
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+            .sum();
+        let steps = (length / flatness).ceil().clamp(1.0, 100.0) as usize;
+        for step in 1..=steps {
+            let t = step as f64 / steps as f64;
+            let u = 1.0 - t;
+            let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+            let x = (0..4).map(|i| weights[i] * p[i].0).sum();
+            let y = (0..4).map(|i| weights[i] * p[i].1).sum();
+            line.push((x, y));
+        }
+    }
+    line
+}
+
+/// How far the control points of a quarter ellipse are from its ends, relative to its radius.
+const KAPPA: f64 = 0.552_284_75;
+
+/// The four cubic Bézier curves closely approximating an ellipse, as in [`Primitive::Bezier`],
+/// for vector formats without ellipses.
+pub(crate) fn ellipse((cx, cy): (f64, f64), (rx, ry): (f64, f64)) -> Vec<(f64, f64)> {
+    let at = |x: f64, y: f64| (cx + x * rx, cy + y * ry);
+    let mut points = vec![at(1.0, 0.0)];
+    for &(x, y) in &[(0.0, 1.0), (-1.0, 0.0), (0.0, -1.0), (1.0, 0.0)] {
+        // from the previous end, a quarter turn before this one
+        let (px, py) = (y, -x);
+        points.push(at(px + KAPPA * x, py + KAPPA * y));
+        points.push(at(x + KAPPA * px, y + KAPPA * py));
+        points.push(at(x, y));
+    }
+    points
+}
+
+/// The contents of a PDF or PostScript string literal for `text` in Latin-1, which the
+/// encodings of the standard fonts share, in the order it's drawn in. Other characters are
+/// written as `?`, and combining marks left out.
+#[cfg(feature = "render")]
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in font::visual(text).chars() {
+        match c {
+            c if font::is_mark(c) => {}
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\u{a0}'..='\u{ff}' => escaped += &format!("\\{:03o}", c as u32),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+/// Formats a coordinate with up to two decimals, for vector formats.
+#[cfg(feature = "render-svg")]
+pub(crate) fn number(value: f64) -> String {
+    let value = (value * 100.0).round() / 100.0;
+    // without negative zeros
+    format!("{}", value + 0.0)
+}
+
+/// The resolution of images of `graph` from its `dpi`, in pixels per inch.
+pub fn dpi(graph: &Graph) -> f64 {
+    graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Dpi(dpi) if *dpi > 0.0 => Some(*dpi),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_DPI)
+}
+
+/// The colors of a color attribute, as RGBA, with their weights.
+fn colors(attribute: &ColorAttribute) -> Vec<(Rgba, Option<f64>)> {
+    match attribute {
+        ColorAttribute::Color(color) => color
+            .to_rgba()
+            .map(|color| (color, None))
+            .into_iter()
+            .collect(),
+        ColorAttribute::ColorList(colors) => colors
+            .iter()
+            .filter_map(|weighted| Some((weighted.color.to_rgba()?, weighted.weight)))
+            .collect(),
+    }
+}
+
+/// The first color of a color attribute, as RGBA.
+fn color(attribute: &ColorAttribute) -> Option<Rgba> {
+    colors(attribute).first().map(|&(color, _)| color)
+}
+
+/// The attributes drawing an element needs, with their defaults.
+struct Look {
+    color: Rgba,
+    /// The colors of `color`, if set, and of `fillcolor`, with their weights.
+    colors: Vec<(Rgba, Option<f64>)>,
+    fill_colors: Vec<(Rgba, Option<f64>)>,
+    filled: bool,
+    striped: bool,
+    wedged: bool,
+    rounded: bool,
+    diagonals: bool,
+    tapered: bool,
+    invisible: bool,
+    width: f64,
+    dashes: Vec<f64>,
+    font: String,
+    font_size: f64,
+    font_color: Rgba,
+    label: Option<String>,
+}
+
+impl Look {
+    fn new(attributes: &[Attribute]) -> Self {
+        let mut look = Look {
+            color: Rgba::BLACK,
+            colors: vec![],
+            fill_colors: vec![],
+            filled: false,
+            striped: false,
+            wedged: false,
+            rounded: false,
+            diagonals: false,
+            tapered: false,
+            invisible: false,
+            width: 1.0,
+            dashes: vec![],
+            font: DEFAULT_FONT.to_owned(),
+            font_size: DEFAULT_FONT_SIZE,
+            font_color: Rgba::BLACK,
+            label: None,
+        };
+        let mut pen_width = None;
+        for attribute in attributes {
+            match attribute {
+                Attribute::Color(value) => {
+                    look.colors = colors(value);
+                    look.color = look.colors.first().map_or(look.color, |&(color, _)| color);
+                }
+                Attribute::FillColor(value) => look.fill_colors = colors(value),
+                Attribute::PenWidth(width) => pen_width = Some(*width),
+                Attribute::FontName(font) => look.font = font.clone(),
+                Attribute::FontSize(size) => look.font_size = *size,
+                Attribute::FontColor(value) => {
+                    look.font_color = value.to_rgba().unwrap_or(look.font_color)
+                }
+                Attribute::Label(label) => look.label = Some(label.clone()),
+                Attribute::Style(styles) => {
+                    for style in styles {
+                        match style {
+                            Style::Filled => look.filled = true,
+                            Style::Striped => look.striped = true,
+                            Style::Wedged => look.wedged = true,
+                            Style::Rounded => look.rounded = true,
+                            Style::Diagonals => look.diagonals = true,
+                            Style::Tapered => look.tapered = true,
+                            Style::Invis => look.invisible = true,
+                            Style::Bold => look.width = 2.0,
+                            Style::Dashed => look.dashes = vec![5.0, 2.0],
+                            Style::Dotted => look.dashes = vec![1.0, 5.0],
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        look.width = pen_width.unwrap_or(look.width);
+        look
+    }
+
+    /// The colors filling the element, from its `fillcolor`, else its `color`, else `default`,
+    /// with the fractions of the fill they take: their weights, or equal shares of what the
+    /// weights leave, the last color taking whatever is left after that.
+    fn fills(&self, default: Rgba) -> Vec<(Rgba, f64)> {
+        let colors = match (self.fill_colors.is_empty(), self.colors.is_empty()) {
+            (false, _) => &self.fill_colors,
+            (true, false) => &self.colors,
+            (true, true) => return vec![(default, 1.0)],
+        };
+        let weights = colors.iter().filter_map(|&(_, weight)| weight);
+        let weighted = weights.map(|weight| weight.max(0.0)).sum::<f64>().min(1.0);
+        let unweighted = colors.iter().filter(|(_, weight)| weight.is_none()).count();
+        let share = (1.0 - weighted) / unweighted.max(1) as f64;
+        let mut left = 1.0;
+        let mut fills = colors
+            .iter()
+            .map(|&(color, weight)| {
+                let fraction = weight.map_or(share, |weight| weight.max(0.0)).min(left);
+                left -= fraction;
+                (color, fraction)
+            })
+            .collect::<Vec<_>>();
+        if let Some(last) = fills.last_mut() {
+            last.1 += left;
+        }
+        fills
+    }
+
+    fn stroke(&self) -> Option<Stroke> {
+        (self.width > 0.0).then(|| Stroke {
+            color: self.color,
+            width: self.width,
+            dashes: self.dashes.clone(),
+        })
+    }
+
+    /// Lines of text centered on `center`, one per line of `label`, measured in the font found
+    /// in `font_path`.
+    fn text(&self, label: &str, center: (f64, f64), font_path: &[PathBuf]) -> Vec<Item> {
+        let font = font::find(&self.font, font_path);
+        let lines = label
+            .split("\\n")
+            .flat_map(|line| line.split('\n'))
+            .collect::<Vec<_>>();
+        let height = LINE_HEIGHT * self.font_size;
+        let first = center.1 - (lines.len() - 1) as f64 * height / 2.0 + 0.3 * self.font_size;
+        lines
+            .into_iter()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(index, line)| Item {
+                shape: Primitive::Text(Text {
+                    position: (center.0, first + index as f64 * height),
+                    text: line.to_owned(),
+                    font: self.font.clone(),
+                    size: self.font_size,
+                    width: font.width(line, self.font_size),
+                    color: self.font_color,
+                }),
+                stroke: None,
+                fill: None,
+            })
+            .collect()
+    }
+}
+
+/// The corners of the outline of `shape`, within the square from `(-1, -1)` to `(1, 1)` with
+/// the y axis pointing up, or `None` for round shapes and shapes without an outline.
+fn corners(shape: &Shape, sides: usize) -> Option<Vec<(f64, f64)>> {
+    let regular = |sides: usize, turn: f64| {
+        let points = (0..sides)
+            .map(|side| {
+                let angle = turn + 2.0 * std::f64::consts::PI * side as f64 / sides as f64;
+                (angle.cos(), angle.sin())
+            })
+            .collect::<Vec<_>>();
+        fit(points)
+    };
+    let down = -std::f64::consts::FRAC_PI_2;
+    let up = std::f64::consts::FRAC_PI_2;
+    Some(match shape {
+        Shape::Ellipse
+        | Shape::Oval
+        | Shape::Circle
+        | Shape::Egg
+        | Shape::Point
+        | Shape::DoubleCircle
+        | Shape::MCircle
+        | Shape::Plaintext
+        | Shape::Plain
+        | Shape::None => return None,
+        Shape::Triangle => regular(3, up),
+        Shape::InvTriangle => regular(3, down),
+        Shape::Diamond | Shape::MDiamond => vec![(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)],
+        Shape::Pentagon => regular(5, up),
+        Shape::Hexagon => regular(6, 0.0),
+        Shape::Septagon => regular(7, up),
+        Shape::Octagon | Shape::DoubleOctagon | Shape::TripleOctagon => {
+            regular(8, std::f64::consts::PI / 8.0)
+        }
+        Shape::Polygon if sides >= 3 => regular(sides, down + std::f64::consts::PI / sides as f64),
+        Shape::Trapezium => vec![(-1.0, -1.0), (1.0, -1.0), (0.6, 1.0), (-0.6, 1.0)],
+        Shape::InvTrapezium => vec![(-0.6, -1.0), (0.6, -1.0), (1.0, 1.0), (-1.0, 1.0)],
+        Shape::Parallelogram => vec![(-1.0, -1.0), (0.6, -1.0), (1.0, 1.0), (-0.6, 1.0)],
+        Shape::House => vec![
+            (-1.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 0.2),
+            (0.0, 1.0),
+            (-1.0, 0.2),
+        ],
+        Shape::InvHouse => vec![
+            (-1.0, 1.0),
+            (-1.0, -0.2),
+            (0.0, -1.0),
+            (1.0, -0.2),
+            (1.0, 1.0),
+        ],
+        Shape::Star => {
+            let points = (0..10)
+                .map(|point| {
+                    let angle = up + std::f64::consts::PI * point as f64 / 5.0;
+                    let radius = if point % 2 == 0 { 1.0 } else { 0.4 };
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect();
+            fit(points)
+        }
+        _ => vec![(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)],
+    })
+}
+
+/// Scales and moves `points` to just fit the square from `(-1, -1)` to `(1, 1)`.
+fn fit(points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let (mut left, mut bottom) = (f64::INFINITY, f64::INFINITY);
+    let (mut right, mut top) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for &(x, y) in &points {
+        left = left.min(x);
+        bottom = bottom.min(y);
+        right = right.max(x);
+        top = top.max(y);
+    }
+    points
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                2.0 * (x - left) / (right - left) - 1.0,
+                2.0 * (y - bottom) / (top - bottom) - 1.0,
+            )
+        })
+        .collect()
+}
+
+/// The element `name` drawn by `items`, with the tooltip and link set by its `attributes`,
+/// in which the `escapes`, such as `\\N` for the name of a node, are replaced.
+fn element(
+    kind: ElementKind,
+    name: String,
+    attributes: &[Attribute],
+    escapes: &[(&str, &str)],
+    items: Vec<Item>,
+) -> Element {
+    let (mut tooltip, mut url, mut target) = (None, None, None);
+    for attribute in attributes {
+        match attribute {
+            Attribute::Tooltip(value) => tooltip = Some(value),
+            Attribute::Url(value) | Attribute::Href(value) => url = Some(value),
+            Attribute::Target(value) => target = Some(value),
+            _ => {}
+        }
+    }
+    let escaped = |text: Option<&String>| {
+        text.map(|text| {
+            escapes.iter().fold(text.clone(), |text, (escape, value)| {
+                text.replace(escape, value)
+            })
+        })
+    };
+    Element {
+        kind,
+        name,
+        tooltip: escaped(tooltip),
+        url: escaped(url),
+        target: target.cloned(),
+        items,
+    }
+}
+
+/// `corners` with each corner rounded off by an arc of `radius`, or less where the sides beside
+/// it are too short for it.
+fn round(corners: &[(f64, f64)], radius: f64) -> Vec<(f64, f64)> {
+    let n = corners.len();
+    let mut points = vec![];
+    for (index, &corner) in corners.iter().enumerate() {
+        let (before, after) = (corners[(index + n - 1) % n], corners[(index + 1) % n]);
+        let toward = |(x, y): (f64, f64)| {
+            let length = (x - corner.0).hypot(y - corner.1);
+            let distance = radius.min(length / 2.0) / length.max(f64::EPSILON);
+            (
+                corner.0 + (x - corner.0) * distance,
+                corner.1 + (y - corner.1) * distance,
+            )
+        };
+        let (start, end) = (toward(before), toward(after));
+        // a quadratic curve with the corner as its control point
+        for step in 0..=4 {
+            let t = f64::from(step) / 4.0;
+            let (u, v, w) = ((1.0 - t) * (1.0 - t), 2.0 * t * (1.0 - t), t * t);
+            points.push((
+                u * start.0 + v * corner.0 + w * end.0,
+                u * start.1 + v * corner.1 + w * end.1,
+            ));
+        }
+    }
+    points
+}
+
+/// The lines across the corners of `corners`, for the `diagonals` style.
+fn diagonals(corners: &[(f64, f64)]) -> Vec<Primitive> {
+    let n = corners.len();
+    (0..n)
+        .map(|index| {
+            let corner = corners[index];
+            let toward = |(x, y): (f64, f64)| {
+                let length = (x - corner.0).hypot(y - corner.1);
+                let distance = DIAGONAL_LENGTH.min(length / 3.0) / length.max(f64::EPSILON);
+                (
+                    corner.0 + (x - corner.0) * distance,
+                    corner.1 + (y - corner.1) * distance,
+                )
+            };
+            let (start, end) = (
+                toward(corners[(index + n - 1) % n]),
+                toward(corners[(index + 1) % n]),
+            );
+            let points = (0..4).map(|i| {
+                let t = f64::from(i) / 3.0;
+                (
+                    start.0 + (end.0 - start.0) * t,
+                    start.1 + (end.1 - start.1) * t,
+                )
+            });
+            Primitive::Bezier(points.collect())
+        })
+        .collect()
+}
+
+/// The vertical stripes filling the box of half size `half` around `center`, from left to right
+/// in the colors of `fills` as wide as their fractions say.
+fn stripes(center: (f64, f64), half: (f64, f64), fills: &[(Rgba, f64)]) -> Vec<Item> {
+    let (top, bottom) = (center.1 - half.1, center.1 + half.1);
+    let mut left = center.0 - half.0;
+    fills
+        .iter()
+        .filter(|(_, fraction)| *fraction > 0.0)
+        .map(|&(color, fraction)| {
+            let right = left + 2.0 * half.0 * fraction;
+            let stripe = vec![(left, top), (right, top), (right, bottom), (left, bottom)];
+            left = right;
+            Item {
+                shape: Primitive::Polygon(stripe),
+                stroke: None,
+                fill: Some(color),
+            }
+        })
+        .collect()
+}
+
+/// The wedges filling the ellipse of radii `radii` around `center`, counterclockwise from its
+/// right in the colors of `fills`, their angles in proportion to their fractions.
+fn wedges(center: (f64, f64), radii: (f64, f64), fills: &[(Rgba, f64)]) -> Vec<Item> {
+    let mut start = 0.0;
+    fills
+        .iter()
+        .filter(|(_, fraction)| *fraction > 0.0)
+        .map(|&(color, fraction)| {
+            let angle = 2.0 * std::f64::consts::PI * fraction;
+            let steps = (fraction * 64.0).ceil() as usize;
+            let mut wedge = vec![center];
+            wedge.extend((0..=steps).map(|step| {
+                let angle = start + angle * step as f64 / steps as f64;
+                (
+                    center.0 + radii.0 * angle.cos(),
+                    center.1 - radii.1 * angle.sin(),
+                )
+            }));
+            start += angle;
+            Item {
+                shape: Primitive::Polygon(wedge),
+                stroke: None,
+                fill: Some(color),
+            }
+        })
+        .collect()
+}
+
+/// The shapes drawing `node`, centered at `center` in drawing coordinates, with `structure` as
+/// its label if it's a record or HTML-like label.
+fn draw_node(
+    node: &Node,
+    center: (f64, f64),
+    structure: Option<&Structure>,
+    font_path: &[PathBuf],
+) -> Vec<Item> {
+    let mut look = Look::new(&node.attributes);
+    if look.invisible {
+        return vec![];
+    }
+    let mut shape = Shape::Ellipse;
+    let (mut sides, mut peripheries) = (4, None);
+    for attribute in &node.attributes {
+        match attribute {
+            Attribute::Shape(value) => shape = value.clone(),
+            Attribute::Sides(value) => sides = (*value).max(0) as usize,
+            Attribute::Peripheries(value) => peripheries = Some((*value).max(0) as usize),
+            _ => {}
+        }
+    }
+    let (width, height) = node.size();
+    let mut half = (
+        width * POINTS_PER_INCH / 2.0,
+        height * POINTS_PER_INCH / 2.0,
+    );
+    let point = shape == Shape::Point;
+    let fills = look.fills(if point { look.color } else { LIGHT_GRAY });
+    let peripheries = peripheries.unwrap_or(match shape {
+        Shape::DoubleCircle | Shape::DoubleOctagon => 2,
+        Shape::TripleOctagon => 3,
+        Shape::Plaintext | Shape::Plain | Shape::None => 0,
+        _ => 1,
+    });
+    match shape {
+        Shape::Circle | Shape::DoubleCircle => {
+            let radius = half.0.max(half.1);
+            half = (radius, radius);
+        }
+        Shape::Point => half = (POINT_SIZE / 2.0, POINT_SIZE / 2.0),
+        Shape::MRecord => look.rounded = true,
+        _ => {}
+    }
+    let corners = corners(&shape, sides);
+    let at = |half: (f64, f64)| {
+        corners.as_ref().map(|corners| {
+            let points = corners
+                .iter()
+                .map(|(x, y)| (center.0 + x * half.0, center.1 - y * half.1));
+            points.collect::<Vec<_>>()
+        })
+    };
+    let outline = |half: (f64, f64), fill: Option<Rgba>| Item {
+        shape: match at(half) {
+            Some(points) if look.rounded => {
+                let radius = CORNER_RADIUS.min(half.0 / 1.5).min(half.1 / 1.5);
+                Primitive::Polygon(round(&points, radius))
+            }
+            Some(points) => Primitive::Polygon(points),
+            None => Primitive::Ellipse {
+                center,
+                radii: half,
+            },
+        },
+        stroke: look.stroke(),
+        fill,
+    };
+
+    // the innermost periphery is filled with one color, or with stripes or wedges of several
+    let inner = peripheries.saturating_sub(1) as f64 * PERIPHERY_GAP;
+    let inner = (half.0 - inner, half.1 - inner);
+    let rectangle = matches!(
+        shape,
+        Shape::Box | Shape::Rect | Shape::Rectangle | Shape::Square
+    ) && !look.rounded;
+    let mut items = if look.striped && rectangle {
+        stripes(center, inner, &fills)
+    } else if look.wedged && corners.is_none() && !point {
+        wedges(center, inner, &fills)
+    } else {
+        vec![]
+    };
+    let fill = (items.is_empty() && (look.filled || point)).then(|| fills[0].0);
+    if peripheries == 0 && fill.is_some() {
+        items.push(Item {
+            stroke: None,
+            ..outline(half, fill)
+        });
+    }
+    for periphery in 0..peripheries {
+        // the outermost periphery is the node's size
+        let shrink = periphery as f64 * PERIPHERY_GAP;
+        let fill = fill.filter(|_| periphery == peripheries - 1);
+        items.push(outline((half.0 - shrink, half.1 - shrink), fill));
+    }
+    let diagonal = look.diagonals || matches!(shape, Shape::MSquare | Shape::MDiamond);
+    if let Some(points) = at(half).filter(|_| diagonal && peripheries > 0) {
+        items.extend(diagonals(&points).into_iter().map(|line| Item {
+            shape: line,
+            stroke: look.stroke(),
+            fill: None,
+        }));
+    }
+    if let Some(structure) = structure.filter(|_| !point) {
+        items.extend(structure.draw(center, (2.0 * half.0, 2.0 * half.1)));
+    } else if !point {
+        let label = look
+            .label
+            .as_deref()
+            .unwrap_or("\\N")
+            .replace("\\N", &node.id);
+        items.extend(look.text(&label, center, font_path));
+    }
+    items
+}
+
+/// Length of the arrow shape `shape` along the edge, relative to [`ARROW_LENGTH`].
+fn arrow_length(shape: ArrowShape) -> f64 {
+    match shape {
+        ArrowShape::Diamond => 1.2,
+        ArrowShape::Dot => 0.8,
+        ArrowShape::None | ArrowShape::Tee => 0.5,
+        _ => 1.0,
+    }
+}
+
+/// The length of the arrowhead `kind` along the edge, in points at `arrowsize` `size`; 0 for no
+/// arrowhead.
+fn arrowhead_length(kind: &ArrowType, size: f64) -> f64 {
+    let arrows = kind.arrows();
+    if arrows.iter().all(|arrow| arrow.shape == ArrowShape::None) {
+        return 0.0;
+    }
+    let lengths = arrows.iter().map(|arrow| arrow_length(arrow.shape));
+    lengths.sum::<f64>() * ARROW_LENGTH * size
+}
+
+/// The points of a cubic Bézier curve.
+type Curve = [(f64, f64); 4];
+
+/// The curve `points` split at `t`, as the curves before and after it.
+fn split(points: Curve, t: f64) -> (Curve, Curve) {
+    let lerp = |a: (f64, f64), b: (f64, f64)| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+    let (p01, p12, p23) = (
+        lerp(points[0], points[1]),
+        lerp(points[1], points[2]),
+        lerp(points[2], points[3]),
+    );
+    let (p012, p123) = (lerp(p01, p12), lerp(p12, p23));
+    let point = lerp(p012, p123);
+    ([points[0], p01, p012, point], [point, p123, p23, points[3]])
+}
+
+/// The half of the cubic Bézier curve `points` on the side `side` of the axis of an arrow, where
+/// its ends are.
+fn half_curve(points: Curve, side: ArrowSide) -> Vec<(f64, f64)> {
+    let (first, second) = split(points, 0.5);
+    if (points[0].1 < 0.0) == (side == ArrowSide::Left) {
+        first.to_vec()
+    } else {
+        second.to_vec()
+    }
+}
+
+/// Shortens the spline through `points` by `length` at its end, clipping its last curve where it
+/// is that far from the end, for the arrowhead there.
+fn clip(points: &mut Vec<(f64, f64)>, length: f64) {
+    let n = points.len();
+    if length == 0.0 || n < 4 {
+        return;
+    }
+    let curve = [points[n - 4], points[n - 3], points[n - 2], points[n - 1]];
+    let end = curve[3];
+    let (mut inside, mut outside) = (0.0, 1.0);
+    for _ in 0..32 {
+        let t = (inside + outside) / 2.0;
+        let point = split(curve, t).0[3];
+        if (point.0 - end.0).hypot(point.1 - end.1) > length {
+            inside = t;
+        } else {
+            outside = t;
+        }
+    }
+    points.truncate(n - 4);
+    points.extend_from_slice(&split(curve, inside).0);
+}
+
+/// The shapes of the arrowhead `kind` at `arrowsize` `size`, with its tip at `tip` and pointing
+/// in the direction `(dx, dy)` of length 1. Shapes are stacked along the edge from the tip, with
+/// lines along the edge where they leave gaps, and filled unless open or made of lines.
+fn arrowhead(kind: &ArrowType, tip: (f64, f64), (dx, dy): (f64, f64), size: f64) -> Vec<Item> {
+    if arrowhead_length(kind, size) == 0.0 {
+        return vec![];
+    }
+    let unit = ARROW_LENGTH * size;
+    let (nx, ny) = (-dy, dx);
+    let mut items = vec![];
+    let mut start = 0.0;
+    for arrow in kind.arrows() {
+        // points in the frame of the shape: along the edge backwards from its tip, and across
+        // the edge, negative on the left, in units of the length of an arrow
+        let at = |(along, across): (f64, f64)| {
+            (
+                tip.0 - dx * (start + along) * unit + nx * across * unit,
+                tip.1 - dy * (start + along) * unit + ny * across * unit,
+            )
+        };
+        let half = |(along, across): (f64, f64)| match arrow.side {
+            Some(ArrowSide::Left) => (along, across.min(0.0)),
+            Some(ArrowSide::Right) => (along, across.max(0.0)),
+            None => (along, across),
+        };
+        let polygon = |points: &[(f64, f64)]| {
+            Primitive::Polygon(points.iter().map(|&point| at(half(point))).collect())
+        };
+        let line = |from: f64, to: f64| {
+            let points = (0..4).map(|i| at((from + (to - from) * i as f64 / 3.0, 0.0)));
+            Primitive::Bezier(points.collect())
+        };
+        let curve = |points: Curve| {
+            let points = match arrow.side {
+                Some(side) => half_curve(points, side),
+                None => points.to_vec(),
+            };
+            Primitive::Bezier(points.into_iter().map(at).collect())
+        };
+        let length = arrow_length(arrow.shape);
+        let (shapes, closed) = match arrow.shape {
+            ArrowShape::None => (vec![line(0.0, length)], false),
+            ArrowShape::Normal => (
+                vec![polygon(&[(0.0, 0.0), (1.0, 0.35), (1.0, -0.35)])],
+                true,
+            ),
+            ArrowShape::Inv => (
+                vec![polygon(&[(1.0, 0.0), (0.0, 0.35), (0.0, -0.35)])],
+                true,
+            ),
+            ArrowShape::Vee => (
+                vec![
+                    polygon(&[(0.0, 0.0), (1.0, 0.35), (0.6, 0.0), (1.0, -0.35)]),
+                    line(0.6, 1.0),
+                ],
+                true,
+            ),
+            ArrowShape::Crow => (
+                vec![
+                    polygon(&[(0.0, 0.45), (1.0, 0.0), (0.0, -0.45), (0.4, 0.0)]),
+                    line(0.0, 0.4),
+                ],
+                true,
+            ),
+            ArrowShape::Tee => (
+                vec![
+                    polygon(&[(0.2, 0.5), (0.4, 0.5), (0.4, -0.5), (0.2, -0.5)]),
+                    line(0.0, length),
+                ],
+                true,
+            ),
+            ArrowShape::Box => (
+                vec![
+                    polygon(&[(0.0, 0.4), (0.8, 0.4), (0.8, -0.4), (0.0, -0.4)]),
+                    line(0.8, 1.0),
+                ],
+                true,
+            ),
+            ArrowShape::Diamond => (
+                vec![polygon(&[(0.0, 0.0), (0.6, 0.3), (1.2, 0.0), (0.6, -0.3)])],
+                true,
+            ),
+            ArrowShape::Dot => {
+                let shape = match arrow.side {
+                    None => Primitive::Ellipse {
+                        center: at((0.4, 0.0)),
+                        radii: (0.4 * unit, 0.4 * unit),
+                    },
+                    Some(_) => polygon(&flatten(&ellipse((0.4, 0.0), (0.4, 0.4)), 0.05)),
+                };
+                (vec![shape], true)
+            }
+            ArrowShape::Curve => (
+                vec![
+                    curve([(0.9, -0.45), (0.3, -0.45), (0.3, 0.45), (0.9, 0.45)]),
+                    line(0.0, length),
+                ],
+                false,
+            ),
+            ArrowShape::ICurve => (
+                vec![
+                    curve([(0.1, -0.45), (0.7, -0.45), (0.7, 0.45), (0.1, 0.45)]),
+                    line(0.0, length),
+                ],
+                false,
+            ),
+        };
+        for shape in shapes {
+            let filled = closed
+                && !arrow.open
+                && matches!(shape, Primitive::Polygon(_) | Primitive::Ellipse { .. });
+            items.push(Item {
+                shape,
+                stroke: None,
+                fill: if filled { Some(Rgba::BLACK) } else { None },
+            });
+        }
+        start += length;
+    }
+    items
+}
+
+/// The point at the middle of the spline through `points`.
+fn middle(points: &[(f64, f64)]) -> (f64, f64) {
+    let curves = (points.len() - 1) / 3;
+    if curves == 0 {
+        return points[points.len() / 2];
+    }
+    let start = 3 * (curves / 2);
+    let t = if curves % 2 == 1 { 0.5 } else { 0.0 };
+    let p = &points[start..start + 4];
+    let u = 1.0 - t;
+    let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+    let x = (0..4).map(|i| weights[i] * p[i].0).sum();
+    let y = (0..4).map(|i| weights[i] * p[i].1).sum();
+    (x, y)
+}
+
+/// The outline of the line through `points` drawn `width` wide and tapering as `dir` says, as
+/// Graphviz's `tapered` style: from the whole width at the tail to nothing at the head forward,
+/// the other way back, widest in the middle both ways, and as wide all along without a direction.
+fn taper(points: &[(f64, f64)], width: f64, dir: &DirType) -> Vec<(f64, f64)> {
+    let line = flatten(points, 2.0);
+    let mut lengths = vec![0.0];
+    for pair in line.windows(2) {
+        let length = (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1);
+        lengths.push(lengths[lengths.len() - 1] + length);
+    }
+    let total = lengths[lengths.len() - 1].max(f64::EPSILON);
+    let (mut left, mut right) = (vec![], vec![]);
+    for (index, &(x, y)) in line.iter().enumerate() {
+        let fraction = lengths[index] / total;
+        let half = width / 2.0
+            * match dir {
+                DirType::Forward => 1.0 - fraction,
+                DirType::Back => fraction,
+                DirType::Both => 1.0 - (2.0 * fraction - 1.0).abs(),
+                DirType::None => 1.0,
+            };
+        let (from, to) = (
+            line[index.saturating_sub(1)],
+            line[(index + 1).min(line.len() - 1)],
+        );
+        let length = (to.0 - from.0).hypot(to.1 - from.1).max(f64::EPSILON);
+        let (nx, ny) = (-(to.1 - from.1) / length, (to.0 - from.0) / length);
+        left.push((x + nx * half, y + ny * half));
+        right.push((x - nx * half, y - ny * half));
+    }
+    left.extend(right.into_iter().rev());
+    left
+}
+
+/// The shapes drawing `edge` through `points` in drawing coordinates, with arrowheads at the
+/// ends in the directions `directed` implies unless `dir` says otherwise.
+fn draw_edge(
+    edge: &Edge,
+    points: &[(f64, f64)],
+    directed: bool,
+    font_path: &[PathBuf],
+) -> Vec<Item> {
+    let look = Look::new(&edge.attributes);
+    if look.invisible || points.len() < 2 {
+        return vec![];
+    }
+    let (mut head, mut tail) = (ArrowType::Normal, ArrowType::Normal);
+    let mut dir = if directed {
+        DirType::Forward
+    } else {
+        DirType::None
+    };
+    let mut size = 1.0;
+    for attribute in &edge.attributes {
+        match attribute {
+            Attribute::ArrowHead(kind) => head = kind.clone(),
+            Attribute::ArrowTail(kind) => tail = kind.clone(),
+            Attribute::ArrowSize(value) => size = *value,
+            Attribute::Dir(value) => dir = value.clone(),
+            _ => {}
+        }
+    }
+    // from the last point of `line` short of `to`, to `to`
+    let direction = |line: &[(f64, f64)], to: (f64, f64)| {
+        let from = line.iter().rev().find(|&&point| point != to);
+        let from = from.copied().unwrap_or(to);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = dx.hypot(dy).max(f64::EPSILON);
+        (dx / length, dy / length)
+    };
+    let colored = |items: Vec<Item>| {
+        items.into_iter().map(|item| Item {
+            fill: item.fill.map(|_| look.color),
+            stroke: Some(Stroke {
+                dashes: vec![],
+                ..look.stroke().unwrap_or(Stroke {
+                    color: look.color,
+                    width: 1.0,
+                    dashes: vec![],
+                })
+            }),
+            ..item
+        })
+    };
+    // the line stops where the arrowheads start, pointing them along its ends
+    let mut line = points.to_vec();
+    let mut arrows = vec![];
+    if matches!(dir, DirType::Forward | DirType::Both) {
+        let tip = points[points.len() - 1];
+        clip(&mut line, arrowhead_length(&head, size));
+        let direction = direction(&line, tip);
+        arrows.extend(colored(arrowhead(&head, tip, direction, size)));
+    }
+    if matches!(dir, DirType::Back | DirType::Both) {
+        let tip = points[0];
+        line.reverse();
+        clip(&mut line, arrowhead_length(&tail, size));
+        let direction = direction(&line, tip);
+        line.reverse();
+        arrows.extend(colored(arrowhead(&tail, tip, direction, size)));
+    }
+    let mut items = vec![if look.tapered {
+        Item {
+            shape: Primitive::Polygon(taper(&line, look.width, &dir)),
+            stroke: None,
+            fill: Some(look.color),
+        }
+    } else {
+        Item {
+            shape: Primitive::Bezier(line),
+            stroke: look.stroke(),
+            fill: None,
+        }
+    }];
+    items.extend(arrows);
+    if let Some(label) = &look.label {
+        items.extend(match Structure::html(label, &look, font_path) {
+            Some(structure) => structure.draw(middle(points), (0.0, 0.0)),
+            None => look.text(label, middle(points), font_path),
+        });
+    }
+    items
+}
+
+/// Draws the laid out `graph`; see the [module documentation](self) for the details.
+pub fn draw(graph: &Graph) -> Result<Drawing, RenderError> {
+    if let Some(node) = graph.nodes().iter().find(|node| node.position().is_none()) {
+        return Err(RenderError::NotLaidOut(node.id.clone()));
+    }
+    let bb = graph
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Bb(bb) => Some((**bb).clone()),
+            _ => None,
+        })
+        .or_else(|| graph.bounding_box())
+        .unwrap_or(Rectangle {
+            lower_left: Point { x: 0.0, y: 0.0 },
+            upper_right: Point { x: 0.0, y: 0.0 },
+        });
+    let look = Look::new(&graph.attributes);
+    let font_path = font::font_path(graph);
+    let name = graph.id.clone().unwrap_or_default();
+    let mut pad = (DEFAULT_PAD, DEFAULT_PAD);
+    let mut background = None;
+    let mut location = TextLocation::Bottom;
+    for attribute in &graph.attributes {
+        match attribute {
+            Attribute::Pad(value) => {
+                pad = (value.x * POINTS_PER_INCH, value.y * POINTS_PER_INCH);
+            }
+            Attribute::BgColor(value) => background = color(value),
+            Attribute::LabelLoc(value) => location = value.clone(),
+            _ => {}
+        }
+    }
+    let structure = look
+        .label
+        .as_ref()
+        .and_then(|label| Structure::html(&label.replace("\\G", &name), &look, &font_path));
+    let label_height = look.label.as_ref().map_or(0.0, |label| match &structure {
+        Some(structure) => structure.size().1,
+        None => label.split("\\n").count() as f64 * LINE_HEIGHT * look.font_size,
+    });
+    let top = if location == TextLocation::Top {
+        label_height
+    } else {
+        0.0
+    };
+    let (width, height) = (
+        bb.upper_right.x - bb.lower_left.x,
+        bb.upper_right.y - bb.lower_left.y,
+    );
+    let point = |x: f64, y: f64| {
+        (
+            x - bb.lower_left.x + pad.0,
+            bb.upper_right.y - y + pad.1 + top,
+        )
+    };
+
+    let mut elements = vec![];
+    let mut label = vec![];
+    if let Some(text) = &look.label {
+        let y = if location == TextLocation::Top {
+            pad.1 + label_height / 2.0
+        } else {
+            pad.1 + height + label_height / 2.0
+        };
+        let center = (pad.0 + width / 2.0, y);
+        label = match &structure {
+            Some(structure) => structure.draw(center, (0.0, 0.0)),
+            None => look.text(&text.replace("\\G", &name), center, &font_path),
+        };
+    }
+    let graph_element = element(
+        ElementKind::Graph,
+        name.clone(),
+        &graph.attributes,
+        &[("\\G", &name)],
+        label,
+    );
+    if !graph_element.items.is_empty()
+        || graph_element.tooltip.is_some()
+        || graph_element.url.is_some()
+    {
+        elements.push(graph_element);
+    }
+    let arrow = if graph.is_directed() { "->" } else { "--" };
+    for edge in graph.edges() {
+        let (tail, head) = (&graph.nodes()[edge.tail], &graph.nodes()[edge.head]);
+        let points = edge
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Pos(Position::Spline(points)) => {
+                    Some(points.iter().map(|p| point(p.x, p.y)).collect::<Vec<_>>())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                let (from, to) = (tail.position().unwrap(), head.position().unwrap());
+                let (from, to) = (point(from.x, from.y), point(to.x, to.y));
+                (0..4)
+                    .map(|i| {
+                        let t = i as f64 / 3.0;
+                        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+                    })
+                    .collect()
+            });
+        let edge_name = format!("{} {} {}", tail.id, arrow, head.id);
+        let escapes = [
+            ("\\E", edge_name.as_str()),
+            ("\\T", &tail.id),
+            ("\\H", &head.id),
+            ("\\G", &name),
+        ];
+        elements.push(element(
+            ElementKind::Edge,
+            edge_name.clone(),
+            &edge.attributes,
+            &escapes,
+            draw_edge(edge, &points, graph.is_directed(), &font_path),
+        ));
+    }
+    for node in graph.nodes() {
+        let position = node.position().unwrap();
+        elements.push(element(
+            ElementKind::Node,
+            node.id.clone(),
+            &node.attributes,
+            &[("\\N", &node.id), ("\\G", &name)],
+            draw_node(
+                node,
+                point(position.x, position.y),
+                Structure::of(node, graph, &font_path).as_ref(),
+                &font_path,
+            ),
+        ));
+    }
+    Ok(Drawing {
+        width: width + 2.0 * pad.0,
+        height: height + 2.0 * pad.1 + label_height,
+        background,
+        elements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::Arrow, ir::parse_graph, layout};
+
+    #[test]
+    fn draw() {
+        let mut graph = Graph::from_ir(
+            &parse_graph(
+                r#"digraph { label="G" a [shape=box style=filled fillcolor=red] b [shape=doublecircle] c [style=invis] a -> b [label=x color=blue] b -> c }"#,
+            )
+            .unwrap(),
+        );
+        assert!(matches!(
+            super::draw(&graph),
+            Err(RenderError::NotLaidOut(_))
+        ));
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let drawing = super::draw(&graph).unwrap();
+        let bb = graph.bounding_box().unwrap();
+        assert_eq!(drawing.width, bb.upper_right.x + 8.0);
+        assert_eq!(drawing.height, bb.upper_right.y + 8.0 + 1.2 * 14.0);
+
+        let element = |name: &str| {
+            drawing
+                .elements
+                .iter()
+                .find(|element| element.name == name)
+                .unwrap()
+        };
+        assert_eq!(element("").kind, ElementKind::Graph);
+        let a = &element("a").items;
+        assert!(matches!(&a[0].shape, Primitive::Polygon(corners) if corners.len() == 4));
+        assert_eq!(a[0].fill, Some(Rgba::opaque(255, 0, 0)));
+        assert!(matches!(&a[1].shape, Primitive::Text(text) if text.text == "a"));
+        // the outer periphery of a double circle, then the inner one
+        let b = &element("b").items;
+        match (&b[0].shape, &b[1].shape) {
+            (Primitive::Ellipse { radii: outer, .. }, Primitive::Ellipse { radii: inner, .. }) => {
+                assert_eq!(outer.0 - inner.0, PERIPHERY_GAP)
+            }
+            shapes => panic!("{:?}", shapes),
+        }
+        assert!(element("c").items.is_empty());
+        let edge = &element("a -> b").items;
+        assert_eq!(
+            edge[0].stroke.as_ref().unwrap().color,
+            Rgba::opaque(0, 0, 255)
+        );
+        assert_eq!(edge[1].fill, Some(Rgba::opaque(0, 0, 255)));
+        assert!(matches!(&edge[2].shape, Primitive::Text(text) if text.text == "x"));
+    }
+
+    #[test]
+    fn styles() {
+        let source = r#"digraph {
+            s [shape=box style=striped fillcolor="red;0.25:blue"]
+            w [style=wedged fillcolor="red:green:blue"]
+            r [shape=box style="rounded,filled"]
+            m [shape=Msquare]
+            s -> w [style=tapered penwidth=4]
+        }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let drawing = super::draw(&graph).unwrap();
+        let element = |name: &str| {
+            let element = drawing.elements.iter().find(|element| element.name == name);
+            &element.unwrap().items
+        };
+        let width = |item: &Item| match &item.shape {
+            Primitive::Polygon(points) => points[1].0 - points[0].0,
+            shape => panic!("{:?}", shape),
+        };
+
+        // a quarter red, the rest blue, under the unfilled outline
+        let s = element("s");
+        assert_eq!(s[0].fill, Some(Rgba::opaque(255, 0, 0)));
+        assert_eq!(s[1].fill, Some(Rgba::opaque(0, 0, 255)));
+        assert!((width(&s[1]) - 3.0 * width(&s[0])).abs() < 1e-9);
+        assert!(s[2].stroke.is_some() && s[2].fill.is_none());
+
+        let w = element("w");
+        assert_eq!(
+            w.iter()
+                .filter(|item| matches!(item.shape, Primitive::Polygon(_)))
+                .count(),
+            3
+        );
+        assert!(matches!(&w[3].shape, Primitive::Ellipse { .. }));
+
+        let r = element("r");
+        assert!(matches!(&r[0].shape, Primitive::Polygon(points) if points.len() == 20));
+        assert_eq!(r[0].fill, Some(LIGHT_GRAY));
+
+        let m = element("m");
+        let lines = m
+            .iter()
+            .filter(|item| matches!(item.shape, Primitive::Bezier(_)));
+        assert_eq!(lines.count(), 4);
+
+        // a filled outline narrowing to the head
+        let edge = element("s -> w");
+        assert!(edge[0].stroke.is_none() && edge[0].fill.is_some());
+        match &edge[0].shape {
+            Primitive::Polygon(points) => {
+                let (first, last) = (points[0], points[points.len() - 1]);
+                assert!(((first.0 - last.0).hypot(first.1 - last.1) - 4.0).abs() < 1e-9);
+                let middle = points.len() / 2;
+                let tip = (points[middle - 1], points[middle]);
+                assert!((tip.0 .0 - tip.1 .0).hypot(tip.0 .1 - tip.1 .1) < 1e-9);
+            }
+            shape => panic!("{:?}", shape),
+        }
+    }
+
+    #[test]
+    fn arrowheads() {
+        let kind = ArrowType::from_keyword("lteeoldiamond").unwrap();
+        let left = Some(ArrowSide::Left);
+        assert_eq!(
+            kind.arrows(),
+            [
+                Arrow {
+                    shape: ArrowShape::Tee,
+                    open: false,
+                    side: left
+                },
+                Arrow {
+                    shape: ArrowShape::Diamond,
+                    open: true,
+                    side: left
+                }
+            ]
+        );
+        assert_eq!(kind.to_string(), "lteeoldiamond");
+        assert_eq!(ArrowType::from_keyword("invodot"), Some(ArrowType::InvODot));
+        assert_eq!(ArrowType::from_keyword("dotdotdotdotdot"), None);
+        assert_eq!(
+            arrowhead_length(&kind, 2.0),
+            (0.5 + 1.2) * ARROW_LENGTH * 2.0
+        );
+        assert_eq!(arrowhead_length(&ArrowType::None, 2.0), 0.0);
+
+        // pointing right: a tee filled with its line, then an open diamond above the edge
+        let items = arrowhead(&kind, (0.0, 0.0), (1.0, 0.0), 2.0);
+        assert_eq!(items.len(), 3);
+        assert!(items[0].fill.is_some() && items[2].fill.is_none());
+        assert!(matches!(&items[1].shape, Primitive::Bezier(_)));
+        match &items[2].shape {
+            Primitive::Polygon(points) => {
+                assert!(points.iter().all(|&(x, y)| x <= -10.0 && y <= 0.0));
+                assert!(points.contains(&(-34.0, 0.0)));
+            }
+            shape => panic!("{:?}", shape),
+        }
+
+        // the line of an edge stops where its arrowhead starts
+        let mut graph = Graph::from_ir(
+            &parse_graph("digraph { a -> b [arrowhead=odot arrowsize=1.5] }").unwrap(),
+        );
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let drawing = super::draw(&graph).unwrap();
+        let edge = &drawing.elements[0].items;
+        match (&edge[0].shape, &edge[1].shape) {
+            (Primitive::Bezier(line), Primitive::Ellipse { center, radii }) => {
+                let end = line[line.len() - 1];
+                let tip = (center.0, center.1 + radii.1);
+                assert!(((end.1 - tip.1) - -12.0).abs() < 0.01);
+                assert!(edge[1].fill.is_none());
+            }
+            shapes => panic!("{:?}", shapes),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! PDF documents of laid out graphs.
+//!
+//! The [`Drawing`](super::Drawing) of a graph is written in vector operators: lines and Bézier
+//! curves, with ellipses made of four curves each, on pages laid out as
+//! [`backend`](super::backend) describes, one the size of the drawing unless the graph has a
+//! `page` size. Text is set in the standard PostScript font closest to its `fontname` (see
+//! [`standard_font`]), which PDF readers are required to provide, so that documents stay small
+//! and text stays searchable without embedding fonts; only Latin-1 characters can be set this
+//! way, others being written as `?`. Text mixing directions is reordered to be read from the
+//! left, as [`visual`](crate::font::visual) describes.
+
+use std::{
+    collections::BTreeSet,
+    io::{self, Write},
+};
+
+use crate::{
+    color::Rgba,
+    font::{standard_font, Font},
+    model::Graph,
+};
+
+use super::{
+    backend::{render, Document, Layout, Page, Paint, Path, Pen, RenderBackend},
+    draw, ellipse, escape, number, Text,
+};
+
+/// Writes `graph`, which must be laid out, as a PDF document.
+pub fn write(graph: &Graph, writer: &mut dyn Write) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let layout = Layout::new(graph, &drawing, 0.0, true);
+    let mut backend = Pdf {
+        writer,
+        pen: Pen::default(),
+        fonts: vec![],
+        alphas: BTreeSet::new(),
+        pages: vec![],
+        content: String::new(),
+        height: 0.0,
+    };
+    render(
+        &drawing,
+        &layout,
+        graph.id.as_deref().unwrap_or(""),
+        &mut backend,
+    )
+}
+
+/// A document being written: the content streams of its pages, with the resources they use.
+struct Pdf<'a> {
+    writer: &'a mut dyn Write,
+    pen: Pen,
+    fonts: Vec<&'static str>,
+    /// Opacities of fills and strokes, as the names of their graphics states: the key setting
+    /// them, `ca` or `CA`, followed by the opacity from 0 to 255.
+    alphas: BTreeSet<String>,
+    /// The size and content stream of each page written.
+    pages: Vec<((f64, f64), String)>,
+    /// The content stream of the current page.
+    content: String,
+    height: f64,
+}
+
+impl Pdf<'_> {
+    /// A point in page coordinates, with the y axis pointing up.
+    fn point(&self, (x, y): (f64, f64)) -> String {
+        format!("{} {}", number(x), number(self.height - y))
+    }
+
+    fn color(&mut self, color: Rgba, operator: &str, alpha: &str) {
+        let channel = |value: u8| number(f64::from(value) / 255.0);
+        self.content += &format!(
+            "{} {} {} {}\n",
+            channel(color.r),
+            channel(color.g),
+            channel(color.b),
+            operator
+        );
+        if color.a < 255 {
+            let name = format!("{}{}", alpha, color.a);
+            self.content += &format!("/{} gs\n", name);
+            self.alphas.insert(name);
+        }
+    }
+
+    fn curves(&self, points: &[(f64, f64)]) -> String {
+        let mut path = format!("{} m\n", self.point(points[0]));
+        for curve in points[1..].chunks_exact(3) {
+            let curve = curve.iter().map(|&point| self.point(point));
+            path += &format!("{} c\n", curve.collect::<Vec<_>>().join(" "));
+        }
+        path
+    }
+
+    fn path(&mut self, shape: &Path) {
+        let mut path = String::new();
+        match shape {
+            Path::Polygon(points) => {
+                for (index, &point) in points.iter().enumerate() {
+                    let operator = if index == 0 { "m" } else { "l" };
+                    path += &format!("{} {}\n", self.point(point), operator);
+                }
+                path += "h\n";
+            }
+            Path::Ellipse { center, radii } => {
+                path += &self.curves(&ellipse(*center, *radii));
+                path += "h\n";
+            }
+            Path::Bezier(points) => path += &self.curves(points),
+        }
+        self.content += &path;
+    }
+}
+
+impl RenderBackend for Pdf<'_> {
+    fn begin(&mut self, _document: &Document) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_page(&mut self, page: &Page) -> io::Result<()> {
+        self.height = page.size.1;
+        let ((x, y), (width, height)) = page.area;
+        self.content = format!(
+            "{} {} {} {} re W n\n",
+            number(x),
+            number(page.size.1 - y - height),
+            number(width),
+            number(height)
+        );
+        Ok(())
+    }
+
+    fn set_color(&mut self, paint: Paint, color: Rgba) -> io::Result<()> {
+        self.pen.set_color(paint, color);
+        Ok(())
+    }
+
+    fn set_line(&mut self, width: f64, dashes: &[f64]) -> io::Result<()> {
+        self.pen.set_line(width, dashes);
+        Ok(())
+    }
+
+    fn draw_path(&mut self, path: &Path, fill: bool, stroke: bool) -> io::Result<()> {
+        self.content += "q\n";
+        if fill {
+            self.color(self.pen.fill, "rg", "ca");
+        }
+        if stroke {
+            self.color(self.pen.stroke, "RG", "CA");
+            let dashes = self.pen.dashes.iter().map(|&length| number(length));
+            self.content += &format!(
+                "{} w\n[{}] 0 d\n",
+                number(self.pen.width),
+                dashes.collect::<Vec<_>>().join(" ")
+            );
+        }
+        self.path(path);
+        self.content += match (fill, stroke) {
+            (true, true) => "B",
+            (true, false) => "f",
+            _ => "S",
+        };
+        self.content += "\nQ\n";
+        Ok(())
+    }
+
+    fn draw_text(&mut self, text: &Text, angle: f64) -> io::Result<()> {
+        let font = standard_font(&text.font);
+        let index = match self.fonts.iter().position(|&used| used == font) {
+            Some(index) => index,
+            None => {
+                self.fonts.push(font);
+                self.fonts.len() - 1
+            }
+        };
+        // centered by the width of the text in the font it's set in, along its baseline
+        let width = Font::standard(font).width(&text.text, text.size);
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let start = (
+            text.position.0 - width / 2.0 * cos,
+            text.position.1 + width / 2.0 * sin,
+        );
+        self.content += "q\n";
+        self.color(text.color, "rg", "ca");
+        let placement = if angle == 0.0 {
+            format!("{} Td", self.point(start))
+        } else {
+            let turn = [cos, sin, -sin, cos].map(number).join(" ");
+            format!("{} {} Tm", turn, self.point(start))
+        };
+        self.content += &format!(
+            "BT\n/F{} {} Tf\n{}\n({}) Tj\nET\nQ\n",
+            index + 1,
+            number(text.size),
+            placement,
+            escape(&text.text)
+        );
+        Ok(())
+    }
+
+    fn end_page(&mut self, page: &Page) -> io::Result<()> {
+        let content = std::mem::take(&mut self.content);
+        self.pages.push((page.size, content));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // the catalog, the page tree, each page and its content stream, then fonts
+        let first_font = 3 + 2 * self.pages.len();
+        let fonts = self
+            .fonts
+            .iter()
+            .enumerate()
+            .map(|(index, _)| format!("/F{} {} 0 R", index + 1, first_font + index))
+            .collect::<Vec<_>>();
+        let states = self
+            .alphas
+            .iter()
+            .map(|name| {
+                let (key, alpha) = name.split_at(2);
+                let alpha = number(alpha.parse::<f64>().unwrap() / 255.0);
+                format!("/{} << /{} {} >>", name, key, alpha)
+            })
+            .collect::<Vec<_>>();
+        let kids = (0..self.pages.len())
+            .map(|index| format!("{} 0 R", 3 + 2 * index))
+            .collect::<Vec<_>>();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                kids.len()
+            ),
+        ];
+        for (index, ((width, height), content)) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << {} >> /ExtGState << {} >> >> /Contents {} 0 R >>",
+                number(*width),
+                number(*height),
+                fonts.join(" "),
+                states.join(" "),
+                4 + 2 * index
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+        for font in &self.fonts {
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                font
+            ));
+        }
+
+        // a comment of bytes above 127 first, to mark the file as binary
+        let mut file = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = vec![];
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(file.len());
+            file.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", index + 1, object).as_bytes());
+        }
+        let xref = file.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            table += &format!("{:010} 00000 n \n", offset);
+        }
+        table += &format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        file.extend_from_slice(table.as_bytes());
+        self.writer.write_all(&file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout};
+
+    #[test]
+    fn pdf() {
+        let source = r##"digraph { a [label="(é)" fontname="Helvetica Bold" style=filled fillcolor="#ff000080"] a -> b }"##;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output).unwrap();
+        let pdf = String::from_utf8_lossy(&output);
+        assert!(pdf.starts_with("%PDF-1.4"));
+        // every object where the cross-reference table says it is
+        let xref = output
+            .windows(5)
+            .position(|word| word == b"xref\n")
+            .unwrap();
+        let table = String::from_utf8(output[xref..].to_vec()).unwrap();
+        for (index, line) in table.lines().skip(3).take(6).enumerate() {
+            let offset = line[..10].parse::<usize>().unwrap();
+            let object = format!("{} 0 obj", index + 1);
+            assert!(output[offset..].starts_with(object.as_bytes()));
+        }
+        assert!(pdf.contains(&format!("startxref\n{}\n", xref)));
+        assert!(pdf.contains("/BaseFont /Helvetica-Bold"));
+        assert!(pdf.contains("/BaseFont /Times-Roman"));
+        assert!(pdf.contains("(\\(\\351\\)) Tj"));
+        assert!(pdf.contains("1 0 0 rg\n/ca128 gs"));
+        assert!(pdf.contains("/ca128 << /ca 0.5 >>"));
+
+        // a page of its own for each node of a chain down pages an inch high
+        let mut graph =
+            Graph::from_ir(&parse_graph("digraph { page=\"1,1\" a -> b -> c }").unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output).unwrap();
+        let pdf = String::from_utf8_lossy(&output);
+        assert!(pdf.contains("/Kids [3 0 R 5 0 R 7 0 R] /Count 3"));
+        assert_eq!(pdf.matches("/MediaBox [0 0 72 72]").count(), 3);
+    }
+}
@@ -0,0 +1,717 @@
+//! PNG images of laid out graphs.
+//!
+//! The [`Drawing`] of a graph is rasterized at the resolution of [`PngOptions::dpi`], scaled and
+//! turned as [`backend`](super::backend) describes: shapes are flattened to polygons, lines
+//! widened to polygons, and polygons filled with antialiasing by their exact coverage of each
+//! pixel across a few sample rows. Text is drawn with a built-in bitmap font scaled to the font
+//! size, its characters in the order they're read in from the left, without combining marks,
+//! spread over the width of the text in the font of its `fontname`, as no fonts are rasterized.
+//!
+//! Images are encoded as 8-bit RGBA, compressed with fixed Huffman codes, which suit the long
+//! runs of a few colors in drawings well enough without the cost of choosing codes per image.
+
+use std::{
+    f64::consts::PI,
+    io::{self, Write},
+};
+
+use crate::{
+    color::Rgba,
+    font::{is_mark, visual},
+    model::Graph,
+};
+
+use super::{
+    backend::{render, Document, Layout, Page, Paint, Path, Pen, RenderBackend},
+    dpi, draw, flatten, Drawing, Text, DEFAULT_DPI,
+};
+
+mod font;
+
+/// Sample rows per row of pixels.
+const SAMPLES: usize = 4;
+
+/// Length in pixels of the pieces curves are flattened to.
+const FLATNESS: f64 = 2.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngOptions {
+    /// Pixels per inch.
+    pub dpi: f64,
+    /// The color of the background, instead of the graph's `bgcolor`, or white if it has none.
+    pub background: Option<Rgba>,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            dpi: DEFAULT_DPI,
+            background: None,
+        }
+    }
+}
+
+impl PngOptions {
+    /// The options set by the graph's `dpi`.
+    pub fn from_graph(graph: &Graph) -> Self {
+        PngOptions {
+            dpi: dpi(graph),
+            ..PngOptions::default()
+        }
+    }
+}
+
+/// Pixels, row by row from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgba>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: usize, y: usize) -> Rgba {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Draws `color` over the pixel at `(x, y)`, covering the given part of it.
+    fn blend(&mut self, x: usize, y: usize, color: Rgba, coverage: f64) {
+        let pixel = &mut self.pixels[y * self.width + x];
+        let alpha = f64::from(color.a) / 255.0 * coverage.min(1.0);
+        let under = f64::from(pixel.a) / 255.0 * (1.0 - alpha);
+        let total = alpha + under;
+        if total <= 0.0 {
+            return;
+        }
+        let mix = |top: u8, bottom: u8| {
+            ((f64::from(top) * alpha + f64::from(bottom) * under) / total).round() as u8
+        };
+        *pixel = Rgba {
+            r: mix(color.r, pixel.r),
+            g: mix(color.g, pixel.g),
+            b: mix(color.b, pixel.b),
+            a: (total * 255.0).round() as u8,
+        };
+    }
+
+    /// Fills the union of `polygons` with `color`.
+    fn fill(&mut self, polygons: &[Vec<(f64, f64)>], color: Rgba) {
+        // edges going down, with +1 if they went down in their polygon and -1 if up
+        let mut edges = vec![];
+        let (mut top, mut bottom) = (f64::INFINITY, f64::NEG_INFINITY);
+        for polygon in polygons {
+            for (i, &a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+                top = top.min(a.1);
+                bottom = bottom.max(a.1);
+                if a.1 < b.1 {
+                    edges.push((a, b, 1));
+                } else if a.1 > b.1 {
+                    edges.push((b, a, -1));
+                }
+            }
+        }
+        if color.a == 0 || edges.is_empty() {
+            return;
+        }
+        let first = top.floor().max(0.0) as usize;
+        let last = (bottom.ceil().max(0.0) as usize).min(self.height);
+        let mut coverage = vec![0.0; self.width];
+        let mut crossings = vec![];
+        for row in first..last {
+            let (mut left, mut right) = (self.width, 0);
+            for sample in 0..SAMPLES {
+                let y = row as f64 + (sample as f64 + 0.5) / SAMPLES as f64;
+                crossings.clear();
+                for &(a, b, winding) in &edges {
+                    let ((x0, y0), (x1, y1)): ((f64, f64), (f64, f64)) = (a, b);
+                    if y0 <= y && y < y1 {
+                        crossings.push((x0 + (y - y0) * (x1 - x0) / (y1 - y0), winding));
+                    }
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+                // spans of nonzero winding
+                let (mut winding, mut start) = (0, 0.0);
+                for &(x, change) in &crossings {
+                    if winding == 0 {
+                        start = x;
+                    }
+                    winding += change;
+                    if winding == 0 {
+                        let (a, b) = (start.max(0.0), x.min(self.width as f64));
+                        if a < b {
+                            cover(&mut coverage, a, b, 1.0 / SAMPLES as f64);
+                            left = left.min(a as usize);
+                            right = right.max((b.ceil() as usize).min(self.width));
+                        }
+                    }
+                }
+            }
+            for (x, covered) in coverage.iter_mut().enumerate().take(right).skip(left) {
+                if *covered > 0.0 {
+                    self.blend(x, row, color, *covered);
+                    *covered = 0.0;
+                }
+            }
+        }
+    }
+}
+
+/// Adds `weight` times the part of each pixel covered by the span from `a` to `b`.
+fn cover(coverage: &mut [f64], a: f64, b: f64, weight: f64) {
+    let (i, j) = (a as usize, b as usize);
+    if i == j {
+        coverage[i] += (b - a) * weight;
+        return;
+    }
+    coverage[i] += (i as f64 + 1.0 - a) * weight;
+    for pixel in &mut coverage[i + 1..j] {
+        *pixel += weight;
+    }
+    if j < coverage.len() {
+        coverage[j] += (b - j as f64) * weight;
+    }
+}
+
+/// The polygon of an ellipse.
+fn ellipse((cx, cy): (f64, f64), (rx, ry): (f64, f64)) -> Vec<(f64, f64)> {
+    let sides = ((rx + ry) * PI / FLATNESS).ceil().clamp(12.0, 360.0) as usize;
+    (0..sides)
+        .map(|side| {
+            let angle = 2.0 * PI * side as f64 / sides as f64;
+            (cx + rx * angle.cos(), cy + ry * angle.sin())
+        })
+        .collect()
+}
+
+/// `polygon`, turned counterclockwise if it's clockwise, so that overlapping polygons add up
+/// rather than cancel out.
+fn counterclockwise(mut polygon: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let area: f64 = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    if area < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+/// The dashes of the line through `points`, following the lengths of dashes and gaps in
+/// `pattern`, or the whole line if it's empty.
+fn dashes(points: &[(f64, f64)], pattern: &[f64]) -> Vec<Vec<(f64, f64)>> {
+    if pattern.is_empty() || pattern.iter().all(|&length| length <= 0.0) {
+        return vec![points.to_vec()];
+    }
+    let mut dashes = vec![];
+    let (mut index, mut left) = (0, pattern[0]);
+    let mut dash = vec![points[0]];
+    for pair in points.windows(2) {
+        let (mut a, b) = (pair[0], pair[1]);
+        let mut length = (b.0 - a.0).hypot(b.1 - a.1);
+        while length > left {
+            let t = left / length;
+            a = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+            length -= left;
+            if index % 2 == 0 {
+                dash.push(a);
+                dashes.push(std::mem::take(&mut dash));
+            } else {
+                dash = vec![a];
+            }
+            index = (index + 1) % pattern.len();
+            left = pattern[index];
+        }
+        left -= length;
+        if index % 2 == 0 {
+            dash.push(b);
+        }
+    }
+    if index % 2 == 0 && dash.len() > 1 {
+        dashes.push(dash);
+    }
+    dashes
+}
+
+/// The polygons covering the line through `points`, of the given `width`, with round joins and
+/// ends.
+fn widen(points: &[(f64, f64)], width: f64) -> Vec<Vec<(f64, f64)>> {
+    let half = width / 2.0;
+    let mut polygons = vec![];
+    for pair in points.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let length = (b.0 - a.0).hypot(b.1 - a.1);
+        if length > 0.0 {
+            let (nx, ny) = (-(b.1 - a.1) / length * half, (b.0 - a.0) / length * half);
+            polygons.push(counterclockwise(vec![
+                (a.0 + nx, a.1 + ny),
+                (b.0 + nx, b.1 + ny),
+                (b.0 - nx, b.1 - ny),
+                (a.0 - nx, a.1 - ny),
+            ]));
+        }
+    }
+    for &point in points {
+        polygons.push(counterclockwise(ellipse(point, (half, half))));
+    }
+    polygons
+}
+
+/// Draws the outline of the shape through `points` with the line of `pen`.
+fn stroke(image: &mut Image, points: &[(f64, f64)], pen: &Pen, scale: f64) {
+    let pattern = pen
+        .dashes
+        .iter()
+        .map(|length| length * scale)
+        .collect::<Vec<_>>();
+    let polygons = dashes(points, &pattern)
+        .iter()
+        .flat_map(|dash| widen(dash, pen.width * scale))
+        .collect::<Vec<_>>();
+    image.fill(&polygons, pen.stroke);
+}
+
+/// Draws `text` with the built-in font, turned `angle` degrees counterclockwise.
+fn text(image: &mut Image, text: &Text, scale: f64, angle: f64) {
+    // a dot of the font, for glyphs 7/12 of the font size high, each in the middle of an equal
+    // share of the width of the text
+    let dot = text.size * scale / 12.0;
+    let width = text.width * scale;
+    let chars = visual(&text.text)
+        .chars()
+        .filter(|&c| !is_mark(c))
+        .collect::<Vec<_>>();
+    let advance = width / chars.len().max(1) as f64;
+    let (left, baseline) = (
+        text.position.0 * scale - width / 2.0 + (advance - font::WIDTH as f64 * dot) / 2.0,
+        text.position.1 * scale,
+    );
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let origin = (text.position.0 * scale, baseline);
+    let turn = |(x, y): (f64, f64)| {
+        let (dx, dy) = (x - origin.0, y - origin.1);
+        (
+            origin.0 + dx * cos + dy * sin,
+            origin.1 - dx * sin + dy * cos,
+        )
+    };
+    let mut dots = vec![];
+    for (index, &c) in chars.iter().enumerate() {
+        for (row, bits) in font::glyph(c).iter().enumerate() {
+            for column in 0..font::WIDTH {
+                if bits & (1 << (font::WIDTH - 1 - column)) != 0 {
+                    let x = left + index as f64 * advance + column as f64 * dot;
+                    let y = baseline - (font::HEIGHT - row) as f64 * dot;
+                    let dot = [(x, y), (x + dot, y), (x + dot, y + dot), (x, y + dot)];
+                    dots.push(dot.iter().map(|&corner| turn(corner)).collect());
+                }
+            }
+        }
+    }
+    image.fill(&dots, text.color);
+}
+
+/// Draws on an image, in pixels `scale` times the size of points.
+struct Raster {
+    image: Image,
+    scale: f64,
+    pen: Pen,
+}
+
+impl RenderBackend for Raster {
+    fn begin(&mut self, _document: &Document) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_page(&mut self, page: &Page) -> io::Result<()> {
+        let (width, height) = (
+            (page.size.0 * self.scale).ceil().max(1.0) as usize,
+            (page.size.1 * self.scale).ceil().max(1.0) as usize,
+        );
+        let clear = Rgba {
+            a: 0,
+            ..Rgba::WHITE
+        };
+        self.image = Image {
+            width,
+            height,
+            pixels: vec![clear; width * height],
+        };
+        Ok(())
+    }
+
+    fn set_color(&mut self, paint: Paint, color: Rgba) -> io::Result<()> {
+        self.pen.set_color(paint, color);
+        Ok(())
+    }
+
+    fn set_line(&mut self, width: f64, dashes: &[f64]) -> io::Result<()> {
+        self.pen.set_line(width, dashes);
+        Ok(())
+    }
+
+    fn draw_path(&mut self, path: &Path, fill: bool, stroke: bool) -> io::Result<()> {
+        let scale = self.scale;
+        let at = |&(x, y): &(f64, f64)| (x * scale, y * scale);
+        let (outline, closed) = match path {
+            Path::Ellipse { center, radii } => (ellipse(at(center), at(radii)), true),
+            Path::Polygon(points) => (points.iter().map(at).collect(), true),
+            Path::Bezier(points) => {
+                let points = points.iter().map(at).collect::<Vec<_>>();
+                (flatten(&points, FLATNESS), false)
+            }
+        };
+        if fill {
+            self.image
+                .fill(std::slice::from_ref(&outline), self.pen.fill);
+        }
+        if stroke {
+            let mut line = outline;
+            if closed {
+                line.push(line[0]);
+            }
+            self::stroke(&mut self.image, &line, &self.pen, scale);
+        }
+        Ok(())
+    }
+
+    fn draw_text(&mut self, shape: &Text, angle: f64) -> io::Result<()> {
+        text(&mut self.image, shape, self.scale, angle);
+        Ok(())
+    }
+
+    fn end_page(&mut self, _page: &Page) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Draws `drawing`, laid out as `layout`, at the resolution and over the background of
+/// `options`.
+pub fn rasterize(drawing: &Drawing, layout: &Layout, options: &PngOptions) -> Image {
+    let layout = Layout {
+        background: Some(
+            options
+                .background
+                .or(layout.background)
+                .unwrap_or(Rgba::WHITE),
+        ),
+        ..layout.clone()
+    };
+    let mut raster = Raster {
+        image: Image {
+            width: 0,
+            height: 0,
+            pixels: vec![],
+        },
+        scale: options.dpi / 72.0,
+        pen: Pen::default(),
+    };
+    render(drawing, &layout, "", &mut raster).expect("drawing on images doesn't fail");
+    raster.image
+}
+
+/// Writes `graph`, which must be laid out, as a PNG image.
+pub fn write(graph: &Graph, writer: &mut dyn Write, options: &PngOptions) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let layout = Layout::new(graph, &drawing, 0.0, false);
+    encode(&rasterize(&drawing, &layout, options), options.dpi, writer)
+}
+
+/// Writes `image` as a PNG file, recording its resolution of `dpi` pixels per inch.
+pub fn encode(image: &Image, dpi: f64, writer: &mut dyn Write) -> io::Result<()> {
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = vec![];
+    header.extend_from_slice(&(image.width as u32).to_be_bytes());
+    header.extend_from_slice(&(image.height as u32).to_be_bytes());
+    // 8 bits per channel, RGBA, then the only compression, filtering and interlacing methods
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    chunk(writer, b"IHDR", &header)?;
+    let per_meter = ((dpi / 0.0254).round() as u32).to_be_bytes();
+    let mut physical = vec![];
+    physical.extend_from_slice(&per_meter);
+    physical.extend_from_slice(&per_meter);
+    physical.push(1);
+    chunk(writer, b"pHYs", &physical)?;
+    // rows of pixels, each after the byte of its filter, none
+    let mut data = Vec::with_capacity((4 * image.width + 1) * image.height);
+    for row in image.pixels.chunks(image.width) {
+        data.push(0);
+        for pixel in row {
+            data.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+        }
+    }
+    chunk(writer, b"IDAT", &zlib(&data, 4 * image.width + 1))?;
+    chunk(writer, b"IEND", &[])
+}
+
+fn chunk(writer: &mut dyn Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Bits written from the least significant bit of each byte first, as in deflate streams.
+struct Bits {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits {
+    fn write(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which goes from its most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        self.write(code.reverse_bits() >> (32 - length), length);
+    }
+
+    fn literal(&mut self, value: u32) {
+        match value {
+            0..=143 => self.code(0x30 + value, 8),
+            144..=255 => self.code(0x190 + value - 144, 9),
+            256..=279 => self.code(value - 256, 7),
+            _ => self.code(0xC0 + value - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTHS: [(u32, u32); 29] = [
+    (3, 0),
+    (4, 0),
+    (5, 0),
+    (6, 0),
+    (7, 0),
+    (8, 0),
+    (9, 0),
+    (10, 0),
+    (11, 1),
+    (13, 1),
+    (15, 1),
+    (17, 1),
+    (19, 2),
+    (23, 2),
+    (27, 2),
+    (31, 2),
+    (35, 3),
+    (43, 3),
+    (51, 3),
+    (59, 3),
+    (67, 4),
+    (83, 4),
+    (99, 4),
+    (115, 4),
+    (131, 5),
+    (163, 5),
+    (195, 5),
+    (227, 5),
+    (258, 0),
+];
+
+const DISTANCES: [(u32, u32); 30] = [
+    (1, 0),
+    (2, 0),
+    (3, 0),
+    (4, 0),
+    (5, 1),
+    (7, 1),
+    (9, 2),
+    (13, 2),
+    (17, 3),
+    (25, 3),
+    (33, 4),
+    (49, 4),
+    (65, 5),
+    (97, 5),
+    (129, 6),
+    (193, 6),
+    (257, 7),
+    (385, 7),
+    (513, 8),
+    (769, 8),
+    (1025, 9),
+    (1537, 9),
+    (2049, 10),
+    (3073, 10),
+    (4097, 11),
+    (6145, 11),
+    (8193, 12),
+    (12289, 12),
+    (16385, 13),
+    (24577, 13),
+];
+
+const MAX_DISTANCE: usize = 32768;
+const MAX_LENGTH: usize = 258;
+const MIN_LENGTH: usize = 3;
+const HASH_BITS: u32 = 15;
+
+/// `data` compressed as a zlib stream, in one deflate block with fixed Huffman codes. Matches
+/// are looked for at the last place the next three bytes were seen, and one pixel and one row
+/// of `stride` bytes back, where drawings mostly repeat themselves.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = Bits {
+        bytes: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+    // the last block, with fixed codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+    let hash = |i: usize| {
+        let key = u32::from(data[i]) | u32::from(data[i + 1]) << 8 | u32::from(data[i + 2]) << 16;
+        (key.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    };
+    let mut last = vec![usize::MAX; 1 << HASH_BITS];
+    let mut i = 0;
+    while i < data.len() {
+        let (mut length, mut distance) = (0, 0);
+        if i + MIN_LENGTH <= data.len() {
+            let seen = std::mem::replace(&mut last[hash(i)], i);
+            let longest = MAX_LENGTH.min(data.len() - i);
+            for &from in &[seen, i.wrapping_sub(4), i.wrapping_sub(stride)] {
+                if from < i && i - from <= MAX_DISTANCE {
+                    let matched = (0..longest)
+                        .take_while(|&k| data[from + k] == data[i + k])
+                        .count();
+                    if matched > length {
+                        length = matched;
+                        distance = i - from;
+                    }
+                }
+            }
+        }
+        if length >= MIN_LENGTH {
+            let code = LENGTHS
+                .iter()
+                .rposition(|&(base, _)| base as usize <= length);
+            let code = code.unwrap();
+            let (base, extra) = LENGTHS[code];
+            bits.literal(257 + code as u32);
+            bits.write(length as u32 - base, extra);
+            let code = DISTANCES
+                .iter()
+                .rposition(|&(base, _)| base as usize <= distance);
+            let code = code.unwrap();
+            let (base, extra) = DISTANCES[code];
+            bits.code(code as u32, 5);
+            bits.write(distance as u32 - base, extra);
+            for k in i + 1..(i + length).min(data.len().saturating_sub(MIN_LENGTH - 1)) {
+                last[hash(k)] = k;
+            }
+            i += length;
+        } else {
+            bits.literal(u32::from(data[i]));
+            i += 1;
+        }
+    }
+    bits.literal(256);
+    let mut bytes = bits.finish();
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    bytes.extend_from_slice(&(b << 16 | a).to_be_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout};
+
+    #[test]
+    fn rasterize() {
+        let source =
+            r#"digraph { bgcolor=yellow dpi=144 a [shape=box style=filled fillcolor=red] a -> b }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let options = PngOptions::from_graph(&graph);
+        assert_eq!(options.dpi, 144.0);
+        let drawing = draw(&graph).unwrap();
+        let layout = Layout::new(&graph, &drawing, 0.0, false);
+        let image = super::rasterize(&drawing, &layout, &options);
+        assert_eq!(image.width, (drawing.width * 2.0).ceil() as usize);
+        assert_eq!(image.pixel(0, 0), Rgba::opaque(255, 255, 0));
+        // inside the box of `a`, off its label
+        let a = graph.nodes()[0].position().unwrap();
+        let bb = graph.bounding_box().unwrap();
+        let (x, y) = (a.x + 4.0 - 20.0, bb.upper_right.y - a.y + 4.0 - 12.0);
+        assert_eq!(
+            image.pixel((x * 2.0) as usize, (y * 2.0) as usize),
+            Rgba::opaque(255, 0, 0)
+        );
+        // the edge between the nodes
+        let b = graph.nodes()[1].position().unwrap();
+        let y = bb.upper_right.y - (a.y + b.y) / 2.0 + 4.0;
+        let (x, y) = (((a.x + 4.0) * 2.0) as usize, (y * 2.0) as usize);
+        let darkest = (x - 2..=x + 2).map(|x| image.pixel(x, y).g).min().unwrap();
+        assert!(darkest < 64);
+    }
+
+    #[test]
+    fn encode() {
+        let image = Image {
+            width: 40,
+            height: 3,
+            pixels: vec![Rgba::WHITE; 120],
+        };
+        let mut png = vec![];
+        super::encode(&image, 72.0, &mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + length]);
+            let crc = &rest[8 + length..12 + length];
+            assert_eq!(crc, crc32(kind.iter().chain(data)).to_be_bytes());
+            chunks.push(String::from_utf8(kind.to_vec()).unwrap());
+            rest = &rest[12 + length..];
+        }
+        assert_eq!(chunks, ["IHDR", "pHYs", "IDAT", "IEND"]);
+        // the rows of white repeat the first pixel, so compress to a few bytes
+        let idat = png.windows(4).position(|kind| kind == b"IDAT").unwrap();
+        let length =
+            u32::from_be_bytes([png[idat - 4], png[idat - 3], png[idat - 2], png[idat - 1]]);
+        assert!(length < 30, "{}", length);
+    }
+}
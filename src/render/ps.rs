@@ -0,0 +1,298 @@
+//! PostScript and Encapsulated PostScript output of laid out graphs, following the conventions
+//! of Graphviz's `-Tps` and `-Teps`.
+//!
+//! The [`Drawing`](super::Drawing) of a graph is drawn a `margin` away from the corner of the
+//! page, half an inch by default, and laid on pages as [`backend`](super::backend) describes:
+//! PostScript graphs with a `page` size are split over as many pages as they need, while EPS
+//! always has a single page the size of the drawing. Text is set in the standard PostScript font
+//! closest to its `fontname`, in Latin-1, and in the order it's read in from the left.
+
+use std::io::{self, Write};
+
+use crate::{color::Rgba, font::standard_font, model::Graph};
+
+use super::{
+    backend::{render, Document, Layout, Page, Paint, Path, Pen, RenderBackend},
+    draw, ellipse, escape, number, Element, Text,
+};
+
+/// Margin around drawings in points, when the graph has no `margin`.
+pub const DEFAULT_MARGIN: f64 = 36.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsFormat {
+    PostScript,
+    /// A single page for embedding in other documents, which ignores the `page` size.
+    Eps,
+}
+
+/// Procedures the pages use: `latin1font` selects a font reencoded in Latin-1, and `center`
+/// shows a string centered on the current point.
+const PROLOG: &str = "\
+/latin1font { % name size -> -
+  exch findfont dup length dict begin
+    { 1 index /FID ne { def } { pop pop } ifelse } forall
+    /Encoding ISOLatin1Encoding def
+    currentdict
+  end
+  /simpledot-latin1 exch definefont exch scalefont setfont
+} bind def
+/center { % string -> -
+  dup stringwidth pop 2 div neg 0 rmoveto show
+} bind def
+";
+
+fn color(color: Rgba) -> String {
+    let channel = |value: u8| number(f64::from(value) / 255.0);
+    format!(
+        "{} {} {} setrgbcolor\n",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+struct PostScript<'a> {
+    writer: &'a mut dyn Write,
+    format: PsFormat,
+    pen: Pen,
+    /// The bounding box of the pages, as in their comments.
+    bounds: String,
+    /// The height of the page, to turn the y axis up.
+    height: f64,
+}
+
+impl PostScript<'_> {
+    /// A point in page coordinates, with the y axis pointing up.
+    fn point(&self, (x, y): (f64, f64)) -> String {
+        format!("{} {}", number(x), number(self.height - y))
+    }
+
+    fn curves(&self, points: &[(f64, f64)]) -> String {
+        let mut path = format!("{} moveto\n", self.point(points[0]));
+        for curve in points[1..].chunks_exact(3) {
+            let curve = curve.iter().map(|&point| self.point(point));
+            path += &format!("{} curveto\n", curve.collect::<Vec<_>>().join(" "));
+        }
+        path
+    }
+}
+
+impl RenderBackend for PostScript<'_> {
+    fn begin(&mut self, document: &Document) -> io::Result<()> {
+        let ((x, y), (width, height)) = document.area;
+        let bounds = if document.paged {
+            (0.0, 0.0, document.size.0, document.size.1)
+        } else {
+            let bottom = document.size.1 - y - height;
+            (x, bottom, x + width, bottom + height)
+        };
+        let header = match self.format {
+            PsFormat::PostScript => "%!PS-Adobe-3.0",
+            PsFormat::Eps => "%!PS-Adobe-3.0 EPSF-3.0",
+        };
+        writeln!(self.writer, "{}", header)?;
+        writeln!(self.writer, "%%Creator: simpledot")?;
+        if !document.name.is_empty() {
+            writeln!(self.writer, "%%Title: {}", document.name.replace('\n', " "))?;
+        }
+        self.bounds = format!(
+            "{} {} {} {}",
+            bounds.0.floor(),
+            bounds.1.floor(),
+            bounds.2.ceil(),
+            bounds.3.ceil()
+        );
+        writeln!(self.writer, "%%Pages: {}", document.pages)?;
+        writeln!(self.writer, "%%BoundingBox: {}", self.bounds)?;
+        writeln!(self.writer, "%%EndComments")?;
+        writeln!(self.writer, "%%BeginProlog\n{}%%EndProlog", PROLOG)
+    }
+
+    fn begin_page(&mut self, page: &Page) -> io::Result<()> {
+        self.height = page.size.1;
+        let ((x, y), (width, height)) = page.area;
+        writeln!(self.writer, "%%Page: {} {}", page.index + 1, page.index + 1)?;
+        writeln!(self.writer, "%%PageBoundingBox: {}", self.bounds)?;
+        writeln!(self.writer, "gsave")?;
+        writeln!(
+            self.writer,
+            "{} {} {} {} rectclip",
+            number(x),
+            number(page.size.1 - y - height),
+            number(width),
+            number(height)
+        )
+    }
+
+    fn begin_element(&mut self, element: &Element) -> io::Result<()> {
+        writeln!(self.writer, "% {}", element.name.replace('\n', " "))
+    }
+
+    fn set_color(&mut self, paint: Paint, color: Rgba) -> io::Result<()> {
+        self.pen.set_color(paint, color);
+        Ok(())
+    }
+
+    fn set_line(&mut self, width: f64, dashes: &[f64]) -> io::Result<()> {
+        self.pen.set_line(width, dashes);
+        Ok(())
+    }
+
+    fn draw_path(&mut self, shape: &Path, fill: bool, stroke: bool) -> io::Result<()> {
+        let mut path = "newpath\n".to_owned();
+        match shape {
+            Path::Polygon(points) => {
+                path += &format!("{} moveto\n", self.point(points[0]));
+                for &corner in &points[1..] {
+                    path += &format!("{} lineto\n", self.point(corner));
+                }
+                path += "closepath\n";
+            }
+            Path::Ellipse { center, radii } => {
+                path += &self.curves(&ellipse(*center, *radii));
+                path += "closepath\n";
+            }
+            Path::Bezier(points) => path += &self.curves(points),
+        }
+        // PostScript has no transparency, so only fully transparent colors are left out
+        let fill = fill && self.pen.fill.a > 0;
+        let stroke = stroke && self.pen.stroke.a > 0;
+        let mut drawn = String::new();
+        if fill {
+            drawn += &path;
+            drawn += &color(self.pen.fill);
+            drawn += if stroke {
+                "gsave fill grestore\n"
+            } else {
+                "fill\n"
+            };
+        }
+        if stroke {
+            if !fill {
+                drawn += &path;
+            }
+            let dashes = self.pen.dashes.iter().map(|&length| number(length));
+            drawn += &format!(
+                "{}{} setlinewidth\n[{}] 0 setdash\nstroke\n",
+                color(self.pen.stroke),
+                number(self.pen.width),
+                dashes.collect::<Vec<_>>().join(" ")
+            );
+        }
+        write!(self.writer, "{}", drawn)
+    }
+
+    fn draw_text(&mut self, text: &Text, angle: f64) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{}/{} {} latin1font",
+            color(text.color),
+            standard_font(&text.font),
+            number(text.size)
+        )?;
+        if angle == 0.0 {
+            writeln!(
+                self.writer,
+                "{} moveto ({}) center",
+                self.point(text.position),
+                escape(&text.text)
+            )
+        } else {
+            writeln!(
+                self.writer,
+                "gsave {} translate {} rotate 0 0 moveto ({}) center grestore",
+                self.point(text.position),
+                number(angle),
+                escape(&text.text)
+            )
+        }
+    }
+
+    fn end_page(&mut self, _page: &Page) -> io::Result<()> {
+        writeln!(self.writer, "grestore\nshowpage")
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.writer, "%%Trailer\n%%EOF")
+    }
+}
+
+/// Writes `graph`, which must be laid out, as PostScript or EPS.
+pub fn write(graph: &Graph, writer: &mut dyn Write, format: PsFormat) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let layout = Layout::new(
+        graph,
+        &drawing,
+        DEFAULT_MARGIN,
+        format == PsFormat::PostScript,
+    );
+    let mut backend = PostScript {
+        writer,
+        format,
+        pen: Pen::default(),
+        bounds: String::new(),
+        height: 0.0,
+    };
+    render(
+        &drawing,
+        &layout,
+        graph.id.as_deref().unwrap_or(""),
+        &mut backend,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout};
+
+    fn postscript(source: &str, format: PsFormat) -> String {
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output, format).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn line<'a>(output: &'a str, prefix: &str) -> &'a str {
+        output
+            .lines()
+            .find(|line| line.starts_with(prefix))
+            .unwrap()
+    }
+
+    #[test]
+    fn ps() {
+        let eps = postscript("digraph G { a [label=\"(a)\"] a -> b }", PsFormat::Eps);
+        assert!(eps.starts_with("%!PS-Adobe-3.0 EPSF-3.0\n"));
+        assert_eq!(line(&eps, "%%Pages:"), "%%Pages: 1");
+        assert_eq!(line(&eps, "%%BoundingBox:"), "%%BoundingBox: 36 36 98 152");
+        assert!(eps.contains("/Times-Roman 14 latin1font\n"));
+        assert!(eps.contains("(\\(a\\)) center\n"));
+
+        // a chain too long for a page, and scaled to fit the size across
+        let chain = (0..12).map(|i| format!("n{}", i)).collect::<Vec<_>>();
+        let source = format!(
+            "digraph {{ page=\"3,3\" margin=\"0.5,0.5\" size=\"0.5,100\" pagedir=TL {} }}",
+            chain.join(" -> ")
+        );
+        let ps = postscript(&source, PsFormat::PostScript);
+        assert_eq!(line(&ps, "%%BoundingBox:"), "%%BoundingBox: 0 0 216 216");
+        let pages = line(&ps, "%%Pages: ")["%%Pages: ".len()..].parse::<usize>();
+        let pages = pages.unwrap();
+        assert!(pages > 1);
+        assert_eq!(ps.matches("showpage").count(), pages);
+        // from the top, so each page shows the part of the drawing 144 points below the last
+        let heights = ps
+            .lines()
+            .filter(|line| line.ends_with("(n0) center"))
+            .map(|line| line.split(' ').nth(1).unwrap().parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(heights.len(), pages);
+        assert_eq!(heights[0] - heights[1], -144.0);
+
+        let turned = postscript("digraph { rotate=90 a }", PsFormat::Eps);
+        assert!(turned.contains("90 rotate 0 0 moveto (a) center"));
+    }
+}
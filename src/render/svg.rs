@@ -0,0 +1,275 @@
+//! SVG images of laid out graphs, structured like Graphviz's: a group per node and edge with its
+//! class, and its name as its `<title>`. The shapes of elements with a `URL`, `tooltip` or
+//! `target` are wrapped in a link to the URL, with the tooltip as its `xlink:title`. Images are a
+//! single page, scaled and turned as [`backend`](super::backend) describes.
+
+use std::io::{self, Write};
+
+use crate::{color::Rgba, export::graphml::escape, font::is_rtl, model::Graph};
+
+use super::{
+    backend::{render, Document, Layout, Page, Paint, Path, Pen, RenderBackend},
+    draw, number, Drawing, Element, ElementKind, Text,
+};
+
+/// Writes `graph`, which must be laid out, as an SVG image.
+pub fn write(graph: &Graph, writer: &mut dyn Write) -> io::Result<()> {
+    let drawing = draw(graph).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let layout = Layout::new(graph, &drawing, 0.0, false);
+    write_drawing(&drawing, &layout, graph.id.as_deref().unwrap_or(""), writer)
+}
+
+fn hex(color: Rgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+struct Svg<'a> {
+    writer: &'a mut dyn Write,
+    pen: Pen,
+    /// The number of nodes and edges so far.
+    counts: (usize, usize),
+    /// Whether the current element is in a group, and in a link.
+    open: (bool, bool),
+}
+
+impl Svg<'_> {
+    /// The `fill` and `stroke` attributes of a path.
+    fn paint(&self, fill: bool, stroke: bool) -> String {
+        let mut paint = String::new();
+        if fill {
+            let fill = self.pen.fill;
+            paint += &format!(" fill=\"{}\"", hex(fill));
+            if fill.a < 255 {
+                paint += &format!(" fill-opacity=\"{}\"", number(f64::from(fill.a) / 255.0));
+            }
+        } else {
+            paint += " fill=\"none\"";
+        }
+        if stroke {
+            let color = self.pen.stroke;
+            paint += &format!(" stroke=\"{}\"", hex(color));
+            if color.a < 255 {
+                let opacity = number(f64::from(color.a) / 255.0);
+                paint += &format!(" stroke-opacity=\"{}\"", opacity);
+            }
+            if self.pen.width != 1.0 {
+                paint += &format!(" stroke-width=\"{}\"", number(self.pen.width));
+            }
+            if !self.pen.dashes.is_empty() {
+                let dashes = self.pen.dashes.iter().map(|&length| number(length));
+                let dashes = dashes.collect::<Vec<_>>().join(",");
+                paint += &format!(" stroke-dasharray=\"{}\"", dashes);
+            }
+        } else {
+            paint += " stroke=\"none\"";
+        }
+        paint
+    }
+}
+
+impl RenderBackend for Svg<'_> {
+    fn begin(&mut self, document: &Document) -> io::Result<()> {
+        let (width, height) = (number(document.size.0), number(document.size.1));
+        writeln!(
+            self.writer,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+        )?;
+        writeln!(
+            self.writer,
+            "<svg width=\"{0}pt\" height=\"{1}pt\" viewBox=\"0 0 {0} {1}\" \
+             xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">",
+            width, height
+        )?;
+        writeln!(self.writer, "<g id=\"graph0\" class=\"graph\">")?;
+        writeln!(self.writer, "<title>{}</title>", escape(document.name))
+    }
+
+    fn begin_page(&mut self, _page: &Page) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_element(&mut self, element: &Element) -> io::Result<()> {
+        let group = match element.kind {
+            ElementKind::Graph => None,
+            ElementKind::Node => {
+                self.counts.0 += 1;
+                Some(format!("node{}\" class=\"node", self.counts.0))
+            }
+            ElementKind::Edge => {
+                self.counts.1 += 1;
+                Some(format!("edge{}\" class=\"edge", self.counts.1))
+            }
+        };
+        if let Some(group) = &group {
+            writeln!(self.writer, "<g id=\"{}\">", group)?;
+            writeln!(self.writer, "<title>{}</title>", escape(&element.name))?;
+        }
+        let link = [
+            ("xlink:href", &element.url),
+            ("xlink:title", &element.tooltip),
+            ("target", &element.target),
+        ]
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = value.as_ref()?;
+            Some(format!(" {}=\"{}\"", name, escape(value)))
+        })
+        .collect::<String>();
+        if !link.is_empty() {
+            writeln!(self.writer, "<a{}>", link)?;
+        }
+        self.open = (group.is_some(), !link.is_empty());
+        Ok(())
+    }
+
+    fn set_color(&mut self, paint: Paint, color: Rgba) -> io::Result<()> {
+        self.pen.set_color(paint, color);
+        Ok(())
+    }
+
+    fn set_line(&mut self, width: f64, dashes: &[f64]) -> io::Result<()> {
+        self.pen.set_line(width, dashes);
+        Ok(())
+    }
+
+    fn draw_path(&mut self, path: &Path, fill: bool, stroke: bool) -> io::Result<()> {
+        let point = |&(x, y): &(f64, f64)| format!("{},{}", number(x), number(y));
+        let paint = self.paint(fill, stroke);
+        match path {
+            Path::Ellipse { center, radii } => writeln!(
+                self.writer,
+                "<ellipse{} cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"/>",
+                paint,
+                number(center.0),
+                number(center.1),
+                number(radii.0),
+                number(radii.1)
+            ),
+            Path::Polygon(points) => writeln!(
+                self.writer,
+                "<polygon{} points=\"{}\"/>",
+                paint,
+                points.iter().map(point).collect::<Vec<_>>().join(" ")
+            ),
+            Path::Bezier(points) => {
+                let curves = points[1..]
+                    .chunks_exact(3)
+                    .map(|curve| curve.iter().map(point).collect::<Vec<_>>().join(" "));
+                writeln!(
+                    self.writer,
+                    "<path{} d=\"M{} C{}\"/>",
+                    paint,
+                    point(&points[0]),
+                    curves.collect::<Vec<_>>().join(" ")
+                )
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &Text, angle: f64) -> io::Result<()> {
+        let mut color = format!(" fill=\"{}\"", hex(text.color));
+        if text.color.a < 255 {
+            let opacity = number(f64::from(text.color.a) / 255.0);
+            color += &format!(" fill-opacity=\"{}\"", opacity);
+        }
+        // the weight and slant of fonts named as in fontconfig patterns, `Times:bold`
+        let mut font = text.font.split(':');
+        let family = font.next().unwrap_or_default();
+        for style in font {
+            match style {
+                "bold" => color += " font-weight=\"bold\"",
+                "italic" => color += " font-style=\"italic\"",
+                _ => {}
+            }
+        }
+        let (x, y) = (number(text.position.0), number(text.position.1));
+        // viewers order the characters of text themselves, from the direction it starts in
+        if is_rtl(&text.text) {
+            color += " direction=\"rtl\"";
+        }
+        if angle != 0.0 {
+            // clockwise, as the y axis points down
+            color += &format!(" transform=\"rotate({} {} {})\"", number(-angle), x, y);
+        }
+        writeln!(
+            self.writer,
+            "<text text-anchor=\"middle\" x=\"{}\" y=\"{}\" font-family=\"{}\" \
+             font-size=\"{}\"{}>{}</text>",
+            x,
+            y,
+            escape(family),
+            number(text.size),
+            color,
+            escape(&text.text)
+        )
+    }
+
+    fn end_element(&mut self, _element: &Element) -> io::Result<()> {
+        if self.open.1 {
+            writeln!(self.writer, "</a>")?;
+        }
+        if self.open.0 {
+            writeln!(self.writer, "</g>")?;
+        }
+        Ok(())
+    }
+
+    fn end_page(&mut self, _page: &Page) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.writer, "</g>\n</svg>")
+    }
+}
+
+/// Writes `drawing`, the drawing of the graph named `name`, as an SVG image laid out as
+/// `layout`.
+pub fn write_drawing(
+    drawing: &Drawing,
+    layout: &Layout,
+    name: &str,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let mut backend = Svg {
+        writer,
+        pen: Pen::default(),
+        counts: (0, 0),
+        open: (false, false),
+    };
+    render(drawing, layout, name, &mut backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir::parse_graph, layout, xml};
+
+    #[test]
+    fn svg() {
+        let source = r#"digraph G { label="a & b" a [tooltip="<\N>" style=dashed] a -> b }"#;
+        let mut graph = Graph::from_ir(&parse_graph(source).unwrap());
+        layout::layout(&mut graph, Some("dot")).unwrap();
+        let mut output = vec![];
+        super::write(&graph, &mut output).unwrap();
+        let svg = xml::parse(&String::from_utf8(output).unwrap()).unwrap();
+        assert_eq!(svg.local_name(), "svg");
+        let root = svg.children_named("g").next().unwrap();
+        assert_eq!(root.children_named("title").next().unwrap().text(), "G");
+        assert_eq!(root.children_named("text").next().unwrap().text(), "a & b");
+        let groups = root.children_named("g").collect::<Vec<_>>();
+        let classes = groups
+            .iter()
+            .map(|group| group.attribute("class").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(classes, ["edge", "node", "node"]);
+        let title = |group: &xml::Element| group.children_named("title").next().unwrap().text();
+        assert_eq!(title(groups[0]), "a -> b");
+        assert_eq!(title(groups[1]), "a");
+        let link = groups[1].children_named("a").next().unwrap();
+        assert_eq!(link.attribute("title"), Some("<a>"));
+        let ellipse = link.children_named("ellipse").next().unwrap();
+        assert_eq!(ellipse.attribute("stroke-dasharray"), Some("5,2"));
+        assert_eq!(ellipse.attribute("fill"), Some("none"));
+    }
+}
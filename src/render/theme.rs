@@ -0,0 +1,189 @@
+//! Themes: default colors, fonts and pen widths for graphs, nodes and edges, applied to a graph
+//! before it's laid out and drawn without editing its source.
+//!
+//! A theme is written as a DOT graph of attribute statements only, such as
+//!
+//! ```dot
+//! graph { bgcolor=black fontcolor=white node [color=white fontcolor=white] edge [color=gray] }
+//! ```
+//!
+//! and [`Theme::apply`] gives each attribute to the graph, node or edge unless it already has
+//! one of the same name, set in the graph's source or by its `node` and `edge` defaults. The
+//! built-in themes are in [`THEMES`]: `dark`, `light`, `high-contrast`, and `colorblind`, in
+//! the palette of Okabe and Ito, which stays distinct with the common kinds of color blindness.
+
+use std::{fs, io, path::Path, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    attribute::Attribute,
+    ir::{parse_graph, AttributeKind, Statement},
+    model::{set_attribute, Graph},
+    validate::parse_error,
+};
+
+/// The built-in themes, by name.
+pub const THEMES: &[(&str, &str)] = &[
+    (
+        "dark",
+        r##"graph {
+            bgcolor="#1e1e1e" fontcolor="#d4d4d4"
+            node [color="#d4d4d4" fontcolor="#d4d4d4" fillcolor="#3c3c3c"]
+            edge [color="#a0a0a0" fontcolor="#d4d4d4"]
+        }"##,
+    ),
+    (
+        "light",
+        r##"graph {
+            bgcolor=white fontcolor="#202020" fontname=Helvetica
+            node [color="#404040" fontcolor="#202020" fillcolor="#f0f0f0" fontname=Helvetica]
+            edge [color="#606060" fontcolor="#202020" fontname=Helvetica]
+        }"##,
+    ),
+    (
+        "high-contrast",
+        r##"graph {
+            bgcolor=black fontcolor=white fontname="Helvetica:bold"
+            node [color=white fontcolor=white fillcolor=black penwidth=2 fontname="Helvetica:bold"]
+            edge [color=yellow fontcolor=yellow penwidth=2 fontname="Helvetica:bold"]
+        }"##,
+    ),
+    (
+        "colorblind",
+        r##"graph {
+            bgcolor=white fontcolor=black
+            node [color="#0072b2" fontcolor=black fillcolor="#56b4e9"]
+            edge [color="#d55e00" fontcolor="#d55e00"]
+        }"##,
+    ),
+];
+
+#[derive(Debug, Error)]
+pub enum ThemeError {
+    #[error("no theme `{0}`, and no such file: {1}")]
+    NotFound(String, io::Error),
+    #[error("invalid theme: {0}")]
+    Invalid(String),
+}
+
+/// Attributes to give graphs, nodes and edges which don't have them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Theme {
+    pub graph: Vec<Attribute>,
+    pub node: Vec<Attribute>,
+    pub edge: Vec<Attribute>,
+}
+
+impl FromStr for Theme {
+    type Err = ThemeError;
+
+    /// Reads a theme from the attribute statements of a DOT graph.
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let graph = parse_graph(source).map_err(|e| ThemeError::Invalid(parse_error(e).message))?;
+        let mut theme = Theme::default();
+        for statement in graph.statements {
+            match statement {
+                Statement::Attribute(statement) => {
+                    let target = match statement.kind {
+                        AttributeKind::Graph => &mut theme.graph,
+                        AttributeKind::Node => &mut theme.node,
+                        AttributeKind::Edge => &mut theme.edge,
+                    };
+                    for attribute in statement.attributes {
+                        set_attribute(target, attribute);
+                    }
+                }
+                Statement::Definition(statement) => {
+                    let attribute = Attribute::from_pair(&statement.lhs, &statement.rhs)
+                        .map_err(|e| ThemeError::Invalid(format!("`{}`: {}", statement.lhs, e)))?;
+                    set_attribute(&mut theme.graph, attribute);
+                }
+                Statement::Node(_) | Statement::Edge(_) => {
+                    return Err(ThemeError::Invalid(
+                        "themes have attribute statements only".to_owned(),
+                    ))
+                }
+            }
+        }
+        Ok(theme)
+    }
+}
+
+impl Theme {
+    /// The built-in theme `name`.
+    pub fn named(name: &str) -> Option<Theme> {
+        let (_, source) = THEMES.iter().find(|(theme, _)| *theme == name)?;
+        Some(source.parse().expect("built-in themes are valid"))
+    }
+
+    /// The built-in theme `name`, or else the theme in the file at that path.
+    pub fn load(name: &str) -> Result<Theme, ThemeError> {
+        if let Some(theme) = Theme::named(name) {
+            return Ok(theme);
+        }
+        let source = fs::read_to_string(Path::new(name))
+            .map_err(|e| ThemeError::NotFound(name.to_owned(), e))?;
+        source.parse()
+    }
+
+    /// Gives `graph`, its nodes and its edges the attributes of the theme they don't have.
+    pub fn apply(&self, graph: &mut Graph) {
+        fn fill(attributes: &mut Vec<Attribute>, theme: &[Attribute]) {
+            for attribute in theme {
+                if !attributes.iter().any(|own| own.name() == attribute.name()) {
+                    attributes.push(attribute.clone());
+                }
+            }
+        }
+        fill(&mut graph.attributes, &self.graph);
+        for node in 0..graph.nodes().len() {
+            fill(&mut graph.node_mut(node).attributes, &self.node);
+        }
+        for edge in 0..graph.edges().len() {
+            fill(&mut graph.edge_mut(edge).attributes, &self.edge);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgba, ir::parse_graph, render};
+
+    #[test]
+    fn themes() {
+        for (name, _) in THEMES {
+            assert!(Theme::named(name).is_some(), "{}", name);
+        }
+        assert!(matches!(
+            Theme::load("no/such/theme.dot"),
+            Err(ThemeError::NotFound(..))
+        ));
+        assert!(matches!(
+            "graph { a -> b }".parse::<Theme>(),
+            Err(ThemeError::Invalid(_))
+        ));
+
+        let mut graph = Graph::from_ir(
+            &parse_graph("digraph { node [color=red] a [fontcolor=blue] a -> b }").unwrap(),
+        );
+        let theme = "graph { bgcolor=black node [color=white fontcolor=white] edge [penwidth=2] }"
+            .parse::<Theme>()
+            .unwrap();
+        theme.apply(&mut graph);
+        let has = |attributes: &[Attribute], name, value| {
+            attributes.contains(&Attribute::from_pair(name, value).unwrap())
+        };
+        let (a, b) = (&graph.nodes()[0], &graph.nodes()[1]);
+        // the graph's own attributes and defaults come first
+        assert!(has(&a.attributes, "color", "red"));
+        assert!(has(&a.attributes, "fontcolor", "blue"));
+        assert!(has(&b.attributes, "fontcolor", "white"));
+        assert!(has(&graph.edges()[0].attributes, "penwidth", "2"));
+
+        crate::layout::layout(&mut graph, Some("dot")).unwrap();
+        let drawing = render::draw(&graph).unwrap();
+        assert_eq!(drawing.background, Some(Rgba::BLACK));
+    }
+}
@@ -0,0 +1,176 @@
+//! Byte-level scanning for the hot loops of DOT parsing: whitespace and comments, identifier
+//! runs, quoted strings and HTML strings.
+//!
+//! These run over bytes rather than decoding characters, with `memchr` finding the quotes,
+//! backslashes and angle brackets that end runs of string contents; the nom combinators above
+//! them are left for the grammar.
+
+use alloc::{borrow::Cow, string::String};
+
+use memchr::{memchr, memchr2, memmem};
+
+/// Whether each byte can be part of an unquoted identifier: an ASCII letter, digit or `_`. The
+/// characters U+0080 to U+00FF are too, which are the two-byte sequences led by 0xC2 and 0xC3.
+static IDENT: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut byte = 0;
+    while byte < 128 {
+        let c = byte as u8;
+        table[byte] = c.is_ascii_alphanumeric() || c == b'_';
+        byte += 1;
+    }
+    table
+};
+
+/// The length of the whitespace (` `, `\t`, `\r` and `\n`) at the start of `input`.
+pub(crate) fn whitespace(input: &str) -> usize {
+    input
+        .bytes()
+        .position(|byte| !matches!(byte, b' ' | b'\t' | b'\r' | b'\n'))
+        .unwrap_or(input.len())
+}
+
+/// The length of the whitespace and DOT [`comment`]s at the start of `input`, which starts a line
+/// if `line_start`.
+pub(crate) fn separator(input: &str, line_start: bool) -> usize {
+    let bytes = input.as_bytes();
+    let mut index = 0;
+    loop {
+        index += whitespace(&input[index..]);
+        let line_start = match index {
+            0 => line_start,
+            _ => bytes[index - 1] == b'\n',
+        };
+        match comment(&bytes[index..], line_start) {
+            Some(length) => index += length,
+            None => return index,
+        }
+    }
+}
+
+/// The length of the comment at the start of `bytes`, if any: a `//` comment up to the end of its
+/// line, a `/* */` comment, running to the end of `bytes` if it isn't closed, or, if `line_start`,
+/// a line of C preprocessor output starting with `#`, which Graphviz skips.
+pub(crate) fn comment(bytes: &[u8], line_start: bool) -> Option<usize> {
+    match bytes {
+        [b'/', b'/', ..] => Some(memchr(b'\n', bytes).unwrap_or(bytes.len())),
+        [b'#', ..] if line_start => Some(memchr(b'\n', bytes).unwrap_or(bytes.len())),
+        [b'/', b'*', rest @ ..] => {
+            Some(memmem::find(rest, b"*/").map_or(bytes.len(), |end| end + 4))
+        }
+        _ => None,
+    }
+}
+
+/// The length of the run of identifier characters at the start of `input`; see [`IDENT`].
+pub(crate) fn ident_run(input: &str) -> usize {
+    let bytes = input.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            byte if IDENT[byte as usize] => index += 1,
+            0xC2 | 0xC3 => index += 2,
+            _ => break,
+        }
+    }
+    index
+}
+
+/// The contents of the double-quoted string at the start of `input`, with escaped quotes and
+/// line continuations unescaped, borrowed if there are none, and the length of the string with
+/// its quotes; `None` if `input` doesn't start with a quote, or with the length of `input` if the
+/// string isn't closed.
+pub(crate) fn quoted(input: &str) -> Option<Result<(Cow<'_, str>, usize), usize>> {
+    let bytes = input.as_bytes();
+    if bytes.first() != Some(&b'"') {
+        return None;
+    }
+    let mut contents = String::new();
+    let mut index = 1;
+    loop {
+        let offset = match memchr2(b'"', b'\\', &bytes[index..]) {
+            Some(offset) => offset,
+            None => return Some(Err(input.len())),
+        };
+        if bytes[index + offset] == b'"' && contents.is_empty() {
+            return Some(Ok((
+                Cow::Borrowed(&input[index..index + offset]),
+                index + offset + 1,
+            )));
+        }
+        contents.push_str(&input[index..index + offset]);
+        index += offset;
+        if bytes[index] == b'"' {
+            return Some(Ok((Cow::Owned(contents), index + 1)));
+        }
+        // only escaped quotes and line continuations are unescaped; other backslashes are kept
+        match &bytes[index + 1..] {
+            [b'"', ..] => {
+                contents.push('"');
+                index += 2;
+            }
+            [b'\r', b'\n', ..] => index += 3,
+            [b'\n', ..] => index += 2,
+            _ => {
+                contents.push('\\');
+                index += 1;
+            }
+        }
+    }
+}
+
+/// The length of the HTML string with balanced angle brackets at the start of `input`, if any.
+pub(crate) fn html(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    if bytes.first() != Some(&b'<') {
+        return None;
+    }
+    let mut depth = 1;
+    let mut index = 1;
+    while depth > 0 {
+        index += memchr2(b'<', b'>', &bytes[index..])?;
+        if bytes[index] == b'<' {
+            depth += 1;
+        } else {
+            depth -= 1;
+        }
+        index += 1;
+    }
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan() {
+        assert_eq!(whitespace(" \t\r\n a "), 5);
+        assert_eq!(whitespace("  "), 2);
+        assert_eq!(whitespace(" // a"), 1);
+        assert_eq!(separator(" // a\n# b\n /* c\n */ d", false), 20);
+        assert_eq!(separator("/* a", false), 4);
+        assert_eq!(separator("/ a", false), 0);
+        // `#` only starts a comment at the start of a line
+        assert_eq!(separator("# a\n b", true), 5);
+        assert_eq!(separator("# a\n b", false), 0);
+        assert_eq!(separator(" # a", true), 1);
+        assert_eq!(comment(b"// a", false), Some(4));
+        assert_eq!(ident_run("a_1é-b"), 5);
+        assert_eq!(ident_run("ab中"), 2);
+        assert_eq!(
+            quoted(r#""a\"b\\c\N" d"#),
+            Some(Ok((r#"a"b\\c\N"#.into(), 11)))
+        );
+        assert_eq!(quoted("\"a\\\r\nb\\\nc\""), Some(Ok(("abc".into(), 10))));
+        assert_eq!(quoted(r#""a\""#), Some(Err(4)));
+        assert!(matches!(
+            quoted(r#""a b" c"#),
+            Some(Ok((Cow::Borrowed("a b"), 5)))
+        ));
+        assert_eq!(quoted("a"), None);
+        assert_eq!(html("<a<b>c</b>> d"), Some(11));
+        assert_eq!(html("<a<b>"), None);
+        assert_eq!(html("a"), None);
+    }
+}
@@ -0,0 +1,735 @@
+//! A small gvpr-style language for batch edits of node and edge attributes.
+//!
+//! A script is a sequence of clauses, each running its body for every node (`N`) or edge (`E`)
+//! for which its optional predicate holds:
+//!
+//! ```text
+//! N [degree == 0] { delete }
+//! N [shape == "box" && label ~= "db"] { shape = "cylinder"; color = "blue" }
+//! E { if weight > 2 { penwidth = weight } else { unset penwidth } }
+//! ```
+//!
+//! Statements assign attributes (`name = expr`), remove them (`unset name`), delete the current
+//! element (`delete`), or branch (`if expr { ... } else { ... }`); they may end with `;`.
+//! Expressions combine attribute names, which evaluate to the attribute's value or the empty
+//! string if it isn't set, numbers and double-quoted strings with `||`, `&&`, `!`, the
+//! comparisons `==`, `!=`, `<`, `<=`, `>`, `>=` and `~=` (contains), and `+`, `-`, `*`, `/` and
+//! `%`. Values compare and add as numbers where both sides are numeric, and as strings
+//! otherwise. Nodes also have `name`, `degree`, `indegree` and `outdegree`, and edges `tail` and
+//! `head`, the names of their ends. `#` and `//` start comments.
+//!
+//! Deleting a node deletes its edges. Deletions take effect at the end of each clause, so degrees
+//! within a clause are those at its start.
+
+use std::{cmp::Ordering, str::FromStr};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while},
+    character::complete::{char, multispace1, not_line_ending, satisfy},
+    combinator::{cut, eof, map, not, opt, peek, recognize, value, verify},
+    error::context,
+    multi::many0,
+    number::complete::double,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    Parser,
+};
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError},
+    ir::{ident_parser, GraphParseError, ParseResult},
+    model::{set_attribute, Graph},
+};
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("{expected} at line {line}, column {column}")]
+    Syntax {
+        expected: &'static str,
+        line: usize,
+        column: usize,
+    },
+    #[error("unknown name `{0}`")]
+    UnknownName(String),
+    #[error("`{0}` can't be assigned")]
+    ReadOnly(String),
+    #[error("invalid value `{value}` for attribute `{name}`")]
+    InvalidValue { name: String, value: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Contains,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Name(String),
+    Not(Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Set(String, Expr),
+    Unset(String),
+    Delete,
+    If(Expr, Vec<Statement>, Vec<Statement>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Node,
+    Edge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Clause {
+    kind: Kind,
+    predicate: Option<Expr>,
+    body: Vec<Statement>,
+}
+
+/// A parsed script; see the [module documentation](self) for the language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    clauses: Vec<Clause>,
+}
+
+const KEYWORDS: &[&str] = &["N", "E", "if", "else", "delete", "unset", "true", "false"];
+
+const NODE_NAMES: &[&str] = &["name", "degree", "indegree", "outdegree"];
+const EDGE_NAMES: &[&str] = &["tail", "head"];
+
+impl Value {
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Num(n) => Some(*n),
+            Value::Str(s) => s.trim().parse().ok(),
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        match self {
+            Value::Bool(b) => *b,
+            Value::Num(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            Value::Str(s) => s,
+            Value::Num(n) if n.fract() == 0.0 && n.abs() < 1e15 => (n as i64).to_string(),
+            Value::Num(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self.as_number(), other.as_number()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => Some(self.clone().into_string().cmp(&other.clone().into_string())),
+        }
+    }
+}
+
+/// Whitespace and comments.
+fn space(input: &str) -> ParseResult<&str, ()> {
+    value(
+        (),
+        many0(alt((
+            multispace1,
+            preceded(char('#'), not_line_ending),
+            preceded(tag("//"), not_line_ending),
+        ))),
+    )(input)
+}
+
+fn token<'a, O>(
+    parser: impl Parser<&'a str, O, nom::error::VerboseError<&'a str>>,
+) -> impl FnMut(&'a str) -> ParseResult<&'a str, O> {
+    preceded(space, parser)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn keyword<'a>(keyword: &'static str) -> impl FnMut(&'a str) -> ParseResult<&'a str, &'a str> {
+    token(terminated(tag(keyword), not(satisfy(is_name_char))))
+}
+
+fn name_parser(input: &str) -> ParseResult<&str, String> {
+    map(
+        token(verify(
+            recognize(pair(
+                satisfy(|c| c.is_alphabetic() || c == '_'),
+                take_while(is_name_char),
+            )),
+            |name: &str| !KEYWORDS.contains(&name),
+        )),
+        str::to_owned,
+    )(input)
+}
+
+fn primary_parser(input: &str) -> ParseResult<&str, Expr> {
+    context(
+        "expected expression",
+        alt((
+            map(
+                token(preceded(
+                    peek(satisfy(|c| c.is_ascii_digit() || c == '.')),
+                    double,
+                )),
+                |n| Expr::Literal(Value::Num(n)),
+            ),
+            map(token(preceded(peek(char('"')), ident_parser)), |s| {
+                Expr::Literal(Value::Str(s))
+            }),
+            value(Expr::Literal(Value::Bool(true)), keyword("true")),
+            value(Expr::Literal(Value::Bool(false)), keyword("false")),
+            map(name_parser, Expr::Name),
+            delimited(
+                token(char('(')),
+                expr_parser,
+                cut(context("expected `)`", token(char(')')))),
+            ),
+        )),
+    )(input)
+}
+
+fn unary_parser(input: &str) -> ParseResult<&str, Expr> {
+    alt((
+        map(preceded(token(char('-')), unary_parser), |e| {
+            Expr::Negate(Box::new(e))
+        }),
+        map(
+            preceded(token(terminated(char('!'), not(char('=')))), unary_parser),
+            |e| Expr::Not(Box::new(e)),
+        ),
+        primary_parser,
+    ))(input)
+}
+
+/// A left-associative chain of `operand`s joined by `op`s.
+fn binary<'a>(
+    input: &'a str,
+    operand: fn(&'a str) -> ParseResult<&'a str, Expr>,
+    op: impl Parser<&'a str, BinaryOp, nom::error::VerboseError<&'a str>>,
+) -> ParseResult<&'a str, Expr> {
+    let (input, first) = operand(input)?;
+    let (input, rest) = many0(pair(token(op), cut(operand)))(input)?;
+    let expr = rest.into_iter().fold(first, |lhs, (op, rhs)| {
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    });
+    Ok((input, expr))
+}
+
+fn product_parser(input: &str) -> ParseResult<&str, Expr> {
+    binary(
+        input,
+        unary_parser,
+        alt((
+            value(BinaryOp::Multiply, char('*')),
+            value(BinaryOp::Divide, char('/')),
+            value(BinaryOp::Remainder, char('%')),
+        )),
+    )
+}
+
+fn sum_parser(input: &str) -> ParseResult<&str, Expr> {
+    binary(
+        input,
+        product_parser,
+        alt((
+            value(BinaryOp::Add, char('+')),
+            value(BinaryOp::Subtract, char('-')),
+        )),
+    )
+}
+
+fn comparison_parser(input: &str) -> ParseResult<&str, Expr> {
+    binary(
+        input,
+        sum_parser,
+        alt((
+            value(BinaryOp::Equal, tag("==")),
+            value(BinaryOp::NotEqual, tag("!=")),
+            value(BinaryOp::LessEqual, tag("<=")),
+            value(BinaryOp::GreaterEqual, tag(">=")),
+            value(BinaryOp::Less, char('<')),
+            value(BinaryOp::Greater, char('>')),
+            value(BinaryOp::Contains, tag("~=")),
+        )),
+    )
+}
+
+fn and_parser(input: &str) -> ParseResult<&str, Expr> {
+    binary(input, comparison_parser, value(BinaryOp::And, tag("&&")))
+}
+
+fn expr_parser(input: &str) -> ParseResult<&str, Expr> {
+    binary(input, and_parser, value(BinaryOp::Or, tag("||")))
+}
+
+fn block_parser(input: &str) -> ParseResult<&str, Vec<Statement>> {
+    preceded(
+        context("expected `{`", token(char('{'))),
+        cut(terminated(
+            many0(statement_parser),
+            context("expected statement or `}`", token(char('}'))),
+        )),
+    )(input)
+}
+
+fn if_parser(input: &str) -> ParseResult<&str, Statement> {
+    let (input, (condition, then)) = preceded(
+        keyword("if"),
+        cut(pair(
+            context("expected condition", expr_parser),
+            block_parser,
+        )),
+    )(input)?;
+    let (input, otherwise) = opt(preceded(
+        keyword("else"),
+        cut(alt((map(if_parser, |s| vec![s]), block_parser))),
+    ))(input)?;
+    Ok((
+        input,
+        Statement::If(condition, then, otherwise.unwrap_or_default()),
+    ))
+}
+
+fn statement_parser(input: &str) -> ParseResult<&str, Statement> {
+    terminated(
+        alt((
+            if_parser,
+            value(Statement::Delete, keyword("delete")),
+            map(
+                preceded(keyword("unset"), cut(name_parser)),
+                Statement::Unset,
+            ),
+            map(
+                pair(
+                    name_parser,
+                    preceded(
+                        token(terminated(char('='), not(char('=')))),
+                        cut(expr_parser),
+                    ),
+                ),
+                |(name, expr)| Statement::Set(name, expr),
+            ),
+        )),
+        opt(token(char(';'))),
+    )(input)
+}
+
+fn clause_parser(input: &str) -> ParseResult<&str, Clause> {
+    map(
+        tuple((
+            alt((
+                value(Kind::Node, keyword("N")),
+                value(Kind::Edge, keyword("E")),
+            )),
+            cut(opt(delimited(
+                token(char('[')),
+                expr_parser,
+                context("expected `]`", token(char(']'))),
+            ))),
+            cut(block_parser),
+        )),
+        |(kind, predicate, body)| Clause {
+            kind,
+            predicate,
+            body,
+        },
+    )(input)
+}
+
+fn script_parser(input: &str) -> ParseResult<&str, Vec<Clause>> {
+    terminated(
+        many0(clause_parser),
+        preceded(space, context("expected `N` or `E`", eof)),
+    )(input)
+}
+
+/// Checks that the names in `expr` are attributes or names for elements of `kind`.
+fn check_expr(expr: &Expr, kind: Kind) -> Result<(), ScriptError> {
+    match expr {
+        Expr::Literal(_) => Ok(()),
+        Expr::Name(name) => check_name(name, kind, false),
+        Expr::Not(e) | Expr::Negate(e) => check_expr(e, kind),
+        Expr::Binary(_, lhs, rhs) => {
+            check_expr(lhs, kind)?;
+            check_expr(rhs, kind)
+        }
+    }
+}
+
+fn check_name(name: &str, kind: Kind, assigned: bool) -> Result<(), ScriptError> {
+    let element_names = match kind {
+        Kind::Node => NODE_NAMES,
+        Kind::Edge => EDGE_NAMES,
+    };
+    if element_names.contains(&name) {
+        return match assigned {
+            true => Err(ScriptError::ReadOnly(name.to_owned())),
+            false => Ok(()),
+        };
+    }
+    match Attribute::from_pair(name, "") {
+        Err(AttributeParseError::AttributeNameNotFound) => {
+            Err(ScriptError::UnknownName(name.to_owned()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_statements(statements: &[Statement], kind: Kind) -> Result<(), ScriptError> {
+    for statement in statements {
+        match statement {
+            Statement::Set(name, expr) => {
+                check_name(name, kind, true)?;
+                check_expr(expr, kind)?;
+            }
+            Statement::Unset(name) => check_name(name, kind, true)?,
+            Statement::Delete => {}
+            Statement::If(condition, then, otherwise) => {
+                check_expr(condition, kind)?;
+                check_statements(then, kind)?;
+                check_statements(otherwise, kind)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let clauses = match script_parser(input) {
+            Ok((_, clauses)) => clauses,
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                let (expected, line, column) = match GraphParseError::from_verbose(input, e) {
+                    GraphParseError::Syntax {
+                        expected,
+                        line,
+                        column,
+                        ..
+                    } => (expected, line, column),
+                    _ => (
+                        "unexpected end of script",
+                        input.lines().count().max(1),
+                        input.lines().last().unwrap_or_default().chars().count() + 1,
+                    ),
+                };
+                return Err(ScriptError::Syntax {
+                    expected,
+                    line,
+                    column,
+                });
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers don't need more input"),
+        };
+        for clause in &clauses {
+            if let Some(predicate) = &clause.predicate {
+                check_expr(predicate, clause.kind)?;
+            }
+            check_statements(&clause.body, clause.kind)?;
+        }
+        Ok(Script { clauses })
+    }
+}
+
+/// The element a clause body runs for.
+struct Element<'a> {
+    names: Vec<(&'static str, Value)>,
+    attributes: &'a mut Vec<Attribute>,
+    deleted: bool,
+}
+
+impl Element<'_> {
+    fn eval(&self, expr: &Expr) -> Value {
+        match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Name(name) => match self.names.iter().find(|(n, _)| n == name) {
+                Some((_, value)) => value.clone(),
+                None => Value::Str(
+                    self.attributes
+                        .iter()
+                        .find(|attribute| attribute.name() == name)
+                        .map(Attribute::value_string)
+                        .unwrap_or_default(),
+                ),
+            },
+            Expr::Not(e) => Value::Bool(!self.eval(e).is_true()),
+            Expr::Negate(e) => Value::Num(-self.eval(e).as_number().unwrap_or(f64::NAN)),
+            Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+                Value::Bool(self.eval(lhs).is_true() || self.eval(rhs).is_true())
+            }
+            Expr::Binary(BinaryOp::And, lhs, rhs) => {
+                Value::Bool(self.eval(lhs).is_true() && self.eval(rhs).is_true())
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.eval(lhs), self.eval(rhs));
+                let ordering = || lhs.compare(&rhs);
+                let numbers = || (lhs.as_number(), rhs.as_number());
+                match op {
+                    BinaryOp::Equal => Value::Bool(ordering() == Some(Ordering::Equal)),
+                    BinaryOp::NotEqual => Value::Bool(ordering() != Some(Ordering::Equal)),
+                    BinaryOp::Less => Value::Bool(ordering() == Some(Ordering::Less)),
+                    BinaryOp::LessEqual => {
+                        Value::Bool(matches!(ordering(), Some(Ordering::Less | Ordering::Equal)))
+                    }
+                    BinaryOp::Greater => Value::Bool(ordering() == Some(Ordering::Greater)),
+                    BinaryOp::GreaterEqual => Value::Bool(matches!(
+                        ordering(),
+                        Some(Ordering::Greater | Ordering::Equal)
+                    )),
+                    BinaryOp::Contains => Value::Bool(
+                        lhs.clone()
+                            .into_string()
+                            .contains(&rhs.clone().into_string()),
+                    ),
+                    BinaryOp::Add => match numbers() {
+                        (Some(a), Some(b)) => Value::Num(a + b),
+                        _ => Value::Str(lhs.into_string() + &rhs.into_string()),
+                    },
+                    _ => {
+                        let (a, b) = match numbers() {
+                            (Some(a), Some(b)) => (a, b),
+                            _ => return Value::Num(f64::NAN),
+                        };
+                        Value::Num(match op {
+                            BinaryOp::Subtract => a - b,
+                            BinaryOp::Multiply => a * b,
+                            BinaryOp::Divide => a / b,
+                            _ => a % b,
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    fn exec(&mut self, statements: &[Statement]) -> Result<(), ScriptError> {
+        for statement in statements {
+            match statement {
+                Statement::Set(name, expr) => {
+                    let value = self.eval(expr).into_string();
+                    let attribute = Attribute::from_pair(name, &value).map_err(|_| {
+                        ScriptError::InvalidValue {
+                            name: name.clone(),
+                            value,
+                        }
+                    })?;
+                    set_attribute(self.attributes, attribute);
+                }
+                Statement::Unset(name) => {
+                    self.attributes.retain(|attribute| attribute.name() != name)
+                }
+                Statement::Delete => self.deleted = true,
+                Statement::If(condition, then, otherwise) => {
+                    if self.eval(condition).is_true() {
+                        self.exec(then)?
+                    } else {
+                        self.exec(otherwise)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a clause for this element, returning whether it deleted the element.
+    fn run(mut self, clause: &Clause) -> Result<bool, ScriptError> {
+        if let Some(predicate) = &clause.predicate {
+            if !self.eval(predicate).is_true() {
+                return Ok(false);
+            }
+        }
+        self.exec(&clause.body)?;
+        Ok(self.deleted)
+    }
+}
+
+impl Script {
+    /// Runs the script over `graph`, stopping at the first attribute assigned an invalid value.
+    pub fn run(&self, graph: &mut Graph) -> Result<(), ScriptError> {
+        for clause in &self.clauses {
+            let nodes = graph.nodes().len();
+            let edges = graph.edges().len();
+            let mut deleted_nodes = vec![false; nodes];
+            let mut deleted_edges = vec![false; edges];
+            match clause.kind {
+                Kind::Node => {
+                    let mut indegree = vec![0usize; nodes];
+                    let mut outdegree = vec![0usize; nodes];
+                    for edge in graph.edges() {
+                        outdegree[edge.tail] += 1;
+                        indegree[edge.head] += 1;
+                    }
+                    for index in 0..nodes {
+                        let node = graph.node_mut(index);
+                        let names = vec![
+                            ("name", Value::Str(node.id.clone())),
+                            (
+                                "degree",
+                                Value::Num((indegree[index] + outdegree[index]) as f64),
+                            ),
+                            ("indegree", Value::Num(indegree[index] as f64)),
+                            ("outdegree", Value::Num(outdegree[index] as f64)),
+                        ];
+                        deleted_nodes[index] = Element {
+                            names,
+                            attributes: &mut node.attributes,
+                            deleted: false,
+                        }
+                        .run(clause)?;
+                    }
+                }
+                Kind::Edge => {
+                    for (index, deleted) in deleted_edges.iter_mut().enumerate() {
+                        let edge = &graph.edges()[index];
+                        let names = vec![
+                            ("tail", Value::Str(graph.nodes()[edge.tail].id.clone())),
+                            ("head", Value::Str(graph.nodes()[edge.head].id.clone())),
+                        ];
+                        *deleted = Element {
+                            names,
+                            attributes: &mut graph.edge_mut(index).attributes,
+                            deleted: false,
+                        }
+                        .run(clause)?;
+                    }
+                }
+            }
+            if deleted_nodes.contains(&true) || deleted_edges.contains(&true) {
+                let kept_nodes = (0..nodes).filter(|&node| !deleted_nodes[node]);
+                let kept_edges = (0..edges).filter(|&index| {
+                    let edge = &graph.edges()[index];
+                    !deleted_edges[index] && !deleted_nodes[edge.tail] && !deleted_nodes[edge.head]
+                });
+                *graph = graph.subgraph(kept_nodes, kept_edges.collect::<Vec<_>>());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attribute::Shape, ir::parse_graph};
+
+    fn run(script: &str, input: &str) -> Graph {
+        let mut graph = Graph::from_ir(&parse_graph(input).unwrap());
+        script.parse::<Script>().unwrap().run(&mut graph).unwrap();
+        graph
+    }
+
+    #[test]
+    fn scripts() {
+        let graph = run(
+            r#"
+            # drop isolated nodes
+            N [degree == 0] { delete }
+            N [label ~= "db"] { shape = "cylinder"; }
+            N [outdegree > 1 && !(name == "x")] { color = "red" } // hubs
+            E {
+                if weight >= 2 { penwidth = weight * 1.5 } else { unset weight }
+            }
+            "#,
+            r#"digraph { a [label="my db"] b c lonely a -> b [weight=2] a -> c [weight=1] }"#,
+        );
+        let ids = graph
+            .nodes()
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(
+            graph.node("a").unwrap().attributes,
+            vec![
+                Attribute::Label("my db".to_owned()),
+                Attribute::Shape(Shape::Cylinder),
+                Attribute::from_pair("color", "red").unwrap(),
+            ]
+        );
+        assert_eq!(
+            graph.edges()[0].attributes,
+            vec![Attribute::Weight(2.0), Attribute::PenWidth(3.0)]
+        );
+        assert!(graph.edges()[1].attributes.is_empty());
+
+        let graph = run(r#"E [tail == "a"] { delete }"#, "graph { a -- b b -- c }");
+        assert_eq!(graph.edges().len(), 1);
+        assert_eq!(graph.nodes().len(), 3);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            "N { colour = red }".parse::<Script>(),
+            Err(ScriptError::UnknownName(name)) if name == "colour"
+        ));
+        assert!(matches!(
+            "N { label = tail }".parse::<Script>(),
+            Err(ScriptError::UnknownName(name)) if name == "tail"
+        ));
+        assert!(matches!(
+            "E { tail = \"a\" }".parse::<Script>(),
+            Err(ScriptError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            "N [degree ==] { delete }".parse::<Script>(),
+            Err(ScriptError::Syntax {
+                expected: "expected expression",
+                line: 1,
+                column: 13
+            })
+        ));
+        assert!(matches!(
+            "N { delete }\nX { }".parse::<Script>(),
+            Err(ScriptError::Syntax {
+                expected: "expected `N` or `E`",
+                line: 2,
+                column: 1
+            })
+        ));
+        let mut graph = Graph::from_ir(&parse_graph("graph { a }").unwrap());
+        assert!(matches!(
+            "N { width = \"wide\" }"
+                .parse::<Script>()
+                .unwrap()
+                .run(&mut graph),
+            Err(ScriptError::InvalidValue { .. })
+        ));
+    }
+}
@@ -0,0 +1,391 @@
+//! Selectors picking out nodes and edges by their attributes, like CSS selectors for graphs.
+//!
+//! A selector names the kind of element, `node`, `edge` or `*` for either, followed by any number
+//! of conditions on the element's attributes, all of which must hold:
+//!
+//! ```text
+//! node[shape=box][label~="db"], edge[color=red]
+//! ```
+//!
+//! * `[name]` holds if the attribute is set;
+//! * `[name=value]` and `[name!=value]` compare values, after normalizing them as when parsing
+//!   DOT, so `[width=1.50]` matches `width=1.5`;
+//! * `[name~=value]`, `[name^=value]` and `[name$=value]` hold if the value contains, starts or
+//!   ends with the given text.
+//!
+//! Values are DOT IDs, quoted if need be. Besides DOT attributes, `id` names the ID of a node,
+//! and `tail` and `head` the IDs of the nodes at the ends of an edge. Comma-separated selectors
+//! match elements matching any of them. Conditions test the effective attributes, with defaults
+//! applied.
+
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, multispace0},
+    combinator::{cut, eof, opt},
+    error::context,
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, terminated},
+    Parser,
+};
+use thiserror::Error;
+
+use crate::{
+    attribute::{Attribute, AttributeParseError},
+    ir::{ident_parser, GraphParseError, ParseResult},
+    model::{Graph, NodeIndex},
+    ws::ws,
+};
+
+#[derive(Debug, Error)]
+pub enum SelectorError {
+    #[error("{expected} at column {column} of selector")]
+    Syntax {
+        expected: &'static str,
+        column: usize,
+    },
+    #[error("unknown attribute `{0}` in selector")]
+    UnknownAttribute(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Node,
+    Edge,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    NotEqual,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    name: String,
+    test: Option<(Op, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Simple {
+    kind: Kind,
+    conditions: Vec<Condition>,
+}
+
+/// A parsed selector; see the [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Simple>,
+}
+
+/// Names which aren't attributes but select on element IDs.
+const ID_NAMES: &[&str] = &["id", "tail", "head"];
+
+fn condition_parser(input: &str) -> ParseResult<&str, Condition> {
+    let name = take_while1(|c: char| c.is_alphanumeric() || c == '_');
+    let op = alt((
+        tag("!=").map(|_| Op::NotEqual),
+        tag("~=").map(|_| Op::Contains),
+        tag("^=").map(|_| Op::StartsWith),
+        tag("$=").map(|_| Op::EndsWith),
+        tag("=").map(|_| Op::Equal),
+    ));
+    delimited(
+        char('['),
+        cut(pair(
+            context("expected attribute name", ws(name)),
+            opt(pair(ws(op), cut(ws(ident_parser)))),
+        )),
+        cut(context("expected `]`", char(']'))),
+    )
+    .map(|(name, test): (&str, _)| Condition {
+        name: name.to_owned(),
+        test,
+    })
+    .parse(input)
+}
+
+fn simple_parser(input: &str) -> ParseResult<&str, Simple> {
+    let kind = context(
+        "expected `node`, `edge` or `*`",
+        alt((
+            tag("node").map(|_| Kind::Node),
+            tag("edge").map(|_| Kind::Edge),
+            tag("*").map(|_| Kind::Any),
+        )),
+    );
+    pair(kind, many0(preceded(multispace0, condition_parser)))
+        .map(|(kind, conditions)| Simple { kind, conditions })
+        .parse(input)
+}
+
+fn selector_parser(input: &str) -> ParseResult<&str, Vec<Simple>> {
+    terminated(
+        separated_list1(ws(char(',')), ws(simple_parser)),
+        context("expected `,` or end of selector", eof),
+    )(input)
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let alternatives = match selector_parser(input) {
+            Ok((_, alternatives)) => alternatives,
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                return Err(match GraphParseError::from_verbose(input, e) {
+                    GraphParseError::Syntax {
+                        expected, column, ..
+                    } => SelectorError::Syntax { expected, column },
+                    _ => SelectorError::Syntax {
+                        expected: "unexpected end of selector",
+                        column: input.chars().count() + 1,
+                    },
+                })
+            }
+            Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers don't need more input"),
+        };
+        for condition in alternatives.iter().flat_map(|simple| &simple.conditions) {
+            let value = condition.test.as_ref().map_or("", |(_, value)| value);
+            if !ID_NAMES.contains(&condition.name.as_str())
+                && matches!(
+                    Attribute::from_pair(&condition.name, value),
+                    Err(AttributeParseError::AttributeNameNotFound)
+                )
+            {
+                return Err(SelectorError::UnknownAttribute(condition.name.clone()));
+            }
+        }
+        Ok(Selector { alternatives })
+    }
+}
+
+impl Condition {
+    /// Whether the condition holds for an element with the given IDs and attributes.
+    fn matches(&self, ids: &[(&str, &str)], attributes: &[Attribute]) -> bool {
+        let (actual, attribute) = match ids.iter().find(|(name, _)| *name == self.name) {
+            Some((_, id)) => (Some(id.to_string()), None),
+            None if ID_NAMES.contains(&self.name.as_str()) => return false,
+            None => {
+                let attribute = attributes
+                    .iter()
+                    .find(|attribute| attribute.name() == self.name);
+                (attribute.map(Attribute::value_string), attribute)
+            }
+        };
+        let (op, expected) = match &self.test {
+            None => return actual.is_some(),
+            Some(test) => test,
+        };
+        let equal = |actual: &str| match attribute {
+            // compare attributes in their normalized form where the value parses
+            Some(attribute) => match Attribute::from_pair(&self.name, expected) {
+                Ok(expected) => *attribute == expected,
+                Err(_) => actual == expected,
+            },
+            None => actual == expected,
+        };
+        match (op, actual) {
+            (Op::NotEqual, None) => true,
+            (_, None) => false,
+            (Op::Equal, Some(actual)) => equal(&actual),
+            (Op::NotEqual, Some(actual)) => !equal(&actual),
+            (Op::Contains, Some(actual)) => actual.contains(expected.as_str()),
+            (Op::StartsWith, Some(actual)) => actual.starts_with(expected.as_str()),
+            (Op::EndsWith, Some(actual)) => actual.ends_with(expected.as_str()),
+        }
+    }
+}
+
+impl Selector {
+    fn matches(&self, kind: Kind, ids: &[(&str, &str)], attributes: &[Attribute]) -> bool {
+        self.alternatives.iter().any(|simple| {
+            (simple.kind == kind || simple.kind == Kind::Any)
+                && simple
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.matches(ids, attributes))
+        })
+    }
+
+    /// Indices of the nodes matching the selector.
+    pub fn nodes(&self, graph: &Graph) -> Vec<NodeIndex> {
+        (0..graph.nodes().len())
+            .filter(|&index| {
+                let node = &graph.nodes()[index];
+                self.matches(Kind::Node, &[("id", &node.id)], &node.attributes)
+            })
+            .collect()
+    }
+
+    /// Indices of the edges matching the selector.
+    pub fn edges(&self, graph: &Graph) -> Vec<usize> {
+        (0..graph.edges().len())
+            .filter(|&index| {
+                let edge = &graph.edges()[index];
+                let ids = [
+                    ("tail", graph.nodes()[edge.tail].id.as_str()),
+                    ("head", graph.nodes()[edge.head].id.as_str()),
+                ];
+                self.matches(Kind::Edge, &ids, &edge.attributes)
+            })
+            .collect()
+    }
+
+    /// The subgraph of the matching elements: the matching nodes with all edges between them,
+    /// along with the matching edges and their ends.
+    pub fn select(&self, graph: &Graph) -> Graph {
+        let nodes = self.nodes(graph);
+        let mut selected = vec![false; graph.nodes().len()];
+        for &node in &nodes {
+            selected[node] = true;
+        }
+        let mut edges = self.edges(graph);
+        edges.extend(
+            graph
+                .edges()
+                .iter()
+                .enumerate()
+                .filter(|(_, edge)| selected[edge.tail] && selected[edge.head])
+                .map(|(index, _)| index),
+        );
+        graph.subgraph(nodes, edges)
+    }
+
+    /// The graph without the matching elements: the nodes which don't match, with the edges
+    /// between them which don't match either.
+    pub fn remove(&self, graph: &Graph) -> Graph {
+        let mut removed = vec![false; graph.nodes().len()];
+        for node in self.nodes(graph) {
+            removed[node] = true;
+        }
+        let mut removed_edges = vec![false; graph.edges().len()];
+        for edge in self.edges(graph) {
+            removed_edges[edge] = true;
+        }
+        let edges = graph
+            .edges()
+            .iter()
+            .enumerate()
+            .filter(|(index, edge)| {
+                !removed_edges[*index] && !removed[edge.tail] && !removed[edge.head]
+            })
+            .map(|(index, _)| index);
+        graph.subgraph(
+            (0..graph.nodes().len()).filter(|&node| !removed[node]),
+            edges,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    const GRAPH: &str = r#"digraph {
+        node [shape=box]
+        web [label="web frontend"]
+        db [label="main db", shape=cylinder, width=1.50]
+        cache
+        web -> db [color=red]
+        web -> cache
+        cache -> db
+    }"#;
+
+    fn ids(graph: &Graph, nodes: Vec<NodeIndex>) -> Vec<&str> {
+        nodes
+            .into_iter()
+            .map(|node| graph.nodes()[node].id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn matching() {
+        let graph = Graph::from_ir(&parse_graph(GRAPH).unwrap());
+        let nodes =
+            |selector: &str| ids(&graph, selector.parse::<Selector>().unwrap().nodes(&graph));
+        assert_eq!(nodes("node[shape=box]"), vec!["web", "cache"]);
+        assert_eq!(nodes(r#"node[label~="db"]"#), vec!["db"]);
+        assert_eq!(nodes("node[width=1.5][label^=main]"), vec!["db"]);
+        assert_eq!(nodes("node[label]"), vec!["web", "db"]);
+        assert_eq!(nodes("node[label!=\"main db\"]"), vec!["web", "cache"]);
+        assert_eq!(nodes("*[id$=e], node [ id = db ]"), vec!["db", "cache"]);
+        assert!(nodes("edge").is_empty());
+
+        let selector = "edge[color=red], edge[tail=cache]"
+            .parse::<Selector>()
+            .unwrap();
+        assert_eq!(selector.edges(&graph), vec![0, 2]);
+        let selected = selector.select(&graph);
+        assert_eq!(
+            selected
+                .nodes()
+                .iter()
+                .map(|node| node.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["web", "db", "cache"]
+        );
+        assert_eq!(selected.edges().len(), 2);
+
+        let selected = "node[shape=box]"
+            .parse::<Selector>()
+            .unwrap()
+            .select(&graph);
+        assert_eq!(selected.nodes().len(), 2);
+        assert_eq!(selected.edges().len(), 1);
+
+        let remaining = "node[id=cache], edge[color=red]"
+            .parse::<Selector>()
+            .unwrap()
+            .remove(&graph);
+        assert_eq!(
+            ids(&remaining, (0..remaining.nodes().len()).collect()),
+            vec!["web", "db"]
+        );
+        assert!(remaining.edges().is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            "node[color=red}".parse::<Selector>(),
+            Err(SelectorError::Syntax {
+                expected: "expected `]`",
+                column: 15
+            })
+        ));
+        assert!(matches!(
+            "node[colour=red]".parse::<Selector>(),
+            Err(SelectorError::UnknownAttribute(name)) if name == "colour"
+        ));
+        assert!(matches!(
+            "node[color=red".parse::<Selector>(),
+            Err(SelectorError::Syntax {
+                expected: "unexpected end of selector",
+                column: 15
+            })
+        ));
+        assert!(matches!(
+            "nodes".parse::<Selector>(),
+            Err(SelectorError::Syntax {
+                expected: "expected `,` or end of selector",
+                column: 5
+            })
+        ));
+        // `#` isn't a comment in selectors
+        assert!(matches!(
+            "node#e".parse::<Selector>(),
+            Err(SelectorError::Syntax {
+                expected: "expected `,` or end of selector",
+                column: 5
+            })
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+//! Summary statistics, for auditing large generated graphs.
+//!
+//! Degrees count edge ends, so a loop adds two to the degree of its node. Components are weakly
+//! connected: edge direction is ignored; digraphs also count their strongly connected components.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{
+    algorithm::{components::component_labels, scc::strong_component_labels},
+    json::JsonValue,
+    model::Graph,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub nodes: usize,
+    pub edges: usize,
+    /// Number of nodes by degree.
+    pub degrees: BTreeMap<usize, usize>,
+    pub components: usize,
+    /// Number of strongly connected components, in digraphs.
+    pub strong_components: Option<usize>,
+    /// Number of graph, node and edge attributes set, by attribute name.
+    pub attributes: BTreeMap<&'static str, usize>,
+}
+
+impl Stats {
+    pub fn new(graph: &Graph) -> Self {
+        let count = |labels: Vec<usize>| labels.into_iter().max().map_or(0, |label| label + 1);
+        let nodes = graph.nodes().len();
+        let mut degree = vec![0; nodes];
+        for edge in graph.edges() {
+            degree[edge.tail] += 1;
+            degree[edge.head] += 1;
+        }
+        let mut degrees = BTreeMap::new();
+        for degree in degree {
+            *degrees.entry(degree).or_default() += 1;
+        }
+
+        let mut attributes = BTreeMap::new();
+        let all = graph
+            .attributes
+            .iter()
+            .chain(graph.nodes().iter().flat_map(|node| &node.attributes))
+            .chain(graph.edges().iter().flat_map(|edge| &edge.attributes));
+        for attribute in all {
+            *attributes.entry(attribute.name()).or_default() += 1;
+        }
+
+        Stats {
+            nodes,
+            edges: graph.edges().len(),
+            degrees,
+            components: count(component_labels(graph)),
+            strong_components: graph
+                .is_directed()
+                .then(|| count(strong_component_labels(graph))),
+            attributes,
+        }
+    }
+}
+
+/// Writes statistics, each along with the file it concerns, as a JSON array of objects with a
+/// `file` member and the members of [`Stats`], where `degrees` and `attributes` map degrees and
+/// attribute names to their counts.
+pub fn write_json<'a, W: Write>(
+    mut writer: W,
+    stats: impl IntoIterator<Item = (&'a str, &'a Stats)>,
+) -> io::Result<()> {
+    let counts = |counts: Vec<(String, usize)>| {
+        JsonValue::Object(
+            counts
+                .into_iter()
+                .map(|(key, count)| (key, count.into()))
+                .collect(),
+        )
+    };
+    JsonValue::Array(
+        stats
+            .into_iter()
+            .map(|(file, stats)| {
+                JsonValue::Object(vec![
+                    ("file".to_owned(), file.into()),
+                    ("nodes".to_owned(), stats.nodes.into()),
+                    ("edges".to_owned(), stats.edges.into()),
+                    (
+                        "degrees".to_owned(),
+                        counts(
+                            stats
+                                .degrees
+                                .iter()
+                                .map(|(degree, count)| (degree.to_string(), *count))
+                                .collect(),
+                        ),
+                    ),
+                    ("components".to_owned(), stats.components.into()),
+                    (
+                        "strong_components".to_owned(),
+                        stats
+                            .strong_components
+                            .map_or(JsonValue::Null, JsonValue::from),
+                    ),
+                    (
+                        "attributes".to_owned(),
+                        counts(
+                            stats
+                                .attributes
+                                .iter()
+                                .map(|(name, count)| (name.to_string(), *count))
+                                .collect(),
+                        ),
+                    ),
+                ])
+            })
+            .collect(),
+    )
+    .write_pretty(&mut writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::parse_graph;
+
+    #[test]
+    fn stats() {
+        let graph = Graph::from_ir(
+            &parse_graph(
+                "digraph { label=G a [shape=box] a -> b -> c [color=red] c -> c d e -> d }",
+            )
+            .unwrap(),
+        );
+        let stats = Stats::new(&graph);
+        assert_eq!((stats.nodes, stats.edges, stats.components), (5, 4, 2));
+        assert_eq!(stats.strong_components, Some(5));
+        assert_eq!(
+            stats.degrees,
+            vec![(1, 3), (2, 1), (3, 1)].into_iter().collect()
+        );
+        assert_eq!(
+            stats.attributes,
+            vec![("color", 2), ("label", 1), ("shape", 1)]
+                .into_iter()
+                .collect()
+        );
+
+        let mut output = vec![];
+        let stats = Stats::new(&Graph::from_ir(&parse_graph("graph { a }").unwrap()));
+        write_json(&mut output, vec![("a.dot", &stats)]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"[
+  {
+    "file": "a.dot",
+    "nodes": 1,
+    "edges": 0,
+    "degrees": {
+      "0": 1
+    },
+    "components": 1,
+    "strong_components": null,
+    "attributes": {}
+  }
+]
+"#
+        );
+    }
+}
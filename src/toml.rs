@@ -0,0 +1,430 @@
+//! Minimal TOML reader producing the JSON value model.
+//!
+//! Supports tables, arrays of tables, dotted keys, inline tables and arrays, all string forms,
+//! integers (including hexadecimal, octal and binary), floats and booleans. Dates and times are
+//! read as strings.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while, take_while1, take_while_m_n},
+    character::complete::{char, line_ending, multispace1, not_line_ending, space0},
+    combinator::{cut, eof, map, map_opt, not, opt, value},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+};
+
+use crate::json::JsonValue;
+
+type TomlResult<'a, O> = nom::IResult<&'a str, O, nom::error::Error<&'a str>>;
+
+type Key = Vec<String>;
+
+#[derive(Debug, Clone)]
+enum Expression {
+    Empty,
+    Table(Key),
+    ArrayTable(Key),
+    KeyValue(Key, JsonValue),
+}
+
+fn comment_parser(input: &str) -> TomlResult<'_, &str> {
+    preceded(char('#'), not_line_ending)(input)
+}
+
+/// Whitespace, newlines and comments, as allowed inside arrays.
+fn multiline_space_parser(input: &str) -> TomlResult<'_, ()> {
+    value((), many0(alt((multispace1, comment_parser))))(input)
+}
+
+fn escape_parser(input: &str) -> TomlResult<'_, String> {
+    preceded(
+        char('\\'),
+        alt((
+            map_opt(
+                alt((
+                    preceded(
+                        char('u'),
+                        take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+                    ),
+                    preceded(
+                        char('U'),
+                        take_while_m_n(8, 8, |c: char| c.is_ascii_hexdigit()),
+                    ),
+                )),
+                |hex| {
+                    u32::from_str_radix(hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .map(String::from)
+                },
+            ),
+            value("\"".to_owned(), char('"')),
+            value("\\".to_owned(), char('\\')),
+            value("\u{8}".to_owned(), char('b')),
+            value("\u{c}".to_owned(), char('f')),
+            value("\n".to_owned(), char('n')),
+            value("\r".to_owned(), char('r')),
+            value("\t".to_owned(), char('t')),
+        )),
+    )(input)
+}
+
+fn basic_string_parser(input: &str) -> TomlResult<'_, String> {
+    delimited(
+        char('"'),
+        map(
+            many0(alt((escape_parser, map(is_not("\"\\\n"), str::to_owned)))),
+            |fragments| fragments.concat(),
+        ),
+        char('"'),
+    )(input)
+}
+
+fn multiline_basic_string_parser(input: &str) -> TomlResult<'_, String> {
+    // a backslash at the end of a line trims the newline and any whitespace that follows
+    let line_continuation = value(
+        String::new(),
+        pair(
+            char('\\'),
+            pair(space0, pair(line_ending, multiline_space_parser)),
+        ),
+    );
+    delimited(
+        pair(tag("\"\"\""), opt(line_ending)),
+        map(
+            many0(alt((
+                line_continuation,
+                escape_parser,
+                map(is_not("\"\\"), str::to_owned),
+                map(terminated(tag("\""), not(tag("\"\""))), str::to_owned),
+            ))),
+            |fragments| fragments.concat(),
+        ),
+        tag("\"\"\""),
+    )(input)
+}
+
+fn literal_string_parser(input: &str) -> TomlResult<'_, String> {
+    map(
+        delimited(
+            char('\''),
+            take_while(|c| c != '\'' && c != '\n'),
+            char('\''),
+        ),
+        str::to_owned,
+    )(input)
+}
+
+fn multiline_literal_string_parser(input: &str) -> TomlResult<'_, String> {
+    let (input, _) = pair(tag("'''"), opt(line_ending))(input)?;
+    let end = input.find("'''").ok_or_else(|| {
+        nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Tag))
+    })?;
+    Ok((&input[end + 3..], input[..end].to_owned()))
+}
+
+fn string_parser(input: &str) -> TomlResult<'_, String> {
+    alt((
+        multiline_basic_string_parser,
+        basic_string_parser,
+        multiline_literal_string_parser,
+        literal_string_parser,
+    ))(input)
+}
+
+fn key_parser(input: &str) -> TomlResult<'_, Key> {
+    separated_list1(
+        delimited(space0, char('.'), space0),
+        alt((
+            basic_string_parser,
+            literal_string_parser,
+            map(
+                take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+                str::to_owned,
+            ),
+        )),
+    )(input)
+}
+
+/// Classifies a bare value: a boolean, a number, or a date or time, which is kept as a string.
+fn bare_value(text: &str) -> Option<JsonValue> {
+    let bytes = text.as_bytes();
+    let is_date = bytes.len() >= 5 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-';
+    let is_time = bytes.len() >= 3 && bytes[..2].iter().all(u8::is_ascii_digit) && bytes[2] == b':';
+    if is_date || is_time {
+        return Some(JsonValue::String(text.to_owned()));
+    }
+    let number = text.replace('_', "");
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, number.strip_prefix('+').unwrap_or(&number)),
+    };
+    let radix = match digits.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => None,
+    };
+    Some(match (text, radix) {
+        ("true", _) => JsonValue::Bool(true),
+        ("false", _) => JsonValue::Bool(false),
+        (_, Some(radix)) => {
+            JsonValue::Number(sign * i64::from_str_radix(&digits[2..], radix).ok()? as f64)
+        }
+        _ if digits == "inf" => JsonValue::Number(sign * f64::INFINITY),
+        _ if digits == "nan" => JsonValue::Number(f64::NAN),
+        _ if digits.starts_with(|c: char| c.is_ascii_digit()) => {
+            JsonValue::Number(number.parse().ok()?)
+        }
+        _ => return None,
+    })
+}
+
+fn value_parser(input: &str) -> TomlResult<'_, JsonValue> {
+    alt((
+        map(string_parser, JsonValue::String),
+        map(
+            delimited(
+                char('['),
+                terminated(
+                    separated_list0(
+                        char(','),
+                        delimited(multiline_space_parser, value_parser, multiline_space_parser),
+                    ),
+                    pair(opt(char(',')), multiline_space_parser),
+                ),
+                char(']'),
+            ),
+            JsonValue::Array,
+        ),
+        map_opt(
+            delimited(
+                pair(char('{'), space0),
+                separated_list0(
+                    delimited(space0, char(','), space0),
+                    separated_pair(
+                        key_parser,
+                        delimited(space0, char('='), space0),
+                        value_parser,
+                    ),
+                ),
+                pair(space0, char('}')),
+            ),
+            |pairs| {
+                let mut table = vec![];
+                for (key, value) in pairs {
+                    insert(&mut table, &key, value)?;
+                }
+                Some(JsonValue::Object(table))
+            },
+        ),
+        map_opt(
+            take_while1(|c: char| c.is_ascii_alphanumeric() || "+-_.:".contains(c)),
+            bare_value,
+        ),
+    ))(input)
+}
+
+fn expression_parser(input: &str) -> TomlResult<'_, Expression> {
+    terminated(
+        preceded(
+            space0,
+            alt((
+                map(
+                    delimited(
+                        tag("[["),
+                        delimited(space0, key_parser, space0),
+                        cut(tag("]]")),
+                    ),
+                    Expression::ArrayTable,
+                ),
+                map(
+                    delimited(
+                        char('['),
+                        delimited(space0, key_parser, space0),
+                        cut(char(']')),
+                    ),
+                    Expression::Table,
+                ),
+                map(
+                    separated_pair(
+                        key_parser,
+                        delimited(space0, char('='), space0),
+                        cut(value_parser),
+                    ),
+                    |(key, value)| Expression::KeyValue(key, value),
+                ),
+                value(Expression::Empty, space0),
+            )),
+        ),
+        cut(preceded(
+            pair(space0, opt(comment_parser)),
+            alt((line_ending, eof)),
+        )),
+    )(input)
+}
+
+/// The table at `path`, creating missing tables. Arrays of tables resolve to their last table.
+fn table_mut<'a>(
+    table: &'a mut Vec<(String, JsonValue)>,
+    path: &[String],
+) -> Option<&'a mut Vec<(String, JsonValue)>> {
+    let (key, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(table),
+    };
+    let index = match table.iter().position(|(k, _)| k == key) {
+        Some(index) => index,
+        None => {
+            table.push((key.clone(), JsonValue::Object(vec![])));
+            table.len() - 1
+        }
+    };
+    match &mut table[index].1 {
+        JsonValue::Object(members) => table_mut(members, rest),
+        JsonValue::Array(tables) => match tables.last_mut() {
+            Some(JsonValue::Object(members)) => table_mut(members, rest),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Inserts `value` at the dotted `key`, failing if the key is already defined.
+fn insert(table: &mut Vec<(String, JsonValue)>, key: &[String], value: JsonValue) -> Option<()> {
+    let (last, path) = key.split_last()?;
+    let table = table_mut(table, path)?;
+    if table.iter().any(|(k, _)| k == last) {
+        return None;
+    }
+    table.push((last.clone(), value));
+    Some(())
+}
+
+/// Parses a TOML document, returning the line of the first error on failure.
+pub(crate) fn parse(input: &str) -> Result<JsonValue, usize> {
+    let line = |rest: &str| input[..input.len() - rest.len()].matches('\n').count() + 1;
+    let mut root = vec![];
+    let mut current = vec![];
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (next, expression) = match expression_parser(rest) {
+            Ok(result) => result,
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => return Err(line(e.input)),
+            Err(nom::Err::Incomplete(_)) => return Err(line("")),
+        };
+        let applied = match expression {
+            Expression::Empty => Some(()),
+            Expression::Table(key) => table_mut(&mut root, &key).map(|_| current = key),
+            Expression::ArrayTable(key) => key.split_last().and_then(|(last, path)| {
+                let parent = table_mut(&mut root, path)?;
+                match parent.iter_mut().find(|(k, _)| k == last) {
+                    Some((_, JsonValue::Array(tables))) => tables.push(JsonValue::Object(vec![])),
+                    Some(_) => return None,
+                    None => parent.push((
+                        last.clone(),
+                        JsonValue::Array(vec![JsonValue::Object(vec![])]),
+                    )),
+                }
+                current = key.clone();
+                Some(())
+            }),
+            Expression::KeyValue(key, value) => {
+                table_mut(&mut root, &current).and_then(|table| insert(table, &key, value))
+            }
+        };
+        if applied.is_none() {
+            return Err(line(rest));
+        }
+        rest = next;
+    }
+    Ok(JsonValue::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let input = r#"# comment
+title = "TOML \"example\"" # comment
+literal = 'C:\path'
+multi = """
+first \
+  second"""
+raw = '''
+line'''
+numbers = [1_000, 0x1f, -2.5e3, inf, true, 1979-05-27T07:32:00Z]
+nested = [ [1, 2], # comment
+  ["a"], ]
+point = { x = 1, y.z = 2 }
+
+[table.sub]
+"quoted key" = 1
+dotted.key = 2
+
+[[items]]
+name = "a"
+[[items]]
+name = "b"
+[items.detail]
+size = 3
+"#;
+        let value = parse(input).unwrap();
+        assert_eq!(
+            value.get("title").and_then(JsonValue::as_str),
+            Some("TOML \"example\"")
+        );
+        assert_eq!(
+            value.get("literal").and_then(JsonValue::as_str),
+            Some("C:\\path")
+        );
+        assert_eq!(
+            value.get("multi").and_then(JsonValue::as_str),
+            Some("first second")
+        );
+        assert_eq!(value.get("raw").and_then(JsonValue::as_str), Some("line"));
+        assert_eq!(
+            value.get("numbers"),
+            Some(&JsonValue::Array(vec![
+                1000.0.into(),
+                31.0.into(),
+                (-2500.0).into(),
+                f64::INFINITY.into(),
+                true.into(),
+                "1979-05-27T07:32:00Z".into(),
+            ]))
+        );
+        assert_eq!(
+            value.get("nested"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Array(vec![1.0.into(), 2.0.into()]),
+                JsonValue::Array(vec!["a".into()]),
+            ]))
+        );
+        let point = value.get("point").unwrap();
+        assert_eq!(point.get("y").and_then(|y| y.get("z")), Some(&2.0.into()));
+        let sub = value.get("table").and_then(|t| t.get("sub")).unwrap();
+        assert_eq!(sub.get("quoted key"), Some(&1.0.into()));
+        assert_eq!(
+            sub.get("dotted").and_then(|d| d.get("key")),
+            Some(&2.0.into())
+        );
+        let items = value.get("items").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].get("name"), Some(&"b".into()));
+        assert_eq!(
+            items[1].get("detail").and_then(|d| d.get("size")),
+            Some(&3.0.into())
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("a = 1\na = 2\n"), Err(2));
+        assert_eq!(parse("a = 1\nb = \n"), Err(2));
+        assert_eq!(parse("[table\n"), Err(1));
+        assert_eq!(parse("a = 1 b = 2\n"), Err(1));
+    }
+}
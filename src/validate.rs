@@ -0,0 +1,734 @@
+//! Structural checks over the intermediate representation.
+//!
+//! [`validate`] and [`check`] report problems Graphviz itself would trip over; [`lint`] adds style
+//! checks on top. Every diagnostic has one of the codes listed in [`RULES`]. [`build`] resolves a
+//! graph into the [`model`] only if it has none of the structural errors of [`BuildError`].
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+};
+
+use thiserror::Error;
+
+use crate::{
+    algorithm::acyclic::find_cycles,
+    attribute::{Attribute, Shape},
+    diagnostic::{Diagnostic, DiagnosticOptions, Diagnostics},
+    ir::{
+        is_html, parse_graph, AttributeKind, EdgeOp, Graph, GraphKind, GraphParseError, Ident,
+        Statement,
+    },
+    model,
+    xml::{self, Content, Element},
+};
+
+/// The diagnostic codes, with what each one reports.
+pub const RULES: &[(&str, &str)] = &[
+    ("syntax", "input which isn't valid DOT"),
+    (
+        "unknown-attribute",
+        "an attribute name Graphviz doesn't know",
+    ),
+    (
+        "edge-operator",
+        "an edge operator not matching the graph kind",
+    ),
+    ("duplicate-edge", "an edge repeated in a strict graph"),
+    ("duplicate-node", "a node declared more than once"),
+    (
+        "conflicting-attribute",
+        "a node declared again with a different value for an attribute",
+    ),
+    (
+        "invalid-port",
+        "an edge port which is empty or has no valid compass point",
+    ),
+    (
+        "dangling-port",
+        "an edge port naming a field or cell its node's label doesn't have",
+    ),
+    (
+        "inapplicable-attribute",
+        "an attribute Graphviz ignores on the component it is set on",
+    ),
+    (
+        "unreachable-node",
+        "a node without edges in a graph which has edges",
+    ),
+    (
+        "repeated-attribute",
+        "an attribute set more than once in one attribute list",
+    ),
+    (
+        "redundant-attribute",
+        "an attribute set to the value it already has by default",
+    ),
+    (
+        "cycle",
+        "a cycle in a digraph, which layered layouts reverse edges to break",
+    ),
+];
+
+/// A structural error in a graph, naming the node or edge at fault.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BuildError {
+    /// An edge written with the operator of the other kind of graph.
+    #[error("edge `{tail} {op} {head}` uses `{op}` in a graph requiring `{expected}`")]
+    EdgeOperator {
+        tail: Ident,
+        head: Ident,
+        op: EdgeOp,
+        expected: EdgeOp,
+    },
+    /// A node declared again with a different value for an attribute an earlier declaration set.
+    #[error("node `{node}` sets `{name}` to both `{first}` and `{second}`")]
+    ConflictingAttribute {
+        node: Ident,
+        name: &'static str,
+        first: String,
+        second: String,
+    },
+    /// A port of an edge which is empty, or whose compass point isn't one, as in `a:f0:up`.
+    #[error("edge `{edge}` meets node `{node}` at invalid port `{port}`")]
+    InvalidPort {
+        edge: String,
+        node: Ident,
+        port: String,
+    },
+    /// A port of an edge naming a field or cell the label of its node doesn't have.
+    #[error("edge `{edge}` meets node `{node}` at port `{port}`, which its label doesn't have")]
+    DanglingPort {
+        edge: String,
+        node: Ident,
+        port: String,
+    },
+}
+
+impl BuildError {
+    /// The code of the diagnostic in [`RULES`] reporting the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BuildError::EdgeOperator { .. } => "edge-operator",
+            BuildError::ConflictingAttribute { .. } => "conflicting-attribute",
+            BuildError::InvalidPort { .. } => "invalid-port",
+            BuildError::DanglingPort { .. } => "dangling-port",
+        }
+    }
+}
+
+/// Resolves a parsed graph into the [`model`], as [`model::Graph::from_ir`] does, failing with the
+/// first structural error found instead: an edge operator not matching the graph kind, a node
+/// declared again with a conflicting attribute, or an edge port that is invalid or that the label
+/// of its node doesn't have.
+pub fn build(graph: &Graph) -> Result<model::Graph, BuildError> {
+    let expected = match graph.kind {
+        GraphKind::Directed => EdgeOp::Directed,
+        GraphKind::Undirected => EdgeOp::Undirected,
+    };
+    for statement in &graph.statements {
+        if let Statement::Edge(edge) = statement {
+            for (op, pair) in edge.ops.iter().zip(edge.list.windows(2)) {
+                if *op != expected {
+                    return Err(BuildError::EdgeOperator {
+                        tail: pair[0].clone(),
+                        head: pair[1].clone(),
+                        op: *op,
+                        expected,
+                    });
+                }
+            }
+        }
+    }
+    if let Some(error) = conflicting_attributes(graph).into_iter().next() {
+        return Err(error);
+    }
+    let model = model::Graph::from_ir(graph);
+    match port_errors(graph, &model).into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(model),
+    }
+}
+
+/// The attributes of nodes declared again with a value different from the one an earlier
+/// declaration set.
+fn conflicting_attributes(graph: &Graph) -> Vec<BuildError> {
+    let mut errors = vec![];
+    let mut declared = HashMap::<&str, HashMap<&str, &Attribute>>::new();
+    for node in graph.node_statements() {
+        let earlier = declared.entry(&node.name).or_default();
+        for attribute in &node.attributes {
+            match earlier.get(attribute.name()) {
+                Some(&first) if first != attribute => {
+                    errors.push(BuildError::ConflictingAttribute {
+                        node: node.name.clone(),
+                        name: attribute.name(),
+                        first: first.value_string(),
+                        second: attribute.value_string(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        for attribute in &node.attributes {
+            earlier.entry(attribute.name()).or_insert(attribute);
+        }
+    }
+    errors
+}
+
+/// The names of the ports of a node: the fields of a record, or the cells and tables of an HTML
+/// label with a `PORT`.
+fn ports(node: &model::Node) -> Vec<String> {
+    let label = node.label().unwrap_or_default();
+    if is_html(&label) {
+        let source = format!("<html>{}</html>", &label[1..label.len() - 1]);
+        let mut ports = vec![];
+        if let Ok(root) = xml::parse(&source.replace("&nbsp;", "&#160;")) {
+            html_ports(&root, &mut ports);
+        }
+        return ports;
+    }
+    if !matches!(
+        node.shape().as_deref(),
+        Some(Shape::Record | Shape::MRecord)
+    ) {
+        return vec![];
+    }
+    let mut ports = vec![];
+    let mut chars = label.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '<' => {
+                let mut port = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '>' => break,
+                        '\\' => port.extend(chars.next()),
+                        c => port.push(c),
+                    }
+                }
+                ports.push(port.trim().to_owned());
+            }
+            _ => {}
+        }
+    }
+    ports
+}
+
+/// Adds the `PORT`s of `element` and the elements in it to `ports`.
+fn html_ports(element: &Element, ports: &mut Vec<String>) {
+    let mut attributes = element.attributes.iter();
+    let port = attributes.find(|(name, _)| name.eq_ignore_ascii_case("port"));
+    ports.extend(port.map(|(_, port)| port.clone()));
+    for child in &element.children {
+        if let Content::Element(child) = child {
+            html_ports(child, ports);
+        }
+    }
+}
+
+/// The ports of edges in `graph`, resolved as `model`, which are invalid or which the labels of
+/// their nodes don't have.
+fn port_errors(graph: &Graph, model: &model::Graph) -> Vec<BuildError> {
+    let op = match graph.kind {
+        GraphKind::Directed => EdgeOp::Directed,
+        GraphKind::Undirected => EdgeOp::Undirected,
+    };
+    let mut errors = vec![];
+    // empty ports are dropped by the model, so are only seen as written
+    for edge in graph.edge_statements() {
+        for (index, pair) in edge.list.windows(2).enumerate() {
+            for end in [index, index + 1] {
+                if edge.ports.get(end).and_then(Option::as_deref) == Some("") {
+                    errors.push(BuildError::InvalidPort {
+                        edge: format!("{} {} {}", pair[0], op, pair[1]),
+                        node: edge.list[end].clone(),
+                        port: String::new(),
+                    });
+                }
+            }
+        }
+    }
+    for edge in model.edges() {
+        let (tail, head) = (&model.nodes()[edge.tail], &model.nodes()[edge.head]);
+        for attribute in &edge.attributes {
+            let (node, port) = match attribute {
+                Attribute::TailPort(port) => (tail, port),
+                Attribute::HeadPort(port) => (head, port),
+                _ => continue,
+            };
+            let name = match &port.port {
+                Some(name) => name,
+                None => continue,
+            };
+            let edge = format!("{} {} {}", tail.id, op, head.id);
+            // a compass point which isn't one is kept as part of the port name
+            if name.contains(':') {
+                errors.push(BuildError::InvalidPort {
+                    edge,
+                    node: node.id.clone(),
+                    port: port.to_string(),
+                });
+            } else if !ports(node).contains(name) {
+                errors.push(BuildError::DanglingPort {
+                    edge,
+                    node: node.id.clone(),
+                    port: name.clone(),
+                });
+            }
+        }
+    }
+    errors
+}
+
+/// Checks a parsed graph, collecting diagnostics according to `options`.
+pub fn validate(graph: &Graph, options: DiagnosticOptions) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new(options);
+    let _ = run_checks(graph, &mut diagnostics);
+    diagnostics
+}
+
+/// Parses and checks `input`, reporting a syntax error as a diagnostic.
+pub fn check(input: &str, options: DiagnosticOptions) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new(options);
+    let _ = match parse_graph(input) {
+        Ok(graph) => run_checks(&graph, &mut diagnostics),
+        Err(e) => diagnostics.report(parse_error(e)),
+    };
+    diagnostics
+}
+
+/// Parses and checks `input` like [`check`], then runs the style checks.
+pub fn lint(input: &str, options: DiagnosticOptions) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new(options);
+    let _ = match parse_graph(input) {
+        Ok(graph) => run_lints(&graph, &mut diagnostics),
+        Err(e) => diagnostics.report(parse_error(e)),
+    };
+    diagnostics
+}
+
+/// The diagnostic reporting a failure to parse: `unknown-attribute` for attribute names Graphviz
+/// doesn't know, and `syntax` for anything else.
+pub fn parse_error(e: GraphParseError<&str>) -> Diagnostic {
+    match e {
+        GraphParseError::Syntax {
+            expected: "expected known attribute name",
+            line,
+            column,
+            found,
+        } => {
+            let name = found
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .next()
+                .unwrap_or_default();
+            Diagnostic::error(
+                "unknown-attribute",
+                format!(
+                    "unknown attribute `{}` at line {}, column {}",
+                    name, line, column
+                ),
+            )
+        }
+        e => Diagnostic::error("syntax", e.to_string()),
+    }
+}
+
+/// A `cycle` warning for each cycle [`find_cycles`] finds in `graph`, with its path.
+pub fn cycles(graph: &model::Graph) -> Vec<Diagnostic> {
+    find_cycles(graph)
+        .into_iter()
+        .map(|cycle| {
+            let nodes = cycle.nodes.iter().chain(&cycle.nodes[..1]);
+            let path = nodes.map(|&node| graph.nodes()[node].id.as_str());
+            Diagnostic::warning(
+                "cycle",
+                format!("cycle `{}`", path.collect::<Vec<_>>().join(" -> ")),
+            )
+        })
+        .collect()
+}
+
+fn run_checks(graph: &Graph, diagnostics: &mut Diagnostics) -> ControlFlow<()> {
+    let expected_op = match graph.kind {
+        GraphKind::Directed => EdgeOp::Directed,
+        GraphKind::Undirected => EdgeOp::Undirected,
+    };
+    let mut nodes = HashSet::new();
+    let mut edges = HashSet::new();
+    for statement in &graph.statements {
+        match statement {
+            Statement::Node(node) => {
+                let first = nodes.insert(node.name.as_str());
+                if !first {
+                    diagnostics.report(Diagnostic::warning(
+                        "duplicate-node",
+                        format!("node `{}` is declared more than once", node.name),
+                    ))?;
+                }
+            }
+            Statement::Edge(edge) => {
+                for (op, pair) in edge.ops.iter().zip(edge.list.windows(2)) {
+                    let (tail, head) = (pair[0].as_str(), pair[1].as_str());
+                    if *op != expected_op {
+                        diagnostics.report(Diagnostic::error(
+                            "edge-operator",
+                            format!(
+                                "edge `{} {} {}` uses `{}` in a graph requiring `{}`",
+                                tail, op, head, op, expected_op
+                            ),
+                        ))?;
+                    }
+                    let key = match graph.kind {
+                        GraphKind::Undirected if head < tail => (head, tail),
+                        _ => (tail, head),
+                    };
+                    if graph.strict && !edges.insert(key) {
+                        diagnostics.report(Diagnostic::warning(
+                            "duplicate-edge",
+                            format!(
+                                "edge `{} {} {}` is repeated in a strict graph",
+                                tail, op, head
+                            ),
+                        ))?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let model = model::Graph::from_ir(graph);
+    let errors = conflicting_attributes(graph).into_iter();
+    for error in errors.chain(port_errors(graph, &model)) {
+        diagnostics.report(Diagnostic::warning(error.code(), error.to_string()))?;
+    }
+    ControlFlow::Continue(())
+}
+
+fn component(kind: &AttributeKind) -> &'static str {
+    match kind {
+        AttributeKind::Graph => "graphs",
+        AttributeKind::Node => "nodes",
+        AttributeKind::Edge => "edges",
+    }
+}
+
+/// Checks one attribute list, set on components of the given kind with the given defaults.
+fn lint_attributes(
+    attributes: &[Attribute],
+    kind: &AttributeKind,
+    defaults: Option<&HashMap<&str, &Attribute>>,
+    diagnostics: &mut Diagnostics,
+) -> ControlFlow<()> {
+    let mut names = HashSet::new();
+    for attribute in attributes {
+        let name = attribute.name();
+        if !attribute.applies_to(kind) {
+            diagnostics.report(Diagnostic::warning(
+                "inapplicable-attribute",
+                format!("`{}` has no effect on {}", name, component(kind)),
+            ))?;
+        }
+        if !names.insert(name) {
+            diagnostics.report(Diagnostic::warning(
+                "repeated-attribute",
+                format!("`{}` is set more than once in one attribute list", name),
+            ))?;
+        }
+        if defaults.and_then(|defaults| defaults.get(name)) == Some(&attribute) {
+            diagnostics.report(Diagnostic::warning(
+                "redundant-attribute",
+                format!(
+                    "`{}={}` repeats the default for {}",
+                    name,
+                    attribute.value_string(),
+                    component(kind)
+                ),
+            ))?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Runs [`run_checks`], then the style checks.
+fn run_lints(graph: &Graph, diagnostics: &mut Diagnostics) -> ControlFlow<()> {
+    run_checks(graph, diagnostics)?;
+    let mut node_defaults = HashMap::new();
+    let mut edge_defaults = HashMap::new();
+    let mut nodes = vec![];
+    let mut connected = HashSet::new();
+    for statement in &graph.statements {
+        match statement {
+            Statement::Attribute(statement) => {
+                lint_attributes(&statement.attributes, &statement.kind, None, diagnostics)?;
+                let defaults = match statement.kind {
+                    AttributeKind::Graph => continue,
+                    AttributeKind::Node => &mut node_defaults,
+                    AttributeKind::Edge => &mut edge_defaults,
+                };
+                for attribute in &statement.attributes {
+                    defaults.insert(attribute.name(), attribute);
+                }
+            }
+            Statement::Node(node) => {
+                nodes.push(node.name.as_str());
+                lint_attributes(
+                    &node.attributes,
+                    &AttributeKind::Node,
+                    Some(&node_defaults),
+                    diagnostics,
+                )?;
+            }
+            Statement::Edge(edge) => {
+                connected.extend(edge.list.iter().map(String::as_str));
+                lint_attributes(
+                    &edge.attributes,
+                    &AttributeKind::Edge,
+                    Some(&edge_defaults),
+                    diagnostics,
+                )?;
+            }
+            Statement::Definition(definition) => {
+                if let Ok(attribute) = Attribute::from_pair(&definition.lhs, &definition.rhs) {
+                    lint_attributes(&[attribute], &AttributeKind::Graph, None, diagnostics)?;
+                }
+            }
+        }
+    }
+    if !connected.is_empty() {
+        let mut reported = HashSet::new();
+        for node in nodes {
+            if !connected.contains(node) && reported.insert(node) {
+                diagnostics.report(Diagnostic::warning(
+                    "unreachable-node",
+                    format!("node `{}` has no edges", node),
+                ))?;
+            }
+        }
+    }
+    if graph.kind == GraphKind::Directed {
+        for diagnostic in cycles(&model::Graph::from_ir(graph)) {
+            diagnostics.report(diagnostic)?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Severity;
+
+    const INPUT: &str = "strict graph { a b a -> b a -- b b -- a c c }";
+
+    fn codes(diagnostics: &Diagnostics) -> Vec<(Severity, &'static str)> {
+        diagnostics.iter().map(|d| (d.severity, d.code)).collect()
+    }
+
+    #[test]
+    fn checks() {
+        let diagnostics = check(INPUT, DiagnosticOptions::default());
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                (Severity::Error, "edge-operator"),
+                (Severity::Warning, "duplicate-edge"),
+                (Severity::Warning, "duplicate-edge"),
+                (Severity::Warning, "duplicate-node"),
+            ]
+        );
+        assert_eq!((diagnostics.errors(), diagnostics.warnings()), (1, 3));
+    }
+
+    #[test]
+    fn limits() {
+        let diagnostics = check(
+            INPUT,
+            DiagnosticOptions {
+                max_diagnostics: Some(2),
+                ..DiagnosticOptions::default()
+            },
+        );
+        assert_eq!(diagnostics.iter().count(), 2);
+        assert_eq!(diagnostics.suppressed(), 2);
+
+        let diagnostics = check(
+            INPUT,
+            DiagnosticOptions {
+                fail_fast: true,
+                ..DiagnosticOptions::default()
+            },
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            vec![(Severity::Error, "edge-operator")]
+        );
+
+        let diagnostics = check(
+            "strict graph { a -- b b -- a }",
+            DiagnosticOptions {
+                warnings_as_errors: true,
+                fail_fast: true,
+                ..DiagnosticOptions::default()
+            },
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            vec![(Severity::Error, "duplicate-edge")]
+        );
+    }
+
+    #[test]
+    fn syntax() {
+        let diagnostics = check("graph { a -> }", DiagnosticOptions::default());
+        assert_eq!(codes(&diagnostics), vec![(Severity::Error, "syntax")]);
+
+        let diagnostics = check("graph { a [colour=red] }", DiagnosticOptions::default());
+        assert_eq!(
+            codes(&diagnostics),
+            vec![(Severity::Error, "unknown-attribute")]
+        );
+        assert_eq!(
+            diagnostics.iter().next().unwrap().message,
+            "unknown attribute `colour` at line 1, column 12"
+        );
+    }
+
+    #[test]
+    fn builds() {
+        let build = |input| build(&parse_graph(input).unwrap());
+        let input = r#"digraph {
+            a [shape=record, label="<f0> x|{<f1> y|z}"]
+            b [label=<<table><tr><td port="p">q</td></tr></table>>]
+            a:f1:s -> b:p:n -> c:s
+            a [shape=record]
+        }"#;
+        assert_eq!(build(input).unwrap().edges().len(), 2);
+
+        assert_eq!(
+            build("graph { a -> b }").unwrap_err(),
+            BuildError::EdgeOperator {
+                tail: "a".into(),
+                head: "b".into(),
+                op: EdgeOp::Directed,
+                expected: EdgeOp::Undirected
+            }
+        );
+        assert_eq!(
+            build("graph { a [color=red] b a [color=blue] }").unwrap_err(),
+            BuildError::ConflictingAttribute {
+                node: "a".into(),
+                name: "color",
+                first: "red".into(),
+                second: "blue".into()
+            }
+        );
+    }
+
+    #[test]
+    fn ports() {
+        let build = |input| build(&parse_graph(input).unwrap()).unwrap_err();
+        assert_eq!(
+            build("digraph { a:f0:up -> b }"),
+            BuildError::InvalidPort {
+                edge: "a -> b".into(),
+                node: "a".into(),
+                port: "f0:up".into()
+            }
+        );
+        assert_eq!(
+            build(r#"digraph { a -> b:"" }"#),
+            BuildError::InvalidPort {
+                edge: "a -> b".into(),
+                node: "b".into(),
+                port: "".into()
+            }
+        );
+        let dangling = |node: &str, port: &str| BuildError::DanglingPort {
+            edge: "a -> b".into(),
+            node: node.into(),
+            port: port.into(),
+        };
+        assert_eq!(build("digraph { a -> b:f0 }"), dangling("b", "f0"));
+        assert_eq!(
+            build(r#"digraph { a [shape=record, label="<f0> x|y"] a:f1 -> b }"#),
+            dangling("a", "f1")
+        );
+        assert_eq!(
+            build("digraph { a -> b [tailport=p] a [label=<<b>x</b>>] }"),
+            dangling("a", "p")
+        );
+
+        let diagnostics = check(
+            "digraph { a -> b:f0  a [color=red]  a [color=blue] }",
+            DiagnosticOptions::default(),
+        );
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                (Severity::Warning, "duplicate-node"),
+                (Severity::Warning, "conflicting-attribute"),
+                (Severity::Warning, "dangling-port")
+            ]
+        );
+    }
+
+    #[test]
+    fn lints() {
+        let input = "digraph {
+            node [shape=box]
+            edge [color=red]
+            a [shape=box, arrowhead=dot]
+            b [label=B, label=C]
+            c
+            a -> b [color=red, weight=2]
+            rankdir=LR
+        }";
+        let diagnostics = lint(input, DiagnosticOptions::default());
+        let messages = diagnostics
+            .iter()
+            .map(|d| (d.code, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "redundant-attribute",
+                    "`shape=box` repeats the default for nodes"
+                ),
+                (
+                    "inapplicable-attribute",
+                    "`arrowhead` has no effect on nodes"
+                ),
+                (
+                    "repeated-attribute",
+                    "`label` is set more than once in one attribute list"
+                ),
+                (
+                    "redundant-attribute",
+                    "`color=red` repeats the default for edges"
+                ),
+                ("unreachable-node", "node `c` has no edges"),
+            ]
+        );
+        // a graph without edges has no unreachable nodes
+        assert!(lint("graph { a b }", DiagnosticOptions::default()).is_empty());
+
+        let diagnostics = lint(
+            "digraph { a -> b -> c -> a  c -> c  b -> a }",
+            DiagnosticOptions::default(),
+        );
+        let messages = diagnostics.iter().map(|d| (d.code, d.message.as_str()));
+        assert_eq!(
+            messages.collect::<Vec<_>>(),
+            vec![
+                ("cycle", "cycle `a -> b -> c -> a`"),
+                ("cycle", "cycle `a -> b -> a`")
+            ]
+        );
+    }
+}
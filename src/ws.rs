@@ -1,15 +1,48 @@
 //! Whitespace combinator shamelessly stolen from
 //! https://github.com/Geal/nom/blob/master/doc/nom_recipes.md.
 
-use nom::{character::complete::multispace0, error::ParseError, sequence::delimited, IResult};
+use nom::{error::ParseError, sequence::delimited, IResult};
+
+use crate::scan::{separator, whitespace};
+
+/// Spaces, tabs and line breaks, as `multispace0` but scanning bytes.
+fn multispace0<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    let (space, rest) = input.split_at(whitespace(input));
+    Ok((rest, space))
+}
 
 /// A combinator that takes a parser `inner` and produces a parser that also consumes both leading and
 /// trailing whitespace, returning the output of `inner`.
-pub fn ws<'a, F: 'a, O, E: ParseError<&'a str>>(
+pub fn ws<'a, F, O, E: ParseError<&'a str>>(
     inner: F,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+    F: FnMut(&'a str) -> IResult<&'a str, O, E> + 'a,
 {
     delimited(multispace0, inner, multispace0)
 }
+
+/// Whitespace and comments, starting a line if `line_start`; see [`separator`].
+fn dot_space<'a, E: ParseError<&'a str>>(
+    line_start: bool,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str, E> {
+    move |input: &'a str| {
+        let (space, rest) = input.split_at(separator(input, line_start));
+        Ok((rest, space))
+    }
+}
+
+/// As [`ws`], but also consuming the comments DOT allows wherever whitespace is.
+pub fn dot_ws<'a, F, O, E: ParseError<&'a str>>(
+    inner: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E> + 'a,
+{
+    delimited(dot_space(false), inner, dot_space(false))
+}
+
+/// The whitespace and comments at the start of a DOT document, where a `#` line may come first.
+pub fn dot_leading<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    dot_space(true)(input)
+}
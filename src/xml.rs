@@ -0,0 +1,262 @@
+//! Minimal XML reader, sufficient for the XML-based graph formats. Supports elements, attributes,
+//! character data, CDATA sections and the predefined and numeric character references. The
+//! prolog, comments, processing instructions and doctype declarations are skipped.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_until, take_while1},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{cut, map, opt, value},
+    error::{context, VerboseError, VerboseErrorKind},
+    multi::many0,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+
+type XmlResult<'a, O> = nom::IResult<&'a str, O, VerboseError<&'a str>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Content>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Content {
+    Element(Element),
+    Text(String),
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+impl Element {
+    /// Element name without any namespace prefix.
+    pub fn local_name(&self) -> &str {
+        local_name(&self.name)
+    }
+
+    /// Looks up an attribute by its name without namespace prefix.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| local_name(n) == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Child elements with the given local name.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter_map(move |child| match child {
+            Content::Element(element) if element.local_name() == name => Some(element),
+            _ => None,
+        })
+    }
+
+    /// Concatenated character data of this element and its descendants.
+    pub fn text(&self) -> String {
+        self.children
+            .iter()
+            .map(|child| match child {
+                Content::Element(element) => element.text(),
+                Content::Text(text) => text.clone(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct XmlError {
+    pub expected: &'static str,
+    pub line: usize,
+}
+
+/// Resolves character references in `text`.
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let resolved = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match resolved {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn name_parser(input: &str) -> XmlResult<'_, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || "_:-.".contains(c))(input)
+}
+
+/// Comments and processing instructions, which may appear anywhere.
+fn ignored_parser(input: &str) -> XmlResult<'_, ()> {
+    alt((
+        value((), delimited(tag("<!--"), take_until("-->"), tag("-->"))),
+        value((), delimited(tag("<?"), take_until("?>"), tag("?>"))),
+    ))(input)
+}
+
+fn misc_parser(input: &str) -> XmlResult<'_, ()> {
+    alt((
+        value((), multispace1),
+        ignored_parser,
+        value((), delimited(tag("<!DOCTYPE"), is_not(">"), char('>'))),
+    ))(input)
+}
+
+fn attribute_parser(input: &str) -> XmlResult<'_, (String, String)> {
+    let (rest, (name, _, value)) = tuple((
+        preceded(multispace1, name_parser),
+        delimited(multispace0, char('='), multispace0),
+        cut(context(
+            "expected quoted attribute value",
+            alt((
+                delimited(char('"'), opt(is_not("\"")), char('"')),
+                delimited(char('\''), opt(is_not("'")), char('\'')),
+            )),
+        )),
+    ))(input)?;
+    Ok((rest, (name.to_owned(), unescape(value.unwrap_or_default()))))
+}
+
+fn content_parser(input: &str) -> XmlResult<'_, Option<Content>> {
+    alt((
+        map(element_parser, |element| Some(Content::Element(element))),
+        map(
+            delimited(tag("<![CDATA["), take_until("]]>"), tag("]]>")),
+            |text: &str| Some(Content::Text(text.to_owned())),
+        ),
+        value(None, ignored_parser),
+        map(is_not("<"), |text: &str| {
+            Some(Content::Text(unescape(text)))
+        }),
+    ))(input)
+}
+
+fn element_parser(input: &str) -> XmlResult<'_, Element> {
+    let (rest, (name, attributes)) =
+        preceded(char('<'), pair(name_parser, many0(attribute_parser)))(input)?;
+    let (rest, empty) = cut(context(
+        "expected end of tag",
+        preceded(
+            multispace0,
+            alt((value(true, tag("/>")), value(false, char('>')))),
+        ),
+    ))(rest)?;
+    let mut element = Element {
+        name: name.to_owned(),
+        attributes,
+        children: vec![],
+    };
+    if empty {
+        return Ok((rest, element));
+    }
+    let (rest, children) = many0(content_parser)(rest)?;
+    let (rest, end) = cut(context(
+        "expected closing tag",
+        delimited(tag("</"), name_parser, terminated(multispace0, char('>'))),
+    ))(rest)?;
+    if end != name {
+        return Err(nom::Err::Failure(VerboseError {
+            errors: vec![(
+                rest,
+                VerboseErrorKind::Context("expected matching closing tag"),
+            )],
+        }));
+    }
+    element.children = children.into_iter().flatten().collect();
+    Ok((rest, element))
+}
+
+/// Parses a document, returning its root element.
+pub(crate) fn parse(input: &str) -> Result<Element, XmlError> {
+    let result = delimited(
+        many0(misc_parser),
+        context("expected root element", element_parser),
+        many0(misc_parser),
+    )(input);
+    let (remaining, expected) = match result {
+        Ok(("", root)) => return Ok(root),
+        Ok((rest, _)) => (rest, "expected end of document"),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => e
+            .errors
+            .iter()
+            .find_map(|(remaining, kind)| match kind {
+                VerboseErrorKind::Context(context) => Some((*remaining, *context)),
+                _ => None,
+            })
+            .unwrap_or((input, "expected element")),
+        Err(nom::Err::Incomplete(_)) => ("", "expected element"),
+    };
+    Err(XmlError {
+        expected,
+        line: input[..input.len() - remaining.len()].matches('\n').count() + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let root = parse(
+            r#"<?xml version="1.0"?>
+<!-- comment -->
+<g:root xmlns:g="urn:x" a='1 &amp; 2'>
+  <child b="&#x41;&#66;"/>text &lt;here&gt;<![CDATA[<raw>]]>
+</g:root>
+"#,
+        )
+        .unwrap();
+        assert_eq!(root.local_name(), "root");
+        assert_eq!(root.attribute("a"), Some("1 & 2"));
+        assert_eq!(
+            root.children_named("child").next().unwrap().attribute("b"),
+            Some("AB")
+        );
+        assert_eq!(root.text().trim(), "text <here><raw>");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("<a>\n<b></a>"),
+            Err(XmlError {
+                expected: "expected matching closing tag",
+                line: 2
+            })
+        );
+        assert_eq!(
+            parse("<a b=c/>").unwrap_err().expected,
+            "expected quoted attribute value"
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! Minimal YAML reader for configuration-style documents, producing the JSON value model.
+//!
+//! Block mappings and sequences, single-line flow collections, plain and quoted scalars, block
+//! scalars (`|` and `>`) and comments are supported. Anchors, aliases, tags, multi-line plain or
+//! flow values and multiple documents are not.
+
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, take_while_m_n},
+    character::complete::{char, space0},
+    combinator::{map, map_opt, opt, value},
+    multi::{many0, separated_list0},
+    sequence::{delimited, preceded, separated_pair, terminated},
+};
+
+use crate::json::JsonValue;
+
+type YamlResult<'a, O> = nom::IResult<&'a str, O, nom::error::Error<&'a str>>;
+
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    /// Text after the indentation.
+    text: &'a str,
+}
+
+/// Removes a trailing comment, which starts with a `#` at the start of the line or after
+/// whitespace, outside of quoted scalars.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let mut previous = ' ';
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') if previous.is_whitespace() || "[{,".contains(previous) => {
+                quote = Some(c)
+            }
+            (None, '#') if previous.is_whitespace() => return text[..i].trim_end(),
+            _ => {}
+        }
+        previous = c;
+    }
+    text.trim_end()
+}
+
+fn is_sequence_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Position of the colon ending the key of a mapping entry, if `text` is one.
+fn mapping_colon(text: &str) -> Option<usize> {
+    let start = match text.chars().next()? {
+        '[' | '{' => return None,
+        quote @ ('"' | '\'') => text[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let colon = start
+        + text[start..].find(": ").or_else(|| {
+            text[start..]
+                .ends_with(':')
+                .then_some(text.len() - start - 1)
+        })?;
+    Some(colon)
+}
+
+fn double_quoted_parser(input: &str) -> YamlResult<'_, String> {
+    let escape = preceded(
+        char('\\'),
+        alt((
+            map_opt(
+                alt((
+                    preceded(
+                        char('u'),
+                        take_while_m_n(4, 4, |c: char| c.is_ascii_hexdigit()),
+                    ),
+                    preceded(
+                        char('x'),
+                        take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit()),
+                    ),
+                )),
+                |hex| {
+                    u32::from_str_radix(hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .map(String::from)
+                },
+            ),
+            value("\"".to_owned(), char('"')),
+            value("\\".to_owned(), char('\\')),
+            value("/".to_owned(), char('/')),
+            value("\0".to_owned(), char('0')),
+            value("\u{8}".to_owned(), char('b')),
+            value("\n".to_owned(), char('n')),
+            value("\r".to_owned(), char('r')),
+            value("\t".to_owned(), char('t')),
+        )),
+    );
+    delimited(
+        char('"'),
+        map(
+            many0(alt((escape, map(is_not("\"\\"), str::to_owned)))),
+            |fragments| fragments.concat(),
+        ),
+        char('"'),
+    )(input)
+}
+
+fn single_quoted_parser(input: &str) -> YamlResult<'_, String> {
+    delimited(
+        char('\''),
+        map(
+            many0(alt((value("'", tag("''")), is_not("'")))),
+            |fragments| fragments.concat(),
+        ),
+        char('\''),
+    )(input)
+}
+
+/// A plain scalar inside a flow collection, which ends at a flow indicator or a `: `.
+fn flow_plain_parser(input: &str) -> YamlResult<'_, &str> {
+    let mut end = input.len();
+    for (i, c) in input.char_indices() {
+        let next = input[i + 1..].chars().next();
+        if ",[]{}".contains(c) || (c == ':' && next.is_none_or(|n| " ,]}".contains(n))) {
+            end = i;
+            break;
+        }
+    }
+    match input[..end].trim_end() {
+        "" => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::IsNot,
+        ))),
+        plain => Ok((&input[plain.len()..], plain)),
+    }
+}
+
+/// Resolves a plain scalar to null, a boolean, a number or a string.
+fn resolve_plain(text: &str) -> JsonValue {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => JsonValue::Null,
+        "true" | "True" | "TRUE" => JsonValue::Bool(true),
+        "false" | "False" | "FALSE" => JsonValue::Bool(false),
+        _ => match text.parse() {
+            Ok(number)
+                if text.chars().all(|c| "0123456789+-.eE".contains(c))
+                    && text.contains(|c: char| c.is_ascii_digit()) =>
+            {
+                JsonValue::Number(number)
+            }
+            _ => JsonValue::String(text.to_owned()),
+        },
+    }
+}
+
+fn flow_key_parser(input: &str) -> YamlResult<'_, String> {
+    alt((
+        double_quoted_parser,
+        single_quoted_parser,
+        map(flow_plain_parser, str::to_owned),
+    ))(input)
+}
+
+fn flow_value_parser(input: &str) -> YamlResult<'_, JsonValue> {
+    delimited(
+        space0,
+        alt((
+            map(
+                delimited(
+                    char('['),
+                    terminated(
+                        separated_list0(char(','), flow_value_parser),
+                        opt(char(',')),
+                    ),
+                    preceded(space0, char(']')),
+                ),
+                JsonValue::Array,
+            ),
+            map(
+                delimited(
+                    char('{'),
+                    terminated(
+                        separated_list0(
+                            char(','),
+                            separated_pair(
+                                delimited(space0, flow_key_parser, space0),
+                                char(':'),
+                                map(opt(flow_value_parser), Option::unwrap_or_default),
+                            ),
+                        ),
+                        opt(char(',')),
+                    ),
+                    preceded(space0, char('}')),
+                ),
+                JsonValue::Object,
+            ),
+            map(double_quoted_parser, JsonValue::String),
+            map(single_quoted_parser, JsonValue::String),
+            map(flow_plain_parser, resolve_plain),
+        )),
+        space0,
+    )(input)
+}
+
+/// Parses a scalar or flow collection written on a single line.
+fn inline_value(text: &str) -> Option<JsonValue> {
+    if !text.starts_with(['[', '{', '"', '\'']) {
+        return Some(resolve_plain(text));
+    }
+    match flow_value_parser(text) {
+        Ok(("", value)) => Some(value),
+        _ => None,
+    }
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Skips blank and comment lines, returning the next line with content.
+    fn current(&mut self) -> Option<Line<'a>> {
+        while let Some(line) = self.lines.get(self.position) {
+            if !strip_comment(line.text).is_empty() {
+                return Some(*line);
+            }
+            self.position += 1;
+        }
+        None
+    }
+
+    /// Parses the node starting at the current line, which must be indented at least `indent`.
+    fn block(&mut self, indent: usize) -> Result<JsonValue, usize> {
+        let line = match self.current() {
+            Some(line) if line.indent >= indent => line,
+            _ => return Ok(JsonValue::Null),
+        };
+        let text = strip_comment(line.text);
+        if is_sequence_item(text) {
+            self.sequence(line.indent)
+        } else if mapping_colon(text).is_some() {
+            self.mapping(line.indent)
+        } else {
+            self.position += 1;
+            self.scalar(text, indent, line.number)
+        }
+    }
+
+    /// Parses an inline value or the header of a block scalar, whose content must be indented at
+    /// least `indent`.
+    fn scalar(&mut self, text: &str, indent: usize, number: usize) -> Result<JsonValue, usize> {
+        if text.starts_with(['|', '>']) {
+            self.block_scalar(text, indent, number)
+        } else {
+            inline_value(text).ok_or(number)
+        }
+    }
+
+    /// Parses the value following a `-` or `key:` with nothing after it, which is a node indented
+    /// more than `indent`, a sequence at `indent` if `allow_sequence`, or null.
+    fn nested(&mut self, indent: usize, allow_sequence: bool) -> Result<JsonValue, usize> {
+        match self.current() {
+            Some(line) if line.indent > indent => self.block(indent + 1),
+            Some(line)
+                if allow_sequence
+                    && line.indent == indent
+                    && is_sequence_item(strip_comment(line.text)) =>
+            {
+                self.sequence(indent)
+            }
+            _ => Ok(JsonValue::Null),
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<JsonValue, usize> {
+        let mut items = vec![];
+        while let Some(line) = self.current() {
+            let text = strip_comment(line.text);
+            if line.indent < indent || (line.indent == indent && !is_sequence_item(text)) {
+                break;
+            }
+            if line.indent > indent {
+                return Err(line.number);
+            }
+            let rest = &text[1..];
+            let item = rest.trim_start();
+            if item.is_empty() {
+                self.position += 1;
+                items.push(self.nested(indent, false)?);
+            } else {
+                // parse the item as if it started its own line, so that a mapping can continue
+                // on the following lines at the same column
+                self.lines[self.position] = Line {
+                    number: line.number,
+                    indent: indent + 1 + rest.len() - item.len(),
+                    text: item,
+                };
+                items.push(self.block(indent + 1)?);
+            }
+        }
+        Ok(JsonValue::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<JsonValue, usize> {
+        let mut members = vec![];
+        while let Some(line) = self.current() {
+            if line.indent < indent {
+                break;
+            }
+            let text = strip_comment(line.text);
+            let colon = match mapping_colon(text) {
+                Some(colon) if line.indent == indent => colon,
+                _ => return Err(line.number),
+            };
+            let key = match inline_value(text[..colon].trim_end()) {
+                Some(JsonValue::String(key)) => key,
+                Some(key) => key.scalar_string().unwrap_or_default(),
+                None => return Err(line.number),
+            };
+            let rest = text[colon + 1..].trim_start();
+            self.position += 1;
+            let value = if rest.is_empty() {
+                self.nested(indent, true)?
+            } else {
+                self.scalar(rest, indent + 1, line.number)?
+            };
+            members.push((key, value));
+        }
+        Ok(JsonValue::Object(members))
+    }
+
+    /// Reads a literal (`|`) or folded (`>`) block scalar with the given header.
+    fn block_scalar(
+        &mut self,
+        header: &str,
+        indent: usize,
+        number: usize,
+    ) -> Result<JsonValue, usize> {
+        let chomping = match &header[1..] {
+            "" => None,
+            "-" => Some(false),
+            "+" => Some(true),
+            _ => return Err(number),
+        };
+        let mut block_indent = None;
+        let mut lines = vec![];
+        while let Some(line) = self.lines.get(self.position) {
+            if line.text.is_empty() {
+                lines.push(String::new());
+            } else {
+                let block_indent = *block_indent.get_or_insert(line.indent);
+                if line.indent < block_indent.max(indent) {
+                    break;
+                }
+                lines.push(format!(
+                    "{}{}",
+                    " ".repeat(line.indent - block_indent),
+                    line.text
+                ));
+            }
+            self.position += 1;
+        }
+        let trailing = lines.iter().rev().take_while(|l| l.is_empty()).count();
+        lines.truncate(lines.len() - trailing);
+        let mut text = if header.starts_with('|') {
+            lines.join("\n")
+        } else {
+            let mut folded = String::new();
+            for line in &lines {
+                if !line.is_empty() && !folded.is_empty() && !folded.ends_with('\n') {
+                    folded.push(' ');
+                }
+                folded.push_str(if line.is_empty() { "\n" } else { line });
+            }
+            folded
+        };
+        match chomping {
+            _ if lines.is_empty() => {}
+            Some(false) => {}
+            None => text.push('\n'),
+            Some(true) => text.push_str(&"\n".repeat(trailing + 1)),
+        }
+        Ok(JsonValue::String(text))
+    }
+}
+
+/// Parses a YAML document, returning the line of the first error on failure.
+pub(crate) fn parse(input: &str) -> Result<JsonValue, usize> {
+    let mut lines = vec![];
+    for (i, raw) in input.lines().enumerate() {
+        if raw.trim_end() == "..." {
+            break;
+        }
+        let text = raw.trim_start_matches(' ');
+        lines.push(Line {
+            number: i + 1,
+            indent: raw.len() - text.len(),
+            text: text.trim_end(),
+        });
+    }
+    let mut parser = Parser { lines, position: 0 };
+    if let Some(line) = parser.current() {
+        if line.indent == 0 && (line.text == "---" || line.text.starts_with("--- ")) {
+            parser.lines[parser.position].text = line.text[3..].trim_start();
+        }
+    }
+    let value = parser.block(0)?;
+    match parser.current() {
+        Some(line) => Err(line.number),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn document() {
+        let input = r#"---
+# comment
+name: "quoted # not a comment"
+count: 3 # comment
+flags: [true, ~, 'it''s', {a: 1, b: [x, y]}]
+list:
+- plain text, with comma
+-
+  nested: value
+- key: 1
+  other: 2
+- - inner
+mapping:
+  child:
+    leaf: ok
+literal: |
+  line one
+    indented
+
+  last
+folded: >-
+  folded
+  text
+empty:
+"#;
+        let value = parse(input).unwrap();
+        assert_eq!(
+            value.get("name").and_then(JsonValue::as_str),
+            Some("quoted # not a comment")
+        );
+        assert_eq!(value.get("count").and_then(JsonValue::as_f64), Some(3.0));
+        assert_eq!(
+            value.get("flags"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Bool(true),
+                JsonValue::Null,
+                "it's".into(),
+                JsonValue::Object(vec![
+                    ("a".to_owned(), 1.0.into()),
+                    (
+                        "b".to_owned(),
+                        JsonValue::Array(vec!["x".into(), "y".into()])
+                    ),
+                ]),
+            ]))
+        );
+        let list = value.get("list").and_then(JsonValue::as_array).unwrap();
+        assert_eq!(list[0], "plain text, with comma".into());
+        assert_eq!(
+            list[1],
+            JsonValue::Object(vec![("nested".to_owned(), "value".into())])
+        );
+        assert_eq!(list[2].get("other").and_then(JsonValue::as_f64), Some(2.0));
+        assert_eq!(list[3], JsonValue::Array(vec!["inner".into()]));
+        assert_eq!(
+            value
+                .get("mapping")
+                .and_then(|m| m.get("child"))
+                .and_then(|c| c.get("leaf")),
+            Some(&"ok".into())
+        );
+        assert_eq!(
+            value.get("literal").and_then(JsonValue::as_str),
+            Some("line one\n  indented\n\nlast\n")
+        );
+        assert_eq!(
+            value.get("folded").and_then(JsonValue::as_str),
+            Some("folded text")
+        );
+        assert_eq!(value.get("empty"), Some(&JsonValue::Null));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("a: 1\n  b: 2\n"), Err(2));
+        assert_eq!(parse("a: [1, 2\n"), Err(1));
+        assert_eq!(parse("- a\nb: c\n"), Err(2));
+    }
+}